@@ -0,0 +1,189 @@
+//! Benchmarks of the listing, filtering and persistence of a large database of synthetic tasks.
+//!
+//! Run them with `cargo bench`, and compare against a baseline with `cargo bench -- --save-baseline <name>` and
+//! `cargo bench -- --baseline <name>`.
+
+use chrono::Duration;
+use criterion::{black_box, criterion_group, criterion_main, Criterion};
+use tempdir::TempDir;
+use toodoux::{
+  config::Config,
+  filter::TaskDescriptionFilter,
+  metadata::{Metadata, Priority},
+  task::{Status, Task, TaskManager, UID},
+};
+
+/// Number of tasks of the synthetic database.
+const TASKS: usize = 10_000;
+
+const PROJECTS: &[&str] = &["toodoux", "toodoux.cli", "home", "work.reviews", "work.ops"];
+const PRIORITIES: &[&str] = &["Low", "Medium", "High", "Critical"];
+const TAGS: &[&str] = &["urgent", "later", "blocked", "waiting", "easy", "research"];
+const WORDS: &[&str] = &[
+  "fix", "write", "review", "release", "the", "listing", "parser", "docs", "bug", "report",
+  "tests", "config",
+];
+
+/// Synthetic database: tasks with a mix of statuses, projects, priorities, tags, notes and checklists.
+fn synthetic_tasks(config: &Config) -> TaskManager {
+  let mut task_mgr = TaskManager::new_from_config(config).unwrap();
+
+  for i in 0..TASKS {
+    let name = (0..4)
+      .map(|j| WORDS[(i * 7 + j * 5) % WORDS.len()])
+      .collect::<Vec<_>>()
+      .join(" ");
+    let mut task = Task::new(format!("{} {}", name, i));
+
+    let mut metadata = vec![
+      Metadata::project(PROJECTS[i % PROJECTS.len()]),
+      Metadata::tag(TAGS[i % TAGS.len()]),
+    ];
+    if i % 3 == 0 {
+      metadata.push(Metadata::priority(Priority::new(
+        PRIORITIES[i % PRIORITIES.len()],
+      )));
+    }
+    if i % 4 == 0 {
+      metadata.push(Metadata::tag(TAGS[(i / 4) % TAGS.len()]));
+    }
+    task.apply_metadata(metadata);
+
+    match i % 10 {
+      0 | 1 => task.change_status(Status::Ongoing),
+      2 => {
+        task.change_status(Status::Ongoing);
+        task.change_status(Status::Paused);
+      }
+      3 => task.change_status(Status::Done),
+      4 => task.change_status(Status::Cancelled),
+      _ => (),
+    }
+
+    if i % 5 == 0 {
+      task.add_note(format!(
+        "Some notes about task {}.\n\nWith a second paragraph.",
+        i
+      ));
+    }
+
+    if i % 8 == 0 {
+      task.add_checklist_item("First step");
+      task.add_checklist_item("Second step");
+    }
+
+    if i % 6 == 0 {
+      task.adjust_spent_time(Duration::minutes((i % 120) as i64), None);
+    }
+
+    task_mgr.register_task(task);
+  }
+
+  task_mgr
+}
+
+fn listing<'a>(
+  task_mgr: &'a TaskManager,
+  config: &Config,
+  metadata: Vec<Metadata>,
+  name: &[&str],
+) -> Vec<(&'a UID, &'a Task)> {
+  task_mgr.filtered_task_listing(
+    config.priorities(),
+    config.no_priority_order(),
+    metadata,
+    TaskDescriptionFilter::new(name.iter().copied(), false),
+    true,
+    true,
+    true,
+    false,
+    false,
+    false,
+    None,
+    false,
+  )
+}
+
+fn filtered_task_listing(c: &mut Criterion) {
+  let config = Config::default();
+  let task_mgr = synthetic_tasks(&config);
+  let mut group = c.benchmark_group("filtered_task_listing");
+
+  group.bench_function("all", |b| {
+    b.iter(|| listing(&task_mgr, &config, Vec::new(), &[]))
+  });
+
+  group.bench_function("metadata", |b| {
+    b.iter(|| {
+      listing(
+        &task_mgr,
+        &config,
+        vec![Metadata::project("work"), Metadata::tag("urgent")],
+        &[],
+      )
+    })
+  });
+
+  group.bench_function("name", |b| {
+    b.iter(|| listing(&task_mgr, &config, Vec::new(), &["review", "parser"]))
+  });
+
+  group.finish();
+}
+
+/// The per-task computations the column widths of listings are derived from.
+///
+/// `DisplayOptions` lives in the binary, which benchmarks cannot depend on; this goes through the same task accessors,
+/// which is where its time goes.
+fn display_columns(c: &mut Criterion) {
+  let config = Config::default();
+  let task_mgr = synthetic_tasks(&config);
+  let tasks = listing(&task_mgr, &config, Vec::new(), &[]);
+
+  c.bench_function("display_columns", |b| {
+    b.iter(|| {
+      tasks.iter().fold(0, |width, (uid, task)| {
+        let notes = task.notes().iter().map(|note| note.content.len()).max();
+        let tags = task.tags().map(str::len).sum::<usize>();
+        let progress = task
+          .checklist_progress()
+          .map_or(0, |(done, all)| done + all);
+
+        width
+          .max(uid.val() as usize)
+          .max(task.age().num_seconds() as usize)
+          .max(task.spent_time().num_seconds() as usize)
+          .max(task.name().len() + task.project().map_or(0, str::len))
+          .max(task.priority().map_or(0, |_| 1) + tags + progress + notes.unwrap_or(0))
+      })
+    })
+  });
+}
+
+fn persistence(c: &mut Criterion) {
+  let dir = TempDir::new("toodoux-bench").unwrap();
+  let config = Config::create(Some(dir.path())).unwrap();
+  let mut task_mgr = synthetic_tasks(&config);
+  task_mgr.save(&config).unwrap();
+
+  let mut group = c.benchmark_group("persistence");
+  group.sample_size(20);
+
+  group.bench_function("save", |b| b.iter(|| task_mgr.save(&config).unwrap()));
+
+  group.bench_function("load", |b| {
+    b.iter(|| TaskManager::new_from_config(black_box(&config)).unwrap())
+  });
+
+  group.bench_function("load_and_save", |b| {
+    b.iter(|| {
+      let mut task_mgr = TaskManager::new_from_config(&config).unwrap();
+      task_mgr.save(&config).unwrap();
+    })
+  });
+
+  group.finish();
+}
+
+criterion_group!(benches, filtered_task_listing, display_columns, persistence);
+criterion_main!(benches);
@@ -1,24 +1,67 @@
 //! Command line interface.
 
 use crate::{
-  interactive_editor::{interactively_edit, InteractiveEditingError},
-  term::Terminal,
+  doctor, drafts,
+  interactive_editor::{interactively_edit, interactively_edit_file, InteractiveEditingError},
+  interactive_list::{self, Action, Screen},
+  mutation_log, quick, rpc,
+  term::{self, Terminal},
 };
-use chrono::{DateTime, Duration, Utc};
+use chrono::{DateTime, Datelike as _, Duration, Local, NaiveDate, Utc};
 use colored::Colorize as _;
 use itertools::Itertools;
-use std::{fmt, fmt::Display, iter::once, path::PathBuf};
-use structopt::StructOpt;
+use std::{
+  borrow::Cow,
+  collections::BTreeSet,
+  fmt,
+  fmt::Display,
+  fs,
+  io::{self, Read as _, Write as _},
+  path::{Path, PathBuf},
+};
+use structopt::{
+  clap::{AppSettings, ArgGroup},
+  StructOpt,
+};
 use toodoux::{
-  config::Config,
-  error::Error,
+  config::{Config, DescriptionOverflow, Hyperlinks, NormalizationConfig},
+  digest::Digest,
+  email,
+  error::{Error, Operation},
   filter::TaskDescriptionFilter,
-  metadata::{Metadata, MetadataValidationError, Priority},
-  task::{Event, Status, Task, TaskManager, UID},
+  io::{export_public, Registry},
+  markdown,
+  metadata::{self, Fields, Metadata, MetadataValidationError, Priorities, Priority},
+  nlp::{self, QuickAdd},
+  normalize, org,
+  policy::{self, PolicyViolation},
+  privacy,
+  publish::Site,
+  share,
+  storage::MemoryStorage,
+  task::{
+    goal::Goal,
+    merge::{Conflict, Strategy},
+    ChecklistItem, EffortRollup, Event, MilestoneCount, Note, ProjectTree, Status, Summary, Task,
+    TaskManager, TimeEntry, UID,
+  },
+  template::{Template, TemplateError},
+  time, tr,
 };
-use unicode_width::UnicodeWidthStr;
+use unicode_segmentation::UnicodeSegmentation as _;
+use unicode_width::{UnicodeWidthChar as _, UnicodeWidthStr};
+
+/// Glyph marking pinned tasks in listings.
+const PIN_GLYPH: &str = "📌";
 
-const PREVIOUS_NOTES_HELP_END_MARKER: &str = "---------------------- >8 ----------------------\n";
+/// Description of the exit codes, shown by `td help exit-codes`.
+const EXIT_CODES: &str = "Exit codes, for scripts to branch on:
+
+  0  success
+  1  invalid usage, or any error not listed below
+  2  missing or unknown task
+  3  storage error: the tasks, the journal or the configuration cannot be read or written
+  4  nothing matched the filter of a listing run with --fail-if-empty";
 
 #[derive(Debug, StructOpt)]
 #[structopt(
@@ -26,8 +69,8 @@ const PREVIOUS_NOTES_HELP_END_MARKER: &str = "---------------------- >8 --------
   about = "A modern task / todo / note management tool."
 )]
 pub struct Command {
-  /// UID of a task to operate on.
-  pub task_uid: Option<UID>,
+  /// UID of a task to operate on; show accepts several of them.
+  pub task_uids: Vec<UID>,
 
   #[structopt(subcommand)]
   pub subcmd: Option<SubCommand>,
@@ -35,6 +78,24 @@ pub struct Command {
   /// Non-default config root to read data and configuration from.
   #[structopt(long, short)]
   pub config: Option<PathBuf>,
+
+  /// Plain output: no colors, no unicode decorations and tab-separated columns in listings.
+  ///
+  /// Overrides the user configuration.
+  #[structopt(long)]
+  pub plain: bool,
+
+  /// Do not ask for confirmation before running destructive commands.
+  ///
+  /// Overrides the user configuration.
+  #[structopt(long, short)]
+  pub yes: bool,
+
+  /// Verbosity level: `-v` for information, `-vv` for debugging and `-vvv` for tracing.
+  ///
+  /// Overrides the `RUST_LOG` environment variable.
+  #[structopt(long, short, parse(from_occurrences))]
+  pub verbose: u8,
 }
 
 #[derive(Debug, StructOpt)]
@@ -54,6 +115,34 @@ pub enum SubCommand {
     #[structopt(short, long)]
     note: bool,
 
+    /// Use the given message as an initial note, instead of writing it in an editor as with --note.
+    ///
+    /// If several messages are given, they are separated by blank lines.
+    #[structopt(short, long, number_of_values = 1, conflicts_with = "note")]
+    message: Vec<String>,
+
+    /// Add the task even if it looks like a duplicate of an active task.
+    #[structopt(short, long)]
+    force: bool,
+
+    /// Put the task in the someday backlog.
+    #[structopt(long)]
+    someday: bool,
+
+    /// Read the task from a Markdown document; use - to read from the standard input.
+    ///
+    /// The first heading is the name of the task and the rest of the document is added as a note. Metadata can be set
+    /// in a front-matter block delimited by --- lines.
+    #[structopt(long, conflicts_with = "content")]
+    from_md: Option<PathBuf>,
+
+    /// Read the task from an email; use - to read from the standard input.
+    ///
+    /// The subject is the name of the task, the body is added as a note and the Message-ID is the external ID of the
+    /// task.
+    #[structopt(long, conflicts_with_all = &["content", "from-md"])]
+    from_email: Option<PathBuf>,
+
     /// Content of the task.
     ///
     /// If nothing is set, an interactive prompt is spawned for you to enter the content
@@ -61,6 +150,11 @@ pub enum SubCommand {
     content: Vec<String>,
   },
 
+  /// Capture a task on a single line and exit; meant to be bound to a hotkey.
+  ///
+  /// The content is read as with add, and the tab key completes projects, tags, contexts, milestones and priorities.
+  Quick,
+
   /// Edit a task.
   #[structopt(visible_aliases = &["e", "ed"])]
   Edit {
@@ -68,9 +162,17 @@ pub enum SubCommand {
     content: Vec<String>,
   },
 
-  /// Show the details of a task.
+  /// Show the details of a task, or of several tasks back to back; e.g. td 3 5 9 show.
   #[structopt(visible_aliases = &["s"])]
-  Show,
+  Show {
+    /// Show the number of notes instead of the notes themselves.
+    #[structopt(short, long)]
+    short: bool,
+
+    /// Show long notes in full instead of collapsing them.
+    #[structopt(long)]
+    full: bool,
+  },
 
   /// Mark a task as todo.
   Todo,
@@ -78,12 +180,61 @@ pub enum SubCommand {
   /// Mark a task as started.
   Start,
 
+  /// Pause a task: its spent time stops accumulating, but it is still shown as started.
+  Pause,
+
   /// Mark a task as done.
   Done,
 
   /// Mark a task as cancelled.
   Cancel,
 
+  /// Move a task to the someday backlog, or back out of it.
+  Someday,
+
+  /// Pin a task at the top of listings.
+  Pin,
+
+  /// Unpin a task.
+  Unpin,
+
+  /// Review the someday backlog, one task at a time.
+  Review,
+
+  /// Pick a random task among the active tasks matching a filter, and offer to start it.
+  Roulette {
+    /// Make urgent tasks more likely to be picked: the ones with a higher priority and the ones due within a week.
+    #[structopt(short, long)]
+    weighted: bool,
+
+    /// Apply filters ignoring case.
+    #[structopt(short = "C", long)]
+    case_insensitive: bool,
+
+    /// Metadata filter.
+    metadata_filter: Vec<String>,
+  },
+
+  /// Walk through the active tasks missing a priority or an effort, asking for them one task at a time.
+  Triage {
+    /// Apply filters ignoring case.
+    #[structopt(short = "C", long)]
+    case_insensitive: bool,
+
+    /// Metadata filter.
+    metadata_filter: Vec<String>,
+  },
+
+  /// Fire the reminders that are due, once; meant to be run periodically, e.g. from cron.
+  Notify,
+
+  /// Postpone a task, hiding it from listings until then.
+  Snooze {
+    /// How long to snooze the task for; e.g. 2d or 3h.
+    #[structopt(parse(try_from_str = parse_duration_arg))]
+    duration: Duration,
+  },
+
   /// Remove a task.
   #[structopt(visible_aliases = &["r", "rm"])]
   Remove {
@@ -92,6 +243,9 @@ pub enum SubCommand {
     all: bool,
   },
 
+  /// Manipulate removed tasks.
+  Trash(TrashCommand),
+
   /// List all the tasks.
   #[structopt(visible_aliases = &["l", "ls"])]
   List {
@@ -103,6 +257,10 @@ pub enum SubCommand {
     #[structopt(short, long)]
     start: bool,
 
+    /// Filter with paused items.
+    #[structopt(short, long)]
+    paused: bool,
+
     /// Filter with done items.
     #[structopt(short, long)]
     done: bool,
@@ -115,14 +273,83 @@ pub enum SubCommand {
     #[structopt(short, long)]
     all: bool,
 
+    /// Also list snoozed items.
+    #[structopt(long)]
+    snoozed: bool,
+
+    /// List the someday backlog instead.
+    #[structopt(long)]
+    someday: bool,
+
+    /// Only print the totals of the listed tasks.
+    #[structopt(long)]
+    summary_only: bool,
+
+    /// Select tasks with the arrow keys and change their status, add notes or show them with single keys.
+    #[structopt(short, long, conflicts_with = "summary-only")]
+    interactive: bool,
+
+    /// Apply filters ignoring case.
+    #[structopt(short = "C", long)]
+    case_insensitive: bool,
+
+    /// Exit with code 4 if no task matches the filter.
+    #[structopt(long)]
+    fail_if_empty: bool,
+
+    /// Number of tasks per page, the first ones in listing order.
+    #[structopt(short = "n", long)]
+    limit: Option<usize>,
+
+    /// Page to show, starting at 1 with the first tasks in listing order.
+    #[structopt(long, default_value = "1")]
+    page: usize,
+
+    /// Don’t print the header of the listing, only the tasks.
+    #[structopt(long)]
+    no_header: bool,
+
+    /// Print the columns of each task separated with a single space, without padding nor colors; meant for scripts.
+    #[structopt(long)]
+    no_padding: bool,
+
+    /// Metadata filter.
+    metadata_filter: Vec<String>,
+  },
+
+  /// Output the tasks matching a filter, one per line, formatted with a template.
+  ///
+  /// Templates contain `{field}` placeholders, such as `{uid}\t{project}\t{name}`. Builtin fields are uid, name,
+  /// status, project, priority, context, tags, created, age, spent, effort, scheduled, deadline, recurrence, notes and
+  /// ext; user-defined fields are available by name. Use `{field:<N}` or `{field:>N}` to align a value in a column.
+  Query {
+    /// Template used to format each task.
+    #[structopt(short, long, default_value = "{uid}\t{name}")]
+    format: String,
+
+    /// Query all the tasks, including done, cancelled, snoozed and someday ones.
+    #[structopt(short, long)]
+    all: bool,
+
     /// Apply filters ignoring case.
     #[structopt(short = "C", long)]
     case_insensitive: bool,
 
+    /// Exit with code 4 if no task matches the filter.
+    #[structopt(long)]
+    fail_if_empty: bool,
+
     /// Metadata filter.
     metadata_filter: Vec<String>,
   },
 
+  /// Suggest the due and high-priority tasks to work on today, based on their estimated effort.
+  Plan {
+    /// Time available today; e.g. 6h or 2h30m.
+    #[structopt(short, long, parse(try_from_str = parse_duration_arg))]
+    capacity: Duration,
+  },
+
   /// List, add and edit notes.
   Note {
     /// UID of a note to operate on.
@@ -132,16 +359,255 @@ pub enum SubCommand {
     subcmd: NoteCommand,
   },
 
+  /// Manipulate the checklist of a task.
+  ///
+  /// Without subcommand, check or uncheck the item referred to by its UID, or show the checklist if no item UID is
+  /// provided.
+  #[structopt(visible_aliases = &["chk"])]
+  Check {
+    /// UID of a checklist item to check or uncheck.
+    item_uid: Option<UID>,
+
+    #[structopt(subcommand)]
+    subcmd: Option<CheckCommand>,
+  },
+
+  /// Track goals with the tasks linked to them.
+  ///
+  /// Without task, show the progress of the goals, or of a single goal along with its tasks. With a task, link it to a
+  /// goal, or show the goal it is linked to.
+  Goal {
+    /// UID of a goal to operate on.
+    goal_uid: Option<UID>,
+
+    #[structopt(subcommand)]
+    subcmd: Option<GoalCommand>,
+  },
+
   /// Show the edit history of a task.
   History,
 
+  /// Show the events of all the tasks, the most recent first; e.g. to find out what was done yesterday.
+  Log {
+    /// Period to show, up to now; e.g. 1d or 2w.
+    #[structopt(short, long, default_value = "1w", parse(try_from_str = parse_duration_arg))]
+    since: Duration,
+
+    /// Number of events per page.
+    #[structopt(short = "n", long, default_value = "50")]
+    limit: usize,
+
+    /// Page to show, starting at 1 with the most recent events.
+    #[structopt(short, long, default_value = "1")]
+    page: usize,
+  },
+
+  /// Adjust the time spent on a task.
+  ///
+  /// Without subcommand, show the timesheet of the task: the periods it was ongoing and the manual adjustments.
+  Time {
+    #[structopt(subcommand)]
+    subcmd: Option<TimeCommand>,
+  },
+
   /// Manipulate projects.
   #[structopt(visible_aliases = &["proj"])]
   Project(ProjectCommand),
+
+  /// Manipulate tags.
+  Tag(TagCommand),
+
+  /// Manipulate contexts.
+  ///
+  /// Without subcommand, show the active context and list the contexts.
+  #[structopt(visible_aliases = &["ctx"])]
+  Context {
+    #[structopt(subcommand)]
+    subcmd: Option<ContextCommand>,
+  },
+
+  /// Summarize what was completed, what is in progress and the upcoming deadlines.
+  Digest {
+    /// Period to summarize, up to now; e.g. 1w or 3d.
+    #[structopt(short, long, default_value = "1w", parse(try_from_str = parse_duration_arg))]
+    since: Duration,
+
+    /// Format of the digest.
+    ///
+    /// Supported formats: markdown, email.
+    #[structopt(short, long, default_value = "markdown")]
+    format: String,
+
+    /// Post the digest to a chat service configured in the [digest] configuration instead of printing it.
+    ///
+    /// Supported services: slack, matrix.
+    #[cfg(feature = "post")]
+    #[structopt(long)]
+    post: Option<toodoux::digest::Chat>,
+  },
+
+  /// Capture a web page sent by a browser extension through org-protocol.
+  ///
+  /// The title of the page is the name of the task; its URL and the selected text are added as a note.
+  Capture {
+    /// org-protocol URL, such as org-protocol://capture?url=…&title=…&body=…
+    #[structopt(long)]
+    url: String,
+
+    /// Add the task even if it looks like a duplicate of an active task.
+    #[structopt(short, long)]
+    force: bool,
+
+    /// Metadata to add to the task, such as @reading #later.
+    content: Vec<String>,
+  },
+
+  /// Synchronize the tasks with an external tracker.
+  #[cfg(feature = "jira")]
+  Sync {
+    #[structopt(subcommand)]
+    tracker: SyncCommand,
+  },
+
+  /// Share a task as a deep link that another toodoux instance can import with --payload.
+  Share {
+    /// Also render the deep link as a QR code.
+    #[cfg(feature = "qr")]
+    #[structopt(long)]
+    qr: bool,
+  },
+
+  /// Import tasks from a file, or a task shared by another toodoux instance.
+  Import {
+    /// Format of the file to import.
+    ///
+    /// Supported formats: org, jsonl.
+    #[structopt(short, long, required_unless = "payload")]
+    format: Option<String>,
+
+    /// Path of the file to import; use - to read from the standard input.
+    #[structopt(required_unless = "payload")]
+    path: Option<PathBuf>,
+
+    /// Deep link or payload of a shared task, as printed by the share command.
+    #[structopt(long, conflicts_with_all = &["format", "path"])]
+    payload: Option<String>,
+
+    /// Import tasks even if they were already imported.
+    #[structopt(long)]
+    allow_duplicates: bool,
+  },
+
+  /// Export all the tasks, or a single one if a task UID is provided.
+  Export {
+    /// Format to export the tasks to.
+    ///
+    /// Supported formats: org, jsonl, md, reminders; see --list-formats.
+    #[structopt(short, long, required_unless = "list-formats")]
+    format: Option<String>,
+
+    /// List the available formats, along with whether they can be imported and exported.
+    #[structopt(long, conflicts_with_all = &["format", "output"])]
+    list_formats: bool,
+
+    /// Path of the file to export to; if absent, tasks are written to the standard output.
+    #[structopt(short, long)]
+    output: Option<PathBuf>,
+  },
+
+  /// Publish the backlog as a static site: an index and a page per project.
+  ///
+  /// The published tasks, their columns and the output directory are set in the [publish] configuration.
+  Publish {
+    /// Directory to write the site to, overriding the configured one.
+    #[structopt(short, long)]
+    output: Option<PathBuf>,
+  },
+
+  /// Compact the journal into the tasks file.
+  Gc {
+    /// Also drop the events superseded by later ones, such as repeated status flips and replaced notes.
+    ///
+    /// What the tasks show — metadata, notes, spent time and key dates — is kept as-is.
+    #[structopt(long)]
+    squash: bool,
+  },
+
+  /// Merge another copy of the tasks file, such as a conflicted copy left by a sync tool.
+  ///
+  /// Tasks changed in a single copy are merged as-is. For each task changed in both copies, the events of each copy
+  /// are shown, and you are asked how to resolve the conflict: union keeps the events of both copies, theirs and mine
+  /// keep a single copy, and edit opens the events of both copies in an editor.
+  Resolve {
+    /// Path of the other copy of the tasks file.
+    path: PathBuf,
+
+    /// Resolve all the conflicts the same way, without asking: union, theirs or mine.
+    #[structopt(short, long)]
+    strategy: Option<Strategy>,
+  },
+
+  /// Diagnose the configuration, the tasks and the environment, and suggest fixes.
+  Doctor,
+
+  /// Inspect the configuration, once its included files are merged.
+  Config(ConfigCommand),
+
+  /// Serve editor plugins with JSON-RPC requests, one per line, over the standard input and output.
+  ///
+  /// Supported methods: list, add, update, watch.
+  Rpc,
+
+  /// Print completion candidates, one per line, for shell completions to offer dynamic values.
+  #[structopt(
+    name = "_complete",
+    setting = AppSettings::Hidden,
+    group = ArgGroup::with_name("kind").required(true)
+  )]
+  Complete {
+    /// Complete the names of the projects.
+    #[structopt(long, group = "kind")]
+    projects: bool,
+
+    /// Complete the tags.
+    #[structopt(long, group = "kind")]
+    tags: bool,
+
+    /// Complete the UIDs of the tasks.
+    #[structopt(long, group = "kind")]
+    uids: bool,
+
+    /// Only print the candidates starting with the given prefix, ignoring case.
+    #[structopt(long, default_value = "")]
+    prefix: String,
+  },
+
+  /// Describe the exit codes, for scripts to branch on.
+  #[structopt(name = "exit-codes", long_about = EXIT_CODES)]
+  ExitCodes,
+}
+
+#[cfg(feature = "jira")]
+#[derive(Debug, StructOpt)]
+pub enum SyncCommand {
+  /// Synchronize with the Jira issues matching the query of the [jira] configuration.
+  Jira {
+    /// Only show the changes, without applying them.
+    #[structopt(long)]
+    dry_run: bool,
+  },
 }
 
 #[derive(Debug, StructOpt)]
 pub enum NoteCommand {
+  /// List the notes, or only the note referred to by its UID along with its replies.
+  #[structopt(visible_aliases = &["ls"])]
+  List {
+    /// Show long notes in full instead of collapsing them.
+    #[structopt(long)]
+    full: bool,
+  },
+
   /// Add a new note.
   ///
   /// You will be prompted to write a note within an editor.
@@ -152,6 +618,20 @@ pub enum NoteCommand {
     /// Overrides the user configuration.
     #[structopt(long)]
     no_history: bool,
+
+    /// UID of a note to reply to, threading the new note under it.
+    #[structopt(short = "r", long)]
+    in_reply_to: Option<UID>,
+
+    /// Read the note from the standard input instead of an editor.
+    #[structopt(long, conflicts_with = "message")]
+    stdin: bool,
+
+    /// Use the given message as the note instead of an editor.
+    ///
+    /// If several messages are given, they are separated by blank lines.
+    #[structopt(short, long, number_of_values = 1)]
+    message: Vec<String>,
   },
 
   /// Edit a note.
@@ -167,11 +647,80 @@ pub enum NoteCommand {
   },
 }
 
+#[derive(Debug, StructOpt)]
+pub enum GoalCommand {
+  /// Add a new goal.
+  #[structopt(visible_aliases = &["a"])]
+  Add {
+    /// Name of the goal.
+    name: Vec<String>,
+
+    /// Date the goal is due; e.g. 2021-09-01.
+    #[structopt(long, parse(try_from_str = parse_date_arg))]
+    due: Option<NaiveDate>,
+  },
+}
+
+#[derive(Debug, StructOpt)]
+pub enum CheckCommand {
+  /// Add a new item to the checklist.
+  #[structopt(visible_aliases = &["a"])]
+  Add {
+    /// Content of the item.
+    content: Vec<String>,
+  },
+}
+
+#[derive(Debug, StructOpt)]
+pub enum TimeCommand {
+  /// Add time spent on the task, e.g. because it was not started while working on it.
+  Add {
+    /// Time to add; e.g. 45min or 1h30m.
+    #[structopt(parse(try_from_str = parse_duration_arg))]
+    duration: Duration,
+
+    /// Reason of the adjustment.
+    reason: Vec<String>,
+  },
+
+  /// Remove time spent on the task, e.g. because it was left started.
+  #[structopt(visible_aliases = &["rm"])]
+  Remove {
+    /// Time to remove; e.g. 10min or 2h.
+    #[structopt(parse(try_from_str = parse_duration_arg))]
+    duration: Duration,
+
+    /// Reason of the adjustment.
+    reason: Vec<String>,
+  },
+}
+
+#[derive(Debug, StructOpt)]
+pub enum TrashCommand {
+  /// List the tasks in the trash.
+  #[structopt(visible_aliases = &["l", "ls"])]
+  List,
+
+  /// Restore a task from the trash.
+  Restore {
+    /// UID of the task to restore.
+    uid: UID,
+  },
+
+  /// Permanently delete the tasks that have been in the trash for longer than the configured retention.
+  Empty {
+    /// Permanently delete all the tasks in the trash, whatever the retention.
+    #[structopt(short, long)]
+    all: bool,
+  },
+}
+
 #[derive(Debug, StructOpt)]
 pub enum ProjectCommand {
   /// Rename a project.
   ///
   /// This has the effect of renamming the project used for all tasks if their current project is the one to rename.
+  /// Subprojects are renamed too: renaming `work` to `job` moves `work.backend` to `job.backend`.
   Rename {
     /// Project to rename.
     current_project: String,
@@ -179,30 +728,157 @@ pub enum ProjectCommand {
     /// New name of the project.
     new_project: String,
   },
+
+  /// Print the hierarchy of projects, along with the number of active tasks in each of them.
+  Tree {
+    /// Count all the tasks, including done and cancelled ones.
+    #[structopt(short, long)]
+    all: bool,
+  },
+
+  /// Show the estimated effort and the time spent on a project, rolled up from its tasks and subprojects.
+  Show {
+    /// Project to show, with or without its leading `@`; subprojects are included.
+    project: String,
+  },
+
+  /// Summarize the milestones of a project, along with their number of open and done tasks.
+  Milestones {
+    /// Project to summarize the milestones of, with or without its leading `@`; subprojects are included.
+    project: String,
+  },
+
+  /// Normalize the projects of all the tasks according to the [normalization] configuration.
+  ///
+  /// Without --apply, only show the projects that would be renamed.
+  Normalize {
+    /// Rename the projects.
+    #[structopt(long)]
+    apply: bool,
+  },
+}
+
+#[derive(Debug, StructOpt)]
+pub enum TagCommand {
+  /// Normalize the tags of all the tasks according to the [normalization] configuration.
+  ///
+  /// Without --apply, only show the tags that would be renamed.
+  Normalize {
+    /// Rename the tags.
+    #[structopt(long)]
+    apply: bool,
+  },
+}
+
+#[derive(Debug, StructOpt)]
+pub enum ConfigCommand {
+  /// Print the effective value of a key, such as main.tasks_file, or of a whole section, such as digest.
+  Get {
+    /// Dotted path of the key or section.
+    key: String,
+
+    /// Also print the file each value comes from, one value per line.
+    #[structopt(long)]
+    origin: bool,
+  },
+}
+
+#[derive(Debug, StructOpt)]
+pub enum ContextCommand {
+  /// Set the active context; listings only show the tasks in that context.
+  Now {
+    /// Context to switch to.
+    context: String,
+  },
+
+  /// Clear the active context.
+  Clear,
+
+  /// List the contexts of the active tasks.
+  #[structopt(visible_aliases = &["l", "ls"])]
+  List,
 }
 
 #[derive(Debug)]
 pub enum SubCmdError {
   MetadataValidationError(MetadataValidationError),
-  CannotEditNote(String),
   EmptyNote,
+  UnknownFormat(String),
+  TemplateError(TemplateError),
   InteractiveEditingError(InteractiveEditingError),
+  TerminalError(io::Error),
   ToodouxError(Error),
+  /// The task to operate on is missing or unknown; the string is the message, such as “missing or unknown task to
+  /// edit”.
+  MissingTask(&'static str),
+  /// A listing run with `--fail-if-empty` matched nothing.
+  EmptyListing,
+  /// Several tasks were given to a command operating on a single one.
+  TooManyTasks,
+  /// A task doesn’t comply with a policy of the configuration.
+  PolicyViolation(PolicyViolation),
+  /// The configuration has no such key.
+  UnknownConfigKey(String),
+  /// Checklist items are numbered from 1, but 0 was given.
+  NoChecklistItemZero,
+  /// A new task looks like a duplicate of the active task with this UID, and `--force` was not passed.
+  Duplicate(UID),
+}
+
+impl SubCmdError {
+  /// Exit code of the process failing with this error.
+  pub fn exit_code(&self) -> ExitCode {
+    match *self {
+      SubCmdError::MissingTask(_) | SubCmdError::ToodouxError(Error::UnknownTask(_)) => {
+        ExitCode::UnknownTask
+      }
+      SubCmdError::ToodouxError(ref e) if e.is_storage() => ExitCode::Storage,
+      SubCmdError::EmptyListing => ExitCode::Empty,
+      _ => ExitCode::Usage,
+    }
+  }
 }
 
 impl fmt::Display for SubCmdError {
   fn fmt(&self, f: &mut fmt::Formatter) -> Result<(), fmt::Error> {
     match *self {
-      SubCmdError::MetadataValidationError(ref e) => write!(f, "metadata validation error: {}", e),
-      SubCmdError::CannotEditNote(ref reason) => write!(f, "cannot edit note: {}", reason),
-      SubCmdError::EmptyNote => f.write_str("the note was empty; nothing added"),
-      SubCmdError::InteractiveEditingError(ref e) => write!(f, "interactive edit error: {}", e),
-      SubCmdError::ToodouxError(ref e) => write!(f, "toodoux error: {}", e),
+      SubCmdError::MetadataValidationError(ref e) => {
+        f.write_str(&tr!("metadata validation error: {}", e))
+      }
+      SubCmdError::EmptyNote => f.write_str(tr!("the note was empty; nothing added")),
+      SubCmdError::UnknownFormat(ref format) => f.write_str(&tr!("unknown format: {}", format)),
+      SubCmdError::TemplateError(ref e) => f.write_str(&tr!("template error: {}", e)),
+      SubCmdError::InteractiveEditingError(ref e) => {
+        f.write_str(&tr!("interactive edit error: {}", e))
+      }
+      SubCmdError::TerminalError(_) => f.write_str(tr!("terminal error")),
+      SubCmdError::ToodouxError(ref e) => e.fmt(f),
+      SubCmdError::MissingTask(msg) => f.write_str(msg),
+      SubCmdError::EmptyListing => f.write_str(tr!("no task matched")),
+      SubCmdError::TooManyTasks => f.write_str(tr!("only show operates on several tasks")),
+      SubCmdError::PolicyViolation(ref e) => f.write_str(&tr!("policy violation: {}", e)),
+      SubCmdError::UnknownConfigKey(ref key) => {
+        f.write_str(&tr!("unknown configuration key: {}", key))
+      }
+      SubCmdError::NoChecklistItemZero => f.write_str(tr!("checklist items are numbered from 1")),
+      SubCmdError::Duplicate(uid) => f.write_str(&tr!(
+        "the task looks like a duplicate of #{}; pass --force to add it anyway",
+        uid
+      )),
     }
   }
 }
 
-impl std::error::Error for SubCmdError {}
+impl std::error::Error for SubCmdError {
+  fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+    match *self {
+      SubCmdError::TerminalError(ref e) => Some(e),
+      // library errors are displayed as is, so their sources are this error’s
+      SubCmdError::ToodouxError(ref e) => std::error::Error::source(e),
+      _ => None,
+    }
+  }
+}
 
 impl From<MetadataValidationError> for SubCmdError {
   fn from(err: MetadataValidationError) -> Self {
@@ -210,8 +886,20 @@ impl From<MetadataValidationError> for SubCmdError {
   }
 }
 
-impl From<InteractiveEditingError> for SubCmdError {
-  fn from(err: InteractiveEditingError) -> Self {
+impl From<PolicyViolation> for SubCmdError {
+  fn from(err: PolicyViolation) -> Self {
+    Self::PolicyViolation(err)
+  }
+}
+
+impl From<TemplateError> for SubCmdError {
+  fn from(err: TemplateError) -> Self {
+    Self::TemplateError(err)
+  }
+}
+
+impl From<InteractiveEditingError> for SubCmdError {
+  fn from(err: InteractiveEditingError) -> Self {
     Self::InteractiveEditingError(err)
   }
 }
@@ -222,18 +910,207 @@ impl From<Error> for SubCmdError {
   }
 }
 
+/// Exit codes of the process; see `td help exit-codes`.
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub enum ExitCode {
+  Usage = 1,
+  UnknownTask = 2,
+  Storage = 3,
+  Empty = 4,
+}
+
+#[allow(clippy::upper_case_acronyms)]
 pub struct CLI<Term> {
   config: Config,
   term: Term,
+  plain: bool,
+  confirm: bool,
+  /// Whether task UIDs and URLs are rendered as hyperlinks.
+  hyperlinks: bool,
+  formats: Registry,
 }
 
+/// Prefix of the hyperlinks of task UIDs, followed by the UID.
+const TASK_LINK_PREFIX: &str = "toodoux://task/";
+
 impl<Term> CLI<Term>
 where
   Term: Terminal,
 {
   /// Create a CLI.
-  pub fn new(config: Config, term: Term) -> Self {
-    Self { config, term }
+  ///
+  /// `plain` forces the plain output mode and `yes` disables confirmation prompts; if `false`, the configuration
+  /// decides.
+  pub fn new(config: Config, term: Term, plain: bool, yes: bool) -> Self {
+    let plain = plain || config.plain_output();
+    let confirm = !yes && config.confirm();
+
+    if plain || !term.supports_color() {
+      colored::control::set_override(false);
+    }
+
+    let hyperlinks = !plain
+      && match config.hyperlinks() {
+        Hyperlinks::Auto => term.supports_hyperlinks(),
+        Hyperlinks::Always => true,
+        Hyperlinks::Never => false,
+      };
+
+    Self {
+      config,
+      term,
+      plain,
+      confirm,
+      hyperlinks,
+      formats: Registry::default(),
+    }
+  }
+
+  /// Render `text` as a hyperlink to the task `uid`, if hyperlinks are enabled.
+  fn task_link(&self, uid: UID, text: impl fmt::Display) -> String {
+    if self.hyperlinks {
+      term::hyperlink(&format!("{}{}", TASK_LINK_PREFIX, uid), text)
+    } else {
+      text.to_string()
+    }
+  }
+
+  /// Render the URLs of `text` as hyperlinks, if hyperlinks are enabled.
+  fn linkify<'a>(&self, text: &'a str) -> Cow<'a, str> {
+    if self.hyperlinks {
+      Cow::Owned(term::linkify(text))
+    } else {
+      Cow::Borrowed(text)
+    }
+  }
+
+  /// Save the tasks, recording the mutation in the mutation log if enabled.
+  fn save(&self, task_mgr: &mut TaskManager) -> Result<(), SubCmdError> {
+    task_mgr.save(&self.config)?;
+
+    if self.config.mutation_log() {
+      if let Err(err) = mutation_log::record(&self.config) {
+        log::warn!(
+          "cannot record mutation in {}: {}",
+          mutation_log::path(&self.config).display(),
+          err
+        );
+      }
+    }
+
+    Ok(())
+  }
+
+  /// Ask the user to confirm a destructive operation.
+  ///
+  /// Always return `true` if confirmation is disabled.
+  fn confirm(&self, question: impl Display) -> bool {
+    !self.confirm || self.ask(question).unwrap_or(false)
+  }
+
+  /// Ask a yes-or-no question; no by default.
+  ///
+  /// Return [`None`] if no more input is available.
+  fn ask(&self, question: impl Display) -> Option<bool> {
+    print!("{} ({}/{}) ➤ ", question, "y".green(), "N".red().bold());
+    let _ = io::stdout().flush();
+
+    self
+      .term
+      .read_line()
+      .map(|input| matches!(input.trim(), "y" | "Y" | "yes"))
+  }
+
+  /// Ask whether to resume the draft of a note left for the task `uid`; yes by default.
+  fn confirm_resume_draft(&self, uid: UID) -> bool {
+    self.confirm_default_yes(tr!(
+      "a draft of a note is left for task {}; resume it?",
+      uid
+    ))
+  }
+
+  /// Ask for confirmation, yes by default; see [`CLI::confirm`].
+  fn confirm_default_yes(&self, question: impl Display) -> bool {
+    if !self.confirm {
+      return true;
+    }
+
+    print!("{} ({}/{}) ➤ ", question, "Y".green().bold(), "n".red());
+    let _ = io::stdout().flush();
+
+    match self.term.read_line() {
+      Some(input) => !matches!(input.trim(), "n" | "N" | "no"),
+      None => true,
+    }
+  }
+
+  /// Correct the projects and tags of `metadata` that are not used yet but look like typos of ones that are.
+  ///
+  /// The corrections are asked for, yes by default, unless they are automatic in the configuration. Without
+  /// confirmations, nothing is corrected, so that scripts get what they asked for.
+  fn correct_metadata_typos(&self, task_mgr: &TaskManager, metadata: &mut [Metadata]) {
+    let autocorrect = self.config.autocorrect_metadata();
+
+    if !autocorrect && !self.confirm {
+      return;
+    }
+
+    for metadata in metadata {
+      let (operator, close, value) = match metadata {
+        Metadata::Project(project) => ("@", task_mgr.close_project(project), project),
+        Metadata::Tag(tag) => ("#", task_mgr.close_tag(tag), tag),
+        _ => continue,
+      };
+
+      if let Some(close) = close {
+        if autocorrect {
+          println!(
+            "{}",
+            tr!("corrected {0}{1} to {0}{2}", operator, value, close).yellow()
+          );
+        } else if !self.confirm_default_yes(tr!(
+          "did you mean {0}{2} instead of {0}{1}?",
+          operator,
+          value,
+          close
+        )) {
+          continue;
+        }
+
+        *value = close.to_owned();
+      }
+    }
+  }
+
+  /// Interactively write a new note for the task `uid`.
+  ///
+  /// The note is written in a draft, resumed if one was left by a previous session and the user wants to. The draft is
+  /// left behind if anything goes wrong; [`CLI::remove_draft`] removes it once the note is saved.
+  fn write_note(&self, uid: UID, task: &Task, with_history: bool) -> Result<String, SubCmdError> {
+    let path = drafts::path(&self.config, uid);
+    let draft = drafts::read(&path)
+      .map_err(Error::io(Operation::Read, &path))?
+      .filter(|draft| read_note(&self.config, draft).is_ok());
+
+    let buffer = match draft {
+      Some(draft) if self.confirm_resume_draft(uid) => draft,
+      _ => note_buffer(&self.config, with_history, task, None)?,
+    };
+    drafts::write(&path, &buffer).map_err(Error::io(Operation::Write, &path))?;
+
+    let note = read_note(&self.config, &interactively_edit_file(&self.config, &path)?);
+    if let Err(SubCmdError::EmptyNote) = note {
+      self.remove_draft(uid)?;
+    }
+
+    note
+  }
+
+  /// Remove the draft of a note of the task `uid`, once the note is saved or abandoned.
+  fn remove_draft(&self, uid: UID) -> Result<(), SubCmdError> {
+    let path = drafts::path(&self.config, uid);
+    drafts::remove(&path).map_err(Error::io(Operation::Write, &path))?;
+    Ok(())
   }
 
   /// Run a subcommand of the CLI.
@@ -241,12 +1118,42 @@ where
     &mut self,
     task_mgr: &mut TaskManager,
     subcmd: Option<SubCommand>,
-    task_uid: Option<UID>,
+    task_uids: Vec<UID>,
   ) -> Result<(), SubCmdError> {
+    if task_uids.len() > 1 && !matches!(subcmd, Some(SubCommand::Show { .. })) {
+      return Err(SubCmdError::TooManyTasks);
+    }
+
+    let task_uid = task_uids.first().copied();
+
     match subcmd {
       // default subcommand
       None => {
-        self.list_active_tasks(task_mgr, true, true, false, false, false, false, vec![])?;
+        if self.config.sectioned_listing() && !self.plain {
+          self.list_sections(task_mgr)?;
+        } else {
+          self.list_active_tasks(
+            task_mgr,
+            true,
+            true,
+            true,
+            false,
+            false,
+            false,
+            false,
+            false,
+            false,
+            false,
+            false,
+            None,
+            1,
+            false,
+            false,
+            vec![],
+          )?;
+        }
+
+        self.hint_review(task_mgr);
       }
 
       Some(subcmd) => {
@@ -255,104 +1162,395 @@ where
             start,
             done,
             note: with_note,
-            content,
+            message,
+            force,
+            someday,
+            from_md,
+            from_email,
+            mut content,
           } => {
             if task_uid.is_none() {
-              let uid = self.add_task(task_mgr, start, done, content)?;
+              let mut body = String::new();
+
+              let uid = if let Some(path) = from_email {
+                let message = match email::Message::parse(&read_input(&path)?) {
+                  Some(message) => message,
+                  None => {
+                    println!("{}", tr!("no email found").red());
+                    return Ok(());
+                  }
+                };
+
+                let existing = message
+                  .message_id
+                  .as_deref()
+                  .and_then(|id| task_mgr.find_external_id(id));
+                if let Some(uid) = existing {
+                  println!(
+                    "{}",
+                    tr!("this email was already added as task {}", uid).yellow()
+                  );
+                  return Ok(());
+                }
+
+                body = match message.from {
+                  Some(from) => format!("From: {}\n\n{}", from, message.body),
+                  None => message.body,
+                };
+
+                // the subject is taken as-is, so that words such as #123 are not mistaken for metadata
+                let quick_add = QuickAdd {
+                  name: message
+                    .subject
+                    .unwrap_or_else(|| tr!("(no subject)").to_owned()),
+                  metadata: message
+                    .message_id
+                    .into_iter()
+                    .map(Metadata::external_id)
+                    .collect(),
+                  ..QuickAdd::default()
+                };
+                self.add_quick_add(task_mgr, quick_add, start, done, force, someday)?
+              } else {
+                if let Some(path) = from_md {
+                  let doc = match markdown::Document::parse(&read_input(&path)?)? {
+                    Some(doc) => doc,
+                    None => {
+                      println!("{}", tr!("no task found in the Markdown document").red());
+                      return Ok(());
+                    }
+                  };
+
+                  content = std::iter::once(doc.title).chain(doc.metadata).collect();
+                  body = doc.body;
+                }
+
+                self.add_task(task_mgr, start, done, force, someday, content)?
+              };
+
+              if !body.is_empty() {
+                if let Some(task) = uid.and_then(|uid| task_mgr.get_mut(uid)) {
+                  task.add_note(body);
+                  self.save(task_mgr)?;
+                }
+              }
+
+              let message = strip_note_comments(&message.join("\n\n"), "");
+              if !message.is_empty() {
+                if let Some(task) = uid.and_then(|uid| task_mgr.get_mut(uid)) {
+                  task.add_note(message);
+                  self.save(task_mgr)?;
+                }
+              }
 
               // TODO: rework this while refactoring
               if with_note {
-                if let Some(task) = task_mgr.get_mut(uid) {
-                  let note = interactively_edit_note(&self.config, false, &task, "")?;
+                if let Some((uid, task)) =
+                  uid.and_then(|uid| task_mgr.get_mut(uid).map(|task| (uid, task)))
+                {
+                  let note = self.write_note(uid, task, false)?;
                   task.add_note(note);
-                  task_mgr.save(&self.config)?;
+                  self.save(task_mgr)?;
+                  self.remove_draft(uid)?;
                 }
               }
             } else {
               println!(
-              "{}",
-              "cannot add a task to another one; maybe you were looking for dependencies instead?"
-                .red()
-            );
+                "{}",
+                tr!("cannot add a task to another one; maybe you were looking for dependencies instead?")
+                  .red()
+              );
             }
           }
 
           SubCommand::Edit { content } => {
-            if let Some(task) = task_uid.and_then(|uid| task_mgr.get_mut(uid)) {
-              Self::edit_task(task, content.iter().map(String::as_str))?;
-              task_mgr.save(&self.config)?;
+            if let Some(uid) = task_uid.filter(|&uid| task_mgr.get(uid).is_some()) {
+              self.edit_task(task_mgr, uid, content.iter().map(String::as_str))?;
+              self.save(task_mgr)?;
             } else {
-              println!("{}", "missing or unknown task to edit".red());
+              return Err(SubCmdError::MissingTask(tr!(
+                "missing or unknown task to edit"
+              )));
             }
           }
 
-          SubCommand::Show => {
-            if let Some((uid, task)) =
-              task_uid.and_then(|uid| task_mgr.get(uid).map(|task| (uid, task)))
-            {
-              self.show_task(uid, task);
-            } else {
-              println!("{}", "missing or unknown task to show".red());
+          SubCommand::Show { short, full } => {
+            if task_uids.is_empty() {
+              return Err(SubCmdError::MissingTask(tr!(
+                "missing or unknown task to show"
+              )));
+            }
+
+            // resolve all the tasks first, so that nothing is shown if one of them doesn’t exist
+            let tasks = task_uids
+              .iter()
+              .map(|&uid| {
+                task_mgr
+                  .get(uid)
+                  .map(|task| (uid, task))
+                  .ok_or(Error::UnknownTask(uid))
+              })
+              .collect::<Result<Vec<_>, _>>()?;
+
+            for (i, (uid, task)) in tasks.into_iter().enumerate() {
+              if i > 0 {
+                self.display_rule();
+              }
+
+              self.show_task(uid, task, short, full);
             }
           }
 
           SubCommand::Todo => {
             if let Some(task) = task_uid.and_then(|uid| task_mgr.get_mut(uid)) {
               task.change_status(Status::Todo);
-              task_mgr.save(&self.config)?;
+              self.save(task_mgr)?;
             } else {
-              println!("{}", "missing or unknown task".red());
+              return Err(SubCmdError::MissingTask(tr!(
+                "missing or unknown task to mark as todo"
+              )));
             }
           }
 
           SubCommand::Start => {
-            if let Some(task) = task_uid.and_then(|uid| task_mgr.get_mut(uid)) {
+            if let Some((uid, task)) =
+              task_uid.and_then(|uid| task_mgr.get_mut(uid).map(|task| (uid, task)))
+            {
               task.change_status(Status::Ongoing);
-              task_mgr.save(&self.config)?;
+              self.pause_other_tasks(task_mgr, uid);
+              self.save(task_mgr)?;
+            } else {
+              return Err(SubCmdError::MissingTask(tr!(
+                "missing or unknown task to start"
+              )));
+            }
+          }
+
+          SubCommand::Pause => {
+            if let Some(task) = task_uid.and_then(|uid| task_mgr.get_mut(uid)) {
+              task.change_status(Status::Paused);
+              self.save(task_mgr)?;
             } else {
-              println!("{}", "missing or unknown task to start".red());
+              return Err(SubCmdError::MissingTask(tr!(
+                "missing or unknown task to pause"
+              )));
             }
           }
 
           SubCommand::Done => {
             if let Some(task) = task_uid.and_then(|uid| task_mgr.get_mut(uid)) {
               task.change_status(Status::Done);
-              task_mgr.save(&self.config)?;
+
+              // recurring tasks come back
+              if let Some(next) = task.next_occurrence() {
+                let uid = task_mgr.register_task(next);
+                println!("{}", tr!("next occurrence is task {}", uid));
+              }
+
+              self.save(task_mgr)?;
             } else {
-              println!("{}", "missing or unknown task to finish".red());
+              return Err(SubCmdError::MissingTask(tr!(
+                "missing or unknown task to finish"
+              )));
             }
           }
 
           SubCommand::Cancel => {
             if let Some(task) = task_uid.and_then(|uid| task_mgr.get_mut(uid)) {
               task.change_status(Status::Cancelled);
-              task_mgr.save(&self.config)?;
+              self.save(task_mgr)?;
+            } else {
+              return Err(SubCmdError::MissingTask(tr!(
+                "missing or unknown task to cancel"
+              )));
+            }
+          }
+
+          SubCommand::Someday => {
+            if let Some((uid, task)) =
+              task_uid.and_then(|uid| task_mgr.get_mut(uid).map(|task| (uid, task)))
+            {
+              let someday = !task.is_someday();
+              task.set_someday(someday);
+              self.save(task_mgr)?;
+
+              if someday {
+                println!("{}", tr!("moved task {} to the someday backlog", uid));
+              } else {
+                println!("{}", tr!("moved task {} out of the someday backlog", uid));
+              }
+            } else {
+              return Err(SubCmdError::MissingTask(tr!(
+                "missing or unknown task to move"
+              )));
+            }
+          }
+
+          SubCommand::Pin | SubCommand::Unpin => {
+            let pinned = matches!(subcmd, SubCommand::Pin);
+
+            if let Some((uid, task)) =
+              task_uid.and_then(|uid| task_mgr.get_mut(uid).map(|task| (uid, task)))
+            {
+              task.set_pinned(pinned);
+              self.save(task_mgr)?;
+
+              if pinned {
+                println!("{}", tr!("pinned task {}", uid));
+              } else {
+                println!("{}", tr!("unpinned task {}", uid));
+              }
+            } else {
+              return Err(SubCmdError::MissingTask(tr!(
+                "missing or unknown task to pin"
+              )));
+            }
+          }
+
+          SubCommand::Review => {
+            self.review(task_mgr)?;
+          }
+
+          SubCommand::Roulette {
+            weighted,
+            case_insensitive,
+            metadata_filter,
+          } => {
+            self.roulette(task_mgr, weighted, case_insensitive, metadata_filter)?;
+          }
+
+          SubCommand::Triage {
+            case_insensitive,
+            metadata_filter,
+          } => {
+            self.triage(task_mgr, case_insensitive, metadata_filter)?;
+          }
+
+          SubCommand::Notify => {
+            self.notify(task_mgr)?;
+          }
+
+          SubCommand::Snooze { duration } => {
+            if let Some((uid, task)) =
+              task_uid.and_then(|uid| task_mgr.get_mut(uid).map(|task| (uid, task)))
+            {
+              let until = task.snooze(duration);
+              self.save(task_mgr)?;
+              println!(
+                "{}",
+                tr!(
+                  "snoozed task {} until {}",
+                  uid,
+                  Self::friendly_date_time(&until)
+                )
+              );
+            } else {
+              return Err(SubCmdError::MissingTask(tr!(
+                "missing or unknown task to snooze"
+              )));
+            }
+          }
+
+          SubCommand::Remove { all } => {
+            if all {
+              let count = task_mgr.tasks().count();
+              if !self.confirm(tr!("move all {} tasks to the trash?", count)) {
+                println!("{}", tr!("aborted").yellow());
+                return Ok(());
+              }
+
+              task_mgr.back_up("remove-all")?;
+              let count = task_mgr.remove_all_tasks();
+              self.save(task_mgr)?;
+              println!("{}", tr!("moved {} tasks to the trash", count));
+            } else if let Some(uid) = task_uid {
+              task_mgr.remove_task(uid)?;
+              self.save(task_mgr)?;
+              println!("{}", tr!("moved task {} to the trash", uid));
             } else {
-              println!("{}", "missing or unknown task to cancel".red());
+              return Err(SubCmdError::MissingTask(tr!(
+                "missing or unknown task to remove"
+              )));
             }
           }
 
-          SubCommand::Remove { .. } => {}
+          SubCommand::Trash(trash_cmd) => {
+            self.trash(task_mgr, trash_cmd)?;
+          }
 
           SubCommand::List {
             todo,
             start,
+            paused,
             done,
             cancelled,
             all,
+            snoozed,
+            someday,
+            summary_only,
+            interactive,
             case_insensitive,
+            fail_if_empty,
+            limit,
+            page,
+            no_header,
+            no_padding,
             metadata_filter,
           } => {
-            self.list_active_tasks(
+            let listed = self.list_active_tasks(
               task_mgr,
               todo,
               start,
+              paused,
               cancelled,
               done,
               all,
+              snoozed,
+              someday,
+              summary_only,
+              interactive,
               case_insensitive,
+              limit,
+              page,
+              no_header,
+              no_padding,
               metadata_filter,
             )?;
+
+            if fail_if_empty && listed == 0 {
+              return Err(SubCmdError::EmptyListing);
+            }
+          }
+
+          SubCommand::Query {
+            format,
+            all,
+            case_insensitive,
+            fail_if_empty,
+            metadata_filter,
+          } => {
+            let queried = self.query(task_mgr, &format, all, case_insensitive, metadata_filter)?;
+
+            if fail_if_empty && queried == 0 {
+              return Err(SubCmdError::EmptyListing);
+            }
+          }
+
+          SubCommand::Quick => {
+            let priorities = self.config.priorities();
+            let line = quick::prompt(tr!("new task ➤ "), |word| {
+              task_mgr.complete_metadata(priorities, word)
+            })
+            .map_err(SubCmdError::TerminalError)?;
+
+            if let Some(line) = line.filter(|line| !line.trim().is_empty()) {
+              let content = line.split_whitespace().map(str::to_owned).collect();
+              self.add_task(task_mgr, false, false, false, false, content)?;
+            }
+          }
+
+          SubCommand::Plan { capacity } => {
+            self.plan(task_mgr, capacity);
           }
 
           // TODO: simplify this pile of shit.
@@ -361,15 +1559,61 @@ where
               task_uid.and_then(|uid| task_mgr.get_mut(uid).map(|task| (uid, task)))
             {
               match subcmd {
-                NoteCommand::Add { no_history } => {
-                  let note = interactively_edit_note(
-                    &self.config,
-                    !no_history && self.config.previous_notes_help(),
-                    &task,
-                    "\n",
-                  )?;
-                  task.add_note(note);
-                  task_mgr.save(&self.config)?;
+                NoteCommand::List { full } => {
+                  let notes = task.notes();
+                  let thread = note_uid.map(|note_uid| usize::from(note_uid.dec()));
+
+                  match (note_uid, thread) {
+                    (Some(note_uid), Some(thread)) if thread >= notes.len() => {
+                      return Err(Error::UnknownNote(note_uid).into());
+                    }
+
+                    (None, _) if notes.is_empty() => {
+                      println!("{}", tr!("no note for this task").bright_black().italic());
+                    }
+
+                    _ => self.show_notes(&notes, thread, full),
+                  }
+                }
+
+                NoteCommand::Add {
+                  no_history,
+                  in_reply_to,
+                  stdin,
+                  message,
+                } => {
+                  // check the replied note before spawning the editor
+                  if let Some(note_uid) = in_reply_to {
+                    if usize::from(note_uid.dec()) >= task.notes().len() {
+                      return Err(Error::UnknownNote(note_uid).into());
+                    }
+                  }
+
+                  let interactive = !stdin && message.is_empty();
+                  let note = if stdin {
+                    // no comments out of an editor; only blank lines around the note are removed
+                    strip_note_comments(&read_input(Path::new("-"))?, "")
+                  } else if !message.is_empty() {
+                    strip_note_comments(&message.join("\n\n"), "")
+                  } else {
+                    self.write_note(uid, task, !no_history && self.config.previous_notes_help())?
+                  };
+
+                  if note.is_empty() {
+                    return Err(SubCmdError::EmptyNote);
+                  }
+
+                  if let Some(note_uid) = in_reply_to {
+                    task.reply_to_note(note_uid.dec(), note)?;
+                  } else {
+                    task.add_note(note);
+                  }
+
+                  self.save(task_mgr)?;
+
+                  if interactive {
+                    self.remove_draft(uid)?;
+                  }
                 }
 
                 NoteCommand::Edit { no_history } => {
@@ -386,34 +1630,151 @@ where
                     let note = interactively_edit_note(
                       &self.config,
                       !no_history && self.config.previous_notes_help(),
-                      &task,
+                      task,
                       prenote,
                     )?;
                     task.replace_note(note_uid, note)?;
-                    task_mgr.save(&self.config)?;
+                    self.save(task_mgr)?;
                   } else {
                     println!(
                       "{}",
-                      format!("cannot edit task {}’s note: no note UID provided", uid).red()
+                      tr!("cannot edit task {}’s note: no note UID provided", uid).red()
                     );
                   }
                 }
               }
             } else {
-              println!(
-                "{}",
-                "missing or unknown task to add, edit or list notes about".red()
-              );
+              return Err(SubCmdError::MissingTask(tr!(
+                "missing or unknown task to add, edit or list notes about"
+              )));
+            }
+          }
+
+          SubCommand::Check { item_uid, subcmd } => {
+            if let Some(task) = task_uid.and_then(|uid| task_mgr.get_mut(uid)) {
+              match (subcmd, item_uid) {
+                (Some(CheckCommand::Add { content }), _) => {
+                  let content = content.join(" ");
+
+                  if content.trim().is_empty() {
+                    println!("{}", tr!("cannot add an empty checklist item").red());
+                    return Ok(());
+                  }
+
+                  task.add_checklist_item(content);
+                }
+
+                (None, Some(item_uid)) => {
+                  if item_uid.val() == 0 {
+                    return Err(SubCmdError::NoChecklistItemZero);
+                  }
+
+                  task.toggle_checklist_item(item_uid.dec())?;
+                }
+
+                (None, None) => {
+                  self.show_checklist(task);
+                  return Ok(());
+                }
+              }
+
+              self.show_checklist(task);
+              self.save(task_mgr)?;
+            } else {
+              return Err(SubCmdError::MissingTask(tr!(
+                "missing or unknown task to check"
+              )));
             }
           }
 
+          SubCommand::Goal { goal_uid, subcmd } => match (task_uid, subcmd) {
+            (_, Some(GoalCommand::Add { name, due })) => {
+              let name = name.join(" ");
+
+              if name.trim().is_empty() {
+                println!("{}", tr!("cannot add a goal without a name").red());
+                return Ok(());
+              }
+
+              let goal_uid = task_mgr.add_goal(Goal::new(name, due));
+              self.save(task_mgr)?;
+              println!("{}", tr!("added goal {}", goal_uid));
+            }
+
+            (Some(uid), None) => {
+              let task = task_mgr.get(uid).ok_or(SubCmdError::MissingTask(tr!(
+                "missing or unknown task to link to a goal"
+              )))?;
+
+              match goal_uid {
+                Some(goal_uid) => {
+                  if task_mgr.goal(goal_uid).is_none() {
+                    return Err(Error::UnknownGoal(goal_uid).into());
+                  }
+
+                  if let Some(task) = task_mgr.get_mut(uid) {
+                    task.set_goal(goal_uid);
+                  }
+
+                  self.save(task_mgr)?;
+                  println!("{}", tr!("linked task {} to goal {}", uid, goal_uid));
+                }
+
+                None => match task.goal() {
+                  Some(goal_uid) => self.show_goal(task_mgr, goal_uid, false)?,
+                  None => println!(
+                    "{}",
+                    tr!("task {} isn’t linked to any goal", uid)
+                      .bright_black()
+                      .italic()
+                  ),
+                },
+              }
+            }
+
+            (None, None) => match goal_uid {
+              Some(goal_uid) => self.show_goal(task_mgr, goal_uid, true)?,
+              None => self.show_goals(task_mgr),
+            },
+          },
+
           SubCommand::History => {
             if let Some((uid, task)) =
               task_uid.and_then(|uid| task_mgr.get(uid).map(|task| (uid, task)))
             {
               self.show_task_history(uid, task);
             } else {
-              println!("{}", "missing or unknown task to display history".red());
+              return Err(SubCmdError::MissingTask(tr!(
+                "missing or unknown task to display history"
+              )));
+            }
+          }
+
+          SubCommand::Log { since, limit, page } => {
+            self.show_log(task_mgr, since, limit, page);
+          }
+
+          SubCommand::Time { subcmd } => {
+            if let Some(task) = task_uid.and_then(|uid| task_mgr.get_mut(uid)) {
+              let (duration, reason) = match subcmd {
+                Some(TimeCommand::Add { duration, reason }) => (duration, reason),
+                Some(TimeCommand::Remove { duration, reason }) => (-duration, reason),
+                None => {
+                  self.show_timesheet(task);
+                  return Ok(());
+                }
+              };
+
+              let reason = reason.join(" ");
+              let reason = Some(reason.trim().to_owned()).filter(|reason| !reason.is_empty());
+              task.adjust_spent_time(duration, reason);
+
+              self.show_timesheet(task);
+              self.save(task_mgr)?;
+            } else {
+              return Err(SubCmdError::MissingTask(tr!(
+                "missing or unknown task to adjust the spent time of"
+              )));
             }
           }
 
@@ -421,24 +1782,214 @@ where
             current_project,
             new_project,
           }) => {
+            let count = task_mgr
+              .tasks()
+              .filter(|(_, task)| {
+                task
+                  .project()
+                  .is_some_and(|project| metadata::is_in_project(project, &current_project, false))
+              })
+              .count();
+            if count != 0
+              && !self.confirm(tr!(
+                "rename project {} to {} in {} tasks?",
+                current_project,
+                new_project,
+                count
+              ))
+            {
+              println!("{}", tr!("aborted").yellow());
+              return Ok(());
+            }
+
             Self::rename_project(task_mgr, current_project, new_project);
-            task_mgr.save(&self.config)?;
+            self.save(task_mgr)?;
           }
-        }
-      }
-    }
 
-    Ok(())
-  }
+          SubCommand::Project(ProjectCommand::Tree { all }) => {
+            self.project_tree(task_mgr, all);
+          }
 
-  /// Extract metadata and print them (if any) on screen to help the user know what they are using.
-  fn extract_metadata(
-    metadata_filter: &[String],
-  ) -> Result<(Vec<Metadata>, String), MetadataValidationError> {
-    let (metadata, name) = Metadata::from_words(metadata_filter.iter().map(String::as_str));
+          SubCommand::Project(ProjectCommand::Show { project }) => {
+            self.show_project(task_mgr, &project);
+          }
+
+          SubCommand::Project(ProjectCommand::Milestones { project }) => {
+            self.project_milestones(task_mgr, &project);
+          }
+
+          SubCommand::Project(ProjectCommand::Normalize { apply }) => {
+            self.normalize_names(task_mgr, false, apply)?;
+          }
+
+          SubCommand::Tag(TagCommand::Normalize { apply }) => {
+            self.normalize_names(task_mgr, true, apply)?;
+          }
+
+          SubCommand::Context { subcmd } => {
+            self.context(task_mgr, subcmd)?;
+          }
+
+          #[cfg(not(feature = "post"))]
+          SubCommand::Digest { since, format } => {
+            self.digest(task_mgr, since, &format)?;
+          }
+
+          #[cfg(feature = "post")]
+          SubCommand::Digest {
+            since,
+            format,
+            post,
+          } => match post {
+            Some(chat) => self.post_digest(task_mgr, since, chat)?,
+            None => self.digest(task_mgr, since, &format)?,
+          },
+
+          SubCommand::Capture {
+            url,
+            force,
+            content,
+          } => {
+            self.capture(task_mgr, &url, force, content)?;
+          }
+
+          #[cfg(feature = "jira")]
+          SubCommand::Sync {
+            tracker: SyncCommand::Jira { dry_run },
+          } => {
+            self.sync_jira(task_mgr, dry_run)?;
+          }
+
+          #[cfg(not(feature = "qr"))]
+          SubCommand::Share {} => {
+            self.share(task_mgr, task_uid)?;
+          }
+
+          #[cfg(feature = "qr")]
+          SubCommand::Share { qr } => {
+            let link = self.share(task_mgr, task_uid)?;
+            if qr {
+              print_qr_code(&link);
+            }
+          }
+
+          SubCommand::Import {
+            format,
+            path,
+            payload,
+            allow_duplicates,
+          } => {
+            if let Some(payload) = payload {
+              let task = share::decode(&payload)?;
+              self.import_tasks(task_mgr, vec![task], allow_duplicates)?;
+            } else if let (Some(format), Some(path)) = (format, path) {
+              self.import(task_mgr, &format, path, allow_duplicates)?;
+            }
+          }
+
+          SubCommand::Export {
+            format,
+            list_formats,
+            output,
+          } => {
+            if list_formats {
+              self.list_formats();
+            } else if let Some(format) = format {
+              self.export(task_mgr, task_uid, &format, output)?;
+            }
+          }
+
+          SubCommand::Publish { output } => {
+            self.publish(task_mgr, output)?;
+          }
+
+          SubCommand::Gc { squash } => {
+            self.gc(task_mgr, squash)?;
+          }
+
+          SubCommand::Resolve { path, strategy } => {
+            self.resolve(task_mgr, &path, strategy)?;
+          }
+
+          SubCommand::Doctor => {
+            doctor::run(self.config.root_dir(), &self.term);
+          }
+
+          SubCommand::Config(ConfigCommand::Get { key, origin }) => {
+            self.config_get(&key, origin)?;
+          }
+
+          SubCommand::Rpc => {
+            rpc::serve(
+              &self.config,
+              task_mgr,
+              |task_mgr| self.save(task_mgr),
+              io::stdin().lock(),
+              io::stdout(),
+            )?;
+          }
+
+          SubCommand::Complete {
+            projects,
+            tags,
+            uids,
+            prefix,
+          } => {
+            // projects and tags are completed as metadata, without their operator
+            let operator = match (projects, tags, uids) {
+              (true, _, _) => Some("@"),
+              (_, true, _) => Some("#"),
+              _ => None,
+            };
+
+            let candidates = match operator {
+              Some(operator) => task_mgr
+                .complete_metadata(self.config.priorities(), &format!("{}{}", operator, prefix))
+                .into_iter()
+                .map(|candidate| candidate[operator.len()..].to_owned())
+                .collect(),
+
+              None => task_mgr
+                .tasks()
+                .map(|(&uid, _)| uid)
+                .sorted()
+                .map(|uid| uid.to_string())
+                .filter(|uid| uid.starts_with(&prefix))
+                .collect::<Vec<_>>(),
+            };
+
+            for candidate in candidates {
+              println!("{}", candidate);
+            }
+          }
+
+          SubCommand::ExitCodes => {
+            println!("{}", EXIT_CODES);
+          }
+        }
+      }
+    }
+
+    Ok(())
+  }
+
+  /// Extract metadata and print them (if any) on screen to help the user know what they are using.
+  fn extract_metadata(
+    priorities: &Priorities,
+    fields: &Fields,
+    normalization: &NormalizationConfig,
+    metadata_filter: &[String],
+    plain: bool,
+  ) -> Result<(Vec<Metadata>, String), MetadataValidationError> {
+    let (mut metadata, name) = Metadata::from_words(
+      priorities,
+      fields,
+      metadata_filter.iter().map(String::as_str),
+    );
+    normalize::metadata(normalization, &mut metadata);
     Metadata::validate(&metadata)?;
 
-    if !metadata.is_empty() {
+    if !plain && !metadata.is_empty() {
       print!(
         "{} {} {}",
         "[".bright_black(),
@@ -451,14 +2002,22 @@ where
   }
 
   /// Extract name filters and print them (if any) on screen to help the user know what they are using.
-  fn extract_name_filters<'a>(name: &'a str, case_insensitive: bool) -> TaskDescriptionFilter<'a> {
+  fn extract_name_filters<'a>(
+    name: &'a str,
+    case_insensitive: bool,
+    plain: bool,
+  ) -> TaskDescriptionFilter<'a> {
     let name_filter = TaskDescriptionFilter::new(name.split_ascii_whitespace(), case_insensitive);
 
+    if plain {
+      return name_filter;
+    }
+
     if !name_filter.is_empty() {
       println!(
         "{} {}: {} {}",
         "[".bright_black(),
-        "contains".italic(),
+        tr!("contains").italic(),
         name_filter.terms().format(", "),
         "]".bright_black()
       );
@@ -469,302 +2028,1099 @@ where
     name_filter
   }
 
-  /// List all tasks.
+  /// List all tasks, and return how many were listed.
   ///
   /// The various arguments allow to refine the listing.
+  #[allow(clippy::too_many_arguments)]
   pub fn list_tasks(
     &self,
     task_mgr: &TaskManager,
     todo: bool,
     start: bool,
+    paused: bool,
     cancelled: bool,
     done: bool,
+    snoozed: bool,
+    someday: Option<bool>,
+    summary_only: bool,
     case_insensitive: bool,
+    limit: Option<usize>,
+    page: usize,
+    no_header: bool,
+    no_padding: bool,
     metadata_filter: Vec<String>,
-  ) -> Result<(), SubCmdError> {
+  ) -> Result<usize, SubCmdError> {
+    // unpadded listings are meant for scripts, and thus are as quiet as plain ones
+    let quiet = self.plain || no_padding;
+    let tasks = self.filter_tasks(
+      task_mgr,
+      todo,
+      start,
+      paused,
+      cancelled,
+      done,
+      snoozed,
+      someday,
+      case_insensitive,
+      quiet,
+      metadata_filter,
+    )?;
+
+    if summary_only {
+      self.display_summary(&tasks);
+    } else {
+      let (shown, more) = Self::paginate(&tasks, limit, page);
+
+      if no_padding {
+        self.display_plain_listing(shown.iter().copied(), !no_header, " ");
+      } else {
+        self.display_listing(shown, !no_header);
+      }
+
+      if more > 0 && !quiet {
+        println!(
+          "{}",
+          tr!("… and {} more; see --page {}", more, page.max(1) + 1).bright_black()
+        );
+      }
+
+      if self.config.summary_footer() && !quiet && !tasks.is_empty() {
+        self.display_summary(&tasks);
+      }
+    }
+
+    Ok(tasks.len())
+  }
+
+  /// List the active tasks in sections — overdue, due soon, in progress and other tasks — each in its own table.
+  fn list_sections(&self, task_mgr: &TaskManager) -> Result<(), SubCmdError> {
+    let tasks = self.filter_tasks(
+      task_mgr,
+      true,
+      true,
+      true,
+      false,
+      false,
+      false,
+      Some(false),
+      false,
+      self.plain,
+      Vec::new(),
+    )?;
+    let now = Utc::now();
+    let horizon = Duration::days(self.config.due_soon_days().into());
+    let mut first = true;
+
+    for &section in ListingSection::ALL {
+      let section_tasks = tasks
+        .iter()
+        .copied()
+        .filter(|(_, task)| ListingSection::of(task, now, horizon) == section)
+        .collect::<Vec<_>>();
+
+      if section_tasks.is_empty() {
+        continue;
+      }
+
+      if !first {
+        println!();
+      }
+      first = false;
+
+      let count = format!("({})", section_tasks.len()).bright_black();
+      match section {
+        ListingSection::Overdue => println!("{} {}", section.title().red().bold(), count),
+        ListingSection::DueSoon => println!("{} {}", section.title().yellow().bold(), count),
+        _ => println!("{} {}", section.title().bold(), count),
+      }
+
+      self.display_tasks(&section_tasks);
+    }
+
+    if self.config.summary_footer() && !tasks.is_empty() {
+      self.display_summary(&tasks);
+    }
+
+    Ok(())
+  }
+
+  /// Page `page` of `tasks`, `limit` tasks per page, along with the number of tasks after that page.
+  ///
+  /// All the tasks are on the first page if there is no limit.
+  fn paginate<T>(tasks: &[T], limit: Option<usize>, page: usize) -> (&[T], usize) {
+    let limit = match limit {
+      Some(limit) => limit,
+      None => return (tasks, 0),
+    };
+    let start = limit.saturating_mul(page.max(1) - 1).min(tasks.len());
+    let end = start.saturating_add(limit).min(tasks.len());
+
+    (&tasks[start..end], tasks.len() - end)
+  }
+
+  /// Get the tasks to list, in listing order.
+  ///
+  /// The filter is printed first, unless `quiet` is set.
+  #[allow(clippy::too_many_arguments)]
+  fn filter_tasks<'a>(
+    &self,
+    task_mgr: &'a TaskManager,
+    todo: bool,
+    start: bool,
+    paused: bool,
+    cancelled: bool,
+    done: bool,
+    snoozed: bool,
+    someday: Option<bool>,
+    case_insensitive: bool,
+    quiet: bool,
+    mut metadata_filter: Vec<String>,
+  ) -> Result<Vec<(UID, &'a Task)>, SubCmdError> {
+    Self::add_context_filter(task_mgr, &mut metadata_filter);
+
     // extract metadata if any and build the name filter
-    let (metadata, name) = Self::extract_metadata(&metadata_filter)?;
+    let (metadata, name) = Self::extract_metadata(
+      self.config.priorities(),
+      self.config.fields(),
+      &self.config.normalization,
+      &metadata_filter,
+      quiet,
+    )?;
 
     // put an extra space between sections (metadata and name filter) if they are both present
-    if !metadata.is_empty() && !name.is_empty() {
+    if !quiet && !metadata.is_empty() && !name.is_empty() {
       print!(" ");
     }
 
-    let name_filter = Self::extract_name_filters(&name, case_insensitive);
+    let name_filter = Self::extract_name_filters(&name, case_insensitive, quiet);
 
     // get the filtered tasks
     let tasks = task_mgr.filtered_task_listing(
+      self.config.priorities(),
+      self.config.no_priority_order(),
       metadata,
       name_filter,
       todo,
       start,
+      paused,
       done,
       cancelled,
+      snoozed,
+      someday,
       case_insensitive,
     );
 
-    // precompute a bunch of data for display widths / padding / etc.
-    let display_opts = DisplayOptions::new(
-      &self.config,
-      &self.term,
-      tasks.iter().map(|&(uid, task)| (*uid, task)),
+    Ok(tasks.into_iter().map(|(&uid, task)| (uid, task)).collect())
+  }
+
+  /// Filter by the active context, unless another context is asked for.
+  fn add_context_filter(task_mgr: &TaskManager, metadata_filter: &mut Vec<String>) {
+    if let Some(context) = task_mgr.context() {
+      if !metadata_filter
+        .iter()
+        .flat_map(|s| s.split(' '))
+        .any(|word| word.starts_with("ctx:"))
+      {
+        metadata_filter.push(format!("ctx:{}", context));
+      }
+    }
+  }
+
+  /// Get the active tasks matching a filter, in listing order, without printing the filter.
+  ///
+  /// Ongoing tasks are left out unless `ongoing` is set; snoozed and someday tasks are always left out.
+  fn match_active_tasks<'a>(
+    &self,
+    task_mgr: &'a TaskManager,
+    ongoing: bool,
+    case_insensitive: bool,
+    mut metadata_filter: Vec<String>,
+  ) -> Result<Vec<(UID, &'a Task)>, SubCmdError> {
+    Self::add_context_filter(task_mgr, &mut metadata_filter);
+    let (metadata, name) = Self::extract_metadata(
+      self.config.priorities(),
+      self.config.fields(),
+      &self.config.normalization,
+      &metadata_filter,
+      true,
+    )?;
+    let name_filter = TaskDescriptionFilter::new(name.split_ascii_whitespace(), case_insensitive);
+
+    let tasks = task_mgr.filtered_task_listing(
+      self.config.priorities(),
+      self.config.no_priority_order(),
+      metadata,
+      name_filter,
+      true,
+      ongoing,
+      true,
+      false,
+      false,
+      false,
+      Some(false),
+      case_insensitive,
+    );
+
+    Ok(tasks.into_iter().map(|(&uid, task)| (uid, task)).collect())
+  }
+
+  /// Output the tasks matching a filter, formatted with a template, and return how many were output.
+  ///
+  /// Only the active tasks are queried, unless `all` is set. Nothing but the formatted tasks is output, so that the
+  /// output can be consumed by scripts.
+  fn query(
+    &self,
+    task_mgr: &TaskManager,
+    format: &str,
+    all: bool,
+    case_insensitive: bool,
+    mut metadata_filter: Vec<String>,
+  ) -> Result<usize, SubCmdError> {
+    let template = Template::parse(format, self.config.fields())?;
+
+    Self::add_context_filter(task_mgr, &mut metadata_filter);
+    let (metadata, name) = Self::extract_metadata(
+      self.config.priorities(),
+      self.config.fields(),
+      &self.config.normalization,
+      &metadata_filter,
+      true,
+    )?;
+    let name_filter = TaskDescriptionFilter::new(name.split_ascii_whitespace(), case_insensitive);
+
+    let tasks = task_mgr.filtered_task_listing(
+      self.config.priorities(),
+      self.config.no_priority_order(),
+      metadata,
+      name_filter,
+      true,
+      true,
+      true,
+      all,
+      all,
+      all,
+      if all { None } else { Some(false) },
+      case_insensitive,
     );
 
+    for &(&uid, task) in &tasks {
+      println!("{}", template.render(&self.config, uid, task));
+    }
+
+    Ok(tasks.len())
+  }
+
+  /// Display the totals of a list of tasks, along with the time spent on them this week.
+  fn display_summary(&self, tasks: &[(UID, &Task)]) {
+    let today = Local::today();
+    let week_start = (today - Duration::days(today.weekday().num_days_from_monday().into()))
+      .and_hms(0, 0, 0)
+      .with_timezone(&Utc);
+    let summary = Summary::new(tasks.iter().map(|&(_, task)| task), Utc::now(), week_start);
+
+    let mut totals = vec![tr!("{} todo", summary.todo), tr!("{} wip", summary.ongoing)];
+
+    if summary.paused > 0 {
+      totals.push(tr!("{} paused", summary.paused));
+    }
+
+    if summary.done > 0 {
+      totals.push(tr!("{} done", summary.done));
+    }
+
+    if summary.cancelled > 0 {
+      totals.push(tr!("{} cancelled", summary.cancelled));
+    }
+
+    let overdue = tr!("{} overdue", summary.overdue);
+    if summary.overdue > 0 {
+      totals.push(overdue.red().to_string());
+    } else {
+      totals.push(overdue);
+    }
+
+    totals.push(tr!(
+      "{} spent this week",
+      time::format_duration(summary.spent)
+    ));
+
+    if self.plain {
+      println!("{}", totals.join(", "));
+    } else {
+      println!("{}", totals.join(&" · ".bright_black().to_string()));
+    }
+  }
+
+  /// Display a list of tasks, along with a header.
+  fn display_tasks(&self, tasks: &[(UID, &Task)]) {
+    self.display_listing(tasks, true);
+  }
+
+  /// Display a list of tasks, along with a header if `header` is set.
+  fn display_listing(&self, tasks: &[(UID, &Task)], header: bool) {
+    if self.plain {
+      self.display_plain_listing(tasks.iter().copied(), header, "\t");
+      return;
+    }
+
+    // precompute a bunch of data for display widths / padding / etc.
+    let display_opts = DisplayOptions::new(&self.config, &self.term, tasks.iter().copied());
+
     // actual display
     // only display header if there are tasks to display
-    if !tasks.is_empty() {
-      self.display_task_header(&display_opts);
+    if tasks.is_empty() {
+      return;
+    }
+
+    let table = &self.config.colors.table;
+    if header {
+      if table.borders {
+        self.display_border(&display_opts);
+      }
+
+      let mut header = String::new();
+      // writing to a string cannot fail
+      let _ = self.display_task_header(&mut header, &display_opts);
+      print!("{}", header);
+
+      if table.borders {
+        self.display_border(&display_opts);
+      }
     }
 
-    for (&uid, task) in tasks {
-      self.display_task_inline(uid, task, &display_opts);
+    for (i, &(uid, task)) in tasks.iter().enumerate() {
+      let mut row = String::new();
+      // writing to a string cannot fail
+      let _ = self.display_task_inline(&mut row, uid, task, &display_opts);
+
+      match table.stripe {
+        Some(ref stripe) if i % 2 == 1 && colored::control::SHOULD_COLORIZE.should_colorize() => {
+          print!("{}", Self::stripe_row(&row, stripe.0.to_bg_str().as_ref()))
+        }
+        _ => print!("{}", row),
+      }
     }
 
-    Ok(())
+    if table.borders {
+      self.display_border(&display_opts);
+    }
+  }
+
+  /// Display a light horizontal border spanning the whole listing.
+  fn display_border(&self, opts: &DisplayOptions) {
+    let width = opts.description_offset
+      + opts
+        .max_description_cols
+        .map_or(0, |cols| opts.description_width.min(cols));
+
+    println!("{}", "─".repeat(width).bright_black());
+  }
+
+  /// Display a light horizontal rule spanning the terminal, separating tasks shown back to back.
+  fn display_rule(&self) {
+    let width = self.term.dimensions().map_or(80, |[width, _]| width);
+
+    if self.plain {
+      println!("{}", "-".repeat(width));
+    } else {
+      println!("{}", "─".repeat(width).bright_black());
+    }
   }
 
+  /// Apply a background color to all the lines of a row, including its already colored parts.
+  fn stripe_row(row: &str, background: &str) -> String {
+    let background = format!("\x1b[{}m", background);
+
+    row
+      .split_inclusive('\n')
+      .map(|line| {
+        let (line, newline) = match line.strip_suffix('\n') {
+          Some(line) => (line, "\n"),
+          None => (line, ""),
+        };
+
+        format!(
+          "{}{}\x1b[0m{}",
+          background,
+          line.replace("\x1b[0m", &format!("\x1b[0m{}", background)),
+          newline
+        )
+      })
+      .collect()
+  }
+
+  /// List the active tasks, unless asked for others, and return how many were listed.
+  #[allow(clippy::too_many_arguments)]
   pub fn list_active_tasks(
     &self,
-    task_mgr: &TaskManager,
+    task_mgr: &mut TaskManager,
     mut todo: bool,
     mut start: bool,
+    mut paused: bool,
     mut cancelled: bool,
     mut done: bool,
     all: bool,
+    snoozed: bool,
+    someday: bool,
+    summary_only: bool,
+    interactive: bool,
     case_insensitive: bool,
+    limit: Option<usize>,
+    page: usize,
+    no_header: bool,
+    no_padding: bool,
     metadata_filter: Vec<String>,
-  ) -> Result<(), SubCmdError> {
+  ) -> Result<usize, SubCmdError> {
     // handle filtering logic
     if all {
       todo = true;
       start = true;
+      paused = true;
       done = true;
       cancelled = true;
-    } else if !(todo || start || done || cancelled) {
-      // if nothing is set, we use “sensible” defaults by listing only “active” tasks (todo and ongoing)
+    } else if !(todo || start || paused || done || cancelled) {
+      // if nothing is set, we use “sensible” defaults by listing only “active” tasks (todo, ongoing and paused)
       todo = true;
       start = true;
+      paused = true;
+    }
+
+    let snoozed = all || snoozed;
+    let someday = if all { None } else { Some(someday) };
+
+    if interactive {
+      if self.plain || !self.term.is_tty() {
+        println!("{}", tr!("interactive listing requires a terminal").red());
+        return Ok(0);
+      }
+
+      let uids = self
+        .filter_tasks(
+          task_mgr,
+          todo,
+          start,
+          paused,
+          cancelled,
+          done,
+          snoozed,
+          someday,
+          case_insensitive,
+          self.plain,
+          metadata_filter,
+        )?
+        .into_iter()
+        .map(|(uid, _)| uid)
+        .collect::<Vec<_>>();
+
+      self.interactive_list(task_mgr, &uids)?;
+      return Ok(uids.len());
     }
 
     self.list_tasks(
       task_mgr,
       todo,
       start,
+      paused,
       cancelled,
       done,
+      snoozed,
+      someday,
+      summary_only,
       case_insensitive,
+      limit,
+      page,
+      no_header,
+      no_padding,
       metadata_filter,
     )
   }
 
+  /// List tasks interactively: rows are selected with the arrow keys and the selected task is acted upon with single
+  /// keys.
+  ///
+  /// Tasks stay in the listing when their status changes, so that a change can be reverted right away.
+  fn interactive_list(&self, task_mgr: &mut TaskManager, uids: &[UID]) -> Result<(), SubCmdError> {
+    if uids.is_empty() {
+      return Ok(());
+    }
+
+    let mut screen = Screen::enter().map_err(SubCmdError::TerminalError)?;
+    let mut selected = 0;
+    let mut top = 0;
+    let mut message = None;
+
+    loop {
+      let lines = {
+        let tasks = uids
+          .iter()
+          .filter_map(|&uid| task_mgr.get(uid).map(|task| (uid, task)))
+          .collect::<Vec<_>>();
+        let opts = DisplayOptions::new(&self.config, &self.term, tasks.iter().copied());
+
+        let mut header = String::new();
+        // writing to a string cannot fail
+        let _ = self.display_task_header(&mut header, &opts);
+
+        let rows = tasks
+          .iter()
+          .enumerate()
+          .map(|(i, &(uid, task))| {
+            let mut row = String::new();
+            let _ = self.display_task_inline(&mut row, uid, task, &opts);
+
+            // reverse video shows the selection even without colors
+            if i == selected {
+              Self::stripe_row(&row, "7")
+            } else {
+              row
+            }
+          })
+          .collect::<Vec<_>>();
+
+        // keep room for the header, the help line and the blank line above it
+        let heights = rows
+          .iter()
+          .map(|row| row.lines().count())
+          .collect::<Vec<_>>();
+        let room = screen.height().saturating_sub(header.lines().count() + 2);
+        top = interactive_list::scroll(&heights, selected, top, room);
+
+        let mut lines = header.lines().map(str::to_owned).collect::<Vec<_>>();
+        lines.extend(
+          rows[top..]
+            .iter()
+            .flat_map(|row| row.lines().map(str::to_owned))
+            .take(room),
+        );
+        lines.push(String::new());
+        lines.push(
+          message
+            .take()
+            .unwrap_or_else(|| interactive_list::HELP.bright_black().to_string()),
+        );
+        lines
+      };
+
+      screen
+        .draw(lines.iter().map(String::as_str))
+        .map_err(SubCmdError::TerminalError)?;
+
+      let uid = uids[selected];
+      match screen.next_action().map_err(SubCmdError::TerminalError)? {
+        Action::Up => selected = selected.saturating_sub(1),
+        Action::Down => selected = (selected + 1).min(uids.len() - 1),
+
+        Action::ChangeStatus(status) => {
+          match task_mgr.get_mut(uid) {
+            Some(task) if task.status() != status => {
+              task.change_status(status);
+
+              // recurring tasks come back
+              if let (Status::Done, Some(next)) = (status, task.next_occurrence()) {
+                let next_uid = task_mgr.register_task(next);
+                message = Some(tr!("next occurrence is task {}", next_uid));
+              }
+
+              if status == Status::Ongoing && self.config.single_wip() {
+                let paused = task_mgr.pause_ongoing_tasks(uid);
+
+                if !paused.is_empty() {
+                  message = Some(tr!("paused task {}", paused.iter().join(", ")));
+                }
+              }
+
+              self.save(task_mgr)?;
+            }
+
+            _ => (),
+          }
+        }
+
+        Action::AddNote => {
+          if let Some(task) = task_mgr.get_mut(uid) {
+            screen.suspend().map_err(SubCmdError::TerminalError)?;
+            let note = self.write_note(uid, task, self.config.previous_notes_help());
+            screen.resume().map_err(SubCmdError::TerminalError)?;
+
+            match note {
+              Ok(note) => {
+                task.add_note(note);
+                self.save(task_mgr)?;
+                self.remove_draft(uid)?;
+              }
+
+              Err(err @ SubCmdError::EmptyNote) => {
+                message = Some(err.to_string().red().to_string());
+              }
+
+              Err(err) => return Err(err),
+            }
+          }
+        }
+
+        Action::Show => {
+          if let Some(task) = task_mgr.get(uid) {
+            screen.suspend().map_err(SubCmdError::TerminalError)?;
+            self.show_task(uid, task, false, false);
+            print!(
+              "{}",
+              tr!("press enter to go back to the listing").bright_black()
+            );
+            let _ = io::stdout().flush();
+            self.term.read_line();
+            screen.resume().map_err(SubCmdError::TerminalError)?;
+          }
+        }
+
+        Action::Quit => return Ok(()),
+      }
+    }
+  }
+
   /// Display the header of tasks.
-  fn display_task_header(&self, opts: &DisplayOptions) {
-    print!(
-      " {uid:<uid_width$} {age:<age_width$}",
-      uid = self.config.uid_col_name().underline(),
-      uid_width = opts.task_uid_width,
-      age = self.config.age_col_name().underline(),
-      age_width = opts.age_width,
-    );
+  fn display_task_header(&self, out: &mut impl fmt::Write, opts: &DisplayOptions) -> fmt::Result {
+    if opts.has_pinned {
+      write!(out, " {:<width$}", "", width = PIN_GLYPH.width())?;
+    }
+
+    let alignment = self.config.column_alignment();
+
+    write!(
+      out,
+      " {} {}",
+      alignment
+        .uid
+        .pad(self.config.uid_col_name().underline(), opts.task_uid_width),
+      alignment
+        .age
+        .pad(self.config.age_col_name().underline(), opts.age_width),
+    )?;
+
+    if opts.activity_width != 0 {
+      write!(
+        out,
+        " {}",
+        alignment.age.pad(
+          self.config.activity_col_name().underline(),
+          opts.activity_width
+        ),
+      )?;
+    }
 
     let display_empty_cols = self.config.display_empty_cols();
 
     if display_empty_cols || opts.has_spent_time {
-      print!(
-        " {spent:<spent_width$}",
-        spent = self.config.spent_col_name().underline(),
-        spent_width = opts.spent_width,
-      );
+      write!(
+        out,
+        " {}",
+        alignment
+          .spent
+          .pad(self.config.spent_col_name().underline(), opts.spent_width),
+      )?;
     }
 
     if display_empty_cols || opts.has_priorities {
-      print!(
+      write!(
+        out,
         " {priority:<prio_width$}",
         priority = self.config.prio_col_name().underline(),
-        prio_width = self.config.prio_col_name().width(),
-      );
+        prio_width = opts.prio_width,
+      )?;
     }
 
     if display_empty_cols || opts.has_projects {
-      print!(
+      write!(
+        out,
         " {project:<project_width$}",
         project = self.config.project_col_name().underline(),
         project_width = opts.project_width,
-      );
+      )?;
     }
 
-    if self.config.display_tags_listings() && (display_empty_cols || opts.has_tags) {
-      print!(
+    if display_empty_cols || opts.has_contexts {
+      write!(
+        out,
+        " {context:<context_width$}",
+        context = self.config.context_col_name().underline(),
+        context_width = opts.context_width,
+      )?;
+    }
+
+    if display_empty_cols || opts.has_milestones {
+      write!(
+        out,
+        " {milestone:<milestone_width$}",
+        milestone = self.config.milestone_col_name().underline(),
+        milestone_width = opts.milestone_width,
+      )?;
+    }
+
+    if self.config.display_tags_listings() && (display_empty_cols || opts.has_tags) {
+      write!(
+        out,
         " {tags:<tags_width$}",
         tags = self.config.tags_col_name().underline(),
         tags_width = opts.tags_width,
-      );
+      )?;
+    }
+
+    for (name, width) in opts.field_columns(&self.config) {
+      write!(
+        out,
+        " {name:<width$}",
+        name = name.underline(),
+        width = width
+      )?;
     }
 
     let notes_nb_width = opts.notes_nb_width;
     if notes_nb_width != 0 {
-      print!(
-        " {notes_nb:<notes_nb_width$}",
-        notes_nb = self.config.notes_nb_col_name().underline(),
-        notes_nb_width = opts
-          .notes_nb_width
-          .max(self.config.notes_nb_col_name().len())
-      );
+      write!(
+        out,
+        " {}",
+        alignment.notes.pad(
+          self.config.notes_nb_col_name().underline(),
+          notes_nb_width.max(self.config.notes_nb_col_name().len())
+        ),
+      )?;
+    }
+
+    if opts.progress_width != 0 {
+      write!(
+        out,
+        " {progress:<progress_width$}",
+        progress = self.config.progress_col_name().underline(),
+        progress_width = opts.progress_width,
+      )?;
     }
 
     if let Some(max_description_cols) = opts.max_description_cols {
-      println!(
+      writeln!(
+        out,
         " {status:<status_width$} {description:<description_width$}",
         status = self.config.status_col_name().underline(),
         status_width = opts.status_width,
         description = self.config.description_col_name().underline(),
         description_width = opts.description_width.min(max_description_cols),
-      );
+      )?;
+    }
+
+    Ok(())
+  }
+
+  /// Display tasks in plain mode.
+  ///
+  /// All columns are always present and separated by tabulations, so that the output is stable and easy to parse.
+  fn display_plain_listing<'a>(
+    &self,
+    tasks: impl Iterator<Item = (UID, &'a Task)>,
+    header: bool,
+    separator: &str,
+  ) {
+    let mut tasks = tasks.peekable();
+
+    // only display header if there are tasks to display
+    if tasks.peek().is_none() {
+      return;
     }
+
+    if header {
+      self.display_plain_header(separator);
+    }
+
+    for (uid, task) in tasks {
+      println!("{}", self.plain_task_row(uid, task, separator));
+    }
+  }
+
+  /// Display the header of a plain listing, the column names being separated with `separator`.
+  fn display_plain_header(&self, separator: &str) {
+    let field_names = self
+      .config
+      .fields()
+      .defs()
+      .iter()
+      .map(|def| def.name.as_str());
+
+    let activity_name = if self.config.display_activity_listings() {
+      Some(self.config.activity_col_name())
+    } else {
+      None
+    };
+
+    println!(
+      "{}",
+      [self.config.uid_col_name(), self.config.age_col_name()]
+        .iter()
+        .copied()
+        .chain(activity_name)
+        .chain([
+          self.config.spent_col_name(),
+          self.config.prio_col_name(),
+          self.config.project_col_name(),
+          self.config.context_col_name(),
+          self.config.milestone_col_name(),
+          self.config.tags_col_name(),
+        ])
+        .chain(field_names)
+        .chain([
+          self.config.notes_nb_col_name(),
+          self.config.progress_col_name(),
+          self.config.status_col_name(),
+          self.config.description_col_name(),
+        ])
+        .join(separator)
+    );
+  }
+
+  /// Representation of a task in plain mode, the cells being separated with `separator`.
+  fn plain_task_row(&self, uid: UID, task: &Task, separator: &str) -> String {
+    let spent_time = task.spent_time();
+    let notes_nb = task.notes().len();
+
+    let fields = self.config.fields().defs().iter().map(|def| {
+      task
+        .field(&def.name)
+        .map(ToString::to_string)
+        .unwrap_or_default()
+    });
+
+    let activity = if self.config.display_activity_listings() {
+      Some(Self::friendly_duration(task.inactivity()))
+    } else {
+      None
+    };
+
+    vec![uid.to_string(), Self::friendly_duration(task.age())]
+      .into_iter()
+      .chain(activity)
+      .chain([
+        if spent_time == Duration::zero() {
+          String::new()
+        } else {
+          Self::friendly_duration(spent_time)
+        },
+        task
+          .priority()
+          .map(|prio| self.config.priorities().label(prio))
+          .unwrap_or_default(),
+        task.project().unwrap_or_default().to_owned(),
+        task.context().unwrap_or_default().to_owned(),
+        task.milestone().unwrap_or_default().to_owned(),
+        task.tags().join(","),
+      ])
+      .chain(fields)
+      .chain([
+        if notes_nb == 0 {
+          String::new()
+        } else {
+          notes_nb.to_string()
+        },
+        task
+          .checklist_progress()
+          .map(|(checked, total)| format!("[{}/{}]", checked, total))
+          .unwrap_or_default(),
+        self.status_alias(task.status()).to_owned(),
+        task.name().to_owned(),
+      ])
+      .join(separator)
   }
 
   /// Display a task to the user.
-  fn display_task_inline(&self, uid: UID, task: &Task, opts: &DisplayOptions) {
+  fn display_task_inline(
+    &self,
+    out: &mut impl fmt::Write,
+    uid: UID,
+    task: &Task,
+    opts: &DisplayOptions,
+  ) -> fmt::Result {
     let task_name = task.name();
     let status = task.status();
 
-    print!(
-      " {uid:<uid_width$} {age:<age_width$}",
-      uid = uid,
-      uid_width = opts.task_uid_width,
-      age = Self::friendly_task_age(task),
-      age_width = opts.age_width,
-    );
+    if opts.has_pinned {
+      if task.is_pinned() {
+        write!(out, " {}", PIN_GLYPH)?;
+      } else {
+        write!(out, " {:<width$}", "", width = PIN_GLYPH.width())?;
+      }
+    }
+
+    let alignment = self.config.column_alignment();
+
+    write!(
+      out,
+      " {} {}",
+      // only the UID is linked, not its padding
+      alignment.uid.pad(uid, opts.task_uid_width).replacen(
+        &uid.to_string(),
+        &self.task_link(uid, uid),
+        1
+      ),
+      self.listed_task_age(task, opts.age_width),
+    )?;
+
+    if opts.activity_width != 0 {
+      write!(
+        out,
+        " {}",
+        alignment.age.pad(
+          Self::friendly_duration(task.inactivity()),
+          opts.activity_width
+        ),
+      )?;
+    }
 
     let display_empty_cols = self.config.display_empty_cols();
 
     if display_empty_cols || opts.has_spent_time {
-      print!(
-        " {spent:<spent_width$}",
-        spent = Self::friendly_spent_time(task.spent_time(), status),
-        spent_width = opts.spent_width,
-      );
+      write!(
+        out,
+        " {}",
+        alignment.spent.pad(
+          Self::friendly_spent_time(task.spent_time(), status),
+          opts.spent_width
+        ),
+      )?;
     }
 
     if display_empty_cols || opts.has_priorities {
       if let Some(prio) = task.priority() {
-        print!(
+        write!(
+          out,
           " {priority:<prio_width$}",
           priority = self.friendly_priority(prio),
-          prio_width = self.config.prio_col_name().width(),
-        );
+          prio_width = opts.prio_width,
+        )?;
       } else {
-        print!(
+        write!(
+          out,
           " {prio:<prio_width$}",
           prio = "",
-          prio_width = self.config.prio_col_name().width(),
-        );
+          prio_width = opts.prio_width,
+        )?;
       }
     }
 
     if display_empty_cols || opts.has_projects {
-      print!(
+      write!(
+        out,
         " {project:<project_width$}",
-        project = Self::friendly_project(task.project().unwrap_or("")),
+        project = Self::friendly_project(DisplayOptions::project_cell(&self.config, task)),
         project_width = opts.project_width,
-      );
+      )?;
+    }
+
+    if display_empty_cols || opts.has_contexts {
+      write!(
+        out,
+        " {context:<context_width$}",
+        context = Self::friendly_context(task.context().unwrap_or("")),
+        context_width = opts.context_width,
+      )?;
+    }
+
+    if display_empty_cols || opts.has_milestones {
+      write!(
+        out,
+        " {milestone:<milestone_width$}",
+        milestone = Self::friendly_milestone(task.milestone().unwrap_or("")),
+        milestone_width = opts.milestone_width,
+      )?;
     }
 
     if self.config.display_tags_listings() && (display_empty_cols || opts.has_tags) {
-      Self::display_tags(task, opts);
+      self.display_tags(out, task, opts)?;
+    }
+
+    for (name, width) in opts.field_columns(&self.config) {
+      write!(
+        out,
+        " {value:<width$}",
+        value = task
+          .field(name)
+          .map(ToString::to_string)
+          .unwrap_or_default()
+          .cyan(),
+        width = width
+      )?;
     }
 
     let notes_nb_width = opts.notes_nb_width;
     let notes_nb = task.notes().len();
     if notes_nb_width != 0 {
-      print!(
-        " {notes_nb:<notes_nb_width$}",
-        notes_nb = Self::friendly_notes_nb(notes_nb),
-        notes_nb_width = opts
-          .notes_nb_width
-          .max(self.config.notes_nb_col_name().len())
-      );
+      write!(
+        out,
+        " {}",
+        alignment.notes.pad(
+          Self::friendly_notes_nb(notes_nb),
+          notes_nb_width.max(self.config.notes_nb_col_name().len())
+        ),
+      )?;
     }
 
-    print!(
+    if opts.progress_width != 0 {
+      write!(
+        out,
+        " {progress:<progress_width$}",
+        progress = Self::friendly_progress(task.checklist_progress()),
+        progress_width = opts.progress_width,
+      )?;
+    }
+
+    write!(
+      out,
       " {status:<status_width$}",
       status = self.highlight_status(status),
       status_width = opts.status_width,
-    );
+    )?;
 
-    self.display_description(opts, status, task_name);
+    self.display_description(out, opts, task, task_name)
   }
 
   /// Display the tags by respecting the allowed tags column size.
-  fn display_tags(task: &Task, opts: &DisplayOptions) {
-    print!(
+  fn display_tags(
+    &self,
+    out: &mut impl fmt::Write,
+    task: &Task,
+    opts: &DisplayOptions,
+  ) -> fmt::Result {
+    write!(
+      out,
       " {tags:<tags_width$}",
-      tags = Itertools::intersperse(task.tags(), ", ")
-        .collect::<String>()
-        .yellow(),
+      tags = DisplayOptions::tags_cell(&self.config, task).yellow(),
       tags_width = opts.tags_width,
-    );
+    )
   }
 
   /// Display a description by respecting the allowed description column size.
   ///
   /// The description is not displayed if no space is available on screen.
-  fn display_description(&self, opts: &DisplayOptions, status: Status, description: &str) {
+  fn display_description(
+    &self,
+    out: &mut impl fmt::Write,
+    opts: &DisplayOptions,
+    task: &Task,
+    description: &str,
+  ) -> fmt::Result {
     if let Some(max_description_cols) = opts.max_description_cols {
-      let mut line_index = 0; // line number we are currently at; cannot exceed config.max_description_lines()
-      let mut rel_offset = 0; // unicode offset in the current line; cannot exceed the description width
-      let mut line_buffer = String::new(); // buffer for the current line
       let description_width = opts.description_width.min(max_description_cols);
+      let lines = wrap(description, description_width, opts.max_description_lines);
 
-      // The algorithm is a bit convoluted, so here’s a bit of explanation. It’s an iterative algorithm that splits the
-      // description into an iterator over words. Each word has a unicode width, which is used to determine whether
-      // appending it to the buffer line will make it longer than the description width. The tricky part comes in with
-      // the fact that we want to display a ellipsis character if the next word is too long (…) and that we would end up
-      // on more line than required.
-      //
-      // Before adding a new word, we check that its size + 1 added to the current unicode offset is still smaller than
-      // the acceptable description width. If it is not the case, it means that adding this word would be out of sight,
-      // so it has to be put on another line. However, if we cannot add another line, we simply add “…” to the current
-      // line buffer and we are done. Otherwise, we just go to the next line, reset the offset and output the word. If we
-      // haven’t passed the end of the line, we simply output the word.
-      print!(" ");
-      for word in description.split_ascii_whitespace() {
-        let word_size = word.width() + 1; // TODO: check what to do about CJK
-
-        if rel_offset + word_size > description_width {
-          // we’ve passed the end of the line; break into another line
-          line_index += 1;
-
-          if line_index >= self.config.max_description_lines() {
-            // we reserve the last column for …
-            // we cannot create another line; add the ellipsis (…) character and stop
-            line_buffer.push('…');
-            break;
-          }
-
-          // we can create another line; display the line buffer first
-          let hl_description = self.highlight_description_line(status, &line_buffer);
-          println!("{:<width$}", hl_description, width = description_width);
-          print!("{:<width$}", "", width = opts.description_offset);
-
-          // reset the line buffer and the relative offset
-          line_buffer.clear();
-          line_buffer.push_str(word);
-          rel_offset = word_size;
-        } else {
-          // we still have room; simply add the word and go on
-          if rel_offset > 0 {
-            line_buffer.push(' ');
-          }
-
-          line_buffer.push_str(word);
-          rel_offset += word_size;
+      write!(out, " ")?;
+      for (i, line) in lines.iter().enumerate() {
+        if i > 0 {
+          write!(out, "{:<width$}", "", width = opts.description_offset)?;
         }
-      }
 
-      let hl_description = self.highlight_description_line(status, &line_buffer);
-      println!("{:<width$}", hl_description, width = description_width);
+        // pad before highlighting, so that the padding is highlighted too; padding is computed from the display width,
+        // as double-width characters would be misaligned otherwise
+        let padding = description_width.saturating_sub(text_width(line));
+        let padded = format!("{}{:<padding$}", line, "", padding = padding);
+        writeln!(out, "{}", self.highlight_description_line(task, &padded))?;
+      }
     }
+
+    Ok(())
   }
 
   /// Find out the age of a task and get a friendly representation.
@@ -774,22 +3130,24 @@ where
     Self::friendly_duration(dur)
   }
 
+  /// Age of a task padded to `width`, highlighted if the task has been lying around for a while.
+  fn listed_task_age(&self, task: &Task, width: usize) -> String {
+    let age = self
+      .config
+      .column_alignment()
+      .age
+      .pad(Self::friendly_task_age(task), width);
+
+    match self.config.colors.age.highlight_for(task) {
+      Some(highlight) => highlight.highlight(age).to_string(),
+      None => age,
+    }
+  }
+
   /// Friendly representation of duration.
   fn friendly_duration(dur: Duration) -> String {
-    if dur.num_minutes() < 1 {
-      format!("{}s", dur.num_seconds())
-    } else if dur.num_hours() < 1 {
-      format!("{}min", dur.num_minutes())
-    } else if dur.num_days() < 1 {
-      format!("{}h", dur.num_hours())
-    } else if dur.num_weeks() < 2 {
-      format!("{}d", dur.num_days())
-    } else if dur.num_weeks() < 4 {
-      // less than four weeks
-      format!("{}w", dur.num_weeks())
-    } else {
-      format!("{}mth", dur.num_weeks() / 4)
-    }
+    let (n, unit) = duration_in_unit(&dur);
+    format!("{}{}", n, unit)
   }
 
   /// String representation of a spent-time.
@@ -809,12 +3167,24 @@ where
   }
 
   /// Friendly representation of priorities.
-  fn friendly_priority(&self, prio: Priority) -> impl Display {
-    match prio {
-      Priority::Low => self.config.colors.priority.low.highlight("LOW"),
-      Priority::Medium => self.config.colors.priority.medium.highlight("MED"),
-      Priority::High => self.config.colors.priority.high.highlight("HIGH"),
-      Priority::Critical => self.config.colors.priority.critical.highlight("CRIT"),
+  ///
+  /// Configured levels are spread, by rank, over the low, medium, high and critical colors.
+  fn friendly_priority(&self, prio: &Priority) -> impl Display {
+    let priorities = self.config.priorities();
+    let name = priorities.label(prio);
+    let colors = &self.config.colors.priority;
+    let last = priorities.levels().len().saturating_sub(1);
+    let bucket = match priorities.rank(prio) {
+      Some(rank) if last > 0 => rank * 3 / last,
+      Some(_) => 3,
+      None => 0,
+    };
+
+    match bucket {
+      0 => colors.low.highlight(name),
+      1 => colors.medium.highlight(name),
+      2 => colors.high.highlight(name),
+      _ => colors.critical.highlight(name),
     }
   }
 
@@ -823,6 +3193,16 @@ where
     project.as_ref().italic()
   }
 
+  /// Friendly representation of a context name.
+  fn friendly_context(context: impl AsRef<str>) -> impl Display {
+    context.as_ref().blue()
+  }
+
+  /// Friendly representation of a milestone name.
+  fn friendly_milestone(milestone: impl AsRef<str>) -> impl Display {
+    milestone.as_ref().magenta()
+  }
+
   /// Friendly representation of a number of notes.
   fn friendly_notes_nb(nb: usize) -> impl Display {
     if nb != 0 {
@@ -832,6 +3212,43 @@ where
     }
   }
 
+  /// User-defined name of a status.
+  fn status_alias(&self, status: Status) -> &str {
+    match status {
+      Status::Todo => self.config.todo_alias(),
+      Status::Ongoing => self.config.wip_alias(),
+      Status::Done => self.config.done_alias(),
+      Status::Cancelled => self.config.cancelled_alias(),
+      Status::Paused => self.config.paused_alias(),
+    }
+  }
+
+  /// Friendly representation of the progress of a checklist.
+  fn friendly_progress(progress: Option<(usize, usize)>) -> impl Display {
+    match progress {
+      Some((checked, total)) => {
+        let output = format!("[{}/{}]", checked, total);
+
+        if checked == total {
+          output.green()
+        } else {
+          output.blue()
+        }
+      }
+
+      None => "".normal(),
+    }
+  }
+
+  /// Friendly representation of a checklist item.
+  fn friendly_checklist_item(nb: usize, item: &ChecklistItem) -> impl Display {
+    if item.checked {
+      format!("{}. [x] {}", nb, item.content).bright_black()
+    } else {
+      format!("{}. [ ] {}", nb, item.content).normal()
+    }
+  }
+
   /// Friendly representation of a status.
   fn highlight_status(&self, status: Status) -> impl Display {
     match status {
@@ -859,16 +3276,29 @@ where
         .status
         .cancelled
         .highlight(self.config.cancelled_alias()),
+      Status::Paused => self
+        .config
+        .colors
+        .status
+        .paused
+        .highlight(self.config.paused_alias()),
     }
   }
 
   /// Highlight a description line
-  fn highlight_description_line(&self, status: Status, line: &str) -> impl Display {
-    match status {
+  ///
+  /// The first color rule matching the task, if any, overrides the highlight of its status.
+  fn highlight_description_line(&self, task: &Task, line: &str) -> impl Display {
+    if let Some(rule) = self.config.colors.rule_for(task) {
+      return rule.highlight.highlight(line);
+    }
+
+    match task.status() {
       Status::Todo => self.config.colors.description.todo.highlight(line),
       Status::Ongoing => self.config.colors.description.ongoing.highlight(line),
       Status::Done => self.config.colors.description.done.highlight(line),
       Status::Cancelled => self.config.colors.description.cancelled.highlight(line),
+      Status::Paused => self.config.colors.description.paused.highlight(line),
     }
   }
 
@@ -883,16 +3313,46 @@ where
     task_mgr: &mut TaskManager,
     start: bool,
     done: bool,
+    force: bool,
+    someday: bool,
     content: Vec<String>,
-  ) -> Result<UID, SubCmdError> {
-    // validate the metadata extracted from the content, if any
-    let (metadata, name) = Metadata::from_words(content.iter().map(|s| s.as_str()));
-    Metadata::validate(&metadata)?;
+  ) -> Result<Option<UID>, SubCmdError> {
+    // extract metadata and natural-language phrases from the content, and validate the metadata, if any
+    let quick_add = nlp::parse(
+      self.config.priorities(),
+      self.config.fields(),
+      content.iter().map(|s| s.as_str()),
+      Local::today().naive_local(),
+    );
 
-    let mut task = Task::new(name);
+    self.add_quick_add(task_mgr, quick_add, start, done, force, someday)
+  }
 
-    // apply the metadata
-    task.apply_metadata(metadata);
+  /// Add the task described by already parsed content.
+  fn add_quick_add(
+    &mut self,
+    task_mgr: &mut TaskManager,
+    mut quick_add: QuickAdd,
+    start: bool,
+    done: bool,
+    force: bool,
+    someday: bool,
+  ) -> Result<Option<UID>, SubCmdError> {
+    normalize::metadata(&self.config.normalization, &mut quick_add.metadata);
+    Metadata::validate(&quick_add.metadata)?;
+    self.correct_metadata_typos(task_mgr, &mut quick_add.metadata);
+
+    let mut task = quick_add.into_task();
+    self.check_policies(&task)?;
+
+    if !force && !self.confirm_not_duplicate(task_mgr, task.name())? {
+      println!("{}", tr!("aborted").yellow());
+      return Ok(None);
+    }
+
+    if someday {
+      task.set_someday(true);
+    }
 
     // determine if we need to switch to another status
     if start {
@@ -902,39 +3362,107 @@ where
     }
 
     let uid = task_mgr.register_task(task.clone());
-    task_mgr.save(&self.config)?;
 
-    // display options
-    let display_opts = DisplayOptions::new(&self.config, &self.term, once((uid, &task)));
+    if start {
+      self.pause_other_tasks(task_mgr, uid);
+    }
+
+    self.save(task_mgr)?;
+
+    self.display_tasks(&[(uid, &task)]);
+
+    Ok(Some(uid))
+  }
+
+  /// Pause the ongoing tasks other than `uid`, if a single ongoing task is allowed.
+  fn pause_other_tasks(&self, task_mgr: &mut TaskManager, uid: UID) {
+    if !self.config.single_wip() {
+      return;
+    }
+
+    for paused in task_mgr.pause_ongoing_tasks(uid) {
+      println!("{}", tr!("paused task {}", paused));
+    }
+  }
+
+  /// Warn about active tasks similar to a new one named `name`, and ask whether to add it anyway.
+  ///
+  /// Only `--force` skips this check: if no one can answer, whether because of `--yes` or a lack of input, the task is
+  /// not added and this fails.
+  fn confirm_not_duplicate(&self, task_mgr: &TaskManager, name: &str) -> Result<bool, SubCmdError> {
+    let similar = task_mgr.similar_active_tasks(name, self.config.duplicate_threshold());
+
+    let most_similar = match similar.first() {
+      Some(&(uid, _, _)) => uid,
+      None => return Ok(true),
+    };
+
+    if !self.confirm {
+      return Err(SubCmdError::Duplicate(most_similar));
+    }
 
-    self.display_task_header(&display_opts);
-    self.display_task_inline(uid, &task, &display_opts);
+    println!("{}", tr!("this task looks like a duplicate of:").yellow());
+    for (uid, task, similarity) in similar {
+      println!(
+        "  {} {} {}",
+        uid.to_string().bold(),
+        task.name(),
+        tr!("({}% similar)", (similarity * 100.).round()).bright_black()
+      );
+    }
 
-    Ok(uid)
+    self
+      .ask(tr!("add it anyway?"))
+      .ok_or(SubCmdError::Duplicate(most_similar))
   }
 
   /// Edit a task’s name or metadata.
   pub fn edit_task<'a>(
-    task: &mut Task,
+    &self,
+    task_mgr: &mut TaskManager,
+    uid: UID,
     content: impl IntoIterator<Item = &'a str>,
   ) -> Result<(), SubCmdError> {
     // validate the metadata extracted from the content, if any
-    let (metadata, name) = Metadata::from_words(content);
+    let (mut metadata, name) =
+      Metadata::from_words(self.config.priorities(), self.config.fields(), content);
+    normalize::metadata(&self.config.normalization, &mut metadata);
     Metadata::validate(&metadata)?;
+    self.correct_metadata_typos(task_mgr, &mut metadata);
+
+    let task = task_mgr.get_mut(uid).ok_or(Error::UnknownTask(uid))?;
+
+    // edit a copy, so that the task is left untouched if it doesn’t comply with the policies anymore
+    let mut edited = task.clone();
 
     // apply the metadata
-    task.apply_metadata(metadata);
+    edited.apply_metadata(metadata);
 
     // if we have a new name, apply it too
     if !name.is_empty() {
-      task.change_name(name);
+      edited.change_name(name);
     }
 
+    self.check_policies(&edited)?;
+    *task = edited;
+
     Ok(())
   }
 
+  /// Check that `task` complies with the policies of the configuration.
+  fn check_policies(&self, task: &Task) -> Result<(), PolicyViolation> {
+    policy::check(
+      &self.config.policies,
+      self.config.priorities(),
+      self.config.fields(),
+      task,
+    )
+  }
+
   /// Show a task.
-  pub fn show_task(&self, uid: UID, task: &Task) {
+  ///
+  /// If `short` is set, only the number of notes is shown, not the notes themselves.
+  pub fn show_task(&self, uid: UID, task: &Task, short: bool, full: bool) {
     let header_hl = &self.config.colors.show_header;
     let status = task.status();
 
@@ -946,20 +3474,25 @@ where
     println!(
       " {}: {}",
       header_hl.highlight(self.config.uid_col_name()),
-      uid
+      self.task_link(uid, uid)
     );
     println!(
       " {}: {}",
       header_hl.highlight(self.config.age_col_name()),
       Self::friendly_task_age(task)
     );
+    println!(
+      " {}: {}",
+      header_hl.highlight(self.config.activity_col_name()),
+      Self::friendly_duration(task.inactivity())
+    );
 
     let spent_time = task.spent_time();
     if spent_time == Duration::zero() {
       println!(
         " {}: {}",
         header_hl.highlight(self.config.spent_col_name()),
-        "not started yet".bright_black().italic()
+        tr!("not started yet").bright_black().italic()
       );
     } else {
       println!(
@@ -985,12 +3518,104 @@ where
       );
     }
 
+    if let Some(context) = task.context() {
+      println!(
+        " {}: {}",
+        header_hl.highlight(self.config.context_col_name()),
+        Self::friendly_context(context)
+      );
+    }
+
+    if let Some(milestone) = task.milestone() {
+      println!(
+        " {}: {}",
+        header_hl.highlight(self.config.milestone_col_name()),
+        Self::friendly_milestone(milestone)
+      );
+    }
+
+    if let Some(scheduled) = task.scheduled() {
+      println!(
+        " {}: {}",
+        header_hl.highlight(tr!("Scheduled")),
+        Self::friendly_date_time(&scheduled)
+      );
+    }
+
+    if let Some(deadline) = task.deadline() {
+      println!(
+        " {}: {}",
+        header_hl.highlight(tr!("Deadline")),
+        Self::friendly_date_time(&deadline)
+      );
+    }
+
+    if let Some(recurrence) = task.recurrence() {
+      println!(
+        " {}: {}",
+        header_hl.highlight(tr!("Recurrence")),
+        recurrence
+      );
+    }
+
+    if task.is_someday() {
+      println!(" {}: {}", header_hl.highlight(tr!("Someday")), tr!("yes"));
+    }
+
+    if task.is_pinned() {
+      println!(" {}: {}", header_hl.highlight(tr!("Pinned")), tr!("yes"));
+    }
+
+    if let Some(external_id) = task.external_id() {
+      println!(
+        " {}: {}",
+        header_hl.highlight(tr!("External ID")),
+        self.linkify(external_id)
+      );
+    }
+
+    for (name, value) in task.fields() {
+      println!(
+        " {}: {}",
+        header_hl.highlight(name),
+        self.linkify(&value.to_string())
+      );
+    }
+
+    if let Some(until) = task.snoozed_until().filter(|&until| until > Utc::now()) {
+      println!(
+        " {}: {}",
+        header_hl.highlight(tr!("Snoozed until")),
+        Self::friendly_date_time(&until)
+      );
+    }
+
+    if let Some(effort) = task.effort() {
+      println!(
+        " {}: {}",
+        header_hl.highlight(tr!("Effort")),
+        time::format_duration(effort)
+      );
+    }
+
+    for reminder in task
+      .reminders()
+      .iter()
+      .filter(|reminder| reminder.delivered.is_none())
+    {
+      println!(
+        " {}: {}",
+        header_hl.highlight(tr!("Reminder")),
+        Self::friendly_date_time(&reminder.date)
+      );
+    }
+
     let mut tags = task.tags();
 
     if let Some(first_tag) = tags.next() {
       let hash = "#".bright_black();
 
-      print!(" {}: ", header_hl.highlight("Tags"));
+      print!(" {}: ", header_hl.highlight(tr!("Tags")));
       print!("{}{}", hash, first_tag.yellow());
 
       for tag in tags {
@@ -1006,97 +3631,1910 @@ where
       self.highlight_status(status)
     );
 
+    if let Some(progress) = task.checklist_progress() {
+      println!(
+        " {}: {}",
+        header_hl.highlight(self.config.progress_col_name()),
+        Self::friendly_progress(Some(progress))
+      );
+      println!();
+      self.show_checklist(task);
+    }
+
+    let notes = task.notes();
+    if short {
+      if !notes.is_empty() {
+        println!(
+          " {}: {}",
+          header_hl.highlight(self.config.notes_nb_col_name()),
+          notes.len()
+        );
+      }
+
+      return;
+    }
+
     println!();
+    self.show_notes(&notes, None, full);
+  }
+
+  /// Show notes, replies indented under the note they reply to.
+  ///
+  /// If `thread` is set, only the note at this index and its replies are shown. Notes longer than the configured number
+  /// of lines are collapsed, unless `full` is set.
+  fn show_notes(&self, notes: &[Note], thread: Option<usize>, full: bool) {
+    let max_lines = self.config.collapsed_note_lines();
+    let mut threads = Note::threads(notes);
+
+    if let Some(thread) = thread {
+      let start = threads
+        .iter()
+        .position(|&(nb, _)| nb == thread)
+        .unwrap_or_default();
+      let depth = threads[start].1;
+      let end = threads[start + 1..]
+        .iter()
+        .position(|&(_, d)| d <= depth)
+        .map_or(threads.len(), |end| start + 1 + end);
+      threads = threads[start..end]
+        .iter()
+        .map(|&(nb, d)| (nb, d - depth))
+        .collect();
+    }
+
+    for (nb, depth) in threads {
+      let note = &notes[nb];
+      let indent = "  ".repeat(depth);
 
-    // show the notes
-    for (nb, note) in task.notes().into_iter().enumerate() {
       print!(
-        "{}{}{}{}",
-        " Note #".bright_black().italic(),
+        "{}{}{}{}{}",
+        indent,
+        tr!(" Note #").bright_black().italic(),
         (nb + 1).to_string().blue().italic(),
-        ", on ".bright_black().italic(),
+        tr!(", on ").bright_black().italic(),
         Self::friendly_date_time(&note.creation_date)
       );
 
+      if let Some(ref author) = note.author {
+        print!("{}{}", tr!(", by ").bright_black().italic(), author.cyan());
+      }
+
       if note.last_modification_date != note.creation_date {
         print!(
           "{}{}",
-          ", edited on ".bright_black().italic(),
+          tr!(", edited on ").bright_black().italic(),
           Self::friendly_date_time(&note.last_modification_date)
         );
       }
-      println!();
 
-      println!("{}", note.content.trim());
-      println!();
-    }
-  }
+      let lines = note.lines();
+      println!(
+        "{}",
+        tr!(" · {} words, {} lines", note.words(), lines)
+          .bright_black()
+          .italic()
+      );
 
-  pub fn show_task_history(&self, uid: UID, task: &Task) {
-    for event in task.history() {
-      // Extract event date from all variants
-      match event {
-        Event::Created(event_date)
-        | Event::StatusChanged { event_date, .. }
-        | Event::NoteAdded { event_date, .. }
-        | Event::NoteReplaced { event_date, .. }
-        | Event::SetProject { event_date, .. }
-        | Event::SetPriority { event_date, .. }
-        | Event::AddTag { event_date, .. } => {
-          print!("{}: ", Self::friendly_date_time(event_date));
+      let shown = if full || max_lines == 0 {
+        lines
+      } else {
+        lines.min(max_lines)
+      };
+
+      for line in note.content.trim().lines().take(shown) {
+        println!("{}{}", indent, self.linkify(line));
+      }
+
+      if shown < lines {
+        println!(
+          "{}{}",
+          indent,
+          tr!("… {} more lines; use --full to show them", lines - shown)
+            .bright_black()
+            .italic()
+        );
+      }
+      println!();
+    }
+  }
+
+  /// Show the checklist of a task.
+  pub fn show_checklist(&self, task: &Task) {
+    let checklist = task.checklist();
+
+    if checklist.is_empty() {
+      println!(
+        "{}",
+        tr!("no checklist for this task").bright_black().italic()
+      );
+    }
+
+    for (nb, item) in checklist.iter().enumerate() {
+      println!(" {}", Self::friendly_checklist_item(nb + 1, item));
+    }
+  }
+
+  pub fn show_task_history(&self, uid: UID, task: &Task) {
+    for (event, author) in task.authored_history() {
+      self.show_authored_event(uid, event, author);
+    }
+  }
+
+  /// Show an event of the task `uid`, along with its date and author, if known.
+  fn show_authored_event(&self, uid: UID, event: &Event, author: Option<&str>) {
+    print!("{}", Self::friendly_date_time(event.date()));
+
+    if let Some(author) = author {
+      print!(" ({})", author.cyan());
+    }
+
+    print!(": ");
+    self.show_event(uid, event);
+  }
+
+  /// Show the progress of all the goals.
+  fn show_goals(&self, task_mgr: &TaskManager) {
+    let mut goals = task_mgr.goals().peekable();
+
+    if goals.peek().is_none() && !self.plain {
+      println!(
+        "{}",
+        tr!("no goal yet; add one with td goal add")
+          .bright_black()
+          .italic()
+      );
+    }
+
+    for (uid, goal) in goals {
+      self.show_goal_progress(task_mgr, uid, goal);
+    }
+  }
+
+  /// Show the progress of a goal, along with its tasks if `with_tasks` is set.
+  fn show_goal(
+    &self,
+    task_mgr: &TaskManager,
+    uid: UID,
+    with_tasks: bool,
+  ) -> Result<(), SubCmdError> {
+    let goal = task_mgr.goal(uid).ok_or(Error::UnknownGoal(uid))?;
+    self.show_goal_progress(task_mgr, uid, goal);
+
+    if with_tasks {
+      self.display_tasks(&task_mgr.goal_tasks(uid));
+    }
+
+    Ok(())
+  }
+
+  /// Show the progress of a goal on a single line; tab-separated in plain mode.
+  fn show_goal_progress(&self, task_mgr: &TaskManager, uid: UID, goal: &Goal) {
+    let progress = task_mgr.goal_progress(uid);
+    let due = goal.due().map(|due| due.format("%Y-%m-%d").to_string());
+
+    if self.plain {
+      println!(
+        "{}\t{}\t{}\t{}\t{}",
+        uid,
+        progress.done,
+        progress.total,
+        due.unwrap_or_default(),
+        goal.name()
+      );
+      return;
+    }
+
+    let ratio = format!("{}/{}", progress.done, progress.total);
+    let ratio = if progress.total > 0 && progress.done == progress.total {
+      ratio.green()
+    } else {
+      ratio.blue()
+    };
+    print!(" {} {} {}", uid.to_string().bold(), ratio, goal.name());
+
+    if let Some(due) = goal.due() {
+      let overdue = due < Local::today().naive_local() && progress.done < progress.total;
+      let due = tr!("(due {})", due.format("%Y-%m-%d"));
+      print!(
+        " {}",
+        if overdue {
+          due.red()
+        } else {
+          due.bright_black()
+        }
+      );
+    }
+
+    println!();
+  }
+
+  /// Show a page of the events of all the tasks that occurred during the last `since`, the most recent first.
+  fn show_log(&self, task_mgr: &TaskManager, since: Duration, limit: usize, page: usize) {
+    let events = task_mgr.events_since(Utc::now() - since);
+    let skipped = limit * (page.max(1) - 1);
+
+    if events.is_empty() && !self.plain {
+      println!(
+        "{}",
+        tr!("nothing happened since {}", time::format_duration(since))
+          .bright_black()
+          .italic()
+      );
+    }
+
+    for &(uid, task, event, author) in events.iter().skip(skipped).take(limit) {
+      print!("{}", Self::friendly_date_time(event.date()));
+
+      if let Some(author) = author {
+        print!(" ({})", author.cyan());
+      }
+
+      print!(": {} {}: ", uid.to_string().bold(), task.name());
+      self.show_event(uid, event);
+    }
+
+    let more = events.len().saturating_sub(skipped + limit);
+    if more > 0 && !self.plain {
+      println!(
+        "{}",
+        tr!("… and {} more; see --page {}", more, page.max(1) + 1).bright_black()
+      );
+    }
+  }
+
+  /// Show what an event of task `uid` changed, on a single line.
+  fn show_event(&self, uid: UID, event: &Event) {
+    match event {
+      Event::Created(_) => {
+        println!("{} {}", tr!("Task created with uid").bright_black(), uid);
+      }
+
+      Event::StatusChanged { status, .. } => {
+        println!(
+          "{} {}",
+          tr!("Status changed to").bright_black(),
+          self.highlight_status(*status)
+        );
+      }
+
+      Event::NoteAdded {
+        content,
+        in_reply_to: None,
+        ..
+      } => {
+        println!("{} {}", tr!("Note added").bright_black(), content);
+      }
+
+      Event::NoteAdded {
+        content,
+        in_reply_to: Some(note_uid),
+        ..
+      } => {
+        println!(
+          "{} {} {} {}",
+          tr!("Reply to note").bright_black(),
+          (note_uid.val() + 1).to_string().blue(),
+          tr!("added").bright_black(),
+          content
+        );
+      }
+
+      Event::NoteReplaced {
+        content, note_uid, ..
+      } => {
+        println!(
+          "{} {} {} {}",
+          tr!("Note").bright_black(),
+          note_uid.to_string().blue(),
+          tr!("updated").bright_black(),
+          content
+        );
+      }
+
+      Event::SetProject { project, .. } => {
+        println!(
+          "{} {}",
+          tr!("Project set to").bright_black(),
+          Self::friendly_project(project)
+        );
+      }
+
+      Event::SetContext { context, .. } => {
+        println!(
+          "{} {}",
+          tr!("Context set to").bright_black(),
+          Self::friendly_context(context)
+        );
+      }
+
+      Event::SetMilestone { milestone, .. } => {
+        println!(
+          "{} {}",
+          tr!("Milestone set to").bright_black(),
+          Self::friendly_milestone(milestone)
+        );
+      }
+
+      Event::SetPriority { priority, .. } => {
+        println!(
+          "{} {}",
+          tr!("Priority set to").bright_black(),
+          self.friendly_priority(priority)
+        );
+      }
+
+      Event::AddTag { tag, .. } => {
+        println!("{}{}", tr!("Tag added #").bright_black(), tag.yellow());
+      }
+
+      Event::RenameTag { tag, new_tag, .. } => {
+        println!(
+          "{}{} {} #{}",
+          tr!("Tag renamed #").bright_black(),
+          tag.yellow(),
+          "→".bright_black(),
+          new_tag.yellow()
+        );
+      }
+
+      Event::Removed { .. } => {
+        println!("{}", tr!("Task moved to the trash").bright_black());
+      }
+
+      Event::Restored { .. } => {
+        println!("{}", tr!("Task restored from the trash").bright_black());
+      }
+
+      Event::SetScheduled { scheduled, .. } => {
+        println!(
+          "{} {}",
+          tr!("Scheduled on").bright_black(),
+          Self::friendly_date_time(scheduled)
+        );
+      }
+
+      Event::SetDeadline { deadline, .. } => {
+        println!(
+          "{} {}",
+          tr!("Deadline set to").bright_black(),
+          Self::friendly_date_time(deadline)
+        );
+      }
+
+      Event::SetRecurrence { recurrence, .. } => {
+        println!("{} {}", tr!("Recurring").bright_black(), recurrence);
+      }
+
+      Event::SetSomeday { someday, .. } => {
+        if *someday {
+          println!("{}", tr!("Moved to the someday backlog").bright_black());
+        } else {
+          println!("{}", tr!("Moved out of the someday backlog").bright_black());
+        }
+      }
+
+      Event::SetPinned { pinned, .. } => {
+        if *pinned {
+          println!("{}", tr!("Pinned").bright_black());
+        } else {
+          println!("{}", tr!("Unpinned").bright_black());
+        }
+      }
+
+      Event::SetGoal { goal, .. } => {
+        println!("{} {}", tr!("Linked to goal").bright_black(), goal);
+      }
+
+      Event::Reviewed { .. } => {
+        println!("{}", tr!("Reviewed").bright_black());
+      }
+
+      Event::SetExternalId { external_id, .. } => {
+        println!(
+          "{} {}",
+          tr!("External ID set to").bright_black(),
+          external_id
+        );
+      }
+
+      Event::Snoozed { until, .. } => {
+        println!(
+          "{} {}",
+          tr!("Snoozed until").bright_black(),
+          Self::friendly_date_time(until)
+        );
+      }
+
+      Event::SetEffort { minutes, .. } => {
+        println!(
+          "{} {}",
+          tr!("Effort estimated to").bright_black(),
+          time::format_duration(Duration::minutes((*minutes).into()))
+        );
+      }
+
+      Event::ReminderAdded { date, .. } => {
+        println!(
+          "{} {}",
+          tr!("Reminder set for").bright_black(),
+          Self::friendly_date_time(date)
+        );
+      }
+
+      Event::ReminderDelivered { reminder_uid, .. } => {
+        println!(
+          "{} {} {}",
+          tr!("Reminder").bright_black(),
+          (reminder_uid.val() + 1).to_string().blue(),
+          tr!("delivered").bright_black()
+        );
+      }
+
+      Event::ChecklistItemAdded { content, .. } => {
+        println!("{} {}", tr!("Checklist item added").bright_black(), content);
+      }
+
+      Event::ChecklistItemToggled { item_uid, .. } => {
+        println!(
+          "{} {} {}",
+          tr!("Checklist item").bright_black(),
+          (item_uid.val() + 1).to_string().blue(),
+          tr!("toggled").bright_black()
+        );
+      }
+
+      Event::SetField { name, value, .. } => {
+        println!(
+          "{} {} {} {}",
+          tr!("Field").bright_black(),
+          name.cyan(),
+          tr!("set to").bright_black(),
+          value
+        );
+      }
+
+      Event::TimeAdjusted {
+        minutes, reason, ..
+      } => {
+        print!(
+          "{} {}",
+          tr!("Spent time adjusted by").bright_black(),
+          Self::friendly_time_adjustment(Duration::minutes(*minutes))
+        );
+
+        if let Some(reason) = reason {
+          print!(" ({})", reason.italic());
+        }
+
+        println!();
+      }
+    }
+  }
+
+  /// Friendly representation of a signed duration.
+  fn friendly_time_adjustment(duration: Duration) -> String {
+    if duration < Duration::zero() {
+      format!("-{}", time::format_duration(-duration))
+    } else {
+      format!("+{}", time::format_duration(duration))
+    }
+  }
+
+  /// Show the timesheet of a task, along with its total spent time.
+  pub fn show_timesheet(&self, task: &Task) {
+    for entry in task.time_entries() {
+      let duration = entry.duration();
+
+      match entry {
+        TimeEntry::Period { start, end } => {
+          let end = end
+            .as_ref()
+            .map_or_else(|| "now".to_owned(), date_time_to_string);
+
+          if self.plain {
+            println!(
+              "{}\t{}\t{}",
+              date_time_to_string(&start),
+              end,
+              time::format_duration(duration)
+            );
+          } else {
+            println!(
+              "{} {} {}: {}",
+              Self::friendly_date_time(&start),
+              "→".bright_black(),
+              end.italic().blue(),
+              time::format_duration(duration).cyan()
+            );
+          }
+        }
+
+        TimeEntry::Adjustment { date, reason, .. } => {
+          if self.plain {
+            println!(
+              "{}\t\t{}\t{}",
+              date_time_to_string(&date),
+              Self::friendly_time_adjustment(duration),
+              reason.unwrap_or_default()
+            );
+          } else {
+            print!(
+              "{}: {}",
+              Self::friendly_date_time(&date),
+              Self::friendly_time_adjustment(duration).yellow()
+            );
+
+            if let Some(reason) = reason {
+              print!(" {}", reason.italic());
+            }
+
+            println!();
+          }
+        }
+      }
+    }
+
+    let total = time::format_duration(task.spent_time());
+    if self.plain {
+      println!("total\t\t{}", total);
+    } else {
+      println!("{} {}", tr!("Total:").bright_black(), total.bold());
+    }
+  }
+
+  /// Remind about the someday tasks due for review, if any.
+  fn hint_review(&self, task_mgr: &TaskManager) {
+    let interval = Duration::days(self.config.review_interval_days().into());
+    let count = task_mgr.someday_tasks_to_review(interval).len();
+
+    if count > 0 && !self.plain {
+      println!(
+        "{}",
+        tr!(
+          "{} someday tasks are waiting for a review; run td review",
+          count
+        )
+        .bright_black()
+        .italic()
+      );
+    }
+  }
+
+  /// Review the someday tasks that haven’t been reviewed for a while, asking what to do with each of them.
+  fn review(&self, task_mgr: &mut TaskManager) -> Result<(), SubCmdError> {
+    let interval = Duration::days(self.config.review_interval_days().into());
+    let uids = task_mgr
+      .someday_tasks_to_review(interval)
+      .into_iter()
+      .map(|(uid, _)| uid)
+      .collect::<Vec<_>>();
+
+    if uids.is_empty() {
+      println!("{}", tr!("nothing to review").bright_black().italic());
+      return Ok(());
+    }
+
+    let mut reviewed = 0;
+    for uid in uids {
+      let task = match task_mgr.get_mut(uid) {
+        Some(task) => task,
+        None => continue,
+      };
+
+      println!(
+        "{} {} {}",
+        uid.to_string().bold(),
+        task.name(),
+        tr!("({} old)", Self::friendly_duration(task.age())).bright_black()
+      );
+      print!(
+        "{}",
+        tr!(
+          "{}eep, {}ctivate, {}ancel, {}kip or {}uit? ➤ ",
+          "k".green(),
+          "a".green(),
+          "c".green(),
+          "s".green(),
+          "q".green()
+        )
+      );
+      let _ = io::stdout().flush();
+
+      match self.term.read_line().as_deref().map(str::trim) {
+        Some("k") => task.review(),
+        Some("a") => task.set_someday(false),
+        Some("c") => task.change_status(Status::Cancelled),
+        Some("s") => continue,
+        _ => break,
+      }
+
+      reviewed += 1;
+    }
+
+    if reviewed > 0 {
+      self.save(task_mgr)?;
+    }
+
+    println!("{}", tr!("reviewed {} tasks", reviewed));
+    Ok(())
+  }
+
+  /// Pick a random task to start among the todo and paused tasks matching a filter, until one is started.
+  fn roulette(
+    &self,
+    task_mgr: &mut TaskManager,
+    weighted: bool,
+    case_insensitive: bool,
+    metadata_filter: Vec<String>,
+  ) -> Result<(), SubCmdError> {
+    let mut candidates = self
+      .match_active_tasks(task_mgr, false, case_insensitive, metadata_filter)?
+      .into_iter()
+      .map(|(uid, task)| (uid, if weighted { self.urgency(task) } else { 1 }))
+      .collect::<Vec<_>>();
+
+    if candidates.is_empty() {
+      println!("{}", tr!("nothing to pick").bright_black().italic());
+      return Ok(());
+    }
+
+    while !candidates.is_empty() {
+      let total = candidates.iter().map(|&(_, weight)| weight).sum::<u64>();
+      let picked = Self::weighted_pick(
+        candidates.iter().map(|&(_, weight)| weight),
+        fastrand::u64(0..total),
+      );
+      let (uid, _) = candidates.swap_remove(picked);
+      let task = match task_mgr.get_mut(uid) {
+        Some(task) => task,
+        None => continue,
+      };
+
+      println!(
+        "{} {} {}",
+        uid.to_string().bold(),
+        task.name(),
+        tr!("({} old)", Self::friendly_duration(task.age())).bright_black()
+      );
+      print!(
+        "{}",
+        tr!(
+          "{}tart it, {}eroll or {}uit? ➤ ",
+          "s".green(),
+          "r".green(),
+          "q".green()
+        )
+      );
+      let _ = io::stdout().flush();
+
+      match self.term.read_line().as_deref().map(str::trim) {
+        Some("s") => {
+          task.change_status(Status::Ongoing);
+          self.pause_other_tasks(task_mgr, uid);
+          self.save(task_mgr)?;
+          println!("{}", tr!("started task {}", uid));
+          return Ok(());
+        }
+        Some("r") => continue,
+        _ => return Ok(()),
+      }
+    }
+
+    println!("{}", tr!("no other task to pick").bright_black().italic());
+    Ok(())
+  }
+
+  /// Weight of a task in a weighted roulette: its priority, doubled if it is due within a week.
+  fn urgency(&self, task: &Task) -> u64 {
+    let priority = self
+      .config
+      .priorities()
+      .sort_key(task.priority(), self.config.no_priority_order()) as u64;
+    let due_soon = task
+      .deadline()
+      .is_some_and(|deadline| deadline <= Utc::now() + Duration::weeks(1));
+
+    if due_soon {
+      2 * priority
+    } else {
+      priority
+    }
+  }
+
+  /// Index of the weight `roll` falls in, `roll` being lower than the sum of the weights.
+  fn weighted_pick(weights: impl IntoIterator<Item = u64>, roll: u64) -> usize {
+    let mut sum = 0;
+
+    for (i, weight) in weights.into_iter().enumerate() {
+      sum += weight;
+
+      if roll < sum {
+        return i;
+      }
+    }
+
+    0
+  }
+
+  /// Ask for the missing priority and effort of the active tasks matching a filter, saving once at the end.
+  fn triage(
+    &self,
+    task_mgr: &mut TaskManager,
+    case_insensitive: bool,
+    metadata_filter: Vec<String>,
+  ) -> Result<(), SubCmdError> {
+    let uids = self
+      .match_active_tasks(task_mgr, true, case_insensitive, metadata_filter)?
+      .into_iter()
+      .filter(|(_, task)| task.priority().is_none() || task.effort().is_none())
+      .map(|(uid, _)| uid)
+      .collect::<Vec<_>>();
+
+    if uids.is_empty() {
+      println!("{}", tr!("nothing to triage").bright_black().italic());
+      return Ok(());
+    }
+
+    let shortcuts = self
+      .config
+      .priorities()
+      .levels()
+      .iter()
+      .map(|level| level.aliases.first().unwrap_or(&level.name).green())
+      .format(", ")
+      .to_string();
+    let mut triaged = 0;
+
+    'tasks: for uid in uids {
+      let task = match task_mgr.get_mut(uid) {
+        Some(task) => task,
+        None => continue,
+      };
+
+      println!(
+        "{} {} {}",
+        uid.to_string().bold(),
+        task.name(),
+        tr!("({} old)", Self::friendly_duration(task.age())).bright_black()
+      );
+
+      let mut changed = false;
+
+      while task.priority().is_none() {
+        print!(
+          "{}",
+          tr!(
+            "priority ({}), empty to skip or {} to quit ➤ ",
+            shortcuts,
+            "q".green()
+          )
+        );
+        let _ = io::stdout().flush();
+
+        match self.term.read_line().as_deref().map(str::trim) {
+          Some("") => break,
+          Some("q") | None => break 'tasks,
+          Some(answer) => match self.config.priorities().find(answer) {
+            Some(priority) => {
+              task.set_priority(priority);
+              changed = true;
+            }
+            None => println!("{}", tr!("unknown priority {}", answer).red()),
+          },
+        }
+      }
+
+      while task.effort().is_none() {
+        print!(
+          "{}",
+          tr!(
+            "effort (e.g. 1h30m), empty to skip or {} to quit ➤ ",
+            "q".green()
+          )
+        );
+        let _ = io::stdout().flush();
+
+        match self.term.read_line().as_deref().map(str::trim) {
+          Some("") => break,
+          Some("q") | None => {
+            triaged += changed as usize;
+            break 'tasks;
+          }
+          Some(answer) => match time::parse_duration(answer) {
+            Ok(effort) => {
+              task.set_effort(effort);
+              changed = true;
+            }
+            Err(err) => println!("{}", err.to_string().red()),
+          },
+        }
+      }
+
+      triaged += changed as usize;
+    }
+
+    if triaged > 0 {
+      self.save(task_mgr)?;
+    }
+
+    println!("{}", tr!("triaged {} tasks", triaged));
+    Ok(())
+  }
+
+  /// Suggest a plan for today, given a capacity.
+  fn plan(&self, task_mgr: &TaskManager, capacity: Duration) {
+    let until = nlp::start_of_day(Local::today().naive_local().succ());
+    let plan = task_mgr.plan(self.config.priorities(), capacity, until);
+
+    if self.plain {
+      for (section, uid, task, effort) in plan
+        .planned
+        .iter()
+        .map(|&(uid, task, effort)| ("planned", uid, task, Some(effort)))
+        .chain(
+          plan
+            .left_out
+            .iter()
+            .map(|&(uid, task, effort)| ("left-out", uid, task, Some(effort))),
+        )
+        .chain(
+          plan
+            .unestimated
+            .iter()
+            .map(|&(uid, task)| ("unestimated", uid, task, None)),
+        )
+      {
+        println!(
+          "{}\t{}\t{}\t{}",
+          section,
+          uid,
+          effort.map(time::format_duration).unwrap_or_default(),
+          task.name()
+        );
+      }
+
+      return;
+    }
+
+    if plan.planned.is_empty() {
+      println!("{}", tr!("nothing to plan").bright_black().italic());
+    } else {
+      println!(
+        "{} {}{}",
+        tr!("plan for").bright_black(),
+        time::format_duration(capacity).bold(),
+        tr!(", {} free", time::format_duration(plan.free)).bright_black()
+      );
+
+      for (i, &(uid, task, effort)) in plan.planned.iter().enumerate() {
+        let due = task
+          .deadline()
+          .filter(|&deadline| deadline < until)
+          .map(|_| tr!(" (due)").red().to_string())
+          .unwrap_or_default();
+        println!(
+          "  {}. {} {} {}{}",
+          i + 1,
+          uid.to_string().bold(),
+          time::format_duration(effort).cyan(),
+          task.name(),
+          due
+        );
+      }
+    }
+
+    if !plan.left_out.is_empty() {
+      println!("{}", tr!("doesn’t fit:").yellow());
+      for &(uid, task, effort) in &plan.left_out {
+        println!(
+          "  {} {} {}",
+          uid.to_string().bold(),
+          time::format_duration(effort).cyan(),
+          task.name()
+        );
+      }
+    }
+
+    if !plan.unestimated.is_empty() {
+      println!(
+        "{}",
+        tr!("not estimated; set their effort with effort:<duration>:").yellow()
+      );
+      for &(uid, task) in &plan.unestimated {
+        println!("  {} {}", uid.to_string().bold(), task.name());
+      }
+    }
+  }
+
+  /// Print the reminders that are due and mark them as delivered, so that they fire only once.
+  fn notify(&self, task_mgr: &mut TaskManager) -> Result<(), SubCmdError> {
+    let due = task_mgr
+      .due_reminders(Utc::now())
+      .into_iter()
+      .map(|(uid, task, reminder_uid, date)| (uid, task.name().to_owned(), reminder_uid, date))
+      .collect::<Vec<_>>();
+
+    if due.is_empty() {
+      return Ok(());
+    }
+
+    for (uid, name, reminder_uid, date) in due {
+      if self.plain {
+        println!("{}\t{}\t{}", uid, nlp::format_date_time(date), name);
+      } else {
+        println!(
+          "{} {} {} {}",
+          tr!("reminder:").yellow().bold(),
+          uid.to_string().bold(),
+          name,
+          format!("({})", Self::friendly_date_time(&date)).bright_black()
+        );
+      }
+
+      if let Some(task) = task_mgr.get_mut(uid) {
+        task.deliver_reminder(reminder_uid)?;
+      }
+    }
+
+    self.save(task_mgr)
+  }
+
+  /// Manipulate contexts.
+  fn context(
+    &self,
+    task_mgr: &mut TaskManager,
+    subcmd: Option<ContextCommand>,
+  ) -> Result<(), SubCmdError> {
+    match subcmd {
+      Some(ContextCommand::Now { context }) => {
+        task_mgr.set_context(Some(context));
+        self.save(task_mgr)?;
+        println!(
+          "{}",
+          tr!(
+            "switched to context {}",
+            Self::friendly_context(task_mgr.context().unwrap_or_default())
+          )
+        );
+      }
+
+      Some(ContextCommand::Clear) => {
+        task_mgr.set_context(None);
+        self.save(task_mgr)?;
+        println!("{}", tr!("cleared the active context"));
+      }
+
+      Some(ContextCommand::List) | None => {
+        let active = task_mgr.context();
+
+        if subcmd.is_none() && !self.plain {
+          match active {
+            Some(context) => println!(
+              "{}",
+              tr!("active context: {}", Self::friendly_context(context))
+            ),
+            None => println!("{}", tr!("no active context").bright_black().italic()),
+          }
+        }
+
+        for (context, count) in task_mgr.contexts() {
+          if self.plain {
+            println!("{}\t{}", context, count);
+          } else {
+            let marker = if active == Some(context) { "*" } else { " " };
+            println!(
+              "{} {} {}",
+              marker,
+              Self::friendly_context(context),
+              tr!("({} tasks)", count).bright_black()
+            );
+          }
+        }
+      }
+    }
+
+    Ok(())
+  }
+
+  /// Print the hierarchy of projects, with the number of tasks in each project and its subprojects.
+  fn project_tree(&self, task_mgr: &TaskManager, all: bool) {
+    let tree = ProjectTree::new(
+      task_mgr
+        .tasks()
+        .map(|(_, task)| task)
+        .filter(|task| all || matches!(task.status(), Status::Todo | Status::Ongoing)),
+    );
+
+    if tree.children.is_empty() {
+      if !self.plain {
+        println!("{}", tr!("no project").bright_black().italic());
+      }
+
+      return;
+    }
+
+    self.display_project_tree(&tree, "", "");
+  }
+
+  /// Show the effort rollup of a project, followed by the ones of its direct subprojects.
+  fn show_project(&self, task_mgr: &TaskManager, project: &str) {
+    let project = project.strip_prefix('@').unwrap_or(project);
+    let (total, subprojects) = task_mgr.project_rollup(project);
+
+    if total == EffortRollup::default() {
+      if !self.plain {
+        println!(
+          "{}",
+          tr!("no task in project {}", project)
+            .bright_black()
+            .italic()
+        );
+      }
+
+      return;
+    }
+
+    if self.plain {
+      for (name, rollup) in std::iter::once((project, &total)).chain(
+        subprojects
+          .iter()
+          .map(|(name, rollup)| (name.as_str(), rollup)),
+      ) {
+        println!(
+          "{}\t{}\t{}\t{}\t{}\t{}",
+          name,
+          rollup.tasks,
+          rollup.done,
+          rollup.estimated.num_minutes(),
+          rollup.spent.num_minutes(),
+          rollup.unestimated
+        );
+      }
+
+      return;
+    }
+
+    let header_hl = &self.config.colors.show_header;
+
+    println!(
+      " {}: {}",
+      header_hl.highlight(self.config.project_col_name()),
+      Self::friendly_project(project)
+    );
+    println!(
+      " {}: {}",
+      header_hl.highlight(tr!("Tasks")),
+      tr!("{} ({} done)", total.tasks, total.done)
+    );
+    println!(
+      " {}: {}",
+      header_hl.highlight(tr!("Effort")),
+      Self::friendly_rollup(&total)
+    );
+
+    if !subprojects.is_empty() {
+      println!(" {}:", header_hl.highlight(tr!("Subprojects")));
+
+      for (name, rollup) in &subprojects {
+        println!(
+          "   {} {}",
+          Self::friendly_project(name),
+          Self::friendly_rollup(rollup)
+        );
+      }
+    }
+  }
+
+  /// Friendly representation of an effort rollup: the estimated effort against the time spent.
+  fn friendly_rollup(rollup: &EffortRollup) -> String {
+    let mut output = tr!(
+      "{} estimated, {} spent",
+      time::format_duration(rollup.estimated).cyan(),
+      time::format_duration(rollup.spent).cyan()
+    );
+
+    if rollup.spent > rollup.estimated && rollup.estimated > Duration::zero() {
+      output += &format!(" {}", tr!("(over estimate)").red());
+    }
+
+    if rollup.unestimated > 0 {
+      output += &format!(
+        " {}",
+        tr!("({} tasks without effort)", rollup.unestimated).bright_black()
+      );
+    }
+
+    output
+  }
+
+  /// Summarize the milestones of a project.
+  fn project_milestones(&self, task_mgr: &TaskManager, project: &str) {
+    let project = project.strip_prefix('@').unwrap_or(project);
+    let milestones = MilestoneCount::per_milestone(task_mgr.tasks().map(|(_, task)| task), project);
+
+    if milestones.is_empty() {
+      if !self.plain {
+        println!("{}", tr!("no milestone").bright_black().italic());
+      }
+
+      return;
+    }
+
+    let width = milestones
+      .keys()
+      .map(|milestone| milestone.width())
+      .max()
+      .unwrap_or_default();
+
+    for (milestone, count) in milestones {
+      if self.plain {
+        println!("{}\t{}\t{}", milestone, count.open, count.done);
+        continue;
+      }
+
+      println!(
+        "{} {}",
+        Self::friendly_milestone(format!(
+          "{}{}",
+          milestone,
+          " ".repeat(width - milestone.width())
+        )),
+        tr!("({} open, {} done)", count.open, count.done).bright_black()
+      );
+    }
+  }
+
+  /// Display the subprojects of a project; `path` is the full name of the project and `indent` the tree drawing
+  /// preceding its subprojects.
+  fn display_project_tree(&self, tree: &ProjectTree, path: &str, indent: &str) {
+    let nb = tree.children.len();
+
+    for (i, (name, child)) in tree.children.iter().enumerate() {
+      let path = if path.is_empty() {
+        name.clone()
+      } else {
+        format!("{}{}{}", path, metadata::PROJECT_SEPARATOR, name)
+      };
+
+      if self.plain {
+        println!("{}\t{}", path, child.count);
+        self.display_project_tree(child, &path, "");
+        continue;
+      }
+
+      // top-level projects are not drawn as branches
+      let last = i + 1 == nb;
+      let (branch, child_indent) = match (path.contains(metadata::PROJECT_SEPARATOR), last) {
+        (false, _) => ("", String::new()),
+        (true, false) => ("├─ ", format!("{}│  ", indent)),
+        (true, true) => ("└─ ", format!("{}   ", indent)),
+      };
+
+      println!(
+        "{}{}{} {}",
+        indent.bright_black(),
+        branch.bright_black(),
+        Self::friendly_project(name),
+        tr!("({} tasks)", child.count).bright_black()
+      );
+      self.display_project_tree(child, &path, &child_indent);
+    }
+  }
+
+  /// Manipulate the trash.
+  pub fn trash(
+    &self,
+    task_mgr: &mut TaskManager,
+    trash_cmd: TrashCommand,
+  ) -> Result<(), SubCmdError> {
+    match trash_cmd {
+      TrashCommand::List => {
+        let mut tasks = task_mgr
+          .trashed_tasks()
+          .map(|(&uid, task)| (uid, task))
+          .collect::<Vec<_>>();
+        tasks.sort_by_key(|&(uid, _)| uid);
+
+        if tasks.is_empty() && !self.plain {
+          println!("{}", tr!("the trash is empty").bright_black().italic());
+        }
+
+        self.display_tasks(&tasks);
+      }
+
+      TrashCommand::Restore { uid } => {
+        task_mgr.restore_task(uid)?;
+        self.save(task_mgr)?;
+        println!("{}", tr!("restored task {}", uid));
+      }
+
+      TrashCommand::Empty { all } => {
+        let retention = if all {
+          Duration::zero()
+        } else {
+          Duration::days(self.config.trash_retention_days().into())
+        };
+
+        let count = task_mgr.expired_trash(retention).len();
+        if count != 0 && !self.confirm(tr!("permanently delete {} tasks from the trash?", count)) {
+          println!("{}", tr!("aborted").yellow());
+          return Ok(());
+        }
+
+        task_mgr.back_up("trash-empty")?;
+        let purged = task_mgr.empty_trash(retention);
+        self.save(task_mgr)?;
+        println!("{}", tr!("permanently deleted {} tasks", purged.len()));
+      }
+    }
+
+    Ok(())
+  }
+
+  /// Import tasks from a file (or the standard input if the path is `-`).
+  pub fn import(
+    &self,
+    task_mgr: &mut TaskManager,
+    format: &str,
+    path: PathBuf,
+    allow_duplicates: bool,
+  ) -> Result<(), SubCmdError> {
+    let input = read_input(&path)?;
+    let importer = self
+      .formats
+      .importer(format)
+      .ok_or_else(|| SubCmdError::UnknownFormat(format.to_owned()))?;
+    let tasks = importer.import(&input, &self.config)?;
+
+    self.import_tasks(task_mgr, tasks, allow_duplicates)
+  }
+
+  /// Register imported tasks and report their UIDs.
+  fn import_tasks(
+    &self,
+    task_mgr: &mut TaskManager,
+    tasks: Vec<Task>,
+    allow_duplicates: bool,
+  ) -> Result<(), SubCmdError> {
+    task_mgr.back_up("import")?;
+    let report = task_mgr.import_tasks(tasks, allow_duplicates);
+    let duplicates = report.iter().filter(|imported| imported.duplicate).count();
+    self.save(task_mgr)?;
+
+    // map the external IDs to the UIDs
+    for imported in report
+      .iter()
+      .filter(|imported| imported.external_id.is_some())
+    {
+      let external_id = imported.external_id.as_deref().unwrap_or_default();
+
+      if self.plain {
+        let outcome = if imported.duplicate {
+          "skipped"
+        } else {
+          "imported"
+        };
+        println!("{}\t{}\t{}", external_id, imported.uid, outcome);
+      } else if imported.duplicate {
+        println!(
+          "{} → {} {}",
+          external_id.bright_black(),
+          imported.uid.to_string().bold(),
+          tr!("(already imported)").bright_black()
+        );
+      } else {
+        println!("{} → {}", external_id, imported.uid.to_string().bold());
+      }
+    }
+
+    if !self.plain {
+      println!(
+        "{}",
+        tr!(
+          "imported {} tasks, skipped {} already imported",
+          report.len() - duplicates,
+          duplicates
+        )
+      );
+    }
+
+    Ok(())
+  }
+
+  /// Print the deep link sharing a task, and return it.
+  pub fn share(
+    &self,
+    task_mgr: &TaskManager,
+    task_uid: Option<UID>,
+  ) -> Result<String, SubCmdError> {
+    let task = task_uid
+      .and_then(|uid| task_mgr.get(uid))
+      .ok_or(SubCmdError::MissingTask(tr!(
+        "missing or unknown task to share"
+      )))?;
+
+    let link = share::link(task)?;
+    println!("{}", link);
+
+    Ok(link)
+  }
+
+  /// Add a task from an org-protocol capture URL; `content` adds metadata to it.
+  pub fn capture(
+    &mut self,
+    task_mgr: &mut TaskManager,
+    url: &str,
+    force: bool,
+    content: Vec<String>,
+  ) -> Result<(), SubCmdError> {
+    let capture = match org::Capture::parse(url) {
+      Some(capture) => capture,
+      None => {
+        println!("{}", tr!("not an org-protocol capture URL").red());
+        return Ok(());
+      }
+    };
+
+    // the title is taken as-is, so that words such as #123 are not mistaken for metadata
+    let mut quick_add = nlp::parse(
+      self.config.priorities(),
+      self.config.fields(),
+      content.iter().map(|s| s.as_str()),
+      Local::today().naive_local(),
+    );
+    let title = match capture.title.as_ref().or(capture.url.as_ref()) {
+      Some(title) => title,
+      None => {
+        println!("{}", tr!("nothing to capture").red());
+        return Ok(());
+      }
+    };
+    quick_add.name = format!("{} {}", quick_add.name, title)
+      .trim_start()
+      .to_owned();
+
+    let uid = self.add_quick_add(task_mgr, quick_add, false, false, force, false)?;
+
+    let note = capture
+      .url
+      .iter()
+      .cloned()
+      .chain(capture.body.iter().map(|body| {
+        body
+          .lines()
+          .map(|line| format!("> {}", line).trim_end().to_owned())
+          .join("\n")
+      }))
+      .join("\n\n");
+
+    if !note.is_empty() {
+      if let Some(task) = uid.and_then(|uid| task_mgr.get_mut(uid)) {
+        task.add_note(note);
+        self.save(task_mgr)?;
+      }
+    }
+
+    Ok(())
+  }
+
+  /// Synchronize the tasks with the Jira issues matching the configured query.
+  #[cfg(feature = "jira")]
+  pub fn sync_jira(&self, task_mgr: &mut TaskManager, dry_run: bool) -> Result<(), SubCmdError> {
+    use toodoux::sync::jira::{self, Change};
+
+    let config = match self.config.jira {
+      Some(ref config) => config,
+      None => {
+        println!(
+          "{}",
+          tr!("missing [jira] section in the configuration").red()
+        );
+        return Ok(());
+      }
+    };
+
+    let client = jira::Client::new(config)?;
+    let issues = client.search(&config.jql)?;
+    let changes = jira::plan(
+      &config.statuses,
+      &issues,
+      task_mgr.tasks().map(|(&uid, task)| (uid, task)),
+    );
+
+    for change in &changes {
+      let uid = if dry_run {
+        match *change {
+          Change::CreateTask(_) => None,
+          Change::UpdateTask { uid, .. } | Change::TransitionIssue { uid, .. } => Some(uid),
+        }
+      } else {
+        client.apply(config, task_mgr, change)?
+      };
+
+      let (key, outcome) = match *change {
+        Change::CreateTask(ref issue) => (&issue.key, tr!("created").to_owned()),
+        Change::UpdateTask {
+          ref key, status, ..
+        } => (key, tr!("marked as {}", self.status_alias(status))),
+        Change::TransitionIssue {
+          ref key, ref to, ..
+        } if dry_run || uid.is_some() => (key, tr!("transitioned to {}", to)),
+        Change::TransitionIssue {
+          ref key, ref to, ..
+        } => (key, tr!("cannot be transitioned to {}", to)),
+      };
+      let uid = uid.map(|uid| uid.to_string()).unwrap_or_default();
+
+      if self.plain {
+        println!("{}\t{}\t{}", key, uid, outcome);
+      } else if uid.is_empty() {
+        println!("{} {}", key, outcome.bright_black());
+      } else {
+        println!("{} → {} {}", key, uid.bold(), outcome.bright_black());
+      }
+    }
+
+    if dry_run {
+      return Ok(());
+    }
+
+    self.save(task_mgr)?;
+
+    if !self.plain {
+      println!(
+        "{}",
+        tr!(
+          "synchronized {} issues, {} changes",
+          issues.len(),
+          changes.len()
+        )
+      );
+    }
+
+    Ok(())
+  }
+
+  /// Write a digest of the activity over the last `since` to the standard output.
+  ///
+  /// Sections and projects are taken from the configuration.
+  pub fn digest(
+    &self,
+    task_mgr: &TaskManager,
+    since: Duration,
+    format: &str,
+  ) -> Result<(), SubCmdError> {
+    let now = Utc::now();
+    let digest = Digest::new(
+      task_mgr.tasks().map(|(&uid, task)| (uid, task)),
+      now - since,
+      now,
+      &self.config.digest.projects,
+      &self.config.privacy,
+    );
+    let sections = &self.config.digest.sections;
+    let out = io::stdout();
+
+    match format {
+      "markdown" | "md" => digest.write_markdown(out, sections)?,
+      "email" => digest.write_email(out, sections)?,
+      _ => return Err(SubCmdError::UnknownFormat(format.to_owned())),
+    }
+
+    Ok(())
+  }
+
+  /// Post a digest of the activity over the last `since` to a chat service.
+  #[cfg(feature = "post")]
+  pub fn post_digest(
+    &self,
+    task_mgr: &TaskManager,
+    since: Duration,
+    chat: toodoux::digest::Chat,
+  ) -> Result<(), SubCmdError> {
+    let now = Utc::now();
+    let digest = Digest::new(
+      task_mgr.tasks().map(|(&uid, task)| (uid, task)),
+      now - since,
+      now,
+      &self.config.digest.projects,
+      &self.config.privacy,
+    );
+    digest.post(&self.config.digest, chat)?;
+
+    if !self.plain {
+      println!("{}", tr!("posted the digest to {}", chat));
+    }
+
+    Ok(())
+  }
+
+  /// Compact the journal into the tasks file.
+  ///
+  /// With `squash`, the superseded events are dropped from the histories first.
+  fn gc(&self, task_mgr: &mut TaskManager, squash: bool) -> Result<(), SubCmdError> {
+    let records = task_mgr.journal_records();
+    task_mgr.back_up("gc")?;
+    let squashed = if squash { task_mgr.squash() } else { 0 };
+    task_mgr.compact(&self.config)?;
+
+    if !self.plain {
+      println!(
+        "{}",
+        tr!("compacted {} journal records into the tasks file", records)
+      );
+
+      if squash {
+        println!("{}", tr!("dropped {} superseded events", squashed));
+      }
+    }
+
+    Ok(())
+  }
+
+  /// Print the effective value of a configuration key; with `origin`, print each value along with the file it comes
+  /// from.
+  fn config_get(&self, key: &str, origin: bool) -> Result<(), SubCmdError> {
+    let value = self
+      .config
+      .value(key)?
+      .ok_or_else(|| SubCmdError::UnknownConfigKey(key.to_owned()))?;
+
+    if origin {
+      let mut values = Vec::new();
+      flatten_config_value(key, value, &mut values);
+
+      for (key, value) in values {
+        let origin = match self.config.origin(&key) {
+          Some(path) => path.display().to_string(),
+          None => tr!("default").to_owned(),
+        };
+        println!("{}\t{} = {}", origin, key, inline_config_value(&value));
+      }
+    } else {
+      match value {
+        toml::Value::String(value) => println!("{}", value),
+        toml::Value::Table(_) => {
+          // nest the section under its path, so that it prints as in a configuration file
+          let section = key.rsplit('.').fold(value, |value, part| {
+            toml::Value::Table(std::iter::once((part.to_owned(), value)).collect())
+          });
+          print!(
+            "{}",
+            toml::to_string_pretty(&section).map_err(Error::CannotSerializeToTOML)?
+          );
         }
+        value => println!("{}", inline_config_value(&value)),
+      }
+    }
+
+    Ok(())
+  }
+
+  /// Merge the copy of the tasks file at `path`, asking how to resolve each conflict unless `strategy` is set.
+  fn resolve(
+    &self,
+    task_mgr: &mut TaskManager,
+    path: &Path,
+    strategy: Option<Strategy>,
+  ) -> Result<(), SubCmdError> {
+    let content = fs::read(path).map_err(Error::io(Operation::ReadTasks, path))?;
+    let theirs = TaskManager::with_storage(&self.config, MemoryStorage::with_tasks(content))?;
+    task_mgr.back_up("resolve")?;
+    let merge = task_mgr.merge(theirs);
+    let mut changed = merge.added.len() + merge.renumbered.len() + merge.updated.len();
+
+    if !self.plain {
+      println!(
+        "{}",
+        tr!(
+          "{} tasks added, {} updated, {} in conflict",
+          merge.added.len() + merge.renumbered.len(),
+          merge.updated.len(),
+          merge.conflicts.len()
+        )
+      );
+
+      for (their_uid, uid) in &merge.renumbered {
+        println!(
+          "{}",
+          tr!("task {} of the other copy added as {}", their_uid, uid).bright_black()
+        );
       }
+    }
 
-      match event {
-        Event::Created(_) => {
-          println!("{} {}", "Task created with uid".bright_black(), uid);
+    let mut left = 0;
+    for conflict in merge.conflicts {
+      let uid = conflict.uid();
+      let task = match strategy {
+        Some(strategy) => Some(conflict.resolve(strategy)),
+        None => self.resolve_conflict(conflict)?,
+      };
+
+      match task {
+        Some(task) => {
+          task_mgr.settle(uid, task);
+          changed += 1;
         }
+        None => left += 1,
+      }
+    }
 
-        Event::StatusChanged { status, .. } => {
-          println!(
-            "{} {}",
-            "Status changed to".bright_black(),
-            self.highlight_status(*status)
-          );
-        }
+    if changed > 0 {
+      self.save(task_mgr)?;
+    }
 
-        Event::NoteAdded { content, .. } => {
-          println!("{} {}", "Note added".bright_black(), content);
-        }
+    if left > 0 && !self.plain {
+      println!(
+        "{}",
+        tr!("{} conflicts left; run resolve again to resolve them", left).yellow()
+      );
+    }
 
-        Event::NoteReplaced {
-          content, note_uid, ..
-        } => {
-          println!(
-            "{} {} {} {}",
-            "Note".bright_black(),
-            note_uid.to_string().blue(),
-            "updated".bright_black(),
-            content
-          );
-        }
+    Ok(())
+  }
 
-        Event::SetProject { project, .. } => {
-          println!(
-            "{} {}",
-            "Project set to".bright_black(),
-            Self::friendly_project(project)
-          );
+  /// Ask how to resolve a conflict, giving the resolved task, or [`None`] if the conflict is skipped.
+  fn resolve_conflict(&self, conflict: Conflict) -> Result<Option<Task>, SubCmdError> {
+    let uid = conflict.uid();
+    println!("{} {}", uid.to_string().bold(), conflict.name());
+
+    for (side, events) in [
+      (tr!("mine"), conflict.mine().collect::<Vec<_>>()),
+      (tr!("theirs"), conflict.theirs().collect()),
+    ] {
+      println!("  {}", side.bold());
+      for (event, author) in events {
+        print!("    ");
+        self.show_authored_event(uid, event, author);
+      }
+    }
+
+    print!(
+      "{}",
+      tr!(
+        "{}nion, {}heirs, {}ine, {}dit or {}kip? ➤ ",
+        "u".green(),
+        "t".green(),
+        "m".green(),
+        "e".green(),
+        "s".green()
+      )
+    );
+    let _ = io::stdout().flush();
+
+    let task = match self.term.read_line().as_deref().map(str::trim) {
+      Some("u") => Some(conflict.resolve(Strategy::Union)),
+      Some("t") => Some(conflict.resolve(Strategy::Theirs)),
+      Some("m") => Some(conflict.resolve(Strategy::Mine)),
+      Some("e") => {
+        let text = interactively_edit(&self.config, "CONFLICT.jsonl", &conflict.to_edit()?)?;
+        Some(conflict.resolve_edited(&text)?)
+      }
+      _ => None,
+    };
+
+    Ok(task)
+  }
+
+  /// List the registered formats, along with what they support.
+  fn list_formats(&self) {
+    let formats = self
+      .formats
+      .formats()
+      .map(|format| {
+        let support = format
+          .importer()
+          .map(|_| "import")
+          .into_iter()
+          .chain(format.exporter().map(|_| "export"))
+          .collect::<Vec<_>>()
+          .join(", ");
+        (format, support)
+      })
+      .collect::<Vec<_>>();
+
+    if self.plain {
+      for (format, support) in formats {
+        println!(
+          "{}\t{}\t{}\t{}",
+          format.name(),
+          format.aliases().join(","),
+          support,
+          format.description()
+        );
+      }
+
+      return;
+    }
+
+    let names = formats
+      .iter()
+      .map(|(format, _)| {
+        let mut name = format.name().to_owned();
+        if !format.aliases().is_empty() {
+          name = format!("{} ({})", name, format.aliases().join(", "));
         }
+        name
+      })
+      .collect::<Vec<_>>();
+    let name_width = names.iter().map(|name| name.width()).max().unwrap_or(0);
 
-        Event::SetPriority { priority, .. } => {
-          println!(
-            "{} {}",
-            "Priority set to".bright_black(),
-            self.friendly_priority(*priority)
-          );
+    for ((format, support), name) in formats.iter().zip(names) {
+      println!(
+        "{}  {:<14}  {}",
+        format!("{}{}", name, " ".repeat(name_width - name.width())).bold(),
+        support,
+        format.description().bright_black()
+      );
+    }
+  }
+
+  /// Export all the tasks to a file (or the standard output if no path is provided).
+  ///
+  /// Private tasks are left out, unless exporting a single task, which is then explicitly asked for.
+  pub fn export(
+    &self,
+    task_mgr: &TaskManager,
+    task_uid: Option<UID>,
+    format: &str,
+    output: Option<PathBuf>,
+  ) -> Result<(), SubCmdError> {
+    let task = task_uid
+      .map(|uid| {
+        task_mgr
+          .get(uid)
+          .map(|task| (uid, task))
+          .ok_or(Error::UnknownTask(uid))
+      })
+      .transpose()?;
+    let exporter = self
+      .formats
+      .exporter(format)
+      .ok_or_else(|| SubCmdError::UnknownFormat(format.to_owned()))?;
+    let mut out: Box<dyn io::Write> = match output {
+      Some(path) => Box::new(io::BufWriter::new(
+        fs::File::create(&path).map_err(Error::io(Operation::Write, path))?,
+      )),
+      None => Box::new(io::stdout()),
+    };
+
+    if let Some(task) = task {
+      exporter.export(&mut out, &self.config, &[task])?;
+      return Ok(());
+    }
+
+    let mut tasks = task_mgr
+      .tasks()
+      .map(|(&uid, task)| (uid, task))
+      .collect::<Vec<_>>();
+    tasks.sort_by_key(|&(uid, _)| uid);
+
+    let private = export_public(exporter, &mut out, &self.config, tasks)?;
+    let private = privacy::counted(&self.config.privacy, private);
+
+    // the standard output may hold the export, hence the standard error
+    if private > 0 && !self.plain {
+      eprintln!("{}", tr!("left out {} private tasks", private));
+    }
+
+    Ok(())
+  }
+
+  /// Publish the tasks as a static site into `output`, or into the configured directory.
+  fn publish(&self, task_mgr: &TaskManager, output: Option<PathBuf>) -> Result<(), SubCmdError> {
+    let dir = output
+      .or_else(|| self.config.publish.output_dir.clone())
+      .unwrap_or_else(|| self.config.root_dir().join("public"));
+    let site = Site::new(
+      &self.config,
+      task_mgr.tasks().map(|(&uid, task)| (uid, task)),
+    );
+    site.write(&dir)?;
+
+    if !self.plain {
+      println!(
+        "{}",
+        tr!("published {} tasks to {}", site.task_count(), dir.display())
+      );
+    }
+
+    Ok(())
+  }
+
+  /// Normalize the tags of all the tasks if `tags` is set, or their projects otherwise.
+  ///
+  /// The names to rename are listed, along with their number of tasks; they are only renamed if `apply` is set.
+  fn normalize_names(
+    &self,
+    task_mgr: &mut TaskManager,
+    tags: bool,
+    apply: bool,
+  ) -> Result<(), SubCmdError> {
+    let normalization = &self.config.normalization;
+    let operator = if tags { "#" } else { "@" };
+    let has_name = |task: &Task, name: &str| {
+      if tags {
+        task.tags().any(|tag| tag == name)
+      } else {
+        task.project() == Some(name)
+      }
+    };
+
+    let names = task_mgr
+      .tasks()
+      .flat_map(|(_, task)| {
+        if tags {
+          task.tags().collect()
+        } else {
+          task.project().into_iter().collect::<Vec<_>>()
         }
+      })
+      .collect::<BTreeSet<_>>();
+    let renames = names
+      .into_iter()
+      .map(|name| {
+        let normalized = if tags {
+          normalize::tag(normalization, name)
+        } else {
+          normalize::project(normalization, name)
+        };
+        (name.to_owned(), normalized)
+      })
+      .filter(|(name, normalized)| name != normalized)
+      .collect::<Vec<_>>();
 
-        Event::AddTag { tag, .. } => {
-          println!("{}{}", "Tag added #".bright_black(), tag.yellow());
+    if renames.is_empty() {
+      println!("{}", tr!("nothing to normalize").bright_black().italic());
+      return Ok(());
+    }
+
+    for (name, normalized) in &renames {
+      let count = task_mgr
+        .tasks()
+        .filter(|(_, task)| has_name(task, name))
+        .count();
+      println!(
+        "{}{} {} {}{} {}",
+        operator,
+        name,
+        "→".bright_black(),
+        operator,
+        normalized.bold(),
+        tr!("({} tasks)", count).bright_black()
+      );
+    }
+
+    if !apply {
+      println!("{}", tr!("run again with --apply to rename them").yellow());
+      return Ok(());
+    }
+
+    let mut updated = BTreeSet::new();
+    for (name, normalized) in renames {
+      if tags {
+        task_mgr.rename_tag(&name, &normalized, |uid| {
+          updated.insert(uid);
+        });
+      } else {
+        let uids = task_mgr
+          .tasks()
+          .filter(|(_, task)| has_name(task, &name))
+          .map(|(&uid, _)| uid)
+          .collect::<Vec<_>>();
+
+        for uid in uids {
+          if let Some(task) = task_mgr.get_mut(uid) {
+            task.set_project(normalized.as_str());
+            updated.insert(uid);
+          }
         }
       }
     }
+
+    self.save(task_mgr)?;
+    println!("{}", tr!("updated {} tasks", updated.len()));
+
+    Ok(())
   }
 
   pub fn rename_project(
@@ -1108,14 +5546,56 @@ where
     let new_project = new_project.as_ref();
     let mut count = 0;
 
-    task_mgr.rename_project(&current_project, &new_project, |_| {
+    task_mgr.rename_project(current_project, new_project, |_| {
       count += 1;
     });
 
     if count != 0 {
-      println!("updated {} tasks", count);
+      println!("{}", tr!("updated {} tasks", count));
     } else {
-      println!("{}", "no task for this project".yellow());
+      println!("{}", tr!("no task for this project").yellow());
+    }
+  }
+}
+
+/// Section of the default listing a task is shown in.
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+enum ListingSection {
+  /// Active tasks past their deadline.
+  Overdue,
+  /// Active tasks with a deadline within the due-soon horizon.
+  DueSoon,
+  /// Ongoing and paused tasks without a close deadline.
+  InProgress,
+  /// Any other active task.
+  Other,
+}
+
+impl ListingSection {
+  /// All the sections, in display order.
+  const ALL: &'static [Self] = &[
+    ListingSection::Overdue,
+    ListingSection::DueSoon,
+    ListingSection::InProgress,
+    ListingSection::Other,
+  ];
+
+  /// Section of an active task, `horizon` being how far ahead a deadline is due soon.
+  fn of(task: &Task, now: DateTime<Utc>, horizon: Duration) -> Self {
+    match task.deadline() {
+      Some(deadline) if deadline < now => ListingSection::Overdue,
+      Some(deadline) if deadline < now + horizon => ListingSection::DueSoon,
+      _ if matches!(task.status(), Status::Ongoing | Status::Paused) => ListingSection::InProgress,
+      _ => ListingSection::Other,
+    }
+  }
+
+  fn title(self) -> &'static str {
+    match self {
+      ListingSection::Overdue => tr!("Overdue"),
+      ListingSection::DueSoon => tr!("Due soon"),
+      ListingSection::InProgress => tr!("In progress"),
+      ListingSection::Other => tr!("Other tasks"),
     }
   }
 }
@@ -1126,14 +5606,24 @@ struct DisplayOptions {
   task_uid_width: usize,
   /// Width of the task age column.
   age_width: usize,
+  /// Width of the activity column.
+  ///
+  /// `0` indicates the column is not displayed.
+  activity_width: usize,
   /// Width of the task spent column.
   spent_width: usize,
   /// Width of the task status column.
   status_width: usize,
+  /// Width of the task priority column.
+  prio_width: usize,
   /// Width of the task description column.
   description_width: usize,
   /// Width of the task project column.
   project_width: usize,
+  /// Width of the task context column.
+  context_width: usize,
+  /// Width of the task milestone column.
+  milestone_width: usize,
   /// Width of the task tags column.
   tags_width: usize,
   /// Whether any task has spent time.
@@ -1142,18 +5632,34 @@ struct DisplayOptions {
   has_priorities: bool,
   /// Whether we have a project in at least one task.
   has_projects: bool,
+  /// Whether we have a context in at least one task.
+  has_contexts: bool,
+  /// Whether we have a milestone in at least one task.
+  has_milestones: bool,
   /// Whether we have a tag in at least one task.
   has_tags: bool,
+  /// Whether at least one task is pinned.
+  has_pinned: bool,
+  /// Width of the column of each user-defined field, in configuration order.
+  ///
+  /// `0` indicates no data.
+  field_widths: Vec<usize>,
   /// Offset to use for the description column.
   description_offset: usize,
   /// Maximum columns to fit in the description column.
   ///
   /// [`None`] implies that the dimension of the terminal don’t allow for descriptions.
   max_description_cols: Option<usize>,
+  /// Maximum number of lines of a description, depending on the configured overflow.
+  max_description_lines: usize,
   /// With of the number of notes column.
   ///
   /// `0` indicates no data.
   notes_nb_width: usize,
+  /// Width of the checklist progress column.
+  ///
+  /// `0` indicates no data.
+  progress_width: usize,
 }
 
 impl DisplayOptions {
@@ -1163,6 +5669,8 @@ impl DisplayOptions {
     term: &impl Terminal,
     tasks: impl IntoIterator<Item = (UID, &'a Task)>,
   ) -> Self {
+    let tasks = tasks.into_iter().collect::<Vec<_>>();
+
     // FIXME: switch to a builder pattern here, because it’s starting to becoming a mess
     let (
       task_uid_width,
@@ -1171,14 +5679,20 @@ impl DisplayOptions {
       status_width,
       description_width,
       project_width,
+      context_width,
+      milestone_width,
       tags_width,
       has_spent_time,
       has_priorities,
       has_projects,
       has_tags,
+      has_pinned,
       notes_nb_width,
-    ) = tasks.into_iter().fold(
-      (0, 0, 0, 0, 0, 0, 0, false, false, false, false, 0),
+      progress_width,
+    ) = tasks.iter().copied().fold(
+      (
+        0, 0, 0, 0, 0, 0, 0, 0, 0, false, false, false, false, false, 0, 0,
+      ),
       |(
         task_uid_width,
         age_width,
@@ -1186,28 +5700,37 @@ impl DisplayOptions {
         status_width,
         description_width,
         project_width,
+        context_width,
+        milestone_width,
         tags_width,
         has_spent_time,
         has_priorities,
         has_projects,
         has_tags,
+        has_pinned,
         notes_nb_width,
+        progress_width,
       ),
        (uid, task)| {
         let task_uid_width = task_uid_width.max(Self::guess_task_uid_width(uid));
         let age_width = age_width.max(Self::guess_duration_width(&task.age()));
         let spent_width = spent_width.max(Self::guess_duration_width(&task.spent_time()));
-        let status_width = status_width.max(Self::guess_task_status_width(&config, task.status()));
-        let description_width = description_width.max(task.name().width());
-        let project_width = project_width.max(Self::guess_task_project_width(&task).unwrap_or(0));
-        let tags_width = tags_width.max(Self::guess_tags_width(&task));
+        let status_width = status_width.max(Self::guess_task_status_width(config, task.status()));
+        let description_width = description_width.max(text_width(task.name()));
+        let project_width = project_width.max(Self::project_cell(config, task).width());
+        let context_width = context_width.max(task.context().map_or(0, UnicodeWidthStr::width));
+        let milestone_width =
+          milestone_width.max(task.milestone().map_or(0, UnicodeWidthStr::width));
+        let tags_width = tags_width.max(Self::tags_cell(config, task).width());
         let has_spent_time = has_spent_time || task.spent_time() != Duration::zero();
         let has_priorities = has_priorities || task.priority().is_some();
         let has_projects = has_projects || task.project().is_some();
         let has_tags = has_tags || task.tags().next().is_some();
+        let has_pinned = has_pinned || task.is_pinned();
         let notes_nb_width = notes_nb_width.max(Self::guess_notes_width(
           task.notes().iter().map(|note| note.content.as_str()),
         ));
+        let progress_width = progress_width.max(Self::guess_progress_width(task));
 
         (
           task_uid_width,
@@ -1216,12 +5739,16 @@ impl DisplayOptions {
           status_width,
           description_width,
           project_width,
+          context_width,
+          milestone_width,
           tags_width,
           has_spent_time,
           has_priorities,
           has_projects,
           has_tags,
+          has_pinned,
           notes_nb_width,
+          progress_width,
         )
       },
     );
@@ -1229,25 +5756,39 @@ impl DisplayOptions {
     let mut opts = Self {
       task_uid_width: task_uid_width.max(config.uid_col_name().width()),
       age_width: age_width.max(config.age_col_name().width()),
+      activity_width: Self::guess_activity_width(config, &tasks),
       spent_width: spent_width.max(config.spent_col_name().width()),
       status_width: status_width.max(config.status_col_name().width()),
+      prio_width: Self::guess_prio_width(config),
       description_width: description_width.max(config.description_col_name().width()),
       project_width: project_width.max(config.project_col_name().width()),
+      context_width: context_width.max(config.context_col_name().width()),
+      milestone_width: milestone_width.max(config.milestone_col_name().width()),
       tags_width: tags_width.max(config.tags_col_name().width()),
       has_spent_time,
       has_priorities,
       has_projects,
+      has_contexts: context_width != 0,
+      has_milestones: milestone_width != 0,
       has_tags,
+      has_pinned,
+      field_widths: Self::guess_field_widths(config, &tasks),
       description_offset: 0,
       max_description_cols: None,
+      max_description_lines: Self::guess_description_lines(config, term.dimensions(), tasks.len()),
       notes_nb_width,
+      progress_width: if progress_width == 0 {
+        0
+      } else {
+        progress_width.max(config.progress_col_name().width())
+      },
     };
 
     opts.description_offset = opts.guess_description_col_offset(config);
 
     if let Some(term_dims) = term.dimensions() {
       opts.max_description_cols = term_dims[0].checked_sub(opts.description_offset);
-    } else {
+    } else if term.is_tty() {
       println!(
         "{}",
         "⚠ You’re using a terminal that doesn’t expose its dimensions; expect broken output ⚠"
@@ -1259,6 +5800,46 @@ impl DisplayOptions {
     opts
   }
 
+  /// Guess the width of the activity column; `0` if it is not displayed.
+  fn guess_activity_width(config: &Config, tasks: &[(UID, &Task)]) -> usize {
+    if !config.display_activity_listings() {
+      return 0;
+    }
+
+    tasks
+      .iter()
+      .map(|(_, task)| Self::guess_duration_width(&task.inactivity()))
+      .max()
+      .unwrap_or_default()
+      .max(config.activity_col_name().width())
+  }
+
+  /// Guess the maximum number of lines of a description, given the dimensions of the terminal and the number of
+  /// listed tasks.
+  fn guess_description_lines(
+    config: &Config,
+    term_dims: Option<[usize; 2]>,
+    tasks_nb: usize,
+  ) -> usize {
+    let max_lines = config.max_description_lines();
+
+    if let (Some([width, _]), Some(min_width)) = (term_dims, config.single_line_below_width()) {
+      if width < min_width {
+        return 1;
+      }
+    }
+
+    match (config.description_overflow(), term_dims) {
+      (DescriptionOverflow::Truncate, _) => max_lines,
+      (DescriptionOverflow::Wrap, _) => usize::MAX,
+      // keep a line for the header and another one for the footer
+      (DescriptionOverflow::Fit, Some([_, height])) if tasks_nb > 0 => {
+        (height.saturating_sub(2) / tasks_nb).clamp(1, max_lines.max(1))
+      }
+      (DescriptionOverflow::Fit, _) => max_lines,
+    }
+  }
+
   /// Guess the number of characters needed to represent a number.
   ///
   /// We limit ourselves to number < 100000.
@@ -1280,27 +5861,10 @@ impl DisplayOptions {
 
   /// Guess the width required to represent a duration.
   ///
-  /// The width is smart enough to take into account the unit (s, min, h, d, w, m or y) as well as the number.
+  /// The width is smart enough to take into account the unit (s, min, h, d, w, mth or y) as well as the number.
   fn guess_duration_width(dur: &Duration) -> usize {
-    if dur.num_minutes() < 1 {
-      // seconds, encoded with "Ns"
-      Self::guess_number_width(dur.num_seconds() as _) + "s".len()
-    } else if dur.num_hours() < 1 {
-      // minutes, encoded with "Nmin"
-      Self::guess_number_width(dur.num_minutes() as _) + "min".len()
-    } else if dur.num_days() < 1 {
-      // hours, encoded with "Nh"
-      Self::guess_number_width(dur.num_hours() as _) + "h".len()
-    } else if dur.num_weeks() < 2 {
-      // days, encoded with "Nd"
-      Self::guess_number_width(dur.num_days() as _) + "d".len()
-    } else if dur.num_weeks() < 4 {
-      // weeks, encoded with "Nw"
-      Self::guess_number_width(dur.num_weeks() as _) + "w".len()
-    } else {
-      // months, encoded with "Nmth"
-      Self::guess_number_width(dur.num_weeks() as usize / 4) + "mth".len()
-    }
+    let (n, unit) = duration_in_unit(dur);
+    Self::guess_number_width(n as _) + unit.len()
   }
 
   /// Guess the width required to represent the task status.
@@ -1310,20 +5874,83 @@ impl DisplayOptions {
       Status::Todo => config.todo_alias().width(),
       Status::Done => config.done_alias().width(),
       Status::Cancelled => config.cancelled_alias().width(),
+      Status::Paused => config.paused_alias().width(),
     };
 
     width.max("Status".len())
   }
 
-  fn guess_task_project_width(task: &Task) -> Option<usize> {
-    task.project().map(UnicodeWidthStr::width)
+  /// Guess the width required to represent any configured priority.
+  fn guess_prio_width(config: &Config) -> usize {
+    let priorities = config.priorities();
+    priorities
+      .levels()
+      .iter()
+      .map(|level| level.label().width())
+      .fold(config.prio_col_name().width(), usize::max)
+  }
+
+  /// Project of a task as displayed in listings, truncated to the configured maximum width.
+  fn project_cell<'a>(config: &Config, task: &'a Task) -> Cow<'a, str> {
+    let project = task.project().unwrap_or_default();
+
+    match config.max_project_width() {
+      Some(max_width) => truncate(project, max_width),
+      None => project.into(),
+    }
+  }
+
+  /// Tags of a task as displayed in listings.
+  ///
+  /// Past the configured maximum number of tags, the other ones are only counted, such as `a, b +3`; the result is then
+  /// truncated to the configured maximum width.
+  fn tags_cell(config: &Config, task: &Task) -> String {
+    let tags = task.tags().collect::<Vec<_>>();
+    let shown = config.max_tags().unwrap_or(tags.len()).min(tags.len());
+    let mut cell = tags[..shown].join(", ");
+
+    if shown < tags.len() {
+      if !cell.is_empty() {
+        cell.push(' ');
+      }
+
+      cell += &format!("+{}", tags.len() - shown);
+    }
+
+    match config.max_tags_width() {
+      Some(max_width) => truncate(&cell, max_width).into_owned(),
+      None => cell,
+    }
+  }
+
+  /// Guess the width required to represent the values of each user-defined field.
+  fn guess_field_widths(config: &Config, tasks: &[(UID, &Task)]) -> Vec<usize> {
+    config
+      .fields()
+      .defs()
+      .iter()
+      .map(|def| {
+        tasks
+          .iter()
+          .filter_map(|(_, task)| task.field(&def.name))
+          .map(|value| value.to_string().width())
+          .max()
+          .map_or(0, |width| width.max(def.name.width()))
+      })
+      .collect()
   }
 
-  /// Guess the width required to represent the task tags.
-  fn guess_tags_width(task: &Task) -> usize {
-    Itertools::intersperse(task.tags(), ", ")
-      .map(UnicodeWidthStr::width)
-      .sum()
+  /// User-defined fields to display as columns, along with the width of their column.
+  fn field_columns<'a>(&'a self, config: &'a Config) -> impl Iterator<Item = (&'a str, usize)> {
+    let display_empty_cols = config.display_empty_cols();
+
+    config
+      .fields()
+      .defs()
+      .iter()
+      .zip(&self.field_widths)
+      .filter(move |&(_, &width)| display_empty_cols || width != 0)
+      .map(|(def, &width)| (def.name.as_str(), width.max(def.name.width())))
   }
 
   /// Compute the column offset at which descriptions can start.
@@ -1333,13 +5960,32 @@ impl DisplayOptions {
     let spent_width;
     let prio_width;
     let project_width;
+    let context_width;
+    let milestone_width;
     let tags_width;
     let notes_nb_width;
+    let progress_width = if self.progress_width == 0 {
+      0
+    } else {
+      self.progress_width + 1
+    };
+    let pin_width = if self.has_pinned {
+      PIN_GLYPH.width() + 1
+    } else {
+      0
+    };
+    let activity_width = if self.activity_width == 0 {
+      0
+    } else {
+      self.activity_width + 1
+    };
 
     if config.display_empty_cols() {
       spent_width = self.spent_width + 1;
-      prio_width = config.prio_col_name().width() + 1;
+      prio_width = self.prio_width + 1;
       project_width = self.project_width + 1;
+      context_width = self.context_width + 1;
+      milestone_width = self.milestone_width + 1;
       tags_width = self.tags_width + 1;
       notes_nb_width = self.notes_nb_width + 1;
     } else {
@@ -1352,7 +5998,7 @@ impl DisplayOptions {
 
       // compute priority width if any
       if self.has_priorities {
-        prio_width = config.prio_col_name().width() + 1;
+        prio_width = self.prio_width + 1;
       } else {
         prio_width = 0;
       }
@@ -1364,6 +6010,20 @@ impl DisplayOptions {
         project_width = 0;
       }
 
+      // compute context width if any
+      if self.has_contexts {
+        context_width = self.context_width + 1;
+      } else {
+        context_width = 0;
+      }
+
+      // compute milestone width if any
+      if self.has_milestones {
+        milestone_width = self.milestone_width + 1;
+      } else {
+        milestone_width = 0;
+      }
+
       // compute tags width if any
       if config.display_tags_listings() && self.has_tags {
         tags_width = self.tags_width + 1; // FIXME
@@ -1379,30 +6039,271 @@ impl DisplayOptions {
       }
     }
 
+    let fields_width = self
+      .field_columns(config)
+      .map(|(_, width)| width + 1)
+      .sum::<usize>();
+
     // The “+ 1” are there because of the blank spaces we have in the output to separate columns.
-    1 + self.task_uid_width
+    pin_width
+      + 1
+      + self.task_uid_width
       + 1
       + self.age_width
       + 1
+      + activity_width
       + spent_width
       + prio_width
       + project_width
+      + context_width
+      + milestone_width
       + tags_width
+      + fields_width
       + notes_nb_width
+      + progress_width
       + self.status_width
       + 1 // to end up on the first column in the description
   }
 
-  /// Guess the maximum width to align notes.
-  fn guess_notes_width<'a>(notes: impl Iterator<Item = &'a str>) -> usize {
-    let nb = notes.count();
-
-    if nb == 0 {
-      0
-    } else {
-      Self::guess_number_width(nb)
+  /// Guess the width required to represent the checklist progress.
+  ///
+  /// `0` is returned if the task doesn’t have any checklist.
+  fn guess_progress_width(task: &Task) -> usize {
+    task.checklist_progress().map_or(0, |(checked, total)| {
+      "[/]".len() + Self::guess_number_width(checked) + Self::guess_number_width(total)
+    })
+  }
+
+  /// Guess the maximum width to align notes.
+  fn guess_notes_width<'a>(notes: impl Iterator<Item = &'a str>) -> usize {
+    let nb = notes.count();
+
+    if nb == 0 {
+      0
+    } else {
+      Self::guess_number_width(nb)
+    }
+  }
+}
+
+/// Read a file passed on the command line, or the standard input if the path is `-`.
+fn read_input(path: &Path) -> Result<String, SubCmdError> {
+  let mut input = String::new();
+
+  if path.as_os_str() == "-" {
+    io::stdin()
+      .read_to_string(&mut input)
+      .map_err(Error::io(Operation::Read, tr!("the standard input")))?;
+  } else {
+    input = fs::read_to_string(path).map_err(Error::io(Operation::Read, path))?;
+  }
+
+  Ok(input)
+}
+
+/// Flatten a configuration value into its values but tables, along with their dotted paths, starting at `key`.
+fn flatten_config_value(key: &str, value: toml::Value, values: &mut Vec<(String, toml::Value)>) {
+  match value {
+    toml::Value::Table(table) => {
+      for (k, value) in table {
+        flatten_config_value(&format!("{}.{}", key, k), value, values);
+      }
+    }
+    value => values.push((key.to_owned(), value)),
+  }
+}
+
+/// Render a configuration value on a single line, tables included.
+fn inline_config_value(value: &toml::Value) -> String {
+  match value {
+    toml::Value::Array(values) => {
+      format!("[{}]", values.iter().map(inline_config_value).join(", "))
+    }
+    toml::Value::Table(table) => format!(
+      "{{ {} }}",
+      table
+        .iter()
+        .map(|(key, value)| format!("{} = {}", config_key(key), inline_config_value(value)))
+        .join(", ")
+    ),
+    value => value.to_string(),
+  }
+}
+
+/// Quote a key of a configuration table if it’s not a bare key.
+fn config_key(key: &str) -> String {
+  if !key.is_empty()
+    && key
+      .chars()
+      .all(|c| c.is_ascii_alphanumeric() || c == '-' || c == '_')
+  {
+    key.to_owned()
+  } else {
+    toml::Value::String(key.to_owned()).to_string()
+  }
+}
+
+/// Render a QR code in the terminal, with light modules on dark backgrounds.
+#[cfg(feature = "qr")]
+fn print_qr_code(data: &str) {
+  use qrcode::{render::unicode::Dense1x2, QrCode};
+
+  match QrCode::new(data) {
+    Ok(code) => {
+      let image = code
+        .render::<Dense1x2>()
+        .dark_color(Dense1x2::Light)
+        .light_color(Dense1x2::Dark)
+        .build();
+      println!("{}", image);
+    }
+
+    Err(err) => println!("{}", tr!("cannot render QR code: {}", err).red()),
+  }
+}
+
+/// Parse a duration passed on the command line.
+fn parse_duration_arg(s: &str) -> Result<Duration, String> {
+  time::parse_duration(s).map_err(|err| tr!("invalid duration {}: {}", s, err))
+}
+
+fn parse_date_arg(s: &str) -> Result<NaiveDate, String> {
+  NaiveDate::parse_from_str(s, "%Y-%m-%d").map_err(|_| tr!("invalid date {}; use YYYY-MM-DD", s))
+}
+
+/// Average length of a month of the Gregorian calendar, in seconds.
+const MONTH_SECONDS: i64 = 2_629_746;
+
+/// Average length of a year of the Gregorian calendar, in seconds.
+const YEAR_SECONDS: i64 = 12 * MONTH_SECONDS;
+
+/// Number of units a duration amounts to, along with the unit, such as `(3, "d")`.
+///
+/// Durations are shown in the largest unit they have at least one of, except for days, shown until two weeks. Months
+/// and years are the average Gregorian ones, so that ages don’t drift over the months as four-week months would.
+fn duration_in_unit(dur: &Duration) -> (i64, &'static str) {
+  let seconds = dur.num_seconds();
+
+  if dur.num_minutes() < 1 {
+    (seconds, "s")
+  } else if dur.num_hours() < 1 {
+    (dur.num_minutes(), "min")
+  } else if dur.num_days() < 1 {
+    (dur.num_hours(), "h")
+  } else if dur.num_weeks() < 2 {
+    (dur.num_days(), "d")
+  } else if seconds < MONTH_SECONDS {
+    (dur.num_weeks(), "w")
+  } else if seconds < YEAR_SECONDS {
+    (seconds / MONTH_SECONDS, "mth")
+  } else {
+    (seconds / YEAR_SECONDS, "y")
+  }
+}
+
+/// Display width of a grapheme cluster.
+///
+/// Emoji sequences — joined with zero-width joiners or using the emoji presentation selector — are displayed as a
+/// single double-width glyph, while summing the width of their characters would count each emoji of the sequence.
+fn grapheme_width(grapheme: &str) -> usize {
+  if grapheme.contains('\u{fe0f}') {
+    2
+  } else if grapheme.contains('\u{200d}') {
+    grapheme.chars().next().and_then(|c| c.width()).unwrap_or(0)
+  } else {
+    grapheme.width()
+  }
+}
+
+/// Display width of a string, taking double-width characters and emoji sequences into account.
+fn text_width(s: &str) -> usize {
+  s.graphemes(true).map(grapheme_width).sum()
+}
+
+/// Truncate a string to fit in `max_width` columns, ending it with an ellipsis if it doesn’t.
+fn truncate(s: &str, max_width: usize) -> Cow<'_, str> {
+  if text_width(s) <= max_width {
+    return s.into();
+  }
+
+  let mut truncated = String::new();
+  let mut width = 0;
+
+  for grapheme in s.graphemes(true) {
+    width += grapheme_width(grapheme);
+
+    if width + 1 > max_width {
+      break;
+    }
+
+    truncated.push_str(grapheme);
+  }
+
+  if max_width > 0 {
+    truncated.push('…');
+  }
+
+  truncated.into()
+}
+
+/// Wrap a text in lines of at most `width` columns, with at most `max_lines` lines.
+///
+/// Lines are broken between words; words too long to fit on a line — which is common with CJK text, which doesn’t use
+/// blanks between words — are broken between grapheme clusters. If the text doesn’t fit in `max_lines` lines, the last
+/// line ends with an ellipsis (…).
+fn wrap(text: &str, width: usize, max_lines: usize) -> Vec<String> {
+  let mut lines = vec![String::new()];
+  let mut line_width = 0; // width of the current line, i.e. the last one
+  let mut truncated = false;
+
+  'words: for word in text.split_whitespace() {
+    let word_width = text_width(word);
+
+    if line_width > 0 {
+      if line_width + 1 + word_width <= width || word_width > width && line_width + 2 <= width {
+        // the word fits on the current line, or is too long for any line anyway and starts on the current one
+        lines.last_mut().unwrap().push(' ');
+        line_width += 1;
+      } else if lines.len() < max_lines {
+        lines.push(String::new());
+        line_width = 0;
+      } else {
+        truncated = true;
+        break;
+      }
+    }
+
+    for grapheme in word.graphemes(true) {
+      let grapheme_width = grapheme_width(grapheme);
+
+      if line_width + grapheme_width > width {
+        if lines.len() >= max_lines {
+          truncated = true;
+          break 'words;
+        }
+
+        lines.push(String::new());
+        line_width = 0;
+      }
+
+      lines.last_mut().unwrap().push_str(grapheme);
+      line_width += grapheme_width;
+    }
+  }
+
+  if truncated {
+    // make room for the ellipsis
+    let line = lines.last_mut().unwrap();
+    while line_width + 1 > width && !line.is_empty() {
+      let grapheme = line.graphemes(true).next_back().unwrap_or_default();
+      line_width -= grapheme_width(grapheme);
+      line.truncate(line.len() - grapheme.len());
     }
+
+    line.push('…');
   }
+
+  lines
 }
 
 /// Friendly string representation of a date.
@@ -1410,103 +6311,173 @@ pub fn date_time_to_string(date_time: &DateTime<Utc>) -> String {
   date_time.format("%a, %d %b %Y at %H:%M").to_string()
 }
 
-/// Interactively edit a note for a given task.
+/// Note template used when the configuration root has no `note-template.md` file: the previous notes, if any, followed
+/// by an empty line for the new note.
+const DEFAULT_NOTE_TEMPLATE: &str = "{history}\n";
+
+/// Previously recorded notes of a task, commented out with `prefix` so that they are discarded from the new note.
+fn previous_notes_help(task: &Task, prefix: &str) -> String {
+  let notes = task
+    .notes()
+    .into_iter()
+    .enumerate()
+    .map(|(i, note)| {
+      let modified_date_str = if note.last_modification_date >= note.creation_date {
+        tr!(
+          ", modified on {}",
+          date_time_to_string(&note.last_modification_date)
+        )
+      } else {
+        String::new()
+      };
+
+      tr!(
+        "Note #{}, on {}{}\n{}\n\n",
+        i + 1,
+        date_time_to_string(&note.creation_date),
+        modified_date_str,
+        note.content.trim()
+      )
+    })
+    .collect::<String>();
+  let help = tr!(
+    "Above are the previously recorded notes. Lines starting with {} are discarded.",
+    prefix
+  );
+
+  notes
+    .lines()
+    .chain(std::iter::once(help.as_str()))
+    .map(|line| {
+      if line.is_empty() {
+        format!("{}\n", prefix)
+      } else {
+        format!("{} {}\n", prefix, line)
+      }
+    })
+    .collect()
+}
+
+/// Remove the lines commented out with `prefix` from a note, along with the blank lines around the rest of the note.
 ///
-/// The note will be pre-populated by the note history if the config allows for it. The `prefill` argument allows to
-/// pre-populate the content of the note.
+/// Comments may be indented, as some editors do when reflowing lines; lines merely containing `prefix` are kept.
+fn strip_note_comments(content: &str, prefix: &str) -> String {
+  let lines = content
+    .lines()
+    .filter(|line| prefix.is_empty() || !line.trim_start().starts_with(prefix))
+    .collect::<Vec<_>>();
+  let is_blank = |line: &&str| line.trim().is_empty();
+  let start = lines.iter().position(|line| !is_blank(line));
+  let end = lines.iter().rposition(|line| !is_blank(line));
+
+  match (start, end) {
+    (Some(start), Some(end)) => lines[start..=end].join("\n"),
+    _ => String::new(),
+  }
+}
+
+/// Render a note template, replacing its `{task_name}`, `{project}`, `{date}` and `{history}` placeholders.
 ///
-/// The note is returned as a [`String`].
-fn interactively_edit_note(
+/// Unknown placeholders are left as-is.
+fn render_note_template(template: &str, task: &Task, date: NaiveDate, history: &str) -> String {
+  let mut output = String::with_capacity(template.len());
+  let mut rest = template;
+
+  while let Some(start) = rest.find('{') {
+    output += &rest[..start];
+    rest = &rest[start..];
+
+    let end = rest.find('}').map_or(rest.len(), |end| end + 1);
+    match &rest[..end] {
+      "{task_name}" => output += task.name(),
+      "{project}" => output += task.project().unwrap_or_default(),
+      "{date}" => output += &date.to_string(),
+      "{history}" => output += history,
+      _ => {
+        // not a placeholder; keep the brace and look for one right after it
+        output.push('{');
+        rest = &rest[1..];
+        continue;
+      }
+    }
+    rest = &rest[end..];
+  }
+
+  output + rest
+}
+
+/// Read the note template of the configuration root, falling back to [`DEFAULT_NOTE_TEMPLATE`] if there is none.
+fn note_template(config: &Config) -> Result<String, Error> {
+  let path = config.note_template_path();
+
+  match fs::read_to_string(&path) {
+    Err(err) if err.kind() == io::ErrorKind::NotFound => Ok(DEFAULT_NOTE_TEMPLATE.to_owned()),
+    content => content.map_err(Error::io(Operation::Read, &path)),
+  }
+}
+
+/// Content of the editor when writing a note for a given task.
+///
+/// The note will be pre-populated by the note history if the config allows for it, commented out with the configured
+/// comment prefix. The `prefill` argument is the content of the note being edited; a new note, without `prefill`, is
+/// pre-populated with the note template instead.
+fn note_buffer(
   config: &Config,
   with_history: bool,
   task: &Task,
-  prefill: &str,
+  prefill: Option<&str>,
 ) -> Result<String, SubCmdError> {
-  let prefill = if with_history {
-    // if we have the previously recorded note help, pre-populate the file with the previous notes
-    let mut new_prefill = task
-      .notes()
-      .into_iter()
-      .enumerate()
-      .map(|(i, note)| {
-        let modified_date_str = if note.last_modification_date >= note.creation_date {
-          format!(
-            ", modified on {}",
-            date_time_to_string(&note.last_modification_date)
-          )
-        } else {
-          String::new()
-        };
-
-        format!(
-          "> Note #{nb}, on {creation_date}{modification_date}\n{content}",
-          nb = i + 1,
-          creation_date = date_time_to_string(&note.creation_date),
-          modification_date = modified_date_str,
-          content = note.content,
-        )
-      })
-      .join("\n\n");
+  let prefix = config.note_comment_prefix();
 
-    new_prefill +=
-      "> Above are the previously recorded notes. You are free to temper with them if you want.\n";
-    new_prefill += "> You can add the content of your note under the following line. However, do not remove this line!\n";
-    new_prefill += PREVIOUS_NOTES_HELP_END_MARKER;
-    new_prefill += prefill;
-    new_prefill
+  // without prefix, the history couldn’t be told apart from the note
+  let history = if with_history && !prefix.is_empty() {
+    previous_notes_help(task, prefix)
   } else {
-    prefill.to_owned()
+    String::new()
   };
 
-  let note_content = interactively_edit(config, "NEW_NOTE.md", &prefill)?;
+  match prefill {
+    Some(prefill) => Ok(history + prefill),
+    None => Ok(render_note_template(
+      &note_template(config)?,
+      task,
+      Local::today().naive_local(),
+      &history,
+    )),
+  }
+}
 
-  if with_history {
-    if let Some(marker_index) = note_content.find(PREVIOUS_NOTES_HELP_END_MARKER) {
-      let content = note_content
-        .get(marker_index + PREVIOUS_NOTES_HELP_END_MARKER.len()..)
-        .unwrap();
+/// Note written in the editor, its lines starting with the comment prefix removed.
+fn read_note(config: &Config, content: &str) -> Result<String, SubCmdError> {
+  let note = strip_note_comments(content, config.note_comment_prefix());
 
-      if content.trim().is_empty() {
-        Err(SubCmdError::EmptyNote)
-      } else {
-        Ok(content.to_owned())
-      }
-    } else {
-      Err(SubCmdError::CannotEditNote(
-        "I told you not to temper with this line!".to_owned(),
-      ))
-    }
+  if note.is_empty() {
+    Err(SubCmdError::EmptyNote)
   } else {
-    if note_content.trim().is_empty() {
-      Err(SubCmdError::EmptyNote)
-    } else {
-      Ok(note_content)
-    }
+    Ok(note)
   }
 }
 
+/// Interactively edit the note `prefill` of a given task.
+///
+/// The note is returned as a [`String`].
+fn interactively_edit_note(
+  config: &Config,
+  with_history: bool,
+  task: &Task,
+  prefill: &str,
+) -> Result<String, SubCmdError> {
+  let buffer = note_buffer(config, with_history, task, Some(prefill))?;
+  read_note(config, &interactively_edit(config, "NEW_NOTE.md", &buffer)?)
+}
+
 #[cfg(test)]
 mod unit_tests {
   use super::*;
 
+  use crate::term::TestTerm;
   use toodoux::config::{ColorConfig, MainConfig};
 
-  struct DummyTerm {
-    dimensions: [usize; 2],
-  }
-
-  impl DummyTerm {
-    pub fn new(dimensions: [usize; 2]) -> Self {
-      Self { dimensions }
-    }
-  }
-
-  impl Terminal for DummyTerm {
-    fn dimensions(&self) -> Option<[usize; 2]> {
-      Some(self.dimensions)
-    }
-  }
-
   #[test]
   fn guess_number_width() {
     for i in 0..10 {
@@ -1540,6 +6511,74 @@ mod unit_tests {
       DisplayOptions::guess_duration_width(&Duration::minutes(59)),
       5
     ); // 59min
+    assert_eq!(
+      DisplayOptions::guess_duration_width(&Duration::days(300)),
+      4
+    ); // 9mth
+    assert_eq!(
+      DisplayOptions::guess_duration_width(&Duration::days(3660)),
+      3
+    ); // 10y
+  }
+
+  #[test]
+  fn duration_in_unit() {
+    let unit = |dur| super::duration_in_unit(&dur);
+
+    assert_eq!(unit(Duration::seconds(59)), (59, "s"));
+    assert_eq!(unit(Duration::seconds(60)), (1, "min"));
+    assert_eq!(unit(Duration::minutes(59)), (59, "min"));
+    assert_eq!(unit(Duration::minutes(60)), (1, "h"));
+    assert_eq!(unit(Duration::hours(23)), (23, "h"));
+    assert_eq!(unit(Duration::hours(24)), (1, "d"));
+    assert_eq!(unit(Duration::days(13)), (13, "d"));
+    assert_eq!(unit(Duration::days(14)), (2, "w"));
+    assert_eq!(unit(Duration::days(30)), (4, "w"));
+    assert_eq!(unit(Duration::days(31)), (1, "mth"));
+    assert_eq!(unit(Duration::days(60)), (1, "mth"));
+    assert_eq!(unit(Duration::days(61)), (2, "mth"));
+    assert_eq!(unit(Duration::days(364)), (11, "mth"));
+    assert_eq!(unit(Duration::days(365)), (11, "mth"));
+    assert_eq!(unit(Duration::days(366)), (1, "y"));
+    assert_eq!(unit(Duration::days(730)), (1, "y"));
+    assert_eq!(unit(Duration::days(731)), (2, "y"));
+  }
+
+  #[test]
+  fn description_lines() {
+    let lines = |main: &str, term_dims, tasks_nb| {
+      let config: Config =
+        toml::from_str(&format!("[main]\nmax_description_lines = 3\n{}", main)).unwrap();
+      DisplayOptions::guess_description_lines(&config, term_dims, tasks_nb)
+    };
+
+    assert_eq!(lines("", Some([100, 40]), 10), 3);
+    assert_eq!(
+      lines("description_overflow = \"wrap\"", Some([100, 40]), 10),
+      usize::MAX
+    );
+    assert_eq!(
+      lines("description_overflow = \"fit\"", Some([100, 40]), 5),
+      3
+    );
+    assert_eq!(
+      lines("description_overflow = \"fit\"", Some([100, 40]), 19),
+      2
+    );
+    assert_eq!(
+      lines("description_overflow = \"fit\"", Some([100, 40]), 100),
+      1
+    );
+    assert_eq!(lines("description_overflow = \"fit\"", None, 100), 3);
+    assert_eq!(lines("single_line_below_width = 80", Some([60, 40]), 10), 1);
+    assert_eq!(
+      lines(
+        "description_overflow = \"wrap\"\nsingle_line_below_width = 80",
+        Some([100, 40]),
+        10
+      ),
+      usize::MAX
+    );
   }
 
   #[test]
@@ -1547,7 +6586,7 @@ mod unit_tests {
     let main_config = MainConfig::default();
     let config = Config::new(main_config, ColorConfig::default());
     let tasks = &[(UID::default(), &Task::new("Foo"))];
-    let term = DummyTerm::new([100, 1]);
+    let term = TestTerm::new();
     let opts = DisplayOptions::new(&config, &term, tasks.iter().copied());
 
     let description_offset = " UID ".len() + "Age ".len() + "Status ".len();
@@ -1558,12 +6597,252 @@ mod unit_tests {
     );
   }
 
+  #[test]
+  fn plain_task_row() {
+    let config = Config::new(MainConfig::default(), ColorConfig::default());
+    let cli = CLI::new(config, TestTerm::new(), true, false);
+    let mut task = Task::new("Foo bar");
+    task.apply_metadata(vec![
+      Metadata::project("toodoux"),
+      Metadata::priority(Priority::new("High")),
+      Metadata::context("computer"),
+      Metadata::milestone("v2"),
+      Metadata::tag("a"),
+      Metadata::tag("b"),
+    ]);
+
+    assert_eq!(
+      cli.plain_task_row(UID::default(), &task, "\t"),
+      "0\t0s\t\tHIGH\ttoodoux\tcomputer\tv2\ta,b\t\t\tTODO\tFoo bar"
+    );
+    assert_eq!(
+      cli.plain_task_row(UID::default(), &task, " "),
+      "0 0s  HIGH toodoux computer v2 a,b   TODO Foo bar"
+    );
+  }
+
+  #[test]
+  fn hyperlinked_task_row() {
+    let config = |hyperlinks| -> Config {
+      toml::from_str(&format!("[main]\nhyperlinks = \"{}\"", hyperlinks)).unwrap()
+    };
+    let task = Task::new("Foo");
+    let tasks = &[(UID::default(), &task)];
+
+    let cli = CLI::new(config("always"), TestTerm::new(), false, false);
+    let opts = DisplayOptions::new(&cli.config, &cli.term, tasks.iter().copied());
+    let mut row = String::new();
+    cli
+      .display_task_inline(&mut row, UID::default(), &task, &opts)
+      .unwrap();
+    assert!(row.contains(&term::hyperlink("toodoux://task/0", 0)));
+
+    // the terminal decides by default, and plain output never has hyperlinks
+    assert!(!CLI::new(config("auto"), TestTerm::new(), false, false).hyperlinks);
+    assert!(!CLI::new(config("always"), TestTerm::new(), true, false).hyperlinks);
+  }
+
+  #[test]
+  fn truncate_to_width() {
+    assert_eq!(truncate("toodoux", 7), "toodoux");
+    assert_eq!(truncate("toodoux", 5), "tood…");
+    assert_eq!(truncate("日本語のプロジェクト", 7), "日本語…");
+    assert_eq!(truncate("toodoux", 0), "");
+  }
+
+  #[test]
+  fn truncated_cells() {
+    let config: Config =
+      toml::from_str("[main]\nmax_project_width = 8\nmax_tags = 2\nmax_tags_width = 10").unwrap();
+    let mut task = Task::new("Foo");
+    task.apply_metadata(vec![
+      Metadata::project("super-cool-project"),
+      Metadata::tag("a"),
+      Metadata::tag("b"),
+      Metadata::tag("c"),
+    ]);
+
+    assert_eq!(DisplayOptions::project_cell(&config, &task), "super-c…");
+    assert_eq!(DisplayOptions::tags_cell(&config, &task), "a, b +1");
+
+    task.apply_metadata(vec![Metadata::tag("documentation")]);
+    assert_eq!(DisplayOptions::tags_cell(&config, &task), "a, b +2");
+
+    let config: Config = toml::from_str("[main]\nmax_tags = 0").unwrap();
+    assert_eq!(DisplayOptions::tags_cell(&config, &task), "+4");
+  }
+
+  #[test]
+  fn wrap_descriptions() {
+    // ASCII
+    assert_eq!(wrap("Write the doc", 13, 2), vec!["Write the doc"]);
+    assert_eq!(wrap("Write the doc", 12, 2), vec!["Write the", "doc"]);
+    assert_eq!(
+      wrap("Write the documentation now", 12, 2),
+      vec!["Write the do", "cumentation…"]
+    );
+    assert_eq!(wrap("Write the doc", 12, 1), vec!["Write the…"]);
+
+    // CJK: double-width characters, without blanks between words
+    assert_eq!(
+      wrap("ドキュメントを書く", 8, 3),
+      vec!["ドキュメ", "ントを書", "く"]
+    );
+    assert_eq!(
+      wrap("ドキュメントを書く", 8, 2),
+      vec!["ドキュメ", "ントを…"]
+    );
+    assert_eq!(
+      wrap("Fix 日本語のテキスト", 7, 3),
+      vec!["Fix 日", "本語の", "テキス…"]
+    );
+
+    // emoji sequences are not split and count as double-width
+    assert_eq!(text_width("👨‍👩‍👧 ❤️ é"), 7);
+    assert_eq!(wrap("🎉🎉🎉 party 👨‍👩‍👧", 6, 3), vec!["🎉🎉🎉", "party", "👨‍👩‍👧"]);
+
+    // every line fits
+    for width in 2..20 {
+      for line in wrap("Mixed 幅の text with 絵文字 🎉 and more", width, 10) {
+        assert!(
+          text_width(&line) <= width,
+          "{:?} is wider than {}",
+          line,
+          width
+        );
+      }
+    }
+  }
+
+  #[test]
+  fn listing_sections() {
+    let now = Utc::now();
+    let horizon = Duration::days(3);
+    let task = |status, deadline: Option<Duration>| {
+      let mut task = Task::new("Foo");
+      task.change_status(status);
+      if let Some(deadline) = deadline {
+        task.set_deadline(now + deadline);
+      }
+      task
+    };
+    let section = |task: &Task| ListingSection::of(task, now, horizon);
+
+    assert_eq!(
+      section(&task(Status::Todo, Some(Duration::hours(-1)))),
+      ListingSection::Overdue
+    );
+    assert_eq!(
+      section(&task(Status::Ongoing, Some(Duration::hours(-1)))),
+      ListingSection::Overdue
+    );
+    assert_eq!(
+      section(&task(Status::Todo, Some(Duration::days(2)))),
+      ListingSection::DueSoon
+    );
+    assert_eq!(
+      section(&task(Status::Paused, Some(Duration::days(4)))),
+      ListingSection::InProgress
+    );
+    assert_eq!(
+      section(&task(Status::Ongoing, None)),
+      ListingSection::InProgress
+    );
+    assert_eq!(
+      section(&task(Status::Todo, Some(Duration::days(4)))),
+      ListingSection::Other
+    );
+    assert_eq!(section(&task(Status::Todo, None)), ListingSection::Other);
+  }
+
+  #[test]
+  fn paginate() {
+    let tasks = (0..5).collect::<Vec<_>>();
+    let paginate = |limit, page| CLI::<TestTerm>::paginate(&tasks, limit, page);
+
+    assert_eq!(paginate(None, 1), (&tasks[..], 0));
+    assert_eq!(paginate(Some(2), 1), (&tasks[0..2], 3));
+    assert_eq!(paginate(Some(2), 0), (&tasks[0..2], 3));
+    assert_eq!(paginate(Some(2), 3), (&tasks[4..], 0));
+    assert_eq!(paginate(Some(2), 4), (&tasks[5..], 0));
+    assert_eq!(paginate(Some(10), 1), (&tasks[..], 0));
+  }
+
+  #[test]
+  fn stripe_row() {
+    assert_eq!(
+      CLI::<TestTerm>::stripe_row(" 1 \x1b[33mfoo\x1b[0m bar\n   baz\n", "48;5;236"),
+      "\x1b[48;5;236m 1 \x1b[33mfoo\x1b[0m\x1b[48;5;236m bar\x1b[0m\n\x1b[48;5;236m   baz\x1b[0m\n"
+    );
+  }
+
+  #[test]
+  fn confirm() {
+    let new_cli = |input: &[&'static str], yes| {
+      let config = Config::new(MainConfig::default(), ColorConfig::default());
+      let term = TestTerm::new().with_input(input.iter().copied());
+      CLI::new(config, term, true, yes)
+    };
+
+    assert!(new_cli(&["y"], false).confirm("?"));
+    assert!(new_cli(&["yes"], false).confirm("?"));
+    assert!(!new_cli(&["n"], false).confirm("?"));
+    assert!(!new_cli(&[""], false).confirm("?"));
+    assert!(!new_cli(&[], false).confirm("?"));
+    assert!(new_cli(&[], true).confirm("?"));
+  }
+
+  #[test]
+  fn correct_metadata_typos() {
+    let mut task_mgr = TaskManager::in_memory();
+    let mut task = Task::new("Write the docs");
+    task.apply_metadata(vec![Metadata::project("work"), Metadata::tag("docs")]);
+    task_mgr.register_task(task);
+
+    let corrected = |input: &[&'static str], yes| {
+      let config = Config::new(MainConfig::default(), ColorConfig::default());
+      let term = TestTerm::new().with_input(input.iter().copied());
+      let mut metadata = vec![
+        Metadata::project("wrok"),
+        Metadata::tag("doc"),
+        Metadata::tag("new"),
+      ];
+      CLI::new(config, term, true, yes).correct_metadata_typos(&task_mgr, &mut metadata);
+      metadata
+    };
+
+    assert_eq!(
+      corrected(&["y", ""], false),
+      vec![
+        Metadata::project("work"),
+        Metadata::tag("docs"),
+        Metadata::tag("new")
+      ]
+    );
+    assert_eq!(
+      corrected(&["n", "y"], false),
+      vec![
+        Metadata::project("wrok"),
+        Metadata::tag("docs"),
+        Metadata::tag("new")
+      ]
+    );
+    assert_eq!(
+      corrected(&[], true),
+      vec![
+        Metadata::project("wrok"),
+        Metadata::tag("doc"),
+        Metadata::tag("new")
+      ]
+    );
+  }
+
   #[test]
   fn display_options_should_yield_no_description_if_too_short() {
     let main_config = MainConfig::default();
     let config = Config::new(main_config, ColorConfig::default());
     let tasks = &[(UID::default(), &Task::new("Foo"))];
-    let term = DummyTerm::new([100, 1]);
+    let term = TestTerm::new();
     let opts = DisplayOptions::new(&config, &term, tasks.iter().copied());
 
     let description_offset = " UID ".len() + "Age ".len() + "Status ".len();
@@ -1573,4 +6852,382 @@ mod unit_tests {
       Some(term.dimensions().unwrap()[0] - description_offset)
     );
   }
+
+  #[test]
+  fn display_options_without_dimensions() {
+    let config = Config::new(MainConfig::default(), ColorConfig::default());
+    let tasks = &[(UID::default(), &Task::new("Foo"))];
+    let term = TestTerm::new().with_dimensions(None).with_tty(false);
+    let opts = DisplayOptions::new(&config, &term, tasks.iter().copied());
+
+    assert_eq!(opts.max_description_cols, None);
+  }
+
+  /// Run `td remove --all` on tasks stored in memory, answering the confirmation prompt with `answer`, and
+  /// return the number of remaining tasks.
+  fn remove_all_with_answer(answer: &str) -> usize {
+    let config = Config::default();
+    let mut task_mgr = TaskManager::in_memory();
+    task_mgr.register_task(Task::new("Foo"));
+    task_mgr.register_task(Task::new("Bar"));
+
+    let term = TestTerm::new().with_input(vec![answer]);
+    let mut cli = CLI::new(config, term, true, false);
+    cli
+      .run(
+        &mut task_mgr,
+        Some(SubCommand::Remove { all: true }),
+        Vec::new(),
+      )
+      .unwrap();
+
+    assert_eq!(cli.term.remaining_input(), 0);
+    task_mgr.tasks().count()
+  }
+
+  #[test]
+  fn remove_all_asks_confirmation() {
+    assert_eq!(remove_all_with_answer("n"), 2);
+    assert_eq!(remove_all_with_answer("y"), 0);
+  }
+
+  #[test]
+  fn empty_trash_asks_confirmation() {
+    let mut task_mgr = TaskManager::in_memory();
+    for name in &["Foo", "Bar"] {
+      let uid = task_mgr.register_task(Task::new(*name));
+      task_mgr.remove_task(uid).unwrap();
+    }
+
+    let empty = || SubCommand::Trash(TrashCommand::Empty { all: true });
+    let term = TestTerm::new().with_input(vec!["n", "y"]);
+    let mut cli = CLI::new(Config::default(), term, true, false);
+
+    cli.run(&mut task_mgr, Some(empty()), Vec::new()).unwrap();
+    assert_eq!(task_mgr.trashed_tasks().count(), 2);
+
+    cli.run(&mut task_mgr, Some(empty()), Vec::new()).unwrap();
+    assert_eq!(task_mgr.trashed_tasks().count(), 0);
+    assert_eq!(cli.term.remaining_input(), 0);
+  }
+
+  #[test]
+  fn duplicates_need_force() {
+    let mut task_mgr = TaskManager::in_memory();
+    let milk = task_mgr.register_task(Task::new("Buy milk"));
+    let add = |force| SubCommand::Add {
+      start: false,
+      done: false,
+      note: false,
+      message: Vec::new(),
+      force,
+      someday: false,
+      from_md: None,
+      from_email: None,
+      content: vec!["Buy milk".to_owned()],
+    };
+
+    // neither --yes nor a lack of input accept a duplicate
+    for yes in &[true, false] {
+      let mut cli = CLI::new(Config::default(), TestTerm::new(), true, *yes);
+      let err = cli
+        .run(&mut task_mgr, Some(add(false)), Vec::new())
+        .unwrap_err();
+      assert_eq!(
+        err.to_string(),
+        format!(
+          "the task looks like a duplicate of #{}; pass --force to add it anyway",
+          milk
+        )
+      );
+      assert_eq!(task_mgr.tasks().count(), 1);
+    }
+
+    let mut cli = CLI::new(Config::default(), TestTerm::new(), true, true);
+    cli.run(&mut task_mgr, Some(add(true)), Vec::new()).unwrap();
+    assert_eq!(task_mgr.tasks().count(), 2);
+  }
+
+  #[test]
+  fn check_item_numbers() {
+    let mut task_mgr = TaskManager::in_memory();
+    let mut task = Task::new("Pack");
+    task.add_checklist_item("tent");
+    task.add_checklist_item("stove");
+    task.add_checklist_item("map");
+    let uid = task_mgr.register_task(task);
+    let mut cli = CLI::new(Config::default(), TestTerm::new(), true, false);
+    let check = |item: &str| SubCommand::Check {
+      item_uid: Some(item.parse().unwrap()),
+      subcmd: None,
+    };
+
+    let err = cli
+      .run(&mut task_mgr, Some(check("0")), vec![uid])
+      .unwrap_err();
+    assert_eq!(err.exit_code(), ExitCode::Usage);
+    assert_eq!(err.to_string(), "checklist items are numbered from 1");
+    assert_eq!(
+      task_mgr.get(uid).unwrap().checklist_progress(),
+      Some((0, 3))
+    );
+
+    let err = cli
+      .run(&mut task_mgr, Some(check("5")), vec![uid])
+      .unwrap_err();
+    assert_eq!(err.to_string(), "checklist item 5 doesn’t exist");
+  }
+
+  #[test]
+  fn exit_codes() {
+    let mut task_mgr = TaskManager::in_memory();
+    task_mgr.register_task(Task::new("Foo"));
+    let mut cli = CLI::new(Config::default(), TestTerm::new(), true, false);
+
+    let err = cli
+      .run(
+        &mut task_mgr,
+        Some(SubCommand::Start),
+        vec!["42".parse().unwrap()],
+      )
+      .unwrap_err();
+    assert_eq!(err.exit_code(), ExitCode::UnknownTask);
+    assert_eq!(err.to_string(), "missing or unknown task to start");
+
+    let err = cli
+      .run(
+        &mut task_mgr,
+        Some(SubCommand::Show {
+          short: true,
+          full: false,
+        }),
+        vec!["0".parse().unwrap(), "42".parse().unwrap()],
+      )
+      .unwrap_err();
+    assert_eq!(err.exit_code(), ExitCode::UnknownTask);
+    assert_eq!(err.to_string(), "task 42 doesn’t exist");
+
+    let err = cli
+      .run(
+        &mut task_mgr,
+        Some(SubCommand::Start),
+        vec!["0".parse().unwrap(), "0".parse().unwrap()],
+      )
+      .unwrap_err();
+    assert_eq!(err.exit_code(), ExitCode::Usage);
+
+    let query = |fail_if_empty, filter: &str| SubCommand::Query {
+      format: "{uid}".to_owned(),
+      all: false,
+      case_insensitive: false,
+      fail_if_empty,
+      metadata_filter: vec![filter.to_owned()],
+    };
+    assert!(cli
+      .run(&mut task_mgr, Some(query(true, "Foo")), Vec::new())
+      .is_ok());
+    assert!(cli
+      .run(&mut task_mgr, Some(query(false, "Bar")), Vec::new())
+      .is_ok());
+    let err = cli
+      .run(&mut task_mgr, Some(query(true, "Bar")), Vec::new())
+      .unwrap_err();
+    assert_eq!(err.exit_code(), ExitCode::Empty);
+
+    let err = SubCmdError::from(Error::io(Operation::SaveTasks, "tasks.json")(
+      io::Error::from(io::ErrorKind::PermissionDenied),
+    ));
+    assert_eq!(err.exit_code(), ExitCode::Storage);
+    assert_eq!(
+      SubCmdError::UnknownFormat("xml".to_owned()).exit_code(),
+      ExitCode::Usage
+    );
+  }
+
+  #[test]
+  fn review_someday_tasks() {
+    let dir = tempdir::TempDir::new("toodoux").unwrap();
+    Config::create(Some(dir.path())).unwrap().save().unwrap();
+    let config_path = dir.path().join("config.toml");
+    let config = fs::read_to_string(&config_path).unwrap();
+    fs::write(
+      &config_path,
+      config.replace("review_interval_days = 7", "review_interval_days = 0"),
+    )
+    .unwrap();
+    let config = Config::from_dir(dir.path()).unwrap().unwrap();
+
+    let mut task_mgr = TaskManager::new_from_config(&config).unwrap();
+    let uids = ["Kept", "Activated", "Cancelled", "Skipped", "Left"]
+      .iter()
+      .map(|name| {
+        let mut task = Task::new(*name);
+        task.set_someday(true);
+        task_mgr.register_task(task)
+      })
+      .collect::<Vec<_>>();
+
+    let term = TestTerm::new().with_input(vec!["k", "a", "c", "s", "q"]);
+    let mut cli = CLI::new(config, term, true, false);
+    cli
+      .run(&mut task_mgr, Some(SubCommand::Review), Vec::new())
+      .unwrap();
+    assert_eq!(cli.term.remaining_input(), 0);
+
+    let task = |i: usize| task_mgr.get(uids[i]).unwrap();
+    assert!(task(0).is_someday());
+    assert!(task(0)
+      .history()
+      .any(|event| matches!(event, Event::Reviewed { .. })));
+    assert!(!task(1).is_someday());
+    assert_eq!(task(2).status(), Status::Cancelled);
+    assert!(task(3).is_someday() && task(4).is_someday());
+    assert!(!task(3)
+      .history()
+      .any(|event| matches!(event, Event::Reviewed { .. })));
+  }
+
+  #[test]
+  fn triage_tasks() {
+    let dir = tempdir::TempDir::new("toodoux").unwrap();
+    Config::create(Some(dir.path())).unwrap().save().unwrap();
+    let config = Config::from_dir(dir.path()).unwrap().unwrap();
+
+    let mut task_mgr = TaskManager::new_from_config(&config).unwrap();
+    let mut estimated = Task::new("Estimated");
+    estimated.set_priority(Priority::new("Low"));
+    estimated.set_effort(Duration::hours(1));
+    let events = estimated.history().count();
+    let estimated = task_mgr.register_task(estimated);
+    let uids = ["Both", "Retried", "Skipped", "Left"]
+      .iter()
+      .map(|name| task_mgr.register_task(Task::new(*name)))
+      .collect::<Vec<_>>();
+
+    let term =
+      TestTerm::new().with_input(vec!["h", "2h", "x", "M", "soon", "30min", "", "", "c", "q"]);
+    let mut cli = CLI::new(config, term, true, false);
+    let triage = SubCommand::Triage {
+      case_insensitive: false,
+      metadata_filter: Vec::new(),
+    };
+    cli.run(&mut task_mgr, Some(triage), Vec::new()).unwrap();
+    assert_eq!(cli.term.remaining_input(), 0);
+
+    let task = |i: usize| task_mgr.get(uids[i]).unwrap();
+    assert_eq!(task(0).priority(), Some(&Priority::new("High")));
+    assert_eq!(task(0).effort(), Some(Duration::hours(2)));
+    assert_eq!(task(1).priority(), Some(&Priority::new("Medium")));
+    assert_eq!(task(1).effort(), Some(Duration::minutes(30)));
+    assert!(task(2).priority().is_none() && task(2).effort().is_none());
+    assert_eq!(task(3).priority(), Some(&Priority::new("Critical")));
+    assert!(task(3).effort().is_none());
+    assert_eq!(task_mgr.get(estimated).unwrap().history().count(), events);
+  }
+
+  #[test]
+  fn roulette() {
+    let dir = tempdir::TempDir::new("toodoux").unwrap();
+    Config::create(Some(dir.path())).unwrap().save().unwrap();
+    let config = Config::from_dir(dir.path()).unwrap().unwrap();
+
+    let mut task_mgr = TaskManager::new_from_config(&config).unwrap();
+    let first = task_mgr.register_task(Task::new("First"));
+    let second = task_mgr.register_task(Task::new("Second"));
+    let roulette = |filter: &[&str]| SubCommand::Roulette {
+      weighted: true,
+      case_insensitive: false,
+      metadata_filter: filter.iter().map(|word| word.to_string()).collect(),
+    };
+
+    let term = TestTerm::new().with_input(vec!["r", "r"]);
+    let mut cli = CLI::new(config, term, true, false);
+    cli
+      .run(&mut task_mgr, Some(roulette(&[])), Vec::new())
+      .unwrap();
+    assert_eq!(cli.term.remaining_input(), 0);
+    assert_eq!(task_mgr.get(first).unwrap().status(), Status::Todo);
+    assert_eq!(task_mgr.get(second).unwrap().status(), Status::Todo);
+
+    cli.term = TestTerm::new().with_input(vec!["s"]);
+    cli
+      .run(&mut task_mgr, Some(roulette(&["Second"])), Vec::new())
+      .unwrap();
+    assert_eq!(cli.term.remaining_input(), 0);
+    assert_eq!(task_mgr.get(first).unwrap().status(), Status::Todo);
+    assert_eq!(task_mgr.get(second).unwrap().status(), Status::Ongoing);
+  }
+
+  #[test]
+  fn weighted_pick() {
+    let pick = |roll| CLI::<TestTerm>::weighted_pick(vec![2, 0, 3], roll);
+
+    assert_eq!(pick(0), 0);
+    assert_eq!(pick(1), 0);
+    assert_eq!(pick(2), 2);
+    assert_eq!(pick(4), 2);
+  }
+
+  #[test]
+  fn note_templates() {
+    let mut task = Task::new("Fix the {date} parser");
+    task.set_project("toodoux");
+    let date = NaiveDate::from_ymd(2021, 6, 10);
+    let render = |template| render_note_template(template, &task, date, "> history\n");
+
+    assert_eq!(render(DEFAULT_NOTE_TEMPLATE), "> history\n\n");
+    assert_eq!(
+      render("# {task_name} ({project}), {date}\n{history}"),
+      "# Fix the {date} parser (toodoux), 2021-06-10\n> history\n"
+    );
+    assert_eq!(
+      render("{unknown} {{date}} {date"),
+      "{unknown} {2021-06-10} {date"
+    );
+  }
+
+  #[test]
+  fn previous_notes_help_is_commented_out() {
+    let mut task = Task::new("Foo");
+    task.add_note("first\n\nwith a blank line");
+    task.add_note("%% starts with the prefix");
+
+    let help = previous_notes_help(&task, "%%");
+    assert!(help.lines().all(|line| line.starts_with("%%")));
+    assert!(help.contains("%% first\n%%\n%% with a blank line\n"));
+    assert_eq!(
+      strip_note_comments(&(help + "\nnew note\n"), "%%"),
+      "new note"
+    );
+  }
+
+  #[test]
+  fn strip_note_comments_from_editor_output() {
+    let strip = |content| strip_note_comments(content, "%%");
+
+    // untouched note, with and without final newline
+    assert_eq!(strip("%% help\n\nnote\n"), "note");
+    assert_eq!(strip("%% help\nnote"), "note");
+
+    // Windows line endings
+    assert_eq!(
+      strip("%% help\r\n\r\nline 1\r\nline 2\r\n"),
+      "line 1\nline 2"
+    );
+
+    // comments reflowed with an indentation, or moved after the note
+    assert_eq!(strip("  %% help\n\t%% more help\nnote\n%% help"), "note");
+
+    // blank lines inside the note are kept, the prefix is only stripped at the start of lines
+    assert_eq!(
+      strip("\n\nfirst\n\n  indented, 100%% sure\n\n\n"),
+      "first\n\n  indented, 100%% sure"
+    );
+
+    // only comments and blank lines
+    assert_eq!(strip("%% help\n   \n%%\n"), "");
+
+    // without prefix, nothing is a comment
+    assert_eq!(strip_note_comments("%% note\n", ""), "%% note");
+  }
 }
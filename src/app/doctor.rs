@@ -0,0 +1,535 @@
+//! Diagnostics of the configuration, the tasks and the environment.
+
+use crate::{
+  interactive_editor::{self, InteractiveEditingError},
+  term::Terminal,
+};
+use colored::Colorize as _;
+use std::{env, fmt::Display, fs, io, path::Path};
+use toodoux::{
+  config::Config,
+  error::{Error, Report as ErrorReport},
+  i18n,
+  task::{IntegrityIssue, TaskManager},
+  tr,
+};
+
+/// Severity of a diagnostic.
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+enum Severity {
+  Ok,
+  Warning,
+  Error,
+}
+
+/// Diagnostics report, printed as it’s being built.
+#[derive(Debug, Default)]
+struct Report {
+  warnings: usize,
+  errors: usize,
+}
+
+impl Report {
+  fn section(&self, name: &str) {
+    println!("{}", name.bold());
+  }
+
+  fn ok(&mut self, msg: impl Display) {
+    self.diagnostic(Severity::Ok, msg, None::<&str>);
+  }
+
+  fn warning(&mut self, msg: impl Display, fix: impl Display) {
+    self.diagnostic(Severity::Warning, msg, Some(fix));
+  }
+
+  fn error(&mut self, msg: impl Display, fix: impl Display) {
+    self.diagnostic(Severity::Error, msg, Some(fix));
+  }
+
+  fn diagnostic(&mut self, severity: Severity, msg: impl Display, fix: Option<impl Display>) {
+    let severity = match severity {
+      Severity::Ok => tr!("ok").green(),
+
+      Severity::Warning => {
+        self.warnings += 1;
+        tr!("warning").yellow()
+      }
+
+      Severity::Error => {
+        self.errors += 1;
+        tr!("error").red().bold()
+      }
+    };
+
+    println!("  {:<7} {}", severity, msg);
+
+    if let Some(fix) = fix {
+      println!("  {:<7} {} {}", "", tr!("fix:").bright_black(), fix);
+    }
+  }
+}
+
+/// Run all the diagnostics on the configuration in `dir` and print them.
+///
+/// The configuration and the tasks are read here rather than beforehand, so that what prevents them from being loaded
+/// can be diagnosed.
+pub fn run(dir: &Path, term: &impl Terminal) {
+  let report = diagnose(dir, term);
+
+  println!();
+  if report.errors == 0 && report.warnings == 0 {
+    println!("{}", tr!("no problem found").green());
+  } else {
+    println!(
+      "{}",
+      tr!(
+        "{} errors, {} warnings",
+        report.errors.to_string().red().bold(),
+        report.warnings.to_string().yellow()
+      )
+    );
+  }
+}
+
+/// Run all the diagnostics on the configuration in `dir`.
+fn diagnose(dir: &Path, term: &impl Terminal) -> Report {
+  let mut report = Report::default();
+
+  let loaded = Config::from_dir(dir);
+  if let Some(lang) = loaded
+    .as_ref()
+    .ok()
+    .and_then(Option::as_ref)
+    .and_then(Config::language)
+  {
+    i18n::set_language(lang);
+  }
+
+  let config = check_config(&mut report, dir, loaded);
+  check_storage(&mut report, &config);
+  check_editor(&mut report, &config);
+  check_terminal(&mut report, term);
+
+  report
+}
+
+/// Check the configuration loaded from `dir`; default values stand in for a missing or unreadable configuration.
+fn check_config(report: &mut Report, dir: &Path, loaded: Result<Option<Config>, Error>) -> Config {
+  report.section(tr!("Configuration"));
+
+  let config_path = dir.join("config.toml");
+  let config = match loaded {
+    Ok(Some(config)) => {
+      report.ok(tr!("configuration file is {}", config_path.display()));
+      config
+    }
+
+    Ok(None) => {
+      report.warning(
+        tr!("configuration file {} doesn’t exist", config_path.display()),
+        tr!("default values are used; run td without --config to create it with the wizard"),
+      );
+      Config::create(Some(dir)).unwrap_or_default()
+    }
+
+    Err(err) => {
+      report.error(
+        tr!("configuration file cannot be read: {}", ErrorReport(&err)),
+        tr!("fix it at the reported line, or move it away and run td to create a new one with the wizard"),
+      );
+      Config::create(Some(dir)).unwrap_or_default()
+    }
+  };
+
+  let root_dir = config.root_dir();
+  if root_dir.is_dir() {
+    report.ok(tr!("configuration root is {}", root_dir.display()));
+  } else {
+    report.error(
+      tr!("configuration root {} doesn’t exist", root_dir.display()),
+      tr!("check the tasks_file key of the configuration"),
+    );
+  }
+
+  let aliases = [
+    config.todo_alias(),
+    config.wip_alias(),
+    config.done_alias(),
+    config.cancelled_alias(),
+  ];
+  let distinct = aliases
+    .iter()
+    .enumerate()
+    .all(|(i, alias)| !aliases[..i].contains(alias));
+  if aliases.iter().any(|alias| alias.is_empty()) || !distinct {
+    report.warning(
+      tr!("status aliases are empty or not distinct"),
+      tr!("set distinct values for todo_alias, wip_alias, done_alias and cancelled_alias"),
+    );
+  } else {
+    report.ok(tr!("status aliases are distinct"));
+  }
+
+  let priorities = config.priorities();
+  let names = priorities
+    .levels()
+    .iter()
+    .flat_map(|level| std::iter::once(&level.name).chain(&level.aliases))
+    .map(|name| name.to_lowercase())
+    .collect::<Vec<_>>();
+  let distinct = names
+    .iter()
+    .enumerate()
+    .all(|(i, name)| !names[..i].contains(name));
+  if names.is_empty() || !distinct {
+    report.warning(
+      tr!("priority levels are missing or their names and aliases are not distinct"),
+      tr!("set distinct names and aliases in priorities, ignoring case"),
+    );
+  } else {
+    report.ok(tr!(
+      "{} priority levels are defined",
+      priorities.levels().len()
+    ));
+  }
+
+  let fields = config.fields().defs();
+  if !fields.is_empty() {
+    let names = fields
+      .iter()
+      .map(|def| def.name.as_str())
+      .collect::<Vec<_>>();
+    let distinct = names
+      .iter()
+      .enumerate()
+      .all(|(i, name)| !names[..i].contains(name));
+    let builtin = names.iter().any(|name| {
+      matches!(
+        *name,
+        "effort" | "ctx" | "milestone" | "remind" | "ext" | "author"
+      )
+    });
+    let invalid = names
+      .iter()
+      .any(|name| name.is_empty() || name.contains(|c: char| c == ':' || c.is_whitespace()));
+    if !distinct || builtin || invalid {
+      report.warning(
+        tr!("user-defined field names are not distinct, clash with a builtin metadata or are invalid"),
+        tr!(
+          "use distinct names without colons nor blanks, other than effort, ctx, milestone, remind, ext and author, \
+           in fields"
+        ),
+      );
+    } else {
+      report.ok(tr!("{} user-defined fields are declared", names.len()));
+    }
+  }
+
+  if config.max_description_lines() == 0 {
+    report.warning(
+      tr!("max_description_lines is 0; descriptions are not displayed in listings"),
+      tr!("set max_description_lines to at least 1"),
+    );
+  }
+
+  config
+}
+
+fn check_storage(report: &mut Report, config: &Config) {
+  report.section(tr!("Tasks"));
+
+  let tasks_path = config.tasks_path();
+  let backups = config.backups_dir();
+  let content = match fs::read(&tasks_path) {
+    Ok(content) => Some(content),
+    Err(err) if err.kind() == io::ErrorKind::NotFound => None,
+    Err(err) => {
+      report.error(
+        tr!("{} cannot be read: {}", tasks_path.display(), err),
+        tr!("check its permissions"),
+      );
+      return;
+    }
+  };
+
+  if let Some(ref content) = content {
+    let unreadable = match TaskManager::unreadable_tasks(content) {
+      Ok(unreadable) => unreadable,
+      Err(err) => {
+        report.error(
+          tr!(
+            "{} cannot be read: {}",
+            tasks_path.display(),
+            ErrorReport(&err)
+          ),
+          tr!(
+            "fix it at the reported line and column, or restore a backup from {}",
+            backups.display()
+          ),
+        );
+        return;
+      }
+    };
+
+    for task in &unreadable {
+      let msg = match task.event {
+        Some(event) => tr!(
+          "event {} of task {} cannot be read: {}",
+          event,
+          task.uid,
+          task.error
+        ),
+        None => tr!("task {} cannot be read: {}", task.uid, task.error),
+      };
+      report.error(
+        msg,
+        tr!(
+          "fix or remove it in {}, or restore a backup from {}",
+          tasks_path.display(),
+          backups.display()
+        ),
+      );
+    }
+
+    if !unreadable.is_empty() {
+      return;
+    }
+  }
+
+  let task_mgr = match TaskManager::new_from_config(config) {
+    Ok(task_mgr) => task_mgr,
+    Err(err) => {
+      report.error(
+        tr!("tasks cannot be loaded: {}", ErrorReport(&err)),
+        tr!(
+          "fix the reported file, or restore a backup from {}",
+          backups.display()
+        ),
+      );
+      return;
+    }
+  };
+
+  if content.is_some() {
+    report.ok(tr!(
+      "{} tasks and {} removed tasks in {}",
+      task_mgr.tasks().count(),
+      task_mgr.trashed_tasks().count(),
+      tasks_path.display()
+    ));
+  } else {
+    report.ok(tr!("no tasks saved yet"));
+  }
+
+  let records = task_mgr.journal_records();
+  if records > 0 {
+    report.ok(tr!(
+      "{} changes journaled in {}; td gc compacts them into the tasks file",
+      records,
+      config.journal_path().display()
+    ));
+  }
+
+  for issue in task_mgr.check_integrity() {
+    let fix = match issue {
+      IntegrityIssue::NextUIDInUse { max_uid, .. } => {
+        tr!(
+          "set next_uid to {} in {}",
+          max_uid.val() + 1,
+          tasks_path.display()
+        )
+      }
+
+      IntegrityIssue::DuplicateUID(uid) => tr!(
+        "remove task {} from either tasks or trash in {}",
+        uid,
+        tasks_path.display()
+      ),
+
+      IntegrityIssue::MissingCreation(uid) | IntegrityIssue::UnorderedHistory(uid) => tr!(
+        "the task was probably edited by hand; check its events with td {} history",
+        uid
+      ),
+
+      IntegrityIssue::DanglingNote { task_uid, .. }
+      | IntegrityIssue::DanglingChecklistItem { task_uid, .. }
+      | IntegrityIssue::DanglingReminder { task_uid, .. } => tr!(
+        "remove the offending event from the history of task {} in {}",
+        task_uid,
+        tasks_path.display()
+      ),
+
+      IntegrityIssue::BrokenSeal(uid) => tr!(
+        "check its events with td {} history; remove its seal from {} to accept them",
+        uid,
+        tasks_path.display()
+      ),
+    };
+
+    report.error(issue, fix);
+  }
+}
+
+fn check_editor(report: &mut Report, config: &Config) {
+  report.section(tr!("Editor"));
+
+  match interactive_editor::editor(config) {
+    Ok(editor) => {
+      let program = &editor[0];
+
+      if find_program(program) {
+        report.ok(tr!("editor is {}", editor.join(" ")));
+      } else {
+        report.error(
+          tr!("editor {} cannot be found", program),
+          tr!("install it, or set $VISUAL, $EDITOR or the interactive_editor key of the configuration"),
+        );
+      }
+    }
+
+    Err(InteractiveEditingError::InvalidEditorCommand(command)) => {
+      report.error(
+        tr!("editor command {} cannot be parsed", command),
+        tr!("check its quotes"),
+      );
+    }
+
+    Err(_) => {
+      report.warning(
+        tr!("no editor is set; notes cannot be written"),
+        tr!("set $VISUAL, $EDITOR or the interactive_editor key of the configuration"),
+      );
+    }
+  }
+}
+
+/// Check whether a program can be run, either as a path or by looking it up in `$PATH`.
+///
+/// On Windows, programs can be named without their extension, as long as it’s one of `%PATHEXT%`.
+fn find_program(program: &str) -> bool {
+  let path = Path::new(program);
+  let names = program_names(program);
+
+  if path.components().count() > 1 {
+    return names.iter().any(|name| Path::new(name).is_file());
+  }
+
+  env::var_os("PATH")
+    .map(|paths| {
+      env::split_paths(&paths).any(|dir| names.iter().any(|name| dir.join(name).is_file()))
+    })
+    .unwrap_or(false)
+}
+
+/// File names a program can be run from: the program itself, and on Windows, the program with each of the extensions
+/// of `%PATHEXT%` if it has none.
+fn program_names(program: &str) -> Vec<String> {
+  let mut names = vec![program.to_owned()];
+
+  if cfg!(windows) && Path::new(program).extension().is_none() {
+    let extensions = env::var("PATHEXT").unwrap_or_else(|_| ".COM;.EXE;.BAT;.CMD".to_owned());
+    names.extend(
+      extensions
+        .split(';')
+        .filter(|ext| !ext.is_empty())
+        .map(|ext| format!("{}{}", program, ext)),
+    );
+  }
+
+  names
+}
+
+fn check_terminal(report: &mut Report, term: &impl Terminal) {
+  report.section(tr!("Terminal"));
+
+  if !term.is_tty() {
+    report.ok(tr!(
+      "output is not a terminal; colors and warnings are disabled"
+    ));
+    return;
+  }
+
+  match term.dimensions() {
+    Some([width, height]) => report.ok(tr!("terminal is {}×{}", width, height)),
+    None => report.warning(
+      tr!("terminal doesn’t expose its dimensions; descriptions cannot be wrapped"),
+      tr!("use --plain to get unpadded, tab-separated listings"),
+    ),
+  }
+
+  if term.supports_color() {
+    report.ok(tr!("terminal supports colors"));
+  } else {
+    report.warning(
+      tr!("colors are disabled"),
+      tr!("unset $NO_COLOR and make sure $TERM is not dumb"),
+    );
+  }
+}
+
+#[cfg(test)]
+mod unit_tests {
+  use super::*;
+  use crate::term::TestTerm;
+  use serde_json as json;
+
+  #[cfg(not(windows))]
+  #[test]
+  fn find_program_in_path() {
+    assert!(find_program("sh"));
+    assert!(find_program("/bin/sh"));
+    assert!(!find_program("surely-not-an-existing-program"));
+  }
+
+  #[cfg(windows)]
+  #[test]
+  fn find_program_in_path() {
+    assert!(find_program("cmd"));
+    assert!(find_program("cmd.exe"));
+    assert!(!find_program("surely-not-an-existing-program"));
+    assert_eq!(program_names("notepad.exe"), vec!["notepad.exe"]);
+  }
+
+  #[test]
+  fn broken_config() {
+    let dir = tempdir::TempDir::new("toodoux-doctor").unwrap();
+    fs::write(dir.path().join("config.toml"), "[main]\ntasks_file = '").unwrap();
+
+    let mut report = Report::default();
+    check_config(&mut report, dir.path(), Config::from_dir(dir.path()));
+    assert_eq!(report.errors, 1);
+  }
+
+  #[test]
+  fn malformed_event() {
+    let dir = tempdir::TempDir::new("toodoux-doctor").unwrap();
+    let config = Config::create(Some(dir.path())).unwrap();
+    let tasks = json::json!({
+      "next_uid": 1,
+      "tasks": {
+        "0": { "name": "Foo", "history": [{ "Created": "2021-01-01T00:00:00Z" }, { "Bogus": 1 }] }
+      }
+    });
+    fs::write(config.tasks_path(), tasks.to_string()).unwrap();
+
+    let unreadable = TaskManager::unreadable_tasks(tasks.to_string().as_bytes()).unwrap();
+    assert_eq!(unreadable.len(), 1);
+    assert_eq!(unreadable[0].uid, "0");
+    assert_eq!(unreadable[0].event, Some(1));
+
+    let mut report = Report::default();
+    check_storage(&mut report, &config);
+    assert_eq!(report.errors, 1);
+  }
+
+  #[test]
+  fn terminal_diagnostics() {
+    let mut report = Report::default();
+    check_terminal(&mut report, &TestTerm::new().with_dimensions(None));
+    assert_eq!(report.warnings, 2);
+
+    let mut report = Report::default();
+    check_terminal(&mut report, &TestTerm::new().with_tty(false));
+    assert_eq!(report.warnings, 0);
+  }
+}
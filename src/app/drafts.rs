@@ -0,0 +1,67 @@
+//! Drafts of notes.
+//!
+//! New notes are written in a draft file in the configuration root, one per task, rather than in a temporary file, so
+//! that they survive a crash of the editor or of toodoux itself. A draft is removed once its note is saved or
+//! abandoned, and offered to be resumed the next time a note is added to the task otherwise.
+
+use std::{
+  fs, io,
+  path::{Path, PathBuf},
+};
+use toodoux::{config::Config, task::UID};
+
+/// Name of the drafts directory, relative to the configuration root.
+pub const DRAFTS_DIR_NAME: &str = "drafts";
+
+/// Path to the draft of a new note of the task `uid`.
+pub fn path(config: &Config, uid: UID) -> PathBuf {
+  config
+    .root_dir()
+    .join(DRAFTS_DIR_NAME)
+    .join(format!("{}.md", uid))
+}
+
+/// Read the draft at `path`, if any.
+pub fn read(path: &Path) -> Result<Option<String>, io::Error> {
+  match fs::read_to_string(path) {
+    Ok(draft) => Ok(Some(draft)),
+    Err(err) if err.kind() == io::ErrorKind::NotFound => Ok(None),
+    Err(err) => Err(err),
+  }
+}
+
+/// Write the draft at `path`, creating the drafts directory if needed.
+pub fn write(path: &Path, draft: &str) -> Result<(), io::Error> {
+  if let Some(dir) = path.parent() {
+    fs::create_dir_all(dir)?;
+  }
+
+  fs::write(path, draft)
+}
+
+/// Remove the draft at `path`, if any.
+pub fn remove(path: &Path) -> Result<(), io::Error> {
+  match fs::remove_file(path) {
+    Err(err) if err.kind() != io::ErrorKind::NotFound => Err(err),
+    _ => Ok(()),
+  }
+}
+
+#[cfg(test)]
+mod unit_tests {
+  use super::*;
+
+  #[test]
+  fn drafts() {
+    let dir = tempdir::TempDir::new("drafts").unwrap();
+    let path = dir.path().join(DRAFTS_DIR_NAME).join("3.md");
+
+    assert_eq!(read(&path).unwrap(), None);
+    write(&path, "half a note").unwrap();
+    assert_eq!(read(&path).unwrap().as_deref(), Some("half a note"));
+
+    remove(&path).unwrap();
+    assert_eq!(read(&path).unwrap(), None);
+    remove(&path).unwrap();
+  }
+}
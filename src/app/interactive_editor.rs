@@ -1,22 +1,28 @@
 //! Interactive editor session.
 //!
-//! This module provides a way to open an editor based on the `$EDITOR` environment variable or what is defined in the
-//! configuration.
+//! This module provides a way to open an editor based on the `$VISUAL` and `$EDITOR` environment variables or what is
+//! defined in the configuration. Editors are commands, parsed as a shell would, so that they can come with arguments,
+//! such as `code --wait`. On Windows, backslashes in commands are kept as path separators, and editors run through
+//! `cmd /C`, which finds the ones installed as batch files, such as `code.cmd`.
 
 use std::{
-  env, error, fmt, fs, io,
+  env, error,
+  ffi::OsString,
+  fmt, fs, io,
   path::{Path, PathBuf},
   process,
   string::FromUtf8Error,
 };
 
-use toodoux::config::Config;
+use toodoux::{config::Config, tr};
 
 /// Errors that can happen while interactively editing files.
 #[derive(Debug)]
 pub enum InteractiveEditingError {
   FileError(io::Error),
   MissingInteractiveEditor,
+  /// The editor command cannot be parsed, e.g. because of an unbalanced quote.
+  InvalidEditorCommand(String),
   InteractiveEditorError(PathBuf, io::Error),
   Utf8Error(FromUtf8Error),
 }
@@ -24,20 +30,22 @@ pub enum InteractiveEditingError {
 impl fmt::Display for InteractiveEditingError {
   fn fmt(&self, f: &mut fmt::Formatter) -> Result<(), fmt::Error> {
     match *self {
-      InteractiveEditingError::FileError(ref err) => write!(f, "unable to open file: {}", err),
-      InteractiveEditingError::MissingInteractiveEditor => f.write_str(
-        "no interactive editor was found; consider configuring either $EDITOR or the configuration",
-      ),
-      InteractiveEditingError::InteractiveEditorError(ref path, ref err) => {
-        write!(
-          f,
-          "interactive editor error at path {}: {}",
-          path.display(),
-          err
-        )
+      InteractiveEditingError::FileError(ref err) => {
+        f.write_str(&tr!("unable to open file: {}", err))
       }
+      InteractiveEditingError::MissingInteractiveEditor => f.write_str(tr!(
+        "no interactive editor was found; consider configuring either $EDITOR or the configuration"
+      )),
+      InteractiveEditingError::InvalidEditorCommand(ref command) => {
+        f.write_str(&tr!("invalid editor command: {}", command))
+      }
+      InteractiveEditingError::InteractiveEditorError(ref path, ref err) => f.write_str(&tr!(
+        "interactive editor error at path {}: {}",
+        path.display(),
+        err
+      )),
       InteractiveEditingError::Utf8Error(ref err) => {
-        write!(f, "error while decoding UTF-8: {}", err)
+        f.write_str(&tr!("error while decoding UTF-8: {}", err))
       }
     }
   }
@@ -57,6 +65,129 @@ impl From<FromUtf8Error> for InteractiveEditingError {
   }
 }
 
+/// Editors known to understand `+$`, which moves the cursor to the last line of the file.
+const LAST_LINE_EDITORS: &[&str] = &["vi", "vim", "nvim"];
+
+/// Find the editor command to use, trying `$VISUAL`, `$EDITOR` and then the user configuration.
+///
+/// The command is returned split into words, the first one being the program; it is never empty.
+pub fn editor(config: &Config) -> Result<Vec<String>, InteractiveEditingError> {
+  let command = if let Some((var, env_editor)) = ["VISUAL", "EDITOR"]
+    .iter()
+    .find_map(|&var| env::var(var).ok().map(|editor| (var, editor)))
+  {
+    log::debug!("editing via ${} ({})", var, env_editor);
+    env_editor
+  } else if let Some(conf_editor) = config.interactive_editor() {
+    log::debug!("editing via configuration editor ({})", conf_editor);
+    conf_editor.to_owned()
+  } else {
+    log::error!("cannot find a suitable interactive editor");
+    return Err(InteractiveEditingError::MissingInteractiveEditor);
+  };
+
+  parse_editor(&command)
+}
+
+/// Split an editor command into words, as a shell would.
+fn parse_editor(command: &str) -> Result<Vec<String>, InteractiveEditingError> {
+  let words = if cfg!(windows) {
+    split_windows_command(command)
+  } else {
+    shlex::split(command)
+  };
+  let words =
+    words.ok_or_else(|| InteractiveEditingError::InvalidEditorCommand(command.to_owned()))?;
+
+  if words.is_empty() {
+    Err(InteractiveEditingError::MissingInteractiveEditor)
+  } else {
+    Ok(words)
+  }
+}
+
+/// Split a command into words as Windows programs do: words are separated by whitespace, unless in double quotes, and
+/// backslashes are kept as-is.
+///
+/// Return [`None`] if a double quote is unbalanced.
+fn split_windows_command(command: &str) -> Option<Vec<String>> {
+  let mut words = Vec::new();
+  let mut word: Option<String> = None;
+  let mut quoted = false;
+
+  for c in command.chars() {
+    match c {
+      '"' => {
+        quoted = !quoted;
+        word.get_or_insert_with(String::new);
+      }
+      c if c.is_whitespace() && !quoted => words.extend(word.take()),
+      c => word.get_or_insert_with(String::new).push(c),
+    }
+  }
+
+  if quoted {
+    None
+  } else {
+    words.extend(word);
+    Some(words)
+  }
+}
+
+/// Command running the editor, given as its program followed by its arguments.
+#[cfg(not(windows))]
+fn editor_command(words: &[OsString]) -> process::Command {
+  let mut command = process::Command::new(&words[0]);
+  command.args(&words[1..]);
+  command
+}
+
+/// Command running the editor, given as its program followed by its arguments, through `cmd /C`.
+#[cfg(windows)]
+fn editor_command(words: &[OsString]) -> process::Command {
+  use std::os::windows::process::CommandExt as _;
+
+  let mut command = process::Command::new("cmd");
+  command.raw_arg(cmd_arguments(words));
+  command
+}
+
+/// Arguments of `cmd` running a command given as its program followed by its arguments.
+///
+/// Each word is quoted, and so is the whole command, so that `cmd` doesn’t interpret the characters in between; `/S`
+/// strips the outer quotes.
+#[cfg(any(windows, test))]
+fn cmd_arguments(words: &[OsString]) -> String {
+  let words = words
+    .iter()
+    .map(|word| format!("\"{}\"", word.to_string_lossy()))
+    .collect::<Vec<_>>();
+
+  format!("/S /C \"{}\"", words.join(" "))
+}
+
+/// Arguments passed to the editor after the file to edit.
+///
+/// They are the `editor_args` of the configuration if set, or `+$` for editors known to understand it.
+fn editor_args<'a>(config: &'a Config, program: &str) -> Vec<&'a str> {
+  match config.editor_args() {
+    Some(args) => args.iter().map(String::as_str).collect(),
+
+    None => {
+      let name = Path::new(program)
+        .file_stem()
+        .and_then(|name| name.to_str())
+        .unwrap_or_default();
+
+      if LAST_LINE_EDITORS.contains(&name) {
+        vec!["+$"]
+      } else {
+        Vec::new()
+      }
+    }
+  }
+}
+
 /// Open an interactive editor for the file named `file_name` and once the file is saved and the editor
 /// exits, returns what the file contains.
 ///
@@ -74,34 +205,95 @@ pub fn interactively_edit(
   log::debug!("creating temporary file {}", file_path.display());
   fs::write(&file_path, content)?;
 
-  let editor;
-  if let Some(env_editor) = env::var("EDITOR").ok() {
-    if env_editor.is_empty() {
-      return Err(InteractiveEditingError::MissingInteractiveEditor);
-    }
-
-    log::debug!("editing via $EDITOR ({})", env_editor);
-    editor = env_editor;
-  } else if let Some(conf_editor) = config.interactive_editor() {
-    if conf_editor.is_empty() {
-      return Err(InteractiveEditingError::MissingInteractiveEditor);
-    }
+  interactively_edit_file(config, &file_path)
+}
 
-    log::debug!("editing via configuration editor ({})", conf_editor);
-    editor = conf_editor.to_owned();
-  } else {
-    log::error!("cannot find a suitable interactive editor");
-    return Err(InteractiveEditingError::MissingInteractiveEditor);
-  }
+/// Open an interactive editor for the existing file at `file_path` and once the file is saved and the editor exits,
+/// returns what the file contains.
+pub fn interactively_edit_file(
+  config: &Config,
+  file_path: &Path,
+) -> Result<String, InteractiveEditingError> {
+  let editor = editor(config)?;
+  let args = editor_args(config, &editor[0]);
+  let words = editor
+    .iter()
+    .map(OsString::from)
+    .chain(Some(file_path.as_os_str().to_owned()))
+    .chain(args.into_iter().map(OsString::from))
+    .collect::<Vec<_>>();
 
-  let _ = process::Command::new(editor)
-    .arg(&file_path)
-    .arg("+$")
+  let _ = editor_command(&words)
     .spawn()
-    .map_err(|e| InteractiveEditingError::InteractiveEditorError(file_path.clone(), e))?
+    .map_err(|e| InteractiveEditingError::InteractiveEditorError(file_path.to_owned(), e))?
     .wait()
-    .map_err(|e| InteractiveEditingError::InteractiveEditorError(file_path.clone(), e))?;
-  let content = fs::read_to_string(file_path)?;
+    .map_err(|e| InteractiveEditingError::InteractiveEditorError(file_path.to_owned(), e))?;
+
+  // editors on Windows may save the file with CRLF line endings
+  let content = fs::read_to_string(file_path)?.replace("\r\n", "\n");
 
   Ok(content)
 }
+
+#[cfg(test)]
+mod unit_tests {
+  use super::*;
+  use toodoux::config::{ColorConfig, MainConfig};
+
+  // commands are split as Windows programs do on Windows; see windows_editor_commands
+  #[cfg(not(windows))]
+  #[test]
+  fn editor_commands() {
+    assert_eq!(parse_editor("vim").unwrap(), vec!["vim"]);
+    assert_eq!(parse_editor("code --wait").unwrap(), vec!["code", "--wait"]);
+    assert_eq!(
+      parse_editor("'/opt/My Editor/edit' -n 'a b'").unwrap(),
+      vec!["/opt/My Editor/edit", "-n", "a b"]
+    );
+
+    assert!(matches!(
+      parse_editor("  "),
+      Err(InteractiveEditingError::MissingInteractiveEditor)
+    ));
+    assert!(matches!(
+      parse_editor("code 'unbalanced"),
+      Err(InteractiveEditingError::InvalidEditorCommand(_))
+    ));
+  }
+
+  #[test]
+  fn windows_editor_commands() {
+    assert_eq!(
+      split_windows_command(r#""C:\Program Files\Notepad++\notepad++.exe" -multiInst"#).unwrap(),
+      vec![r"C:\Program Files\Notepad++\notepad++.exe", "-multiInst"]
+    );
+    assert_eq!(
+      split_windows_command(r"C:\tools\nvim.exe  --clean").unwrap(),
+      vec![r"C:\tools\nvim.exe", "--clean"]
+    );
+    assert!(split_windows_command(r#"code "--wait"#).is_none());
+
+    let words = ["code", "--wait", r"C:\Temp\note.md"].map(OsString::from);
+    assert_eq!(
+      cmd_arguments(&words),
+      r#"/S /C ""code" "--wait" "C:\Temp\note.md"""#
+    );
+  }
+
+  #[cfg(windows)]
+  #[test]
+  fn windows_editor_parsing() {
+    assert_eq!(
+      parse_editor(r"C:\tools\vim.exe -n").unwrap(),
+      vec![r"C:\tools\vim.exe", "-n"]
+    );
+  }
+
+  #[test]
+  fn last_line_argument() {
+    let config = Config::new(MainConfig::default(), ColorConfig::default());
+    assert_eq!(editor_args(&config, "vim"), vec!["+$"]);
+    assert_eq!(editor_args(&config, "/usr/bin/nvim"), vec!["+$"]);
+    assert!(editor_args(&config, "code").is_empty());
+  }
+}
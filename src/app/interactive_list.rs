@@ -0,0 +1,176 @@
+//! Interactive listing session.
+//!
+//! The interactive listing takes the terminal over — raw mode and alternate screen — so that rows can be selected with
+//! the arrow keys and acted upon with single keys. Rendering the rows is left to the CLI; this module only deals with
+//! the terminal and the key bindings.
+
+use crossterm::{
+  cursor,
+  event::{self, Event, KeyCode, KeyEvent, KeyModifiers},
+  execute, queue,
+  style::Print,
+  terminal::{self, ClearType},
+};
+use std::io::{self, Write as _};
+use toodoux::task::Status;
+
+/// Key bindings, displayed at the bottom of the screen.
+pub const HELP: &str =
+  "↑/↓ select · t todo · s start · p pause · d done · c cancel · n note · enter show · q quit";
+
+/// Actions bound to keys.
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub enum Action {
+  /// Select the previous row.
+  Up,
+  /// Select the next row.
+  Down,
+  /// Change the status of the selected task.
+  ChangeStatus(Status),
+  /// Add a note to the selected task.
+  AddNote,
+  /// Show the details of the selected task.
+  Show,
+  /// Leave the interactive listing.
+  Quit,
+}
+
+impl Action {
+  /// Action bound to a key, if any.
+  pub fn from_key(key: KeyEvent) -> Option<Self> {
+    match key.code {
+      KeyCode::Char('c') if key.modifiers.contains(KeyModifiers::CONTROL) => Some(Action::Quit),
+      KeyCode::Up | KeyCode::Char('k') => Some(Action::Up),
+      KeyCode::Down | KeyCode::Char('j') => Some(Action::Down),
+      KeyCode::Char('t') => Some(Action::ChangeStatus(Status::Todo)),
+      KeyCode::Char('s') => Some(Action::ChangeStatus(Status::Ongoing)),
+      KeyCode::Char('p') => Some(Action::ChangeStatus(Status::Paused)),
+      KeyCode::Char('d') => Some(Action::ChangeStatus(Status::Done)),
+      KeyCode::Char('c') => Some(Action::ChangeStatus(Status::Cancelled)),
+      KeyCode::Char('n') => Some(Action::AddNote),
+      KeyCode::Enter => Some(Action::Show),
+      KeyCode::Char('q') | KeyCode::Esc => Some(Action::Quit),
+      _ => None,
+    }
+  }
+}
+
+/// Terminal taken over by the interactive listing.
+///
+/// The terminal is given back when the screen is dropped.
+pub struct Screen {
+  out: io::Stdout,
+}
+
+impl Screen {
+  /// Take the terminal over.
+  pub fn enter() -> io::Result<Self> {
+    let mut screen = Screen { out: io::stdout() };
+    screen.resume()?;
+    Ok(screen)
+  }
+
+  /// Give the terminal back temporarily, e.g. to run an editor.
+  pub fn suspend(&mut self) -> io::Result<()> {
+    execute!(self.out, cursor::Show, terminal::LeaveAlternateScreen)?;
+    terminal::disable_raw_mode()
+  }
+
+  /// Take the terminal over again after having suspended the screen.
+  pub fn resume(&mut self) -> io::Result<()> {
+    terminal::enable_raw_mode()?;
+    execute!(self.out, terminal::EnterAlternateScreen, cursor::Hide)
+  }
+
+  /// Number of lines of the screen.
+  pub fn height(&self) -> usize {
+    terminal::size().map_or(24, |(_, height)| height.into())
+  }
+
+  /// Replace the content of the screen with lines.
+  pub fn draw<'a>(&mut self, lines: impl IntoIterator<Item = &'a str>) -> io::Result<()> {
+    queue!(self.out, terminal::Clear(ClearType::All))?;
+
+    for (i, line) in lines.into_iter().enumerate() {
+      queue!(self.out, cursor::MoveTo(0, i as u16), Print(line))?;
+    }
+
+    self.out.flush()
+  }
+
+  /// Wait for a key bound to an action.
+  pub fn next_action(&self) -> io::Result<Action> {
+    loop {
+      if let Event::Key(key) = event::read()? {
+        if let Some(action) = Action::from_key(key) {
+          return Ok(action);
+        }
+      }
+    }
+  }
+}
+
+impl Drop for Screen {
+  fn drop(&mut self) {
+    let _ = self.suspend();
+  }
+}
+
+/// Find the first row to display so that the `selected` row is visible.
+///
+/// `heights` are the number of lines of each row and `room` the number of lines available. The view scrolls as little
+/// as possible from the `top` row previously displayed.
+pub fn scroll(heights: &[usize], selected: usize, top: usize, room: usize) -> usize {
+  if selected < top {
+    return selected;
+  }
+
+  let mut top = top;
+  while top < selected && heights[top..=selected].iter().sum::<usize>() > room {
+    top += 1;
+  }
+
+  top
+}
+
+#[cfg(test)]
+mod unit_tests {
+  use super::*;
+
+  #[test]
+  fn key_bindings() {
+    let key = |code| Action::from_key(KeyEvent::new(code, KeyModifiers::NONE));
+
+    assert_eq!(key(KeyCode::Down), Some(Action::Down));
+    assert_eq!(key(KeyCode::Char('k')), Some(Action::Up));
+    assert_eq!(
+      key(KeyCode::Char('s')),
+      Some(Action::ChangeStatus(Status::Ongoing))
+    );
+    assert_eq!(
+      key(KeyCode::Char('c')),
+      Some(Action::ChangeStatus(Status::Cancelled))
+    );
+    assert_eq!(
+      Action::from_key(KeyEvent::new(KeyCode::Char('c'), KeyModifiers::CONTROL)),
+      Some(Action::Quit)
+    );
+    assert_eq!(key(KeyCode::Enter), Some(Action::Show));
+    assert_eq!(key(KeyCode::Char('x')), None);
+  }
+
+  #[test]
+  fn scrolling() {
+    let heights = [1, 2, 1, 1, 3];
+
+    // everything fits
+    assert_eq!(scroll(&heights, 4, 0, 8), 0);
+    // scrolling down as little as possible
+    assert_eq!(scroll(&heights, 3, 0, 3), 2);
+    assert_eq!(scroll(&heights, 4, 0, 4), 3);
+    // scrolling up to the selected row
+    assert_eq!(scroll(&heights, 1, 3, 4), 1);
+    // a row taller than the screen is still selected
+    assert_eq!(scroll(&heights, 4, 0, 2), 4);
+  }
+}
@@ -0,0 +1,35 @@
+//! Mutation log.
+//!
+//! When enabled in the configuration, every save of the tasks appends a line to a log file in the configuration root,
+//! recording who mutated the tasks, when and with which command.
+
+use chrono::Utc;
+use std::{
+  env,
+  fs::OpenOptions,
+  io::{self, Write as _},
+  path::PathBuf,
+};
+use toodoux::config::Config;
+
+/// Name of the mutation log file, relative to the configuration root.
+pub const MUTATION_LOG_FILE_NAME: &str = "toodoux.log";
+
+/// Path to the mutation log file.
+pub fn path(config: &Config) -> PathBuf {
+  config.root_dir().join(MUTATION_LOG_FILE_NAME)
+}
+
+/// Append a mutation entry to the log.
+pub fn record(config: &Config) -> Result<(), io::Error> {
+  let who = env::var("USER")
+    .or_else(|_| env::var("USERNAME"))
+    .unwrap_or_else(|_| "unknown".to_owned());
+  let what = env::args().skip(1).collect::<Vec<_>>().join(" ");
+
+  let mut file = OpenOptions::new()
+    .create(true)
+    .append(true)
+    .open(path(config))?;
+  writeln!(file, "{}\t{}\t{}", Utc::now().to_rfc3339(), who, what)
+}
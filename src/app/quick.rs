@@ -0,0 +1,273 @@
+//! Quick capture prompt.
+//!
+//! `td quick` reads a task on a single line and exits right after adding it, so that it can be bound to a hotkey of
+//! the window manager opening a small terminal. The line is edited in raw mode, so that metadata can be completed with
+//! the tab key; completing is left to the CLI, this module only deals with the terminal and the line.
+
+use colored::Colorize as _;
+use crossterm::{
+  cursor,
+  event::{self, Event, KeyCode, KeyEvent, KeyModifiers},
+  queue,
+  style::Print,
+  terminal::{self, ClearType},
+};
+use std::io;
+
+/// Outcome of a key pressed while editing the line.
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub enum Outcome {
+  /// The line was submitted.
+  Submit(String),
+  /// The line was abandoned.
+  Cancel,
+}
+
+/// Line being edited, along with the completions shown after it.
+#[derive(Debug, Default)]
+pub struct LineEditor {
+  line: String,
+  /// Position of the cursor in the line, in bytes.
+  cursor: usize,
+  /// Completions shown when several of them match the word being typed.
+  hints: Vec<String>,
+}
+
+impl LineEditor {
+  /// Handle a key, completing the word before the cursor with `complete` on tab.
+  ///
+  /// Return the outcome of the edition once the line is submitted or abandoned.
+  pub fn handle(
+    &mut self,
+    key: KeyEvent,
+    complete: impl FnOnce(&str) -> Vec<String>,
+  ) -> Option<Outcome> {
+    let ctrl = key.modifiers.contains(KeyModifiers::CONTROL);
+
+    if key.code != KeyCode::Tab {
+      self.hints.clear();
+    }
+
+    match key.code {
+      KeyCode::Enter => return Some(Outcome::Submit(self.line.clone())),
+      KeyCode::Esc => return Some(Outcome::Cancel),
+      KeyCode::Char('c') | KeyCode::Char('d') if ctrl => return Some(Outcome::Cancel),
+      KeyCode::Char('a') if ctrl => self.cursor = 0,
+      KeyCode::Char('e') if ctrl => self.cursor = self.line.len(),
+      KeyCode::Char('u') if ctrl => {
+        self.line.drain(..self.cursor);
+        self.cursor = 0;
+      }
+      KeyCode::Home => self.cursor = 0,
+      KeyCode::End => self.cursor = self.line.len(),
+      KeyCode::Left => self.cursor = self.previous_boundary(),
+      KeyCode::Right => self.cursor = self.next_boundary(),
+
+      KeyCode::Backspace => {
+        let start = self.previous_boundary();
+        self.line.drain(start..self.cursor);
+        self.cursor = start;
+      }
+
+      KeyCode::Delete => {
+        let end = self.next_boundary();
+        self.line.drain(self.cursor..end);
+      }
+
+      KeyCode::Tab => self.complete(complete),
+
+      KeyCode::Char(c) if !ctrl => {
+        self.line.insert(self.cursor, c);
+        self.cursor += c.len_utf8();
+      }
+
+      _ => (),
+    }
+
+    None
+  }
+
+  /// Complete the word before the cursor.
+  ///
+  /// A single completion replaces the word; several ones are shown as hints, the word being extended with their common
+  /// prefix.
+  fn complete(&mut self, complete: impl FnOnce(&str) -> Vec<String>) {
+    let start = self.line[..self.cursor].rfind(' ').map_or(0, |i| i + 1);
+    let completions = complete(&self.line[start..self.cursor]);
+
+    let replacement = match completions.as_slice() {
+      [] => return,
+      [completion] => format!("{} ", completion),
+      [first, rest @ ..] => {
+        let common = rest.iter().fold(first.as_str(), |common, completion| {
+          let len = common
+            .char_indices()
+            .zip(completion.chars())
+            .find(|((_, a), b)| a != b)
+            .map_or(common.len().min(completion.len()), |((i, _), _)| i);
+          &common[..len]
+        });
+        let common = common.to_owned();
+        self.hints = completions;
+        common
+      }
+    };
+
+    // keep what was typed when completions only share a shorter prefix, e.g. because of case
+    if replacement.len() > self.cursor - start {
+      self.line.replace_range(start..self.cursor, &replacement);
+      self.cursor = start + replacement.len();
+    }
+  }
+
+  /// Byte position of the character before the cursor.
+  fn previous_boundary(&self) -> usize {
+    self.line[..self.cursor]
+      .char_indices()
+      .next_back()
+      .map_or(0, |(i, _)| i)
+  }
+
+  /// Byte position of the character after the cursor.
+  fn next_boundary(&self) -> usize {
+    self.line[self.cursor..]
+      .chars()
+      .next()
+      .map_or(self.cursor, |c| self.cursor + c.len_utf8())
+  }
+
+  /// Redraw the line, after `prompt`, on the current line of the terminal.
+  fn draw(&self, out: &mut impl io::Write, prompt: &str) -> io::Result<()> {
+    queue!(
+      out,
+      cursor::MoveToColumn(0),
+      terminal::Clear(ClearType::UntilNewLine),
+      Print(prompt),
+      Print(&self.line[..self.cursor]),
+      cursor::SavePosition,
+      Print(&self.line[self.cursor..]),
+    )?;
+
+    if !self.hints.is_empty() {
+      queue!(
+        out,
+        Print(format!("  {}", self.hints.join(" ")).bright_black())
+      )?;
+    }
+
+    queue!(out, cursor::RestorePosition)?;
+    out.flush()
+  }
+}
+
+/// Raw mode, left when dropped.
+struct RawMode;
+
+impl RawMode {
+  fn enable() -> io::Result<Self> {
+    terminal::enable_raw_mode()?;
+    Ok(RawMode)
+  }
+}
+
+impl Drop for RawMode {
+  fn drop(&mut self) {
+    let _ = terminal::disable_raw_mode();
+  }
+}
+
+/// Read a line after `prompt`, completing the word before the cursor with `complete` on tab.
+///
+/// Return [`None`] if the line is abandoned, with escape or control-c.
+pub fn prompt(prompt: &str, complete: impl Fn(&str) -> Vec<String>) -> io::Result<Option<String>> {
+  let mut out = io::stdout();
+  let mut editor = LineEditor::default();
+  let raw_mode = RawMode::enable()?;
+
+  editor.draw(&mut out, prompt)?;
+  let outcome = loop {
+    if let Event::Key(key) = event::read()? {
+      if let Some(outcome) = editor.handle(key, &complete) {
+        break outcome;
+      }
+
+      editor.draw(&mut out, prompt)?;
+    }
+  };
+
+  drop(raw_mode);
+  println!();
+
+  match outcome {
+    Outcome::Submit(line) => Ok(Some(line)),
+    Outcome::Cancel => Ok(None),
+  }
+}
+
+#[cfg(test)]
+mod unit_tests {
+  use super::*;
+
+  fn typed(editor: &mut LineEditor, s: &str) {
+    for c in s.chars() {
+      editor.handle(KeyEvent::new(KeyCode::Char(c), KeyModifiers::NONE), |_| {
+        Vec::new()
+      });
+    }
+  }
+
+  fn key(editor: &mut LineEditor, code: KeyCode) -> Option<Outcome> {
+    editor.handle(KeyEvent::new(code, KeyModifiers::NONE), |word| {
+      ["@toodoux", "@tools", "#docs"]
+        .iter()
+        .filter(|completion| completion.starts_with(word))
+        .map(|&completion| completion.to_owned())
+        .collect()
+    })
+  }
+
+  #[test]
+  fn editing() {
+    let mut editor = LineEditor::default();
+    typed(&mut editor, "Fix the bög");
+    key(&mut editor, KeyCode::Left);
+    key(&mut editor, KeyCode::Backspace);
+    typed(&mut editor, "u");
+    key(&mut editor, KeyCode::End);
+    typed(&mut editor, "!");
+    assert_eq!(
+      key(&mut editor, KeyCode::Enter),
+      Some(Outcome::Submit("Fix the bug!".to_owned()))
+    );
+    assert_eq!(key(&mut editor, KeyCode::Esc), Some(Outcome::Cancel));
+  }
+
+  #[test]
+  fn completion() {
+    let mut editor = LineEditor::default();
+    typed(&mut editor, "Write #d");
+    key(&mut editor, KeyCode::Tab);
+    assert_eq!(editor.line, "Write #docs ");
+
+    // several completions extend the word to their common prefix and are shown
+    typed(&mut editor, "@t");
+    key(&mut editor, KeyCode::Tab);
+    assert_eq!(editor.line, "Write #docs @too");
+    assert_eq!(editor.hints, vec!["@toodoux", "@tools"]);
+    typed(&mut editor, "d");
+    assert!(editor.hints.is_empty());
+    key(&mut editor, KeyCode::Tab);
+    assert_eq!(editor.line, "Write #docs @toodoux ");
+
+    // completing in the middle of the line
+    key(&mut editor, KeyCode::Home);
+    typed(&mut editor, "#");
+    key(&mut editor, KeyCode::Tab);
+    assert_eq!(editor.line, "#docs Write #docs @toodoux ");
+
+    // nothing to complete
+    typed(&mut editor, "x");
+    key(&mut editor, KeyCode::Tab);
+    assert_eq!(editor.line, "#docs xWrite #docs @toodoux ");
+  }
+}
@@ -0,0 +1,479 @@
+//! Editor plugin protocol.
+//!
+//! `td rpc` speaks [JSON-RPC 2.0](https://www.jsonrpc.org/specification) over its standard input and output, one
+//! message per line, so that editor plugins can keep a single process around instead of running `td` for every
+//! keystroke. Tasks are represented the same way as in the JSON Lines export.
+//!
+//! The tasks are reloaded whenever another process changes them, and clients that sent a `watch` request are notified
+//! of such changes.
+
+use crate::cli::SubCmdError;
+use chrono::Local;
+use serde::Deserialize;
+use serde_json::{self as json, json};
+use std::{
+  fs,
+  io::{self, BufRead, Write},
+  path::PathBuf,
+  sync::{Arc, Mutex},
+  thread,
+  time::{Duration, SystemTime},
+};
+use toodoux::{
+  config::Config,
+  error::{Error, Operation, Report},
+  filter::TaskDescriptionFilter,
+  jsonl::Record,
+  metadata::{Metadata, MetadataValidationError},
+  nlp, normalize,
+  policy::{self, PolicyViolation},
+  task::{Status, TaskManager, UID},
+};
+
+/// Interval at which the tasks file is checked for changes once watched.
+const WATCH_INTERVAL: Duration = Duration::from_secs(1);
+
+/// JSON-RPC request, or notification if it has no ID.
+#[derive(Debug, Deserialize)]
+struct Request {
+  #[serde(default)]
+  id: Option<json::Value>,
+  method: String,
+  #[serde(default)]
+  params: json::Value,
+}
+
+/// Parameters of the `list` method.
+#[derive(Debug, Default, Deserialize)]
+#[serde(default)]
+struct ListParams {
+  /// Metadata and name filter, as with `td list`.
+  filter: String,
+  /// List all the tasks instead of the active ones.
+  all: bool,
+}
+
+/// Parameters of the `add` method.
+#[derive(Debug, Deserialize)]
+struct AddParams {
+  /// Content of the task, as with `td add`.
+  content: String,
+}
+
+/// Parameters of the `update` method.
+#[derive(Debug, Deserialize)]
+struct UpdateParams {
+  uid: UID,
+  /// New name and metadata, as with `td edit`.
+  #[serde(default)]
+  content: Option<String>,
+  #[serde(default)]
+  status: Option<Status>,
+}
+
+/// Error answered to a request.
+#[derive(Debug)]
+struct RpcError {
+  code: i64,
+  message: String,
+}
+
+impl RpcError {
+  fn parse(err: json::Error) -> Self {
+    RpcError {
+      code: -32700,
+      message: format!("parse error: {}", err),
+    }
+  }
+
+  fn unknown_method(method: &str) -> Self {
+    RpcError {
+      code: -32601,
+      message: format!("unknown method: {}", method),
+    }
+  }
+
+  fn invalid_params(err: json::Error) -> Self {
+    RpcError {
+      code: -32602,
+      message: format!("invalid params: {}", err),
+    }
+  }
+}
+
+impl From<SubCmdError> for RpcError {
+  fn from(err: SubCmdError) -> Self {
+    RpcError {
+      code: -32000,
+      message: Report(&err).to_string(),
+    }
+  }
+}
+
+impl From<Error> for RpcError {
+  fn from(err: Error) -> Self {
+    SubCmdError::from(err).into()
+  }
+}
+
+impl From<MetadataValidationError> for RpcError {
+  fn from(err: MetadataValidationError) -> Self {
+    SubCmdError::from(err).into()
+  }
+}
+
+impl From<PolicyViolation> for RpcError {
+  fn from(err: PolicyViolation) -> Self {
+    SubCmdError::from(err).into()
+  }
+}
+
+impl From<json::Error> for RpcError {
+  fn from(err: json::Error) -> Self {
+    Error::from(err).into()
+  }
+}
+
+/// Dates at which the files storing the tasks — the tasks file and the journal — were last modified, if they exist.
+fn modified(paths: &[PathBuf; 2]) -> [Option<SystemTime>; 2] {
+  let modified = |path: &PathBuf| fs::metadata(path).and_then(|md| md.modified()).ok();
+  [modified(&paths[0]), modified(&paths[1])]
+}
+
+fn storage_paths(config: &Config) -> [PathBuf; 2] {
+  [config.tasks_path(), config.journal_path()]
+}
+
+/// Protocol server.
+struct Server<'a, W, S> {
+  config: &'a Config,
+  task_mgr: &'a mut TaskManager,
+  /// Save the tasks.
+  save: S,
+  output: Arc<Mutex<W>>,
+  /// Date of the last modification of the tasks file we know about.
+  modified: [Option<SystemTime>; 2],
+  watching: bool,
+}
+
+impl<'a, W, S> Server<'a, W, S>
+where
+  W: Write + Send + 'static,
+  S: FnMut(&mut TaskManager) -> Result<(), SubCmdError>,
+{
+  /// Send a message on its own line.
+  fn send(&self, message: json::Value) -> io::Result<()> {
+    let mut output = self.output.lock().unwrap();
+    writeln!(output, "{}", message)?;
+    output.flush()
+  }
+
+  /// Handle a line of input, answering it unless it’s a notification.
+  fn handle_line(&mut self, line: &str) -> io::Result<()> {
+    let request = match json::from_str::<Request>(line) {
+      Ok(request) => request,
+      Err(err) => return self.answer(json::Value::Null, Err(RpcError::parse(err))),
+    };
+
+    let result = self.reload().and_then(|_| self.dispatch(&request));
+
+    match request.id {
+      Some(id) => self.answer(id, result),
+      None => Ok(()),
+    }
+  }
+
+  fn answer(&self, id: json::Value, result: Result<json::Value, RpcError>) -> io::Result<()> {
+    self.send(match result {
+      Ok(result) => json!({ "jsonrpc": "2.0", "id": id, "result": result }),
+      Err(err) => json!({
+        "jsonrpc": "2.0",
+        "id": id,
+        "error": { "code": err.code, "message": err.message },
+      }),
+    })
+  }
+
+  /// Reload the tasks if another process changed them.
+  fn reload(&mut self) -> Result<(), RpcError> {
+    let modified = modified(&storage_paths(self.config));
+
+    if modified != self.modified {
+      log::debug!("tasks changed; reloading them");
+      *self.task_mgr = TaskManager::new_from_config(self.config)?;
+      self.modified = modified;
+    }
+
+    Ok(())
+  }
+
+  fn persist(&mut self) -> Result<(), RpcError> {
+    (self.save)(self.task_mgr)?;
+    self.modified = modified(&storage_paths(self.config));
+    Ok(())
+  }
+
+  fn dispatch(&mut self, request: &Request) -> Result<json::Value, RpcError> {
+    let params = || request.params.clone();
+
+    match request.method.as_str() {
+      "list" => {
+        let params = if request.params.is_null() {
+          ListParams::default()
+        } else {
+          json::from_value(params()).map_err(RpcError::invalid_params)?
+        };
+        self.list(params)
+      }
+
+      "add" => self.add(json::from_value(params()).map_err(RpcError::invalid_params)?),
+      "update" => self.update(json::from_value(params()).map_err(RpcError::invalid_params)?),
+      "watch" => self.watch(),
+      method => Err(RpcError::unknown_method(method)),
+    }
+  }
+
+  fn list(&self, params: ListParams) -> Result<json::Value, RpcError> {
+    let (mut metadata, name) = Metadata::from_words(
+      self.config.priorities(),
+      self.config.fields(),
+      params.filter.split(' '),
+    );
+    normalize::metadata(&self.config.normalization, &mut metadata);
+    Metadata::validate(&metadata)?;
+    let name_filter = TaskDescriptionFilter::new(name.split_ascii_whitespace(), false);
+
+    let tasks = self.task_mgr.filtered_task_listing(
+      self.config.priorities(),
+      self.config.no_priority_order(),
+      metadata,
+      name_filter,
+      true,
+      true,
+      true,
+      params.all,
+      params.all,
+      params.all,
+      if params.all { None } else { Some(false) },
+      false,
+    );
+
+    Ok(json::to_value(
+      tasks
+        .into_iter()
+        .map(|(&uid, task)| Record::new(uid, task))
+        .collect::<Vec<_>>(),
+    )?)
+  }
+
+  fn add(&mut self, params: AddParams) -> Result<json::Value, RpcError> {
+    let mut quick_add = nlp::parse(
+      self.config.priorities(),
+      self.config.fields(),
+      params.content.split(' '),
+      Local::today().naive_local(),
+    );
+    normalize::metadata(&self.config.normalization, &mut quick_add.metadata);
+    Metadata::validate(&quick_add.metadata)?;
+
+    let task = quick_add.into_task();
+    policy::check(
+      &self.config.policies,
+      self.config.priorities(),
+      self.config.fields(),
+      &task,
+    )?;
+
+    let uid = self.task_mgr.register_task(task);
+    self.persist()?;
+
+    self.record(uid)
+  }
+
+  fn update(&mut self, params: UpdateParams) -> Result<json::Value, RpcError> {
+    let task = self
+      .task_mgr
+      .get_mut(params.uid)
+      .ok_or(Error::UnknownTask(params.uid))?;
+
+    if let Some(content) = params.content {
+      let (mut metadata, name) = Metadata::from_words(
+        self.config.priorities(),
+        self.config.fields(),
+        content.split(' '),
+      );
+      normalize::metadata(&self.config.normalization, &mut metadata);
+      Metadata::validate(&metadata)?;
+
+      // edit a copy, so that the task is left untouched if it doesn’t comply with the policies anymore
+      let mut edited = task.clone();
+      edited.apply_metadata(metadata);
+
+      if !name.is_empty() {
+        edited.change_name(name);
+      }
+
+      policy::check(
+        &self.config.policies,
+        self.config.priorities(),
+        self.config.fields(),
+        &edited,
+      )?;
+      *task = edited;
+    }
+
+    if let Some(status) = params.status {
+      task.change_status(status);
+
+      if status == Status::Ongoing && self.config.single_wip() {
+        self.task_mgr.pause_ongoing_tasks(params.uid);
+      }
+    }
+
+    self.persist()?;
+
+    self.record(params.uid)
+  }
+
+  /// Start notifying the client when the tasks change, with `changed` notifications.
+  fn watch(&mut self) -> Result<json::Value, RpcError> {
+    if self.watching {
+      return Ok(json::Value::Bool(true));
+    }
+
+    self.watching = true;
+    let output = self.output.clone();
+    let paths = storage_paths(self.config);
+    let mut last_modified = modified(&paths);
+
+    thread::spawn(move || loop {
+      thread::sleep(WATCH_INTERVAL);
+
+      let modified = modified(&paths);
+      if modified != last_modified {
+        last_modified = modified;
+
+        let mut output = output.lock().unwrap();
+        let sent = writeln!(
+          output,
+          "{}",
+          json!({ "jsonrpc": "2.0", "method": "changed" })
+        )
+        .and_then(|_| output.flush());
+        if sent.is_err() {
+          break;
+        }
+      }
+    });
+
+    Ok(json::Value::Bool(true))
+  }
+
+  fn record(&self, uid: UID) -> Result<json::Value, RpcError> {
+    let task = self.task_mgr.get(uid).ok_or(Error::UnknownTask(uid))?;
+    Ok(json::to_value(Record::new(uid, task))?)
+  }
+}
+
+/// Serve requests read from `input` until it’s closed, writing the responses to `output`.
+pub fn serve<W>(
+  config: &Config,
+  task_mgr: &mut TaskManager,
+  save: impl FnMut(&mut TaskManager) -> Result<(), SubCmdError>,
+  input: impl BufRead,
+  output: W,
+) -> Result<(), SubCmdError>
+where
+  W: Write + Send + 'static,
+{
+  let mut server = Server {
+    config,
+    task_mgr,
+    save,
+    output: Arc::new(Mutex::new(output)),
+    modified: modified(&storage_paths(config)),
+    watching: false,
+  };
+
+  for line in input.lines() {
+    let line = line.map_err(Error::io(Operation::Read, "the standard input"))?;
+
+    if line.trim().is_empty() {
+      continue;
+    }
+
+    server.handle_line(&line).map_err(Error::CannotExport)?;
+  }
+
+  Ok(())
+}
+
+#[cfg(test)]
+mod unit_tests {
+  use super::*;
+
+  /// Output shared with the server, so that the tests can read it back.
+  #[derive(Clone, Default)]
+  struct SharedOutput(Arc<Mutex<Vec<u8>>>);
+
+  impl Write for SharedOutput {
+    fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+      self.0.lock().unwrap().write(buf)
+    }
+
+    fn flush(&mut self) -> io::Result<()> {
+      Ok(())
+    }
+  }
+
+  #[test]
+  fn requests() {
+    let dir = tempdir::TempDir::new("toodoux-rpc").unwrap();
+    let config: Config = toml::from_str(&format!(
+      "[main]\ntasks_file = {:?}",
+      dir.path().display().to_string()
+    ))
+    .unwrap();
+    let mut task_mgr = TaskManager::new_from_config(&config).unwrap();
+    let output = SharedOutput::default();
+
+    let input = [
+      r#"{"jsonrpc": "2.0", "id": 1, "method": "add", "params": {"content": "Write the plugin @toodoux"}}"#,
+      r#"{"jsonrpc": "2.0", "id": 2, "method": "update", "params": {"uid": 0, "status": "Ongoing"}}"#,
+      r#"{"jsonrpc": "2.0", "id": 3, "method": "list", "params": {"filter": "@toodoux"}}"#,
+      r#"{"jsonrpc": "2.0", "id": 4, "method": "update", "params": {"uid": 7}}"#,
+      r#"{"jsonrpc": "2.0", "id": 5, "method": "remove"}"#,
+      r#"{"jsonrpc": "2.0", "method": "list"}"#,
+      "{",
+    ]
+    .join("\n");
+
+    serve(
+      &config,
+      &mut task_mgr,
+      |task_mgr| Ok(task_mgr.save(&config)?),
+      input.as_bytes(),
+      output.clone(),
+    )
+    .unwrap();
+
+    let output = String::from_utf8(output.0.lock().unwrap().clone()).unwrap();
+    let responses = output
+      .lines()
+      .map(|line| json::from_str::<json::Value>(line).unwrap())
+      .collect::<Vec<_>>();
+    assert_eq!(responses.len(), 6);
+
+    assert_eq!(responses[0]["result"]["name"], "Write the plugin");
+    assert_eq!(responses[1]["result"]["status"], "Ongoing");
+    assert_eq!(responses[2]["result"][0]["project"], "toodoux");
+    assert_eq!(responses[3]["error"]["code"], -32000);
+    assert_eq!(responses[4]["error"]["code"], -32601);
+    assert_eq!(responses[5]["error"]["code"], -32700);
+    assert_eq!(responses[5]["id"], json::Value::Null);
+
+    // the tasks were saved
+    let task_mgr = TaskManager::new_from_config(&config).unwrap();
+    assert_eq!(task_mgr.tasks().count(), 1);
+  }
+}
@@ -1,8 +1,27 @@
 //! An abstracton of a terminal.
 
+use std::{
+  env, fmt,
+  io::{self, IsTerminal as _},
+};
+
 pub trait Terminal {
   /// Get the dimension (in characters / columns) of the terminal.
   fn dimensions(&self) -> Option<[usize; 2]>;
+
+  /// Read a line of input, without its trailing newline.
+  ///
+  /// Return [`None`] if no more input is available.
+  fn read_line(&self) -> Option<String>;
+
+  /// Whether the output is an interactive terminal, as opposed to a pipe or a file.
+  fn is_tty(&self) -> bool;
+
+  /// Whether the terminal can display colors.
+  fn supports_color(&self) -> bool;
+
+  /// Whether the terminal renders hyperlinks — the OSC 8 escape sequence — as clickable links.
+  fn supports_hyperlinks(&self) -> bool;
 }
 
 /// Default terminal abstraction..
@@ -12,4 +31,211 @@ impl Terminal for DefaultTerm {
   fn dimensions(&self) -> Option<[usize; 2]> {
     term_size::dimensions().map(|(w, h)| [w, h])
   }
+
+  fn read_line(&self) -> Option<String> {
+    let mut input = String::new();
+    match io::stdin().read_line(&mut input) {
+      Ok(0) | Err(_) => None,
+      Ok(_) => Some(input.trim_end_matches(&['\r', '\n'][..]).to_owned()),
+    }
+  }
+
+  fn is_tty(&self) -> bool {
+    io::stdout().is_terminal()
+  }
+
+  fn supports_color(&self) -> bool {
+    // see https://no-color.org
+    self.is_tty()
+      && env::var_os("NO_COLOR").is_none()
+      && env::var("TERM").map_or(true, |t| t != "dumb")
+      && enable_ansi_sequences()
+  }
+
+  fn supports_hyperlinks(&self) -> bool {
+    self.is_tty()
+      && env::var("TERM").map_or(true, |t| t != "dumb")
+      && detect_hyperlinks(|var| env::var(var).ok())
+  }
+}
+
+/// Detect whether the terminal supports hyperlinks from its environment variables, looked up with `var`.
+///
+/// There is no way to query a terminal for it, so only terminals known to support them, and setting variables that
+/// identify them, are detected. `$FORCE_HYPERLINK` overrides the detection, as with other tools.
+fn detect_hyperlinks(var: impl Fn(&str) -> Option<String>) -> bool {
+  if let Some(force) = var("FORCE_HYPERLINK") {
+    return force != "0";
+  }
+
+  let version = |name| var(name).and_then(|v| v.parse::<u32>().ok());
+  let term = var("TERM").unwrap_or_default();
+
+  [
+    "WT_SESSION",
+    "KITTY_WINDOW_ID",
+    "WEZTERM_EXECUTABLE",
+    "DOMTERM",
+  ]
+  .iter()
+  .any(|&name| var(name).is_some())
+    || matches!(
+      var("TERM_PROGRAM").as_deref(),
+      Some("iTerm.app" | "WezTerm" | "vscode" | "ghostty" | "Hyper")
+    )
+    || version("VTE_VERSION").is_some_and(|v| v >= 5000)
+    || var("KONSOLE_VERSION").is_some()
+    || ["kitty", "alacritty", "foot", "ghostty"]
+      .iter()
+      .any(|&name| term.contains(name))
+}
+
+/// Render `text` as a hyperlink to `url`.
+pub fn hyperlink(url: &str, text: impl fmt::Display) -> String {
+  format!("\x1b]8;;{}\x1b\\{}\x1b]8;;\x1b\\", url, text)
+}
+
+/// Render the `http` and `https` URLs of `text` as hyperlinks, leaving the rest untouched.
+///
+/// URLs end at the first whitespace; punctuation ending them, such as a final dot or a closing parenthesis, is left
+/// out.
+pub fn linkify(text: &str) -> String {
+  text
+    .split(' ')
+    .map(|word| {
+      let start = match word.find("https://").or_else(|| word.find("http://")) {
+        Some(start) => start,
+        None => return word.to_owned(),
+      };
+      let url = word[start..]
+        .trim_end_matches(&['.', ',', ';', ':', '!', '?', ')', ']', '>', '"', '\''][..]);
+      let end = start + url.len();
+
+      format!("{}{}{}", &word[..start], hyperlink(url, url), &word[end..])
+    })
+    .collect::<Vec<_>>()
+    .join(" ")
+}
+
+/// Make the console interpret ANSI escape sequences, used for colors; return whether it does.
+///
+/// Consoles on Windows, such as conhost, only interpret them once virtual terminal processing is enabled, which older
+/// versions of Windows don’t support; terminals setting `$TERM`, such as the one of Git Bash, always interpret them.
+#[cfg(windows)]
+fn enable_ansi_sequences() -> bool {
+  crossterm::ansi_support::supports_ansi()
+}
+
+/// Make the console interpret ANSI escape sequences, used for colors; return whether it does.
+#[cfg(not(windows))]
+fn enable_ansi_sequences() -> bool {
+  true
+}
+
+/// Scripted terminal, used to test interactive flows.
+#[cfg(test)]
+pub struct TestTerm {
+  dimensions: Option<[usize; 2]>,
+  input: std::cell::RefCell<std::collections::VecDeque<String>>,
+  tty: bool,
+}
+
+#[cfg(test)]
+impl TestTerm {
+  /// A 100×1 terminal without any input.
+  pub fn new() -> Self {
+    Self {
+      dimensions: Some([100, 1]),
+      input: Default::default(),
+      tty: true,
+    }
+  }
+
+  pub fn with_dimensions(mut self, dimensions: impl Into<Option<[usize; 2]>>) -> Self {
+    self.dimensions = dimensions.into();
+    self
+  }
+
+  /// Lines to read, in order.
+  pub fn with_input<'a>(mut self, lines: impl IntoIterator<Item = &'a str>) -> Self {
+    self.input = std::cell::RefCell::new(lines.into_iter().map(str::to_owned).collect());
+    self
+  }
+
+  pub fn with_tty(mut self, tty: bool) -> Self {
+    self.tty = tty;
+    self
+  }
+
+  /// Number of lines that haven’t been read yet.
+  pub fn remaining_input(&self) -> usize {
+    self.input.borrow().len()
+  }
+}
+
+#[cfg(test)]
+impl Terminal for TestTerm {
+  fn dimensions(&self) -> Option<[usize; 2]> {
+    self.dimensions
+  }
+
+  fn read_line(&self) -> Option<String> {
+    self.input.borrow_mut().pop_front()
+  }
+
+  fn is_tty(&self) -> bool {
+    self.tty
+  }
+
+  fn supports_color(&self) -> bool {
+    false
+  }
+
+  fn supports_hyperlinks(&self) -> bool {
+    false
+  }
+}
+
+#[cfg(test)]
+mod unit_tests {
+  use super::*;
+
+  #[test]
+  fn hyperlinks_detection() {
+    let env = |vars: &'static [(&'static str, &'static str)]| {
+      move |name: &str| {
+        vars
+          .iter()
+          .find(|&&(var, _)| var == name)
+          .map(|&(_, value)| value.to_owned())
+      }
+    };
+
+    assert!(!detect_hyperlinks(env(&[("TERM", "xterm-256color")])));
+    assert!(detect_hyperlinks(env(&[("TERM", "xterm-kitty")])));
+    assert!(detect_hyperlinks(env(&[("TERM_PROGRAM", "iTerm.app")])));
+    assert!(detect_hyperlinks(env(&[("VTE_VERSION", "6003")])));
+    assert!(!detect_hyperlinks(env(&[("VTE_VERSION", "4601")])));
+    assert!(detect_hyperlinks(env(&[("FORCE_HYPERLINK", "1")])));
+    assert!(!detect_hyperlinks(env(&[
+      ("FORCE_HYPERLINK", "0"),
+      ("WT_SESSION", "1")
+    ])));
+  }
+
+  #[test]
+  fn linkified_urls() {
+    assert_eq!(linkify("no link here"), "no link here");
+    assert_eq!(
+      linkify("see (https://example.org/a_b). Thanks"),
+      format!(
+        "see ({}). Thanks",
+        hyperlink("https://example.org/a_b", "https://example.org/a_b")
+      )
+    );
+    assert_eq!(
+      hyperlink("toodoux://task/42", 42),
+      "\x1b]8;;toodoux://task/42\x1b\\42\x1b]8;;\x1b\\"
+    );
+  }
 }
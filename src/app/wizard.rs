@@ -0,0 +1,108 @@
+//! Configuration creation wizard.
+
+use crate::term::Terminal;
+use colored::Colorize as _;
+use std::io::{self, Write as _};
+use toodoux::tr;
+
+/// Ask the user whether the configuration file should be created.
+///
+/// Keep asking until the answer is understood; running out of input is a refusal.
+pub fn ask_config_creation(term: &impl Terminal) -> bool {
+  print_introduction_text();
+
+  loop {
+    print_wizard_question();
+
+    let input = match term.read_line() {
+      Some(input) => input,
+      None => return false,
+    };
+
+    match input.trim_end() {
+      "Y" | "y" | "" => return true,
+      "N" | "n" => return false,
+      _ => {
+        println!(
+          "{}",
+          tr!("I’m so sorry, but I didn’t quite get that.").red()
+        );
+      }
+    }
+  }
+}
+
+fn print_introduction_text() {
+  println!(
+    "{}",
+    tr!(
+      "Hello! It seems like you’re new to {0}!\n\n{0} is a modern take on task / todo lists, mostly based on the \
+       amazing emacs’ {1} and {2}. Instead of recreating the same plugin inside everybody’s favorite editors over and \
+       over, {0} takes it the UNIX way and just does one thing good: {3}.\n\nYou will first be able to {4} new tasks, \
+       {5} existing tasks, {6} some and {7} them all. Then, you will be able to enjoy more advanced features, such as \
+       {8} and {9} tasks, {10} them, as well as {11}. Time metadata are automatically handled for you to follow along.",
+      "toodoux".purple().bold(),
+      "Org-Mode".purple().bold(),
+      "taskwarrior".purple().bold(),
+      tr!("editing tasks").bold(),
+      tr!("add").green().bold(),
+      tr!("edit").green().bold(),
+      tr!("remove").green().bold(),
+      tr!("list").green().bold(),
+      tr!("capturing").green().bold(),
+      tr!("refiling").green().bold(),
+      tr!("filtering").green().bold(),
+      tr!("putting deadlines").green().bold(),
+    )
+  );
+}
+
+fn print_wizard_question() {
+  print!(
+    "\n{wizard_question} ({Y}/{n}) ➤ ",
+    wizard_question =
+      tr!("You don’t seem to have a configuration set up…\nWould you like to set it up?").blue(),
+    Y = "Y".green().bold(),
+    n = "n".red(),
+  );
+
+  // a closed output is not worth a panic; the answer is read anyway
+  let _ = io::stdout().flush();
+}
+
+pub fn print_no_file_information() {
+  println!(
+    "\n{toodoux} {rest}",
+    toodoux = "toodoux".purple().bold(),
+    rest = tr!(
+      "won’t work without a configuration file. If you don’t want to generate it via this interactive wizard, you \
+       can create it by hand and put it in the right folder, depending on the platform you run on."
+    )
+    .red()
+  );
+}
+
+#[cfg(test)]
+mod unit_tests {
+  use super::*;
+  use crate::term::TestTerm;
+
+  #[test]
+  fn ask_config_creation_answers() {
+    assert!(ask_config_creation(&TestTerm::new().with_input(vec!["y"])));
+    assert!(ask_config_creation(&TestTerm::new().with_input(vec![""])));
+    assert!(!ask_config_creation(&TestTerm::new().with_input(vec!["n"])));
+  }
+
+  #[test]
+  fn ask_config_creation_asks_again() {
+    let term = TestTerm::new().with_input(vec!["what", "maybe", "Y"]);
+    assert!(ask_config_creation(&term));
+    assert_eq!(term.remaining_input(), 0);
+  }
+
+  #[test]
+  fn ask_config_creation_without_input() {
+    assert!(!ask_config_creation(&TestTerm::new()));
+  }
+}
@@ -1,5 +1,6 @@
 //! Initiate the configuration file creation when not present.
 
+use chrono::Duration;
 use colored::{Color as Col, ColoredString, Colorize};
 use core::fmt::Formatter;
 use serde::{
@@ -7,19 +8,37 @@ use serde::{
   Deserialize, Serialize,
 };
 use std::{
-  fmt, fs,
+  collections::BTreeMap,
+  env, fmt, fs,
   ops::Deref,
   path::{Path, PathBuf},
   str::FromStr,
 };
 
-use crate::error::Error;
+use crate::{
+  error::{Error, Operation},
+  i18n::Language,
+  metadata::{self, Fields, NoPriorityOrder, Priorities},
+  task::{Status, Task},
+  tr,
+};
 
 #[derive(Debug, Deserialize, Serialize, Default)]
 #[serde(default)]
 pub struct Config {
   pub main: MainConfig,
   pub colors: ColorConfig,
+  pub digest: DigestConfig,
+  pub publish: PublishConfig,
+  pub privacy: PrivacyConfig,
+  pub normalization: NormalizationConfig,
+  #[serde(skip_serializing_if = "Vec::is_empty")]
+  pub policies: Vec<Policy>,
+  #[serde(skip_serializing_if = "Option::is_none")]
+  pub jira: Option<JiraConfig>,
+  /// Files the keys were read from, by dotted path; keys absent from every file have their default value.
+  #[serde(skip)]
+  origins: BTreeMap<String, PathBuf>,
 }
 
 #[derive(Debug, Deserialize, Serialize)]
@@ -27,10 +46,16 @@ pub struct Config {
 pub struct MainConfig {
   /// Editor to use for interactive editing.
   ///
-  /// If absent, default to `$EDITOR`. If neither the configuration or `$EDITOR` is set,
-  /// interactive editing is disabled.
+  /// It is a command, possibly with arguments, such as `code --wait`. `$VISUAL` and `$EDITOR` take precedence over it.
+  /// If none of them is set, interactive editing is disabled.
   interactive_editor: Option<String>,
 
+  /// Arguments passed to the editor after the file to edit.
+  ///
+  /// If absent, `+$` is passed to vi, Vim and Neovim, so that they open at the end of the file, and nothing to other
+  /// editors.
+  editor_args: Option<Vec<String>>,
+
   /// Path to the folder containing all the tasks.
   tasks_file: PathBuf,
 
@@ -46,12 +71,18 @@ pub struct MainConfig {
   /// Name of the “CANCELLED” state.
   cancelled_alias: String,
 
+  /// Name of the “PAUSED” state.
+  paused_alias: String,
+
   /// “UID” column name.
   uid_col_name: String,
 
   /// “Age” column name.
   age_col_name: String,
 
+  /// “Activity” column name.
+  activity_col_name: String,
+
   /// “Spent” column name.
   spent_col_name: String,
 
@@ -61,6 +92,12 @@ pub struct MainConfig {
   /// “Project” column name.
   project_col_name: String,
 
+  /// “Context” column name.
+  context_col_name: String,
+
+  /// “Milestone” column name.
+  milestone_col_name: String,
+
   /// “Tags” column name.
   tags_col_name: String,
 
@@ -76,88 +113,390 @@ pub struct MainConfig {
   /// Maximum number of warping lines of task description before breaking it (and adding the ellipsis character).
   max_description_lines: usize,
 
+  /// How descriptions longer than their column overflow: `"truncate"`, `"wrap"` or `"fit"`.
+  description_overflow: DescriptionOverflow,
+
+  /// Width of the terminal, in columns, below which descriptions are kept on a single line, whatever the overflow.
+  single_line_below_width: Option<usize>,
+
   /// "Number of notes” column name."
   notes_nb_col_name: String,
 
+  /// “Progress” column name.
+  progress_col_name: String,
+
   /// Display tags in listings.
   display_tags_listings: bool,
 
+  /// Display the time since the last activity on tasks in listings, next to their age.
+  display_activity_listings: bool,
+
+  /// Maximum width of the project column in listings; longer project names are truncated with an ellipsis.
+  max_project_width: Option<usize>,
+
+  /// Maximum width of the tags column in listings; longer lists of tags are truncated with an ellipsis.
+  max_tags_width: Option<usize>,
+
+  /// Maximum number of tags displayed in listings; the other ones are counted, such as `a, b +3`.
+  max_tags: Option<usize>,
+
+  /// Display a footer with the totals of the tasks after listings.
+  summary_footer: bool,
+
+  /// Split the default listing, `td` without subcommand, into sections: overdue, due soon, in progress and other tasks.
+  ///
+  /// Set it to `false` to get a single listing.
+  sectioned_listing: bool,
+
+  /// Number of days ahead a deadline is considered due soon in the sectioned listing.
+  due_soon_days: u32,
+
   /// Show the previous notes when adding a new note.
   ///
   /// This option allows to show all the previously recorded notes for a given task as a header of the current note.
   /// The note history will be automatically discarded and will not appear in the new note.
   previous_notes_help: bool,
+
+  /// Number of lines above which notes are collapsed in `td show` and `td note ls`, unless `--full` is passed.
+  ///
+  /// `0` never collapses notes.
+  collapsed_note_lines: usize,
+
+  /// Prefix of the comment lines in the editor when writing a note.
+  ///
+  /// The previous notes are commented out with this prefix, and every line starting with it is removed from the note.
+  /// An empty prefix disables comments, and thus the previous notes help.
+  note_comment_prefix: String,
+
+  /// Number of days removed tasks are kept in the trash before `td trash empty` purges them.
+  trash_retention_days: u32,
+
+  /// Minimum similarity, between `0` and `1`, for a new task to be considered a duplicate of an active task.
+  ///
+  /// Set it above `1` to disable duplicate detection.
+  duplicate_threshold: f32,
+
+  /// Correct the projects and tags that look like typos of existing ones without asking.
+  ///
+  /// Otherwise, the correction is suggested and must be confirmed.
+  autocorrect_metadata: bool,
+
+  /// Ask for confirmation before running destructive commands.
+  ///
+  /// Can be bypassed with `--yes`; set it to `false` for scripts.
+  confirm: bool,
+
+  /// Record every mutation of the tasks in a log file in the configuration root.
+  ///
+  /// Useful to troubleshoot synchronization and scripting issues.
+  mutation_log: bool,
+
+  /// Plain output.
+  ///
+  /// Disable colors, unicode decorations and column padding in listings, and output tab-separated columns instead.
+  plain_output: bool,
+
+  /// Number of days after which someday tasks are surfaced again by `td review`.
+  review_interval_days: u32,
+
+  /// Allow a single ongoing task at a time: starting a task pauses the other ongoing ones.
+  single_wip: bool,
+
+  /// Seal the history of each task with a hash chain over its events, so that `td doctor` detects manual edits of the
+  /// tasks file.
+  integrity_hashes: bool,
+
+  /// Append the changes to a journal instead of rewriting the whole tasks file on every save.
+  ///
+  /// The journal is compacted into the tasks file once it grows large, or with `td gc`.
+  journal: bool,
+
+  /// Write the tasks file without indentation and with shorter names for the fields of events, to shrink it.
+  ///
+  /// Both forms are always read, so that the option can be toggled at any time.
+  compact_tasks_file: bool,
+
+  /// Number of backups of the tasks kept in the `backups` directory, taken before destructive commands such as
+  /// `remove --all`, `import` or `gc`, and before migrations.
+  ///
+  /// Set it to `0` to disable backups.
+  backups: usize,
+
+  /// Name recorded as the author of every change to the tasks.
+  ///
+  /// Useful when several people share the same tasks.
+  author: Option<String>,
+
+  /// Language of the user-facing strings, such as `"fr"`.
+  ///
+  /// If absent, default to the language of the locale (`$LC_ALL`, `$LC_MESSAGES` or `$LANG`), or English.
+  language: Option<Language>,
+
+  /// Whether task UIDs and URLs are rendered as clickable hyperlinks: `"auto"`, `"always"` or `"never"`.
+  hyperlinks: Hyperlinks,
+
+  /// Where tasks without a priority are listed: `"above-lowest"`, `"below-lowest"` or `"last"`.
+  no_priority_order: NoPriorityOrder,
+
+  /// Alignment of the numeric columns in listings.
+  column_alignment: ColumnAlignment,
+
+  /// User-defined fields, settable with the `name:value` metadata and displayed as extra columns in listings.
+  #[serde(skip_serializing_if = "Fields::is_empty")]
+  fields: Fields,
+
+  /// Priority levels, from the lowest to the highest.
+  priorities: Priorities,
+}
+
+/// How descriptions longer than their column overflow in listings.
+#[derive(Clone, Copy, Debug, Default, Deserialize, Eq, PartialEq, Serialize)]
+#[serde(rename_all = "kebab-case")]
+pub enum DescriptionOverflow {
+  /// Wrap up to `max_description_lines` lines, the last one ending with an ellipsis.
+  #[default]
+  Truncate,
+  /// Wrap on as many lines as needed.
+  Wrap,
+  /// Wrap on as many lines as the height of the terminal allows for all the listed tasks, up to
+  /// `max_description_lines` lines.
+  Fit,
+}
+
+/// Whether task UIDs and URLs are rendered as clickable terminal hyperlinks, with the OSC 8 escape sequence.
+#[derive(Clone, Copy, Debug, Default, Deserialize, Eq, PartialEq, Serialize)]
+#[serde(rename_all = "lowercase")]
+pub enum Hyperlinks {
+  /// Only if the terminal is known to support them.
+  #[default]
+  Auto,
+  Always,
+  Never,
+}
+
+/// Alignment of a column in listings.
+#[derive(Clone, Copy, Debug, Deserialize, Eq, PartialEq, Serialize)]
+#[serde(rename_all = "lowercase")]
+pub enum Alignment {
+  Left,
+  Right,
+}
+
+impl Alignment {
+  /// Pad a value to `width` columns according to the alignment.
+  pub fn pad(self, value: impl fmt::Display, width: usize) -> String {
+    match self {
+      Alignment::Left => format!("{:<width$}", value, width = width),
+      Alignment::Right => format!("{:>width$}", value, width = width),
+    }
+  }
+}
+
+/// Alignment of the numeric columns in listings; they are right-aligned by default, so that digits line up.
+#[derive(Debug, Deserialize, Serialize)]
+#[serde(default)]
+pub struct ColumnAlignment {
+  /// Alignment of the UID column.
+  pub uid: Alignment,
+
+  /// Alignment of the age column.
+  pub age: Alignment,
+
+  /// Alignment of the spent time column.
+  pub spent: Alignment,
+
+  /// Alignment of the number of notes column.
+  pub notes: Alignment,
+}
+
+impl Default for ColumnAlignment {
+  fn default() -> Self {
+    Self {
+      uid: Alignment::Right,
+      age: Alignment::Right,
+      spent: Alignment::Right,
+      notes: Alignment::Right,
+    }
+  }
 }
 
 impl Default for MainConfig {
   fn default() -> Self {
     Self {
       interactive_editor: None,
+      editor_args: None,
       tasks_file: dirs::config_dir().unwrap().join("toodoux"),
       todo_alias: "TODO".to_owned(),
       wip_alias: "WIP".to_owned(),
       done_alias: "DONE".to_owned(),
       cancelled_alias: "CANCELLED".to_owned(),
+      paused_alias: "PAUSED".to_owned(),
       uid_col_name: "UID".to_owned(),
-      age_col_name: "Age".to_owned(),
-      spent_col_name: "Spent".to_owned(),
-      prio_col_name: "Prio".to_owned(),
-      project_col_name: "Project".to_owned(),
-      tags_col_name: "Tags".to_owned(),
-      status_col_name: "Status".to_owned(),
-      description_col_name: "Description".to_owned(),
-      notes_nb_col_name: "Notes".to_owned(),
+      age_col_name: tr!("Age").to_owned(),
+      activity_col_name: tr!("Activity").to_owned(),
+      spent_col_name: tr!("Spent").to_owned(),
+      prio_col_name: tr!("Prio").to_owned(),
+      project_col_name: tr!("Project").to_owned(),
+      context_col_name: tr!("Context").to_owned(),
+      milestone_col_name: tr!("Milestone").to_owned(),
+      tags_col_name: tr!("Tags").to_owned(),
+      status_col_name: tr!("Status").to_owned(),
+      description_col_name: tr!("Description").to_owned(),
+      notes_nb_col_name: tr!("Notes").to_owned(),
+      progress_col_name: tr!("Progress").to_owned(),
       display_empty_cols: false,
       max_description_lines: 2,
+      description_overflow: DescriptionOverflow::default(),
+      single_line_below_width: None,
       display_tags_listings: true,
+      display_activity_listings: false,
+      max_project_width: None,
+      max_tags_width: None,
+      max_tags: None,
+      summary_footer: true,
+      sectioned_listing: true,
+      due_soon_days: 3,
       previous_notes_help: true,
+      collapsed_note_lines: 20,
+      note_comment_prefix: "%%".to_owned(),
+      trash_retention_days: 30,
+      duplicate_threshold: 0.8,
+      autocorrect_metadata: false,
+      confirm: true,
+      mutation_log: false,
+      plain_output: false,
+      review_interval_days: 7,
+      single_wip: false,
+      integrity_hashes: false,
+      journal: false,
+      compact_tasks_file: false,
+      backups: 10,
+      author: None,
+      language: None,
+      hyperlinks: Hyperlinks::default(),
+      no_priority_order: NoPriorityOrder::default(),
+      column_alignment: ColumnAlignment::default(),
+      fields: Fields::default(),
+      priorities: Priorities::default(),
     }
   }
 }
 
 impl MainConfig {
-  #[allow(dead_code)]
+  #[allow(dead_code, clippy::too_many_arguments)]
   pub fn new(
     interactive_editor: impl Into<Option<String>>,
+    editor_args: impl Into<Option<Vec<String>>>,
     tasks_file: impl Into<PathBuf>,
     todo_alias: impl Into<String>,
     wip_alias: impl Into<String>,
     done_alias: impl Into<String>,
     cancelled_alias: impl Into<String>,
+    paused_alias: impl Into<String>,
     uid_col_name: impl Into<String>,
     age_col_name: impl Into<String>,
+    activity_col_name: impl Into<String>,
     spent_col_name: impl Into<String>,
     prio_col_name: impl Into<String>,
     project_col_name: impl Into<String>,
+    context_col_name: impl Into<String>,
+    milestone_col_name: impl Into<String>,
     tags_col_name: impl Into<String>,
     status_col_name: impl Into<String>,
     description_col_name: impl Into<String>,
     notes_nb_col_name: impl Into<String>,
+    progress_col_name: impl Into<String>,
     display_empty_cols: bool,
     max_description_lines: usize,
+    description_overflow: DescriptionOverflow,
+    single_line_below_width: impl Into<Option<usize>>,
     display_tags_listings: bool,
+    display_activity_listings: bool,
+    max_project_width: impl Into<Option<usize>>,
+    max_tags_width: impl Into<Option<usize>>,
+    max_tags: impl Into<Option<usize>>,
+    summary_footer: bool,
+    sectioned_listing: bool,
+    due_soon_days: u32,
     previous_notes_help: bool,
+    collapsed_note_lines: usize,
+    note_comment_prefix: impl Into<String>,
+    trash_retention_days: u32,
+    duplicate_threshold: f32,
+    autocorrect_metadata: bool,
+    confirm: bool,
+    mutation_log: bool,
+    plain_output: bool,
+    review_interval_days: u32,
+    single_wip: bool,
+    integrity_hashes: bool,
+    journal: bool,
+    compact_tasks_file: bool,
+    backups: usize,
+    author: impl Into<Option<String>>,
+    language: impl Into<Option<Language>>,
+    hyperlinks: Hyperlinks,
+    no_priority_order: NoPriorityOrder,
+    column_alignment: ColumnAlignment,
+    fields: Fields,
+    priorities: Priorities,
   ) -> Self {
     Self {
       interactive_editor: interactive_editor.into(),
+      editor_args: editor_args.into(),
       tasks_file: tasks_file.into(),
       todo_alias: todo_alias.into(),
       wip_alias: wip_alias.into(),
       done_alias: done_alias.into(),
       cancelled_alias: cancelled_alias.into(),
+      paused_alias: paused_alias.into(),
       uid_col_name: uid_col_name.into(),
       age_col_name: age_col_name.into(),
+      activity_col_name: activity_col_name.into(),
       spent_col_name: spent_col_name.into(),
       prio_col_name: prio_col_name.into(),
       project_col_name: project_col_name.into(),
+      context_col_name: context_col_name.into(),
+      milestone_col_name: milestone_col_name.into(),
       tags_col_name: tags_col_name.into(),
       status_col_name: status_col_name.into(),
       description_col_name: description_col_name.into(),
       notes_nb_col_name: notes_nb_col_name.into(),
+      progress_col_name: progress_col_name.into(),
       display_empty_cols,
       max_description_lines,
+      description_overflow,
+      single_line_below_width: single_line_below_width.into(),
       display_tags_listings,
+      display_activity_listings,
+      max_project_width: max_project_width.into(),
+      max_tags_width: max_tags_width.into(),
+      max_tags: max_tags.into(),
+      summary_footer,
+      sectioned_listing,
+      due_soon_days,
       previous_notes_help,
+      collapsed_note_lines,
+      note_comment_prefix: note_comment_prefix.into(),
+      trash_retention_days,
+      duplicate_threshold,
+      autocorrect_metadata,
+      confirm,
+      mutation_log,
+      plain_output,
+      review_interval_days,
+      single_wip,
+      integrity_hashes,
+      journal,
+      compact_tasks_file,
+      backups,
+      author: author.into(),
+      language: language.into(),
+      hyperlinks,
+      no_priority_order,
+      column_alignment,
+      fields,
+      priorities,
     }
   }
 }
@@ -165,15 +504,30 @@ impl MainConfig {
 impl Config {
   #[allow(dead_code)]
   pub fn new(main: MainConfig, colors: ColorConfig) -> Self {
-    Config { main, colors }
+    Config {
+      main,
+      colors,
+      digest: DigestConfig::default(),
+      publish: PublishConfig::default(),
+      privacy: PrivacyConfig::default(),
+      normalization: NormalizationConfig::default(),
+      policies: Vec::new(),
+      jira: None,
+      origins: BTreeMap::new(),
+    }
   }
 
-  fn get_config_path() -> Result<PathBuf, Error> {
+  /// Directory of the configuration when none is given explicitly.
+  pub fn get_config_path() -> Result<PathBuf, Error> {
     log::trace!("getting configuration root path from the environment");
-    let home = dirs::config_dir().ok_or_else(|| Error::NoConfigDir)?;
-    let path = Path::new(&home).join("toodoux");
+    let path = if cfg!(windows) {
+      let profile = env::var_os("USERPROFILE").map(PathBuf::from);
+      windows_config_dir(profile.as_deref(), dirs::config_dir())
+    } else {
+      dirs::config_dir().map(|home| home.join("toodoux"))
+    };
 
-    Ok(path)
+    path.ok_or(Error::NoConfigDir)
   }
 
   pub fn from_dir(path: impl AsRef<Path>) -> Result<Option<Self>, Error> {
@@ -181,14 +535,40 @@ impl Config {
 
     log::trace!("reading configuration from {}", path.display());
     if path.is_file() {
-      let content = fs::read_to_string(&path).map_err(Error::CannotOpenFile)?;
-      let parsed = toml::from_str(&content).map_err(Error::CannotDeserializeFromTOML)?;
-      Ok(Some(parsed))
+      let mut origins = BTreeMap::new();
+      let table = read_layers(&path, &mut Vec::new(), &mut origins)?;
+      let mut config: Config = toml::Value::Table(table)
+        .try_into()
+        .map_err(|source| Error::InvalidConfig { path, source })?;
+      config.origins = origins;
+      Ok(Some(config))
     } else {
       Ok(None)
     }
   }
 
+  /// Effective value of a key, as a dotted path such as `main.tasks_file`; `None` if there is no such key.
+  pub fn value(&self, key: &str) -> Result<Option<toml::Value>, Error> {
+    let mut value = toml::Value::try_from(self).map_err(Error::CannotSerializeToTOML)?;
+
+    for part in key.split('.') {
+      match value {
+        toml::Value::Table(mut table) => match table.remove(part) {
+          Some(v) => value = v,
+          None => return Ok(None),
+        },
+        _ => return Ok(None),
+      }
+    }
+
+    Ok(Some(value))
+  }
+
+  /// File a key was read from, as a dotted path to a value — not a table; `None` if it has its default value.
+  pub fn origin(&self, key: &str) -> Option<&Path> {
+    self.origins.get(key).map(PathBuf::as_path)
+  }
+
   pub fn root_dir(&self) -> &Path {
     &self.main.tasks_file
   }
@@ -201,10 +581,26 @@ impl Config {
     self.main.interactive_editor.as_deref()
   }
 
+  pub fn editor_args(&self) -> Option<&[String]> {
+    self.main.editor_args.as_deref()
+  }
+
   pub fn tasks_path(&self) -> PathBuf {
     self.main.tasks_file.join("tasks.json")
   }
 
+  pub fn journal_path(&self) -> PathBuf {
+    self.main.tasks_file.join("tasks.journal")
+  }
+
+  pub fn backups_dir(&self) -> PathBuf {
+    self.main.tasks_file.join("backups")
+  }
+
+  pub fn note_template_path(&self) -> PathBuf {
+    self.main.tasks_file.join("note-template.md")
+  }
+
   pub fn todo_alias(&self) -> &str {
     &self.main.todo_alias
   }
@@ -221,6 +617,10 @@ impl Config {
     &self.main.cancelled_alias
   }
 
+  pub fn paused_alias(&self) -> &str {
+    &self.main.paused_alias
+  }
+
   pub fn uid_col_name(&self) -> &str {
     &self.main.uid_col_name
   }
@@ -229,6 +629,10 @@ impl Config {
     &self.main.age_col_name
   }
 
+  pub fn activity_col_name(&self) -> &str {
+    &self.main.activity_col_name
+  }
+
   pub fn spent_col_name(&self) -> &str {
     &self.main.spent_col_name
   }
@@ -241,6 +645,14 @@ impl Config {
     &self.main.project_col_name
   }
 
+  pub fn context_col_name(&self) -> &str {
+    &self.main.context_col_name
+  }
+
+  pub fn milestone_col_name(&self) -> &str {
+    &self.main.milestone_col_name
+  }
+
   pub fn tags_col_name(&self) -> &str {
     &self.main.tags_col_name
   }
@@ -257,6 +669,10 @@ impl Config {
     &self.main.notes_nb_col_name
   }
 
+  pub fn progress_col_name(&self) -> &str {
+    &self.main.progress_col_name
+  }
+
   pub fn display_empty_cols(&self) -> bool {
     self.main.display_empty_cols
   }
@@ -265,14 +681,135 @@ impl Config {
     self.main.max_description_lines
   }
 
+  pub fn description_overflow(&self) -> DescriptionOverflow {
+    self.main.description_overflow
+  }
+
+  pub fn single_line_below_width(&self) -> Option<usize> {
+    self.main.single_line_below_width
+  }
+
   pub fn display_tags_listings(&self) -> bool {
     self.main.display_tags_listings
   }
 
+  pub fn display_activity_listings(&self) -> bool {
+    self.main.display_activity_listings
+  }
+
+  pub fn max_project_width(&self) -> Option<usize> {
+    self.main.max_project_width
+  }
+
+  pub fn max_tags_width(&self) -> Option<usize> {
+    self.main.max_tags_width
+  }
+
+  pub fn max_tags(&self) -> Option<usize> {
+    self.main.max_tags
+  }
+
+  pub fn summary_footer(&self) -> bool {
+    self.main.summary_footer
+  }
+
+  pub fn sectioned_listing(&self) -> bool {
+    self.main.sectioned_listing
+  }
+
+  pub fn due_soon_days(&self) -> u32 {
+    self.main.due_soon_days
+  }
+
   pub fn previous_notes_help(&self) -> bool {
     self.main.previous_notes_help
   }
 
+  pub fn collapsed_note_lines(&self) -> usize {
+    self.main.collapsed_note_lines
+  }
+
+  pub fn note_comment_prefix(&self) -> &str {
+    &self.main.note_comment_prefix
+  }
+
+  pub fn trash_retention_days(&self) -> u32 {
+    self.main.trash_retention_days
+  }
+
+  pub fn duplicate_threshold(&self) -> f32 {
+    self.main.duplicate_threshold
+  }
+
+  pub fn autocorrect_metadata(&self) -> bool {
+    self.main.autocorrect_metadata
+  }
+
+  pub fn confirm(&self) -> bool {
+    self.main.confirm
+  }
+
+  pub fn mutation_log(&self) -> bool {
+    self.main.mutation_log
+  }
+
+  pub fn plain_output(&self) -> bool {
+    self.main.plain_output
+  }
+
+  pub fn review_interval_days(&self) -> u32 {
+    self.main.review_interval_days
+  }
+
+  pub fn single_wip(&self) -> bool {
+    self.main.single_wip
+  }
+
+  pub fn integrity_hashes(&self) -> bool {
+    self.main.integrity_hashes
+  }
+
+  pub fn journal(&self) -> bool {
+    self.main.journal
+  }
+
+  pub fn compact_tasks_file(&self) -> bool {
+    self.main.compact_tasks_file
+  }
+
+  pub fn backups(&self) -> usize {
+    self.main.backups
+  }
+
+  pub fn author(&self) -> Option<&str> {
+    self.main.author.as_deref()
+  }
+
+  /// Configured language, if any.
+  pub fn language(&self) -> Option<Language> {
+    self.main.language
+  }
+
+  pub fn fields(&self) -> &Fields {
+    &self.main.fields
+  }
+
+  pub fn priorities(&self) -> &Priorities {
+    &self.main.priorities
+  }
+
+  pub fn hyperlinks(&self) -> Hyperlinks {
+    self.main.hyperlinks
+  }
+
+  pub fn no_priority_order(&self) -> NoPriorityOrder {
+    self.main.no_priority_order
+  }
+
+  pub fn column_alignment(&self) -> &ColumnAlignment {
+    &self.main.column_alignment
+  }
+
   pub fn get() -> Result<Option<Self>, Error> {
     let path = Self::get_config_path()?;
     Self::from_dir(path)
@@ -298,10 +835,11 @@ impl Config {
 
   pub fn save(&self) -> Result<(), Error> {
     let root_dir = self.root_dir();
-    fs::create_dir_all(root_dir).map_err(Error::CannotSave)?;
+    fs::create_dir_all(root_dir).map_err(Error::io(Operation::SaveConfig, root_dir))?;
 
+    let path = self.config_toml_path();
     let serialized = toml::to_string_pretty(self).map_err(Error::CannotSerializeToTOML)?;
-    let _ = fs::write(self.config_toml_path(), serialized).map_err(Error::CannotSave)?;
+    fs::write(&path, serialized).map_err(Error::io(Operation::SaveConfig, &path))?;
 
     Ok(())
   }
@@ -336,6 +874,100 @@ impl StyleAttribute {
   }
 }
 
+/// Configuration directory on Windows, given the user profile — `%USERPROFILE%` — and the roaming application data.
+///
+/// A `.config\toodoux` directory in the user profile, as on other systems, takes precedence, so that configurations
+/// shared with them are found; otherwise, the directory is in the roaming application data, found in the user profile
+/// if the known folder cannot be queried.
+fn windows_config_dir(profile: Option<&Path>, app_data: Option<PathBuf>) -> Option<PathBuf> {
+  profile
+    .map(|profile| profile.join(".config").join("toodoux"))
+    .filter(|path| path.is_dir())
+    .or_else(|| {
+      app_data
+        .or_else(|| profile.map(|profile| profile.join("AppData").join("Roaming")))
+        .map(|app_data| app_data.join("toodoux"))
+    })
+}
+
+/// Read a configuration file, along with the files it includes, into a single TOML table.
+///
+/// The files listed in the `include` key, relative to the including file, are merged in order, and the including file
+/// on top of them, so that later files override earlier ones: tables are merged key by key, and other values —
+/// arrays included — are replaced. `including` is the chain of files being read, to detect cycles, and `origins`
+/// records which file each value comes from.
+fn read_layers(
+  path: &Path,
+  including: &mut Vec<PathBuf>,
+  origins: &mut BTreeMap<String, PathBuf>,
+) -> Result<toml::value::Table, Error> {
+  // the same file can be reached through different paths
+  let canonical = fs::canonicalize(path).unwrap_or_else(|_| path.to_owned());
+  if including.contains(&canonical) {
+    return Err(Error::ConfigIncludeCycle(path.to_owned()));
+  }
+
+  log::trace!("reading configuration layer {}", path.display());
+  let content = fs::read_to_string(path).map_err(Error::io(Operation::ReadConfig, path))?;
+  let invalid = |source| Error::InvalidConfig {
+    path: path.to_owned(),
+    source,
+  };
+  let mut layer = toml::from_str::<toml::value::Table>(&content).map_err(invalid)?;
+  let includes = match layer.remove("include") {
+    Some(includes) => includes.try_into::<Vec<PathBuf>>().map_err(invalid)?,
+    None => Vec::new(),
+  };
+
+  let dir = path.parent().unwrap_or_else(|| Path::new(""));
+  let mut merged = toml::value::Table::new();
+  including.push(canonical);
+  for include in includes {
+    let included = read_layers(&dir.join(include), including, origins)?;
+    merge_tables(&mut merged, included);
+  }
+  including.pop();
+
+  record_origins(&layer, "", path, origins);
+  merge_tables(&mut merged, layer);
+  Ok(merged)
+}
+
+/// Merge `layer` into `table`: tables are merged key by key, and other values replaced.
+fn merge_tables(table: &mut toml::value::Table, layer: toml::value::Table) {
+  for (key, value) in layer {
+    match (table.get_mut(&key), value) {
+      (Some(toml::Value::Table(table)), toml::Value::Table(layer)) => merge_tables(table, layer),
+      (_, value) => {
+        table.insert(key, value);
+      }
+    }
+  }
+}
+
+/// Record `path` as the origin of all the values of `layer` — but tables, which are merged — under `prefix`.
+fn record_origins(
+  layer: &toml::value::Table,
+  prefix: &str,
+  path: &Path,
+  origins: &mut BTreeMap<String, PathBuf>,
+) {
+  for (key, value) in layer {
+    let key = if prefix.is_empty() {
+      key.clone()
+    } else {
+      format!("{}.{}", prefix, key)
+    };
+
+    match value {
+      toml::Value::Table(table) => record_origins(table, &key, path, origins),
+      _ => {
+        origins.insert(key, path.to_owned());
+      }
+    }
+  }
+}
+
 #[derive(Debug, Deserialize, Serialize, Default)]
 #[serde(default)]
 pub struct ColorConfig {
@@ -343,14 +975,140 @@ pub struct ColorConfig {
   pub status: TaskStatusColorConfig,
   pub priority: PriorityColorConfig,
   pub show_header: ShowHeaderColorConfig,
+  pub table: TableColorConfig,
+  pub age: AgeColorConfig,
+  /// Rules overriding the description highlight of the tasks they match; the first matching rule wins.
+  #[serde(skip_serializing_if = "Vec::is_empty")]
+  pub rules: Vec<ColorRule>,
+}
+
+impl ColorConfig {
+  /// Find the first rule matching a task, if any.
+  pub fn rule_for(&self, task: &Task) -> Option<&ColorRule> {
+    self.rules.iter().find(|rule| rule.matches(task))
+  }
+}
+
+/// Appearance of the tables of listings.
+#[derive(Debug, Default, Deserialize, Serialize)]
+#[serde(default)]
+pub struct TableColorConfig {
+  /// Background color of every other row; rows are not striped if absent.
+  #[serde(skip_serializing_if = "Option::is_none")]
+  pub stripe: Option<Color>,
+
+  /// Draw light horizontal borders above and below the header, and below the last row.
+  pub borders: bool,
+}
+
+/// Highlight of the age of the active tasks that have been lying around for a while.
+#[derive(Debug, Deserialize, Serialize)]
+#[serde(default)]
+pub struct AgeColorConfig {
+  /// Age, in days, from which a task is stale; `0` disables it.
+  pub stale_days: u32,
+
+  /// Age, in days, from which a task is likely dead; `0` disables it.
+  pub dead_days: u32,
+
+  /// Style of the age of stale tasks.
+  pub stale: Highlight,
+
+  /// Style of the age of likely dead tasks.
+  pub dead: Highlight,
+}
+
+impl Default for AgeColorConfig {
+  fn default() -> Self {
+    Self {
+      stale_days: 30,
+      dead_days: 90,
+      stale: Highlight {
+        foreground: Some(Color(Col::Yellow)),
+        background: None,
+        style: vec![],
+      },
+      dead: Highlight {
+        foreground: Some(Color(Col::Red)),
+        background: None,
+        style: vec![],
+      },
+    }
+  }
+}
+
+impl AgeColorConfig {
+  /// Highlight of the age of a task, if it is active and old enough to have one.
+  pub fn highlight_for(&self, task: &Task) -> Option<&Highlight> {
+    if !task.status().is_active() {
+      return None;
+    }
+
+    let age = task.age();
+    let reached = |days: u32| days > 0 && age >= Duration::days(days.into());
+
+    if reached(self.dead_days) {
+      Some(&self.dead)
+    } else if reached(self.stale_days) {
+      Some(&self.stale)
+    } else {
+      None
+    }
+  }
 }
 
+/// Highlight applied to the description of the tasks matching all the criteria of the rule.
+///
+/// A rule without any criterion doesn’t match any task.
 #[derive(Debug, Deserialize, Serialize)]
+pub struct ColorRule {
+  /// Tag the task must have.
+  #[serde(default, skip_serializing_if = "Option::is_none")]
+  pub tag: Option<String>,
+
+  /// Project the task must be in.
+  #[serde(default, skip_serializing_if = "Option::is_none")]
+  pub project: Option<String>,
+
+  /// Context the task must be in.
+  #[serde(default, skip_serializing_if = "Option::is_none")]
+  pub context: Option<String>,
+
+  #[serde(flatten)]
+  pub highlight: Highlight,
+}
+
+impl ColorRule {
+  /// Check whether a task matches this rule.
+  pub fn matches(&self, task: &Task) -> bool {
+    if self.tag.is_none() && self.project.is_none() && self.context.is_none() {
+      return false;
+    }
+
+    self
+      .tag
+      .as_ref()
+      .map_or(true, |tag| task.tags().any(|t| t == tag))
+      && self.project.as_deref().map_or(true, |project| {
+        task
+          .project()
+          .is_some_and(|own| metadata::is_in_project(own, project, false))
+      })
+      && self
+        .context
+        .as_deref()
+        .map_or(true, |context| task.context() == Some(context))
+  }
+}
+
+#[derive(Debug, Deserialize, Serialize)]
+#[serde(default)]
 pub struct TaskDescriptionColorConfig {
   pub ongoing: Highlight,
   pub todo: Highlight,
   pub done: Highlight,
   pub cancelled: Highlight,
+  pub paused: Highlight,
 }
 
 impl Default for TaskDescriptionColorConfig {
@@ -376,16 +1134,23 @@ impl Default for TaskDescriptionColorConfig {
         background: Some(Color(Col::Black)),
         style: vec![StyleAttribute::Dimmed, StyleAttribute::Strikethrough],
       },
+      paused: Highlight {
+        foreground: Some(Color(Col::Black)),
+        background: Some(Color(Col::Yellow)),
+        style: vec![],
+      },
     }
   }
 }
 
 #[derive(Debug, Deserialize, Serialize)]
+#[serde(default)]
 pub struct TaskStatusColorConfig {
   pub ongoing: Highlight,
   pub todo: Highlight,
   pub done: Highlight,
   pub cancelled: Highlight,
+  pub paused: Highlight,
 }
 
 impl Default for TaskStatusColorConfig {
@@ -411,6 +1176,11 @@ impl Default for TaskStatusColorConfig {
         background: None,
         style: vec![StyleAttribute::Dimmed],
       },
+      paused: Highlight {
+        foreground: Some(Color(Col::Yellow)),
+        background: None,
+        style: vec![StyleAttribute::Bold],
+      },
     }
   }
 }
@@ -644,9 +1414,282 @@ impl Serialize for Color {
   }
 }
 
+/// Content of the digests generated by `td digest`.
+#[derive(Debug, Deserialize, Serialize)]
+#[serde(default)]
+pub struct DigestConfig {
+  /// Sections to include, in that order.
+  pub sections: Vec<DigestSection>,
+  /// Projects to include; if empty, all the tasks are included.
+  #[serde(skip_serializing_if = "Vec::is_empty")]
+  pub projects: Vec<String>,
+  /// Incoming webhook URL of the Slack channel digests are posted to.
+  #[serde(skip_serializing_if = "Option::is_none")]
+  pub slack_webhook: Option<String>,
+  /// Matrix room digests are posted to.
+  #[serde(skip_serializing_if = "Option::is_none")]
+  pub matrix: Option<MatrixConfig>,
+}
+
+impl Default for DigestConfig {
+  fn default() -> Self {
+    Self {
+      sections: vec![
+        DigestSection::Completed,
+        DigestSection::InProgress,
+        DigestSection::Upcoming,
+      ],
+      projects: Vec::new(),
+      slack_webhook: None,
+      matrix: None,
+    }
+  }
+}
+
+/// Static site generated by `td publish`.
+#[derive(Debug, Deserialize, Serialize)]
+#[serde(default)]
+pub struct PublishConfig {
+  /// Directory the site is written to; defaults to the `public` directory of the tasks folder.
+  #[serde(skip_serializing_if = "Option::is_none")]
+  pub output_dir: Option<PathBuf>,
+  /// Tasks left out of the site, in the metadata syntax; a task is left out if it has all the metadata of any of them.
+  ///
+  /// Private tasks are left out anyway.
+  #[serde(skip_serializing_if = "Vec::is_empty")]
+  pub exclude: Vec<String>,
+  /// Publish the done and cancelled tasks too.
+  pub include_closed: bool,
+  /// Columns of the task tables, in that order; the name of the tasks is always shown.
+  pub fields: Vec<PublishField>,
+}
+
+impl Default for PublishConfig {
+  fn default() -> Self {
+    Self {
+      output_dir: None,
+      exclude: Vec::new(),
+      include_closed: false,
+      fields: vec![
+        PublishField::Uid,
+        PublishField::Status,
+        PublishField::Priority,
+        PublishField::Tags,
+        PublishField::Deadline,
+      ],
+    }
+  }
+}
+
+/// Column of the task tables of a published site.
+#[derive(Clone, Copy, Debug, Deserialize, Eq, PartialEq, Serialize)]
+#[serde(rename_all = "lowercase")]
+pub enum PublishField {
+  Uid,
+  Status,
+  Priority,
+  Tags,
+  Context,
+  Milestone,
+  Deadline,
+  Effort,
+  Notes,
+}
+
+/// Private tasks, left out of the exports and reports.
+#[derive(Debug, Deserialize, Serialize)]
+#[serde(default)]
+pub struct PrivacyConfig {
+  /// Tags marking tasks as private, matched ignoring case.
+  pub tags: Vec<String>,
+  /// How the private tasks left out are mentioned.
+  pub redaction: Redaction,
+}
+
+impl Default for PrivacyConfig {
+  fn default() -> Self {
+    Self {
+      tags: vec!["private".to_owned()],
+      redaction: Redaction::Count,
+    }
+  }
+}
+
+/// How the private tasks left out of exports and reports are mentioned.
+#[derive(Clone, Copy, Debug, Deserialize, Eq, PartialEq, Serialize)]
+#[serde(rename_all = "lowercase")]
+pub enum Redaction {
+  /// Private tasks are silently left out.
+  Omit,
+  /// Reports mention how many private tasks were left out.
+  Count,
+}
+
+/// Normalization of the project and tag names read from the command line.
+#[derive(Debug, Default, Deserialize, Serialize)]
+#[serde(default)]
+pub struct NormalizationConfig {
+  /// Lowercase the names.
+  pub lowercase: bool,
+  /// Compose the accented letters written as a letter followed by combining marks, as Unicode NFC does.
+  pub nfc: bool,
+  /// Tags replaced with other ones, such as `bugs = "bug"`.
+  #[serde(skip_serializing_if = "BTreeMap::is_empty")]
+  pub tag_aliases: BTreeMap<String, String>,
+  /// Projects replaced with other ones, along with their subprojects.
+  #[serde(skip_serializing_if = "BTreeMap::is_empty")]
+  pub project_aliases: BTreeMap<String, String>,
+}
+
+/// Policy the tasks must comply with when they are added or edited, such as “every task must have a project”.
+#[derive(Clone, Debug, Default, Deserialize, Eq, PartialEq, Serialize)]
+#[serde(default)]
+pub struct Policy {
+  /// Metadata, in the metadata syntax, of the tasks the policy applies to, such as `#work`; empty for all the tasks.
+  #[serde(skip_serializing_if = "String::is_empty")]
+  pub when: String,
+  /// Metadata the tasks must have.
+  #[serde(skip_serializing_if = "Vec::is_empty")]
+  pub require: Vec<PolicyMetadata>,
+  /// Metadata the tasks must not have.
+  #[serde(skip_serializing_if = "Vec::is_empty")]
+  pub forbid: Vec<PolicyMetadata>,
+  /// Explanation shown along with violations, such as the convention the policy enforces.
+  #[serde(skip_serializing_if = "Option::is_none")]
+  pub message: Option<String>,
+}
+
+/// Metadata a policy requires or forbids.
+#[derive(Clone, Copy, Debug, Deserialize, Eq, PartialEq, Serialize)]
+#[serde(rename_all = "lowercase")]
+pub enum PolicyMetadata {
+  Project,
+  Priority,
+  Tag,
+  Context,
+  Milestone,
+  Effort,
+  Deadline,
+}
+
+/// Matrix room digests are posted to.
+#[derive(Clone, Debug, Deserialize, Eq, PartialEq, Serialize)]
+pub struct MatrixConfig {
+  /// Base URL of the homeserver, such as `https://matrix.org`.
+  pub homeserver: String,
+  /// ID of the room, such as `!abcdef:matrix.org`.
+  pub room: String,
+  /// Environment variable holding the access token of the user posting the digests.
+  #[serde(default = "default_matrix_token_env")]
+  pub token_env: String,
+}
+
+fn default_matrix_token_env() -> String {
+  "MATRIX_ACCESS_TOKEN".to_owned()
+}
+
+/// Section of a digest.
+#[derive(Clone, Copy, Debug, Deserialize, Eq, PartialEq, Serialize)]
+#[serde(rename_all = "kebab-case")]
+pub enum DigestSection {
+  /// Tasks completed during the period.
+  Completed,
+  /// Tasks in progress.
+  InProgress,
+  /// Active tasks due soon.
+  Upcoming,
+}
+
+/// Synchronization with Jira, used by `td sync jira`.
+#[derive(Clone, Debug, Deserialize, Eq, PartialEq, Serialize)]
+pub struct JiraConfig {
+  /// Base URL of the Jira instance, such as `https://example.atlassian.net`.
+  pub url: String,
+  /// User to authenticate as, usually an email address.
+  pub user: String,
+  /// Environment variable holding the API token of the user, so that it doesn’t end up in the configuration.
+  #[serde(default = "default_jira_token_env")]
+  pub token_env: String,
+  /// JQL query selecting the issues to synchronize.
+  pub jql: String,
+  /// Project of the tasks created from issues.
+  #[serde(default, skip_serializing_if = "Option::is_none")]
+  pub project: Option<String>,
+  /// Jira statuses the statuses of the tasks map to.
+  #[serde(default)]
+  pub statuses: JiraStatuses,
+}
+
+fn default_jira_token_env() -> String {
+  "JIRA_API_TOKEN".to_owned()
+}
+
+/// Names of the Jira statuses the statuses of the tasks map to.
+///
+/// Paused and cancelled tasks don’t have a Jira counterpart unless configured.
+#[derive(Clone, Debug, Deserialize, Eq, PartialEq, Serialize)]
+#[serde(default)]
+pub struct JiraStatuses {
+  pub todo: String,
+  pub ongoing: String,
+  #[serde(skip_serializing_if = "Option::is_none")]
+  pub paused: Option<String>,
+  pub done: String,
+  #[serde(skip_serializing_if = "Option::is_none")]
+  pub cancelled: Option<String>,
+}
+
+impl Default for JiraStatuses {
+  fn default() -> Self {
+    Self {
+      todo: "To Do".to_owned(),
+      ongoing: "In Progress".to_owned(),
+      paused: None,
+      done: "Done".to_owned(),
+      cancelled: None,
+    }
+  }
+}
+
+impl JiraStatuses {
+  /// Name of the Jira status a status maps to, if any.
+  pub fn jira_status(&self, status: Status) -> Option<&str> {
+    match status {
+      Status::Todo => Some(&self.todo),
+      Status::Ongoing => Some(&self.ongoing),
+      Status::Paused => self.paused.as_deref(),
+      Status::Done => Some(&self.done),
+      Status::Cancelled => self.cancelled.as_deref(),
+    }
+  }
+
+  /// Status a Jira status maps to, if any; names are compared case-insensitively.
+  pub fn status(&self, jira_status: &str) -> Option<Status> {
+    [
+      Status::Todo,
+      Status::Ongoing,
+      Status::Paused,
+      Status::Done,
+      Status::Cancelled,
+    ]
+    .iter()
+    .copied()
+    .find(|&status| {
+      self
+        .jira_status(status)
+        .is_some_and(|name| name.eq_ignore_ascii_case(jira_status))
+    })
+  }
+}
+
 #[cfg(test)]
 mod tests {
   use super::*;
+  use crate::{
+    metadata::{FieldDef, FieldKind, Metadata},
+    task::Event,
+  };
+  use chrono::Utc;
   use serde_test::*;
 
   #[test]
@@ -700,4 +1743,284 @@ mod tests {
       assert_eq!(expected, opts.highlight("test"));
     }
   }
+
+  #[test]
+  fn color_rules() {
+    let config: ColorConfig = toml::from_str(
+      r#"
+rules = [
+  { tag = "urgent", foreground = "red", style = ["bold"] },
+  { project = "toodoux", context = "computer", background = "blue" },
+  { foreground = "green" },
+]
+"#,
+    )
+    .unwrap();
+
+    let mut urgent = Task::new("Fix prod");
+    urgent.apply_metadata(vec![Metadata::tag("urgent"), Metadata::project("toodoux")]);
+    let rule = config.rule_for(&urgent).unwrap();
+    assert_eq!(rule.highlight.foreground, Some(Color(Col::Red)));
+    assert!(matches!(rule.highlight.style[..], [StyleAttribute::Bold]));
+
+    let mut coding = Task::new("Write code");
+    coding.apply_metadata(vec![Metadata::project("toodoux")]);
+    assert!(config.rule_for(&coding).is_none());
+    coding.apply_metadata(vec![Metadata::context("computer")]);
+    assert_eq!(
+      config.rule_for(&coding).unwrap().highlight.background,
+      Some(Color(Col::Blue))
+    );
+
+    assert!(config.rule_for(&Task::new("Nap")).is_none());
+    assert!(toml::to_string(&config).is_ok());
+  }
+
+  #[test]
+  fn column_alignment() {
+    let config: MainConfig = toml::from_str("[column_alignment]\nuid = \"left\"").unwrap();
+    assert_eq!(config.column_alignment.uid, Alignment::Left);
+    assert_eq!(config.column_alignment.age, Alignment::Right);
+
+    assert_eq!(Alignment::Left.pad(42, 4), "42  ");
+    assert_eq!(Alignment::Right.pad(42, 4), "  42");
+    assert_eq!(Alignment::Right.pad(1234, 2), "1234");
+  }
+
+  #[test]
+  fn age_colors() {
+    let config: ColorConfig = toml::from_str(
+      r#"
+[age]
+stale_days = 0
+dead_days = 60
+"#,
+    )
+    .unwrap();
+    let created = |days: i64| {
+      Task::from_history(
+        "Old",
+        vec![Event::Created(Utc::now() - Duration::days(days))],
+      )
+    };
+
+    assert!(config.age.highlight_for(&created(45)).is_none());
+    assert_eq!(
+      config.age.highlight_for(&created(60)).unwrap().foreground,
+      Some(Color(Col::Red))
+    );
+
+    let mut done = created(100);
+    done.change_status(Status::Done);
+    assert!(config.age.highlight_for(&done).is_none());
+
+    let defaults = AgeColorConfig::default();
+    assert!(defaults.highlight_for(&created(10)).is_none());
+    assert_eq!(
+      defaults.highlight_for(&created(30)).unwrap().foreground,
+      Some(Color(Col::Yellow))
+    );
+  }
+
+  #[test]
+  fn digest_sections() {
+    let config: Config = toml::from_str(
+      r#"
+[digest]
+sections = ["upcoming", "in-progress"]
+projects = ["work"]
+slack_webhook = "https://hooks.slack.com/services/T0/B0/X"
+
+[digest.matrix]
+homeserver = "https://matrix.org"
+room = "!standup:matrix.org"
+"#,
+    )
+    .unwrap();
+    assert_eq!(
+      config.digest.sections,
+      vec![DigestSection::Upcoming, DigestSection::InProgress]
+    );
+    assert_eq!(config.digest.projects, vec!["work".to_owned()]);
+    assert_eq!(
+      config.digest.slack_webhook.as_deref(),
+      Some("https://hooks.slack.com/services/T0/B0/X")
+    );
+    assert_eq!(
+      config.digest.matrix.map(|matrix| matrix.token_env),
+      Some("MATRIX_ACCESS_TOKEN".to_owned())
+    );
+
+    let config: Config = toml::from_str("").unwrap();
+    assert_eq!(config.digest.sections.len(), 3);
+  }
+
+  #[test]
+  fn policies() {
+    let config: Config = toml::from_str(
+      r##"
+[[policies]]
+require = ["project"]
+
+[[policies]]
+when = "#work"
+require = ["priority", "effort"]
+forbid = ["context"]
+"##,
+    )
+    .unwrap();
+    assert_eq!(config.policies.len(), 2);
+    assert_eq!(config.policies[0].when, "");
+    assert_eq!(
+      config.policies[1].require,
+      vec![PolicyMetadata::Priority, PolicyMetadata::Effort]
+    );
+    assert_eq!(config.policies[1].forbid, vec![PolicyMetadata::Context]);
+
+    let config: Config = toml::from_str("").unwrap();
+    assert!(config.policies.is_empty());
+  }
+
+  #[test]
+  fn jira_statuses() {
+    let config: Config = toml::from_str(
+      r#"
+[jira]
+url = "https://example.atlassian.net"
+user = "me@example.org"
+jql = "assignee = currentUser()"
+
+[jira.statuses]
+ongoing = "In Review"
+cancelled = "Won't Do"
+"#,
+    )
+    .unwrap();
+    let jira = config.jira.unwrap();
+    assert_eq!(jira.token_env, "JIRA_API_TOKEN");
+    assert_eq!(jira.statuses.jira_status(Status::Todo), Some("To Do"));
+    assert_eq!(jira.statuses.jira_status(Status::Paused), None);
+    assert_eq!(jira.statuses.status("in review"), Some(Status::Ongoing));
+    assert_eq!(jira.statuses.status("Won't Do"), Some(Status::Cancelled));
+    assert_eq!(jira.statuses.status("Blocked"), None);
+
+    let config: Config = toml::from_str("").unwrap();
+    assert_eq!(config.jira, None);
+  }
+
+  #[test]
+  fn fields() {
+    let config: Config = toml::from_str(
+      r#"
+[[main.fields]]
+name = "client"
+type = "string"
+
+[[main.fields]]
+name = "points"
+type = "number"
+"#,
+    )
+    .unwrap();
+    assert_eq!(
+      config.fields().defs(),
+      &[
+        FieldDef::new("client", FieldKind::String),
+        FieldDef::new("points", FieldKind::Number)
+      ]
+    );
+    assert!(toml::from_str::<Config>(&toml::to_string(&config).unwrap()).is_ok());
+  }
+
+  #[test]
+  fn includes() {
+    let dir = tempdir::TempDir::new("toodoux-config").unwrap();
+    let write = |name: &str, content: &str| {
+      let path = dir.path().join(name);
+      fs::create_dir_all(path.parent().unwrap()).unwrap();
+      fs::write(path, content).unwrap();
+    };
+
+    write(
+      "team/defaults.toml",
+      r#"
+[main]
+todo_alias = "BACKLOG"
+wip_alias = "DOING"
+
+[digest]
+projects = ["team", "ops"]
+"#,
+    );
+    write(
+      "work.toml",
+      r#"
+include = ["team/defaults.toml"]
+
+[main]
+wip_alias = "NOW"
+"#,
+    );
+    write(
+      "config.toml",
+      r#"
+include = ["work.toml"]
+
+[main]
+todo_alias = "TODO"
+
+[digest]
+projects = ["mine"]
+"#,
+    );
+
+    let config = Config::from_dir(dir.path()).unwrap().unwrap();
+    assert_eq!(config.main.todo_alias, "TODO");
+    assert_eq!(config.main.wip_alias, "NOW");
+    assert_eq!(config.digest.projects, vec!["mine".to_owned()]);
+    assert_eq!(
+      config.value("main.wip_alias").unwrap(),
+      Some(toml::Value::String("NOW".to_owned()))
+    );
+    assert_eq!(config.value("main.nope").unwrap(), None);
+    assert_eq!(
+      config.origin("main.wip_alias"),
+      Some(dir.path().join("work.toml").as_path())
+    );
+    assert_eq!(
+      config.origin("main.todo_alias"),
+      Some(dir.path().join("config.toml").as_path())
+    );
+    assert_eq!(config.origin("main.done_alias"), None);
+
+    write("team/defaults.toml", "include = [\"../config.toml\"]");
+    assert!(matches!(
+      Config::from_dir(dir.path()),
+      Err(Error::ConfigIncludeCycle(_))
+    ));
+  }
+
+  #[test]
+  fn windows_config_dirs() {
+    let profile = tempdir::TempDir::new("toodoux-profile").unwrap();
+    let profile = profile.path();
+    let app_data = profile.join("AppData").join("Roaming");
+
+    assert_eq!(
+      windows_config_dir(Some(profile), Some(app_data.clone())),
+      Some(app_data.join("toodoux"))
+    );
+    assert_eq!(
+      windows_config_dir(Some(profile), None),
+      Some(app_data.join("toodoux"))
+    );
+    assert_eq!(windows_config_dir(None, None), None);
+
+    let dotfiles = profile.join(".config").join("toodoux");
+    fs::create_dir_all(&dotfiles).unwrap();
+    assert_eq!(
+      windows_config_dir(Some(profile), Some(app_data)),
+      Some(dotfiles)
+    );
+  }
 }
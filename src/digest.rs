@@ -0,0 +1,533 @@
+//! Digests summarizing the activity over a period of time.
+//!
+//! A digest lists the tasks completed during the period, the tasks in progress and the upcoming deadlines. It is
+//! rendered as Markdown, which reads fine as plaintext, optionally preceded by email headers so that it can be piped to
+//! `sendmail`.
+//!
+//! Private tasks are left out; unless configured otherwise, the digest ends with how many of them would have been
+//! listed.
+//!
+//! With the `post` feature, a digest can also be posted to a Slack channel, as Slack blocks, or to a Matrix room, as
+//! Markdown along with its HTML rendering.
+
+use crate::{
+  config::{DigestSection, PrivacyConfig},
+  error::Error,
+  privacy,
+  task::{Event, Status, Task, UID},
+};
+use chrono::{DateTime, Local, Utc};
+use serde_json::json;
+use std::{
+  collections::BTreeSet,
+  fmt,
+  io::{self, Write},
+  str::FromStr,
+};
+
+/// Chat services a digest can be posted to.
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub enum Chat {
+  Slack,
+  Matrix,
+}
+
+impl FromStr for Chat {
+  type Err = String;
+
+  fn from_str(s: &str) -> Result<Self, Self::Err> {
+    match s {
+      "slack" => Ok(Chat::Slack),
+      "matrix" => Ok(Chat::Matrix),
+      _ => Err(format!(
+        "unknown chat service {}; expected slack or matrix",
+        s
+      )),
+    }
+  }
+}
+
+impl fmt::Display for Chat {
+  fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+    match *self {
+      Chat::Slack => f.write_str("slack"),
+      Chat::Matrix => f.write_str("matrix"),
+    }
+  }
+}
+
+/// Activity over a period of time.
+#[derive(Debug)]
+pub struct Digest<'a> {
+  /// Start of the period.
+  pub since: DateTime<Utc>,
+  /// End of the period.
+  pub until: DateTime<Utc>,
+  /// Tasks completed during the period, by completion date.
+  pub completed: Vec<(UID, &'a Task)>,
+  /// Tasks in progress, including paused ones, by UID.
+  pub in_progress: Vec<(UID, &'a Task)>,
+  /// Active tasks due within a period as long as the digest one after its end, by deadline.
+  pub upcoming: Vec<(UID, &'a Task)>,
+  /// Number of private tasks that would have been listed, if they are counted.
+  pub private: usize,
+}
+
+impl<'a> Digest<'a> {
+  /// Build the digest of `tasks` between `since` and `until`.
+  ///
+  /// If `projects` is not empty, only the tasks in one of them are considered. Private tasks are left out.
+  pub fn new(
+    tasks: impl IntoIterator<Item = (UID, &'a Task)>,
+    since: DateTime<Utc>,
+    until: DateTime<Utc>,
+    projects: &[String],
+    privacy: &PrivacyConfig,
+  ) -> Self {
+    let redacted = privacy::redact(privacy, tasks);
+    let mut digest = Self::collect(redacted.tasks, since, until, projects);
+
+    // a private task may be listed in several sections, but is counted once
+    let private = Self::collect(redacted.private, since, until, projects);
+    let private = private
+      .completed
+      .iter()
+      .chain(&private.in_progress)
+      .chain(&private.upcoming)
+      .map(|&(uid, _)| uid)
+      .collect::<BTreeSet<_>>();
+    digest.private = privacy::counted(privacy, private.len());
+
+    digest
+  }
+
+  fn collect(
+    tasks: impl IntoIterator<Item = (UID, &'a Task)>,
+    since: DateTime<Utc>,
+    until: DateTime<Utc>,
+    projects: &[String],
+  ) -> Self {
+    let horizon = until + (until - since);
+    let mut digest = Digest {
+      since,
+      until,
+      completed: Vec::new(),
+      in_progress: Vec::new(),
+      upcoming: Vec::new(),
+      private: 0,
+    };
+
+    let tasks = tasks.into_iter().filter(|(_, task)| {
+      projects.is_empty()
+        || task
+          .project()
+          .is_some_and(|project| projects.iter().any(|p| p == project))
+    });
+
+    for (uid, task) in tasks {
+      match task.status() {
+        Status::Done => {
+          if completion_date(task).is_some_and(|date| date >= since && date <= until) {
+            digest.completed.push((uid, task));
+          }
+        }
+
+        Status::Ongoing | Status::Paused => digest.in_progress.push((uid, task)),
+        Status::Todo | Status::Cancelled => (),
+      }
+
+      if task.status().is_active() && task.deadline().is_some_and(|deadline| deadline <= horizon) {
+        digest.upcoming.push((uid, task));
+      }
+    }
+
+    digest
+      .completed
+      .sort_by_key(|&(uid, task)| (completion_date(task), uid));
+    digest.in_progress.sort_by_key(|&(uid, _)| uid);
+    digest
+      .upcoming
+      .sort_by_key(|&(uid, task)| (task.deadline(), uid));
+
+    digest
+  }
+
+  /// Title of the digest, mentioning its period.
+  pub fn title(&self) -> String {
+    format!(
+      "Digest from {} to {}",
+      format_date(&self.since),
+      format_date(&self.until)
+    )
+  }
+
+  /// Render the digest as Markdown, with only the `sections` asked for, in that order.
+  pub fn write_markdown(
+    &self,
+    mut out: impl Write,
+    sections: &[DigestSection],
+  ) -> Result<(), Error> {
+    self
+      .write_sections(&mut out, sections)
+      .map_err(Error::CannotExport)
+  }
+
+  /// Render the digest as an email: a few headers, followed by its Markdown rendering.
+  pub fn write_email(&self, mut out: impl Write, sections: &[DigestSection]) -> Result<(), Error> {
+    writeln!(out, "Subject: {}", self.title())
+      .and_then(|_| writeln!(out, "Content-Type: text/plain; charset=utf-8"))
+      .and_then(|_| writeln!(out))
+      .map_err(Error::CannotExport)?;
+
+    self.write_markdown(out, sections)
+  }
+
+  /// Render the digest as a Slack message made of blocks: a header and a section per digest section.
+  pub fn slack_message(&self, sections: &[DigestSection]) -> serde_json::Value {
+    let mut blocks = vec![json!({
+      "type": "header",
+      "text": { "type": "plain_text", "text": self.title() },
+    })];
+
+    for &section in sections {
+      let (title, tasks) = self.section(section);
+      let mut text = format!("*{} ({})*", title, tasks.len());
+
+      if tasks.is_empty() {
+        text.push_str("\nNothing.");
+      }
+
+      for &(uid, task) in tasks {
+        text.push_str("\n• ");
+        text.push_str(&escape_slack(&self.item(section, uid, task)));
+      }
+
+      blocks.push(json!({
+        "type": "section",
+        "text": { "type": "mrkdwn", "text": text },
+      }));
+    }
+
+    if self.private > 0 {
+      blocks.push(json!({
+        "type": "context",
+        "elements": [{ "type": "mrkdwn", "text": privacy::notice(self.private) }],
+      }));
+    }
+
+    json!({ "text": self.title(), "blocks": blocks })
+  }
+
+  /// Render the digest as HTML, as Matrix clients expect along with the Markdown rendering.
+  pub fn write_html(&self, mut out: impl Write, sections: &[DigestSection]) -> Result<(), Error> {
+    self
+      .write_html_sections(&mut out, sections)
+      .map_err(Error::CannotExport)
+  }
+
+  /// Post the digest to a chat service configured in `config`.
+  #[cfg(feature = "post")]
+  pub fn post(&self, config: &crate::config::DigestConfig, chat: Chat) -> Result<(), Error> {
+    let missing =
+      |key| Error::CannotPost(format!("{} is not set in the [digest] configuration", key));
+
+    match chat {
+      Chat::Slack => {
+        let webhook = config
+          .slack_webhook
+          .as_deref()
+          .ok_or_else(|| missing("slack_webhook"))?;
+
+        ureq::post(webhook)
+          .send_json(self.slack_message(&config.sections))
+          .map_err(post_error)?;
+      }
+
+      Chat::Matrix => {
+        let matrix = config.matrix.as_ref().ok_or_else(|| missing("matrix"))?;
+        let token = std::env::var(&matrix.token_env).map_err(|_| {
+          Error::CannotPost(format!(
+            "the {} environment variable must hold a Matrix access token",
+            matrix.token_env
+          ))
+        })?;
+
+        let mut body = Vec::new();
+        let mut html = Vec::new();
+        self.write_markdown(&mut body, &config.sections)?;
+        self.write_html(&mut html, &config.sections)?;
+
+        // the transaction ID makes retries idempotent
+        let url = matrix_send_url(
+          &matrix.homeserver,
+          &matrix.room,
+          &format!("toodoux-{}", Utc::now().timestamp_millis()),
+        );
+
+        ureq::put(&url)
+          .set("Authorization", &format!("Bearer {}", token))
+          .send_json(json!({
+            "msgtype": "m.notice",
+            "body": String::from_utf8_lossy(&body),
+            "format": "org.matrix.custom.html",
+            "formatted_body": String::from_utf8_lossy(&html),
+          }))
+          .map_err(post_error)?;
+      }
+    }
+
+    Ok(())
+  }
+
+  /// Title and tasks of a section.
+  fn section(&self, section: DigestSection) -> (&'static str, &[(UID, &'a Task)]) {
+    match section {
+      DigestSection::Completed => ("Completed", &self.completed),
+      DigestSection::InProgress => ("In progress", &self.in_progress),
+      DigestSection::Upcoming => ("Upcoming deadlines", &self.upcoming),
+    }
+  }
+
+  /// Line describing a task in a section.
+  fn item(&self, section: DigestSection, uid: UID, task: &Task) -> String {
+    let mut item = format!("[{}] {}", uid, task.name());
+
+    if let Some(project) = task.project() {
+      item.push_str(&format!(" (@{})", project));
+    }
+
+    if let (DigestSection::Upcoming, Some(deadline)) = (section, task.deadline()) {
+      item.push_str(&format!(" — due {}", format_date(&deadline)));
+    }
+
+    item
+  }
+
+  fn write_sections(&self, out: &mut impl Write, sections: &[DigestSection]) -> io::Result<()> {
+    writeln!(out, "# {}", self.title())?;
+
+    for &section in sections {
+      let (title, tasks) = self.section(section);
+
+      writeln!(out)?;
+      writeln!(out, "## {} ({})", title, tasks.len())?;
+      writeln!(out)?;
+
+      if tasks.is_empty() {
+        writeln!(out, "Nothing.")?;
+      }
+
+      for &(uid, task) in tasks {
+        writeln!(out, "- {}", self.item(section, uid, task))?;
+      }
+    }
+
+    if self.private > 0 {
+      writeln!(out)?;
+      writeln!(out, "_{}_", privacy::notice(self.private))?;
+    }
+
+    Ok(())
+  }
+
+  fn write_html_sections(
+    &self,
+    out: &mut impl Write,
+    sections: &[DigestSection],
+  ) -> io::Result<()> {
+    writeln!(out, "<h1>{}</h1>", escape_html(&self.title()))?;
+
+    for &section in sections {
+      let (title, tasks) = self.section(section);
+      writeln!(out, "<h2>{} ({})</h2>", title, tasks.len())?;
+
+      if tasks.is_empty() {
+        writeln!(out, "<p>Nothing.</p>")?;
+        continue;
+      }
+
+      writeln!(out, "<ul>")?;
+      for &(uid, task) in tasks {
+        writeln!(
+          out,
+          "<li>{}</li>",
+          escape_html(&self.item(section, uid, task))
+        )?;
+      }
+      writeln!(out, "</ul>")?;
+    }
+
+    if self.private > 0 {
+      writeln!(out, "<p><em>{}</em></p>", privacy::notice(self.private))?;
+    }
+
+    Ok(())
+  }
+}
+
+#[cfg(feature = "post")]
+fn post_error(err: ureq::Error) -> Error {
+  match err {
+    ureq::Error::Status(code, response) => Error::CannotPost(format!(
+      "{} returned {}: {}",
+      response.get_url().to_owned(),
+      code,
+      response.into_string().unwrap_or_default()
+    )),
+    ureq::Error::Transport(transport) => Error::CannotPost(transport.to_string()),
+  }
+}
+
+/// URL sending a message to a Matrix room, given by alias or ID, as the transaction `txn_id`.
+#[cfg(feature = "post")]
+fn matrix_send_url(homeserver: &str, room: &str, txn_id: &str) -> String {
+  format!(
+    "{}/_matrix/client/v3/rooms/{}/send/m.room.message/{}",
+    homeserver.trim_end_matches('/'),
+    percent_encode_segment(room),
+    percent_encode_segment(txn_id)
+  )
+}
+
+/// Percent-encode a URL path segment, leaving only unreserved characters as-is.
+#[cfg(feature = "post")]
+fn percent_encode_segment(s: &str) -> String {
+  s.bytes()
+    .map(|byte| match byte {
+      b'A'..=b'Z' | b'a'..=b'z' | b'0'..=b'9' | b'-' | b'.' | b'_' | b'~' => {
+        char::from(byte).to_string()
+      }
+      _ => format!("%{:02X}", byte),
+    })
+    .collect()
+}
+
+/// Escape the characters Slack interprets as control sequences.
+fn escape_slack(s: &str) -> String {
+  s.replace('&', "&amp;")
+    .replace('<', "&lt;")
+    .replace('>', "&gt;")
+}
+
+pub(crate) fn escape_html(s: &str) -> String {
+  escape_slack(s).replace('"', "&quot;")
+}
+
+/// Date at which a task was last marked as done, if it was.
+fn completion_date(task: &Task) -> Option<DateTime<Utc>> {
+  task
+    .history()
+    .filter_map(|event| match event {
+      Event::StatusChanged {
+        event_date,
+        status: Status::Done,
+      } => Some(*event_date),
+      _ => None,
+    })
+    .next_back()
+}
+
+fn format_date(date: &DateTime<Utc>) -> impl std::fmt::Display {
+  date.with_timezone(&Local).format("%Y-%m-%d")
+}
+
+#[cfg(test)]
+mod unit_tests {
+  use super::*;
+  use crate::metadata::Metadata;
+  use chrono::Duration;
+
+  #[test]
+  fn digest() {
+    let now = Utc::now();
+    let since = now - Duration::weeks(1);
+
+    let mut done = Task::new("Write the report");
+    done.apply_metadata(vec![Metadata::project("work")]);
+    done.change_status(Status::Done);
+    let mut ongoing = Task::new("Refactor");
+    ongoing.apply_metadata(vec![Metadata::project("work")]);
+    ongoing.change_status(Status::Ongoing);
+    let mut due = Task::new("Pay taxes");
+    due.apply_metadata(vec![Metadata::project("home")]);
+    due.set_deadline(now + Duration::days(3));
+    let mut later = Task::new("Renew passport");
+    later.set_deadline(now + Duration::weeks(3));
+    let mut party = Task::new("Plan the party");
+    party.apply_metadata(vec![Metadata::project("work"), Metadata::tag("private")]);
+    party.change_status(Status::Ongoing);
+    party.set_deadline(now + Duration::days(1));
+
+    let uid = |s: &str| s.parse::<UID>().unwrap();
+    let tasks = vec![
+      (uid("0"), &done),
+      (uid("1"), &ongoing),
+      (uid("2"), &due),
+      (uid("3"), &later),
+      (uid("4"), &party),
+    ];
+    let privacy = PrivacyConfig::default();
+
+    // the digest ends once the tasks are completed
+    let now = Utc::now();
+    let digest = Digest::new(tasks.clone(), since, now, &[], &privacy);
+    assert_eq!(digest.completed.len(), 1);
+    assert_eq!(digest.in_progress.len(), 1);
+    assert_eq!(digest.upcoming.len(), 1);
+    assert_eq!(digest.private, 1);
+
+    let digest = Digest::new(tasks, since, now, &["work".to_owned()], &privacy);
+    assert!(digest.upcoming.is_empty());
+
+    let mut out = Vec::new();
+    digest
+      .write_email(
+        &mut out,
+        &[DigestSection::Completed, DigestSection::Upcoming],
+      )
+      .unwrap();
+    let out = String::from_utf8(out).unwrap();
+    assert!(out.starts_with("Subject: Digest from "));
+    assert!(out.contains("## Completed (1)\n\n- [0] Write the report (@work)\n"));
+    assert!(out.contains("## Upcoming deadlines (0)\n\nNothing.\n"));
+    assert!(!out.contains("In progress"));
+    assert!(out.ends_with("\n_1 private task not shown._\n"));
+
+    let message = digest.slack_message(&[DigestSection::Completed]);
+    assert_eq!(message["blocks"][0]["type"], "header");
+    assert_eq!(
+      message["blocks"][1]["text"]["text"],
+      "*Completed (1)*\n• [0] Write the report (@work)"
+    );
+
+    let mut html = Vec::new();
+    digest
+      .write_html(&mut html, &[DigestSection::Upcoming])
+      .unwrap();
+    let html = String::from_utf8(html).unwrap();
+    assert!(html.ends_with(
+      "<h2>Upcoming deadlines (0)</h2>\n<p>Nothing.</p>\n<p><em>1 private task not shown.</em></p>\n"
+    ));
+  }
+
+  #[test]
+  fn chats() {
+    assert_eq!("slack".parse(), Ok(Chat::Slack));
+    assert_eq!("matrix".parse(), Ok(Chat::Matrix));
+    assert!("irc".parse::<Chat>().is_err());
+    assert_eq!(escape_slack("<a & b>"), "&lt;a &amp; b&gt;");
+  }
+
+  #[cfg(feature = "post")]
+  #[test]
+  fn matrix_rooms_are_encoded() {
+    assert_eq!(
+      matrix_send_url("https://matrix.org/", "!abc/d?e%f:example.org", "toodoux-1"),
+      "https://matrix.org/_matrix/client/v3/rooms/%21abc%2Fd%3Fe%25f%3Aexample.org/send/m.room.message/toodoux-1"
+    );
+    assert_eq!(
+      percent_encode_segment("#team:example.org"),
+      "%23team%3Aexample.org"
+    );
+  }
+}
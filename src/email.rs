@@ -0,0 +1,355 @@
+//! Email ingestion.
+//!
+//! Tasks can be added from RFC 822 messages, as piped by mail clients such as mutt. The parsing is tolerant and limited
+//! to what tasks need: a few headers, with their RFC 2047 encoded words decoded, and the first plain-text part of the
+//! body, with its transfer encoding decoded. UTF-8, US-ASCII and Latin-1 charsets are supported.
+
+/// Email message, as far as tasks are concerned.
+#[derive(Clone, Debug, Default, Eq, PartialEq)]
+pub struct Message {
+  /// Subject of the message, without its `Re:` and `Fwd:` prefixes.
+  pub subject: Option<String>,
+  /// Sender of the message.
+  pub from: Option<String>,
+  /// Message-ID, without its angle brackets.
+  pub message_id: Option<String>,
+  /// First plain-text part of the body, trimmed.
+  pub body: String,
+}
+
+impl Message {
+  /// Parse a message; return [`None`] if it has no headers.
+  pub fn parse(input: &str) -> Option<Self> {
+    let part = Part::parse(input.as_bytes());
+
+    if part.headers.is_empty() {
+      return None;
+    }
+
+    let header = |name: &str| part.header(name).map(decode_words);
+    let subject = header("Subject")
+      .map(|subject| strip_reply_prefixes(&subject).to_owned())
+      .filter(|subject| !subject.is_empty());
+    let message_id = part.header("Message-ID").map(|id| {
+      id.trim()
+        .trim_start_matches('<')
+        .trim_end_matches('>')
+        .to_owned()
+    });
+
+    Some(Message {
+      subject,
+      from: header("From"),
+      message_id,
+      body: part.text().unwrap_or_default().trim().to_owned(),
+    })
+  }
+}
+
+/// MIME part: headers and raw body.
+struct Part<'a> {
+  headers: Vec<(String, String)>,
+  body: &'a [u8],
+}
+
+impl<'a> Part<'a> {
+  fn parse(input: &'a [u8]) -> Self {
+    let mut headers: Vec<(String, String)> = Vec::new();
+    let mut rest = input;
+
+    while !rest.is_empty() {
+      let end = rest
+        .iter()
+        .position(|&b| b == b'\n')
+        .map_or(rest.len(), |i| i + 1);
+      let (line, next) = rest.split_at(end);
+      let line = String::from_utf8_lossy(line);
+      let line = line.trim_end_matches(&['\r', '\n'][..]);
+
+      if line.is_empty() {
+        rest = next;
+        break;
+      }
+
+      if line.starts_with(&[' ', '\t'][..]) {
+        // folded header
+        if let Some((_, value)) = headers.last_mut() {
+          value.push(' ');
+          value.push_str(line.trim());
+        }
+      } else if let Some((name, value)) = line.split_once(':') {
+        headers.push((name.trim().to_owned(), value.trim().to_owned()));
+      } else {
+        // not a header anymore; everything is body
+        break;
+      }
+
+      rest = next;
+    }
+
+    Part {
+      headers,
+      body: rest,
+    }
+  }
+
+  fn header(&self, name: &str) -> Option<&str> {
+    self
+      .headers
+      .iter()
+      .find(|(n, _)| n.eq_ignore_ascii_case(name))
+      .map(|(_, value)| value.as_str())
+  }
+
+  /// Parameter of the `Content-Type` header, such as `charset` or `boundary`.
+  fn content_type_param(&self, param: &str) -> Option<String> {
+    self
+      .header("Content-Type")?
+      .split(';')
+      .skip(1)
+      .filter_map(|kv| kv.split_once('='))
+      .find(|(key, _)| key.trim().eq_ignore_ascii_case(param))
+      .map(|(_, value)| value.trim().trim_matches('"').to_owned())
+  }
+
+  /// First plain-text content, looking into multipart bodies.
+  fn text(&self) -> Option<String> {
+    let content_type = self
+      .header("Content-Type")
+      .and_then(|ct| ct.split(';').next())
+      .unwrap_or("text/plain")
+      .trim()
+      .to_ascii_lowercase();
+
+    if content_type.starts_with("multipart/") {
+      let boundary = format!("--{}", self.content_type_param("boundary")?);
+      let body = String::from_utf8_lossy(self.body);
+
+      return body
+        .split(boundary.as_str())
+        .skip(1)
+        .take_while(|part| !part.starts_with("--"))
+        .find_map(|part| {
+          let part = part
+            .strip_prefix("\r\n")
+            .or_else(|| part.strip_prefix('\n'))?;
+          Part::parse(part.as_bytes()).text()
+        });
+    }
+
+    if content_type != "text/plain" {
+      return None;
+    }
+
+    let encoding = self
+      .header("Content-Transfer-Encoding")
+      .unwrap_or("7bit")
+      .trim()
+      .to_ascii_lowercase();
+    let bytes = match encoding.as_str() {
+      "quoted-printable" => decode_quoted_printable(self.body, false),
+      "base64" => decode_base64(self.body)?,
+      _ => self.body.to_vec(),
+    };
+
+    Some(decode_charset(
+      &bytes,
+      self.content_type_param("charset").as_deref(),
+    ))
+  }
+}
+
+/// Strip the `Re:`, `Fwd:` and `Fw:` prefixes of a subject.
+fn strip_reply_prefixes(subject: &str) -> &str {
+  let mut subject = subject.trim();
+
+  loop {
+    let stripped = ["re:", "fwd:", "fw:"].iter().find_map(|prefix| {
+      subject
+        .get(..prefix.len())
+        .filter(|s| s.eq_ignore_ascii_case(prefix))
+        .map(|_| subject[prefix.len()..].trim_start())
+    });
+
+    match stripped {
+      Some(s) => subject = s,
+      None => return subject,
+    }
+  }
+}
+
+/// Decode the RFC 2047 encoded words of a header, such as `=?UTF-8?B?w6l0w6k=?=`.
+///
+/// Whitespace between two encoded words is dropped; words that cannot be decoded are left as-is.
+fn decode_words(value: &str) -> String {
+  let mut decoded = String::new();
+  let mut pending_space = String::new();
+  let mut after_word = false;
+
+  for token in value.split_inclusive(char::is_whitespace) {
+    let word = token.trim_end();
+    let space = &token[word.len()..];
+
+    match decode_word(word) {
+      Some(text) => {
+        if !after_word {
+          decoded.push_str(&pending_space);
+        }
+
+        decoded.push_str(&text);
+        after_word = true;
+      }
+
+      None => {
+        decoded.push_str(&pending_space);
+        decoded.push_str(word);
+        after_word = word.is_empty() && after_word;
+      }
+    }
+
+    pending_space = space.to_owned();
+  }
+
+  decoded.push_str(&pending_space);
+  decoded.trim().to_owned()
+}
+
+fn decode_word(word: &str) -> Option<String> {
+  let inner = word.strip_prefix("=?")?.strip_suffix("?=")?;
+  let mut parts = inner.splitn(3, '?');
+  let (charset, encoding, text) = (parts.next()?, parts.next()?, parts.next()?);
+
+  let bytes = match encoding {
+    "B" | "b" => decode_base64(text.as_bytes())?,
+    "Q" | "q" => decode_quoted_printable(text.as_bytes(), true),
+    _ => return None,
+  };
+
+  Some(decode_charset(&bytes, Some(charset)))
+}
+
+/// Decode quoted-printable content; in encoded words, `_` stands for a space.
+fn decode_quoted_printable(input: &[u8], word: bool) -> Vec<u8> {
+  let mut decoded = Vec::with_capacity(input.len());
+  let mut i = 0;
+
+  while i < input.len() {
+    match input[i] {
+      b'=' if input[i + 1..].starts_with(b"\r\n") => i += 3,
+      b'=' if input[i + 1..].starts_with(b"\n") => i += 2,
+
+      b'=' => {
+        let byte = input
+          .get(i + 1..i + 3)
+          .and_then(|hex| std::str::from_utf8(hex).ok())
+          .and_then(|hex| u8::from_str_radix(hex, 16).ok());
+
+        match byte {
+          Some(byte) => {
+            decoded.push(byte);
+            i += 3;
+          }
+
+          None => {
+            decoded.push(b'=');
+            i += 1;
+          }
+        }
+      }
+
+      b'_' if word => {
+        decoded.push(b' ');
+        i += 1;
+      }
+
+      byte => {
+        decoded.push(byte);
+        i += 1;
+      }
+    }
+  }
+
+  decoded
+}
+
+fn decode_base64(input: &[u8]) -> Option<Vec<u8>> {
+  let input = input
+    .iter()
+    .copied()
+    .filter(|b| !b.is_ascii_whitespace())
+    .collect::<Vec<_>>();
+  base64::decode(input).ok()
+}
+
+/// Decode text in a charset; unknown charsets are decoded as UTF-8.
+fn decode_charset(bytes: &[u8], charset: Option<&str>) -> String {
+  match charset.map(str::to_ascii_lowercase).as_deref() {
+    Some("iso-8859-1") | Some("latin1") | Some("windows-1252") => {
+      bytes.iter().map(|&b| char::from(b)).collect()
+    }
+
+    _ => String::from_utf8_lossy(bytes).into_owned(),
+  }
+  .replace("\r\n", "\n")
+}
+
+#[cfg(test)]
+mod unit_tests {
+  use super::*;
+
+  #[test]
+  fn parse_simple_message() {
+    let message = Message::parse(
+      "From: Alice <alice@example.org>\r\nSubject: Re: Fwd: Review\r\n the #123 patch\r\n\
+       Message-ID: <1234@example.org>\r\n\r\nCan you have a look?\r\nThanks!\r\n",
+    )
+    .unwrap();
+
+    assert_eq!(message.subject.as_deref(), Some("Review the #123 patch"));
+    assert_eq!(message.from.as_deref(), Some("Alice <alice@example.org>"));
+    assert_eq!(message.message_id.as_deref(), Some("1234@example.org"));
+    assert_eq!(message.body, "Can you have a look?\nThanks!");
+
+    assert_eq!(Message::parse("Not an email\n\nat all"), None);
+  }
+
+  #[test]
+  fn parse_multipart_message() {
+    let message = Message::parse(
+      r#"Subject: =?UTF-8?B?UsOpdW5pb24=?= =?ISO-8859-1?Q?_d'=E9t=E9?= prep
+Content-Type: multipart/alternative; boundary="XYZ"
+
+This is a multipart message.
+--XYZ
+Content-Type: text/html; charset=utf-8
+
+<p>Ignored</p>
+--XYZ
+Content-Type: text/plain; charset=utf-8
+Content-Transfer-Encoding: quoted-printable
+
+Bring the caf=C3=A9 and a very long line that is =
+wrapped.
+--XYZ--
+"#,
+    )
+    .unwrap();
+
+    assert_eq!(message.subject.as_deref(), Some("Réunion d'été prep"));
+    assert_eq!(message.message_id, None);
+    assert_eq!(
+      message.body,
+      "Bring the café and a very long line that is wrapped."
+    );
+  }
+
+  #[test]
+  fn decode_base64_body() {
+    let message = Message::parse(
+      "Subject: Hi\nContent-Type: text/plain; charset=utf-8\nContent-Transfer-Encoding: base64\n\nw6l0w6kg\nMjAyMQ==\n",
+    )
+    .unwrap();
+
+    assert_eq!(message.body, "été 2021");
+  }
+}
@@ -1,47 +1,191 @@
-use crate::task::UID;
+//! Errors.
+//!
+//! Errors describe what went wrong along with its context — the operation, the file or the task — and expose the
+//! underlying error as their [`source`](std::error::Error::source). Use [`Report`] to display an error along with all
+//! its sources.
+
+use crate::{task::UID, tr};
 use serde_json as json;
-use std::{fmt, io};
+use std::{
+  error, fmt, io,
+  path::{Path, PathBuf},
+};
 
 #[derive(Debug)]
 pub enum Error {
-  CannotOpenFile(io::Error),
-  CannotSave(io::Error),
+  /// I/O error while operating on a file.
+  Io {
+    operation: Operation,
+    path: PathBuf,
+    source: io::Error,
+  },
+  CannotExport(io::Error),
+  CannotSync(String),
+  CannotPost(String),
+  InvalidPayload(String),
+  /// The tasks file cannot be parsed.
+  InvalidTasks {
+    path: Option<PathBuf>,
+    source: json::Error,
+  },
+  /// A record of the journal cannot be parsed; lines start at 1.
+  InvalidJournal {
+    path: Option<PathBuf>,
+    line: usize,
+    source: json::Error,
+  },
+  /// An event edited by hand cannot be parsed; lines start at 1.
+  InvalidEvent {
+    line: usize,
+    source: json::Error,
+  },
+  /// The configuration file cannot be parsed.
+  InvalidConfig {
+    path: PathBuf,
+    source: toml::de::Error,
+  },
+  /// A configuration file includes itself, directly or through other files.
+  ConfigIncludeCycle(PathBuf),
   CannotDeserializeFromJSON(json::Error),
   CannotDeserializeFromTOML(toml::de::Error),
   CannotSerializeToTOML(toml::ser::Error),
   CannotDeserializeFromSerde(serde::de::value::Error),
   NoConfigDir,
+  InvalidTasksVersion(String),
+  UnsupportedTasksVersion {
+    found: u32,
+    supported: u32,
+  },
+  UnknownTask(UID),
   UnknownNote(UID),
+  UnknownChecklistItem(UID),
+  UnknownReminder(UID),
+  UnknownGoal(UID),
+}
+
+impl Error {
+  /// Wrap an I/O error with the operation and the path it happened on; meant to be used with `map_err`.
+  pub fn io(operation: Operation, path: impl Into<PathBuf>) -> impl FnOnce(io::Error) -> Self {
+    let path = path.into();
+    move |source| Error::Io {
+      operation,
+      path,
+      source,
+    }
+  }
+
+  /// Whether the error comes from reading or writing files, such as the tasks, the journal or the configuration.
+  pub fn is_storage(&self) -> bool {
+    matches!(
+      *self,
+      Error::Io { .. }
+        | Error::CannotExport(_)
+        | Error::InvalidTasks { .. }
+        | Error::InvalidJournal { .. }
+        | Error::InvalidConfig { .. }
+        | Error::ConfigIncludeCycle(_)
+        | Error::NoConfigDir
+        | Error::InvalidTasksVersion(_)
+        | Error::UnsupportedTasksVersion { .. }
+    )
+  }
 }
 
 impl fmt::Display for Error {
   fn fmt(&self, f: &mut fmt::Formatter) -> Result<(), fmt::Error> {
     match *self {
-      Error::CannotOpenFile(ref e) => {
-        write!(f, "cannot open file: {}", e)
+      Error::Io {
+        operation,
+        ref path,
+        ..
+      } => f.write_str(&tr!("cannot {} {}", operation, path.display())),
+
+      Error::CannotExport(_) => f.write_str(tr!("cannot export")),
+
+      Error::CannotSync(ref e) => f.write_str(&tr!("cannot sync: {}", e)),
+
+      Error::CannotPost(ref e) => f.write_str(&tr!("cannot post: {}", e)),
+
+      Error::InvalidPayload(ref e) => f.write_str(&tr!("invalid payload: {}", e)),
+
+      Error::InvalidTasks { ref path, .. } => {
+        f.write_str(tr!("invalid tasks"))?;
+        write_path(f, path.as_deref())
+      }
+
+      Error::InvalidJournal {
+        ref path, line, ..
+      } => {
+        f.write_str(&tr!("invalid journal record at line {}", line))?;
+        write_path(f, path.as_deref())
       }
 
-      Error::CannotSave(ref e) => write!(f, "cannot save: {}", e),
+      Error::InvalidEvent { line, .. } => f.write_str(&tr!("invalid event at line {}", line)),
 
-      Error::CannotDeserializeFromJSON(ref e) => {
-        write!(f, "cannot deserialize from JSON: {}", e)
+      Error::InvalidConfig { ref path, .. } => {
+        f.write_str(&tr!("invalid configuration in {}", path.display()))
       }
 
-      Error::CannotDeserializeFromTOML(ref e) => {
-        write!(f, "cannot deserialize from TOML: {}", e)
+      Error::ConfigIncludeCycle(ref path) => {
+        f.write_str(&tr!("{} includes itself", path.display()))
       }
 
-      Error::CannotSerializeToTOML(ref e) => {
-        write!(f, "cannot serialize to TOML: {}", e)
+      Error::CannotDeserializeFromJSON(_) => f.write_str(tr!("invalid JSON")),
+
+      Error::CannotDeserializeFromTOML(_) => f.write_str(tr!("invalid TOML")),
+
+      Error::CannotSerializeToTOML(_) => f.write_str(tr!("cannot serialize to TOML")),
+
+      Error::CannotDeserializeFromSerde(_) => f.write_str(tr!("cannot deserialize")),
+
+      Error::NoConfigDir => f.write_str(tr!("cannot find configuration directory")),
+
+      Error::InvalidTasksVersion(ref version) => {
+        f.write_str(&tr!("invalid tasks version: {}", version))
       }
 
-      Error::CannotDeserializeFromSerde(ref e) => {
-        write!(f, "cannot deserialize: {}", e)
+      Error::UnsupportedTasksVersion { found, supported } => f.write_str(&tr!(
+        "tasks are at version {} but this version of toodoux only supports up to version {}; please upgrade toodoux",
+        found,
+        supported
+      )),
+
+      Error::UnknownTask(uid) => f.write_str(&tr!("task {} doesn’t exist", uid)),
+
+      Error::UnknownNote(uid) => f.write_str(&tr!("note {} doesn’t exist", uid)),
+
+      Error::UnknownChecklistItem(uid) => {
+        f.write_str(&tr!("checklist item {} doesn’t exist", uid))
       }
 
-      Error::NoConfigDir => f.write_str("cannot find configuration directory"),
+      Error::UnknownReminder(uid) => f.write_str(&tr!("reminder {} doesn’t exist", uid)),
 
-      Error::UnknownNote(uid) => write!(f, "note {} doesn’t exist", uid),
+      Error::UnknownGoal(uid) => f.write_str(&tr!("goal {} doesn’t exist", uid)),
+    }
+  }
+}
+
+fn write_path(f: &mut fmt::Formatter, path: Option<&Path>) -> Result<(), fmt::Error> {
+  match path {
+    Some(path) => f.write_str(&tr!(" in {}", path.display())),
+    None => Ok(()),
+  }
+}
+
+impl error::Error for Error {
+  fn source(&self) -> Option<&(dyn error::Error + 'static)> {
+    match *self {
+      Error::Io { ref source, .. } | Error::CannotExport(ref source) => Some(source),
+      Error::InvalidTasks { ref source, .. }
+      | Error::InvalidJournal { ref source, .. }
+      | Error::InvalidEvent { ref source, .. }
+      | Error::CannotDeserializeFromJSON(ref source) => Some(source),
+      Error::InvalidConfig { ref source, .. } | Error::CannotDeserializeFromTOML(ref source) => {
+        Some(source)
+      }
+      Error::CannotSerializeToTOML(ref source) => Some(source),
+      Error::CannotDeserializeFromSerde(ref source) => Some(source),
+      _ => None,
     }
   }
 }
@@ -69,3 +213,89 @@ impl From<serde::de::value::Error> for Error {
     Self::CannotDeserializeFromSerde(err)
   }
 }
+
+/// Operation on a file.
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub enum Operation {
+  ReadTasks,
+  SaveTasks,
+  BackupTasks,
+  RemoveBackup,
+  ReadJournal,
+  AppendJournal,
+  RepairJournal,
+  RemoveJournal,
+  ReadConfig,
+  SaveConfig,
+  Read,
+  Write,
+}
+
+impl fmt::Display for Operation {
+  fn fmt(&self, f: &mut fmt::Formatter) -> Result<(), fmt::Error> {
+    f.write_str(match self {
+      Operation::ReadTasks => tr!("read tasks from"),
+      Operation::SaveTasks => tr!("save tasks to"),
+      Operation::BackupTasks => tr!("back up tasks to"),
+      Operation::RemoveBackup => tr!("remove backup"),
+      Operation::ReadJournal => tr!("read journal"),
+      Operation::AppendJournal => tr!("append to journal"),
+      Operation::RepairJournal => tr!("repair journal"),
+      Operation::RemoveJournal => tr!("remove journal"),
+      Operation::ReadConfig => tr!("read configuration from"),
+      Operation::SaveConfig => tr!("save configuration to"),
+      Operation::Read => tr!("read"),
+      Operation::Write => tr!("write"),
+    })
+  }
+}
+
+/// Error displayed along with all its sources, separated by colons.
+pub struct Report<'a>(pub &'a dyn error::Error);
+
+impl fmt::Display for Report<'_> {
+  fn fmt(&self, f: &mut fmt::Formatter) -> Result<(), fmt::Error> {
+    write!(f, "{}", self.0)?;
+
+    let mut source = self.0.source();
+    while let Some(err) = source {
+      write!(f, ": {}", err)?;
+      source = err.source();
+    }
+
+    Ok(())
+  }
+}
+
+#[cfg(test)]
+mod unit_tests {
+  use super::*;
+
+  #[test]
+  fn report() {
+    let err = Error::io(Operation::SaveTasks, "/path/tasks.json")(io::Error::new(
+      io::ErrorKind::PermissionDenied,
+      "permission denied",
+    ));
+    assert_eq!(err.to_string(), "cannot save tasks to /path/tasks.json");
+    assert_eq!(
+      Report(&err).to_string(),
+      "cannot save tasks to /path/tasks.json: permission denied"
+    );
+
+    let err = Error::InvalidJournal {
+      path: Some(PathBuf::from("/path/tasks.journal")),
+      line: 3,
+      source: json::from_str::<json::Value>("{").unwrap_err(),
+    };
+    assert_eq!(
+      Report(&err).to_string(),
+      "invalid journal record at line 3 in /path/tasks.journal: EOF while parsing an object at line 1 column 1"
+    );
+
+    assert_eq!(
+      Report(&Error::UnknownTask(UID::default())).to_string(),
+      "task 0 doesn’t exist"
+    );
+  }
+}
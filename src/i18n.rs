@@ -0,0 +1,297 @@
+//! Localization of the user-facing strings.
+//!
+//! Strings are written in English in the code and looked up, gettext-style, in the catalog of the selected language:
+//! the English string is the identifier of the message, and is used as is when the catalog has no translation for it.
+//! Use the [`tr!`](crate::tr) macro to translate a string and fill its placeholders.
+//!
+//! Placeholders are written `{}`, replaced by the arguments in order, or `{0}`, `{1}`, … to refer to an argument by
+//! position, so that translations can reorder them. `{{` and `}}` are literal braces.
+//!
+//! The language is selected once, with [`set_language`], from the configuration or the locale environment variables
+//! (see [`Language::from_env`]); it defaults to English.
+
+mod fr;
+
+use serde::{Deserialize, Serialize};
+use std::{
+  env, fmt,
+  sync::atomic::{AtomicU8, Ordering},
+};
+
+/// Language of the user-facing strings.
+#[derive(Clone, Copy, Debug, Deserialize, Eq, PartialEq, Serialize)]
+pub enum Language {
+  #[serde(rename = "en")]
+  English,
+  #[serde(rename = "fr")]
+  French,
+}
+
+impl Language {
+  /// Language of a locale, such as `fr`, `fr_FR.UTF-8` or `en-US`, if supported.
+  pub fn from_locale(locale: &str) -> Option<Self> {
+    let lang = locale.split(&['_', '-', '.', '@'][..]).next()?;
+
+    match lang.to_ascii_lowercase().as_str() {
+      "en" | "c" | "posix" => Some(Language::English),
+      "fr" => Some(Language::French),
+      _ => None,
+    }
+  }
+
+  /// Language of the locale set in the environment.
+  ///
+  /// As with gettext, `LC_ALL` takes precedence over `LC_MESSAGES`, which takes precedence over `LANG`.
+  pub fn from_env() -> Option<Self> {
+    ["LC_ALL", "LC_MESSAGES", "LANG"]
+      .iter()
+      .filter_map(|var| env::var(var).ok())
+      .find(|locale| !locale.is_empty())
+      .and_then(|locale| Self::from_locale(&locale))
+  }
+
+  fn catalog(self) -> &'static [(&'static str, &'static str)] {
+    match self {
+      Language::English => &[],
+      Language::French => fr::CATALOG,
+    }
+  }
+}
+
+static LANGUAGE: AtomicU8 = AtomicU8::new(Language::English as u8);
+
+/// Select the language of the user-facing strings.
+pub fn set_language(lang: Language) {
+  LANGUAGE.store(lang as u8, Ordering::Relaxed);
+}
+
+/// Selected language.
+pub fn language() -> Language {
+  if LANGUAGE.load(Ordering::Relaxed) == Language::French as u8 {
+    Language::French
+  } else {
+    Language::English
+  }
+}
+
+/// Translate a message in the selected language.
+pub fn translate(msg: &'static str) -> &'static str {
+  translate_in(language(), msg)
+}
+
+/// Translate a message in a given language.
+pub fn translate_in(lang: Language, msg: &'static str) -> &'static str {
+  lang
+    .catalog()
+    .iter()
+    .find(|(id, _)| *id == msg)
+    .map_or(msg, |(_, translation)| *translation)
+}
+
+/// Fill the placeholders of a message with arguments.
+///
+/// Placeholders referring to missing arguments are left empty.
+pub fn format(msg: &str, args: &[&dyn fmt::Display]) -> String {
+  let mut output = String::with_capacity(msg.len());
+  let mut next = 0;
+  let mut chars = msg.chars().peekable();
+
+  while let Some(c) = chars.next() {
+    match c {
+      '{' if chars.peek() == Some(&'{') => {
+        chars.next();
+        output.push('{');
+      }
+
+      '}' if chars.peek() == Some(&'}') => {
+        chars.next();
+        output.push('}');
+      }
+
+      '{' => {
+        let mut index = String::new();
+        for c in chars.by_ref().take_while(|&c| c != '}') {
+          index.push(c);
+        }
+
+        let index = if index.is_empty() {
+          next += 1;
+          next - 1
+        } else {
+          index.parse().unwrap_or(usize::MAX)
+        };
+
+        if let Some(arg) = args.get(index) {
+          output.push_str(&arg.to_string());
+        }
+      }
+
+      _ => output.push(c),
+    }
+  }
+
+  output
+}
+
+/// Translate a message in the selected language and fill its placeholders.
+///
+/// Without arguments, this is the `&'static str` translation, left unformatted; with arguments, it’s a `String`.
+///
+/// ```
+/// use toodoux::tr;
+///
+/// assert_eq!(tr!("aborted"), "aborted");
+/// assert_eq!(tr!("moved task {} to the trash", 3), "moved task 3 to the trash");
+/// ```
+#[macro_export]
+macro_rules! tr {
+  ($msg:literal) => {
+    $crate::i18n::translate($msg)
+  };
+
+  ($msg:literal, $($arg:expr),+ $(,)?) => {
+    $crate::i18n::format($crate::i18n::translate($msg), &[$(&$arg as &dyn ::std::fmt::Display),+])
+  };
+}
+
+#[cfg(test)]
+mod unit_tests {
+  use super::*;
+  use std::{collections::BTreeSet, fs, path::Path};
+
+  #[test]
+  fn from_locale() {
+    assert_eq!(Language::from_locale("fr"), Some(Language::French));
+    assert_eq!(Language::from_locale("fr_FR.UTF-8"), Some(Language::French));
+    assert_eq!(Language::from_locale("fr-CA"), Some(Language::French));
+    assert_eq!(
+      Language::from_locale("en_US.UTF-8"),
+      Some(Language::English)
+    );
+    assert_eq!(Language::from_locale("C.UTF-8"), Some(Language::English));
+    assert_eq!(Language::from_locale("de_DE"), None);
+    assert_eq!(Language::from_locale(""), None);
+  }
+
+  #[test]
+  fn format_placeholders() {
+    assert_eq!(format("moved {} tasks", &[&3]), "moved 3 tasks");
+    assert_eq!(format("{} and {}", &[&"a", &"b"]), "a and b");
+    assert_eq!(format("{1} then {0}", &[&"a", &"b"]), "b then a");
+    assert_eq!(format("{{literal}} {}", &[&1]), "{literal} 1");
+    assert_eq!(format("missing {} {}", &[&1]), "missing 1 ");
+  }
+
+  #[test]
+  fn translate_falls_back_to_english() {
+    assert_eq!(translate_in(Language::English, "aborted"), "aborted");
+    assert_eq!(translate_in(Language::French, "aborted"), "abandon");
+    assert_eq!(
+      translate_in(Language::French, "not translated"),
+      "not translated"
+    );
+  }
+
+  /// Indices of the arguments a message refers to.
+  fn placeholders(msg: &str) -> BTreeSet<usize> {
+    let msg = msg.replace("{{", "").replace("}}", "");
+    let mut next = 0;
+
+    msg
+      .split('{')
+      .skip(1)
+      .map(|rest| {
+        let index = &rest[..rest.find('}').unwrap()];
+        if index.is_empty() {
+          next += 1;
+          next - 1
+        } else {
+          index.parse().unwrap()
+        }
+      })
+      .collect()
+  }
+
+  #[test]
+  fn catalogs_are_consistent() {
+    for lang in &[Language::French] {
+      let catalog = lang.catalog();
+
+      for (i, (id, translation)) in catalog.iter().enumerate() {
+        assert!(
+          catalog[i + 1..].iter().all(|(other, _)| other != id),
+          "duplicate message: {}",
+          id
+        );
+        assert_eq!(
+          placeholders(id),
+          placeholders(translation),
+          "placeholders of {:?}",
+          id
+        );
+      }
+    }
+  }
+
+  /// Messages passed to `tr!` in the sources.
+  fn messages(dir: &Path, messages: &mut Vec<String>) {
+    for entry in fs::read_dir(dir).unwrap() {
+      let path = entry.unwrap().path();
+
+      if path.is_dir() {
+        self::messages(&path, messages);
+      } else if path.extension() == Some("rs".as_ref()) {
+        let source = fs::read_to_string(&path).unwrap();
+
+        for call in source.split(concat!("tr", "!(")).skip(1) {
+          let call = call.trim_start();
+          if !call.starts_with('"') {
+            continue;
+          }
+
+          let mut msg = String::new();
+          let mut chars = call[1..].chars();
+          while let Some(c) = chars.next() {
+            match c {
+              '"' => break,
+              '\\' => match chars.next() {
+                Some('n') => msg.push('\n'),
+                Some('t') => msg.push('\t'),
+                // line continuation; the leading whitespace of the next line is skipped too
+                Some('\n') => {
+                  chars = chars.as_str().trim_start().chars();
+                }
+                Some(c) => msg.push(c),
+                None => (),
+              },
+              _ => msg.push(c),
+            }
+          }
+
+          messages.push(msg);
+        }
+      }
+    }
+  }
+
+  #[test]
+  fn catalogs_are_complete() {
+    let mut msgs = Vec::new();
+    messages(
+      &Path::new(env!("CARGO_MANIFEST_DIR")).join("src"),
+      &mut msgs,
+    );
+    assert!(!msgs.is_empty());
+
+    for lang in &[Language::French] {
+      for msg in &msgs {
+        assert!(
+          lang.catalog().iter().any(|(id, _)| id == msg),
+          "{:?} has no {:?} translation",
+          msg,
+          lang
+        );
+      }
+    }
+  }
+}
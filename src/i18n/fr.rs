@@ -0,0 +1,767 @@
+//! French catalog.
+
+pub(super) const CATALOG: &[(&str, &str)] = &[
+  // column names
+  ("Age", "Âge"),
+  ("Activity", "Activité"),
+  ("Spent", "Passé"),
+  ("Prio", "Prio"),
+  ("Project", "Projet"),
+  ("Context", "Contexte"),
+  ("Milestone", "Jalon"),
+  ("Tags", "Étiquettes"),
+  ("Status", "Statut"),
+  ("Description", "Description"),
+  ("Notes", "Notes"),
+  ("Progress", "Avancement"),
+  // errors
+  ("cannot {} {}", "impossible de {} {}"),
+  ("cannot export", "impossible d’exporter"),
+  ("cannot sync: {}", "impossible de synchroniser : {}"),
+  ("cannot post: {}", "impossible de publier : {}"),
+  ("invalid payload: {}", "contenu invalide : {}"),
+  ("invalid tasks", "tâches invalides"),
+  (
+    "invalid journal record at line {}",
+    "enregistrement du journal invalide à la ligne {}",
+  ),
+  ("invalid event at line {}", "événement invalide à la ligne {}"),
+  ("invalid configuration in {}", "configuration invalide dans {}"),
+  ("{} includes itself", "{} s’inclut lui-même"),
+  ("invalid JSON", "JSON invalide"),
+  ("invalid TOML", "TOML invalide"),
+  ("cannot serialize to TOML", "impossible de sérialiser en TOML"),
+  ("cannot deserialize", "impossible de désérialiser"),
+  (
+    "cannot find configuration directory",
+    "impossible de trouver le dossier de configuration",
+  ),
+  ("invalid tasks version: {}", "version des tâches invalide : {}"),
+  (
+    "tasks are at version {} but this version of toodoux only supports up to version {}; please upgrade toodoux",
+    "les tâches sont à la version {} mais cette version de toodoux ne gère que jusqu’à la version {} ; veuillez \
+     mettre à jour toodoux",
+  ),
+  ("task {} doesn’t exist", "la tâche {} n’existe pas"),
+  ("note {} doesn’t exist", "la note {} n’existe pas"),
+  (
+    "checklist item {} doesn’t exist",
+    "l’élément de liste {} n’existe pas",
+  ),
+  ("reminder {} doesn’t exist", "le rappel {} n’existe pas"),
+  ("goal {} doesn’t exist", "l’objectif {} n’existe pas"),
+  (" in {}", " dans {}"),
+  ("read tasks from", "lire les tâches depuis"),
+  ("save tasks to", "enregistrer les tâches dans"),
+  ("back up tasks to", "sauvegarder les tâches dans"),
+  ("remove backup", "supprimer la sauvegarde"),
+  ("read journal", "lire le journal"),
+  ("append to journal", "écrire dans le journal"),
+  ("repair journal", "réparer le journal"),
+  ("remove journal", "supprimer le journal"),
+  ("read configuration from", "lire la configuration depuis"),
+  ("save configuration to", "enregistrer la configuration dans"),
+  ("read", "lire"),
+  ("write", "écrire"),
+  ("the standard input", "l’entrée standard"),
+  ("too many projects: {}", "trop de projets : {}"),
+  ("too many priorities: {}", "trop de priorités : {}"),
+  ("too many contexts: {}", "trop de contextes : {}"),
+  ("too many milestones: {}", "trop de jalons : {}"),
+  ("too many external IDs: {}", "trop d’identifiants externes : {}"),
+  (
+    "next UID is {} but task {} already exists",
+    "le prochain UID est {} mais la tâche {} existe déjà",
+  ),
+  (
+    "task {} is both active and in the trash",
+    "la tâche {} est à la fois active et dans la corbeille",
+  ),
+  (
+    "history of task {} doesn’t start with its creation",
+    "l’historique de la tâche {} ne commence pas par sa création",
+  ),
+  (
+    "history of task {} is not in chronological order",
+    "l’historique de la tâche {} n’est pas dans l’ordre chronologique",
+  ),
+  (
+    "task {} replaces note {}, which doesn’t exist",
+    "la tâche {} remplace la note {}, qui n’existe pas",
+  ),
+  (
+    "task {} toggles checklist item {}, which doesn’t exist",
+    "la tâche {} coche l’élément de liste {}, qui n’existe pas",
+  ),
+  (
+    "task {} delivers reminder {}, which doesn’t exist",
+    "la tâche {} délivre le rappel {}, qui n’existe pas",
+  ),
+  (
+    "history of task {} doesn’t match its integrity hash",
+    "l’historique de la tâche {} ne correspond pas à son empreinte d’intégrité",
+  ),
+  (
+    "the history must start with the creation of the task",
+    "l’historique doit commencer par la création de la tâche",
+  ),
+  ("the task is already created", "la tâche est déjà créée"),
+  (
+    "the event, on {}, happens before the last event of the history, on {}",
+    "l’événement, le {}, a lieu avant le dernier événement de l’historique, le {}",
+  ),
+  ("unclosed placeholder", "emplacement non fermé"),
+  (
+    "unmatched }; use }} for a literal brace",
+    "} sans correspondance ; utilisez }} pour une accolade littérale",
+  ),
+  (
+    "unknown field {}; available fields are {} and the user-defined ones",
+    "champ {} inconnu ; les champs disponibles sont {} et ceux définis par l’utilisateur",
+  ),
+  ("invalid alignment: {}", "alignement invalide : {}"),
+  ("metadata validation error: {}", "métadonnées invalides : {}"),
+  (
+    "the note was empty; nothing added",
+    "la note était vide ; rien n’a été ajouté",
+  ),
+  ("unknown format: {}", "format inconnu : {}"),
+  ("template error: {}", "erreur de modèle : {}"),
+  ("interactive edit error: {}", "erreur d’édition interactive : {}"),
+  ("terminal error", "erreur du terminal"),
+  ("unable to open file: {}", "impossible d’ouvrir le fichier : {}"),
+  (
+    "no interactive editor was found; consider configuring either $EDITOR or the configuration",
+    "aucun éditeur interactif n’a été trouvé ; configurez $EDITOR ou la configuration",
+  ),
+  ("invalid editor command: {}", "commande d’éditeur invalide : {}"),
+  (
+    "interactive editor error at path {}: {}",
+    "erreur de l’éditeur interactif pour {} : {}",
+  ),
+  (
+    "error while decoding UTF-8: {}",
+    "erreur lors du décodage de l’UTF-8 : {}",
+  ),
+  ("invalid duration {}: {}", "durée {} invalide : {}"),
+  ("invalid date {}; use YYYY-MM-DD", "date {} invalide ; utilisez AAAA-MM-JJ"),
+  ("empty duration", "durée vide"),
+  ("missing number before {}", "nombre manquant avant {}"),
+  (
+    "missing unit after {0}; use min, h, d or w, e.g. {0}h",
+    "unité manquante après {0} ; utilisez min, h, d ou w, par exemple {0}h",
+  ),
+  (
+    "unknown unit {}; use min, h, d or w",
+    "unité {} inconnue ; utilisez min, h, d ou w",
+  ),
+  ("duration too long", "durée trop longue"),
+  ("cannot render QR code: {}", "impossible d’afficher le QR code : {}"),
+  // tasks
+  ("aborted", "abandon"),
+  ("no task matched", "aucune tâche ne correspond"),
+  (
+    "only show operates on several tasks",
+    "seule la commande show opère sur plusieurs tâches",
+  ),
+  ("no email found", "aucun email trouvé"),
+  (
+    "this email was already added as task {}",
+    "cet email a déjà été ajouté en tant que tâche {}",
+  ),
+  ("(no subject)", "(sans objet)"),
+  (
+    "no task found in the Markdown document",
+    "aucune tâche trouvée dans le document Markdown",
+  ),
+  (
+    "cannot add a task to another one; maybe you were looking for dependencies instead?",
+    "impossible d’ajouter une tâche à une autre ; vous cherchiez peut-être les dépendances ?",
+  ),
+  (
+    "missing or unknown task to edit",
+    "tâche à modifier manquante ou inconnue",
+  ),
+  (
+    "missing or unknown task to show",
+    "tâche à afficher manquante ou inconnue",
+  ),
+  (
+    "missing or unknown task to mark as todo",
+    "tâche à marquer à faire manquante ou inconnue",
+  ),
+  (
+    "missing or unknown task to start",
+    "tâche à démarrer manquante ou inconnue",
+  ),
+  (
+    "missing or unknown task to pause",
+    "tâche à mettre en pause manquante ou inconnue",
+  ),
+  (
+    "missing or unknown task to finish",
+    "tâche à terminer manquante ou inconnue",
+  ),
+  (
+    "missing or unknown task to cancel",
+    "tâche à annuler manquante ou inconnue",
+  ),
+  (
+    "missing or unknown task to move",
+    "tâche à déplacer manquante ou inconnue",
+  ),
+  (
+    "missing or unknown task to pin",
+    "tâche à épingler manquante ou inconnue",
+  ),
+  (
+    "missing or unknown task to snooze",
+    "tâche à reporter manquante ou inconnue",
+  ),
+  (
+    "missing or unknown task to remove",
+    "tâche à supprimer manquante ou inconnue",
+  ),
+  (
+    "missing or unknown task to add, edit or list notes about",
+    "tâche dont ajouter, modifier ou lister les notes manquante ou inconnue",
+  ),
+  (
+    "missing or unknown task to check",
+    "tâche à cocher manquante ou inconnue",
+  ),
+  (
+    "missing or unknown task to display history",
+    "tâche dont afficher l’historique manquante ou inconnue",
+  ),
+  (
+    "missing or unknown task to adjust the spent time of",
+    "tâche dont ajuster le temps passé manquante ou inconnue",
+  ),
+  (
+    "missing or unknown task to share",
+    "tâche à partager manquante ou inconnue",
+  ),
+  ("next occurrence is task {}", "la prochaine occurrence est la tâche {}"),
+  (
+    "moved task {} to the someday backlog",
+    "tâche {} déplacée dans les tâches « un jour »",
+  ),
+  (
+    "moved task {} out of the someday backlog",
+    "tâche {} sortie des tâches « un jour »",
+  ),
+  ("pinned task {}", "tâche {} épinglée"),
+  ("unpinned task {}", "tâche {} désépinglée"),
+  ("snoozed task {} until {}", "tâche {} reportée jusqu’au {}"),
+  (
+    "move all {} tasks to the trash?",
+    "déplacer les {} tâches dans la corbeille ?",
+  ),
+  (
+    "permanently delete {} tasks from the trash?",
+    "supprimer définitivement {} tâches de la corbeille ?",
+  ),
+  ("moved {} tasks to the trash", "{} tâches déplacées dans la corbeille"),
+  ("moved task {} to the trash", "tâche {} déplacée dans la corbeille"),
+  (
+    "cannot edit task {}’s note: no note UID provided",
+    "impossible de modifier la note de la tâche {} : aucun UID de note fourni",
+  ),
+  (
+    "cannot add an empty checklist item",
+    "impossible d’ajouter un élément de liste vide",
+  ),
+  // goals
+  (
+    "cannot add a goal without a name",
+    "impossible d’ajouter un objectif sans nom",
+  ),
+  ("added goal {}", "objectif {} ajouté"),
+  (
+    "missing or unknown task to link to a goal",
+    "tâche à lier à un objectif manquante ou inconnue",
+  ),
+  ("linked task {} to goal {}", "tâche {} liée à l’objectif {}"),
+  ("task {} isn’t linked to any goal", "la tâche {} n’est liée à aucun objectif"),
+  (
+    "no goal yet; add one with td goal add",
+    "aucun objectif pour l’instant ; ajoutez-en un avec td goal add",
+  ),
+  ("(due {})", "(pour le {})"),
+  (
+    "rename project {} to {} in {} tasks?",
+    "renommer le projet {} en {} dans {} tâches ?",
+  ),
+  ("paused task {}", "tâche {} mise en pause"),
+  (
+    "this task looks like a duplicate of:",
+    "cette tâche ressemble à un doublon de :",
+  ),
+  (
+    "the task looks like a duplicate of #{}; pass --force to add it anyway",
+    "la tâche ressemble à un doublon de #{} ; passez --force pour l’ajouter quand même",
+  ),
+  ("({}% similar)", "(similaire à {} %)"),
+  ("corrected {0}{1} to {0}{2}", "{0}{1} corrigé en {0}{2}"),
+  (
+    "did you mean {0}{2} instead of {0}{1}?",
+    "vouliez-vous dire {0}{2} au lieu de {0}{1} ?",
+  ),
+  ("add it anyway?", "l’ajouter quand même ?"),
+  // listings
+  ("contains", "contient"),
+  ("{} todo", "{} à faire"),
+  ("{} wip", "{} en cours"),
+  ("{} paused", "{} en pause"),
+  ("{} done", "{} terminées"),
+  ("{} cancelled", "{} annulées"),
+  ("{} overdue", "{} en retard"),
+  ("{} spent this week", "{} passées cette semaine"),
+  (
+    "interactive listing requires a terminal",
+    "la liste interactive nécessite un terminal",
+  ),
+  (
+    "press enter to go back to the listing",
+    "appuyez sur entrée pour revenir à la liste",
+  ),
+  // showing tasks
+  ("not started yet", "pas encore commencée"),
+  ("Scheduled", "Prévue"),
+  ("Deadline", "Échéance"),
+  ("Recurrence", "Récurrence"),
+  ("Someday", "Un jour"),
+  ("yes", "oui"),
+  ("Pinned", "Épinglée"),
+  ("External ID", "Identifiant externe"),
+  ("Snoozed until", "Reportée jusqu’au"),
+  ("Effort", "Effort"),
+  ("Reminder", "Rappel"),
+  (" Note #", " Note n°"),
+  (", on ", ", le "),
+  (", by ", ", par "),
+  (", edited on ", ", modifiée le "),
+  ("no checklist for this task", "pas de liste pour cette tâche"),
+  ("no note for this task", "pas de note pour cette tâche"),
+  ("new task ➤ ", "nouvelle tâche ➤ "),
+  (
+    "a draft of a note is left for task {}; resume it?",
+    "un brouillon de note reste pour la tâche {} ; le reprendre ?",
+  ),
+  (" · {} words, {} lines", " · {} mots, {} lignes"),
+  (
+    "… {} more lines; use --full to show them",
+    "… {} lignes de plus ; utilisez --full pour les afficher",
+  ),
+  // history
+  ("Task created with uid", "Tâche créée avec l’uid"),
+  ("Status changed to", "Statut changé en"),
+  ("Note added", "Note ajoutée"),
+  ("Reply to note", "Réponse à la note"),
+  ("added", "ajoutée"),
+  ("Note", "Note"),
+  ("updated", "mise à jour"),
+  ("Project set to", "Projet défini à"),
+  ("Context set to", "Contexte défini à"),
+  ("Milestone set to", "Jalon défini à"),
+  ("Priority set to", "Priorité définie à"),
+  ("Tag added #", "Étiquette ajoutée #"),
+  ("Tag renamed #", "Étiquette renommée #"),
+  ("Task moved to the trash", "Tâche déplacée dans la corbeille"),
+  ("Task restored from the trash", "Tâche restaurée depuis la corbeille"),
+  ("Scheduled on", "Prévue le"),
+  ("Deadline set to", "Échéance fixée au"),
+  ("Recurring", "Récurrente"),
+  ("Moved to the someday backlog", "Déplacée dans les tâches « un jour »"),
+  (
+    "Moved out of the someday backlog",
+    "Sortie des tâches « un jour »",
+  ),
+  ("Unpinned", "Désépinglée"),
+  ("Reviewed", "Revue"),
+  ("Linked to goal", "Liée à l’objectif"),
+  ("External ID set to", "Identifiant externe défini à"),
+  ("Effort estimated to", "Effort estimé à"),
+  ("Reminder set for", "Rappel prévu le"),
+  ("delivered", "délivré"),
+  ("Checklist item added", "Élément de liste ajouté"),
+  ("Checklist item", "Élément de liste"),
+  ("toggled", "coché ou décoché"),
+  ("Field", "Champ"),
+  ("set to", "défini à"),
+  ("Spent time adjusted by", "Temps passé ajusté de"),
+  ("Total:", "Total :"),
+  // review and planning
+  (
+    "{} someday tasks are waiting for a review; run td review",
+    "{} tâches « un jour » attendent d’être revues ; lancez td review",
+  ),
+  ("nothing to review", "rien à revoir"),
+  ("({} old)", "(depuis {})"),
+  (
+    "{}eep, {}ctivate, {}ancel, {}kip or {}uit? ➤ ",
+    "{} garder, {} activer, {} annuler, {} passer ou {} quitter ? ➤ ",
+  ),
+  ("reviewed {} tasks", "{} tâches revues"),
+  ("nothing to triage", "rien à trier"),
+  (
+    "priority ({}), empty to skip or {} to quit ➤ ",
+    "priorité ({}), vide pour passer ou {} pour quitter ➤ ",
+  ),
+  ("unknown priority {}", "priorité inconnue {}"),
+  (
+    "effort (e.g. 1h30m), empty to skip or {} to quit ➤ ",
+    "effort (p. ex. 1h30m), vide pour passer ou {} pour quitter ➤ ",
+  ),
+  ("triaged {} tasks", "{} tâches triées"),
+  ("nothing to pick", "rien à tirer au sort"),
+  (
+    "{}tart it, {}eroll or {}uit? ➤ ",
+    "{} commencer, {} retirer ou {} quitter ? ➤ ",
+  ),
+  ("started task {}", "tâche {} commencée"),
+  ("no other task to pick", "plus d’autre tâche à tirer au sort"),
+  ("nothing happened since {}", "rien ne s’est passé depuis {}"),
+  (
+    "… and {} more; see --page {}",
+    "… et {} de plus ; voir --page {}",
+  ),
+  ("nothing to plan", "rien à planifier"),
+  ("plan for", "plan pour"),
+  (", {} free", ", {} de libre"),
+  (" (due)", " (échue)"),
+  ("doesn’t fit:", "ne rentre pas :"),
+  (
+    "not estimated; set their effort with effort:<duration>:",
+    "non estimées ; fixez leur effort avec effort:<durée> :",
+  ),
+  ("reminder:", "rappel :"),
+  // contexts and projects
+  ("switched to context {}", "contexte {} activé"),
+  ("cleared the active context", "contexte actif retiré"),
+  ("active context: {}", "contexte actif : {}"),
+  ("no active context", "aucun contexte actif"),
+  ("({} tasks)", "({} tâches)"),
+  ("no project", "aucun projet"),
+  ("({} open, {} done)", "({} en cours, {} terminées)"),
+  ("{} ({} done)", "{} ({} terminées)"),
+  ("Subprojects", "Sous-projets"),
+  ("{} estimated, {} spent", "{} estimé, {} passé"),
+  ("(over estimate)", "(estimation dépassée)"),
+  ("({} tasks without effort)", "({} tâches sans effort)"),
+  ("no task in project {}", "aucune tâche dans le projet {}"),
+  ("Overdue", "En retard"),
+  ("Due soon", "Bientôt dues"),
+  ("In progress", "En cours"),
+  ("Other tasks", "Autres tâches"),
+  ("no milestone", "aucun jalon"),
+  ("updated {} tasks", "{} tâches mises à jour"),
+  ("no task for this project", "aucune tâche pour ce projet"),
+  // trash
+  ("the trash is empty", "la corbeille est vide"),
+  ("restored task {}", "tâche {} restaurée"),
+  ("permanently deleted {} tasks", "{} tâches définitivement supprimées"),
+  // import, sharing and synchronization
+  ("(already imported)", "(déjà importée)"),
+  (
+    "imported {} tasks, skipped {} already imported",
+    "{} tâches importées, {} déjà importées ignorées",
+  ),
+  (
+    "not an org-protocol capture URL",
+    "ce n’est pas une URL de capture org-protocol",
+  ),
+  ("nothing to capture", "rien à capturer"),
+  ("nothing to normalize", "rien à normaliser"),
+  ("policy violation: {}", "règle non respectée : {}"),
+  ("unknown configuration key: {}", "clé de configuration inconnue : {}"),
+  (
+    "checklist items are numbered from 1",
+    "les éléments de liste sont numérotés à partir de 1",
+  ),
+  ("default", "défaut"),
+  ("every task must have {}", "toute tâche doit avoir {}"),
+  ("no task may have {}", "aucune tâche ne peut avoir {}"),
+  ("tasks with {} must have {}", "les tâches avec {} doivent avoir {}"),
+  (
+    "tasks with {} must not have {}",
+    "les tâches avec {} ne doivent pas avoir {}",
+  ),
+  ("a project", "un projet"),
+  ("a priority", "une priorité"),
+  ("a tag", "une étiquette"),
+  ("a context", "un contexte"),
+  ("a milestone", "un jalon"),
+  ("an effort estimate", "une estimation d’effort"),
+  ("a deadline", "une échéance"),
+  (
+    "run again with --apply to rename them",
+    "relancez avec --apply pour les renommer",
+  ),
+  (
+    "missing [jira] section in the configuration",
+    "section [jira] manquante dans la configuration",
+  ),
+  ("created", "créée"),
+  ("marked as {}", "marquée {}"),
+  ("transitioned to {}", "passée à {}"),
+  ("cannot be transitioned to {}", "ne peut pas passer à {}"),
+  (
+    "synchronized {} issues, {} changes",
+    "{} tickets synchronisés, {} changements",
+  ),
+  ("posted the digest to {}", "résumé publié sur {}"),
+  ("published {} tasks to {}", "{} tâches publiées dans {}"),
+  ("left out {} private tasks", "{} tâches privées laissées de côté"),
+  (
+    "compacted {} journal records into the tasks file",
+    "{} enregistrements du journal compactés dans le fichier des tâches",
+  ),
+  ("dropped {} superseded events", "{} événements obsolètes supprimés"),
+  // conflicts
+  (
+    "unknown strategy {}; use union, theirs or mine",
+    "stratégie {} inconnue ; utilisez union, theirs ou mine",
+  ),
+  (
+    "Task {} ({}) changed in both copies since they diverged.",
+    "La tâche {} ({}) a changé dans les deux copies depuis qu’elles ont divergé.",
+  ),
+  (
+    "Delete the events to drop, or edit them, and save; the events before them are kept.",
+    "Supprimez les événements à abandonner, ou modifiez-les, et enregistrez ; les événements précédents sont \
+     conservés.",
+  ),
+  (
+    "Notes, reminders and checklist items are numbered from 0, in the order they are added.",
+    "Les notes, rappels et éléments de liste sont numérotés à partir de 0, dans l’ordre de leur ajout.",
+  ),
+  (
+    "{} tasks added, {} updated, {} in conflict",
+    "{} tâches ajoutées, {} mises à jour, {} en conflit",
+  ),
+  (
+    "task {} of the other copy added as {}",
+    "tâche {} de l’autre copie ajoutée en tant que {}",
+  ),
+  ("mine", "cette copie"),
+  ("theirs", "l’autre copie"),
+  (
+    "{}nion, {}heirs, {}ine, {}dit or {}kip? ➤ ",
+    "{} union, {} l’autre copie, {} cette copie, {} éditer ou {} passer ? ➤ ",
+  ),
+  (
+    "{} conflicts left; run resolve again to resolve them",
+    "{} conflits restants ; relancez resolve pour les résoudre",
+  ),
+  // notes
+  (", modified on {}", ", modifiée le {}"),
+  ("Note #{}, on {}{}\n{}\n\n", "Note n°{}, le {}{}\n{}\n\n"),
+  (
+    "Above are the previously recorded notes. Lines starting with {} are discarded.",
+    "Ci-dessus se trouvent les notes précédentes. Les lignes commençant par {} sont ignorées.",
+  ),
+  // doctor
+  ("ok", "ok"),
+  ("warning", "attention"),
+  ("error", "erreur"),
+  ("fix:", "solution :"),
+  ("no problem found", "aucun problème trouvé"),
+  ("{} errors, {} warnings", "{} erreurs, {} avertissements"),
+  ("Configuration", "Configuration"),
+  ("configuration root is {}", "la racine de la configuration est {}"),
+  (
+    "configuration root {} doesn’t exist",
+    "la racine de la configuration {} n’existe pas",
+  ),
+  (
+    "check the tasks_file key of the configuration",
+    "vérifiez la clé tasks_file de la configuration",
+  ),
+  ("configuration file is {}", "le fichier de configuration est {}"),
+  (
+    "configuration file {} doesn’t exist",
+    "le fichier de configuration {} n’existe pas",
+  ),
+  (
+    "default values are used; run td without --config to create it with the wizard",
+    "les valeurs par défaut sont utilisées ; lancez td sans --config pour le créer avec l’assistant",
+  ),
+  (
+    "configuration file cannot be read: {}",
+    "le fichier de configuration ne peut pas être lu : {}",
+  ),
+  (
+    "fix it at the reported line, or move it away and run td to create a new one with the wizard",
+    "corrigez-le à la ligne indiquée, ou déplacez-le et lancez td pour en créer un nouveau avec l’assistant",
+  ),
+  (
+    "status aliases are empty or not distinct",
+    "les alias des statuts sont vides ou pas distincts",
+  ),
+  (
+    "set distinct values for todo_alias, wip_alias, done_alias and cancelled_alias",
+    "donnez des valeurs distinctes à todo_alias, wip_alias, done_alias et cancelled_alias",
+  ),
+  ("status aliases are distinct", "les alias des statuts sont distincts"),
+  (
+    "priority levels are missing or their names and aliases are not distinct",
+    "les niveaux de priorité sont absents ou leurs noms et alias ne sont pas distincts",
+  ),
+  (
+    "set distinct names and aliases in priorities, ignoring case",
+    "donnez des noms et alias distincts, sans tenir compte de la casse, dans priorities",
+  ),
+  (
+    "{} priority levels are defined",
+    "{} niveaux de priorité sont définis",
+  ),
+  (
+    "user-defined field names are not distinct, clash with a builtin metadata or are invalid",
+    "les noms des champs définis par l’utilisateur ne sont pas distincts, entrent en conflit avec une métadonnée \
+     intégrée ou sont invalides",
+  ),
+  (
+    "use distinct names without colons nor blanks, other than effort, ctx, milestone, remind, ext and author, \
+     in fields",
+    "utilisez des noms distincts, sans deux-points ni espaces, autres que effort, ctx, milestone, remind, ext et \
+     author, dans fields",
+  ),
+  (
+    "{} user-defined fields are declared",
+    "{} champs définis par l’utilisateur sont déclarés",
+  ),
+  (
+    "max_description_lines is 0; descriptions are not displayed in listings",
+    "max_description_lines vaut 0 ; les descriptions ne sont pas affichées dans les listes",
+  ),
+  (
+    "set max_description_lines to at least 1",
+    "donnez au moins 1 à max_description_lines",
+  ),
+  ("Tasks", "Tâches"),
+  (
+    "{} tasks and {} removed tasks in {}",
+    "{} tâches et {} tâches supprimées dans {}",
+  ),
+  ("no tasks saved yet", "aucune tâche enregistrée pour l’instant"),
+  ("{} cannot be read: {}", "{} ne peut pas être lu : {}"),
+  ("check its permissions", "vérifiez ses permissions"),
+  (
+    "fix it at the reported line and column, or restore a backup from {}",
+    "corrigez-le à la ligne et à la colonne indiquées, ou restaurez une sauvegarde depuis {}",
+  ),
+  (
+    "event {} of task {} cannot be read: {}",
+    "l’événement {} de la tâche {} ne peut pas être lu : {}",
+  ),
+  ("task {} cannot be read: {}", "la tâche {} ne peut pas être lue : {}"),
+  (
+    "fix or remove it in {}, or restore a backup from {}",
+    "corrigez-la ou retirez-la dans {}, ou restaurez une sauvegarde depuis {}",
+  ),
+  ("tasks cannot be loaded: {}", "les tâches ne peuvent pas être chargées : {}"),
+  (
+    "fix the reported file, or restore a backup from {}",
+    "corrigez le fichier indiqué, ou restaurez une sauvegarde depuis {}",
+  ),
+  (
+    "{} changes journaled in {}; td gc compacts them into the tasks file",
+    "{} changements journalisés dans {} ; td gc les compacte dans le fichier des tâches",
+  ),
+  ("set next_uid to {} in {}", "donnez {} à next_uid dans {}"),
+  (
+    "remove task {} from either tasks or trash in {}",
+    "retirez la tâche {} de tasks ou de trash dans {}",
+  ),
+  (
+    "the task was probably edited by hand; check its events with td {} history",
+    "la tâche a sans doute été modifiée à la main ; vérifiez ses événements avec td {} history",
+  ),
+  (
+    "remove the offending event from the history of task {} in {}",
+    "retirez l’événement fautif de l’historique de la tâche {} dans {}",
+  ),
+  (
+    "check its events with td {} history; remove its seal from {} to accept them",
+    "vérifiez ses événements avec td {} history ; retirez son sceau de {} pour les accepter",
+  ),
+  ("Editor", "Éditeur"),
+  ("editor is {}", "l’éditeur est {}"),
+  ("editor {} cannot be found", "l’éditeur {} est introuvable"),
+  (
+    "editor command {} cannot be parsed",
+    "la commande d’éditeur {} est illisible",
+  ),
+  ("check its quotes", "vérifiez ses guillemets"),
+  (
+    "install it, or set $VISUAL, $EDITOR or the interactive_editor key of the configuration",
+    "installez-le, ou définissez $VISUAL, $EDITOR ou la clé interactive_editor de la configuration",
+  ),
+  (
+    "no editor is set; notes cannot be written",
+    "aucun éditeur n’est défini ; les notes ne peuvent pas être écrites",
+  ),
+  (
+    "set $VISUAL, $EDITOR or the interactive_editor key of the configuration",
+    "définissez $VISUAL, $EDITOR ou la clé interactive_editor de la configuration",
+  ),
+  ("Terminal", "Terminal"),
+  (
+    "output is not a terminal; colors and warnings are disabled",
+    "la sortie n’est pas un terminal ; les couleurs et avertissements sont désactivés",
+  ),
+  ("terminal is {}×{}", "le terminal fait {}×{}"),
+  (
+    "terminal doesn’t expose its dimensions; descriptions cannot be wrapped",
+    "le terminal n’expose pas ses dimensions ; les descriptions ne peuvent pas être coupées",
+  ),
+  (
+    "use --plain to get unpadded, tab-separated listings",
+    "utilisez --plain pour des listes sans alignement, séparées par des tabulations",
+  ),
+  ("terminal supports colors", "le terminal gère les couleurs"),
+  ("colors are disabled", "les couleurs sont désactivées"),
+  (
+    "unset $NO_COLOR and make sure $TERM is not dumb",
+    "retirez $NO_COLOR et vérifiez que $TERM ne vaut pas dumb",
+  ),
+  // configuration wizard
+  (
+    "I’m so sorry, but I didn’t quite get that.",
+    "Je suis désolé, mais je n’ai pas bien compris.",
+  ),
+  (
+    "Hello! It seems like you’re new to {0}!\n\n{0} is a modern take on task / todo lists, mostly based on the \
+     amazing emacs’ {1} and {2}. Instead of recreating the same plugin inside everybody’s favorite editors over and \
+     over, {0} takes it the UNIX way and just does one thing good: {3}.\n\nYou will first be able to {4} new tasks, \
+     {5} existing tasks, {6} some and {7} them all. Then, you will be able to enjoy more advanced features, such as \
+     {8} and {9} tasks, {10} them, as well as {11}. Time metadata are automatically handled for you to follow along.",
+    "Bonjour ! Il semble que vous découvriez {0} !\n\n{0} est une version moderne des listes de tâches, largement \
+     inspirée de l’excellent {1} d’emacs et de {2}. Plutôt que de recréer le même plugin dans l’éditeur favori de \
+     chacun encore et encore, {0} suit la voie UNIX et ne fait qu’une chose, bien : {3}.\n\nVous pourrez d’abord {4} \
+     de nouvelles tâches, {5} des tâches existantes, en {6} certaines et toutes les {7}. Vous pourrez ensuite \
+     profiter de fonctionnalités plus avancées, comme {8} et {9} des tâches, les {10}, ou encore {11}. Les \
+     métadonnées de temps sont gérées automatiquement pour vous permettre de suivre le tout.",
+  ),
+  ("editing tasks", "éditer des tâches"),
+  ("add", "ajouter"),
+  ("edit", "modifier"),
+  ("remove", "supprimer"),
+  ("list", "lister"),
+  ("capturing", "capturer"),
+  ("refiling", "reclasser"),
+  ("filtering", "filtrer"),
+  ("putting deadlines", "fixer des échéances"),
+  (
+    "You don’t seem to have a configuration set up…\nWould you like to set it up?",
+    "Vous ne semblez pas avoir de configuration…\nVoulez-vous la créer ?",
+  ),
+  (
+    "won’t work without a configuration file. If you don’t want to generate it via this interactive wizard, you can \
+     create it by hand and put it in the right folder, depending on the platform you run on.",
+    "ne fonctionnera pas sans fichier de configuration. Si vous ne voulez pas le générer avec cet assistant \
+     interactif, vous pouvez le créer à la main et le placer dans le bon dossier, selon votre plateforme.",
+  ),
+];
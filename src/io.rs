@@ -0,0 +1,343 @@
+//! Import and export formats.
+//!
+//! Each format implements [`Format`], and [`Exporter`], [`Importer`] or both, so that the command line — and the
+//! round-trip tests — can handle all of them the same way. Formats are looked up by name in a [`Registry`], which holds
+//! the built-in formats by default; crates embedding toodoux can register their own formats in it.
+//!
+//! Tasks are exported with [`export_public`], which leaves the private tasks out whatever the format, so that exporters
+//! don’t have to care about privacy.
+
+use crate::{
+  config::Config,
+  error::Error,
+  jsonl, markdown, org, privacy, reminders,
+  task::{Task, UID},
+};
+use std::io::Write;
+
+/// Import and export format.
+pub trait Format {
+  /// Name of the format, as passed to `--format`.
+  fn name(&self) -> &str;
+
+  /// Other names the format can be referred to with.
+  fn aliases(&self) -> &[&str] {
+    &[]
+  }
+
+  /// Short description of the format.
+  fn description(&self) -> &str;
+
+  /// Exporter of the format, if tasks can be exported to it.
+  fn exporter(&self) -> Option<&dyn Exporter> {
+    None
+  }
+
+  /// Importer of the format, if tasks can be imported from it.
+  fn importer(&self) -> Option<&dyn Importer> {
+    None
+  }
+}
+
+/// Format tasks can be exported to.
+pub trait Exporter {
+  /// Export tasks to `out`.
+  fn export(
+    &self,
+    out: &mut dyn Write,
+    config: &Config,
+    tasks: &[(UID, &Task)],
+  ) -> Result<(), Error>;
+}
+
+/// Format tasks can be imported from.
+pub trait Importer {
+  /// Import the tasks found in `input`.
+  fn import(&self, input: &str, config: &Config) -> Result<Vec<Task>, Error>;
+}
+
+/// [Org-Mode](https://orgmode.org) documents.
+#[derive(Clone, Copy, Debug)]
+pub struct Org;
+
+impl Format for Org {
+  fn name(&self) -> &str {
+    "org"
+  }
+
+  fn description(&self) -> &str {
+    "Org-Mode document, with scheduling, clocks and notes"
+  }
+
+  fn exporter(&self) -> Option<&dyn Exporter> {
+    Some(self)
+  }
+
+  fn importer(&self) -> Option<&dyn Importer> {
+    Some(self)
+  }
+}
+
+impl Exporter for Org {
+  fn export(
+    &self,
+    out: &mut dyn Write,
+    config: &Config,
+    tasks: &[(UID, &Task)],
+  ) -> Result<(), Error> {
+    org::export(out, tasks.iter().copied(), config.priorities())
+  }
+}
+
+impl Importer for Org {
+  fn import(&self, input: &str, config: &Config) -> Result<Vec<Task>, Error> {
+    Ok(org::import(input, config.priorities()))
+  }
+}
+
+/// [JSON Lines](https://jsonlines.org), one task per line.
+#[derive(Clone, Copy, Debug)]
+pub struct Jsonl;
+
+impl Format for Jsonl {
+  fn name(&self) -> &str {
+    "jsonl"
+  }
+
+  fn description(&self) -> &str {
+    "JSON Lines, one snapshot and history per task"
+  }
+
+  fn exporter(&self) -> Option<&dyn Exporter> {
+    Some(self)
+  }
+
+  fn importer(&self) -> Option<&dyn Importer> {
+    Some(self)
+  }
+}
+
+impl Exporter for Jsonl {
+  fn export(&self, out: &mut dyn Write, _: &Config, tasks: &[(UID, &Task)]) -> Result<(), Error> {
+    jsonl::export(out, tasks.iter().copied())
+  }
+}
+
+impl Importer for Jsonl {
+  fn import(&self, input: &str, _: &Config) -> Result<Vec<Task>, Error> {
+    jsonl::import(input)
+  }
+}
+
+/// Markdown documents, one per task.
+#[derive(Clone, Copy, Debug)]
+pub struct Markdown;
+
+impl Format for Markdown {
+  fn name(&self) -> &str {
+    "markdown"
+  }
+
+  fn aliases(&self) -> &[&str] {
+    &["md"]
+  }
+
+  fn description(&self) -> &str {
+    "Markdown document, one per task"
+  }
+
+  fn exporter(&self) -> Option<&dyn Exporter> {
+    Some(self)
+  }
+}
+
+impl Exporter for Markdown {
+  fn export(
+    &self,
+    out: &mut dyn Write,
+    config: &Config,
+    tasks: &[(UID, &Task)],
+  ) -> Result<(), Error> {
+    markdown::export(out, config, tasks.iter().copied())
+  }
+}
+
+/// AppleScript adding the tasks to Apple Reminders.
+#[derive(Clone, Copy, Debug)]
+pub struct Reminders;
+
+impl Format for Reminders {
+  fn name(&self) -> &str {
+    "reminders"
+  }
+
+  fn description(&self) -> &str {
+    "AppleScript adding the tasks with a deadline to Apple Reminders"
+  }
+
+  fn exporter(&self) -> Option<&dyn Exporter> {
+    Some(self)
+  }
+}
+
+impl Exporter for Reminders {
+  fn export(
+    &self,
+    out: &mut dyn Write,
+    config: &Config,
+    tasks: &[(UID, &Task)],
+  ) -> Result<(), Error> {
+    reminders::export(out, tasks.iter().copied(), config.priorities())
+  }
+}
+
+/// Export the public tasks among `tasks` to `out` with `exporter`; return the number of private tasks left out.
+pub fn export_public<'a>(
+  exporter: &dyn Exporter,
+  out: &mut dyn Write,
+  config: &Config,
+  tasks: impl IntoIterator<Item = (UID, &'a Task)>,
+) -> Result<usize, Error> {
+  let redacted = privacy::redact(&config.privacy, tasks);
+  exporter.export(out, config, &redacted.tasks)?;
+  Ok(redacted.private.len())
+}
+
+/// Formats, looked up by name.
+pub struct Registry {
+  formats: Vec<Box<dyn Format>>,
+}
+
+impl Default for Registry {
+  /// Registry of the built-in formats.
+  fn default() -> Self {
+    let mut registry = Registry::new();
+    registry.register(Org);
+    registry.register(Jsonl);
+    registry.register(Markdown);
+    registry.register(Reminders);
+    registry
+  }
+}
+
+impl Registry {
+  /// Empty registry.
+  pub fn new() -> Self {
+    Registry {
+      formats: Vec::new(),
+    }
+  }
+
+  /// Register a format; it replaces the formats already registered with the same name.
+  pub fn register(&mut self, format: impl Format + 'static) {
+    self
+      .formats
+      .retain(|registered| registered.name() != format.name());
+    self.formats.push(Box::new(format));
+  }
+
+  /// Registered formats, in registration order.
+  pub fn formats(&self) -> impl DoubleEndedIterator<Item = &dyn Format> {
+    self.formats.iter().map(|format| format.as_ref())
+  }
+
+  /// Find a format by name or alias; the most recently registered format wins.
+  pub fn get(&self, name: &str) -> Option<&dyn Format> {
+    self
+      .formats()
+      .rev()
+      .find(|format| format.name() == name || format.aliases().contains(&name))
+  }
+
+  /// Find the exporter of a format by name.
+  pub fn exporter(&self, name: &str) -> Option<&dyn Exporter> {
+    self.get(name)?.exporter()
+  }
+
+  /// Find the importer of a format by name.
+  pub fn importer(&self, name: &str) -> Option<&dyn Importer> {
+    self.get(name)?.importer()
+  }
+}
+
+#[cfg(test)]
+mod unit_tests {
+  use super::*;
+
+  /// Format exporting the names of the tasks, one per line.
+  struct Names;
+
+  impl Format for Names {
+    fn name(&self) -> &str {
+      "names"
+    }
+
+    fn aliases(&self) -> &[&str] {
+      &["md"]
+    }
+
+    fn description(&self) -> &str {
+      "task names"
+    }
+
+    fn exporter(&self) -> Option<&dyn Exporter> {
+      Some(self)
+    }
+  }
+
+  impl Exporter for Names {
+    fn export(&self, out: &mut dyn Write, _: &Config, tasks: &[(UID, &Task)]) -> Result<(), Error> {
+      for (_, task) in tasks {
+        writeln!(out, "{}", task.name()).map_err(Error::CannotExport)?;
+      }
+
+      Ok(())
+    }
+  }
+
+  #[test]
+  fn registry() {
+    let mut registry = Registry::default();
+    let names = |registry: &Registry| {
+      registry
+        .formats()
+        .map(|format| format.name().to_owned())
+        .collect::<Vec<_>>()
+    };
+    assert_eq!(
+      names(&registry),
+      vec!["org", "jsonl", "markdown", "reminders"]
+    );
+
+    assert!(registry.importer("org").is_some());
+    assert!(registry.importer("md").is_none());
+    assert_eq!(registry.get("md").map(Format::name), Some("markdown"));
+    assert!(registry.get("taskwarrior").is_none());
+
+    registry.register(Names);
+    assert_eq!(names(&registry).len(), 5);
+    assert_eq!(registry.get("md").map(Format::name), Some("names"));
+
+    let task = Task::new("Foo");
+    let mut out = Vec::new();
+    registry
+      .exporter("names")
+      .unwrap()
+      .export(&mut out, &Config::default(), &[(UID::default(), &task)])
+      .unwrap();
+    assert_eq!(out, b"Foo\n");
+
+    // private tasks never reach the exporter
+    let mut diary = Task::new("Diary");
+    diary.apply_metadata(vec![crate::metadata::Metadata::tag("private")]);
+    let mut out = Vec::new();
+    let private = export_public(
+      registry.exporter("names").unwrap(),
+      &mut out,
+      &Config::default(),
+      vec![(UID::default(), &task), ("1".parse().unwrap(), &diary)],
+    )
+    .unwrap();
+    assert_eq!((out.as_slice(), private), (&b"Foo\n"[..], 1));
+  }
+}
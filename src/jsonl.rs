@@ -0,0 +1,156 @@
+//! JSON Lines import and export.
+//!
+//! Each task is written on its own line as a JSON object: a flattened snapshot of its current state — status, metadata,
+//! dates and spent time — along with its full event history. Tasks are written one at a time, so the output can be
+//! streamed into `jq` or any data pipeline, whatever the number of tasks.
+//!
+//! Importing only reads the name and the history of each task, the snapshot being derived from them.
+
+use crate::{
+  error::Error,
+  metadata::Priority,
+  task::{Event, Recurrence, Status, Task, UID},
+};
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use serde_json as json;
+use std::io::Write;
+
+/// Snapshot of a task, as written on a line.
+#[derive(Debug, Serialize)]
+pub struct Record<'a> {
+  uid: UID,
+  name: &'a str,
+  status: Status,
+  created: Option<&'a DateTime<Utc>>,
+  project: Option<&'a str>,
+  priority: Option<&'a Priority>,
+  context: Option<&'a str>,
+  milestone: Option<&'a str>,
+  tags: Vec<&'a str>,
+  scheduled: Option<DateTime<Utc>>,
+  deadline: Option<DateTime<Utc>>,
+  recurrence: Option<Recurrence>,
+  effort_minutes: Option<i64>,
+  spent_minutes: i64,
+  someday: bool,
+  pinned: bool,
+  notes: usize,
+  external_id: Option<&'a str>,
+  history: Vec<&'a Event>,
+}
+
+impl<'a> Record<'a> {
+  pub fn new(uid: UID, task: &'a Task) -> Self {
+    Record {
+      uid,
+      name: task.name(),
+      status: task.status(),
+      created: task.creation_date(),
+      project: task.project(),
+      priority: task.priority(),
+      context: task.context(),
+      milestone: task.milestone(),
+      tags: task.tags().collect(),
+      scheduled: task.scheduled(),
+      deadline: task.deadline(),
+      recurrence: task.recurrence(),
+      effort_minutes: task.effort().map(|effort| effort.num_minutes()),
+      spent_minutes: task.spent_time().num_minutes(),
+      someday: task.is_someday(),
+      pinned: task.is_pinned(),
+      notes: task.notes().len(),
+      external_id: task.external_id(),
+      history: task.history().collect(),
+    }
+  }
+}
+
+/// Part of a line read back when importing.
+#[derive(Debug, Deserialize)]
+struct ImportedRecord {
+  name: String,
+  history: Vec<Event>,
+}
+
+/// Import tasks from JSON Lines, one task per line; blank lines are ignored.
+pub fn import(input: &str) -> Result<Vec<Task>, Error> {
+  input
+    .lines()
+    .filter(|line| !line.trim().is_empty())
+    .map(|line| {
+      let record: ImportedRecord = json::from_str(line)?;
+      Ok(Task::from_history(record.name, record.history))
+    })
+    .collect()
+}
+
+/// Export tasks as JSON Lines, one task per line.
+pub fn export<'a>(
+  mut out: impl Write,
+  tasks: impl IntoIterator<Item = (UID, &'a Task)>,
+) -> Result<(), Error> {
+  for (uid, task) in tasks {
+    json::to_writer(&mut out, &Record::new(uid, task))
+      .map_err(|err| Error::CannotExport(err.into()))?;
+    writeln!(out).map_err(Error::CannotExport)?;
+  }
+
+  out.flush().map_err(Error::CannotExport)
+}
+
+#[cfg(test)]
+mod unit_tests {
+  use super::*;
+  use crate::metadata::Metadata;
+
+  #[test]
+  fn one_task_per_line() {
+    let mut foo = Task::new("Foo");
+    foo.apply_metadata(vec![Metadata::project("toodoux"), Metadata::tag("cli")]);
+    foo.change_status(Status::Ongoing);
+    let bar = Task::new("Bar with a\nnewline");
+    let uid = |s: &str| s.parse::<UID>().unwrap();
+
+    let mut out = Vec::new();
+    export(&mut out, vec![(uid("1"), &foo), (uid("2"), &bar)]).unwrap();
+    let out = String::from_utf8(out).unwrap();
+    let lines = out.lines().collect::<Vec<_>>();
+    assert_eq!(lines.len(), 2);
+
+    let foo: json::Value = json::from_str(lines[0]).unwrap();
+    assert_eq!(foo["uid"], 1);
+    assert_eq!(foo["status"], "Ongoing");
+    assert_eq!(foo["project"], "toodoux");
+    assert_eq!(foo["tags"], json::json!(["cli"]));
+    assert_eq!(foo["history"].as_array().unwrap().len(), 5);
+
+    let bar: json::Value = json::from_str(lines[1]).unwrap();
+    assert_eq!(bar["name"], "Bar with a\nnewline");
+    assert!(bar["project"].is_null());
+  }
+
+  #[test]
+  fn import_lines() {
+    let mut foo = Task::new("Foo");
+    foo.apply_metadata(vec![Metadata::project("toodoux")]);
+    foo.change_status(Status::Done);
+
+    let mut out = Vec::new();
+    export(&mut out, vec![("1".parse().unwrap(), &foo)]).unwrap();
+    let input = format!("\n{}\n", String::from_utf8(out).unwrap());
+    let tasks = import(&input).unwrap();
+
+    assert_eq!(tasks.len(), 1);
+    assert_eq!(tasks[0].name(), "Foo");
+    assert_eq!(
+      tasks[0].history().collect::<Vec<_>>(),
+      foo.history().collect::<Vec<_>>()
+    );
+
+    assert!(matches!(
+      import("{\"name\": \"Foo\"}"),
+      Err(Error::CannotDeserializeFromJSON(_))
+    ));
+  }
+}
@@ -1,5 +1,25 @@
 pub mod config;
+pub mod digest;
+pub mod email;
 pub mod error;
 pub mod filter;
+pub mod i18n;
+pub mod io;
+pub mod jsonl;
+pub mod markdown;
 pub mod metadata;
+pub mod migration;
+pub mod nlp;
+pub mod normalize;
+pub mod org;
+pub mod policy;
+pub mod privacy;
+pub mod publish;
+pub mod reminders;
+pub mod share;
+pub mod similarity;
+pub mod storage;
+pub mod sync;
 pub mod task;
+pub mod template;
+pub mod time;
@@ -0,0 +1,494 @@
+//! Markdown import and export.
+//!
+//! Each task is rendered as a self-contained Markdown document — its name as title, a table of its metadata, its
+//! checklist, its notes and a summary of its history — that can be pasted as-is into a pull request description or a
+//! wiki page. Several tasks are separated by horizontal rules.
+//!
+//! The other way around, a task can be read from a Markdown document: its first heading is the name of the task and the
+//! rest of the document a note. The document can start with a front-matter block, delimited by `---` lines, holding
+//! metadata in the inline syntax, such as `@toodoux +h #doc`. So that documents written for static-site generators or
+//! other tools can be imported too, the front matter can also be in YAML — still delimited by `---` lines — or in TOML,
+//! delimited by `+++` lines.
+
+use crate::{
+  config::Config,
+  error::Error,
+  task::{Event, Note, Status, Task, UID},
+  time,
+};
+use chrono::{DateTime, Local, NaiveDate, Utc};
+use itertools::Itertools as _;
+use std::io::{self, Write};
+
+/// Markdown document describing a task.
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct Document {
+  /// Name of the task: the first heading, the `title` of the front matter or the first line, in that order.
+  pub title: String,
+  /// Metadata from the front-matter block, in the metadata syntax, such as `@toodoux` or `due 2021-06-10`.
+  pub metadata: Vec<String>,
+  /// Rest of the document, trimmed.
+  pub body: String,
+}
+
+impl Document {
+  /// Parse a Markdown document; return [`None`] if it is empty.
+  ///
+  /// Fail if the document has a TOML front matter that cannot be parsed.
+  pub fn parse(input: &str) -> Result<Option<Self>, Error> {
+    let mut lines = input.lines().peekable();
+    let delimiter = lines
+      .peek()
+      .map(|line| line.trim_end())
+      .filter(|line| *line == "---" || *line == "+++");
+    let mut front_matter = Vec::new();
+
+    if let Some(delimiter) = delimiter {
+      lines.next();
+
+      for line in &mut lines {
+        if line.trim_end() == delimiter {
+          break;
+        }
+
+        front_matter.push(line);
+      }
+    }
+
+    let (front_title, metadata) = match delimiter {
+      Some("+++") => front_matter_metadata(toml_keys(&front_matter.join("\n"))?),
+      _ if is_yaml(&front_matter) => front_matter_metadata(yaml_keys(&front_matter)),
+      _ => (
+        None,
+        front_matter
+          .iter()
+          .map(|line| line.trim().to_owned())
+          .filter(|line| !line.is_empty())
+          .collect(),
+      ),
+    };
+
+    let lines = lines.collect::<Vec<_>>();
+    let title_index = lines.iter().position(|line| heading(line).is_some());
+    let title_index = match (title_index, &front_title) {
+      (None, None) => lines.iter().position(|line| !line.trim().is_empty()),
+      _ => title_index,
+    };
+    let title = match (title_index, front_title) {
+      (Some(i), _) => heading(lines[i]).unwrap_or(lines[i]).trim().to_owned(),
+      (None, Some(title)) => title,
+      (None, None) => return Ok(None),
+    };
+
+    let body = lines
+      .iter()
+      .enumerate()
+      .filter(|&(i, _)| Some(i) != title_index)
+      .map(|(_, line)| *line)
+      .join("\n");
+
+    Ok(Some(Document {
+      title,
+      metadata,
+      body: body.trim().to_owned(),
+    }))
+  }
+}
+
+/// Whether a front matter delimited by `---` lines is in YAML rather than in the metadata syntax.
+///
+/// Lines starting with `#` are YAML comments but also tags, so the first other line decides.
+fn is_yaml(front_matter: &[&str]) -> bool {
+  front_matter
+    .iter()
+    .map(|line| line.trim())
+    .find(|line| !line.is_empty() && !line.starts_with('#'))
+    .and_then(|line| line.split_once(':'))
+    .is_some_and(|(key, value)| {
+      !key.is_empty()
+        && key
+          .chars()
+          .all(|c| c.is_alphanumeric() || c == '_' || c == '-')
+        && (value.is_empty() || value.starts_with(' '))
+    })
+}
+
+/// Keys of a YAML front matter along with their values.
+///
+/// Only the subset of YAML found in front matters is supported: scalars and lists of scalars, either inline (`[a, b]`)
+/// or as blocks of `- item` lines. Nested mappings are ignored.
+fn yaml_keys(front_matter: &[&str]) -> Vec<(String, Vec<String>)> {
+  let mut keys: Vec<(String, Vec<String>)> = Vec::new();
+
+  for line in front_matter {
+    let trimmed = line.trim();
+
+    if trimmed.is_empty() || trimmed.starts_with('#') {
+      continue;
+    }
+
+    if let Some(item) = trimmed.strip_prefix('-') {
+      // item of a block list, belonging to the last key
+      if let Some((_, values)) = keys.last_mut() {
+        values.extend(yaml_scalar(item));
+      }
+
+      continue;
+    }
+
+    if line.starts_with(char::is_whitespace) {
+      continue;
+    }
+
+    if let Some((key, value)) = trimmed.split_once(':') {
+      let value = value.trim();
+      let values = match value.strip_prefix('[').and_then(|v| v.strip_suffix(']')) {
+        Some(items) => items.split(',').filter_map(yaml_scalar).collect(),
+        None => yaml_scalar(value).into_iter().collect(),
+      };
+
+      keys.push((key.trim().to_owned(), values));
+    }
+  }
+
+  keys
+}
+
+/// Value of a YAML scalar, unquoted and without its trailing comment; [`None`] if it is empty.
+fn yaml_scalar(s: &str) -> Option<String> {
+  let s = s.trim();
+  let unquoted = s
+    .strip_prefix('"')
+    .and_then(|s| s.strip_suffix('"'))
+    .or_else(|| s.strip_prefix('\'').and_then(|s| s.strip_suffix('\'')));
+
+  let s = match unquoted {
+    Some(s) => s,
+    None => s.split_once(" #").map_or(s, |(s, _)| s).trim_end(),
+  };
+
+  Some(s.to_owned()).filter(|s| !s.is_empty())
+}
+
+/// Keys of a TOML front matter along with their values.
+fn toml_keys(front_matter: &str) -> Result<Vec<(String, Vec<String>)>, Error> {
+  let table = toml::from_str::<toml::value::Table>(front_matter)?;
+  let scalar = |value: &toml::Value| match value {
+    toml::Value::String(s) => Some(s.clone()),
+    toml::Value::Integer(_)
+    | toml::Value::Float(_)
+    | toml::Value::Boolean(_)
+    | toml::Value::Datetime(_) => Some(value.to_string()),
+    toml::Value::Array(_) | toml::Value::Table(_) => None,
+  };
+
+  Ok(
+    table
+      .into_iter()
+      .map(|(key, value)| {
+        let values = match value {
+          toml::Value::Array(ref items) => items.iter().filter_map(scalar).collect(),
+          _ => scalar(&value).into_iter().collect(),
+        };
+
+        (key, values)
+      })
+      .collect(),
+  )
+}
+
+/// Title and metadata of a YAML or TOML front matter.
+///
+/// The `project`, `tags`, `priority` and `due` keys — or `deadline` — are converted to the metadata syntax; `due`
+/// accepts dates as well as natural-language phrases, such as `next friday`. Other keys are ignored.
+fn front_matter_metadata(keys: Vec<(String, Vec<String>)>) -> (Option<String>, Vec<String>) {
+  // the metadata syntax uses spaces as separators
+  let word = |s: &str| s.split_whitespace().join("-");
+  let mut title = None;
+  let mut metadata = Vec::new();
+
+  for (key, values) in keys {
+    match key.to_lowercase().as_str() {
+      "title" => title = values.into_iter().next(),
+      "project" => metadata.extend(values.first().map(|project| format!("@{}", word(project)))),
+      "tags" | "tag" => metadata.extend(
+        values
+          .iter()
+          .map(|tag| format!("#{}", word(tag.trim_start_matches('#')))),
+      ),
+      "priority" => metadata.extend(values.first().map(|prio| format!("+{}", word(prio)))),
+      "due" | "deadline" => metadata.extend(values.first().map(|due| {
+        // keep only the day of full dates, such as 2021-06-10T09:00:00+02:00
+        let date = due
+          .get(..10)
+          .filter(|date| NaiveDate::parse_from_str(date, "%Y-%m-%d").is_ok());
+        format!("due {}", date.unwrap_or(due))
+      })),
+      _ => (),
+    }
+  }
+
+  (title, metadata)
+}
+
+/// Text of an ATX heading (`# Title`), without its markers.
+fn heading(line: &str) -> Option<&str> {
+  let text = line.trim_start_matches('#');
+  let level = line.len() - text.len();
+
+  if (1..=6).contains(&level) && (text.is_empty() || text.starts_with(' ')) {
+    Some(text.trim().trim_end_matches('#').trim_end())
+  } else {
+    None
+  }
+}
+
+/// Export tasks as Markdown documents.
+///
+/// Status aliases and priority labels are looked up in `config`.
+pub fn export<'a>(
+  mut out: impl Write,
+  config: &Config,
+  tasks: impl IntoIterator<Item = (UID, &'a Task)>,
+) -> Result<(), Error> {
+  for (i, (uid, task)) in tasks.into_iter().enumerate() {
+    if i > 0 {
+      writeln!(out, "\n---\n").map_err(Error::CannotExport)?;
+    }
+
+    write_task(&mut out, config, uid, task).map_err(Error::CannotExport)?;
+  }
+
+  out.flush().map_err(Error::CannotExport)
+}
+
+fn write_task(out: &mut impl Write, config: &Config, uid: UID, task: &Task) -> io::Result<()> {
+  writeln!(out, "# {}", task.name())?;
+  writeln!(out)?;
+  writeln!(out, "| Field | Value |")?;
+  writeln!(out, "| ----- | ----- |")?;
+
+  for (field, value) in metadata_rows(config, uid, task) {
+    writeln!(out, "| {} | {} |", field, escape_cell(&value))?;
+  }
+
+  let checklist = task.checklist();
+  if !checklist.is_empty() {
+    writeln!(out)?;
+    writeln!(out, "## Checklist")?;
+    writeln!(out)?;
+
+    for item in checklist {
+      let check = if item.checked { 'x' } else { ' ' };
+      writeln!(out, "- [{}] {}", check, item.content)?;
+    }
+  }
+
+  let notes = task.notes();
+  if !notes.is_empty() {
+    writeln!(out)?;
+    writeln!(out, "## Notes")?;
+
+    // replies are quoted under the note they reply to
+    for (nb, depth) in Note::threads(&notes) {
+      let note = &notes[nb];
+      let quote = "> ".repeat(depth);
+
+      writeln!(out)?;
+      write!(
+        out,
+        "{}**Note #{}**, on {}",
+        quote,
+        nb + 1,
+        format_date_time(&note.creation_date)
+      )?;
+
+      if let Some(ref author) = note.author {
+        write!(out, ", by {}", author)?;
+      }
+
+      writeln!(out)?;
+      writeln!(out, "{}", quote.trim_end())?;
+
+      for line in note.content.trim().lines() {
+        writeln!(out, "{}", format!("{}{}", quote, line).trim_end())?;
+      }
+    }
+  }
+
+  writeln!(out)?;
+  writeln!(out, "## History")?;
+  writeln!(out)?;
+
+  let mut events = 0;
+  for event in task.history() {
+    events += 1;
+
+    let summary = match event {
+      Event::Created(_) => "Created".to_owned(),
+      Event::StatusChanged { status, .. } => format!("Marked as {}", status_alias(config, *status)),
+      Event::Removed { .. } => "Removed".to_owned(),
+      Event::Restored { .. } => "Restored".to_owned(),
+      _ => continue,
+    };
+
+    writeln!(out, "- {}: {}", format_date_time(event.date()), summary)?;
+  }
+
+  writeln!(out)?;
+  writeln!(
+    out,
+    "_{} events in total; run `td {} history` for the details._",
+    events, uid
+  )
+}
+
+/// Metadata of a task, as field-value rows; missing metadata are skipped.
+fn metadata_rows(config: &Config, uid: UID, task: &Task) -> Vec<(&'static str, String)> {
+  let date = |date: Option<DateTime<Utc>>| date.map(|date| format_date_time(&date).to_string());
+
+  vec![
+    ("UID", Some(uid.to_string())),
+    (
+      "Status",
+      Some(status_alias(config, task.status()).to_owned()),
+    ),
+    ("Project", task.project().map(str::to_owned)),
+    (
+      "Priority",
+      task.priority().map(|prio| config.priorities().label(prio)),
+    ),
+    ("Context", task.context().map(str::to_owned)),
+    ("Milestone", task.milestone().map(str::to_owned)),
+    (
+      "Tags",
+      Some(task.tags().map(|tag| format!("#{}", tag)).join(", ")).filter(|tags| !tags.is_empty()),
+    ),
+    ("Created", date(task.creation_date().copied())),
+    ("Scheduled", date(task.scheduled())),
+    ("Deadline", date(task.deadline())),
+    ("Recurrence", task.recurrence().map(|rec| rec.to_string())),
+    ("Effort", task.effort().map(time::format_duration)),
+    ("Spent", Some(time::format_duration(task.spent_time()))),
+    ("External ID", task.external_id().map(str::to_owned)),
+  ]
+  .into_iter()
+  .filter_map(|(field, value)| value.map(|value| (field, value)))
+  .collect()
+}
+
+fn status_alias(config: &Config, status: Status) -> &str {
+  match status {
+    Status::Todo => config.todo_alias(),
+    Status::Ongoing => config.wip_alias(),
+    Status::Done => config.done_alias(),
+    Status::Cancelled => config.cancelled_alias(),
+    Status::Paused => config.paused_alias(),
+  }
+}
+
+/// Escape a value so that it fits in a table cell.
+fn escape_cell(value: &str) -> String {
+  value.replace('|', "\\|").replace('\n', " ")
+}
+
+fn format_date_time(date: &DateTime<Utc>) -> impl std::fmt::Display {
+  date.with_timezone(&Local).format("%Y-%m-%d %H:%M")
+}
+
+#[cfg(test)]
+mod unit_tests {
+  use super::*;
+  use crate::metadata::Metadata;
+
+  #[test]
+  fn parse_document() {
+    let doc = Document::parse(
+      "---\n@toodoux +h\n#doc\n---\nSome context.\n\n## Write the docs ##\n\nFirst, the CLI.\n# Not a title\n",
+    )
+    .unwrap()
+    .unwrap();
+    assert_eq!(doc.title, "Write the docs");
+    assert_eq!(doc.metadata, vec!["@toodoux +h", "#doc"]);
+    assert_eq!(
+      doc.body,
+      "Some context.\n\n\nFirst, the CLI.\n# Not a title"
+    );
+
+    let doc = Document::parse("\nWrite the docs\n#hashtag is not a heading")
+      .unwrap()
+      .unwrap();
+    assert_eq!(doc.title, "Write the docs");
+    assert!(doc.metadata.is_empty());
+    assert_eq!(doc.body, "#hashtag is not a heading");
+
+    assert_eq!(Document::parse("---\n@toodoux\n---\n\n").unwrap(), None);
+  }
+
+  #[test]
+  fn parse_front_matter() {
+    let doc = Document::parse(
+      "---\n# from the blog\ntitle: \"Write: the docs\"\nproject: toodoux\ntags: [doc, \"#cli\"]\nlayout: post\n\
+       priority: high # or urgent\ndue: 2021-06-10T09:00:00+02:00\nauthors:\n  - alice\n---\nFirst, the CLI.\n",
+    )
+    .unwrap()
+    .unwrap();
+    assert_eq!(doc.title, "Write: the docs");
+    assert_eq!(
+      doc.metadata,
+      vec!["@toodoux", "#doc", "#cli", "+high", "due 2021-06-10"]
+    );
+    assert_eq!(doc.body, "First, the CLI.");
+
+    let doc = Document::parse(
+      "---\ntags:\n  - doc\n  - good first issue\ndeadline: next friday\n---\n# Write the docs\n",
+    )
+    .unwrap()
+    .unwrap();
+    assert_eq!(doc.title, "Write the docs");
+    assert_eq!(
+      doc.metadata,
+      vec!["#doc", "#good-first-issue", "due next friday"]
+    );
+
+    let doc = Document::parse(
+      "+++\ntitle = \"Write the docs\"\ntags = [\"doc\"]\npriority = \"h\"\ndue = 2021-06-10\n\n[extra]\nx = 1\n+++\n",
+    )
+    .unwrap()
+    .unwrap();
+    assert_eq!(doc.title, "Write the docs");
+    assert_eq!(doc.metadata, vec!["due 2021-06-10", "+h", "#doc"]);
+    assert!(doc.body.is_empty());
+
+    assert!(Document::parse("+++\ntitle = \n+++\n# Write the docs").is_err());
+  }
+
+  #[test]
+  fn export_task() {
+    let config = Config::default();
+    let mut task = Task::new("Write the | docs");
+    task.apply_metadata(vec![Metadata::project("toodoux"), Metadata::tag("doc")]);
+    task.add_checklist_item("cli.md");
+    task.add_note("First draft.\n\nMore to come.");
+    task.reply_to_note(UID::default(), "Looks good").unwrap();
+    task.change_status(Status::Done);
+    let uid = "3".parse::<UID>().unwrap();
+
+    let mut out = Vec::new();
+    export(&mut out, &config, vec![(uid, &task), (uid, &task)]).unwrap();
+    let out = String::from_utf8(out).unwrap();
+
+    assert!(
+      out.starts_with("# Write the | docs\n\n| Field | Value |\n| ----- | ----- |\n| UID | 3 |\n")
+    );
+    assert!(out.contains("| Status | DONE |\n| Project | toodoux |\n| Tags | #doc |\n"));
+    assert!(!out.contains("| Deadline |"));
+    assert!(out.contains("## Checklist\n\n- [ ] cli.md\n"));
+    assert!(out.contains("First draft.\n\nMore to come.\n\n> **Note #2**, on "));
+    assert!(out.contains("\n>\n> Looks good\n"));
+    assert!(out.contains(": Marked as DONE\n"));
+    assert!(
+      out.contains("_8 events in total; run `td 3 history` for the details._\n\n---\n\n# Write")
+    );
+  }
+}
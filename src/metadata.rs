@@ -1,12 +1,20 @@
 //! Metadata available to users for filtering / creating tasks.
 
+use crate::{
+  nlp,
+  time::{self, DurationError},
+  tr,
+};
+use chrono::{DateTime, Duration, Utc};
 use colored::Colorize as _;
 use serde::{Deserialize, Serialize};
 use std::{
+  cmp::Ordering,
   error::Error,
   fmt::{self, Display},
   str::FromStr,
 };
+use unicase::UniCase;
 
 /// Possible errors that can happen when validating metadata.
 #[derive(Debug)]
@@ -16,6 +24,15 @@ pub enum MetadataValidationError {
 
   /// Too many priorities; you should use only one or none.
   TooManyPriorities(usize),
+
+  /// Too many contexts; you should use only one or none.
+  TooManyContexts(usize),
+
+  /// Too many external IDs; you should use only one or none.
+  TooManyExternalIds(usize),
+
+  /// Too many milestones; you should use only one or none.
+  TooManyMilestones(usize),
 }
 
 impl Error for MetadataValidationError {}
@@ -23,12 +40,46 @@ impl Error for MetadataValidationError {}
 impl Display for MetadataValidationError {
   fn fmt(&self, f: &mut fmt::Formatter) -> Result<(), fmt::Error> {
     match *self {
-      MetadataValidationError::TooManyProjects(nb) => write!(f, "too many projects: {}", nb),
-      MetadataValidationError::TooManyPriorities(nb) => write!(f, "too many priorities: {}", nb),
+      MetadataValidationError::TooManyProjects(nb) => {
+        f.write_str(&tr!("too many projects: {}", nb))
+      }
+      MetadataValidationError::TooManyPriorities(nb) => {
+        f.write_str(&tr!("too many priorities: {}", nb))
+      }
+      MetadataValidationError::TooManyContexts(nb) => {
+        f.write_str(&tr!("too many contexts: {}", nb))
+      }
+      MetadataValidationError::TooManyExternalIds(nb) => {
+        f.write_str(&tr!("too many external IDs: {}", nb))
+      }
+      MetadataValidationError::TooManyMilestones(nb) => {
+        f.write_str(&tr!("too many milestones: {}", nb))
+      }
     }
   }
 }
 
+/// Separator of the levels of a project hierarchy, as in `work.backend.api`.
+pub const PROJECT_SEPARATOR: char = '.';
+
+/// Check whether `project` is `ancestor` or one of its descendants in the project hierarchy.
+///
+/// `work.backend` is in `work`, but `workshop` isn’t.
+pub fn is_in_project(project: &str, ancestor: &str, case_insensitive: bool) -> bool {
+  let (head, rest) = match project.get(..ancestor.len()) {
+    Some(head) => (head, &project[ancestor.len()..]),
+    None => return false,
+  };
+
+  let same = if case_insensitive {
+    UniCase::new(head) == UniCase::new(ancestor)
+  } else {
+    head == ancestor
+  };
+
+  same && (rest.is_empty() || rest.starts_with(PROJECT_SEPARATOR))
+}
+
 /// Task metadata.
 #[derive(Clone, Debug, Eq, PartialEq)]
 pub enum Metadata {
@@ -38,6 +89,23 @@ pub enum Metadata {
   Priority(Priority),
   /// Tag.
   Tag(String),
+  /// Estimated effort.
+  Effort(Duration),
+  /// Context, i.e. where or with what the task can be done.
+  Context(String),
+  /// Milestone of the project the task is part of, such as a version.
+  Milestone(String),
+  /// Date at which to be reminded about the task.
+  Reminder(DateTime<Utc>),
+  /// ID of the task in another tool, such as an issue tracker.
+  ExternalId(String),
+  /// Value of a user-defined field, by field name.
+  Field(String, FieldValue),
+  /// Author of the task; only meaningful as a filter, as authors are recorded automatically.
+  Author(String),
+  /// Time since the last activity on the task, compared with a duration: [`Ordering::Greater`] for `inactive.gt:14d`
+  /// and [`Ordering::Less`] for `inactive.lt:14d`; only meaningful as a filter.
+  Inactive(Ordering, Duration),
 }
 
 impl From<Priority> for Metadata {
@@ -52,13 +120,13 @@ impl Metadata {
   pub fn validate<'a>(
     metadata: impl IntoIterator<Item = &'a Metadata>,
   ) -> Result<(), MetadataValidationError> {
-    let (proj_nb, prio_nb) = metadata
-      .into_iter()
-      .fold((0, 0), |(proj_nb, prio_nb), md| match md {
-        Metadata::Project(_) => (proj_nb + 1, proj_nb),
-        Metadata::Priority(_) => (proj_nb, prio_nb + 1),
-        _ => (proj_nb, prio_nb),
-      });
+    let metadata = metadata.into_iter().collect::<Vec<_>>();
+    let count = |is_kind: fn(&Metadata) -> bool| metadata.iter().filter(|md| is_kind(md)).count();
+    let proj_nb = count(|md| matches!(md, Metadata::Project(_)));
+    let prio_nb = count(|md| matches!(md, Metadata::Priority(_)));
+    let ctx_nb = count(|md| matches!(md, Metadata::Context(_)));
+    let ext_nb = count(|md| matches!(md, Metadata::ExternalId(_)));
+    let milestone_nb = count(|md| matches!(md, Metadata::Milestone(_)));
 
     if proj_nb > 1 {
       return Err(MetadataValidationError::TooManyProjects(proj_nb));
@@ -68,6 +136,18 @@ impl Metadata {
       return Err(MetadataValidationError::TooManyPriorities(prio_nb));
     }
 
+    if ctx_nb > 1 {
+      return Err(MetadataValidationError::TooManyContexts(ctx_nb));
+    }
+
+    if ext_nb > 1 {
+      return Err(MetadataValidationError::TooManyExternalIds(ext_nb));
+    }
+
+    if milestone_nb > 1 {
+      return Err(MetadataValidationError::TooManyMilestones(milestone_nb));
+    }
+
     Ok(())
   }
 
@@ -86,8 +166,54 @@ impl Metadata {
     Metadata::Tag(name.into())
   }
 
+  /// Create a metadata representing a context.
+  pub fn context(name: impl Into<String>) -> Self {
+    Metadata::Context(name.into())
+  }
+
+  /// Create a metadata representing a milestone.
+  pub fn milestone(name: impl Into<String>) -> Self {
+    Metadata::Milestone(name.into())
+  }
+
+  /// Create a metadata representing an estimated effort.
+  pub fn effort(effort: Duration) -> Self {
+    Metadata::Effort(effort)
+  }
+
+  /// Create a metadata representing an external ID.
+  pub fn external_id(id: impl Into<String>) -> Self {
+    Metadata::ExternalId(id.into())
+  }
+
+  /// Create a metadata representing the value of a user-defined field.
+  pub fn field(name: impl Into<String>, value: FieldValue) -> Self {
+    Metadata::Field(name.into(), value)
+  }
+
+  /// Create a metadata representing an author.
+  pub fn author(name: impl Into<String>) -> Self {
+    Metadata::Author(name.into())
+  }
+
+  /// Create a metadata representing an inactivity, compared with `duration` with `ordering`.
+  pub fn inactive(ordering: Ordering, duration: Duration) -> Self {
+    Metadata::Inactive(ordering, duration)
+  }
+
+  /// Create a metadata representing a reminder.
+  pub fn reminder(date: DateTime<Utc>) -> Self {
+    Metadata::Reminder(date)
+  }
+
   /// Find metadata in a list of words encoded as a string.
-  pub fn from_words<'a>(strings: impl IntoIterator<Item = &'a str>) -> (Vec<Metadata>, String) {
+  ///
+  /// Priorities are looked up in `priorities` and user-defined fields in `fields`.
+  pub fn from_words<'a>(
+    priorities: &Priorities,
+    fields: &Fields,
+    strings: impl IntoIterator<Item = &'a str>,
+  ) -> (Vec<Metadata>, String) {
     let mut metadata = Vec::new();
     let mut output = Vec::new();
 
@@ -95,7 +221,7 @@ impl Metadata {
       let words = s.split(' ').filter(|s| !s.is_empty());
 
       for word in words {
-        if let Ok(md) = word.parse() {
+        if let Ok(md) = Metadata::parse(word, priorities, fields) {
           metadata.push(md);
         } else {
           output.push(word);
@@ -114,57 +240,373 @@ impl Metadata {
   pub fn filter_like(&self) -> impl Display {
     match *self {
       Metadata::Project(ref p) => format!("@{}", p).magenta(),
-      Metadata::Priority(ref p) => format!("+{:?}", p).yellow(),
+      Metadata::Priority(ref p) => format!("+{}", p.name()).yellow(),
       Metadata::Tag(ref t) => format!("#{}", t).green(),
+      Metadata::Effort(effort) => format!("effort:{}", time::format_duration(effort)).cyan(),
+      Metadata::Context(ref c) => format!("ctx:{}", c).blue(),
+      Metadata::Milestone(ref m) => format!("milestone:{}", m).magenta(),
+      Metadata::Reminder(date) => format!("remind:{}", nlp::format_date_time(date)).cyan(),
+      Metadata::ExternalId(ref id) => format!("ext:{}", id).bright_black(),
+      Metadata::Field(ref name, ref value) => format!("{}:{}", name, value).cyan(),
+      Metadata::Author(ref author) => format!("author:{}", author).bright_black(),
+      Metadata::Inactive(ordering, duration) => format!(
+        "inactive.{}:{}",
+        if ordering == Ordering::Less {
+          "lt"
+        } else {
+          "gt"
+        },
+        time::format_duration(duration)
+      )
+      .cyan(),
     }
   }
 }
 
-impl FromStr for Metadata {
-  type Err = MetadataParsingError;
-
-  fn from_str(s: &str) -> Result<Self, Self::Err> {
+impl Metadata {
+  /// Parse a single metadata, looking up priorities in `priorities` and user-defined fields in `fields`.
+  pub fn parse(
+    s: &str,
+    priorities: &Priorities,
+    fields: &Fields,
+  ) -> Result<Self, MetadataParsingError> {
     let len = s.len();
     if len < 2 {
       return Err(MetadataParsingError::Unknown(s.to_owned()));
     }
 
+    if let Some(context) = s.strip_prefix("ctx:") {
+      if context.is_empty() {
+        return Err(MetadataParsingError::Unknown(s.to_owned()));
+      }
+
+      return Ok(Metadata::context(context));
+    }
+
+    if let Some(milestone) = s.strip_prefix("milestone:") {
+      if milestone.is_empty() {
+        return Err(MetadataParsingError::Unknown(s.to_owned()));
+      }
+
+      return Ok(Metadata::milestone(milestone));
+    }
+
+    if let Some(id) = s.strip_prefix("ext:") {
+      if id.is_empty() {
+        return Err(MetadataParsingError::Unknown(s.to_owned()));
+      }
+
+      return Ok(Metadata::external_id(id));
+    }
+
+    if let Some(author) = s.strip_prefix("author:") {
+      if author.is_empty() {
+        return Err(MetadataParsingError::Unknown(s.to_owned()));
+      }
+
+      return Ok(Metadata::author(author));
+    }
+
+    if let Some(date) = s.strip_prefix("remind:") {
+      return nlp::parse_date_time(date)
+        .map(Metadata::reminder)
+        .ok_or_else(|| MetadataParsingError::Unknown(s.to_owned()));
+    }
+
+    for &(prefix, ordering) in &[
+      ("inactive.gt:", Ordering::Greater),
+      ("inactive.lt:", Ordering::Less),
+    ] {
+      if let Some(duration) = s.strip_prefix(prefix) {
+        return time::parse_duration(duration)
+          .map(|duration| Metadata::inactive(ordering, duration))
+          .map_err(|_| MetadataParsingError::Unknown(s.to_owned()));
+      }
+    }
+
+    if let Some(effort) = s.strip_prefix("effort:") {
+      return time::parse_duration(effort)
+        .map(Metadata::effort)
+        .map_err(MetadataParsingError::InvalidEffort);
+    }
+
     match s.as_bytes()[0] {
       b'@' => Ok(Metadata::project(&s[1..])),
-      b'+' => {
-        if len == 2 {
-          match s.as_bytes()[1] {
-            b'l' => Ok(Metadata::priority(Priority::Low)),
-            b'm' => Ok(Metadata::priority(Priority::Medium)),
-            b'h' => Ok(Metadata::priority(Priority::High)),
-            b'c' => Ok(Metadata::priority(Priority::Critical)),
-            _ => Err(MetadataParsingError::UnknownPriority),
-          }
-        } else {
-          Err(MetadataParsingError::UnknownPriority)
-        }
-      }
+      b'+' => priorities
+        .find(&s[1..])
+        .map(Metadata::priority)
+        .ok_or(MetadataParsingError::UnknownPriority),
       b'#' => Ok(Metadata::tag(&s[1..])),
-      _ => Err(MetadataParsingError::Unknown(s.to_owned())),
+      _ => s
+        .split_once(':')
+        .and_then(|(name, value)| {
+          let def = fields.find(name)?;
+          def
+            .kind
+            .parse(value)
+            .map(|value| Metadata::field(def.name.clone(), value))
+        })
+        .ok_or_else(|| MetadataParsingError::Unknown(s.to_owned())),
     }
   }
 }
 
+/// Parse with the default priorities and no user-defined fields.
+impl FromStr for Metadata {
+  type Err = MetadataParsingError;
+
+  fn from_str(s: &str) -> Result<Self, Self::Err> {
+    Metadata::parse(s, &Priorities::default(), &Fields::default())
+  }
+}
+
 #[derive(Clone, Debug, Eq, PartialEq)]
 pub enum MetadataParsingError {
   /// Occurs when a priority is not recognized as valid.
   UnknownPriority,
   /// Occurs when a string is not recognized as metadata.
   Unknown(String),
+  /// Occurs when the duration of an effort is invalid.
+  InvalidEffort(DurationError),
+}
+
+/// Priority, referring to a level of the configured [`Priorities`] by name.
+#[derive(Clone, Debug, Deserialize, Eq, Hash, PartialEq, Serialize)]
+#[serde(transparent)]
+pub struct Priority(String);
+
+impl Priority {
+  pub fn new(name: impl Into<String>) -> Self {
+    Priority(name.into())
+  }
+
+  pub fn name(&self) -> &str {
+    &self.0
+  }
+}
+
+/// A priority level.
+#[derive(Clone, Debug, Deserialize, Eq, PartialEq, Serialize)]
+pub struct PriorityLevel {
+  /// Name of the level, recorded in the tasks.
+  pub name: String,
+  /// Other names that can be used with the `+` operator.
+  #[serde(default, skip_serializing_if = "Vec::is_empty")]
+  pub aliases: Vec<String>,
+  /// Name to display in listings; defaults to the uppercase name.
+  #[serde(default, skip_serializing_if = "Option::is_none")]
+  pub label: Option<String>,
+}
+
+impl PriorityLevel {
+  pub fn new(
+    name: impl Into<String>,
+    aliases: impl IntoIterator<Item = impl Into<String>>,
+    label: impl Into<Option<String>>,
+  ) -> Self {
+    Self {
+      name: name.into(),
+      aliases: aliases.into_iter().map(Into::into).collect(),
+      label: label.into(),
+    }
+  }
+
+  /// Name to display in listings.
+  pub fn label(&self) -> String {
+    self
+      .label
+      .clone()
+      .unwrap_or_else(|| self.name.to_uppercase())
+  }
+}
+
+/// Ordered priority levels, from the lowest to the highest.
+#[derive(Clone, Debug, Deserialize, Eq, PartialEq, Serialize)]
+#[serde(transparent)]
+pub struct Priorities {
+  levels: Vec<PriorityLevel>,
 }
 
-/// Priority.
-#[derive(Clone, Copy, Debug, Deserialize, Eq, Ord, PartialEq, PartialOrd, Serialize)]
-pub enum Priority {
-  Low,
-  Medium,
-  High,
-  Critical,
+impl Default for Priorities {
+  fn default() -> Self {
+    Self::new(vec![
+      PriorityLevel::new("Low", vec!["l"], None),
+      PriorityLevel::new("Medium", vec!["m"], "MED".to_owned()),
+      PriorityLevel::new("High", vec!["h"], None),
+      PriorityLevel::new("Critical", vec!["c"], "CRIT".to_owned()),
+    ])
+  }
+}
+
+impl Priorities {
+  /// Create priorities from levels, ordered from the lowest to the highest.
+  pub fn new(levels: Vec<PriorityLevel>) -> Self {
+    Self { levels }
+  }
+
+  /// Levels, from the lowest to the highest.
+  pub fn levels(&self) -> &[PriorityLevel] {
+    &self.levels
+  }
+
+  /// Find the priority named `name`, either by its name or one of its aliases, ignoring case.
+  pub fn find(&self, name: &str) -> Option<Priority> {
+    self
+      .levels
+      .iter()
+      .find(|level| {
+        level.name.eq_ignore_ascii_case(name)
+          || level
+            .aliases
+            .iter()
+            .any(|alias| alias.eq_ignore_ascii_case(name))
+      })
+      .map(|level| Priority::new(level.name.clone()))
+  }
+
+  /// Get the level of a priority.
+  ///
+  /// Return [`None`] if the priority is not a configured level anymore.
+  pub fn level(&self, priority: &Priority) -> Option<&PriorityLevel> {
+    self.levels.iter().find(|level| level.name == priority.0)
+  }
+
+  /// Rank of a priority, `0` being the lowest level.
+  ///
+  /// Return [`None`] if the priority is not a configured level anymore.
+  pub fn rank(&self, priority: &Priority) -> Option<usize> {
+    self
+      .levels
+      .iter()
+      .position(|level| level.name == priority.0)
+  }
+
+  /// Key sorting tasks by priority in listings, the greatest being listed first.
+  ///
+  /// Tasks without a priority, or with a priority that is not a configured level anymore, are placed according to
+  /// `no_priority`; [`NoPriorityOrder::Last`] is left to the caller, as it takes precedence over other sort keys.
+  pub fn sort_key(&self, priority: Option<&Priority>, no_priority: NoPriorityOrder) -> usize {
+    match priority.and_then(|priority| self.rank(priority)) {
+      Some(rank) => 2 * rank + 2,
+      None if no_priority == NoPriorityOrder::AboveLowest => 3,
+      None => 1,
+    }
+  }
+
+  /// Name to display for a priority.
+  pub fn label(&self, priority: &Priority) -> String {
+    self
+      .level(priority)
+      .map_or_else(|| priority.0.to_uppercase(), PriorityLevel::label)
+  }
+}
+
+/// Where tasks without a priority are listed, relative to the tasks with one.
+#[derive(Clone, Copy, Debug, Default, Deserialize, Eq, PartialEq, Serialize)]
+#[serde(rename_all = "kebab-case")]
+pub enum NoPriorityOrder {
+  /// Right above the tasks of the lowest priority level.
+  AboveLowest,
+  /// Right below the tasks of the lowest priority level.
+  #[default]
+  BelowLowest,
+  /// After all the tasks with a priority, pinned ones included.
+  Last,
+}
+
+/// Type of a user-defined field.
+#[derive(Clone, Copy, Debug, Deserialize, Eq, PartialEq, Serialize)]
+#[serde(rename_all = "lowercase")]
+pub enum FieldKind {
+  /// Any string, without blanks.
+  String,
+  /// Local date, with an optional time; e.g. `2021-06-10` or `2021-06-10T09:00`.
+  Date,
+  /// Number, possibly decimal.
+  Number,
+}
+
+impl FieldKind {
+  /// Parse a value of this type.
+  pub fn parse(self, value: &str) -> Option<FieldValue> {
+    match self {
+      _ if value.is_empty() => None,
+      FieldKind::String => Some(FieldValue::String(value.to_owned())),
+      FieldKind::Date => nlp::parse_date_time(value).map(FieldValue::Date),
+      FieldKind::Number => value
+        .parse::<f64>()
+        .ok()
+        .filter(|n| n.is_finite())
+        .map(FieldValue::Number),
+    }
+  }
+}
+
+/// Value of a user-defined field.
+#[derive(Clone, Debug, Deserialize, PartialEq, Serialize)]
+pub enum FieldValue {
+  String(String),
+  Date(DateTime<Utc>),
+  Number(f64),
+}
+
+// numbers are always finite
+impl Eq for FieldValue {}
+
+impl Display for FieldValue {
+  fn fmt(&self, f: &mut fmt::Formatter) -> Result<(), fmt::Error> {
+    match *self {
+      FieldValue::String(ref s) => f.write_str(s),
+      FieldValue::Date(date) => f.write_str(&nlp::format_date_time(date)),
+      FieldValue::Number(n) => n.fmt(f),
+    }
+  }
+}
+
+/// Declaration of a user-defined field.
+#[derive(Clone, Debug, Deserialize, Eq, PartialEq, Serialize)]
+pub struct FieldDef {
+  /// Name of the field, used as the metadata operator — `name:value` — and as the column name.
+  pub name: String,
+  /// Type of the values.
+  #[serde(rename = "type")]
+  pub kind: FieldKind,
+}
+
+impl FieldDef {
+  pub fn new(name: impl Into<String>, kind: FieldKind) -> Self {
+    Self {
+      name: name.into(),
+      kind,
+    }
+  }
+}
+
+/// User-defined fields.
+#[derive(Clone, Debug, Default, Deserialize, Eq, PartialEq, Serialize)]
+#[serde(transparent)]
+pub struct Fields {
+  defs: Vec<FieldDef>,
+}
+
+impl Fields {
+  pub fn new(defs: Vec<FieldDef>) -> Self {
+    Self { defs }
+  }
+
+  /// Declarations, in configuration order.
+  pub fn defs(&self) -> &[FieldDef] {
+    &self.defs
+  }
+
+  pub fn is_empty(&self) -> bool {
+    self.defs.is_empty()
+  }
+
+  /// Find the declaration of the field named `name`.
+  pub fn find(&self, name: &str) -> Option<&FieldDef> {
+    self.defs.iter().find(|def| def.name == name)
+  }
 }
 
 #[cfg(test)]
@@ -186,6 +628,18 @@ mod unit_tests {
     );
   }
 
+  #[test]
+  fn project_hierarchy() {
+    assert!(is_in_project("work", "work", false));
+    assert!(is_in_project("work.backend.api", "work", false));
+    assert!(is_in_project("work.backend.api", "work.backend", false));
+    assert!(!is_in_project("workshop", "work", false));
+    assert!(!is_in_project("work", "work.backend", false));
+    assert!(!is_in_project("Work.backend", "work", false));
+    assert!(is_in_project("Work.backend", "work", true));
+    assert!(!is_in_project("日本", "日", false));
+  }
+
   #[test]
   fn tag() {
     assert_eq!("#foo".parse::<Metadata>(), Ok(Metadata::tag("foo")));
@@ -202,22 +656,22 @@ mod unit_tests {
   fn priority() {
     assert_eq!(
       "+l".parse::<Metadata>(),
-      Ok(Metadata::Priority(Priority::Low))
+      Ok(Metadata::Priority(Priority::new("Low")))
     );
 
     assert_eq!(
       "+m".parse::<Metadata>(),
-      Ok(Metadata::Priority(Priority::Medium))
+      Ok(Metadata::Priority(Priority::new("Medium")))
     );
 
     assert_eq!(
       "+h".parse::<Metadata>(),
-      Ok(Metadata::Priority(Priority::High))
+      Ok(Metadata::Priority(Priority::new("High")))
     );
 
     assert_eq!(
       "+c".parse::<Metadata>(),
-      Ok(Metadata::Priority(Priority::Critical))
+      Ok(Metadata::Priority(Priority::new("Critical")))
     );
 
     assert_eq!(
@@ -231,20 +685,223 @@ mod unit_tests {
     );
   }
 
+  #[test]
+  fn context() {
+    assert_eq!(
+      "ctx:phone".parse::<Metadata>(),
+      Ok(Metadata::context("phone"))
+    );
+
+    assert_eq!(
+      "ctx:".parse::<Metadata>(),
+      Err(MetadataParsingError::Unknown("ctx:".to_owned()))
+    );
+  }
+
+  #[test]
+  fn milestone() {
+    assert_eq!(
+      "milestone:v2".parse::<Metadata>(),
+      Ok(Metadata::milestone("v2"))
+    );
+
+    assert_eq!(
+      "milestone:".parse::<Metadata>(),
+      Err(MetadataParsingError::Unknown("milestone:".to_owned()))
+    );
+  }
+
+  #[test]
+  fn external_id() {
+    assert_eq!(
+      "ext:JIRA-123".parse::<Metadata>(),
+      Ok(Metadata::external_id("JIRA-123"))
+    );
+
+    assert_eq!(
+      "ext:".parse::<Metadata>(),
+      Err(MetadataParsingError::Unknown("ext:".to_owned()))
+    );
+  }
+
+  #[test]
+  fn fields() {
+    let fields = Fields::new(vec![
+      FieldDef::new("client", FieldKind::String),
+      FieldDef::new("due_review", FieldKind::Date),
+      FieldDef::new("points", FieldKind::Number),
+    ]);
+    let parse = |s| Metadata::parse(s, &Priorities::default(), &fields);
+
+    assert_eq!(
+      parse("client:acme"),
+      Ok(Metadata::field(
+        "client",
+        FieldValue::String("acme".to_owned())
+      ))
+    );
+    assert_eq!(
+      parse("points:2.5"),
+      Ok(Metadata::field("points", FieldValue::Number(2.5)))
+    );
+    assert_eq!(
+      parse("due_review:2021-06-10"),
+      Ok(Metadata::field(
+        "due_review",
+        FieldValue::Date(nlp::parse_date_time("2021-06-10").unwrap())
+      ))
+    );
+    assert!(parse("points:many").is_err());
+    assert!(parse("client:").is_err());
+    assert!(parse("other:acme").is_err());
+  }
+
+  #[test]
+  fn validate() {
+    assert!(Metadata::validate(&[
+      Metadata::project("a"),
+      Metadata::priority(Priority::new("High"))
+    ])
+    .is_ok());
+    assert!(matches!(
+      Metadata::validate(&[Metadata::project("a"), Metadata::project("b")]),
+      Err(MetadataValidationError::TooManyProjects(2))
+    ));
+    assert!(matches!(
+      Metadata::validate(&[Metadata::context("phone"), Metadata::context("home")]),
+      Err(MetadataValidationError::TooManyContexts(2))
+    ));
+    assert!(matches!(
+      Metadata::validate(&[Metadata::external_id("A-1"), Metadata::external_id("A-2")]),
+      Err(MetadataValidationError::TooManyExternalIds(2))
+    ));
+    assert!(matches!(
+      Metadata::validate(&[Metadata::milestone("v1"), Metadata::milestone("v2")]),
+      Err(MetadataValidationError::TooManyMilestones(2))
+    ));
+  }
+
+  #[test]
+  fn effort() {
+    assert_eq!(
+      "effort:1h30m".parse::<Metadata>(),
+      Ok(Metadata::effort(Duration::minutes(90)))
+    );
+
+    assert_eq!(
+      "effort:soon".parse::<Metadata>(),
+      Err(MetadataParsingError::InvalidEffort(
+        DurationError::UnknownUnit("soon".to_owned())
+      ))
+    );
+  }
+
+  #[test]
+  fn reminder() {
+    assert_eq!(
+      "remind:2021-06-10T09:00".parse::<Metadata>(),
+      Ok(Metadata::reminder(
+        nlp::parse_date_time("2021-06-10T09:00").unwrap()
+      ))
+    );
+
+    assert_eq!(
+      "remind:later".parse::<Metadata>(),
+      Err(MetadataParsingError::Unknown("remind:later".to_owned()))
+    );
+  }
+
+  #[test]
+  fn author() {
+    assert_eq!(
+      "author:alice".parse::<Metadata>(),
+      Ok(Metadata::author("alice"))
+    );
+
+    assert_eq!(
+      "author:".parse::<Metadata>(),
+      Err(MetadataParsingError::Unknown("author:".to_owned()))
+    );
+  }
+
+  #[test]
+  fn inactive() {
+    assert_eq!(
+      "inactive.gt:14d".parse::<Metadata>(),
+      Ok(Metadata::inactive(Ordering::Greater, Duration::days(14)))
+    );
+    assert_eq!(
+      "inactive.lt:2d".parse::<Metadata>(),
+      Ok(Metadata::inactive(Ordering::Less, Duration::days(2)))
+    );
+
+    assert_eq!(
+      "inactive.gt:soon".parse::<Metadata>(),
+      Err(MetadataParsingError::Unknown("inactive.gt:soon".to_owned()))
+    );
+  }
+
   #[test]
   fn extract_metadata_output() {
     let input = "@project1 #tag1 +h Hello, this is world!  #tag2";
-    let (metadata, output) = Metadata::from_words(vec![input].into_iter());
+    let (metadata, output) =
+      Metadata::from_words(&Priorities::default(), &Fields::default(), vec![input]);
 
     assert_eq!(
       metadata,
       vec![
         Metadata::project("project1"),
         Metadata::tag("tag1"),
-        Metadata::priority(Priority::High),
+        Metadata::priority(Priority::new("High")),
         Metadata::tag("tag2")
       ]
     );
     assert_eq!(output, "Hello, this is world!");
   }
+
+  #[test]
+  fn configured_priorities() {
+    let priorities = Priorities::new(
+      (0..5)
+        .rev()
+        .map(|i| PriorityLevel::new(format!("P{}", i), Vec::<String>::new(), None))
+        .collect(),
+    );
+
+    assert_eq!(
+      Metadata::parse("+P0", &priorities, &Fields::default()),
+      Ok(Metadata::priority(Priority::new("P0")))
+    );
+    assert_eq!(
+      Metadata::parse("+p3", &priorities, &Fields::default()),
+      Ok(Metadata::priority(Priority::new("P3")))
+    );
+    assert_eq!(
+      Metadata::parse("+h", &priorities, &Fields::default()),
+      Err(MetadataParsingError::UnknownPriority)
+    );
+
+    assert_eq!(priorities.rank(&Priority::new("P4")), Some(0));
+    assert_eq!(priorities.rank(&Priority::new("P0")), Some(4));
+    assert_eq!(priorities.rank(&Priority::new("High")), None);
+    assert_eq!(priorities.label(&Priority::new("P0")), "P0");
+  }
+
+  #[test]
+  fn default_priorities() {
+    let priorities = Priorities::default();
+
+    assert_eq!(priorities.find("high"), Some(Priority::new("High")));
+    assert_eq!(priorities.find("c"), Some(Priority::new("Critical")));
+    assert_eq!(priorities.label(&Priority::new("Medium")), "MED");
+    assert_eq!(priorities.label(&Priority::new("Low")), "LOW");
+  }
+
+  #[test]
+  fn priority_serialization_compat() {
+    // priorities used to be serialized as a fixed enum
+    let priority: Priority = serde_json::from_str(r#""High""#).unwrap();
+    assert_eq!(priority, Priority::new("High"));
+    assert_eq!(serde_json::to_string(&priority).unwrap(), r#""High""#);
+  }
 }
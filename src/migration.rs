@@ -0,0 +1,123 @@
+//! Migrations of the persisted tasks.
+//!
+//! The layout of the tasks file is versioned via the `version` field of [`TaskManager`]. When loading tasks written by
+//! an older version of **toodoux**, the migrations are applied in order to upgrade them to [`TASKS_VERSION`]; tasks
+//! written by a newer version are rejected rather than misread.
+//!
+//! Any change to what is persisted that an older version would fail to read, or would read and then drop when saving
+//! — new event kinds or fields, new files next to the tasks file — bumps the version, with a migration, even one that
+//! leaves the tasks as they are. The version is bumped once per release, whatever the number of such changes.
+//!
+//! [`TaskManager`]: crate::task::TaskManager
+
+use crate::error::Error;
+use serde_json as json;
+use std::convert::TryFrom;
+
+/// Current version of the layout of the tasks file.
+pub const TASKS_VERSION: u32 = 2;
+
+/// A migration, upgrading tasks from a version to the next one.
+type Migration = fn(&mut json::Value) -> Result<(), Error>;
+
+/// All the migrations, indexed by the version they upgrade from.
+const MIGRATIONS: [Migration; TASKS_VERSION as usize] = [unversioned_to_v1, v1_to_v2];
+
+/// Get the version of serialized tasks.
+///
+/// Tasks without a version were written before versioning was introduced and are version `0`.
+pub fn version(tasks: &json::Value) -> Result<u32, Error> {
+  match tasks.get("version") {
+    None => Ok(0),
+    Some(version) => version
+      .as_u64()
+      .and_then(|version| u32::try_from(version).ok())
+      .ok_or_else(|| Error::InvalidTasksVersion(version.to_string())),
+  }
+}
+
+/// Upgrade serialized tasks to [`TASKS_VERSION`].
+///
+/// Return the version the tasks were upgraded from, if they had to be.
+pub fn migrate(tasks: &mut json::Value) -> Result<Option<u32>, Error> {
+  let from = version(tasks)?;
+
+  if from > TASKS_VERSION {
+    return Err(Error::UnsupportedTasksVersion {
+      found: from,
+      supported: TASKS_VERSION,
+    });
+  }
+
+  if from == TASKS_VERSION {
+    return Ok(None);
+  }
+
+  for version in from..TASKS_VERSION {
+    log::info!(
+      "migrating tasks from version {} to {}",
+      version,
+      version + 1
+    );
+    MIGRATIONS[version as usize](tasks)?;
+    tasks["version"] = json::Value::from(version + 1);
+  }
+
+  Ok(Some(from))
+}
+
+/// The first versioned layout is the same as the unversioned one.
+fn unversioned_to_v1(_: &mut json::Value) -> Result<(), Error> {
+  Ok(())
+}
+
+/// Version 2 adds, to what older versions would either fail to read or read and then drop when saving:
+///
+/// - The `Paused` status, and events setting the context, milestone, recurrence, effort, someday flag, pin, goal,
+///   external ID and user-defined fields of tasks, renaming their tags, snoozing and reviewing them, adding and
+///   delivering their reminders, and adjusting their spent time.
+/// - Replies between notes, the author and sequence number of events, and the seal of histories.
+/// - The active context, goals and logical clock of the task manager.
+/// - Short names for the fields of events, and the journal of changes next to the tasks file.
+///
+/// Tasks of version 1 have none of these, so they are read as they are.
+fn v1_to_v2(_: &mut json::Value) -> Result<(), Error> {
+  Ok(())
+}
+
+#[cfg(test)]
+mod unit_tests {
+  use super::*;
+
+  #[test]
+  fn migrate_unversioned() {
+    let mut tasks = json::json!({ "next_uid": 0, "tasks": {} });
+
+    assert_eq!(migrate(&mut tasks).unwrap(), Some(0));
+    assert_eq!(version(&tasks).unwrap(), TASKS_VERSION);
+    assert_eq!(migrate(&mut tasks).unwrap(), None);
+  }
+
+  #[test]
+  fn reject_newer_version() {
+    let mut tasks = json::json!({ "version": TASKS_VERSION + 1, "next_uid": 0, "tasks": {} });
+
+    match migrate(&mut tasks) {
+      Err(Error::UnsupportedTasksVersion { found, supported }) => {
+        assert_eq!(found, TASKS_VERSION + 1);
+        assert_eq!(supported, TASKS_VERSION);
+      }
+
+      r => panic!("unexpected migration result: {:?}", r),
+    }
+  }
+
+  #[test]
+  fn reject_invalid_version() {
+    let mut tasks = json::json!({ "version": "one", "next_uid": 0, "tasks": {} });
+    assert!(matches!(
+      migrate(&mut tasks),
+      Err(Error::InvalidTasksVersion(_))
+    ));
+  }
+}
@@ -0,0 +1,516 @@
+//! Natural-language quick-add parsing.
+//!
+//! When adding a task, phrases such as `every month`, `starting friday`, `due tomorrow` or `high priority` are
+//! extracted from its content, along with the regular [metadata syntax](crate::metadata), in a single pass over its
+//! words. Phrases are matched case-insensitively; a phrase that cannot be fully understood — e.g. `every` not followed
+//! by a period — is left in the name of the task. Priority phrases have no trigger word, so they are only recognized
+//! when nothing but other phrases follows them.
+
+use crate::{
+  metadata::{Fields, Metadata, Priorities},
+  task::{add_months, Recurrence, Task},
+};
+use chrono::{
+  DateTime, Datelike as _, Duration, Local, NaiveDate, NaiveDateTime, TimeZone as _, Utc, Weekday,
+};
+
+/// Result of parsing the content of a new task.
+#[derive(Clone, Debug, Default, Eq, PartialEq)]
+pub struct QuickAdd {
+  /// Name of the task, i.e. the words that are neither metadata nor phrases.
+  pub name: String,
+  /// Metadata, either from the metadata syntax or from priority phrases.
+  pub metadata: Vec<Metadata>,
+  /// Recurrence, from `every …` phrases.
+  pub recurrence: Option<Recurrence>,
+  /// Start date, from `starting …` phrases or `every <weekday>`.
+  pub start: Option<NaiveDate>,
+  /// Due date, from `due …` and `by …` phrases.
+  pub due: Option<NaiveDate>,
+}
+
+impl QuickAdd {
+  /// Create the task described by the parsed content.
+  pub fn into_task(self) -> Task {
+    let mut task = Task::new(self.name);
+    task.apply_metadata(self.metadata);
+
+    if let Some(recurrence) = self.recurrence {
+      task.set_recurrence(recurrence);
+    }
+
+    if let Some(start) = self.start {
+      task.set_scheduled(start_of_day(start));
+    }
+
+    if let Some(due) = self.due {
+      task.set_deadline(start_of_day(due));
+    }
+
+    task
+  }
+}
+
+/// A phrase recognized in the content of a task.
+enum Phrase {
+  Metadata(Metadata),
+  Recurrence(Recurrence, Option<NaiveDate>),
+  Start(NaiveDate),
+  Due(NaiveDate),
+}
+
+/// Parse the content of a new task; relative dates are relative to `today`.
+///
+/// Priorities, either with the `+` operator or in priority phrases, are looked up in `priorities`; user-defined fields
+/// are looked up in `fields`.
+pub fn parse<'a>(
+  priorities: &Priorities,
+  fields: &Fields,
+  strings: impl IntoIterator<Item = &'a str>,
+  today: NaiveDate,
+) -> QuickAdd {
+  let words = strings
+    .into_iter()
+    .flat_map(|s| s.split(' '))
+    .filter(|s| !s.is_empty())
+    .collect::<Vec<_>>();
+  let lowercase = words.iter().map(|w| w.to_lowercase()).collect::<Vec<_>>();
+  let lowercase = lowercase.iter().map(String::as_str).collect::<Vec<_>>();
+
+  let mut quick_add = QuickAdd::default();
+  let mut name = Vec::new();
+  let mut i = 0;
+
+  while i < words.len() {
+    match parse_phrase(priorities, fields, &words[i..], &lowercase[i..], today) {
+      Some((phrase, consumed)) => {
+        match phrase {
+          Phrase::Metadata(md) => quick_add.metadata.push(md),
+
+          Phrase::Recurrence(recurrence, start) => {
+            quick_add.recurrence = Some(recurrence);
+            quick_add.start = quick_add.start.or(start);
+          }
+
+          Phrase::Start(date) => quick_add.start = Some(date),
+          Phrase::Due(date) => quick_add.due = Some(date),
+        }
+
+        i += consumed;
+      }
+
+      None => {
+        name.push(words[i]);
+        i += 1;
+      }
+    }
+  }
+
+  quick_add.name = name.join(" ");
+  quick_add
+}
+
+/// Convert a date to the start of that day, in local time.
+pub fn start_of_day(date: NaiveDate) -> DateTime<Utc> {
+  Local
+    .from_local_datetime(&date.and_hms(0, 0, 0))
+    .earliest()
+    .map_or_else(
+      || Utc.from_utc_datetime(&date.and_hms(0, 0, 0)),
+      |date| date.with_timezone(&Utc),
+    )
+}
+
+/// Parse a local date and time such as `2021-06-10T09:00`, or a date such as `2021-06-10`, meaning the start of that
+/// day.
+pub fn parse_date_time(s: &str) -> Option<DateTime<Utc>> {
+  if let Ok(date) = NaiveDate::parse_from_str(s, "%Y-%m-%d") {
+    return Some(start_of_day(date));
+  }
+
+  let date_time = NaiveDateTime::parse_from_str(s, "%Y-%m-%dT%H:%M").ok()?;
+  Local
+    .from_local_datetime(&date_time)
+    .earliest()
+    .map(|date_time| date_time.with_timezone(&Utc))
+}
+
+/// Format a date and time the way [`parse_date_time`] reads it, in local time; e.g. `2021-06-10T09:00`.
+pub fn format_date_time(date_time: DateTime<Utc>) -> String {
+  date_time
+    .with_timezone(&Local)
+    .format("%Y-%m-%dT%H:%M")
+    .to_string()
+}
+
+/// Try to parse a phrase starting at the first of `words`; `lowercase` are the same words, in lowercase.
+///
+/// Return the phrase and the number of words it spans.
+fn parse_phrase(
+  priorities: &Priorities,
+  fields: &Fields,
+  words: &[&str],
+  lowercase: &[&str],
+  today: NaiveDate,
+) -> Option<(Phrase, usize)> {
+  if let Ok(md) = Metadata::parse(words.first()?, priorities, fields) {
+    return Some((Phrase::Metadata(md), 1));
+  }
+
+  match lowercase {
+    ["every", rest @ ..] => {
+      parse_recurrence(rest, today).map(|(r, start, n)| (Phrase::Recurrence(r, start), n + 1))
+    }
+
+    ["starting", rest @ ..] => {
+      parse_date(rest, today).map(|(date, n)| (Phrase::Start(date), n + 1))
+    }
+
+    ["due", rest @ ..] | ["by", rest @ ..] => {
+      parse_date(rest, today).map(|(date, n)| (Phrase::Due(date), n + 1))
+    }
+
+    // priority phrases have no trigger word, so they are only recognized at the end of the name, in order not to eat
+    // names such as “Triage high priority bugs”
+    [level, "priority", ..] | ["priority", level, ..]
+      if only_phrases(priorities, fields, &words[2..], &lowercase[2..], today) =>
+    {
+      priorities
+        .find(level)
+        .map(|prio| (Phrase::Metadata(Metadata::priority(prio)), 2))
+    }
+
+    _ => None,
+  }
+}
+
+/// Check whether `words` are only made of phrases, i.e. none of them belongs to the name of the task.
+fn only_phrases(
+  priorities: &Priorities,
+  fields: &Fields,
+  words: &[&str],
+  lowercase: &[&str],
+  today: NaiveDate,
+) -> bool {
+  let mut i = 0;
+
+  while i < words.len() {
+    match parse_phrase(priorities, fields, &words[i..], &lowercase[i..], today) {
+      Some((_, consumed)) => i += consumed,
+      None => return false,
+    }
+  }
+
+  true
+}
+
+/// Largest number of periods accepted in `every <n> <unit>` phrases.
+const MAX_RECURRENCE: u32 = 1000;
+
+/// Parse what follows `every`, returning the recurrence, an optional start date and the number of words spanned.
+fn parse_recurrence(
+  words: &[&str],
+  today: NaiveDate,
+) -> Option<(Recurrence, Option<NaiveDate>, usize)> {
+  match words {
+    [first, ..] if parse_weekday(first).is_some() => {
+      let weekday = parse_weekday(first)?;
+      Some((Recurrence::Weeks(1), Some(next_weekday(today, weekday)), 1))
+    }
+
+    ["other", unit, ..] => parse_unit(unit, 2).map(|r| (r, None, 2)),
+
+    [n, unit, ..] if n.parse::<u32>().is_ok_and(|n| n > 0 && n <= MAX_RECURRENCE) => {
+      parse_unit(unit, n.parse().ok()?).map(|r| (r, None, 2))
+    }
+
+    [unit, ..] => parse_unit(unit, 1).map(|r| (r, None, 1)),
+
+    [] => None,
+  }
+}
+
+/// Parse a recurrence unit, either singular or plural.
+fn parse_unit(unit: &str, n: u32) -> Option<Recurrence> {
+  match unit.strip_suffix('s').unwrap_or(unit) {
+    "day" => Some(Recurrence::Days(n)),
+    "week" => Some(Recurrence::Weeks(n)),
+    "month" => Some(Recurrence::Months(n)),
+    "year" => Some(Recurrence::Years(n)),
+    _ => None,
+  }
+}
+
+/// Parse a date, returning it along with the number of words spanned.
+///
+/// Weekdays refer to the next such day, excluding today.
+fn parse_date(words: &[&str], today: NaiveDate) -> Option<(NaiveDate, usize)> {
+  match words {
+    ["today", ..] => Some((today, 1)),
+    ["tomorrow", ..] => Some((today + Duration::days(1), 1)),
+
+    ["next", first, ..] if parse_weekday(first).is_some() => {
+      Some((next_weekday(today, parse_weekday(first)?), 2))
+    }
+
+    ["next", "week", ..] => Some((today + Duration::weeks(1), 2)),
+    ["next", "month", ..] => Some((add_months(today, 1)?, 2)),
+    ["next", "year", ..] => Some((add_months(today, 12)?, 2)),
+
+    ["in", n, unit, ..] => {
+      let n = n.parse().ok()?;
+      let date = match parse_unit(unit, n)? {
+        Recurrence::Days(n) => today.checked_add_signed(Duration::days(n.into()))?,
+        Recurrence::Weeks(n) => today.checked_add_signed(Duration::weeks(n.into()))?,
+        Recurrence::Months(n) => add_months(today, n)?,
+        Recurrence::Years(n) => add_months(today, n.checked_mul(12)?)?,
+      };
+
+      Some((date, 3))
+    }
+
+    [first, ..] => parse_weekday(first)
+      .map(|weekday| (next_weekday(today, weekday), 1))
+      .or_else(|| {
+        NaiveDate::parse_from_str(first, "%Y-%m-%d")
+          .ok()
+          .map(|date| (date, 1))
+      }),
+
+    [] => None,
+  }
+}
+
+/// Parse a full or abbreviated weekday name.
+fn parse_weekday(word: &str) -> Option<Weekday> {
+  match word {
+    "monday" | "mon" => Some(Weekday::Mon),
+    "tuesday" | "tue" => Some(Weekday::Tue),
+    "wednesday" | "wed" => Some(Weekday::Wed),
+    "thursday" | "thu" => Some(Weekday::Thu),
+    "friday" | "fri" => Some(Weekday::Fri),
+    "saturday" | "sat" => Some(Weekday::Sat),
+    "sunday" | "sun" => Some(Weekday::Sun),
+    _ => None,
+  }
+}
+
+/// Next day after `today` that is a `weekday`.
+fn next_weekday(today: NaiveDate, weekday: Weekday) -> NaiveDate {
+  let days = (7 + weekday.num_days_from_monday() - today.weekday().num_days_from_monday()) % 7;
+  today + Duration::days(if days == 0 { 7 } else { days.into() })
+}
+
+#[cfg(test)]
+mod unit_tests {
+  use super::*;
+  use crate::metadata::Priority;
+
+  /// A friday.
+  fn today() -> NaiveDate {
+    NaiveDate::from_ymd(2021, 6, 4)
+  }
+
+  fn date(m: u32, d: u32) -> NaiveDate {
+    NaiveDate::from_ymd(2021, m, d)
+  }
+
+  fn parse_str(s: &str) -> QuickAdd {
+    parse(&Priorities::default(), &Fields::default(), vec![s], today())
+  }
+
+  #[test]
+  fn plain_name() {
+    let quick_add = parse_str("Write the documentation");
+
+    assert_eq!(quick_add.name, "Write the documentation");
+    assert_eq!(
+      quick_add,
+      QuickAdd {
+        name: "Write the documentation".to_owned(),
+        ..QuickAdd::default()
+      }
+    );
+  }
+
+  #[test]
+  fn full_example() {
+    let quick_add = parse_str("Pay rent every month starting friday +h @home");
+
+    assert_eq!(quick_add.name, "Pay rent");
+    assert_eq!(quick_add.recurrence, Some(Recurrence::Months(1)));
+    assert_eq!(quick_add.start, Some(date(6, 11)));
+    assert_eq!(quick_add.due, None);
+    assert_eq!(
+      quick_add.metadata,
+      vec![
+        Metadata::priority(Priority::new("High")),
+        Metadata::project("home")
+      ]
+    );
+  }
+
+  #[test]
+  fn words_are_split() {
+    let quick_add = parse(
+      &Priorities::default(),
+      &Fields::default(),
+      vec!["Pay rent", "every", "week"],
+      today(),
+    );
+
+    assert_eq!(quick_add.name, "Pay rent");
+    assert_eq!(quick_add.recurrence, Some(Recurrence::Weeks(1)));
+  }
+
+  #[test]
+  fn recurrences() {
+    let recurrence = |s| parse_str(s).recurrence;
+
+    assert_eq!(recurrence("a every day"), Some(Recurrence::Days(1)));
+    assert_eq!(recurrence("a every week"), Some(Recurrence::Weeks(1)));
+    assert_eq!(recurrence("a every month"), Some(Recurrence::Months(1)));
+    assert_eq!(recurrence("a every year"), Some(Recurrence::Years(1)));
+    assert_eq!(recurrence("a every 3 days"), Some(Recurrence::Days(3)));
+    assert_eq!(recurrence("a every 2 weeks"), Some(Recurrence::Weeks(2)));
+    assert_eq!(
+      recurrence("a every other month"),
+      Some(Recurrence::Months(2))
+    );
+    assert_eq!(recurrence("a Every 1 Year"), Some(Recurrence::Years(1)));
+  }
+
+  #[test]
+  fn weekday_recurrence_starts_on_next_weekday() {
+    let quick_add = parse_str("Water plants every monday");
+
+    assert_eq!(quick_add.name, "Water plants");
+    assert_eq!(quick_add.recurrence, Some(Recurrence::Weeks(1)));
+    assert_eq!(quick_add.start, Some(date(6, 7)));
+
+    // an explicit start date wins
+    let quick_add = parse_str("Water plants every monday starting 2021-07-05");
+    assert_eq!(quick_add.start, Some(date(7, 5)));
+  }
+
+  #[test]
+  fn dates() {
+    let start = |s| parse_str(s).start;
+
+    assert_eq!(start("a starting today"), Some(today()));
+    assert_eq!(start("a starting tomorrow"), Some(date(6, 5)));
+    assert_eq!(start("a starting friday"), Some(date(6, 11)));
+    assert_eq!(start("a starting sat"), Some(date(6, 5)));
+    assert_eq!(start("a starting next wednesday"), Some(date(6, 9)));
+    assert_eq!(start("a starting next week"), Some(date(6, 11)));
+    assert_eq!(start("a starting next month"), Some(date(7, 4)));
+    assert_eq!(
+      start("a starting next year"),
+      Some(NaiveDate::from_ymd(2022, 6, 4))
+    );
+    assert_eq!(start("a starting in 3 days"), Some(date(6, 7)));
+    assert_eq!(start("a starting in 2 weeks"), Some(date(6, 18)));
+    assert_eq!(start("a starting in 1 month"), Some(date(7, 4)));
+    assert_eq!(start("a starting 2021-12-25"), Some(date(12, 25)));
+  }
+
+  #[test]
+  fn due_dates() {
+    let quick_add = parse_str("Send the report due tomorrow");
+    assert_eq!(quick_add.name, "Send the report");
+    assert_eq!(quick_add.due, Some(date(6, 5)));
+
+    let quick_add = parse_str("Send the report by monday");
+    assert_eq!(quick_add.name, "Send the report");
+    assert_eq!(quick_add.due, Some(date(6, 7)));
+  }
+
+  #[test]
+  fn priorities() {
+    let priority = |s| parse_str(s).metadata;
+
+    assert_eq!(
+      priority("a low priority"),
+      vec![Metadata::priority(Priority::new("Low"))]
+    );
+    assert_eq!(
+      priority("a Medium priority"),
+      vec![Metadata::priority(Priority::new("Medium"))]
+    );
+    assert_eq!(
+      priority("a priority high"),
+      vec![Metadata::priority(Priority::new("High"))]
+    );
+    assert_eq!(
+      priority("a critical priority"),
+      vec![Metadata::priority(Priority::new("Critical"))]
+    );
+    assert_eq!(
+      priority("a high priority due tomorrow @home"),
+      vec![
+        Metadata::priority(Priority::new("High")),
+        Metadata::project("home")
+      ]
+    );
+  }
+
+  #[test]
+  fn incomplete_phrases_are_kept() {
+    for s in &[
+      "Read every book",
+      "Review due diligence",
+      "Go by train",
+      "Starting the engine",
+      "Discuss the priority list",
+      "Triage high priority bugs",
+      "Sort priority low items",
+      "every",
+      "a every 0 days",
+      "a starting in 3",
+      "a starting in 4000000000 days",
+      "a starting in 400000000 years",
+      "a every 4000000000 years",
+      "a due 2021-13-01",
+    ] {
+      let quick_add = parse_str(s);
+      assert_eq!(quick_add.name, *s);
+      assert_eq!(quick_add.recurrence, None);
+      assert_eq!(quick_add.start, None);
+      assert_eq!(quick_add.due, None);
+      assert!(quick_add.metadata.is_empty());
+    }
+  }
+
+  #[test]
+  fn add_months_clamps_days() {
+    assert_eq!(
+      add_months(NaiveDate::from_ymd(2021, 1, 31), 1),
+      Some(date(2, 28))
+    );
+    assert_eq!(
+      add_months(NaiveDate::from_ymd(2020, 1, 31), 1),
+      Some(NaiveDate::from_ymd(2020, 2, 29))
+    );
+    assert_eq!(
+      add_months(date(11, 30), 3),
+      Some(NaiveDate::from_ymd(2022, 2, 28))
+    );
+    assert_eq!(add_months(today(), u32::MAX), None);
+  }
+
+  #[test]
+  fn date_times() {
+    let date_time = parse_date_time("2021-06-10T09:00").unwrap();
+    assert_eq!(
+      date_time.with_timezone(&Local).naive_local(),
+      date(6, 10).and_hms(9, 0, 0)
+    );
+    assert_eq!(format_date_time(date_time), "2021-06-10T09:00");
+    assert_eq!(
+      parse_date_time("2021-06-10"),
+      Some(start_of_day(date(6, 10)))
+    );
+    assert_eq!(parse_date_time("tomorrow"), None);
+    assert_eq!(parse_date_time("2021-06-10T25:00"), None);
+  }
+}
@@ -0,0 +1,224 @@
+//! Normalization of project and tag names.
+//!
+//! Projects and tags read from the command line are normalized according to the `[normalization]` section of the
+//! configuration, so that `#Bugs`, `#bugs` and `#bug` don’t end up being three different tags: names can be lowercased,
+//! their accented letters composed, and aliases replaced with the names they stand for.
+
+use crate::{
+  config::NormalizationConfig,
+  metadata::{self, Metadata},
+};
+
+/// Combining marks, along with the letters they compose with and the resulting letters, in the same order.
+const COMPOSITIONS: &[(char, &str, &str)] = &[
+  (
+    '\u{300}',
+    "AEIOUaeiouÜüNnĒēŌōWwÂâĂăÊêÔôƠơƯưYy",
+    "ÀÈÌÒÙàèìòùǛǜǸǹḔḕṐṑẀẁẦầẰằỀềỒồỜờỪừỲỳ",
+  ),
+  (
+    '\u{301}',
+    "AEIOUYaeiouyCcLlNnRrSsZzÜüGgÅåÆæØø",
+    "ÁÉÍÓÚÝáéíóúýĆćĹĺŃńŔŕŚśŹźǗǘǴǵǺǻǼǽǾǿ",
+  ),
+  (
+    '\u{301}',
+    "ÇçĒēÏïKkMmÕõŌōPpŨũWwÂâĂăÊêÔôƠơƯư",
+    "ḈḉḖḗḮḯḰḱḾḿṌṍṒṓṔṕṸṹẂẃẤấẮắẾếỐốỚớỨứ",
+  ),
+  (
+    '\u{302}',
+    "AEIOUaeiouCcGgHhJjSsWwYyZzẠạẸẹỌọ",
+    "ÂÊÎÔÛâêîôûĈĉĜĝĤĥĴĵŜŝŴŵŶŷẐẑẬậỆệỘộ",
+  ),
+  (
+    '\u{303}',
+    "ANOanoIiUuVvÂâĂăEeÊêÔôƠơƯưYy",
+    "ÃÑÕãñõĨĩŨũṼṽẪẫẴẵẼẽỄễỖỗỠỡỮữỸỹ",
+  ),
+  (
+    '\u{304}',
+    "AaEeIiOoUuÜüÄäȦȧÆæǪǫÖöÕõȮȯYyGgḶḷṚṛ",
+    "ĀāĒēĪīŌōŪūǕǖǞǟǠǡǢǣǬǭȪȫȬȭȰȱȲȳḠḡḸḹṜṝ",
+  ),
+  ('\u{306}', "AaEeGgIiOoUuȨȩẠạ", "ĂăĔĕĞğĬĭŎŏŬŭḜḝẶặ"),
+  (
+    '\u{307}',
+    "CcEeGgIZzAaOoBbDdFfHhMmNnPpRrSsŚśŠ",
+    "ĊċĖėĠġİŻżȦȧȮȯḂḃḊḋḞḟḢḣṀṁṄṅṖṗṘṙṠṡṤṥṦ",
+  ),
+  ('\u{307}', "šṢṣTtWwXxYyſ", "ṧṨṩṪṫẆẇẊẋẎẏẛ"),
+  (
+    '\u{308}',
+    "AEIOUaeiouyYHhÕõŪūWwXxt",
+    "ÄËÏÖÜäëïöüÿŸḦḧṎṏṺṻẄẅẌẍẗ",
+  ),
+  ('\u{30a}', "AaUuwy", "ÅåŮůẘẙ"),
+  ('\u{30b}', "OoUu", "ŐőŰű"),
+  (
+    '\u{30c}',
+    "CcDdEeLlNnRrSsTtZzAaIiOoUuÜüGgKkƷʒ",
+    "ČčĎďĚěĽľŇňŘřŠšŤťŽžǍǎǏǐǑǒǓǔǙǚǦǧǨǩǮǯ",
+  ),
+  ('\u{30c}', "jHh", "ǰȞȟ"),
+  (
+    '\u{323}',
+    "BbDdHhKkLlMmNnRrSsTtVvWwZzAaEeIiOo",
+    "ḄḅḌḍḤḥḲḳḶḷṂṃṆṇṚṛṢṣṬṭṾṿẈẉẒẓẠạẸẹỊịỌọ",
+  ),
+  ('\u{323}', "ƠơUuƯưYy", "ỢợỤụỰựỴỵ"),
+  (
+    '\u{327}',
+    "CcGgKkLlNnRrSsTtEeDdHh",
+    "ÇçĢģĶķĻļŅņŖŗŞşŢţȨȩḐḑḨḩ",
+  ),
+  ('\u{328}', "AaEeIiUuOo", "ĄąĘęĮįŲųǪǫ"),
+];
+
+/// Normalize a project name.
+///
+/// Aliases apply to subprojects too: with `work = "job"`, `work.backend` becomes `job.backend`. The longest alias wins.
+pub fn project(config: &NormalizationConfig, project: &str) -> String {
+  let project = canonical(config, project);
+  let alias = config
+    .project_aliases
+    .iter()
+    .map(|(alias, name)| (canonical(config, alias), name))
+    .filter(|(alias, _)| metadata::is_in_project(&project, alias, false))
+    .max_by_key(|(alias, _)| alias.len());
+
+  match alias {
+    Some((alias, name)) => format!("{}{}", canonical(config, name), &project[alias.len()..]),
+    None => project,
+  }
+}
+
+/// Normalize a tag.
+pub fn tag(config: &NormalizationConfig, tag: &str) -> String {
+  let tag = canonical(config, tag);
+  let alias = config
+    .tag_aliases
+    .iter()
+    .find(|(alias, _)| canonical(config, alias) == tag)
+    .map(|(_, name)| canonical(config, name));
+
+  alias.unwrap_or(tag)
+}
+
+/// Normalize the projects and tags of `metadata`.
+pub fn metadata(config: &NormalizationConfig, metadata: &mut [Metadata]) {
+  for md in metadata {
+    match md {
+      Metadata::Project(name) => *name = project(config, name),
+      Metadata::Tag(name) => *name = tag(config, name),
+      _ => (),
+    }
+  }
+}
+
+/// Apply the case and unicode rules to a name; aliases are left to the caller.
+fn canonical(config: &NormalizationConfig, name: &str) -> String {
+  let name = if config.nfc {
+    compose(name)
+  } else {
+    name.to_owned()
+  };
+
+  if config.lowercase {
+    name.to_lowercase()
+  } else {
+    name
+  }
+}
+
+/// Compose the accented letters written as a letter followed by combining marks, as Unicode NFC does; e.g. `e`
+/// followed by U+0301 becomes `é`.
+///
+/// Only the letters of the Latin-1 Supplement, Latin Extended-A and B and Latin Extended Additional blocks are
+/// composed, which covers the accented letters of the languages written with the Latin alphabet.
+fn compose(s: &str) -> String {
+  let mut composed = String::with_capacity(s.len());
+
+  for c in s.chars() {
+    let pair = composed
+      .chars()
+      .next_back()
+      .and_then(|last| compose_pair(last, c));
+
+    match pair {
+      Some(pair) => {
+        composed.pop();
+        composed.push(pair);
+      }
+
+      None => composed.push(c),
+    }
+  }
+
+  composed
+}
+
+/// Compose a letter with a combining mark, if they form a single letter.
+fn compose_pair(letter: char, mark: char) -> Option<char> {
+  COMPOSITIONS
+    .iter()
+    .filter(|&&(m, _, _)| m == mark)
+    .find_map(|(_, letters, composed)| {
+      let i = letters.chars().position(|l| l == letter)?;
+      composed.chars().nth(i)
+    })
+}
+
+#[cfg(test)]
+mod unit_tests {
+  use super::*;
+
+  #[test]
+  fn composition() {
+    assert_eq!(compose("cafe\u{301}"), "café");
+    assert_eq!(compose("e\u{323}\u{302}"), "ệ");
+    assert_eq!(compose("u\u{308}\u{304}"), "ǖ");
+    assert_eq!(compose("\u{301}x\u{301}"), "\u{301}x\u{301}");
+    assert_eq!(compose("déjà"), "déjà");
+  }
+
+  #[test]
+  fn normalization() {
+    let mut config = NormalizationConfig::default();
+    assert_eq!(tag(&config, "Bugs"), "Bugs");
+
+    config.lowercase = true;
+    config.nfc = true;
+    config
+      .tag_aliases
+      .insert("Bugs".to_owned(), "bug".to_owned());
+    config
+      .project_aliases
+      .insert("work".to_owned(), "job".to_owned());
+    config
+      .project_aliases
+      .insert("work.web".to_owned(), "web".to_owned());
+
+    assert_eq!(tag(&config, "BUGS"), "bug");
+    assert_eq!(tag(&config, "Cafe\u{301}"), "café");
+    assert_eq!(project(&config, "Work"), "job");
+    assert_eq!(project(&config, "work.Backend"), "job.backend");
+    assert_eq!(project(&config, "work.web.api"), "web.api");
+    assert_eq!(project(&config, "workshop"), "workshop");
+
+    let mut md = vec![
+      Metadata::project("Work"),
+      Metadata::tag("bugs"),
+      Metadata::context("Desk"),
+    ];
+    metadata(&config, &mut md);
+    assert_eq!(
+      md,
+      vec![
+        Metadata::project("job"),
+        Metadata::tag("bug"),
+        Metadata::context("Desk")
+      ]
+    );
+  }
+}
@@ -0,0 +1,888 @@
+//! Org-Mode import and export.
+//!
+//! Tasks are mapped to Org headlines starting with a TODO keyword. On top of the headline itself (keyword, priority
+//! cookie, title and tags), the following Org constructs are supported, so that scheduling and time data survive the
+//! trip:
+//!
+//! - `SCHEDULED:` and `DEADLINE:` planning timestamps.
+//! - The `:PROPERTIES:` drawer, for the `CATEGORY` (mapped to the project), `CREATED` and `ID` properties. A file-level
+//!   `#+CATEGORY:` keyword is also honored.
+//! - The `:LOGBOOK:` drawer: state changes, `CLOCK:` entries (mapped to WIP periods) and notes.
+//!
+//! Imported tasks get an external ID, so that importing the same file twice doesn’t duplicate them: the `ID` property
+//! if present, or one made of the category and the title of the headline otherwise. It is exported back as the `ID`
+//! property.
+//!
+//! Org timestamps are expressed in local time.
+//!
+//! Tasks can also be captured from a browser with the [org-protocol](https://orgmode.org/manual/Protocols.html)
+//! `capture` sub-protocol, which browser extensions use to send the URL, the title and the selected text of a page.
+
+use crate::{
+  error::Error,
+  metadata::{Priorities, Priority},
+  task::{Event, Status, Task, UID},
+};
+use chrono::{DateTime, Local, NaiveDate, NaiveTime, TimeZone, Utc};
+use std::io::Write;
+
+/// TODO keywords recognized when importing, along with the status they map to.
+const KEYWORDS: &[(&str, Status)] = &[
+  ("TODO", Status::Todo),
+  ("NEXT", Status::Todo),
+  ("WAITING", Status::Todo),
+  ("WIP", Status::Ongoing),
+  ("STARTED", Status::Ongoing),
+  ("ONGOING", Status::Ongoing),
+  ("PAUSED", Status::Paused),
+  ("DONE", Status::Done),
+  ("CANCELLED", Status::Cancelled),
+  ("CANCELED", Status::Cancelled),
+];
+
+/// Whether a line is a headline, i.e. stars followed by a blank; other lines starting with stars, such as `*bold*`
+/// text, belong to the body.
+fn is_headline(line: &str) -> bool {
+  let rest = line.trim_start_matches('*');
+  rest.len() < line.len() && rest.starts_with(' ')
+}
+
+/// Import tasks from the content of an Org file.
+///
+/// Headlines that don’t start with a TODO keyword are ignored, as well as timestamps that cannot be parsed. Priority
+/// cookies map to `priorities`, `[#A]` being the highest level.
+pub fn import(input: &str, priorities: &Priorities) -> Vec<Task> {
+  let mut tasks = Vec::new();
+  let mut file_category = None;
+  let mut entry: Option<Entry> = None;
+  let mut drawer = None;
+
+  for line in input.lines() {
+    let trimmed = line.trim();
+
+    if is_headline(line) {
+      if let Some(entry) = entry.take() {
+        tasks.push(entry.into_task(file_category.as_deref()));
+      }
+
+      entry = Entry::from_headline(line, priorities);
+      drawer = None;
+      continue;
+    }
+
+    if let Some(category) = trimmed.strip_prefix("#+CATEGORY:") {
+      file_category = Some(category.trim().to_owned());
+      continue;
+    }
+
+    let entry = match entry.as_mut() {
+      Some(entry) => entry,
+      None => continue,
+    };
+
+    match drawer {
+      _ if trimmed == ":END:" => {
+        entry.flush_note();
+        drawer = None;
+      }
+
+      Some(Drawer::Properties) => entry.parse_property(trimmed),
+
+      Some(Drawer::Logbook) => entry.parse_logbook_line(trimmed),
+
+      Some(Drawer::Other) => (),
+
+      None => {
+        if trimmed == ":PROPERTIES:" {
+          drawer = Some(Drawer::Properties);
+        } else if trimmed == ":LOGBOOK:" {
+          drawer = Some(Drawer::Logbook);
+        } else if trimmed.len() > 2 && trimmed.starts_with(':') && trimmed.ends_with(':') {
+          drawer = Some(Drawer::Other);
+        } else if trimmed.starts_with("SCHEDULED:")
+          || trimmed.starts_with("DEADLINE:")
+          || trimmed.starts_with("CLOSED:")
+        {
+          entry.parse_planning(trimmed);
+        } else {
+          entry.body.push(line.to_owned());
+        }
+      }
+    }
+  }
+
+  if let Some(entry) = entry {
+    tasks.push(entry.into_task(file_category.as_deref()));
+  }
+
+  tasks
+}
+
+/// Export tasks as an Org document.
+///
+/// Priorities are mapped to cookies according to `priorities`, the highest level being `[#A]`.
+pub fn export<'a>(
+  mut out: impl Write,
+  tasks: impl IntoIterator<Item = (UID, &'a Task)>,
+  priorities: &Priorities,
+) -> Result<(), Error> {
+  for (uid, task) in tasks {
+    export_task(&mut out, uid, task, priorities).map_err(Error::CannotExport)?;
+  }
+
+  Ok(())
+}
+
+fn export_task(
+  out: &mut impl Write,
+  uid: UID,
+  task: &Task,
+  priorities: &Priorities,
+) -> std::io::Result<()> {
+  // headline
+  write!(out, "* {}", keyword(task.status()))?;
+
+  if let Some(cookie) = task
+    .priority()
+    .and_then(|priority| priority_cookie(priority, priorities))
+  {
+    write!(out, " [#{}]", cookie)?;
+  }
+
+  write!(out, " {}", task.name())?;
+
+  let tags = task.tags().collect::<Vec<_>>();
+  if !tags.is_empty() {
+    write!(out, " :{}:", tags.join(":"))?;
+  }
+
+  writeln!(out)?;
+
+  // planning
+  let planning = task
+    .scheduled()
+    .map(|date| format!("SCHEDULED: <{}>", format_timestamp(&date)))
+    .into_iter()
+    .chain(
+      task
+        .deadline()
+        .map(|date| format!("DEADLINE: <{}>", format_timestamp(&date))),
+    )
+    .collect::<Vec<_>>();
+
+  if !planning.is_empty() {
+    writeln!(out, "  {}", planning.join(" "))?;
+  }
+
+  // properties
+  writeln!(out, "  :PROPERTIES:")?;
+  writeln!(out, "  :TOODOUX_UID: {}", uid)?;
+
+  if let Some(external_id) = task.external_id() {
+    writeln!(out, "  :ID: {}", external_id)?;
+  }
+
+  if let Some(project) = task.project() {
+    writeln!(out, "  :CATEGORY: {}", project)?;
+  }
+
+  if let Some(creation_date) = task.creation_date() {
+    writeln!(out, "  :CREATED: [{}]", format_timestamp(creation_date))?;
+  }
+
+  writeln!(out, "  :END:")?;
+
+  // logbook; Org puts the most recent entries first
+  let logbook = logbook_entries(task);
+  if !logbook.is_empty() {
+    writeln!(out, "  :LOGBOOK:")?;
+
+    for entry in logbook.iter().rev() {
+      writeln!(out, "  {}", entry)?;
+    }
+
+    writeln!(out, "  :END:")?;
+  }
+
+  Ok(())
+}
+
+/// Compute the logbook entries of a task, in chronological order.
+///
+/// Periods spent in WIP are encoded as clocks while other status changes are encoded as state changes.
+fn logbook_entries(task: &Task) -> Vec<String> {
+  let mut entries = Vec::new();
+  let mut previous_status = None;
+  let mut clock_start = None;
+
+  for event in task.history() {
+    match event {
+      Event::StatusChanged { event_date, status } => {
+        if let Some(start) = clock_start.take() {
+          entries.push(format_clock(&start, event_date));
+        }
+
+        match previous_status {
+          _ if *status == Status::Ongoing => clock_start = Some(*event_date),
+
+          // going back to TODO after WIP is already implied by the end of the clock
+          Some(Status::Ongoing) if *status == Status::Todo => (),
+
+          Some(previous_status) => entries.push(format!(
+            "- State {:<12} from {:<12} [{}]",
+            format!("\"{}\"", keyword(*status)),
+            format!("\"{}\"", keyword(previous_status)),
+            format_timestamp(event_date)
+          )),
+
+          None => (),
+        }
+
+        previous_status = Some(*status);
+      }
+
+      Event::NoteAdded {
+        event_date,
+        content,
+        ..
+      } => {
+        let mut entry = format!("- Note taken on [{}] \\\\", format_timestamp(event_date));
+
+        for line in content.trim().lines() {
+          entry.push('\n');
+
+          if !line.is_empty() {
+            entry.push_str("    ");
+            entry.push_str(line);
+          }
+        }
+
+        entries.push(entry);
+      }
+
+      _ => (),
+    }
+  }
+
+  if let Some(start) = clock_start {
+    entries.push(format!("CLOCK: [{}]", format_timestamp(&start)));
+  }
+
+  entries
+}
+
+fn format_clock(start: &DateTime<Utc>, end: &DateTime<Utc>) -> String {
+  let minutes = end.signed_duration_since(*start).num_minutes();
+
+  format!(
+    "CLOCK: [{}]--[{}] => {:2}:{:02}",
+    format_timestamp(start),
+    format_timestamp(end),
+    minutes / 60,
+    minutes % 60
+  )
+}
+
+/// Format a date as the inner part of an Org timestamp (i.e. without the brackets).
+fn format_timestamp(date: &DateTime<Utc>) -> String {
+  date
+    .with_timezone(&Local)
+    .format("%Y-%m-%d %a %H:%M")
+    .to_string()
+}
+
+/// Parse the inner part of an Org timestamp (i.e. without the brackets).
+///
+/// The day name, repeaters and warning delays are ignored. If no time is present, midnight is assumed.
+fn parse_timestamp(s: &str) -> Option<DateTime<Utc>> {
+  let mut parts = s.split_whitespace();
+  let date = NaiveDate::parse_from_str(parts.next()?, "%Y-%m-%d").ok()?;
+  let time = parts
+    .find_map(|part| {
+      // time ranges, such as 10:00-11:00, only keep their start time
+      let time = part.split('-').next()?;
+      NaiveTime::parse_from_str(time, "%H:%M").ok()
+    })
+    .unwrap_or_else(|| NaiveTime::from_hms(0, 0, 0));
+
+  Local
+    .from_local_datetime(&date.and_time(time))
+    .earliest()
+    .map(|date| date.with_timezone(&Utc))
+}
+
+/// Find the first timestamp delimited by `open` and `close` in the input string and parse it.
+///
+/// Return the parsed timestamp along with the rest of the string after the timestamp.
+fn find_timestamp(s: &str, open: char, close: char) -> Option<(Option<DateTime<Utc>>, &str)> {
+  let start = s.find(open)?;
+  let len = s[start..].find(close)?;
+  let timestamp = parse_timestamp(&s[start + 1..start + len]);
+
+  Some((timestamp, &s[start + len + 1..]))
+}
+
+fn keyword(status: Status) -> &'static str {
+  match status {
+    Status::Todo => "TODO",
+    Status::Ongoing => "WIP",
+    Status::Done => "DONE",
+    Status::Cancelled => "CANCELLED",
+    Status::Paused => "PAUSED",
+  }
+}
+
+fn status_from_keyword(keyword: &str) -> Option<Status> {
+  KEYWORDS
+    .iter()
+    .find(|(kw, _)| *kw == keyword)
+    .map(|(_, status)| *status)
+}
+
+/// Cookie letter of a priority; only the 26 highest levels have one.
+fn priority_cookie(priority: &Priority, priorities: &Priorities) -> Option<char> {
+  let rank = priorities.rank(priority)?;
+  let index = priorities.levels().len() - 1 - rank;
+
+  if index < 26 {
+    Some((b'A' + index as u8) as char)
+  } else {
+    None
+  }
+}
+
+fn priority_from_cookie(cookie: &str, priorities: &Priorities) -> Option<Priority> {
+  let letter = cookie.strip_prefix("[#")?.strip_suffix(']')?;
+
+  match letter.as_bytes() {
+    &[letter @ b'A'..=b'Z'] => {
+      let levels = priorities.levels();
+      let rank = levels.len().checked_sub(1 + usize::from(letter - b'A'))?;
+      Some(Priority::new(levels[rank].name.clone()))
+    }
+
+    _ => None,
+  }
+}
+
+/// Drawers we care about.
+#[derive(Clone, Copy, Debug)]
+enum Drawer {
+  Properties,
+  Logbook,
+  Other,
+}
+
+/// An Org entry being parsed.
+#[derive(Debug, Default)]
+struct Entry {
+  name: String,
+  status: Option<Status>,
+  priority: Option<Priority>,
+  tags: Vec<String>,
+  scheduled: Option<DateTime<Utc>>,
+  deadline: Option<DateTime<Utc>>,
+  category: Option<String>,
+  created: Option<DateTime<Utc>>,
+  id: Option<String>,
+  /// Events found in the logbook, in the order of the entries they come from.
+  events: Vec<Event>,
+  /// Note being currently parsed in the logbook.
+  note: Option<(DateTime<Utc>, Vec<String>)>,
+  body: Vec<String>,
+}
+
+impl Entry {
+  /// Parse a headline; return [`None`] if it’s not a task.
+  fn from_headline(line: &str, priorities: &Priorities) -> Option<Self> {
+    let rest = line.trim_start_matches('*');
+    let mut words = rest.split_whitespace().collect::<Vec<_>>();
+    let status = status_from_keyword(words.first()?)?;
+    words.remove(0);
+
+    let priority = words
+      .first()
+      .and_then(|word| priority_from_cookie(word, priorities));
+    if priority.is_some() {
+      words.remove(0);
+    }
+
+    let mut tags = Vec::new();
+    if let Some(last) = words.last() {
+      if last.len() > 2 && last.starts_with(':') && last.ends_with(':') {
+        tags = last
+          .split(':')
+          .filter(|tag| !tag.is_empty())
+          .map(str::to_owned)
+          .collect();
+        words.pop();
+      }
+    }
+
+    Some(Entry {
+      name: words.join(" "),
+      status: Some(status),
+      priority,
+      tags,
+      ..Entry::default()
+    })
+  }
+
+  fn parse_planning(&mut self, mut line: &str) {
+    while let Some(index) = line.find(|c: char| c.is_ascii_uppercase()) {
+      line = &line[index..];
+
+      if let Some(rest) = line.strip_prefix("SCHEDULED:") {
+        if let Some((date, rest)) = find_timestamp(rest, '<', '>') {
+          self.scheduled = date;
+          line = rest;
+          continue;
+        }
+      } else if let Some(rest) = line.strip_prefix("DEADLINE:") {
+        if let Some((date, rest)) = find_timestamp(rest, '<', '>') {
+          self.deadline = date;
+          line = rest;
+          continue;
+        }
+      } else if let Some(rest) = line.strip_prefix("CLOSED:") {
+        if let Some((_, rest)) = find_timestamp(rest, '[', ']') {
+          line = rest;
+          continue;
+        }
+      }
+
+      break;
+    }
+  }
+
+  fn parse_property(&mut self, line: &str) {
+    if let Some(category) = line.strip_prefix(":CATEGORY:") {
+      self.category = Some(category.trim().to_owned());
+    } else if let Some(created) = line.strip_prefix(":CREATED:") {
+      self.created = find_timestamp(created, '[', ']')
+        .or_else(|| find_timestamp(created, '<', '>'))
+        .and_then(|(date, _)| date);
+    } else if let Some(id) = line.strip_prefix(":ID:") {
+      self.id = Some(id.trim().to_owned()).filter(|id| !id.is_empty());
+    }
+  }
+
+  fn parse_logbook_line(&mut self, line: &str) {
+    if let Some(state) = line.strip_prefix("- State ") {
+      self.flush_note();
+
+      let status = state.split('"').nth(1).and_then(status_from_keyword);
+      let date = find_timestamp(state, '[', ']').and_then(|(date, _)| date);
+
+      if let (Some(status), Some(event_date)) = (status, date) {
+        self.log(vec![Event::StatusChanged { event_date, status }]);
+      }
+    } else if let Some(clock) = line.strip_prefix("CLOCK:") {
+      self.flush_note();
+
+      if let Some((Some(start), rest)) = find_timestamp(clock, '[', ']') {
+        let mut events = vec![Event::StatusChanged {
+          event_date: start,
+          status: Status::Ongoing,
+        }];
+
+        if let Some((Some(end), _)) = find_timestamp(rest, '[', ']') {
+          events.push(Event::StatusChanged {
+            event_date: end,
+            status: Status::Todo,
+          });
+        }
+
+        self.log(events);
+      }
+    } else if let Some(note) = line.strip_prefix("- Note taken on ") {
+      self.flush_note();
+
+      if let Some((Some(date), rest)) = find_timestamp(note, '[', ']') {
+        let first_line = rest.trim().trim_end_matches("\\\\").trim();
+        let lines = if first_line.is_empty() {
+          Vec::new()
+        } else {
+          vec![first_line.to_owned()]
+        };
+
+        self.note = Some((date, lines));
+      }
+    } else if let Some((_, ref mut lines)) = self.note {
+      lines.push(line.to_owned());
+    }
+  }
+
+  /// Record the note being currently parsed, if any.
+  fn flush_note(&mut self) {
+    if let Some((event_date, lines)) = self.note.take() {
+      self.log(vec![Event::NoteAdded {
+        event_date,
+        content: lines.join("\n"),
+        in_reply_to: None,
+      }]);
+    }
+  }
+
+  /// Record the events of a logbook entry.
+  ///
+  /// The logbook lists the most recent entries first, so the events are prepended: events of different entries
+  /// occurring at the same time, such as two consecutive clocks, then stay in chronological order.
+  fn log(&mut self, events: Vec<Event>) {
+    self.events.splice(0..0, events);
+  }
+
+  fn into_task(mut self, file_category: Option<&str>) -> Task {
+    self.flush_note();
+
+    // stable sort, so that events occurring at the same time keep the order of their entries
+    self.events.sort_by_key(|event| *event.date());
+
+    let created = self
+      .created
+      .or_else(|| self.events.first().map(|event| *event.date()))
+      .or(self.scheduled)
+      .unwrap_or_else(Utc::now);
+
+    let mut history = vec![
+      Event::Created(created),
+      Event::StatusChanged {
+        event_date: created,
+        status: Status::Todo,
+      },
+    ];
+
+    let category = self.category.as_deref().or(file_category);
+    let external_id = match self.id {
+      Some(id) => id,
+      None => format!("org:{}/{}", category.unwrap_or_default(), self.name),
+    };
+    history.push(Event::SetExternalId {
+      event_date: created,
+      external_id,
+    });
+
+    if let Some(project) = category {
+      history.push(Event::SetProject {
+        event_date: created,
+        project: project.to_owned(),
+      });
+    }
+
+    if let Some(priority) = self.priority {
+      history.push(Event::SetPriority {
+        event_date: created,
+        priority,
+      });
+    }
+
+    for tag in self.tags {
+      history.push(Event::AddTag {
+        event_date: created,
+        tag,
+      });
+    }
+
+    if let Some(scheduled) = self.scheduled {
+      history.push(Event::SetScheduled {
+        event_date: created,
+        scheduled,
+      });
+    }
+
+    if let Some(deadline) = self.deadline {
+      history.push(Event::SetDeadline {
+        event_date: created,
+        deadline,
+      });
+    }
+
+    let body = self.body.join("\n");
+    if !body.trim().is_empty() {
+      history.push(Event::NoteAdded {
+        event_date: created,
+        content: body.trim().to_owned(),
+        in_reply_to: None,
+      });
+    }
+
+    let last_date = self.events.last().map_or(created, |event| *event.date());
+    history.extend(self.events);
+
+    // ensure the status of the task is the one from the headline
+    let current_status = history.iter().rev().find_map(|event| match event {
+      Event::StatusChanged { status, .. } => Some(*status),
+      _ => None,
+    });
+
+    if let Some(status) = self.status {
+      if current_status != Some(status) {
+        history.push(Event::StatusChanged {
+          event_date: last_date,
+          status,
+        });
+      }
+    }
+
+    Task::from_history(self.name, history)
+  }
+}
+
+/// Page captured with an `org-protocol://capture` URL.
+#[derive(Clone, Debug, Default, Eq, PartialEq)]
+pub struct Capture {
+  /// URL of the captured page.
+  pub url: Option<String>,
+  /// Title of the captured page.
+  pub title: Option<String>,
+  /// Text selected in the page.
+  pub body: Option<String>,
+}
+
+impl Capture {
+  /// Parse an `org-protocol://capture` URL.
+  ///
+  /// Both the key-value syntax (`capture?template=t&url=…&title=…&body=…`) and the legacy one
+  /// (`capture:/t/url/title/body`) are supported; the template is ignored. Return [`None`] if the URL is not a capture
+  /// one.
+  pub fn parse(url: &str) -> Option<Self> {
+    let url = url.strip_prefix("org-protocol:")?.trim_start_matches('/');
+    let args = url.strip_prefix("capture")?;
+    let decode = |s: &str| Some(percent_decode(s)).filter(|s| !s.is_empty());
+
+    if let Some(query) = args.strip_prefix('?') {
+      let mut capture = Capture::default();
+
+      for (key, value) in query.split('&').filter_map(|kv| kv.split_once('=')) {
+        match key {
+          "url" => capture.url = decode(value),
+          "title" => capture.title = decode(value),
+          "body" => capture.body = decode(value),
+          _ => (),
+        }
+      }
+
+      return Some(capture);
+    }
+
+    let parts = args
+      .strip_prefix(':')?
+      .trim_start_matches('/')
+      .split('/')
+      .collect::<Vec<_>>();
+
+    // the template is optional in the oldest form of the legacy syntax
+    let parts = match parts.len() {
+      3 => &parts[..],
+      4 => &parts[1..],
+      _ => return None,
+    };
+
+    Some(Capture {
+      url: decode(parts[0]),
+      title: decode(parts[1]),
+      body: decode(parts[2]),
+    })
+  }
+}
+
+/// Decode a percent-encoded string; invalid escapes are left as-is.
+fn percent_decode(s: &str) -> String {
+  let bytes = s.as_bytes();
+  let mut decoded = Vec::with_capacity(bytes.len());
+  let mut i = 0;
+
+  while i < bytes.len() {
+    let escaped = bytes
+      .get(i + 1..i + 3)
+      .filter(|_| bytes[i] == b'%')
+      .and_then(|hex| std::str::from_utf8(hex).ok())
+      .and_then(|hex| u8::from_str_radix(hex, 16).ok());
+
+    match escaped {
+      Some(byte) => {
+        decoded.push(byte);
+        i += 3;
+      }
+
+      None => {
+        decoded.push(bytes[i]);
+        i += 1;
+      }
+    }
+  }
+
+  String::from_utf8_lossy(&decoded).into_owned()
+}
+
+#[cfg(test)]
+mod unit_tests {
+  use super::*;
+  use chrono::Duration;
+
+  const ORG: &str = r#"#+CATEGORY: home
+
+* Not a task
+* TODO [#A] Pay the rent :money:admin:
+  SCHEDULED: <2021-06-10 Thu> DEADLINE: <2021-06-12 Sat 10:00 -1d>
+* DONE Write the report :work:
+  CLOSED: [2021-06-03 Thu 12:00]
+  :PROPERTIES:
+  :CATEGORY: work
+  :CREATED: [2021-06-01 Tue 09:00]
+  :ID: 5c1b2f0e-report
+  :END:
+  :LOGBOOK:
+  - State "DONE"       from "TODO"       [2021-06-03 Thu 12:00]
+  - Note taken on [2021-06-02 Wed 10:00] \\
+    First draft is ready.
+  CLOCK: [2021-06-02 Wed 09:00]--[2021-06-02 Wed 11:30] =>  2:30
+  :END:
+  Some more context.
+"#;
+
+  fn local(s: &str) -> DateTime<Utc> {
+    parse_timestamp(s).unwrap()
+  }
+
+  #[test]
+  fn import_headlines_and_planning() {
+    let tasks = import(ORG, &Priorities::default());
+    assert_eq!(tasks.len(), 2);
+
+    let rent = &tasks[0];
+    assert_eq!(rent.name(), "Pay the rent");
+    assert_eq!(rent.status(), Status::Todo);
+    assert_eq!(rent.priority(), Some(&Priority::new("Critical")));
+    assert_eq!(rent.project(), Some("home"));
+    assert_eq!(rent.tags().collect::<Vec<_>>(), vec!["money", "admin"]);
+    assert_eq!(rent.scheduled(), Some(local("2021-06-10")));
+    assert_eq!(rent.deadline(), Some(local("2021-06-12 Sat 10:00")));
+    assert_eq!(rent.external_id(), Some("org:home/Pay the rent"));
+    assert_eq!(tasks[1].external_id(), Some("5c1b2f0e-report"));
+  }
+
+  #[test]
+  fn import_logbook() {
+    let tasks = import(ORG, &Priorities::default());
+    let report = &tasks[1];
+
+    assert_eq!(report.name(), "Write the report");
+    assert_eq!(report.status(), Status::Done);
+    assert_eq!(report.project(), Some("work"));
+    assert_eq!(report.creation_date(), Some(&local("2021-06-01 Tue 09:00")));
+    assert_eq!(
+      report.spent_time(),
+      Duration::hours(2) + Duration::minutes(30)
+    );
+
+    let notes = report.notes();
+    assert_eq!(notes.len(), 2);
+    assert_eq!(notes[0].content, "Some more context.");
+    assert_eq!(notes[1].content, "First draft is ready.");
+    assert_eq!(notes[1].creation_date, local("2021-06-02 Wed 10:00"));
+  }
+
+  #[test]
+  fn import_emphasised_body_lines() {
+    let org = "* TODO Pack\n*bold* reminder\n**important** line\nlast words\n* TODO Leave\n";
+    let tasks = import(org, &Priorities::default());
+    assert_eq!(tasks.len(), 2);
+
+    let notes = tasks[0].notes();
+    assert_eq!(notes.len(), 1);
+    assert_eq!(
+      notes[0].content,
+      "*bold* reminder\n**important** line\nlast words"
+    );
+    assert_eq!(tasks[1].name(), "Leave");
+  }
+
+  #[test]
+  fn export_import_round_trip() {
+    let tasks = import(ORG, &Priorities::default());
+    let mut output = Vec::new();
+    export(
+      &mut output,
+      tasks
+        .iter()
+        .enumerate()
+        .map(|(uid, task)| (format!("{}", uid).parse().unwrap(), task)),
+      &Priorities::default(),
+    )
+    .unwrap();
+
+    let reimported = import(&String::from_utf8(output).unwrap(), &Priorities::default());
+    assert_eq!(reimported.len(), tasks.len());
+
+    for (a, b) in tasks.iter().zip(&reimported) {
+      assert_eq!(a.name(), b.name());
+      assert_eq!(a.status(), b.status());
+      assert_eq!(a.priority(), b.priority());
+      assert_eq!(a.project(), b.project());
+      assert_eq!(a.tags().collect::<Vec<_>>(), b.tags().collect::<Vec<_>>());
+      assert_eq!(a.scheduled(), b.scheduled());
+      assert_eq!(a.deadline(), b.deadline());
+      assert_eq!(a.external_id(), b.external_id());
+      assert_eq!(a.spent_time(), b.spent_time());
+      assert_eq!(
+        a.notes().iter().map(|n| &n.content).collect::<Vec<_>>(),
+        b.notes().iter().map(|n| &n.content).collect::<Vec<_>>()
+      );
+    }
+  }
+
+  #[test]
+  fn priority_cookies() {
+    let priorities = Priorities::default();
+
+    assert_eq!(
+      priority_cookie(&Priority::new("Critical"), &priorities),
+      Some('A')
+    );
+    assert_eq!(
+      priority_cookie(&Priority::new("Low"), &priorities),
+      Some('D')
+    );
+    assert_eq!(
+      priority_cookie(&Priority::new("Unknown"), &priorities),
+      None
+    );
+
+    assert_eq!(
+      priority_from_cookie("[#B]", &priorities),
+      Some(Priority::new("High"))
+    );
+    assert_eq!(priority_from_cookie("[#E]", &priorities), None);
+    assert_eq!(priority_from_cookie("[#a]", &priorities), None);
+  }
+
+  #[test]
+  fn capture() {
+    assert_eq!(
+      Capture::parse(
+        "org-protocol://capture?template=t&url=https%3A%2F%2Fexample.org%2F%3Fa%3D1&title=Issue%20%23123&body="
+      ),
+      Some(Capture {
+        url: Some("https://example.org/?a=1".to_owned()),
+        title: Some("Issue #123".to_owned()),
+        body: None,
+      })
+    );
+
+    assert_eq!(
+      Capture::parse("org-protocol:/capture:/t/https%3A%2F%2Fexample.org/Caf%C3%A9/Some%20text%ZZ"),
+      Some(Capture {
+        url: Some("https://example.org".to_owned()),
+        title: Some("Café".to_owned()),
+        body: Some("Some text%ZZ".to_owned()),
+      })
+    );
+
+    assert_eq!(
+      Capture::parse("org-protocol://store-link?url=https%3A%2F%2Fexample.org"),
+      None
+    );
+    assert_eq!(Capture::parse("https://example.org"), None);
+  }
+}
@@ -0,0 +1,173 @@
+//! Policies the tasks must comply with.
+//!
+//! Policies are set in the `[[policies]]` sections of the configuration, and checked when tasks are added or edited.
+//! Each of them requires or forbids some metadata, either on every task or on the tasks having some metadata, such as
+//! “a priority is required for #work tasks”.
+
+use crate::{
+  config::{Policy, PolicyMetadata},
+  metadata::{Fields, Metadata, Priorities},
+  task::Task,
+  tr,
+};
+use std::{error::Error, fmt};
+
+/// Violation of a policy by a task.
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct PolicyViolation {
+  /// Metadata of the tasks the policy applies to, as written in the configuration; empty for all the tasks.
+  pub when: String,
+  /// Metadata the task has but must not have, or doesn’t have but must have.
+  pub metadata: PolicyMetadata,
+  /// Whether the metadata is required; it is forbidden otherwise.
+  pub required: bool,
+  /// Explanation of the policy, if any.
+  pub message: Option<String>,
+}
+
+impl fmt::Display for PolicyViolation {
+  fn fmt(&self, f: &mut fmt::Formatter) -> Result<(), fmt::Error> {
+    let metadata = metadata_name(self.metadata);
+    let when = self.when.trim();
+
+    let rule = match (when.is_empty(), self.required) {
+      (true, true) => tr!("every task must have {}", metadata),
+      (true, false) => tr!("no task may have {}", metadata),
+      (false, true) => tr!("tasks with {} must have {}", when, metadata),
+      (false, false) => tr!("tasks with {} must not have {}", when, metadata),
+    };
+
+    match self.message {
+      Some(ref message) => write!(f, "{} ({})", rule, message),
+      None => f.write_str(&rule),
+    }
+  }
+}
+
+impl Error for PolicyViolation {}
+
+/// Name of a kind of metadata, along with its article, as in “must have a project”.
+fn metadata_name(metadata: PolicyMetadata) -> &'static str {
+  match metadata {
+    PolicyMetadata::Project => tr!("a project"),
+    PolicyMetadata::Priority => tr!("a priority"),
+    PolicyMetadata::Tag => tr!("a tag"),
+    PolicyMetadata::Context => tr!("a context"),
+    PolicyMetadata::Milestone => tr!("a milestone"),
+    PolicyMetadata::Effort => tr!("an effort estimate"),
+    PolicyMetadata::Deadline => tr!("a deadline"),
+  }
+}
+
+/// Check whether `task` has some kind of metadata.
+fn has(task: &Task, metadata: PolicyMetadata) -> bool {
+  match metadata {
+    PolicyMetadata::Project => task.project().is_some(),
+    PolicyMetadata::Priority => task.priority().is_some(),
+    PolicyMetadata::Tag => task.tags().next().is_some(),
+    PolicyMetadata::Context => task.context().is_some(),
+    PolicyMetadata::Milestone => task.milestone().is_some(),
+    PolicyMetadata::Effort => task.effort().is_some(),
+    PolicyMetadata::Deadline => task.deadline().is_some(),
+  }
+}
+
+/// Check that `task` complies with `policies`, returning the first violation otherwise.
+///
+/// The metadata the policies apply to are looked up in `priorities` and `fields`, and matched ignoring case.
+pub fn check(
+  policies: &[Policy],
+  priorities: &Priorities,
+  fields: &Fields,
+  task: &Task,
+) -> Result<(), PolicyViolation> {
+  for policy in policies {
+    let (when, _) = Metadata::from_words(priorities, fields, policy.when.split(' '));
+
+    if !task.check_metadata(&when, true) {
+      continue;
+    }
+
+    let required = policy
+      .require
+      .iter()
+      .map(|&metadata| (metadata, true))
+      .find(|&(metadata, _)| !has(task, metadata));
+    let forbidden = policy
+      .forbid
+      .iter()
+      .map(|&metadata| (metadata, false))
+      .find(|&(metadata, _)| has(task, metadata));
+
+    if let Some((metadata, required)) = required.or(forbidden) {
+      return Err(PolicyViolation {
+        when: policy.when.clone(),
+        metadata,
+        required,
+        message: policy.message.clone(),
+      });
+    }
+  }
+
+  Ok(())
+}
+
+#[cfg(test)]
+mod unit_tests {
+  use super::*;
+  use crate::metadata::Priority;
+
+  #[test]
+  fn policies() {
+    let policies = vec![
+      Policy {
+        require: vec![PolicyMetadata::Project],
+        ..Policy::default()
+      },
+      Policy {
+        when: "#work".to_owned(),
+        require: vec![PolicyMetadata::Priority],
+        forbid: vec![PolicyMetadata::Context],
+        message: Some("work is prioritized in the weekly meeting".to_owned()),
+      },
+    ];
+    let check = |metadata| {
+      let mut task = Task::new("Fix the bug");
+      task.apply_metadata(metadata);
+      check(&policies, &Priorities::default(), &Fields::default(), &task)
+    };
+
+    assert_eq!(
+      check(vec![]).unwrap_err().to_string(),
+      "every task must have a project"
+    );
+    assert!(check(vec![Metadata::project("toodoux")]).is_ok());
+    assert_eq!(
+      check(vec![Metadata::project("toodoux"), Metadata::tag("Work")])
+        .unwrap_err()
+        .to_string(),
+      "tasks with #work must have a priority (work is prioritized in the weekly meeting)"
+    );
+    assert!(check(vec![
+      Metadata::project("toodoux"),
+      Metadata::tag("work"),
+      Metadata::priority(Priority::new("High")),
+    ])
+    .is_ok());
+    assert_eq!(
+      check(vec![
+        Metadata::project("toodoux"),
+        Metadata::tag("work"),
+        Metadata::priority(Priority::new("High")),
+        Metadata::context("home"),
+      ])
+      .unwrap_err(),
+      PolicyViolation {
+        when: "#work".to_owned(),
+        metadata: PolicyMetadata::Context,
+        required: false,
+        message: Some("work is prioritized in the weekly meeting".to_owned()),
+      }
+    );
+  }
+}
@@ -0,0 +1,89 @@
+//! Private tasks.
+//!
+//! Tasks tagged `#private` — or with any of the tags set in the `[privacy]` configuration — never leave the machine:
+//! they are left out of the exports, digests and published sites. Rather than each of them checking the tags, they all
+//! go through [`redact`], which splits the tasks into the public and the private ones. Depending on the configured
+//! [`Redaction`], reports then mention how many private tasks were left out.
+
+use crate::{
+  config::{PrivacyConfig, Redaction},
+  task::{Task, UID},
+};
+use unicase::UniCase;
+
+/// Tasks split into public and private ones.
+#[derive(Clone, Debug, Default)]
+pub struct Redacted<'a> {
+  /// Public tasks, in their original order.
+  pub tasks: Vec<(UID, &'a Task)>,
+  /// Private tasks, left out, in their original order.
+  pub private: Vec<(UID, &'a Task)>,
+}
+
+/// Check whether a task is private.
+pub fn is_private(config: &PrivacyConfig, task: &Task) -> bool {
+  task.tags().any(|tag| {
+    config
+      .tags
+      .iter()
+      .any(|private| UniCase::new(private.as_str()) == UniCase::new(tag))
+  })
+}
+
+/// Split `tasks` into the public and the private ones.
+pub fn redact<'a>(
+  config: &PrivacyConfig,
+  tasks: impl IntoIterator<Item = (UID, &'a Task)>,
+) -> Redacted<'a> {
+  let (private, tasks) = tasks
+    .into_iter()
+    .partition(|&(_, task)| is_private(config, task));
+
+  Redacted { tasks, private }
+}
+
+/// Number of private tasks left out to mention in a report: `private` if the redaction counts them, zero otherwise.
+pub fn counted(config: &PrivacyConfig, private: usize) -> usize {
+  match config.redaction {
+    Redaction::Omit => 0,
+    Redaction::Count => private,
+  }
+}
+
+/// Line of a report mentioning the private tasks left out.
+pub fn notice(private: usize) -> String {
+  match private {
+    1 => "1 private task not shown.".to_owned(),
+    _ => format!("{} private tasks not shown.", private),
+  }
+}
+
+#[cfg(test)]
+mod unit_tests {
+  use super::*;
+  use crate::metadata::Metadata;
+
+  #[test]
+  fn redaction() {
+    let mut config = PrivacyConfig::default();
+    let mut diary = Task::new("Write the diary");
+    diary.apply_metadata(vec![Metadata::tag("Private")]);
+    let mut gift = Task::new("Buy a gift");
+    gift.apply_metadata(vec![Metadata::tag("surprise")]);
+
+    let uid = |s: &str| s.parse::<UID>().unwrap();
+    let tasks = vec![(uid("0"), &diary), (uid("1"), &gift)];
+
+    let redacted = redact(&config, tasks.clone());
+    assert_eq!(redacted.tasks.len(), 1);
+    assert_eq!(redacted.private.len(), 1);
+    assert_eq!(redacted.private[0].0, uid("0"));
+    assert_eq!(counted(&config, 1), 1);
+    assert_eq!(notice(2), "2 private tasks not shown.");
+
+    config.tags.push("surprise".to_owned());
+    config.redaction = Redaction::Omit;
+    assert!(redact(&config, tasks).tasks.is_empty());
+    assert_eq!(counted(&config, 2), 0);
+  }
+}
@@ -0,0 +1,410 @@
+//! Read-only static site publishing the backlog.
+//!
+//! `td publish` writes an index page, listing the projects along with the tasks that have none, and a page per
+//! project. Private tasks and the tasks matching the `exclude` list of the `[publish]` configuration are left out, and
+//! so are the closed tasks unless asked for. The site is self-contained — a few HTML files with inline styles
+//! — so that it can be served by any web server or shared as-is.
+
+use crate::{
+  config::{Config, PublishField},
+  digest::escape_html,
+  error::{Error, Operation},
+  metadata::Metadata,
+  privacy,
+  task::{Status, Task, UID},
+  time,
+};
+use chrono::{DateTime, Local, Utc};
+use itertools::Itertools as _;
+use std::{
+  collections::{BTreeMap, HashSet},
+  fmt::Write as _,
+  fs,
+  path::Path,
+};
+
+const STYLE: &str = "body { font-family: sans-serif; max-width: 60em; margin: 2em auto; padding: 0 1em; }
+table { border-collapse: collapse; width: 100%; }
+th, td { text-align: left; padding: 0.3em 0.6em; border-bottom: 1px solid #ddd; vertical-align: top; }
+footer { margin-top: 2em; color: #888; font-size: small; }";
+
+/// Static site built from the published tasks.
+#[derive(Debug)]
+pub struct Site<'a> {
+  config: &'a Config,
+  /// Published tasks without project, by UID.
+  unassigned: Vec<(UID, &'a Task)>,
+  /// Pages of the projects having published tasks, by project.
+  projects: BTreeMap<&'a str, ProjectPage<'a>>,
+  /// Number of private tasks left out, if they are counted.
+  private: usize,
+}
+
+/// Page of a project.
+#[derive(Debug)]
+struct ProjectPage<'a> {
+  file_name: String,
+  /// Published tasks of the project.
+  tasks: Vec<(UID, &'a Task)>,
+}
+
+impl<'a> Site<'a> {
+  /// Build the site of `tasks`, selected according to the `[publish]` section of `config`.
+  pub fn new(config: &'a Config, tasks: impl IntoIterator<Item = (UID, &'a Task)>) -> Self {
+    let publish = &config.publish;
+    let exclude = publish
+      .exclude
+      .iter()
+      .map(|exclude| {
+        Metadata::from_words(config.priorities(), config.fields(), exclude.split(' ')).0
+      })
+      .filter(|metadata| !metadata.is_empty())
+      .collect::<Vec<_>>();
+
+    let tasks = tasks.into_iter().filter(|(_, task)| {
+      (publish.include_closed || task.status().is_active())
+        && !exclude
+          .iter()
+          .any(|metadata| task.check_metadata(metadata, true))
+    });
+    let redacted = privacy::redact(&config.privacy, tasks);
+
+    let mut unassigned = Vec::new();
+    let mut by_project = BTreeMap::<_, Vec<_>>::new();
+
+    for (uid, task) in redacted.tasks {
+      match task.project() {
+        Some(project) => by_project.entry(project).or_default().push((uid, task)),
+        None => unassigned.push((uid, task)),
+      }
+    }
+
+    sort_tasks(&mut unassigned);
+
+    // several projects may share a slug; the next ones are numbered so that each gets its own page
+    let mut file_names = HashSet::new();
+    let projects = by_project
+      .into_iter()
+      .map(|(project, mut tasks)| {
+        let slug = slug(project);
+        let file_name = (1..)
+          .map(|n| match n {
+            1 => format!("project-{}.html", slug),
+            _ => format!("project-{}-{}.html", slug, n),
+          })
+          .find(|file_name| file_names.insert(file_name.clone()))
+          .unwrap_or_default();
+
+        sort_tasks(&mut tasks);
+        (project, ProjectPage { file_name, tasks })
+      })
+      .collect();
+
+    Site {
+      config,
+      unassigned,
+      projects,
+      private: privacy::counted(&config.privacy, redacted.private.len()),
+    }
+  }
+
+  /// Number of published tasks.
+  pub fn task_count(&self) -> usize {
+    self.unassigned.len()
+      + self
+        .projects
+        .values()
+        .map(|page| page.tasks.len())
+        .sum::<usize>()
+  }
+
+  /// Pages of the site, as file names along with their HTML content; the index comes first.
+  pub fn pages(&self) -> Vec<(String, String)> {
+    let generated = Utc::now();
+    let mut pages = vec![("index.html".to_owned(), self.index(&generated))];
+
+    for (project, ProjectPage { file_name, tasks }) in &self.projects {
+      let mut body = String::new();
+      let _ = writeln!(body, "<p><a href=\"index.html\">← Backlog</a></p>");
+      let _ = writeln!(body, "<h1>@{}</h1>", escape_html(project));
+      self.write_tasks(&mut body, tasks);
+
+      pages.push((
+        file_name.clone(),
+        page(&format!("@{}", project), &body, &generated),
+      ));
+    }
+
+    pages
+  }
+
+  /// Write the pages of the site into `dir`, creating it if needed.
+  ///
+  /// Pages of projects that are not published anymore are left as-is, but nothing links to them.
+  pub fn write(&self, dir: &Path) -> Result<(), Error> {
+    fs::create_dir_all(dir).map_err(Error::io(Operation::Write, dir))?;
+
+    for (file_name, content) in self.pages() {
+      let path = dir.join(file_name);
+      fs::write(&path, content).map_err(Error::io(Operation::Write, path))?;
+    }
+
+    Ok(())
+  }
+
+  fn index(&self, generated: &DateTime<Utc>) -> String {
+    let mut body = String::new();
+    let _ = writeln!(body, "<h1>Backlog</h1>");
+
+    if !self.projects.is_empty() {
+      let _ = writeln!(body, "<h2>Projects</h2>");
+      let _ = writeln!(body, "<ul>");
+
+      for (project, ProjectPage { file_name, tasks }) in &self.projects {
+        let _ = writeln!(
+          body,
+          "<li><a href=\"{}\">@{}</a> ({})</li>",
+          escape_html(file_name),
+          escape_html(project),
+          tasks.len()
+        );
+      }
+
+      let _ = writeln!(body, "</ul>");
+    }
+
+    if !self.unassigned.is_empty() || self.projects.is_empty() {
+      let _ = writeln!(body, "<h2>Without project</h2>");
+      self.write_tasks(&mut body, &self.unassigned);
+    }
+
+    if self.private > 0 {
+      let _ = writeln!(body, "<p><em>{}</em></p>", privacy::notice(self.private));
+    }
+
+    page("Backlog", &body, generated)
+  }
+
+  /// Write a table of tasks, with the configured columns.
+  fn write_tasks(&self, out: &mut String, tasks: &[(UID, &Task)]) {
+    if tasks.is_empty() {
+      let _ = writeln!(out, "<p>Nothing.</p>");
+      return;
+    }
+
+    let columns = self.columns();
+    let _ = writeln!(out, "<table>");
+    let _ = write!(out, "<tr>");
+
+    for &column in &columns {
+      let _ = write!(out, "<th>{}</th>", column.map_or("Name", field_title));
+    }
+
+    let _ = writeln!(out, "</tr>");
+
+    for &(uid, task) in tasks {
+      let _ = write!(out, "<tr>");
+
+      for &column in &columns {
+        let cell = match column {
+          Some(field) => self.cell(field, uid, task),
+          None => escape_html(task.name()),
+        };
+        let _ = write!(out, "<td>{}</td>", cell);
+      }
+
+      let _ = writeln!(out, "</tr>");
+    }
+
+    let _ = writeln!(out, "</table>");
+  }
+
+  /// Columns of the task tables; [`None`] stands for the name, which comes right after the UID, or first without it.
+  fn columns(&self) -> Vec<Option<PublishField>> {
+    let fields = &self.config.publish.fields;
+    let name = fields
+      .iter()
+      .position(|&field| field == PublishField::Uid)
+      .map_or(0, |i| i + 1);
+    let mut columns = fields.iter().copied().map(Some).collect::<Vec<_>>();
+    columns.insert(name, None);
+    columns
+  }
+
+  /// Content of a cell, escaped; empty if the task doesn’t have the field.
+  fn cell(&self, field: PublishField, uid: UID, task: &Task) -> String {
+    let config = self.config;
+    let value = match field {
+      PublishField::Uid => Some(uid.to_string()),
+      PublishField::Status => Some(status_alias(config, task.status()).to_owned()),
+      PublishField::Priority => task.priority().map(|prio| config.priorities().label(prio)),
+      PublishField::Tags => Some(task.tags().map(|tag| format!("#{}", tag)).join(" ")),
+      PublishField::Context => task.context().map(str::to_owned),
+      PublishField::Milestone => task.milestone().map(str::to_owned),
+      PublishField::Deadline => task.deadline().map(|deadline| {
+        deadline
+          .with_timezone(&Local)
+          .format("%Y-%m-%d")
+          .to_string()
+      }),
+      PublishField::Effort => task.effort().map(time::format_duration),
+
+      PublishField::Notes => {
+        let notes = task.notes();
+        return notes
+          .iter()
+          .map(|note| {
+            format!(
+              "<p>{}</p>",
+              escape_html(note.content.trim()).replace('\n', "<br>")
+            )
+          })
+          .join("");
+      }
+    };
+
+    value.map(|value| escape_html(&value)).unwrap_or_default()
+  }
+}
+
+/// Wrap the body of a page into a complete HTML document.
+fn page(title: &str, body: &str, generated: &DateTime<Utc>) -> String {
+  format!(
+    "<!DOCTYPE html>
+<html>
+<head>
+<meta charset=\"utf-8\">
+<title>{}</title>
+<style>
+{}
+</style>
+</head>
+<body>
+{}<footer>Generated by toodoux on {}.</footer>
+</body>
+</html>
+",
+    escape_html(title),
+    STYLE,
+    body,
+    generated.with_timezone(&Local).format("%Y-%m-%d %H:%M")
+  )
+}
+
+/// Sort tasks by status — in progress first, closed last — and then by UID.
+fn sort_tasks(tasks: &mut [(UID, &Task)]) {
+  tasks.sort_by_key(|&(uid, task)| {
+    let rank = match task.status() {
+      Status::Ongoing => 0,
+      Status::Paused => 1,
+      Status::Todo => 2,
+      Status::Done => 3,
+      Status::Cancelled => 4,
+    };
+
+    (rank, uid)
+  });
+}
+
+/// File-name-friendly version of a project name.
+fn slug(project: &str) -> String {
+  project
+    .chars()
+    .map(|c| match c {
+      c if c.is_alphanumeric() => c.to_lowercase().next().unwrap_or(c),
+      '.' | '-' | '_' => c,
+      _ => '_',
+    })
+    .collect()
+}
+
+fn field_title(field: PublishField) -> &'static str {
+  match field {
+    PublishField::Uid => "UID",
+    PublishField::Status => "Status",
+    PublishField::Priority => "Priority",
+    PublishField::Tags => "Tags",
+    PublishField::Context => "Context",
+    PublishField::Milestone => "Milestone",
+    PublishField::Deadline => "Deadline",
+    PublishField::Effort => "Effort",
+    PublishField::Notes => "Notes",
+  }
+}
+
+fn status_alias(config: &Config, status: Status) -> &str {
+  match status {
+    Status::Todo => config.todo_alias(),
+    Status::Ongoing => config.wip_alias(),
+    Status::Done => config.done_alias(),
+    Status::Cancelled => config.cancelled_alias(),
+    Status::Paused => config.paused_alias(),
+  }
+}
+
+#[cfg(test)]
+mod unit_tests {
+  use super::*;
+
+  #[test]
+  fn site() {
+    let mut config = Config::default();
+    let mut fix = Task::new("Fix <script> injection");
+    fix.apply_metadata(vec![Metadata::project("Web site"), Metadata::tag("bug")]);
+    let mut diary = Task::new("Write the diary");
+    diary.apply_metadata(vec![Metadata::project("home"), Metadata::tag("Private")]);
+    let mut done = Task::new("Release v1");
+    done.apply_metadata(vec![Metadata::project("web site")]);
+    done.change_status(Status::Done);
+    let inbox = Task::new("Call the bank");
+
+    let uid = |s: &str| s.parse::<UID>().unwrap();
+    let tasks = vec![
+      (uid("0"), &fix),
+      (uid("1"), &diary),
+      (uid("2"), &done),
+      (uid("3"), &inbox),
+    ];
+
+    let site = Site::new(&config, tasks.clone());
+    assert_eq!(site.task_count(), 2);
+
+    let pages = site.pages();
+    let names = pages
+      .iter()
+      .map(|(name, _)| name.as_str())
+      .collect::<Vec<_>>();
+    assert_eq!(names, vec!["index.html", "project-web_site.html"]);
+
+    let index = &pages[0].1;
+    assert!(index.contains("<a href=\"project-web_site.html\">@Web site</a> (1)"));
+    assert!(index.contains(
+      "<tr><td>3</td><td>Call the bank</td><td>TODO</td><td></td><td></td><td></td></tr>"
+    ));
+    assert!(!index.contains("diary"));
+    assert!(index.contains("<p><em>1 private task not shown.</em></p>"));
+    assert!(pages[1].1.contains("<td>Fix &lt;script&gt; injection</td>"));
+
+    // closed tasks, with projects sharing a slug, and custom columns
+    config.publish.include_closed = true;
+    config.publish.fields = vec![PublishField::Tags, PublishField::Uid];
+    let site = Site::new(&config, tasks);
+    let pages = site.pages();
+    let names = pages
+      .iter()
+      .map(|(name, _)| name.as_str())
+      .collect::<Vec<_>>();
+    assert_eq!(
+      names,
+      vec![
+        "index.html",
+        "project-web_site.html",
+        "project-web_site-2.html"
+      ]
+    );
+    assert!(pages[1]
+      .1
+      .contains("<tr><th>Tags</th><th>UID</th><th>Name</th></tr>"));
+    assert_eq!(slug("Web site/Blog"), "web_site_blog");
+  }
+}
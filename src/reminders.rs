@@ -0,0 +1,192 @@
+//! Apple Reminders export.
+//!
+//! Tasks are exported as an AppleScript that adds them to the _toodoux_ list of Reminders, so that their deadlines
+//! trigger notifications on every device synchronized with it. The script can be run with `osascript`, or from a
+//! _Run AppleScript_ action in macOS Shortcuts.
+//!
+//! Only the active tasks with a deadline are exported. Each reminder carries the UID of its task in its notes, so that
+//! running a newer script updates the reminders instead of duplicating them.
+
+use crate::{
+  error::Error,
+  metadata::{Priorities, Priority},
+  task::{Task, UID},
+};
+use chrono::{DateTime, Datelike as _, Local, Timelike as _, Utc};
+use std::io::{self, Write};
+
+/// Name of the Reminders list the tasks are added to.
+const LIST: &str = "toodoux";
+
+/// Export tasks as an AppleScript adding them to Reminders.
+///
+/// Priorities are mapped according to `priorities`: the highest level is a high priority, the lowest one a low
+/// priority and the other ones a medium priority.
+pub fn export<'a>(
+  mut out: impl Write,
+  tasks: impl IntoIterator<Item = (UID, &'a Task)>,
+  priorities: &Priorities,
+) -> Result<(), Error> {
+  write_header(&mut out).map_err(Error::CannotExport)?;
+
+  for (uid, task) in tasks {
+    if let (true, Some(deadline)) = (task.status().is_active(), task.deadline()) {
+      write_task(&mut out, uid, task, deadline, priorities).map_err(Error::CannotExport)?;
+    }
+  }
+
+  writeln!(out, "end tell").map_err(Error::CannotExport)?;
+  out.flush().map_err(Error::CannotExport)
+}
+
+fn write_header(out: &mut impl Write) -> io::Result<()> {
+  writeln!(out, "-- Generated by toodoux; run with osascript.")?;
+  writeln!(out)?;
+
+  // date literals depend on the locale, so dates are built from their components
+  writeln!(out, "on makeDate(y, m, d, h, mi)")?;
+  writeln!(out, "  set theDate to current date")?;
+  writeln!(out, "  set day of theDate to 1")?;
+  writeln!(out, "  set year of theDate to y")?;
+  writeln!(out, "  set month of theDate to m")?;
+  writeln!(out, "  set day of theDate to d")?;
+  writeln!(out, "  set time of theDate to h * hours + mi * minutes")?;
+  writeln!(out, "  return theDate")?;
+  writeln!(out, "end makeDate")?;
+  writeln!(out)?;
+  writeln!(out, "tell application \"Reminders\"")?;
+  writeln!(out, "  if not (exists list {}) then", quote(LIST))?;
+  writeln!(
+    out,
+    "    make new list with properties {{name:{}}}",
+    quote(LIST)
+  )?;
+  writeln!(out, "  end if")?;
+  writeln!(out, "  set theList to list {}", quote(LIST))
+}
+
+fn write_task(
+  out: &mut impl Write,
+  uid: UID,
+  task: &Task,
+  deadline: DateTime<Utc>,
+  priorities: &Priorities,
+) -> io::Result<()> {
+  let marker = format!("[toodoux {}]", uid);
+  let deadline = deadline.with_timezone(&Local);
+  let body = task
+    .project()
+    .map(|project| format!("@{}", project))
+    .into_iter()
+    .chain(task.tags().map(|tag| format!("#{}", tag)))
+    .chain(Some(marker.clone()))
+    .collect::<Vec<_>>()
+    .join(" ");
+
+  writeln!(out)?;
+  writeln!(
+    out,
+    "  set matches to (reminders of theList whose body contains {})",
+    quote(&marker)
+  )?;
+  writeln!(out, "  if matches is {{}} then")?;
+  writeln!(
+    out,
+    "    set theReminder to make new reminder at end of theList"
+  )?;
+  writeln!(out, "  else")?;
+  writeln!(out, "    set theReminder to item 1 of matches")?;
+  writeln!(out, "  end if")?;
+  writeln!(out, "  tell theReminder")?;
+  writeln!(out, "    set name to {}", quote(task.name()))?;
+  writeln!(out, "    set body to {}", quote(&body))?;
+  writeln!(
+    out,
+    "    set due date to my makeDate({}, {}, {}, {}, {})",
+    deadline.year(),
+    deadline.month(),
+    deadline.day(),
+    deadline.hour(),
+    deadline.minute()
+  )?;
+  writeln!(
+    out,
+    "    set priority to {}",
+    reminder_priority(task.priority(), priorities)
+  )?;
+  writeln!(out, "  end tell")
+}
+
+/// Priority of a reminder: 1 is high, 5 medium, 9 low and 0 none.
+fn reminder_priority(priority: Option<&Priority>, priorities: &Priorities) -> u8 {
+  let levels = priorities.levels().len();
+
+  match priority.and_then(|priority| priorities.rank(priority)) {
+    None => 0,
+    Some(rank) if rank + 1 == levels => 1,
+    Some(0) => 9,
+    Some(_) => 5,
+  }
+}
+
+/// Quote a string as an AppleScript literal.
+fn quote(s: &str) -> String {
+  let escaped = s
+    .replace('\\', "\\\\")
+    .replace('"', "\\\"")
+    .replace('\n', "\\n");
+  format!("\"{}\"", escaped)
+}
+
+#[cfg(test)]
+mod unit_tests {
+  use super::*;
+  use crate::{metadata::Metadata, nlp, task::Status};
+  use chrono::NaiveDate;
+
+  #[test]
+  fn export_reminders() {
+    let priorities = Priorities::default();
+    let mut rent = Task::new("Pay the \"rent\"");
+    rent.apply_metadata(vec![
+      Metadata::project("home"),
+      Metadata::tag("money"),
+      Metadata::priority(Priority::new("Critical")),
+    ]);
+    rent.set_deadline(nlp::start_of_day(NaiveDate::from_ymd(2021, 6, 12)));
+    let mut done = rent.clone();
+    done.change_status(Status::Done);
+    let someday = Task::new("Learn the banjo");
+
+    let uid = |s: &str| s.parse::<UID>().unwrap();
+    let mut out = Vec::new();
+    export(
+      &mut out,
+      vec![(uid("3"), &rent), (uid("4"), &done), (uid("5"), &someday)],
+      &priorities,
+    )
+    .unwrap();
+    let out = String::from_utf8(out).unwrap();
+
+    assert!(out.contains("whose body contains \"[toodoux 3]\")\n"));
+    assert!(out.contains("    set name to \"Pay the \\\"rent\\\"\"\n"));
+    assert!(out.contains("    set body to \"@home #money [toodoux 3]\"\n"));
+    assert!(out.contains("    set due date to my makeDate(2021, 6, 12, 0, 0)\n"));
+    assert!(out.contains("    set priority to 1\n"));
+    assert!(!out.contains("[toodoux 4]"));
+    assert!(!out.contains("banjo"));
+    assert!(out.ends_with("  end tell\nend tell\n"));
+  }
+
+  #[test]
+  fn priorities() {
+    let priorities = Priorities::default();
+    let prio = |name| reminder_priority(Some(&Priority::new(name)), &priorities);
+
+    assert_eq!(prio("Critical"), 1);
+    assert_eq!(prio("High"), 5);
+    assert_eq!(prio("Low"), 9);
+    assert_eq!(prio("Unknown"), 0);
+    assert_eq!(reminder_priority(None, &priorities), 0);
+  }
+}
@@ -0,0 +1,139 @@
+//! Task sharing.
+//!
+//! A task is shared as a compact payload: its name and history, as JSON, compressed with DEFLATE and encoded as
+//! URL-safe base64. The payload is wrapped in a `toodoux://share/` deep link, short enough to be pasted in a chat or
+//! rendered as a QR code, and another toodoux instance can ingest it back.
+//!
+//! Shared tasks are given an external ID derived from their creation date, so that ingesting a payload twice — or a
+//! payload of a task shared again after being updated — doesn’t duplicate the task.
+
+use crate::{error::Error, task::Task};
+use flate2::{read::DeflateDecoder, write::DeflateEncoder, Compression};
+use serde_json as json;
+use sha2::{Digest as _, Sha256};
+use std::io::Read as _;
+
+/// Prefix of the deep links wrapping payloads.
+pub const LINK_PREFIX: &str = "toodoux://share/";
+
+/// Prefix of the external IDs given to shared tasks.
+const EXTERNAL_ID_PREFIX: &str = "share:";
+
+/// Encode a task as a payload.
+pub fn encode(task: &Task) -> Result<String, Error> {
+  let mut encoder = DeflateEncoder::new(Vec::new(), Compression::best());
+  json::to_writer(&mut encoder, task).map_err(|err| Error::CannotExport(err.into()))?;
+  let compressed = encoder.finish().map_err(Error::CannotExport)?;
+
+  Ok(base64::encode_config(compressed, base64::URL_SAFE_NO_PAD))
+}
+
+/// Encode a task as a deep link.
+pub fn link(task: &Task) -> Result<String, Error> {
+  encode(task).map(|payload| format!("{}{}", LINK_PREFIX, payload))
+}
+
+/// Decode a payload, or the deep link wrapping it, back into a task.
+///
+/// The task is given an external ID if it has none yet.
+pub fn decode(payload: &str) -> Result<Task, Error> {
+  let payload = payload.trim();
+  let payload = payload.strip_prefix(LINK_PREFIX).unwrap_or(payload);
+
+  let compressed = base64::decode_config(payload, base64::URL_SAFE_NO_PAD)
+    .map_err(|err| Error::InvalidPayload(err.to_string()))?;
+  let mut serialized = Vec::new();
+  DeflateDecoder::new(compressed.as_slice())
+    .read_to_end(&mut serialized)
+    .map_err(|err| Error::InvalidPayload(err.to_string()))?;
+  let mut task: Task =
+    json::from_slice(&serialized).map_err(|err| Error::InvalidPayload(err.to_string()))?;
+
+  if task.external_id().is_none() {
+    if let Some(external_id) = external_id(&task) {
+      task.set_external_id(external_id);
+    }
+  }
+
+  Ok(task)
+}
+
+/// External ID of a shared task, which stays the same for all its payloads.
+fn external_id(task: &Task) -> Option<String> {
+  let creation_date = task.creation_date()?;
+  let hash = Sha256::digest(creation_date.to_rfc3339().as_bytes());
+  let hash = hash
+    .iter()
+    .take(8)
+    .map(|byte| format!("{:02x}", byte))
+    .collect::<String>();
+
+  Some(format!("{}{}", EXTERNAL_ID_PREFIX, hash))
+}
+
+#[cfg(test)]
+mod unit_tests {
+  use super::*;
+  use crate::{
+    metadata::{Metadata, Priority},
+    task::Status,
+  };
+
+  #[test]
+  fn round_trip() {
+    let mut task = Task::new("Review the release notes");
+    task.apply_metadata(vec![
+      Metadata::project("toodoux"),
+      Metadata::tag("release"),
+      Metadata::priority(Priority::new("High")),
+    ]);
+    task.change_status(Status::Ongoing);
+
+    let link = link(&task).unwrap();
+    assert!(link.starts_with(LINK_PREFIX));
+    assert!(link[LINK_PREFIX.len()..]
+      .chars()
+      .all(|c| c.is_ascii_alphanumeric() || c == '-' || c == '_'));
+
+    let shared = decode(&link).unwrap();
+    assert_eq!(shared.name(), task.name());
+    assert_eq!(shared.status(), Status::Ongoing);
+    assert_eq!(shared.project(), Some("toodoux"));
+    assert_eq!(shared.tags().collect::<Vec<_>>(), vec!["release"]);
+    assert_eq!(shared.creation_date(), task.creation_date());
+
+    // the external ID doesn’t depend on updates
+    let external_id = shared.external_id().unwrap();
+    assert!(external_id.starts_with(EXTERNAL_ID_PREFIX));
+    task.change_status(Status::Done);
+    let updated = decode(&encode(&task).unwrap()).unwrap();
+    assert_eq!(updated.external_id(), Some(external_id));
+    assert_eq!(updated.status(), Status::Done);
+
+    // shared tasks that already have an external ID keep it
+    task.set_external_id("PROJ-1");
+    assert_eq!(
+      decode(&encode(&task).unwrap()).unwrap().external_id(),
+      Some("PROJ-1")
+    );
+  }
+
+  #[test]
+  fn invalid_payloads() {
+    assert!(matches!(
+      decode("not a payload!"),
+      Err(Error::InvalidPayload(_))
+    ));
+    assert!(matches!(
+      decode("toodoux://share/AAAA"),
+      Err(Error::InvalidPayload(_))
+    ));
+    assert!(matches!(
+      decode(&base64::encode_config(
+        b"not deflate",
+        base64::URL_SAFE_NO_PAD
+      )),
+      Err(Error::InvalidPayload(_))
+    ));
+  }
+}
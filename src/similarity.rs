@@ -0,0 +1,137 @@
+//! Similarity between task names, used to detect duplicates, and between project and tag names, used to catch typos.
+
+/// Normalize a name for comparison.
+///
+/// Letters are lowercased, punctuation is dropped and whitespace is collapsed, so that `Pay the rent!` and
+/// `pay  the rent` are the same.
+pub fn normalize(name: &str) -> String {
+  name
+    .split(|c: char| !c.is_alphanumeric())
+    .filter(|word| !word.is_empty())
+    .map(str::to_lowercase)
+    .collect::<Vec<_>>()
+    .join(" ")
+}
+
+/// Similarity between two names, between `0` (completely different) and `1` (same normalized names).
+///
+/// It’s the Levenshtein distance between the normalized names, relative to the length of the longest one.
+pub fn similarity(a: &str, b: &str) -> f32 {
+  let a = normalize(a).chars().collect::<Vec<_>>();
+  let b = normalize(b).chars().collect::<Vec<_>>();
+  let max_len = a.len().max(b.len());
+
+  if max_len == 0 {
+    return 1.;
+  }
+
+  1. - levenshtein(&a, &b, false) as f32 / max_len as f32
+}
+
+/// Number of single character insertions, deletions and substitutions needed to go from `a` to `b`; with
+/// `transpositions`, swapping two adjacent characters counts as a single edit too.
+fn levenshtein(a: &[char], b: &[char], transpositions: bool) -> usize {
+  // the row before the previous one is only used for transpositions
+  let mut before = vec![0; b.len() + 1];
+  let mut previous = (0..=b.len()).collect::<Vec<_>>();
+  let mut current = vec![0; b.len() + 1];
+
+  for (i, ca) in a.iter().enumerate() {
+    current[0] = i + 1;
+
+    for (j, cb) in b.iter().enumerate() {
+      let substitution = previous[j] + usize::from(ca != cb);
+      let mut distance = substitution.min(previous[j + 1] + 1).min(current[j] + 1);
+
+      if transpositions && i > 0 && j > 0 && *ca == b[j - 1] && a[i - 1] == *cb {
+        distance = distance.min(before[j - 1] + 1);
+      }
+
+      current[j + 1] = distance;
+    }
+
+    std::mem::swap(&mut before, &mut previous);
+    std::mem::swap(&mut previous, &mut current);
+  }
+
+  previous[b.len()]
+}
+
+/// Find the candidate `name` is most likely a typo of, unless `name` is one of the candidates.
+///
+/// Names are compared ignoring case. A candidate is close enough if it is at most one typo — insertion, deletion or
+/// substitution of a character, or transposition of two adjacent characters — per four characters of `name` away from
+/// it; e.g. `wrok` for `work`, or `Docs` for `doc`. The closest candidate wins, then the first one alphabetically.
+pub fn close_match<'a>(
+  name: &str,
+  candidates: impl IntoIterator<Item = &'a str>,
+) -> Option<&'a str> {
+  let candidates = candidates.into_iter().collect::<Vec<_>>();
+
+  if candidates.contains(&name) {
+    return None;
+  }
+
+  let name = name.to_lowercase().chars().collect::<Vec<_>>();
+  let max_typos = (name.len() + 1) / 4;
+
+  candidates
+    .into_iter()
+    .map(|candidate| {
+      let chars = candidate.to_lowercase().chars().collect::<Vec<_>>();
+      (levenshtein(&name, &chars, true), candidate)
+    })
+    .filter(|&(typos, _)| typos <= max_typos)
+    .min()
+    .map(|(_, candidate)| candidate)
+}
+
+#[cfg(test)]
+mod unit_tests {
+  use super::*;
+
+  #[test]
+  fn normalize_names() {
+    assert_eq!(normalize("Pay the rent!"), "pay the rent");
+    assert_eq!(normalize("  pay   THE rent "), "pay the rent");
+    assert_eq!(normalize("..."), "");
+  }
+
+  #[test]
+  fn levenshtein_distance() {
+    let chars = |s: &str| s.chars().collect::<Vec<_>>();
+    assert_eq!(levenshtein(&chars("kitten"), &chars("sitting"), false), 3);
+    assert_eq!(levenshtein(&chars(""), &chars("abc"), false), 3);
+    assert_eq!(levenshtein(&chars("abc"), &chars("abc"), false), 0);
+    assert_eq!(levenshtein(&chars("wrok"), &chars("work"), false), 2);
+  }
+
+  #[test]
+  fn similar_names() {
+    assert_eq!(similarity("Pay the rent!", "pay the rent"), 1.);
+    assert!(similarity("Pay the rent", "Pay the rents") > 0.9);
+    assert!(similarity("Pay the rent", "Walk the dog") < 0.5);
+  }
+
+  #[test]
+  fn typo_distance() {
+    let chars = |s: &str| s.chars().collect::<Vec<_>>();
+    assert_eq!(levenshtein(&chars("wrok"), &chars("work"), true), 1);
+    assert_eq!(levenshtein(&chars("kitten"), &chars("sitting"), true), 3);
+    assert_eq!(levenshtein(&chars("ca"), &chars("abc"), true), 3);
+    assert_eq!(levenshtein(&chars(""), &chars("abc"), true), 3);
+  }
+
+  #[test]
+  fn close_matches() {
+    let candidates = ["work", "toodoux", "doc", "ui", "ux"];
+    assert_eq!(close_match("wrok", candidates), Some("work"));
+    assert_eq!(close_match("Work", candidates), Some("work"));
+    assert_eq!(close_match("docs", candidates), Some("doc"));
+    assert_eq!(close_match("todoux", candidates), Some("toodoux"));
+    assert_eq!(close_match("work", candidates), None);
+    assert_eq!(close_match("ue", candidates), None);
+    assert_eq!(close_match("walk", candidates), None);
+    assert_eq!(close_match("wrok", []), None);
+  }
+}
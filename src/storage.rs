@@ -0,0 +1,334 @@
+//! Storage of the tasks.
+//!
+//! A [`TaskManager`] persists its tasks in a [`Storage`]: a tasks file, holding a snapshot of all the tasks, and a
+//! journal of the changes made since the snapshot was written (see the `journal` configuration option). The storage
+//! only moves bytes around; serializing the tasks, migrating them and replaying the journal is up to the task manager.
+//!
+//! [`FileStorage`] is the storage used by the command line. [`MemoryStorage`] keeps everything in memory, for tests and
+//! for crates embedding toodoux that persist the tasks their own way.
+//!
+//! [`TaskManager`]: crate::task::TaskManager
+
+use crate::{
+  config::Config,
+  error::{Error, Operation},
+};
+use chrono::Utc;
+use std::{
+  fmt,
+  fs::{self, OpenOptions},
+  io::{self, Write as _},
+  path::{Path, PathBuf},
+};
+
+/// Storage of a tasks file and its journal.
+pub trait Storage: fmt::Debug + Send {
+  /// Path of the tasks file, if it is stored in a file.
+  fn tasks_path(&self) -> Option<&Path> {
+    None
+  }
+
+  /// Path of the journal, if it is stored in a file.
+  fn journal_path(&self) -> Option<&Path> {
+    None
+  }
+
+  /// Whether the tasks file exists.
+  fn has_tasks(&self) -> bool;
+
+  /// Read the tasks file, if it exists.
+  fn read_tasks(&self) -> Result<Option<Vec<u8>>, Error>;
+
+  /// Back up the tasks file and the journal before a destructive operation, such as `gc` or a migration, named by
+  /// `reason`.
+  fn back_up(&self, reason: &str) -> Result<(), Error>;
+
+  /// Replace the content of the tasks file.
+  fn write_tasks(&mut self, content: &[u8]) -> Result<(), Error>;
+
+  /// Read the journal, if it exists.
+  fn read_journal(&self) -> Result<Option<String>, Error>;
+
+  /// Append to the journal, creating it if needed; the content is durably written when this returns.
+  fn append_journal(&mut self, content: &str) -> Result<(), Error>;
+
+  /// Truncate the journal to its first `len` bytes.
+  fn truncate_journal(&mut self, len: usize) -> Result<(), Error>;
+
+  /// Remove the journal, if it exists.
+  fn remove_journal(&mut self) -> Result<(), Error>;
+}
+
+/// Tasks file and journal on the filesystem.
+///
+/// Backups are snapshots of the tasks file and the journal, named after their date and reason, in a directory where
+/// only the latest ones are kept.
+#[derive(Clone, Debug)]
+pub struct FileStorage {
+  tasks_path: PathBuf,
+  journal_path: PathBuf,
+  backups_dir: PathBuf,
+  /// Number of backups to keep; none are taken if `0`.
+  backups: usize,
+}
+
+impl FileStorage {
+  /// Storage without backups.
+  pub fn new(tasks_path: impl Into<PathBuf>, journal_path: impl Into<PathBuf>) -> Self {
+    FileStorage {
+      tasks_path: tasks_path.into(),
+      journal_path: journal_path.into(),
+      backups_dir: PathBuf::new(),
+      backups: 0,
+    }
+  }
+
+  /// Keep the last `backups` backups in `backups_dir`.
+  pub fn with_backups(mut self, backups_dir: impl Into<PathBuf>, backups: usize) -> Self {
+    self.backups_dir = backups_dir.into();
+    self.backups = backups;
+    self
+  }
+
+  /// Storage at the paths of the configuration.
+  pub fn from_config(config: &Config) -> Self {
+    Self::new(config.tasks_path(), config.journal_path())
+      .with_backups(config.backups_dir(), config.backups())
+  }
+
+  /// Remove the oldest backups, keeping the last `backups` ones.
+  fn rotate_backups(&self) -> Result<(), Error> {
+    let read_error = Error::io(Operation::Read, &self.backups_dir);
+    let mut stems = fs::read_dir(&self.backups_dir)
+      .and_then(|entries| entries.collect::<Result<Vec<_>, _>>())
+      .map_err(read_error)?
+      .into_iter()
+      .filter_map(|entry| {
+        let path = entry.path();
+        let json = path
+          .extension()
+          .is_some_and(|extension| extension == "json");
+        path.file_stem().filter(|_| json).map(ToOwned::to_owned)
+      })
+      .collect::<Vec<_>>();
+
+    // backups are named after their date, so that their names sort chronologically
+    stems.sort();
+    let outdated = stems.len().saturating_sub(self.backups);
+
+    for stem in &stems[..outdated] {
+      for extension in ["json", "journal"] {
+        let mut name = stem.clone();
+        name.push(".");
+        name.push(extension);
+        let path = self.backups_dir.join(name);
+        log::debug!("removing backup {}", path.display());
+
+        match fs::remove_file(&path) {
+          Err(err) if err.kind() != io::ErrorKind::NotFound => {
+            return Err(Error::io(Operation::RemoveBackup, &path)(err))
+          }
+          _ => (),
+        }
+      }
+    }
+
+    Ok(())
+  }
+}
+
+impl Storage for FileStorage {
+  fn tasks_path(&self) -> Option<&Path> {
+    Some(&self.tasks_path)
+  }
+
+  fn journal_path(&self) -> Option<&Path> {
+    Some(&self.journal_path)
+  }
+
+  fn has_tasks(&self) -> bool {
+    self.tasks_path.is_file()
+  }
+
+  fn read_tasks(&self) -> Result<Option<Vec<u8>>, Error> {
+    match fs::read(&self.tasks_path) {
+      Ok(content) => Ok(Some(content)),
+      Err(err) if err.kind() == io::ErrorKind::NotFound => Ok(None),
+      Err(err) => Err(Error::io(Operation::ReadTasks, &self.tasks_path)(err)),
+    }
+  }
+
+  fn back_up(&self, reason: &str) -> Result<(), Error> {
+    if self.backups == 0 || !self.has_tasks() {
+      return Ok(());
+    }
+
+    fs::create_dir_all(&self.backups_dir)
+      .map_err(Error::io(Operation::BackupTasks, &self.backups_dir))?;
+
+    let stem = format!("{}-{}", Utc::now().format("%Y%m%dT%H%M%S%6fZ"), reason);
+    let backup_path = self.backups_dir.join(format!("{}.json", stem));
+    log::info!("backing up tasks to {}", backup_path.display());
+    fs::copy(&self.tasks_path, &backup_path)
+      .map_err(Error::io(Operation::BackupTasks, &backup_path))?;
+
+    if self.journal_path.is_file() {
+      let backup_path = self.backups_dir.join(format!("{}.journal", stem));
+      fs::copy(&self.journal_path, &backup_path)
+        .map_err(Error::io(Operation::BackupTasks, &backup_path))?;
+    }
+
+    self.rotate_backups()
+  }
+
+  fn write_tasks(&mut self, content: &[u8]) -> Result<(), Error> {
+    fs::write(&self.tasks_path, content).map_err(Error::io(Operation::SaveTasks, &self.tasks_path))
+  }
+
+  fn read_journal(&self) -> Result<Option<String>, Error> {
+    match fs::read_to_string(&self.journal_path) {
+      Ok(content) => Ok(Some(content)),
+      Err(err) if err.kind() == io::ErrorKind::NotFound => Ok(None),
+      Err(err) => Err(Error::io(Operation::ReadJournal, &self.journal_path)(err)),
+    }
+  }
+
+  fn append_journal(&mut self, content: &str) -> Result<(), Error> {
+    OpenOptions::new()
+      .create(true)
+      .append(true)
+      .open(&self.journal_path)
+      .and_then(|mut file| {
+        file.write_all(content.as_bytes())?;
+        file.sync_data()
+      })
+      .map_err(Error::io(Operation::AppendJournal, &self.journal_path))
+  }
+
+  fn truncate_journal(&mut self, len: usize) -> Result<(), Error> {
+    OpenOptions::new()
+      .write(true)
+      .open(&self.journal_path)
+      .and_then(|file| file.set_len(len as u64))
+      .map_err(Error::io(Operation::RepairJournal, &self.journal_path))
+  }
+
+  fn remove_journal(&mut self) -> Result<(), Error> {
+    match fs::remove_file(&self.journal_path) {
+      Err(err) if err.kind() != io::ErrorKind::NotFound => {
+        Err(Error::io(Operation::RemoveJournal, &self.journal_path)(err))
+      }
+      _ => Ok(()),
+    }
+  }
+}
+
+/// Tasks file and journal in memory.
+///
+/// Backups are not kept.
+#[derive(Clone, Debug, Default)]
+pub struct MemoryStorage {
+  tasks: Option<Vec<u8>>,
+  journal: Option<String>,
+}
+
+impl MemoryStorage {
+  pub fn new() -> Self {
+    Self::default()
+  }
+
+  /// Storage whose tasks file holds `tasks`.
+  pub fn with_tasks(tasks: impl Into<Vec<u8>>) -> Self {
+    MemoryStorage {
+      tasks: Some(tasks.into()),
+      journal: None,
+    }
+  }
+}
+
+impl Storage for MemoryStorage {
+  fn has_tasks(&self) -> bool {
+    self.tasks.is_some()
+  }
+
+  fn read_tasks(&self) -> Result<Option<Vec<u8>>, Error> {
+    Ok(self.tasks.clone())
+  }
+
+  fn back_up(&self, _: &str) -> Result<(), Error> {
+    Ok(())
+  }
+
+  fn write_tasks(&mut self, content: &[u8]) -> Result<(), Error> {
+    self.tasks = Some(content.to_owned());
+    Ok(())
+  }
+
+  fn read_journal(&self) -> Result<Option<String>, Error> {
+    Ok(self.journal.clone())
+  }
+
+  fn append_journal(&mut self, content: &str) -> Result<(), Error> {
+    self
+      .journal
+      .get_or_insert_with(String::new)
+      .push_str(content);
+    Ok(())
+  }
+
+  fn truncate_journal(&mut self, len: usize) -> Result<(), Error> {
+    if let Some(journal) = &mut self.journal {
+      journal.truncate(len);
+    }
+
+    Ok(())
+  }
+
+  fn remove_journal(&mut self) -> Result<(), Error> {
+    self.journal = None;
+    Ok(())
+  }
+}
+
+#[cfg(test)]
+mod unit_tests {
+  use super::*;
+
+  #[test]
+  fn rotate_backups() {
+    let dir = tempdir::TempDir::new("toodoux-storage").unwrap();
+    let storage = FileStorage::new(
+      dir.path().join("tasks.json"),
+      dir.path().join("tasks.journal"),
+    )
+    .with_backups(dir.path().join("backups"), 2);
+    let backups = || {
+      let mut backups = fs::read_dir(dir.path().join("backups"))
+        .map(|entries| {
+          entries
+            .map(|entry| entry.unwrap().file_name().into_string().unwrap())
+            .collect::<Vec<_>>()
+        })
+        .unwrap_or_default();
+      backups.sort();
+      backups
+    };
+
+    // nothing to back up yet
+    storage.back_up("gc").unwrap();
+    assert!(backups().is_empty());
+
+    fs::write(dir.path().join("tasks.json"), "{}").unwrap();
+    storage.back_up("first").unwrap();
+    fs::write(dir.path().join("tasks.journal"), "").unwrap();
+    storage.back_up("second").unwrap();
+    storage.back_up("third").unwrap();
+
+    let backups = backups();
+    assert_eq!(backups.len(), 4, "{:?}", backups);
+    assert!(backups[0].ends_with("-second.journal"));
+    assert!(backups[1].ends_with("-second.json"));
+    assert!(backups[2].ends_with("-third.journal"));
+    assert!(backups[3].ends_with("-third.json"));
+  }
+}
@@ -0,0 +1,7 @@
+//! Synchronization with external trackers.
+//!
+//! Each tracker lives in its own module, behind a feature of the same name, so that the HTTP stack is only pulled in
+//! when needed.
+
+#[cfg(feature = "jira")]
+pub mod jira;
@@ -0,0 +1,458 @@
+//! Jira synchronization.
+//!
+//! The issues matching a JQL query are mirrored as tasks, with the issue key as external ID. Statuses are mapped with
+//! the `[jira.statuses]` configuration; an issue in a status that is not mapped takes the status of its category (to
+//! do, in progress or done).
+//!
+//! When the status of a task and the status of its issue disagree, the most recent change wins: if the task changed
+//! status after the issue was last updated, the issue is transitioned to the matching Jira status; otherwise, the task
+//! takes the status of the issue.
+
+use crate::{
+  config::{JiraConfig, JiraStatuses},
+  error::Error,
+  metadata::Metadata,
+  nlp,
+  task::{Event, Status, Task, TaskManager, UID},
+};
+use chrono::{DateTime, NaiveDate, Utc};
+use serde::Deserialize;
+use std::collections::HashMap;
+
+/// Number of issues fetched per request.
+const PAGE_SIZE: usize = 100;
+
+/// Jira issue, as far as synchronization is concerned.
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct Issue {
+  /// Key of the issue, such as `PROJ-123`.
+  pub key: String,
+  /// Summary of the issue, used as name of the task.
+  pub summary: String,
+  /// Name of the status of the issue.
+  pub status: String,
+  /// Key of the category of the status: `new`, `indeterminate` or `done`.
+  pub status_category: String,
+  /// Date at which the issue was last updated.
+  pub updated: DateTime<Utc>,
+  /// Due date of the issue.
+  pub due: Option<NaiveDate>,
+  /// Labels of the issue, used as tags.
+  pub labels: Vec<String>,
+}
+
+impl Issue {
+  /// Status of the task mirroring the issue.
+  pub fn task_status(&self, statuses: &JiraStatuses) -> Status {
+    statuses
+      .status(&self.status)
+      .unwrap_or(match self.status_category.as_str() {
+        "indeterminate" => Status::Ongoing,
+        "done" => Status::Done,
+        _ => Status::Todo,
+      })
+  }
+
+  /// Create the task mirroring the issue.
+  pub fn to_task(&self, config: &JiraConfig) -> Task {
+    let mut task = Task::new(self.summary.clone());
+    task.apply_metadata(
+      config
+        .project
+        .iter()
+        .map(Metadata::project)
+        .chain(self.labels.iter().map(Metadata::tag))
+        .chain(Some(Metadata::external_id(&self.key))),
+    );
+
+    if let Some(due) = self.due {
+      task.set_deadline(nlp::start_of_day(due));
+    }
+
+    let status = self.task_status(&config.statuses);
+    if status != Status::Todo {
+      task.change_status(status);
+    }
+
+    task
+  }
+}
+
+/// Change needed to synchronize a task and an issue.
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub enum Change {
+  /// Create a task mirroring the issue.
+  CreateTask(Issue),
+  /// Change the status of the task mirroring an issue.
+  UpdateTask {
+    uid: UID,
+    key: String,
+    status: Status,
+  },
+  /// Transition an issue to the Jira status matching the status of its task.
+  TransitionIssue { uid: UID, key: String, to: String },
+}
+
+/// Changes needed to synchronize `tasks` with `issues`.
+pub fn plan<'a>(
+  statuses: &JiraStatuses,
+  issues: &[Issue],
+  tasks: impl IntoIterator<Item = (UID, &'a Task)>,
+) -> Vec<Change> {
+  let tasks = tasks
+    .into_iter()
+    .filter_map(|(uid, task)| Some((task.external_id()?, (uid, task))))
+    .collect::<HashMap<_, _>>();
+  let mut changes = Vec::new();
+
+  for issue in issues {
+    let (uid, task) = match tasks.get(issue.key.as_str()) {
+      Some(&(uid, task)) => (uid, task),
+      None => {
+        changes.push(Change::CreateTask(issue.clone()));
+        continue;
+      }
+    };
+
+    let status = task.status();
+    let in_sync = status == issue.task_status(statuses)
+      || statuses
+        .jira_status(status)
+        .is_some_and(|name| name.eq_ignore_ascii_case(&issue.status));
+
+    if in_sync {
+      continue;
+    }
+
+    let changed_locally = last_status_change(task).is_some_and(|date| date > issue.updated);
+    match (statuses.jira_status(status), changed_locally) {
+      (Some(to), true) => changes.push(Change::TransitionIssue {
+        uid,
+        key: issue.key.clone(),
+        to: to.to_owned(),
+      }),
+
+      // the status of the task has no Jira counterpart; keep it
+      (None, true) => (),
+
+      _ => changes.push(Change::UpdateTask {
+        uid,
+        key: issue.key.clone(),
+        status: issue.task_status(statuses),
+      }),
+    }
+  }
+
+  changes
+}
+
+/// Date at which the status of a task last changed, or at which it was created.
+fn last_status_change(task: &Task) -> Option<DateTime<Utc>> {
+  task
+    .history()
+    .rev()
+    .find_map(|event| match event {
+      Event::StatusChanged { event_date, .. } => Some(*event_date),
+      _ => None,
+    })
+    .or_else(|| task.creation_date().copied())
+}
+
+/// Client of the Jira REST API.
+pub struct Client {
+  agent: ureq::Agent,
+  url: String,
+  authorization: String,
+}
+
+impl Client {
+  /// Create a client; the API token is read from the environment variable set in the configuration.
+  pub fn new(config: &JiraConfig) -> Result<Self, Error> {
+    let token = std::env::var(&config.token_env).map_err(|_| {
+      Error::CannotSync(format!(
+        "the {} environment variable must hold a Jira API token",
+        config.token_env
+      ))
+    })?;
+    let credentials = base64::encode(format!("{}:{}", config.user, token));
+
+    Ok(Client {
+      agent: ureq::Agent::new(),
+      url: config.url.trim_end_matches('/').to_owned(),
+      authorization: format!("Basic {}", credentials),
+    })
+  }
+
+  /// Fetch all the issues matching a JQL query.
+  pub fn search(&self, jql: &str) -> Result<Vec<Issue>, Error> {
+    let mut issues = Vec::new();
+
+    loop {
+      let page: SearchPage = self
+        .agent
+        .get(&format!("{}/rest/api/2/search", self.url))
+        .set("Authorization", &self.authorization)
+        .query("jql", jql)
+        .query("fields", "summary,status,updated,duedate,labels")
+        .query("startAt", &issues.len().to_string())
+        .query("maxResults", &PAGE_SIZE.to_string())
+        .call()
+        .map_err(request_error)?
+        .into_json()
+        .map_err(|e| Error::CannotSync(e.to_string()))?;
+
+      let fetched = page.issues.len();
+      for issue in page.issues {
+        issues.push(issue.into_issue()?);
+      }
+
+      if fetched == 0 || issues.len() >= page.total {
+        return Ok(issues);
+      }
+    }
+  }
+
+  /// Transition an issue to the status named `to`.
+  ///
+  /// Return `false` if the workflow of the issue doesn’t allow it.
+  pub fn transition(&self, key: &str, to: &str) -> Result<bool, Error> {
+    let url = format!("{}/rest/api/2/issue/{}/transitions", self.url, key);
+    let transitions: Transitions = self
+      .agent
+      .get(&url)
+      .set("Authorization", &self.authorization)
+      .call()
+      .map_err(request_error)?
+      .into_json()
+      .map_err(|e| Error::CannotSync(e.to_string()))?;
+
+    let transition = transitions
+      .transitions
+      .into_iter()
+      .find(|transition| transition.to.name.eq_ignore_ascii_case(to));
+    let transition = match transition {
+      Some(transition) => transition,
+      None => return Ok(false),
+    };
+
+    self
+      .agent
+      .post(&url)
+      .set("Authorization", &self.authorization)
+      .send_json(serde_json::json!({ "transition": { "id": transition.id } }))
+      .map_err(request_error)?;
+
+    Ok(true)
+  }
+
+  /// Apply a change, returning the UID of the task it is about.
+  ///
+  /// Return [`None`] if an issue cannot be transitioned to the wanted status.
+  pub fn apply(
+    &self,
+    config: &JiraConfig,
+    task_mgr: &mut TaskManager,
+    change: &Change,
+  ) -> Result<Option<UID>, Error> {
+    match *change {
+      Change::CreateTask(ref issue) => {
+        let imported = task_mgr.import_tasks(Some(issue.to_task(config)), false);
+        Ok(imported.first().map(|imported| imported.uid))
+      }
+
+      Change::UpdateTask { uid, status, .. } => {
+        let task = task_mgr.get_mut(uid).ok_or(Error::UnknownTask(uid))?;
+        task.change_status(status);
+        Ok(Some(uid))
+      }
+
+      Change::TransitionIssue {
+        uid,
+        ref key,
+        ref to,
+      } => {
+        let transitioned = self.transition(key, to)?;
+        Ok(Some(uid).filter(|_| transitioned))
+      }
+    }
+  }
+}
+
+fn request_error(err: ureq::Error) -> Error {
+  match err {
+    ureq::Error::Status(code, response) => Error::CannotSync(format!(
+      "{} returned {}: {}",
+      response.get_url().to_owned(),
+      code,
+      response.into_string().unwrap_or_default()
+    )),
+    ureq::Error::Transport(transport) => Error::CannotSync(transport.to_string()),
+  }
+}
+
+#[derive(Deserialize)]
+struct SearchPage {
+  total: usize,
+  issues: Vec<RawIssue>,
+}
+
+#[derive(Deserialize)]
+struct RawIssue {
+  key: String,
+  fields: RawFields,
+}
+
+#[derive(Deserialize)]
+struct RawFields {
+  summary: String,
+  status: RawStatus,
+  updated: String,
+  duedate: Option<String>,
+  #[serde(default)]
+  labels: Vec<String>,
+}
+
+#[derive(Deserialize)]
+struct RawStatus {
+  name: String,
+  #[serde(rename = "statusCategory")]
+  category: RawStatusCategory,
+}
+
+#[derive(Deserialize)]
+struct RawStatusCategory {
+  key: String,
+}
+
+impl RawIssue {
+  fn into_issue(self) -> Result<Issue, Error> {
+    let updated = DateTime::parse_from_str(&self.fields.updated, "%Y-%m-%dT%H:%M:%S%.f%z")
+      .map_err(|e| Error::CannotSync(format!("invalid date {}: {}", self.fields.updated, e)))?;
+    let due = self
+      .fields
+      .duedate
+      .and_then(|due| NaiveDate::parse_from_str(&due, "%Y-%m-%d").ok());
+
+    Ok(Issue {
+      key: self.key,
+      summary: self.fields.summary,
+      status: self.fields.status.name,
+      status_category: self.fields.status.category.key,
+      updated: updated.with_timezone(&Utc),
+      due,
+      labels: self.fields.labels,
+    })
+  }
+}
+
+#[derive(Deserialize)]
+struct Transitions {
+  transitions: Vec<Transition>,
+}
+
+#[derive(Deserialize)]
+struct Transition {
+  id: String,
+  to: RawStatus,
+}
+
+#[cfg(test)]
+mod unit_tests {
+  use super::*;
+  use chrono::Duration;
+
+  fn issue(key: &str, status: &str, category: &str, updated: DateTime<Utc>) -> Issue {
+    Issue {
+      key: key.to_owned(),
+      summary: format!("Fix {}", key),
+      status: status.to_owned(),
+      status_category: category.to_owned(),
+      updated,
+      due: None,
+      labels: vec!["backend".to_owned()],
+    }
+  }
+
+  #[test]
+  fn parse_issue() {
+    let page: SearchPage = serde_json::from_str(
+      r#"{ "total": 1, "issues": [{ "key": "PROJ-1", "fields": {
+        "summary": "Fix the build", "updated": "2021-06-10T09:00:00.000+0200", "duedate": "2021-06-12",
+        "status": { "name": "In Review", "statusCategory": { "key": "indeterminate" } }
+      } }] }"#,
+    )
+    .unwrap();
+    let issue = page
+      .issues
+      .into_iter()
+      .next()
+      .unwrap()
+      .into_issue()
+      .unwrap();
+
+    assert_eq!(issue.key, "PROJ-1");
+    assert_eq!(issue.updated.to_rfc3339(), "2021-06-10T07:00:00+00:00");
+    assert_eq!(issue.due, NaiveDate::from_ymd_opt(2021, 6, 12));
+    assert!(issue.labels.is_empty());
+    assert_eq!(issue.task_status(&JiraStatuses::default()), Status::Ongoing);
+  }
+
+  #[test]
+  fn plan_changes() {
+    let config = JiraConfig {
+      url: "https://example.atlassian.net".to_owned(),
+      user: "me@example.org".to_owned(),
+      token_env: "JIRA_API_TOKEN".to_owned(),
+      jql: "assignee = currentUser()".to_owned(),
+      project: Some("work".to_owned()),
+      statuses: JiraStatuses::default(),
+    };
+    let before = Utc::now() - Duration::days(1);
+    let after = Utc::now() + Duration::days(1);
+
+    let synced = issue("PROJ-1", "In Progress", "indeterminate", before).to_task(&config);
+    let mut done_locally = issue("PROJ-2", "To Do", "new", before).to_task(&config);
+    done_locally.change_status(Status::Done);
+    let mut paused_locally =
+      issue("PROJ-3", "In Progress", "indeterminate", before).to_task(&config);
+    paused_locally.change_status(Status::Paused);
+    // paused has no Jira counterpart, so the issue is left as-is
+    let done_remotely = issue("PROJ-4", "To Do", "new", before).to_task(&config);
+
+    assert_eq!(synced.project(), Some("work"));
+    assert_eq!(synced.tags().collect::<Vec<_>>(), vec!["backend"]);
+    assert_eq!(synced.status(), Status::Ongoing);
+
+    let uid = |s: &str| s.parse::<UID>().unwrap();
+    let tasks = vec![
+      (uid("1"), &synced),
+      (uid("2"), &done_locally),
+      (uid("3"), &paused_locally),
+      (uid("4"), &done_remotely),
+    ];
+    let issues = vec![
+      issue("PROJ-1", "In Progress", "indeterminate", before),
+      issue("PROJ-2", "To Do", "new", before),
+      issue("PROJ-3", "In Progress", "indeterminate", before),
+      issue("PROJ-4", "Closed", "done", after),
+      issue("PROJ-5", "To Do", "new", before),
+    ];
+
+    assert_eq!(
+      plan(&config.statuses, &issues, tasks),
+      vec![
+        Change::TransitionIssue {
+          uid: uid("2"),
+          key: "PROJ-2".to_owned(),
+          to: "Done".to_owned()
+        },
+        Change::UpdateTask {
+          uid: uid("4"),
+          key: "PROJ-4".to_owned(),
+          status: Status::Done
+        },
+        Change::CreateTask(issues[4].clone()),
+      ]
+    );
+  }
+}
@@ -1,42 +1,154 @@
 //! Tasks related code.
 
+mod compact;
+pub mod goal;
+mod journal;
+pub mod merge;
+mod squash;
+
 use crate::{
-  config::Config, error::Error, filter::TaskDescriptionFilter, metadata::Metadata,
-  metadata::Priority,
+  config::Config,
+  error::Error,
+  filter::TaskDescriptionFilter,
+  metadata::Metadata,
+  metadata::{self, FieldValue, NoPriorityOrder, Priorities, Priority, PROJECT_SEPARATOR},
+  migration::{self, TASKS_VERSION},
+  similarity,
+  storage::{FileStorage, MemoryStorage, Storage},
+  tr,
 };
-use chrono::{DateTime, Duration, Utc};
+use chrono::{DateTime, Datelike, Duration, Utc};
+use goal::Goal;
+use journal::Persisted;
 use serde::{Deserialize, Serialize};
 use serde_json as json;
-use std::{cmp::Reverse, collections::HashMap, fmt, fs, str::FromStr};
+use sha2::{Digest as _, Sha256};
+use std::{
+  cmp::Reverse,
+  collections::{BTreeMap, BTreeSet, HashMap},
+  fmt,
+  path::Path,
+  str::FromStr,
+  sync::atomic::{self, AtomicU64},
+};
 use unicase::UniCase;
 
 /// Create, edit, remove and list tasks.
 #[derive(Debug, Deserialize, Serialize)]
 pub struct TaskManager {
+  /// Version of the layout; see the [`migration`] module.
+  #[serde(default)]
+  version: u32,
   /// Next UID to use for the next task to create.
   next_uid: UID,
   /// List of known tasks.
   tasks: HashMap<UID, Task>,
+  /// Removed tasks, which can still be restored until the trash is emptied.
+  #[serde(default, skip_serializing_if = "HashMap::is_empty")]
+  trash: HashMap<UID, Task>,
+  /// Active context, filtering listings.
+  #[serde(default, skip_serializing_if = "Option::is_none")]
+  context: Option<String>,
+  /// Goals tasks can be linked to.
+  #[serde(default, skip_serializing_if = "BTreeMap::is_empty")]
+  goals: BTreeMap<UID, Goal>,
+  /// Logical clock: sequence number of the last recorded event.
+  #[serde(default, skip_serializing_if = "is_zero")]
+  clock: u64,
+  /// State of the tasks as last persisted, to journal the changes made since then.
+  #[serde(skip)]
+  persisted: Persisted,
+  /// Storage the tasks are loaded from and saved to.
+  #[serde(skip, default = "default_storage")]
+  storage: Box<dyn Storage>,
+}
+
+fn default_storage() -> Box<dyn Storage> {
+  Box::new(MemoryStorage::new())
 }
 
 impl TaskManager {
-  /// Create a manager from a configuration.
+  /// Create a manager from a configuration, storing the tasks in the files it points to.
+  ///
+  /// The journal, if any, is replayed on top of the tasks file.
   pub fn new_from_config(config: &Config) -> Result<Self, Error> {
-    let path = config.tasks_path();
+    Self::with_storage(config, FileStorage::from_config(config))
+  }
 
-    if path.is_file() {
-      Ok(json::from_reader(
-        fs::File::open(path).map_err(Error::CannotOpenFile)?,
-      )?)
-    } else {
-      let task_mgr = TaskManager {
-        next_uid: UID::default(),
-        tasks: HashMap::new(),
-      };
-      Ok(task_mgr)
+  /// Create a manager storing the tasks in `storage`.
+  ///
+  /// The journal, if any, is replayed on top of the tasks file.
+  pub fn with_storage(config: &Config, storage: impl Storage + 'static) -> Result<Self, Error> {
+    let invalid_tasks = |source| Error::InvalidTasks {
+      path: storage.tasks_path().map(Path::to_owned),
+      source,
+    };
+
+    let (mut task_mgr, migrated) = match storage.read_tasks()? {
+      Some(content) => {
+        let mut tasks: json::Value = json::from_slice(&content).map_err(invalid_tasks)?;
+        compact::expand_tasks(&mut tasks);
+
+        // backup the tasks before upgrading them, in case anything goes wrong
+        let version = migration::version(&tasks)?;
+        if version < TASKS_VERSION {
+          storage.back_up(&format!("v{}", version))?;
+        }
+
+        let migrated = migration::migrate(&mut tasks)?;
+        let task_mgr = json::from_value(tasks).map_err(invalid_tasks)?;
+        (task_mgr, migrated.is_some())
+      }
+
+      None => (Self::in_memory(), false),
+    };
+
+    task_mgr.storage = Box::new(storage);
+    let records = journal::replay(&mut task_mgr)?;
+    let seq = max_seq(
+      task_mgr
+        .tasks
+        .values()
+        .chain(task_mgr.trash.values())
+        .flat_map(|task| &task.history),
+    );
+    task_mgr.tick(seq);
+    task_mgr.persisted = Persisted::new(&task_mgr, records);
+
+    if migrated {
+      task_mgr.compact(config)?;
+    }
+
+    Ok(task_mgr)
+  }
+
+  /// Create an empty manager storing the tasks in memory.
+  pub fn in_memory() -> Self {
+    TaskManager {
+      version: TASKS_VERSION,
+      next_uid: UID::default(),
+      tasks: HashMap::new(),
+      trash: HashMap::new(),
+      context: None,
+      goals: BTreeMap::new(),
+      clock: 0,
+      persisted: Persisted::default(),
+      storage: default_storage(),
     }
   }
 
+  /// Back up the tasks as last saved before a destructive operation named by `reason`, such as `gc`.
+  ///
+  /// Backups are taken by the storage, if it keeps any.
+  pub fn back_up(&self, reason: &str) -> Result<(), Error> {
+    self.storage.back_up(reason)
+  }
+
+  /// Storage of the tasks.
+  pub fn storage(&self) -> &dyn Storage {
+    self.storage.as_ref()
+  }
+
   /// Increment the next UID to use.
   fn increment_uid(&mut self) {
     let uid = self.next_uid.0 + 1;
@@ -53,11 +165,155 @@ impl TaskManager {
     uid
   }
 
+  /// Pause the ongoing tasks, except `uid`.
+  ///
+  /// Return the UIDs of the paused tasks, sorted.
+  pub fn pause_ongoing_tasks(&mut self, uid: UID) -> Vec<UID> {
+    let mut paused = self
+      .tasks
+      .iter_mut()
+      .filter(|(&other, task)| other != uid && task.status() == Status::Ongoing)
+      .map(|(&other, task)| {
+        task.change_status(Status::Paused);
+        other
+      })
+      .collect::<Vec<_>>();
+    paused.sort();
+
+    paused
+  }
+
+  /// Register imported tasks, skipping the ones that were already imported unless `allow_duplicates` is set.
+  ///
+  /// Tasks are recognized by their external ID, among the active and the removed tasks; tasks without external ID are
+  /// always registered. The returned report maps each task to its UID.
+  pub fn import_tasks(
+    &mut self,
+    tasks: impl IntoIterator<Item = Task>,
+    allow_duplicates: bool,
+  ) -> Vec<ImportedTask> {
+    let mut report = Vec::new();
+
+    for task in tasks {
+      let external_id = task.external_id().map(str::to_owned);
+      let existing = external_id
+        .as_deref()
+        .filter(|_| !allow_duplicates)
+        .and_then(|external_id| self.find_external_id(external_id));
+
+      let (uid, duplicate) = match existing {
+        Some(uid) => (uid, true),
+        None => (self.register_task(task), false),
+      };
+
+      report.push(ImportedTask {
+        external_id,
+        uid,
+        duplicate,
+      });
+    }
+
+    report
+  }
+
+  /// Find the task, active or removed, with the given external ID.
+  pub fn find_external_id(&self, external_id: &str) -> Option<UID> {
+    self
+      .tasks
+      .iter()
+      .chain(&self.trash)
+      .filter(|(_, task)| task.external_id() == Some(external_id))
+      .map(|(&uid, _)| uid)
+      .min()
+  }
+
+  /// Save the tasks.
+  ///
+  /// If the journal is enabled, only the changes made since the tasks were loaded are appended to it, unless it grew
+  /// too large; the whole tasks file is written otherwise.
   pub fn save(&mut self, config: &Config) -> Result<(), Error> {
-    Ok(json::to_writer_pretty(
-      fs::File::create(config.tasks_path()).map_err(Error::CannotSave)?,
-      self,
-    )?)
+    self.save_tasks(config, false)
+  }
+
+  /// Save the tasks, compacting the journal into the tasks file.
+  pub fn compact(&mut self, config: &Config) -> Result<(), Error> {
+    self.save_tasks(config, true)
+  }
+
+  /// Number of records in the journal, not compacted into the tasks file yet.
+  pub fn journal_records(&self) -> usize {
+    self.persisted.records()
+  }
+
+  fn save_tasks(&mut self, config: &Config, compact: bool) -> Result<(), Error> {
+    self.sequence();
+
+    let integrity_hashes = config.integrity_hashes();
+    for task in self.tasks.values_mut().chain(self.trash.values_mut()) {
+      task.stamp(config.author());
+
+      if integrity_hashes {
+        task.seal();
+      } else {
+        task.seal = None;
+      }
+    }
+
+    let journaled = !compact
+      && config.journal()
+      && self.version == TASKS_VERSION
+      && self.persisted.records() < journal::COMPACTION_THRESHOLD
+      && self.storage.has_tasks();
+
+    if journaled {
+      let records = self.persisted.records() + journal::append(self, config.compact_tasks_file())?;
+      self.persisted = Persisted::new(self, records);
+      return Ok(());
+    }
+
+    self.version = TASKS_VERSION;
+    let content = if config.compact_tasks_file() {
+      let mut tasks = json::to_value(&*self)?;
+      compact::shorten_tasks(&mut tasks);
+      json::to_vec(&tasks)?
+    } else {
+      json::to_vec_pretty(self)?
+    };
+    self.storage.write_tasks(&content)?;
+    self.storage.remove_journal()?;
+    self.persisted = Persisted::new(self, 0);
+
+    Ok(())
+  }
+
+  /// Number the events recorded since the tasks were loaded with the next values of the logical clock.
+  ///
+  /// Sequence numbers follow the order in which the events were recorded, across all the tasks.
+  fn sequence(&mut self) {
+    let mut pending = self
+      .tasks
+      .values_mut()
+      .chain(self.trash.values_mut())
+      .flat_map(|task| task.history.iter_mut())
+      .filter_map(|entry| entry.pending.map(|order| (order, entry)))
+      .collect::<Vec<_>>();
+    pending.sort_by_key(|&(order, _)| order);
+
+    for (_, entry) in pending {
+      self.clock += 1;
+      entry.seq = self.clock;
+    }
+  }
+
+  /// Advance the logical clock up to `seq`, the sequence number of an event that may come from another copy of the
+  /// tasks, so that the next events are ordered after it.
+  fn tick(&mut self, seq: u64) {
+    self.clock = self.clock.max(seq);
+  }
+
+  /// Sequence number of the last recorded event.
+  pub fn clock(&self) -> u64 {
+    self.clock
   }
 
   pub fn tasks(&self) -> impl Iterator<Item = (&UID, &Task)> {
@@ -72,6 +328,173 @@ impl TaskManager {
     self.tasks.get_mut(&uid)
   }
 
+  /// Get the active context, if any.
+  pub fn context(&self) -> Option<&str> {
+    self.context.as_deref()
+  }
+
+  /// Set or clear the active context.
+  pub fn set_context(&mut self, context: Option<String>) {
+    self.context = context;
+  }
+
+  /// List the contexts of the active tasks, by name, along with their number of active tasks.
+  pub fn contexts(&self) -> Vec<(&str, usize)> {
+    let mut contexts = HashMap::new();
+
+    for (_, task) in self.tasks() {
+      if let (Some(context), true) = (task.context(), task.status().is_active()) {
+        *contexts.entry(context).or_insert(0) += 1;
+      }
+    }
+
+    let mut contexts = contexts.into_iter().collect::<Vec<_>>();
+    contexts.sort();
+    contexts
+  }
+
+  /// Complete a metadata word being typed, such as `@too` or `#doc`, with the values used by the tasks.
+  ///
+  /// Projects (`@`), tags (`#`), contexts (`ctx:`) and milestones (`milestone:`) are completed from all the tasks, and
+  /// priorities (`+`) from the names and aliases of `priorities`. Values are matched ignoring case, and the completions
+  /// are sorted; words without metadata operator have none.
+  pub fn complete_metadata(&self, priorities: &Priorities, word: &str) -> Vec<String> {
+    let tasks = self.tasks.values();
+    let (operator, values): (_, BTreeSet<&str>) = if word.starts_with("ctx:") {
+      ("ctx:", tasks.filter_map(Task::context).collect())
+    } else if word.starts_with("milestone:") {
+      ("milestone:", tasks.filter_map(Task::milestone).collect())
+    } else if word.starts_with('@') {
+      ("@", tasks.filter_map(Task::project).collect())
+    } else if word.starts_with('#') {
+      ("#", tasks.flat_map(Task::tags).collect())
+    } else if word.starts_with('+') {
+      let names = priorities
+        .levels()
+        .iter()
+        .flat_map(|level| std::iter::once(&level.name).chain(&level.aliases))
+        .map(String::as_str)
+        .collect();
+      ("+", names)
+    } else {
+      return Vec::new();
+    };
+
+    let word = word.to_lowercase();
+    values
+      .into_iter()
+      .map(|value| format!("{}{}", operator, value))
+      .filter(|completion| completion.to_lowercase().starts_with(&word))
+      .collect()
+  }
+
+  /// Find the project used by the tasks that `project` is most likely a typo of, unless it’s already used.
+  pub fn close_project(&self, project: &str) -> Option<&str> {
+    let projects = self
+      .tasks
+      .values()
+      .filter_map(Task::project)
+      .collect::<BTreeSet<_>>();
+    similarity::close_match(project, projects)
+  }
+
+  /// Find the tag used by the tasks that `tag` is most likely a typo of, unless it’s already used.
+  pub fn close_tag(&self, tag: &str) -> Option<&str> {
+    let tags = self
+      .tasks
+      .values()
+      .flat_map(Task::tags)
+      .collect::<BTreeSet<_>>();
+    similarity::close_match(tag, tags)
+  }
+
+  /// Rename a tag of all the tasks.
+  pub fn rename_tag(
+    &mut self,
+    current_tag: impl AsRef<str>,
+    new_tag: impl AsRef<str>,
+    mut on_renamed: impl FnMut(UID),
+  ) {
+    let current_tag = current_tag.as_ref();
+    let new_tag = new_tag.as_ref();
+
+    for (uid, task) in &mut self.tasks {
+      if task.tags().any(|tag| tag == current_tag) {
+        task.rename_tag(current_tag, new_tag);
+        on_renamed(*uid);
+      }
+    }
+  }
+
+  /// Move a task to the trash.
+  pub fn remove_task(&mut self, uid: UID) -> Result<(), Error> {
+    let mut task = self.tasks.remove(&uid).ok_or(Error::UnknownTask(uid))?;
+    task.record_event(Event::Removed {
+      event_date: Utc::now(),
+    });
+    self.trash.insert(uid, task);
+
+    Ok(())
+  }
+
+  /// Move all the tasks to the trash.
+  ///
+  /// Return the number of removed tasks.
+  pub fn remove_all_tasks(&mut self) -> usize {
+    let uids = self.tasks.keys().copied().collect::<Vec<_>>();
+
+    for &uid in &uids {
+      let _ = self.remove_task(uid);
+    }
+
+    uids.len()
+  }
+
+  /// Iterate over the tasks in the trash.
+  pub fn trashed_tasks(&self) -> impl Iterator<Item = (&UID, &Task)> {
+    self.trash.iter()
+  }
+
+  /// Restore a task from the trash.
+  pub fn restore_task(&mut self, uid: UID) -> Result<(), Error> {
+    let mut task = self.trash.remove(&uid).ok_or(Error::UnknownTask(uid))?;
+    task.record_event(Event::Restored {
+      event_date: Utc::now(),
+    });
+    self.tasks.insert(uid, task);
+
+    Ok(())
+  }
+
+  /// UIDs of the tasks that have been in the trash for longer than `retention`.
+  pub fn expired_trash(&self, retention: Duration) -> Vec<UID> {
+    let now = Utc::now();
+    self
+      .trash
+      .iter()
+      .filter(|(_, task)| {
+        task
+          .removal_date()
+          .map_or(true, |date| now.signed_duration_since(*date) >= retention)
+      })
+      .map(|(&uid, _)| uid)
+      .collect()
+  }
+
+  /// Permanently delete the tasks that have been in the trash for longer than `retention`.
+  ///
+  /// Return the UIDs of the purged tasks.
+  pub fn empty_trash(&mut self, retention: Duration) -> Vec<UID> {
+    let purged = self.expired_trash(retention);
+
+    for uid in &purged {
+      self.trash.remove(uid);
+    }
+
+    purged
+  }
+
+  /// Rename a project, along with all its subprojects: renaming `work` to `job` moves `work.backend` to `job.backend`.
   pub fn rename_project(
     &mut self,
     current_project: impl AsRef<str>,
@@ -83,8 +506,9 @@ impl TaskManager {
 
     for (uid, task) in &mut self.tasks {
       match task.project() {
-        Some(project) if project == current_project => {
-          task.set_project(new_project);
+        Some(project) if metadata::is_in_project(project, current_project, false) => {
+          let renamed = format!("{}{}", new_project, &project[current_project.len()..]);
+          task.set_project(renamed);
           on_renamed(*uid);
         }
 
@@ -93,56 +517,349 @@ impl TaskManager {
     }
   }
 
-  /// Get a listing of tasks that can be filtered with metadata and name filters.
-  pub fn filtered_task_listing(
+  /// Roll up the effort and spent time of the tasks of a project, including its subprojects.
+  ///
+  /// The rollup of each direct subproject is returned along, by full name; e.g. `work.backend` for `work`.
+  pub fn project_rollup(&self, project: &str) -> (EffortRollup, BTreeMap<String, EffortRollup>) {
+    let mut total = EffortRollup::default();
+    let mut subprojects = BTreeMap::<String, EffortRollup>::new();
+
+    for task in self.tasks.values() {
+      let task_project = match task.project() {
+        Some(task_project) if metadata::is_in_project(task_project, project, false) => task_project,
+        _ => continue,
+      };
+
+      total.add(task);
+
+      let rest = &task_project[project.len()..];
+      if let Some(name) = rest
+        .strip_prefix(PROJECT_SEPARATOR)
+        .and_then(|rest| rest.split(PROJECT_SEPARATOR).next())
+      {
+        let subproject = format!(
+          "{}{}{}",
+          &task_project[..project.len()],
+          PROJECT_SEPARATOR,
+          name
+        );
+        subprojects.entry(subproject).or_default().add(task);
+      }
+    }
+
+    (total, subprojects)
+  }
+
+  /// Find the active tasks (to do or on-going) with a name similar to `name`.
+  ///
+  /// Only tasks with a similarity of at least `threshold` are returned, most similar first.
+  pub fn similar_active_tasks(&self, name: &str, threshold: f32) -> Vec<(UID, &Task, f32)> {
+    let mut similar = self
+      .tasks()
+      .filter(|(_, task)| task.status().is_active())
+      .map(|(&uid, task)| (uid, task, similarity::similarity(name, task.name())))
+      .filter(|&(_, _, similarity)| similarity >= threshold)
+      .collect::<Vec<_>>();
+
+    similar.sort_by(|(a_uid, _, a), (b_uid, _, b)| b.total_cmp(a).then(a_uid.cmp(b_uid)));
+    similar
+  }
+
+  /// Suggest which active tasks to work on until `until`, given a `capacity`.
+  ///
+  /// Candidates are the tasks that are neither in the someday backlog nor snoozed past `until`, and that are either due
+  /// by `until` or in the upper half of the priority levels. Due tasks come first, by deadline, then by priority;
+  /// candidates are then greedily planned as long as their remaining effort fits in the capacity left.
+  pub fn plan(
     &self,
+    priorities: &Priorities,
+    capacity: Duration,
+    until: DateTime<Utc>,
+  ) -> Plan<'_> {
+    let levels = priorities.levels().len();
+    let mut candidates = self
+      .tasks()
+      .filter(|(_, task)| task.status().is_active())
+      .filter(|(_, task)| !task.is_snoozed(until) && !task.is_someday())
+      .map(|(&uid, task)| {
+        let due = task.deadline().filter(|&deadline| deadline <= until);
+        let rank = task
+          .priority()
+          .and_then(|priority| priorities.rank(priority));
+        (uid, task, due, rank)
+      })
+      .filter(|&(_, _, due, rank)| due.is_some() || rank.is_some_and(|rank| rank * 2 >= levels))
+      .collect::<Vec<_>>();
+
+    candidates.sort_by_key(|&(uid, task, due, rank)| {
+      (due.is_none(), due, Reverse(rank), task.status(), uid)
+    });
+
+    let mut plan = Plan {
+      free: capacity,
+      ..Plan::default()
+    };
+
+    for (uid, task, _, _) in candidates {
+      match task.remaining_effort() {
+        Some(effort) if effort <= plan.free => {
+          plan.free = plan.free - effort;
+          plan.planned.push((uid, task, effort));
+        }
+
+        Some(effort) => plan.left_out.push((uid, task, effort)),
+        None => plan.unestimated.push((uid, task)),
+      }
+    }
+
+    plan
+  }
+
+  /// Find the reminders of active tasks that are due at `date` and not delivered yet, by date.
+  ///
+  /// Reminders are returned along with the UID and the task they belong to and their own UID.
+  pub fn due_reminders(&self, date: DateTime<Utc>) -> Vec<(UID, &Task, UID, DateTime<Utc>)> {
+    let mut reminders = self
+      .tasks()
+      .filter(|(_, task)| task.status().is_active())
+      .flat_map(|(&uid, task)| {
+        task
+          .reminders()
+          .into_iter()
+          .enumerate()
+          .filter(|(_, reminder)| reminder.delivered.is_none() && reminder.date <= date)
+          .map(move |(i, reminder)| (uid, task, UID(i as _), reminder.date))
+      })
+      .collect::<Vec<_>>();
+
+    reminders.sort_by_key(|&(uid, _, reminder_uid, date)| (date, uid, reminder_uid));
+    reminders
+  }
+
+  /// Find the active tasks of the someday backlog that haven’t been reviewed for `interval`, by UID.
+  pub fn someday_tasks_to_review(&self, interval: Duration) -> Vec<(UID, &Task)> {
+    let now = Utc::now();
+    let mut tasks = self
+      .tasks()
+      .filter(|(_, task)| task.status().is_active())
+      .filter(|(_, task)| task.is_someday())
+      .filter(|(_, task)| {
+        task
+          .review_date()
+          .map_or(true, |date| now.signed_duration_since(*date) >= interval)
+      })
+      .map(|(&uid, task)| (uid, task))
+      .collect::<Vec<_>>();
+
+    tasks.sort_by_key(|&(uid, _)| uid);
+    tasks
+  }
+
+  /// Find the events of all the tasks, active or removed, that occurred at or after `since`, the most recent first.
+  ///
+  /// Events are returned along with the UID of their task, the task and their author, if known. Ties are broken by
+  /// sequence number, then by UID, then by position in the history, the latest first.
+  pub fn events_since(&self, since: DateTime<Utc>) -> Vec<(UID, &Task, &Event, Option<&str>)> {
+    let mut events = self
+      .tasks
+      .iter()
+      .chain(&self.trash)
+      .flat_map(|(&uid, task)| {
+        task
+          .history
+          .iter()
+          .enumerate()
+          .filter(|(_, entry)| *entry.event.date() >= since)
+          .map(move |(i, entry)| {
+            let event = (uid, task, &entry.event, entry.author.as_deref());
+            ((i, entry.seq), event)
+          })
+      })
+      .collect::<Vec<_>>();
+
+    events.sort_by_key(|&((i, seq), (uid, _, event, _))| {
+      (Reverse(*event.date()), Reverse(seq), uid, Reverse(i))
+    });
+    events.into_iter().map(|(_, event)| event).collect()
+  }
+
+  /// Find the tasks, active and removed, that cannot be read in the content of a tasks file, without loading it.
+  ///
+  /// Return an error if the content cannot be read as a whole, e.g. if it is not JSON or of a newer version.
+  pub fn unreadable_tasks(content: &[u8]) -> Result<Vec<UnreadableTask>, Error> {
+    let mut tasks: json::Value =
+      json::from_slice(content).map_err(|source| Error::InvalidTasks { path: None, source })?;
+    compact::expand_tasks(&mut tasks);
+    migration::migrate(&mut tasks)?;
+
+    let mut unreadable = Vec::new();
+    for key in &["tasks", "trash"] {
+      let tasks = match tasks.get(*key).and_then(json::Value::as_object) {
+        Some(tasks) => tasks,
+        None => continue,
+      };
+
+      for (uid, task) in tasks {
+        let error = match json::from_value::<Task>(task.clone()) {
+          Ok(_) => continue,
+          Err(error) => error,
+        };
+
+        // the first event that cannot be read is the one whose history stops being readable
+        let history = task
+          .get("history")
+          .and_then(json::Value::as_array)
+          .cloned()
+          .unwrap_or_default();
+        let event = (0..history.len()).find(|&i| {
+          let mut task = task.clone();
+          task["history"] = json::Value::from(history[..=i].to_vec());
+          json::from_value::<Task>(task).is_err()
+        });
+
+        unreadable.push(UnreadableTask {
+          uid: uid.clone(),
+          event,
+          error,
+        });
+      }
+    }
+
+    Ok(unreadable)
+  }
+
+  /// Check the integrity of the tasks, active and removed.
+  ///
+  /// Issues are returned ordered by task UID.
+  pub fn check_integrity(&self) -> Vec<IntegrityIssue> {
+    let mut issues = Vec::new();
+
+    let max_uid = self.tasks.keys().chain(self.trash.keys()).max().copied();
+    if let Some(max_uid) = max_uid {
+      if self.next_uid <= max_uid {
+        issues.push(IntegrityIssue::NextUIDInUse {
+          next_uid: self.next_uid,
+          max_uid,
+        });
+      }
+    }
+
+    let mut uids = self.tasks.keys().copied().collect::<Vec<_>>();
+    uids.sort();
+
+    let mut trash_uids = self.trash.keys().copied().collect::<Vec<_>>();
+    trash_uids.sort();
+
+    for uid in trash_uids {
+      if self.tasks.contains_key(&uid) {
+        issues.push(IntegrityIssue::DuplicateUID(uid));
+      } else {
+        uids.push(uid);
+      }
+    }
+
+    for uid in uids {
+      let task = self.tasks.get(&uid).or_else(|| self.trash.get(&uid));
+      if let Some(task) = task {
+        task.check_integrity(uid, &mut issues);
+      }
+    }
+
+    issues
+  }
+
+  /// Get a listing of tasks that can be filtered with metadata and name filters.
+  ///
+  /// Priorities are ordered according to `priorities`, and tasks without a priority according to `no_priority`.
+  #[allow(clippy::too_many_arguments)]
+  pub fn filtered_task_listing<'a>(
+    &'a self,
+    priorities: &Priorities,
+    no_priority: NoPriorityOrder,
     metadata: Vec<Metadata>,
     name_filter: TaskDescriptionFilter,
     todo: bool,
     start: bool,
+    paused: bool,
     done: bool,
     cancelled: bool,
+    snoozed: bool,
+    someday: Option<bool>,
     case_insensitive: bool,
-  ) -> Vec<(&UID, &Task)> {
-    let mut tasks: Vec<_> = self
-      .tasks()
-      .filter(|(_, task)| {
-        // filter the task depending on what is passed as argument
-        let status_filter = match task.status() {
-          Status::Ongoing => start,
-          Status::Todo => todo,
-          Status::Done => done,
-          Status::Cancelled => cancelled,
-        };
+  ) -> Vec<(&'a UID, &'a Task)> {
+    let now = Utc::now();
+    let listed = |(uid, task): (&'a UID, &'a Task)| {
+      if !snoozed && task.is_snoozed(now) {
+        return None;
+      }
 
-        if metadata.is_empty() {
-          status_filter
-        } else {
-          status_filter && task.check_metadata(metadata.iter(), case_insensitive)
-        }
-      })
-      .filter(|(_, task)| {
-        if !name_filter.is_empty() {
-          let mut name_filter = name_filter.clone();
+      if someday.is_some_and(|someday| task.is_someday() != someday) {
+        return None;
+      }
 
-          for word in task.name().split_ascii_whitespace() {
-            let word_found = name_filter.remove(word);
+      // filter the task depending on what is passed as argument
+      let status_filter = match task.status() {
+        Status::Ongoing => start,
+        Status::Todo => todo,
+        Status::Done => done,
+        Status::Cancelled => cancelled,
+        Status::Paused => paused,
+      };
 
-            if word_found && name_filter.is_empty() {
-              return true;
-            }
-          }
+      if !status_filter || !task.check_metadata(metadata.iter(), case_insensitive) {
+        return None;
+      }
 
-          false
-        } else {
-          true
-        }
-      })
-      .collect();
+      if !name_filter.is_empty() {
+        let mut name_filter = name_filter.clone();
+        let found = task
+          .name()
+          .split_ascii_whitespace()
+          .any(|word| name_filter.remove(word) && name_filter.is_empty());
 
-    tasks.sort_by_key(|&(uid, task)| Reverse((task.priority(), task.age(), task.status(), uid)));
+        if !found {
+          return None;
+        }
+      }
 
-    tasks
+      // pinned tasks float to the top, unless they have no priority and those are listed last; then sort by priority,
+      // then by age (oldest first) and status; ties are broken by UID so that the ordering doesn’t depend on the
+      // iteration order of the tasks nor on when the age of each task is computed
+      let last = no_priority == NoPriorityOrder::Last
+        && task
+          .priority()
+          .and_then(|priority| priorities.rank(priority))
+          .is_none();
+      let key = (
+        last,
+        !task.is_pinned(),
+        Reverse(priorities.sort_key(task.priority(), no_priority)),
+        task.creation_date().copied().unwrap_or(now),
+        Reverse(task.status()),
+        *uid,
+      );
+
+      Some((key, (uid, task)))
+    };
+
+    // the sort keys are unique, as they end with the UID, so that an unstable sort is deterministic
+    #[cfg(feature = "parallel")]
+    let tasks = {
+      use rayon::prelude::*;
+
+      let mut tasks: Vec<_> = self.tasks.par_iter().filter_map(listed).collect();
+      tasks.par_sort_unstable_by_key(|&(key, _)| key);
+      tasks
+    };
+
+    #[cfg(not(feature = "parallel"))]
+    let tasks = {
+      let mut tasks: Vec<_> = self.tasks.iter().filter_map(listed).collect();
+      tasks.sort_unstable_by_key(|&(key, _)| key);
+      tasks
+    };
+
+    tasks.into_iter().map(|(_, task)| task).collect()
   }
 }
 
@@ -151,7 +868,10 @@ pub struct Task {
   /// Name of the task.
   name: String,
   /// Event history.
-  history: Vec<Event>,
+  history: Vec<HistoryEntry>,
+  /// Seal of the history, if integrity hashes are enabled.
+  #[serde(default, skip_serializing_if = "Option::is_none")]
+  seal: Option<Seal>,
 }
 
 impl Task {
@@ -159,15 +879,26 @@ impl Task {
   pub fn new(name: impl Into<String>) -> Self {
     let date = Utc::now();
 
-    Task {
-      name: name.into(),
-      history: vec![
+    Task::from_history(
+      name,
+      vec![
         Event::Created(date),
         Event::StatusChanged {
           event_date: date,
           status: Status::Todo,
         },
       ],
+    )
+  }
+
+  /// Create a [`Task`] from an already existing history.
+  ///
+  /// This is used by importers to reproduce the history of a task coming from another tool.
+  pub(crate) fn from_history(name: impl Into<String>, history: Vec<Event>) -> Self {
+    Task {
+      name: name.into(),
+      history: history.into_iter().map(HistoryEntry::new).collect(),
+      seal: None,
     }
   }
 
@@ -179,8 +910,7 @@ impl Task {
   /// Get the current status of the [`Task`].
   pub fn status(&self) -> Status {
     self
-      .history
-      .iter()
+      .history()
       .filter_map(|event| match event {
         Event::StatusChanged { status, .. } => Some(status),
         _ => None,
@@ -192,17 +922,47 @@ impl Task {
 
   /// Get the creation date of the [`Task`].
   pub fn creation_date(&self) -> Option<&DateTime<Utc>> {
-    self.history.iter().find_map(|event| match event {
+    self.history().find_map(|event| match event {
       Event::Created(ref date) => Some(date),
       _ => None,
     })
   }
 
+  /// Get the date at which the [`Task`] was last moved to the trash, if any.
+  pub fn removal_date(&self) -> Option<&DateTime<Utc>> {
+    self.history().rev().find_map(|event| match event {
+      Event::Removed { ref event_date } => Some(event_date),
+      _ => None,
+    })
+  }
+
+  /// Get the date at which the [`Task`] was last reviewed or moved to the someday backlog, if any.
+  pub fn review_date(&self) -> Option<&DateTime<Utc>> {
+    self.history().rev().find_map(|event| match event {
+      Event::Reviewed { ref event_date }
+      | Event::SetSomeday {
+        ref event_date,
+        someday: true,
+      } => Some(event_date),
+      _ => None,
+    })
+  }
+
   /// Get the age of the [`Task`]; i.e. the duration since its creation date.
   pub fn age(&self) -> Duration {
     Utc::now().signed_duration_since(self.creation_date().copied().unwrap_or_else(Utc::now))
   }
 
+  /// Get the date of the last activity on the [`Task`]; i.e. the date of its last event.
+  pub fn last_activity(&self) -> Option<&DateTime<Utc>> {
+    self.history().map(Event::date).max()
+  }
+
+  /// Get the duration since the last activity on the [`Task`].
+  pub fn inactivity(&self) -> Duration {
+    Utc::now().signed_duration_since(self.last_activity().copied().unwrap_or_else(Utc::now))
+  }
+
   /// Change the name of the [`Task`].
   pub fn change_name(&mut self, name: impl Into<String>) {
     self.name = name.into()
@@ -210,7 +970,7 @@ impl Task {
 
   /// Change the status of the [`Task`].
   pub fn change_status(&mut self, status: Status) {
-    self.history.push(Event::StatusChanged {
+    self.record_event(Event::StatusChanged {
       event_date: Utc::now(),
       status,
     });
@@ -218,23 +978,128 @@ impl Task {
 
   /// Add a new note to the [`Task`].
   pub fn add_note(&mut self, content: impl Into<String>) {
-    self.history.push(Event::NoteAdded {
+    self.record_event(Event::NoteAdded {
       event_date: Utc::now(),
       content: content.into(),
+      in_reply_to: None,
     });
   }
 
-  /// Replace the content of a note for a given [`Task`].
-  pub fn replace_note(&mut self, note_uid: UID, content: impl Into<String>) -> Result<(), Error> {
-    // ensure the note exists first
-    let mut count = 0;
-    let id: u32 = note_uid.into();
-    let previous_note = self.history.iter().find(|event| match event {
-      Event::NoteAdded { .. } => {
-        if id == count {
-          true
-        } else {
-          count += 1;
+  /// Add a new note in reply to the note `note_uid`, starting or continuing a thread.
+  pub fn reply_to_note(&mut self, note_uid: UID, content: impl Into<String>) -> Result<(), Error> {
+    if usize::from(note_uid) >= self.notes().len() {
+      return Err(Error::UnknownNote(note_uid));
+    }
+
+    self.record_event(Event::NoteAdded {
+      event_date: Utc::now(),
+      content: content.into(),
+      in_reply_to: Some(note_uid),
+    });
+
+    Ok(())
+  }
+
+  /// Seal the history of the [`Task`], extending its previous seal.
+  ///
+  /// A history whose sealed events were altered is left as-is, so that the tampering can still be detected.
+  fn seal(&mut self) {
+    let intact = self.seal.as_ref().map_or(true, |seal| {
+      seal.events <= self.history.len() && seal.hash == Seal::hash(&self.history[..seal.events])
+    });
+
+    if intact {
+      self.seal = Some(Seal {
+        events: self.history.len(),
+        hash: Seal::hash(&self.history),
+      });
+    }
+  }
+
+  /// Check the integrity of the history of the [`Task`], pushing any issue to `issues`.
+  fn check_integrity(&self, uid: UID, issues: &mut Vec<IntegrityIssue>) {
+    // the whole history is sealed every time the tasks are saved
+    if let Some(ref seal) = self.seal {
+      if seal.events != self.history.len() || seal.hash != Seal::hash(&self.history) {
+        issues.push(IntegrityIssue::BrokenSeal(uid));
+      }
+    }
+
+    if !matches!(self.history().next(), Some(Event::Created(_))) {
+      issues.push(IntegrityIssue::MissingCreation(uid));
+    }
+
+    if self
+      .history
+      .windows(2)
+      .any(|entries| entries[0].event.date() > entries[1].event.date())
+    {
+      issues.push(IntegrityIssue::UnorderedHistory(uid));
+    }
+
+    let mut notes = 0;
+    let mut checklist_items = 0;
+    let mut reminders = 0;
+    for event in self.history() {
+      match event {
+        Event::NoteAdded {
+          in_reply_to: Some(note_uid),
+          ..
+        } if usize::from(*note_uid) >= notes => {
+          issues.push(IntegrityIssue::DanglingNote {
+            task_uid: uid,
+            note_uid: *note_uid,
+          });
+          notes += 1;
+        }
+
+        Event::NoteAdded { .. } => notes += 1,
+
+        Event::NoteReplaced { note_uid, .. } if usize::from(*note_uid) >= notes => {
+          issues.push(IntegrityIssue::DanglingNote {
+            task_uid: uid,
+            note_uid: *note_uid,
+          });
+        }
+
+        Event::ChecklistItemAdded { .. } => checklist_items += 1,
+
+        Event::ChecklistItemToggled { item_uid, .. }
+          if usize::from(*item_uid) >= checklist_items =>
+        {
+          issues.push(IntegrityIssue::DanglingChecklistItem {
+            task_uid: uid,
+            item_uid: *item_uid,
+          });
+        }
+
+        Event::ReminderAdded { .. } => reminders += 1,
+
+        Event::ReminderDelivered { reminder_uid, .. }
+          if usize::from(*reminder_uid) >= reminders =>
+        {
+          issues.push(IntegrityIssue::DanglingReminder {
+            task_uid: uid,
+            reminder_uid: *reminder_uid,
+          });
+        }
+
+        _ => (),
+      }
+    }
+  }
+
+  /// Replace the content of a note for a given [`Task`].
+  pub fn replace_note(&mut self, note_uid: UID, content: impl Into<String>) -> Result<(), Error> {
+    // ensure the note exists first
+    let mut count = 0;
+    let id: u32 = note_uid.into();
+    let previous_note = self.history().find(|event| match event {
+      Event::NoteAdded { .. } => {
+        if id == count {
+          true
+        } else {
+          count += 1;
           false
         }
       }
@@ -246,7 +1111,7 @@ impl Task {
       return Err(Error::UnknownNote(note_uid));
     }
 
-    self.history.push(Event::NoteReplaced {
+    self.record_event(Event::NoteReplaced {
       event_date: Utc::now(),
       note_uid,
       content: content.into(),
@@ -259,16 +1124,19 @@ impl Task {
   pub fn notes(&self) -> Vec<Note> {
     let mut notes = Vec::new();
 
-    for event in &self.history {
+    for (event, author) in self.authored_history() {
       match event {
         Event::NoteAdded {
           event_date,
           content,
+          in_reply_to,
         } => {
           let note = Note {
             creation_date: *event_date,
             last_modification_date: *event_date,
             content: content.clone(),
+            in_reply_to: *in_reply_to,
+            author: author.map(str::to_owned),
           };
           notes.push(note);
         }
@@ -291,38 +1159,367 @@ impl Task {
     notes
   }
 
+  /// Add a new item to the checklist of the [`Task`].
+  pub fn add_checklist_item(&mut self, content: impl Into<String>) {
+    self.record_event(Event::ChecklistItemAdded {
+      event_date: Utc::now(),
+      content: content.into(),
+    });
+  }
+
+  /// Check or uncheck an item of the checklist of the [`Task`].
+  pub fn toggle_checklist_item(&mut self, item_uid: UID) -> Result<(), Error> {
+    if usize::from(item_uid) >= self.checklist().len() {
+      // items are numbered from 1 when shown
+      return Err(Error::UnknownChecklistItem(UID(
+        item_uid.0.saturating_add(1),
+      )));
+    }
+
+    self.record_event(Event::ChecklistItemToggled {
+      event_date: Utc::now(),
+      item_uid,
+    });
+
+    Ok(())
+  }
+
+  /// Get the checklist, if any.
+  pub fn checklist(&self) -> Vec<ChecklistItem> {
+    let mut checklist = Vec::new();
+
+    for event in self.history() {
+      match event {
+        Event::ChecklistItemAdded { content, .. } => {
+          checklist.push(ChecklistItem {
+            content: content.clone(),
+            checked: false,
+          });
+        }
+
+        Event::ChecklistItemToggled { item_uid, .. } => {
+          if let Some(item) = checklist.get_mut(usize::from(*item_uid)) {
+            item.checked = !item.checked;
+          }
+        }
+
+        _ => (),
+      }
+    }
+
+    checklist
+  }
+
+  /// Get the progress of the checklist as the number of checked items and the total number of items.
+  ///
+  /// Returns [`None`] if the task doesn’t have a checklist.
+  pub fn checklist_progress(&self) -> Option<(usize, usize)> {
+    let checklist = self.checklist();
+
+    if checklist.is_empty() {
+      None
+    } else {
+      let checked = checklist.iter().filter(|item| item.checked).count();
+      Some((checked, checklist.len()))
+    }
+  }
+
+  /// Add a reminder about the [`Task`] at `date`.
+  pub fn add_reminder(&mut self, date: DateTime<Utc>) {
+    self.record_event(Event::ReminderAdded {
+      event_date: Utc::now(),
+      date,
+    });
+  }
+
+  /// Mark a reminder of the [`Task`] as delivered.
+  pub fn deliver_reminder(&mut self, reminder_uid: UID) -> Result<(), Error> {
+    if usize::from(reminder_uid) >= self.reminders().len() {
+      return Err(Error::UnknownReminder(reminder_uid));
+    }
+
+    self.record_event(Event::ReminderDelivered {
+      event_date: Utc::now(),
+      reminder_uid,
+    });
+
+    Ok(())
+  }
+
+  /// Get the reminders, delivered or not.
+  pub fn reminders(&self) -> Vec<Reminder> {
+    let mut reminders = Vec::new();
+
+    for event in self.history() {
+      match event {
+        Event::ReminderAdded { date, .. } => {
+          reminders.push(Reminder {
+            date: *date,
+            delivered: None,
+          });
+        }
+
+        Event::ReminderDelivered {
+          event_date,
+          reminder_uid,
+        } => {
+          if let Some(reminder) = reminders.get_mut(usize::from(*reminder_uid)) {
+            reminder.delivered = Some(*event_date);
+          }
+        }
+
+        _ => (),
+      }
+    }
+
+    reminders
+  }
+
+  /// Check whether the task has a reminder at `date`.
+  fn has_reminder(&self, date: DateTime<Utc>) -> bool {
+    self
+      .reminders()
+      .iter()
+      .any(|reminder| reminder.date == date)
+  }
+
   /// Iterate over the whole history, if any.
-  pub fn history(&self) -> impl Iterator<Item = &Event> {
-    self.history.iter()
+  pub fn history(&self) -> impl DoubleEndedIterator<Item = &Event> {
+    self.history.iter().map(|entry| &entry.event)
+  }
+
+  /// Iterate over the whole history, along with the author of each event, if known.
+  pub fn authored_history(&self) -> impl Iterator<Item = (&Event, Option<&str>)> {
+    self
+      .history
+      .iter()
+      .map(|entry| (&entry.event, entry.author.as_deref()))
+  }
+
+  /// Get the author of the [`Task`], i.e. the author of its creation, if known.
+  pub fn author(&self) -> Option<&str> {
+    self
+      .authored_history()
+      .next()
+      .and_then(|(_, author)| author)
+  }
+
+  /// Record an event in the history.
+  fn record_event(&mut self, event: Event) {
+    self.history.push(HistoryEntry::new(event));
+  }
+
+  /// Create a [`Task`] by appending `events` one by one, as with [`Task::push_event`].
+  ///
+  /// Integrations such as sync engines and importers use it to reproduce the exact history of a task; the first event
+  /// must be the creation of the task.
+  pub fn from_events(
+    name: impl Into<String>,
+    events: impl IntoIterator<Item = Event>,
+  ) -> Result<Self, EventError> {
+    let mut task = Task {
+      name: name.into(),
+      history: Vec::new(),
+      seal: None,
+    };
+
+    for event in events {
+      task.push_event(event)?;
+    }
+
+    if task.history.is_empty() {
+      return Err(EventError::MissingCreation);
+    }
+
+    Ok(task)
+  }
+
+  /// Append an event to the history, as is, rather than through the setters.
+  ///
+  /// The event is validated against the history, so that the task stays consistent: the history starts with a single
+  /// creation, events are in chronological order — events at the same date are fine — and the notes, checklist items
+  /// and reminders the event refers to exist.
+  pub fn push_event(&mut self, event: Event) -> Result<(), EventError> {
+    self.validate_event(&event)?;
+    self.record_event(event);
+    Ok(())
+  }
+
+  /// Check that `event` can be appended to the history.
+  fn validate_event(&self, event: &Event) -> Result<(), EventError> {
+    let last = match (self.history().next_back(), event) {
+      (None, Event::Created(_)) => return Ok(()),
+      (None, _) => return Err(EventError::MissingCreation),
+      (Some(_), Event::Created(_)) => return Err(EventError::DuplicateCreation),
+      (Some(last), _) => last,
+    };
+
+    if event.date() < last.date() {
+      return Err(EventError::Unordered {
+        date: *event.date(),
+        last: *last.date(),
+      });
+    }
+
+    let count = |counted: fn(&Event) -> bool| self.history().filter(|event| counted(event)).count();
+
+    match *event {
+      Event::NoteAdded {
+        in_reply_to: Some(note_uid),
+        ..
+      }
+      | Event::NoteReplaced { note_uid, .. }
+        if usize::from(note_uid) >= count(|event| matches!(event, Event::NoteAdded { .. })) =>
+      {
+        Err(EventError::UnknownNote(note_uid))
+      }
+
+      Event::ChecklistItemToggled { item_uid, .. }
+        if usize::from(item_uid)
+          >= count(|event| matches!(event, Event::ChecklistItemAdded { .. })) =>
+      {
+        Err(EventError::UnknownChecklistItem(item_uid))
+      }
+
+      Event::ReminderDelivered { reminder_uid, .. }
+        if usize::from(reminder_uid)
+          >= count(|event| matches!(event, Event::ReminderAdded { .. })) =>
+      {
+        Err(EventError::UnknownReminder(reminder_uid))
+      }
+
+      _ => Ok(()),
+    }
+  }
+
+  /// Stamp the events recorded since the [`Task`] was loaded with `author`.
+  fn stamp(&mut self, author: Option<&str>) {
+    for entry in self
+      .history
+      .iter_mut()
+      .filter(|entry| entry.pending.is_some())
+    {
+      entry.author = author.map(str::to_owned);
+      entry.pending = None;
+    }
+  }
+
+  /// Get the history along with the sequence number of each event.
+  ///
+  /// Sequence numbers are assigned when the tasks are saved and increase across all tasks, so that they order events
+  /// recorded at the same date; events recorded before they were introduced have `0`.
+  pub fn sequenced_history(&self) -> impl Iterator<Item = (&Event, u64)> {
+    self.history.iter().map(|entry| (&entry.event, entry.seq))
   }
 
   /// Compute the time spent on this task.
   pub fn spent_time(&self) -> Duration {
+    self.spent_time_since(chrono::MIN_DATETIME)
+  }
+
+  /// Compute the time spent on this task since `since`.
+  ///
+  /// WIP periods that started before `since` only count for their part after it. Manual adjustments count if they were
+  /// made after `since`; the spent time is never negative, though.
+  pub fn spent_time_since(&self, since: DateTime<Utc>) -> Duration {
+    let spent_between = |start: DateTime<Utc>, end: DateTime<Utc>| {
+      end
+        .signed_duration_since(start.max(since))
+        .max(Duration::zero())
+    };
+
     let (spent, last_wip) =
       self
-        .history
-        .iter()
+        .history()
         .fold((Duration::zero(), None), |(spent, last_wip), event| {
           match event {
             Event::StatusChanged { event_date, status } => match (status, last_wip) {
+              // We go from WIP to WIP again; keep counting from the first start
+              (Status::Ongoing, Some(last_wip)) => (spent, Some(last_wip)),
               // We go from any status to WIP status; return the spent time untouched and set the new “last_wip” with the
               // time at which the status change occurred
-              (Status::Ongoing, _) => (spent, Some(*event_date)),
+              (Status::Ongoing, None) => (spent, Some(*event_date)),
               // We go to anything but WIP while the previous status was WIP; accumulate.
-              (_, Some(last_wip)) => (spent + (event_date.signed_duration_since(last_wip)), None),
+              (_, Some(last_wip)) => (spent + spent_between(last_wip, *event_date), None),
               // We go between inactive status, ignore
               _ => (spent, last_wip),
             },
+            Event::TimeAdjusted {
+              event_date,
+              minutes,
+              ..
+            } if *event_date >= since => (spent + Duration::minutes(*minutes), last_wip),
             _ => (spent, last_wip),
           }
         });
 
-    if let Some(last_wip) = last_wip {
+    let spent = if let Some(last_wip) = last_wip {
       // last status was WIP; accumulate moaaar
-      spent + Utc::now().signed_duration_since(last_wip)
+      spent + spent_between(last_wip, Utc::now())
     } else {
       spent
+    };
+
+    spent.max(Duration::zero())
+  }
+
+  /// Adjust the time spent on this task, e.g. to account for work done without starting it.
+  ///
+  /// `duration` is negative to remove time.
+  pub fn adjust_spent_time(&mut self, duration: Duration, reason: Option<String>) {
+    self.record_event(Event::TimeAdjusted {
+      event_date: Utc::now(),
+      minutes: duration.num_minutes(),
+      reason,
+    });
+  }
+
+  /// Timesheet of this task: the periods it was ongoing and the manual adjustments of its spent time, in chronological
+  /// order.
+  pub fn time_entries(&self) -> Vec<TimeEntry> {
+    let mut entries = Vec::new();
+    let mut last_wip = None;
+
+    for event in self.history() {
+      match event {
+        Event::StatusChanged { event_date, status } => match (status, last_wip) {
+          (Status::Ongoing, None) => last_wip = Some(*event_date),
+          (Status::Ongoing, Some(_)) => (),
+          (_, Some(start)) => {
+            entries.push(TimeEntry::Period {
+              start,
+              end: Some(*event_date),
+            });
+            last_wip = None;
+          }
+          _ => (),
+        },
+
+        Event::TimeAdjusted {
+          event_date,
+          minutes,
+          reason,
+        } => entries.push(TimeEntry::Adjustment {
+          date: *event_date,
+          duration: Duration::minutes(*minutes),
+          reason: reason.clone(),
+        }),
+
+        _ => (),
+      }
+    }
+
+    if let Some(start) = last_wip {
+      entries.push(TimeEntry::Period { start, end: None });
     }
+
+    // periods are only known once they end; they come first when they start along with an adjustment
+    entries.sort_by_key(|entry| match *entry {
+      TimeEntry::Period { start, .. } => (start, false),
+      TimeEntry::Adjustment { date, .. } => (date, true),
+    });
+
+    entries
   }
 
   /// Mark this task as part of the input project.
@@ -330,7 +1527,7 @@ impl Task {
   /// If a project was already present, this method overrides it. Passing an empty string puts that task into the
   /// _orphaned_ project.
   pub fn set_project(&mut self, project: impl Into<String>) {
-    self.history.push(Event::SetProject {
+    self.record_event(Event::SetProject {
       event_date: Utc::now(),
       project: project.into(),
     });
@@ -340,15 +1537,140 @@ impl Task {
   ///
   /// If a priority was already set, this method overrides it. Passing [`None`] removes the priority.
   pub fn set_priority(&mut self, priority: Priority) {
-    self.history.push(Event::SetPriority {
+    self.record_event(Event::SetPriority {
       event_date: Utc::now(),
       priority,
     });
   }
 
+  /// Schedule this task; i.e. set the date at which work on it should start.
+  ///
+  /// If the task was already scheduled, this method overrides it.
+  pub fn set_scheduled(&mut self, scheduled: DateTime<Utc>) {
+    self.record_event(Event::SetScheduled {
+      event_date: Utc::now(),
+      scheduled,
+    });
+  }
+
+  /// Set the deadline of this task.
+  ///
+  /// If a deadline was already set, this method overrides it.
+  pub fn set_deadline(&mut self, deadline: DateTime<Utc>) {
+    self.record_event(Event::SetDeadline {
+      event_date: Utc::now(),
+      deadline,
+    });
+  }
+
+  /// Set the recurrence of this task.
+  ///
+  /// If the task was already recurring, this method overrides it.
+  pub fn set_recurrence(&mut self, recurrence: Recurrence) {
+    self.record_event(Event::SetRecurrence {
+      event_date: Utc::now(),
+      recurrence,
+    });
+  }
+
+  /// Set the context in which this task can be done.
+  ///
+  /// If a context was already set, this method overrides it.
+  pub fn set_context(&mut self, context: impl Into<String>) {
+    self.record_event(Event::SetContext {
+      event_date: Utc::now(),
+      context: context.into(),
+    });
+  }
+
+  /// Set the milestone of the project this task is part of.
+  ///
+  /// If a milestone was already set, this method overrides it.
+  pub fn set_milestone(&mut self, milestone: impl Into<String>) {
+    self.record_event(Event::SetMilestone {
+      event_date: Utc::now(),
+      milestone: milestone.into(),
+    });
+  }
+
+  /// Set the ID of this task in the tool it was imported from.
+  pub fn set_external_id(&mut self, external_id: impl Into<String>) {
+    self.record_event(Event::SetExternalId {
+      event_date: Utc::now(),
+      external_id: external_id.into(),
+    });
+  }
+
+  /// Set the value of the user-defined field `name`.
+  ///
+  /// If the field was already set, this method overrides it.
+  pub fn set_field(&mut self, name: impl Into<String>, value: FieldValue) {
+    self.record_event(Event::SetField {
+      event_date: Utc::now(),
+      name: name.into(),
+      value,
+    });
+  }
+
+  /// Move this task to, or out of, the someday backlog.
+  pub fn set_someday(&mut self, someday: bool) {
+    self.record_event(Event::SetSomeday {
+      event_date: Utc::now(),
+      someday,
+    });
+  }
+
+  /// Pin this task at the top of listings, or unpin it.
+  pub fn set_pinned(&mut self, pinned: bool) {
+    self.record_event(Event::SetPinned {
+      event_date: Utc::now(),
+      pinned,
+    });
+  }
+
+  /// Link this task to a goal, replacing the goal it was linked to, if any.
+  pub fn set_goal(&mut self, goal: UID) {
+    self.record_event(Event::SetGoal {
+      event_date: Utc::now(),
+      goal,
+    });
+  }
+
+  /// Record that this task has been reviewed.
+  pub fn review(&mut self) {
+    self.record_event(Event::Reviewed {
+      event_date: Utc::now(),
+    });
+  }
+
+  /// Snooze this task for `duration`, pushing its schedule forward.
+  ///
+  /// The schedule is pushed from now, or from the current schedule if it is in the future. Return the new schedule.
+  pub fn snooze(&mut self, duration: Duration) -> DateTime<Utc> {
+    let now = Utc::now();
+    let until = self.scheduled().map_or(now, |scheduled| scheduled.max(now)) + duration;
+
+    self.record_event(Event::Snoozed {
+      event_date: now,
+      until,
+    });
+
+    until
+  }
+
+  /// Set the estimated effort of this task, to the minute.
+  ///
+  /// If an effort was already estimated, this method overrides it.
+  pub fn set_effort(&mut self, effort: Duration) {
+    self.record_event(Event::SetEffort {
+      event_date: Utc::now(),
+      minutes: effort.num_minutes().max(0) as u32,
+    });
+  }
+
   /// Add a tag to task.
   pub fn add_tag(&mut self, tag: impl Into<String>) {
-    self.history.push(Event::AddTag {
+    self.record_event(Event::AddTag {
       event_date: Utc::now(),
       tag: tag.into(),
     });
@@ -361,6 +1683,14 @@ impl Task {
         Metadata::Project(project) => self.set_project(project),
         Metadata::Priority(priority) => self.set_priority(priority),
         Metadata::Tag(tag) => self.add_tag(tag),
+        Metadata::Effort(effort) => self.set_effort(effort),
+        Metadata::Context(context) => self.set_context(context),
+        Metadata::Milestone(milestone) => self.set_milestone(milestone),
+        Metadata::Reminder(date) => self.add_reminder(date),
+        Metadata::ExternalId(id) => self.set_external_id(id),
+        Metadata::Field(name, value) => self.set_field(name, value),
+        // authors are stamped when saving, and activity is recorded by the events themselves
+        Metadata::Author(_) | Metadata::Inactive(..) => (),
       }
     }
   }
@@ -371,19 +1701,54 @@ impl Task {
     metadata: impl IntoIterator<Item = &'a Metadata>,
     case_insensitive: bool,
   ) -> bool {
+    let in_project = |project| {
+      self
+        .project()
+        .is_some_and(|own| metadata::is_in_project(own, project, case_insensitive))
+    };
+
     if case_insensitive {
-      let own_project = self.project().map(UniCase::new);
       let own_tags = self.tags().map(UniCase::new).collect::<Vec<_>>();
       metadata.into_iter().all(|md| match md {
-        Metadata::Project(ref project) => own_project == Some(UniCase::new(project)),
-        Metadata::Priority(priority) => self.priority() == Some(*priority),
+        Metadata::Project(ref project) => in_project(project),
+        Metadata::Priority(priority) => self.priority() == Some(priority),
         Metadata::Tag(ref tag) => own_tags.contains(&UniCase::new(tag)),
+        Metadata::Effort(effort) => self.effort() == Some(*effort),
+        Metadata::Context(ref context) => {
+          self.context().map(UniCase::new) == Some(UniCase::new(context))
+        }
+        Metadata::Milestone(ref milestone) => {
+          self.milestone().map(UniCase::new) == Some(UniCase::new(milestone))
+        }
+        Metadata::Reminder(date) => self.has_reminder(*date),
+        Metadata::ExternalId(ref id) => {
+          self.external_id().map(UniCase::new) == Some(UniCase::new(id))
+        }
+        Metadata::Field(ref name, FieldValue::String(ref s)) => {
+          self.field(name).is_some_and(|value| match value {
+            FieldValue::String(own) => UniCase::new(own) == UniCase::new(s),
+            _ => false,
+          })
+        }
+        Metadata::Field(ref name, ref value) => self.field(name) == Some(value),
+        Metadata::Author(ref author) => {
+          self.author().map(UniCase::new) == Some(UniCase::new(author))
+        }
+        Metadata::Inactive(ordering, duration) => self.inactivity().cmp(duration) == *ordering,
       })
     } else {
       metadata.into_iter().all(|md| match md {
-        Metadata::Project(ref project) => self.project() == Some(project),
-        Metadata::Priority(priority) => self.priority() == Some(*priority),
+        Metadata::Project(ref project) => in_project(project),
+        Metadata::Priority(priority) => self.priority() == Some(priority),
         Metadata::Tag(ref tag) => self.tags().any(|t| t == tag),
+        Metadata::Effort(effort) => self.effort() == Some(*effort),
+        Metadata::Context(ref context) => self.context() == Some(context),
+        Metadata::Milestone(ref milestone) => self.milestone() == Some(milestone),
+        Metadata::Reminder(date) => self.has_reminder(*date),
+        Metadata::ExternalId(ref id) => self.external_id() == Some(id),
+        Metadata::Field(ref name, ref value) => self.field(name) == Some(value),
+        Metadata::Author(ref author) => self.author() == Some(author),
+        Metadata::Inactive(ordering, duration) => self.inactivity().cmp(duration) == *ordering,
       })
     }
   }
@@ -391,38 +1756,297 @@ impl Task {
   /// Get the current project.
   pub fn project(&self) -> Option<&str> {
     self
-      .history
-      .iter()
+      .history()
       .filter_map(|event| match event {
         Event::SetProject { ref project, .. } => Some(project.as_str()),
         _ => None,
       })
-      .last()
+      .next_back()
   }
 
-  /// Get the current project.
-  pub fn priority(&self) -> Option<Priority> {
+  /// Get the current context.
+  pub fn context(&self) -> Option<&str> {
     self
-      .history
-      .iter()
+      .history()
       .filter_map(|event| match event {
-        Event::SetPriority { priority, .. } => Some(*priority),
+        Event::SetContext { ref context, .. } => Some(context.as_str()),
         _ => None,
       })
-      .last()
+      .next_back()
+  }
+
+  /// Get the current milestone.
+  pub fn milestone(&self) -> Option<&str> {
+    self
+      .history()
+      .filter_map(|event| match event {
+        Event::SetMilestone { ref milestone, .. } => Some(milestone.as_str()),
+        _ => None,
+      })
+      .next_back()
+  }
+
+  /// Get the ID of this task in the tool it was imported from, if any.
+  pub fn external_id(&self) -> Option<&str> {
+    self
+      .history()
+      .filter_map(|event| match event {
+        Event::SetExternalId {
+          ref external_id, ..
+        } => Some(external_id.as_str()),
+        _ => None,
+      })
+      .next_back()
+  }
+
+  /// Get the current value of the user-defined field `name`, if set.
+  pub fn field(&self, name: &str) -> Option<&FieldValue> {
+    self
+      .history()
+      .filter_map(|event| match event {
+        Event::SetField {
+          name: ref n,
+          ref value,
+          ..
+        } if n == name => Some(value),
+        _ => None,
+      })
+      .next_back()
+  }
+
+  /// Get the current values of all the user-defined fields set on this task, by order of first definition.
+  pub fn fields(&self) -> Vec<(&str, &FieldValue)> {
+    let mut fields: Vec<(&str, &FieldValue)> = Vec::new();
+
+    for event in self.history() {
+      if let Event::SetField {
+        ref name,
+        ref value,
+        ..
+      } = event
+      {
+        match fields.iter_mut().find(|(n, _)| n == name) {
+          Some(field) => field.1 = value,
+          None => fields.push((name, value)),
+        }
+      }
+    }
+
+    fields
+  }
+
+  /// Get the current priority.
+  pub fn priority(&self) -> Option<&Priority> {
+    self
+      .history()
+      .filter_map(|event| match event {
+        Event::SetPriority { priority, .. } => Some(priority),
+        _ => None,
+      })
+      .next_back()
+  }
+
+  /// Get the estimated effort of the task, if any.
+  pub fn effort(&self) -> Option<Duration> {
+    self
+      .history()
+      .filter_map(|event| match event {
+        Event::SetEffort { minutes, .. } => Some(Duration::minutes((*minutes).into())),
+        _ => None,
+      })
+      .next_back()
+  }
+
+  /// Get the effort still required to complete the task, i.e. its estimated effort minus the time spent on it.
+  pub fn remaining_effort(&self) -> Option<Duration> {
+    self
+      .effort()
+      .map(|effort| (effort - self.spent_time()).max(Duration::zero()))
+  }
+
+  /// Get the date at which the task is scheduled, if any.
+  ///
+  /// Snoozing a task reschedules it.
+  pub fn scheduled(&self) -> Option<DateTime<Utc>> {
+    self
+      .history()
+      .filter_map(|event| match event {
+        Event::SetScheduled { scheduled, .. } => Some(*scheduled),
+        Event::Snoozed { until, .. } => Some(*until),
+        _ => None,
+      })
+      .next_back()
+  }
+
+  /// Check whether the task is in the someday backlog.
+  pub fn is_someday(&self) -> bool {
+    self
+      .history()
+      .filter_map(|event| match event {
+        Event::SetSomeday { someday, .. } => Some(*someday),
+        _ => None,
+      })
+      .next_back()
+      .unwrap_or(false)
+  }
+
+  /// Check whether the task is pinned at the top of listings.
+  pub fn is_pinned(&self) -> bool {
+    self
+      .history()
+      .filter_map(|event| match event {
+        Event::SetPinned { pinned, .. } => Some(*pinned),
+        _ => None,
+      })
+      .next_back()
+      .unwrap_or(false)
+  }
+
+  /// Get the goal the task is linked to, if any.
+  pub fn goal(&self) -> Option<UID> {
+    self
+      .history()
+      .filter_map(|event| match event {
+        Event::SetGoal { goal, .. } => Some(*goal),
+        _ => None,
+      })
+      .next_back()
+  }
+
+  /// Get the date until which the task is snoozed, if it was ever snoozed.
+  pub fn snoozed_until(&self) -> Option<DateTime<Utc>> {
+    self
+      .history()
+      .filter_map(|event| match event {
+        Event::Snoozed { until, .. } => Some(*until),
+        _ => None,
+      })
+      .next_back()
+  }
+
+  /// Check whether the task is still snoozed at `date`.
+  pub fn is_snoozed(&self, date: DateTime<Utc>) -> bool {
+    self.snoozed_until().is_some_and(|until| until > date)
+  }
+
+  /// Get the deadline of the task, if any.
+  pub fn deadline(&self) -> Option<DateTime<Utc>> {
+    self
+      .history()
+      .filter_map(|event| match event {
+        Event::SetDeadline { deadline, .. } => Some(*deadline),
+        _ => None,
+      })
+      .next_back()
+  }
+
+  /// Get the recurrence of the task, if any.
+  pub fn recurrence(&self) -> Option<Recurrence> {
+    self
+      .history()
+      .filter_map(|event| match event {
+        Event::SetRecurrence { recurrence, .. } => Some(*recurrence),
+        _ => None,
+      })
+      .next_back()
+  }
+
+  /// Create the next occurrence of a recurring task.
+  ///
+  /// The next occurrence has the same name and metadata. Its schedule and deadline are moved forward by as many
+  /// recurrence periods as needed to be in the future; if the task had neither, it is scheduled one period from now.
+  ///
+  /// Return [`None`] if the task is not recurring, or if its next occurrence would be out of the range of dates.
+  pub fn next_occurrence(&self) -> Option<Task> {
+    let recurrence = self.recurrence()?;
+    let now = Utc::now();
+
+    let mut task = Task::new(self.name.clone());
+    task.apply_metadata(self.project().map(Metadata::project));
+    task.apply_metadata(self.priority().cloned().map(Metadata::priority));
+    task.apply_metadata(self.context().map(Metadata::context));
+    task.apply_metadata(self.milestone().map(Metadata::milestone));
+    task.apply_metadata(self.tags().map(Metadata::tag).collect::<Vec<_>>());
+    task.apply_metadata(
+      self
+        .fields()
+        .into_iter()
+        .map(|(name, value)| Metadata::field(name, value.clone()))
+        .collect::<Vec<_>>(),
+    );
+    task.set_recurrence(recurrence);
+
+    if let Some(effort) = self.effort() {
+      task.set_effort(effort);
+    }
+
+    let (scheduled, deadline) = match (self.scheduled(), self.deadline()) {
+      (None, None) => (Some(now), None),
+      dates => dates,
+    };
+
+    // the anchor is the date that must end up in the future
+    let anchor = scheduled.or(deadline)?;
+    let mut periods = 1;
+    while recurrence.advance(anchor, periods)? <= now {
+      periods = periods.checked_add(1)?;
+    }
+
+    if let Some(scheduled) = scheduled {
+      task.set_scheduled(recurrence.advance(scheduled, periods)?);
+    }
+
+    if let Some(deadline) = deadline {
+      task.set_deadline(recurrence.advance(deadline, periods)?);
+    }
+
+    Some(task)
+  }
+
+  /// Rename a tag of the task.
+  pub fn rename_tag(&mut self, tag: impl Into<String>, new_tag: impl Into<String>) {
+    self.record_event(Event::RenameTag {
+      event_date: Utc::now(),
+      tag: tag.into(),
+      new_tag: new_tag.into(),
+    });
   }
 
   /// Get the current tags of a task.
   pub fn tags(&self) -> impl Iterator<Item = &str> {
-    self.history.iter().filter_map(|event| match event {
-      Event::AddTag { ref tag, .. } => Some(tag.as_str()),
-      _ => None,
-    })
+    let mut tags = Vec::new();
+
+    for event in self.history() {
+      match event {
+        Event::AddTag { ref tag, .. } => tags.push(tag.as_str()),
+
+        // a renamed tag takes the place of its first occurrence, unless the task already has the new tag
+        Event::RenameTag {
+          ref tag,
+          ref new_tag,
+          ..
+        } if tag != new_tag => {
+          if let Some(i) = tags.iter().position(|t| t == tag) {
+            if !tags.contains(&new_tag.as_str()) {
+              tags[i] = new_tag.as_str();
+            }
+
+            tags.retain(|t| t != tag);
+          }
+        }
+
+        _ => (),
+      }
+    }
+
+    tags.into_iter()
   }
 }
 
 /// Unique identifier.
-#[derive(Clone, Copy, Debug, Deserialize, Hash, Eq, Ord, PartialEq, PartialOrd, Serialize)]
+#[derive(
+  Clone, Copy, Debug, Default, Deserialize, Hash, Eq, Ord, PartialEq, PartialOrd, Serialize,
+)]
 pub struct UID(u32);
 
 impl UID {
@@ -431,7 +2055,7 @@ impl UID {
   }
 
   pub fn dec(self) -> Self {
-    Self(self.0.checked_sub(1).unwrap_or(0))
+    Self(self.0.saturating_sub(1))
   }
 }
 
@@ -447,12 +2071,6 @@ impl From<UID> for usize {
   }
 }
 
-impl Default for UID {
-  fn default() -> Self {
-    UID(0)
-  }
-}
-
 impl FromStr for UID {
   type Err = <u32 as FromStr>::Err;
 
@@ -486,12 +2104,26 @@ pub enum Status {
   ///
   /// Users will typically have "CANCELLED", "WONTFIX", etc.
   Cancelled,
+  /// A “paused” state.
+  ///
+  /// The task was started and interrupted: its spent time is kept but doesn’t accumulate anymore.
+  Paused,
+}
+
+impl Status {
+  /// Whether a task with this status is still to be worked on, as opposed to done or cancelled.
+  pub fn is_active(self) -> bool {
+    matches!(self, Status::Todo | Status::Ongoing | Status::Paused)
+  }
 }
 
 /// Task event.
 ///
 /// Such events occurred when a change is made to a task (created, edited, scheduled, state
 /// changed, etc.).
+///
+/// Events are usually recorded by the setters of [`Task`]; integrations reproducing the history of a task from another
+/// tool can build them and append them with [`Task::push_event`], or create the task with [`Task::from_events`].
 #[derive(Clone, Debug, Deserialize, Eq, PartialEq, Serialize)]
 pub enum Event {
   /// Event generated when a task is created.
@@ -503,10 +2135,12 @@ pub enum Event {
     status: Status,
   },
 
-  /// Event generated when a note is added to a task.
+  /// Event generated when a note is added to a task, possibly in reply to another note.
   NoteAdded {
     event_date: DateTime<Utc>,
     content: String,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    in_reply_to: Option<UID>,
   },
 
   /// Event generated when a note is replaced in a task.
@@ -528,11 +2162,195 @@ pub enum Event {
     priority: Priority,
   },
 
+  /// Event generated when a context is set on a task.
+  SetContext {
+    event_date: DateTime<Utc>,
+    context: String,
+  },
+
+  /// Event generated when a milestone is set on a task.
+  SetMilestone {
+    event_date: DateTime<Utc>,
+    milestone: String,
+  },
+
   /// Event generated when a tag is added to a task.
   AddTag {
     event_date: DateTime<Utc>,
     tag: String,
   },
+
+  /// Event generated when a tag of a task is renamed, such as when normalizing tags.
+  RenameTag {
+    event_date: DateTime<Utc>,
+    tag: String,
+    new_tag: String,
+  },
+
+  /// Event generated when a task is scheduled.
+  SetScheduled {
+    event_date: DateTime<Utc>,
+    scheduled: DateTime<Utc>,
+  },
+
+  /// Event generated when a deadline is set on a task.
+  SetDeadline {
+    event_date: DateTime<Utc>,
+    deadline: DateTime<Utc>,
+  },
+
+  /// Event generated when a recurrence is set on a task.
+  SetRecurrence {
+    event_date: DateTime<Utc>,
+    recurrence: Recurrence,
+  },
+
+  /// Event generated when the effort of a task is estimated.
+  SetEffort {
+    event_date: DateTime<Utc>,
+    minutes: u32,
+  },
+
+  /// Event generated when a task is snoozed; it is rescheduled and hidden from listings until then.
+  Snoozed {
+    event_date: DateTime<Utc>,
+    until: DateTime<Utc>,
+  },
+
+  /// Event generated when a task is moved to, or out of, the someday backlog.
+  SetSomeday {
+    event_date: DateTime<Utc>,
+    someday: bool,
+  },
+
+  /// Event generated when a task is pinned at the top of listings, or unpinned.
+  SetPinned {
+    event_date: DateTime<Utc>,
+    pinned: bool,
+  },
+
+  /// Event generated when a task is linked to a goal.
+  SetGoal {
+    event_date: DateTime<Utc>,
+    goal: UID,
+  },
+
+  /// Event generated when a task of the someday backlog is reviewed and kept there.
+  Reviewed { event_date: DateTime<Utc> },
+
+  /// Event generated when a task is imported from another tool, recording its ID there.
+  SetExternalId {
+    event_date: DateTime<Utc>,
+    external_id: String,
+  },
+
+  /// Event generated when a task is moved to the trash.
+  Removed { event_date: DateTime<Utc> },
+
+  /// Event generated when a task is restored from the trash.
+  Restored { event_date: DateTime<Utc> },
+
+  /// Event generated when a reminder is added to a task.
+  ReminderAdded {
+    event_date: DateTime<Utc>,
+    date: DateTime<Utc>,
+  },
+
+  /// Event generated when a reminder of a task is delivered.
+  ReminderDelivered {
+    event_date: DateTime<Utc>,
+    reminder_uid: UID,
+  },
+
+  /// Event generated when an item is added to the checklist of a task.
+  ChecklistItemAdded {
+    event_date: DateTime<Utc>,
+    content: String,
+  },
+
+  /// Event generated when an item of the checklist of a task is checked or unchecked.
+  ChecklistItemToggled {
+    event_date: DateTime<Utc>,
+    item_uid: UID,
+  },
+
+  /// Event generated when a user-defined field is set.
+  SetField {
+    event_date: DateTime<Utc>,
+    name: String,
+    value: FieldValue,
+  },
+
+  /// Event generated when the spent time of a task is manually adjusted; `minutes` is negative when time is removed.
+  TimeAdjusted {
+    event_date: DateTime<Utc>,
+    minutes: i64,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    reason: Option<String>,
+  },
+}
+
+impl Event {
+  /// Get the date at which the event occurred.
+  pub fn date(&self) -> &DateTime<Utc> {
+    match self {
+      Event::Created(event_date)
+      | Event::StatusChanged { event_date, .. }
+      | Event::NoteAdded { event_date, .. }
+      | Event::NoteReplaced { event_date, .. }
+      | Event::SetProject { event_date, .. }
+      | Event::SetPriority { event_date, .. }
+      | Event::SetContext { event_date, .. }
+      | Event::SetMilestone { event_date, .. }
+      | Event::AddTag { event_date, .. }
+      | Event::RenameTag { event_date, .. }
+      | Event::SetScheduled { event_date, .. }
+      | Event::SetDeadline { event_date, .. }
+      | Event::SetRecurrence { event_date, .. }
+      | Event::SetEffort { event_date, .. }
+      | Event::Snoozed { event_date, .. }
+      | Event::SetSomeday { event_date, .. }
+      | Event::SetPinned { event_date, .. }
+      | Event::SetGoal { event_date, .. }
+      | Event::Reviewed { event_date }
+      | Event::SetExternalId { event_date, .. }
+      | Event::Removed { event_date }
+      | Event::Restored { event_date }
+      | Event::ReminderAdded { event_date, .. }
+      | Event::ReminderDelivered { event_date, .. }
+      | Event::ChecklistItemAdded { event_date, .. }
+      | Event::ChecklistItemToggled { event_date, .. }
+      | Event::SetField { event_date, .. }
+      | Event::TimeAdjusted { event_date, .. } => event_date,
+    }
+  }
+}
+
+/// Entry of the timesheet of a task.
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub enum TimeEntry {
+  /// Period during which the task was ongoing; `end` is [`None`] if it still is.
+  Period {
+    start: DateTime<Utc>,
+    end: Option<DateTime<Utc>>,
+  },
+
+  /// Manual adjustment of the spent time; `duration` is negative when time was removed.
+  Adjustment {
+    date: DateTime<Utc>,
+    duration: Duration,
+    reason: Option<String>,
+  },
+}
+
+impl TimeEntry {
+  /// Time accounted for by this entry.
+  pub fn duration(&self) -> Duration {
+    match *self {
+      TimeEntry::Period { start, end } => end.unwrap_or_else(Utc::now) - start,
+      TimeEntry::Adjustment { duration, .. } => duration,
+    }
+  }
 }
 
 /// A note.
@@ -541,4 +2359,1635 @@ pub struct Note {
   pub creation_date: DateTime<Utc>,
   pub last_modification_date: DateTime<Utc>,
   pub content: String,
+  /// UID of the note this note replies to, if any.
+  pub in_reply_to: Option<UID>,
+  /// Author of the note, if known.
+  pub author: Option<String>,
+}
+
+impl Note {
+  /// Number of words of the note.
+  pub fn words(&self) -> usize {
+    self.content.split_whitespace().count()
+  }
+
+  /// Number of lines of the note, leading and trailing blank lines excepted.
+  pub fn lines(&self) -> usize {
+    self.content.trim().lines().count()
+  }
+
+  /// Order notes by thread: each note is followed by its replies, recursively.
+  ///
+  /// Return the index of each note along with its depth in its thread. Notes replying to a note that doesn’t precede
+  /// them start a new thread.
+  pub fn threads(notes: &[Note]) -> Vec<(usize, usize)> {
+    fn visit(replies: &[Vec<usize>], note: usize, depth: usize, threads: &mut Vec<(usize, usize)>) {
+      threads.push((note, depth));
+
+      for &reply in &replies[note] {
+        visit(replies, reply, depth + 1, threads);
+      }
+    }
+
+    let mut roots = Vec::new();
+    let mut replies = vec![Vec::new(); notes.len()];
+    for (i, note) in notes.iter().enumerate() {
+      match note.in_reply_to.map(usize::from) {
+        Some(parent) if parent < i => replies[parent].push(i),
+        _ => roots.push(i),
+      }
+    }
+
+    let mut threads = Vec::with_capacity(notes.len());
+    for root in roots {
+      visit(&replies, root, 0, &mut threads);
+    }
+
+    threads
+  }
+}
+
+/// A checklist item.
+#[derive(Debug, Clone, Eq, PartialEq)]
+pub struct ChecklistItem {
+  pub content: String,
+  pub checked: bool,
+}
+
+/// Outcome of the import of a task; see [`TaskManager::import_tasks`].
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct ImportedTask {
+  /// ID of the task in the tool it was imported from, if any.
+  pub external_id: Option<String>,
+  /// UID of the task; for a duplicate, the UID of the task previously imported.
+  pub uid: UID,
+  /// Whether the task was already imported, and then skipped.
+  pub duplicate: bool,
+}
+
+/// A reminder about a task.
+#[derive(Debug, Clone, Eq, PartialEq)]
+pub struct Reminder {
+  /// Date at which the reminder is due.
+  pub date: DateTime<Utc>,
+  /// Date at which the reminder was delivered, if it was.
+  pub delivered: Option<DateTime<Utc>>,
+}
+
+/// How often a task recurs.
+#[derive(Clone, Copy, Debug, Eq, Hash, PartialEq, Serialize, Deserialize)]
+pub enum Recurrence {
+  Days(u32),
+  Weeks(u32),
+  Months(u32),
+  Years(u32),
+}
+
+impl Recurrence {
+  /// Move a date forward by `periods` recurrence periods.
+  ///
+  /// When adding months or years, the day is clamped to the last day of the resulting month.
+  ///
+  /// Return [`None`] if the resulting date is out of range.
+  pub fn advance(self, date: DateTime<Utc>, periods: u32) -> Option<DateTime<Utc>> {
+    match self {
+      Recurrence::Days(n) => {
+        date.checked_add_signed(Duration::days(i64::from(n.checked_mul(periods)?)))
+      }
+      Recurrence::Weeks(n) => {
+        date.checked_add_signed(Duration::weeks(i64::from(n.checked_mul(periods)?)))
+      }
+      Recurrence::Months(n) => add_months(date, n.checked_mul(periods)?),
+      Recurrence::Years(n) => add_months(date, n.checked_mul(12)?.checked_mul(periods)?),
+    }
+  }
+}
+
+impl fmt::Display for Recurrence {
+  fn fmt(&self, f: &mut fmt::Formatter) -> Result<(), fmt::Error> {
+    let (n, unit) = match *self {
+      Recurrence::Days(n) => (n, "day"),
+      Recurrence::Weeks(n) => (n, "week"),
+      Recurrence::Months(n) => (n, "month"),
+      Recurrence::Years(n) => (n, "year"),
+    };
+
+    if n == 1 {
+      write!(f, "every {}", unit)
+    } else {
+      write!(f, "every {} {}s", n, unit)
+    }
+  }
+}
+
+/// Add months to a date, clamping the day to the last day of the resulting month.
+///
+/// Return [`None`] if the resulting year is out of range.
+pub(crate) fn add_months<D>(date: D, months: u32) -> Option<D>
+where
+  D: Datelike,
+{
+  let months = date.month0().checked_add(months)?;
+  let year = date.year().checked_add((months / 12) as i32)?;
+  let month = months % 12 + 1;
+
+  // if the year is valid, the first day always exists, so the last valid day of the month is eventually found
+  (1..=date.day()).rev().find_map(|day| {
+    date
+      .with_day(1)
+      .and_then(|d| d.with_year(year))
+      .and_then(|d| d.with_month(month))
+      .and_then(|d| d.with_day(day))
+  })
+}
+
+/// Tasks suggested by [`TaskManager::plan`].
+#[derive(Debug)]
+pub struct Plan<'a> {
+  /// Tasks to work on, in order, along with their remaining effort.
+  pub planned: Vec<(UID, &'a Task, Duration)>,
+  /// Candidate tasks that don’t fit in the capacity left, along with their remaining effort.
+  pub left_out: Vec<(UID, &'a Task, Duration)>,
+  /// Candidate tasks without estimated effort.
+  pub unestimated: Vec<(UID, &'a Task)>,
+  /// Capacity left once the planned tasks are done.
+  pub free: Duration,
+}
+
+impl Default for Plan<'_> {
+  fn default() -> Self {
+    Self {
+      planned: Vec::new(),
+      left_out: Vec::new(),
+      unestimated: Vec::new(),
+      free: Duration::zero(),
+    }
+  }
+}
+
+/// Totals of a list of tasks.
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub struct Summary {
+  /// Number of tasks to do.
+  pub todo: usize,
+  /// Number of ongoing tasks.
+  pub ongoing: usize,
+  /// Number of paused tasks.
+  pub paused: usize,
+  /// Number of done tasks.
+  pub done: usize,
+  /// Number of cancelled tasks.
+  pub cancelled: usize,
+  /// Number of active tasks whose deadline is passed.
+  pub overdue: usize,
+  /// Time spent on the tasks since the start of the summarized period.
+  pub spent: Duration,
+}
+
+impl Summary {
+  /// Summarize `tasks` at `now`, accounting for the time spent on them since `since`.
+  pub fn new<'a>(
+    tasks: impl IntoIterator<Item = &'a Task>,
+    now: DateTime<Utc>,
+    since: DateTime<Utc>,
+  ) -> Self {
+    let mut summary = Summary {
+      todo: 0,
+      ongoing: 0,
+      paused: 0,
+      done: 0,
+      cancelled: 0,
+      overdue: 0,
+      spent: Duration::zero(),
+    };
+
+    for task in tasks {
+      let status = task.status();
+
+      match status {
+        Status::Todo => summary.todo += 1,
+        Status::Ongoing => summary.ongoing += 1,
+        Status::Done => summary.done += 1,
+        Status::Cancelled => summary.cancelled += 1,
+        Status::Paused => summary.paused += 1,
+      }
+
+      if status.is_active() && task.deadline().is_some_and(|deadline| deadline < now) {
+        summary.overdue += 1;
+      }
+
+      summary.spent = summary.spent + task.spent_time_since(since);
+    }
+
+    summary
+  }
+}
+
+/// Project hierarchy, built from the dotted names of projects, such as `work.backend.api`.
+#[derive(Clone, Debug, Default, Eq, PartialEq)]
+pub struct ProjectTree {
+  /// Number of tasks in the project, including the ones in its subprojects.
+  pub count: usize,
+  /// Subprojects, by name.
+  pub children: BTreeMap<String, ProjectTree>,
+}
+
+impl ProjectTree {
+  /// Build the hierarchy of the projects of `tasks`.
+  ///
+  /// The root of the hierarchy has no name and counts the tasks with a project.
+  pub fn new<'a>(tasks: impl IntoIterator<Item = &'a Task>) -> Self {
+    let mut root = ProjectTree::default();
+
+    for project in tasks.into_iter().filter_map(Task::project) {
+      root.count += 1;
+
+      let mut node = &mut root;
+      for name in project.split(PROJECT_SEPARATOR) {
+        node = node.children.entry(name.to_owned()).or_default();
+        node.count += 1;
+      }
+    }
+
+    root
+  }
+}
+
+/// Number of open and done tasks of a milestone.
+#[derive(Clone, Copy, Debug, Default, Eq, PartialEq)]
+pub struct MilestoneCount {
+  /// Number of tasks neither done nor cancelled.
+  pub open: usize,
+  /// Number of done tasks.
+  pub done: usize,
+}
+
+impl MilestoneCount {
+  /// Count the tasks of `tasks` in each milestone of `project`, including its subprojects.
+  ///
+  /// Tasks without milestone and cancelled tasks are not counted.
+  pub fn per_milestone<'a>(
+    tasks: impl IntoIterator<Item = &'a Task>,
+    project: &str,
+  ) -> BTreeMap<String, Self> {
+    let mut milestones = BTreeMap::<String, Self>::new();
+
+    for task in tasks.into_iter().filter(|task| {
+      task
+        .project()
+        .is_some_and(|task_project| metadata::is_in_project(task_project, project, false))
+    }) {
+      let milestone = match task.milestone() {
+        Some(milestone) => milestone,
+        None => continue,
+      };
+
+      match task.status() {
+        Status::Cancelled => (),
+        Status::Done => milestones.entry(milestone.to_owned()).or_default().done += 1,
+        _ => milestones.entry(milestone.to_owned()).or_default().open += 1,
+      }
+    }
+
+    milestones
+  }
+}
+
+/// Estimated effort and spent time of a group of tasks, such as a project.
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub struct EffortRollup {
+  /// Number of tasks, cancelled ones excepted.
+  pub tasks: usize,
+  /// Number of done tasks.
+  pub done: usize,
+  /// Sum of the efforts of the tasks, cancelled ones excepted.
+  pub estimated: Duration,
+  /// Number of tasks without effort, cancelled ones excepted.
+  pub unestimated: usize,
+  /// Time spent on the tasks, including the cancelled ones.
+  pub spent: Duration,
+}
+
+impl Default for EffortRollup {
+  fn default() -> Self {
+    Self {
+      tasks: 0,
+      done: 0,
+      estimated: Duration::zero(),
+      unestimated: 0,
+      spent: Duration::zero(),
+    }
+  }
+}
+
+impl EffortRollup {
+  /// Add a task to the rollup.
+  pub fn add(&mut self, task: &Task) {
+    self.spent = self.spent + task.spent_time();
+
+    if task.status() == Status::Cancelled {
+      return;
+    }
+
+    self.tasks += 1;
+
+    if task.status() == Status::Done {
+      self.done += 1;
+    }
+
+    match task.effort() {
+      Some(effort) => self.estimated = self.estimated + effort,
+      None => self.unestimated += 1,
+    }
+  }
+}
+
+/// Event of the history of a task, along with its author.
+///
+/// The author and sequence number are serialized along with the fields of the event, and only when they are known.
+#[derive(Clone, Debug, Deserialize, Serialize)]
+struct HistoryEntry {
+  #[serde(flatten)]
+  event: Event,
+  /// Author of the event, as configured by whoever recorded it.
+  #[serde(default, skip_serializing_if = "Option::is_none")]
+  author: Option<String>,
+  /// Sequence number of the event, from the logical clock of the task manager; `0` if unknown.
+  #[serde(default, skip_serializing_if = "is_zero")]
+  seq: u64,
+  /// Order in which the event was recorded since the tasks were loaded, if it was; such events are stamped with the
+  /// author and a sequence number when saved.
+  #[serde(skip)]
+  pending: Option<u64>,
+}
+
+/// Order of the events recorded by this process.
+static RECORDED: AtomicU64 = AtomicU64::new(0);
+
+impl HistoryEntry {
+  fn new(event: Event) -> Self {
+    HistoryEntry {
+      event,
+      author: None,
+      seq: 0,
+      pending: Some(RECORDED.fetch_add(1, atomic::Ordering::Relaxed)),
+    }
+  }
+}
+
+/// Highest sequence number of `entries`, or `0` if none is known.
+fn max_seq<'a>(entries: impl IntoIterator<Item = &'a HistoryEntry>) -> u64 {
+  entries
+    .into_iter()
+    .map(|entry| entry.seq)
+    .max()
+    .unwrap_or(0)
+}
+
+fn is_zero(n: &u64) -> bool {
+  *n == 0
+}
+
+/// Seal of the history of a task.
+///
+/// The seal is a hash chain over the events of the history: the hash of an event is computed from the hash of the
+/// previous event and the event itself, so that altering any event changes the final hash.
+#[derive(Clone, Debug, Deserialize, Eq, PartialEq, Serialize)]
+struct Seal {
+  /// Number of sealed events, from the start of the history.
+  events: usize,
+  /// Hash of the last sealed event, as hexadecimal.
+  hash: String,
+}
+
+impl Seal {
+  /// Hash a sequence of events, along with their authors.
+  fn hash(entries: &[HistoryEntry]) -> String {
+    let hash = entries.iter().fold([0; 32], |previous, entry| {
+      let mut hasher = Sha256::new();
+      hasher.update(previous);
+      // serializing an event cannot fail
+      hasher.update(json::to_vec(entry).unwrap_or_default());
+      hasher.finalize().into()
+    });
+
+    hash.iter().map(|byte| format!("{:02x}", byte)).collect()
+  }
+}
+
+/// Task that cannot be read; see [`TaskManager::unreadable_tasks`].
+#[derive(Debug)]
+pub struct UnreadableTask {
+  /// UID of the task, as written in the tasks file.
+  pub uid: String,
+  /// Index of the first event that cannot be read, if the history is to blame.
+  pub event: Option<usize>,
+  /// Why the task cannot be read.
+  pub error: json::Error,
+}
+
+/// Problem found while checking the integrity of the tasks.
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub enum IntegrityIssue {
+  /// The next UID to use is already used by a task.
+  NextUIDInUse { next_uid: UID, max_uid: UID },
+
+  /// A task is both active and in the trash.
+  DuplicateUID(UID),
+
+  /// The history of a task doesn’t start with its creation.
+  MissingCreation(UID),
+
+  /// The events of a task are not in chronological order.
+  UnorderedHistory(UID),
+
+  /// A note replacement refers to a note that doesn’t exist.
+  DanglingNote { task_uid: UID, note_uid: UID },
+
+  /// A checklist toggle refers to an item that doesn’t exist.
+  DanglingChecklistItem { task_uid: UID, item_uid: UID },
+
+  /// A reminder delivery refers to a reminder that doesn’t exist.
+  DanglingReminder { task_uid: UID, reminder_uid: UID },
+
+  /// The history of a task doesn’t match its seal; it was edited by hand.
+  BrokenSeal(UID),
+}
+
+/// Event that cannot be appended to the history of a task; see [`Task::push_event`].
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub enum EventError {
+  /// The history doesn’t start with the creation of the task.
+  MissingCreation,
+
+  /// The task is already created.
+  DuplicateCreation,
+
+  /// The event happens before the last event of the history.
+  Unordered {
+    date: DateTime<Utc>,
+    last: DateTime<Utc>,
+  },
+
+  /// The event refers to a note that doesn’t exist.
+  UnknownNote(UID),
+
+  /// The event refers to a checklist item that doesn’t exist.
+  UnknownChecklistItem(UID),
+
+  /// The event refers to a reminder that doesn’t exist.
+  UnknownReminder(UID),
+}
+
+impl fmt::Display for EventError {
+  fn fmt(&self, f: &mut fmt::Formatter) -> Result<(), fmt::Error> {
+    match *self {
+      EventError::MissingCreation => {
+        f.write_str(tr!("the history must start with the creation of the task"))
+      }
+      EventError::DuplicateCreation => f.write_str(tr!("the task is already created")),
+      EventError::Unordered { date, last } => f.write_str(&tr!(
+        "the event, on {}, happens before the last event of the history, on {}",
+        date.to_rfc3339(),
+        last.to_rfc3339()
+      )),
+      EventError::UnknownNote(uid) => f.write_str(&tr!("note {} doesn’t exist", uid)),
+      EventError::UnknownChecklistItem(uid) => {
+        f.write_str(&tr!("checklist item {} doesn’t exist", uid))
+      }
+      EventError::UnknownReminder(uid) => f.write_str(&tr!("reminder {} doesn’t exist", uid)),
+    }
+  }
+}
+
+impl std::error::Error for EventError {}
+
+impl fmt::Display for IntegrityIssue {
+  fn fmt(&self, f: &mut fmt::Formatter) -> Result<(), fmt::Error> {
+    match *self {
+      IntegrityIssue::NextUIDInUse { next_uid, max_uid } => f.write_str(&tr!(
+        "next UID is {} but task {} already exists",
+        next_uid,
+        max_uid
+      )),
+
+      IntegrityIssue::DuplicateUID(uid) => {
+        f.write_str(&tr!("task {} is both active and in the trash", uid))
+      }
+
+      IntegrityIssue::MissingCreation(uid) => f.write_str(&tr!(
+        "history of task {} doesn’t start with its creation",
+        uid
+      )),
+
+      IntegrityIssue::UnorderedHistory(uid) => f.write_str(&tr!(
+        "history of task {} is not in chronological order",
+        uid
+      )),
+
+      IntegrityIssue::DanglingNote { task_uid, note_uid } => f.write_str(&tr!(
+        "task {} replaces note {}, which doesn’t exist",
+        task_uid,
+        note_uid
+      )),
+
+      IntegrityIssue::DanglingChecklistItem { task_uid, item_uid } => f.write_str(&tr!(
+        "task {} toggles checklist item {}, which doesn’t exist",
+        task_uid,
+        item_uid
+      )),
+
+      IntegrityIssue::DanglingReminder {
+        task_uid,
+        reminder_uid,
+      } => f.write_str(&tr!(
+        "task {} delivers reminder {}, which doesn’t exist",
+        task_uid,
+        reminder_uid
+      )),
+
+      IntegrityIssue::BrokenSeal(uid) => f.write_str(&tr!(
+        "history of task {} doesn’t match its integrity hash",
+        uid
+      )),
+    }
+  }
+}
+
+#[cfg(test)]
+mod unit_tests {
+  use super::*;
+  use std::cmp::Ordering;
+
+  #[test]
+  fn checklist_progress() {
+    let mut task = Task::new("Foo");
+    assert_eq!(task.checklist_progress(), None);
+
+    task.add_checklist_item("write tests");
+    task.add_checklist_item("write docs");
+    assert_eq!(task.checklist_progress(), Some((0, 2)));
+
+    task.toggle_checklist_item(UID(1)).unwrap();
+    assert_eq!(task.checklist_progress(), Some((1, 2)));
+    assert!(task.checklist()[1].checked);
+
+    task.toggle_checklist_item(UID(1)).unwrap();
+    assert_eq!(task.checklist_progress(), Some((0, 2)));
+
+    assert_eq!(
+      task.toggle_checklist_item(UID(4)).unwrap_err().to_string(),
+      "checklist item 5 doesn’t exist"
+    );
+  }
+
+  #[test]
+  fn in_memory_storage() {
+    let config: Config = toml::from_str("[main]\njournal = true").unwrap();
+    let mut task_mgr = TaskManager::in_memory();
+    let foo = task_mgr.register_task(Task::new("Foo"));
+    task_mgr.save(&config).unwrap();
+    assert!(task_mgr.storage().has_tasks());
+
+    // once the tasks file exists, changes are journaled
+    task_mgr.get_mut(foo).unwrap().change_status(Status::Done);
+    let bar = task_mgr.register_task(Task::new("Bar"));
+    task_mgr.save(&config).unwrap();
+    assert_eq!(task_mgr.journal_records(), 3);
+
+    let mut storage = MemoryStorage::with_tasks(task_mgr.storage().read_tasks().unwrap().unwrap());
+    let journal = task_mgr.storage().read_journal().unwrap().unwrap();
+    storage.append_journal(&journal).unwrap();
+    let mut loaded = TaskManager::with_storage(&config, storage).unwrap();
+    assert_eq!(loaded.get(foo).map(Task::status), Some(Status::Done));
+    assert_eq!(loaded.get(bar).map(Task::name), Some("Bar"));
+    assert_eq!(loaded.journal_records(), 3);
+
+    loaded.compact(&config).unwrap();
+    assert_eq!(loaded.storage().read_journal().unwrap(), None);
+    assert_eq!(loaded.journal_records(), 0);
+  }
+
+  #[test]
+  fn sequence_numbers() {
+    let config: Config = toml::from_str("[main]\njournal = true").unwrap();
+    let seqs = |task: &Task| {
+      task
+        .sequenced_history()
+        .map(|(_, seq)| seq)
+        .collect::<Vec<_>>()
+    };
+
+    let mut task_mgr = TaskManager::in_memory();
+    let foo = task_mgr.register_task(Task::new("Foo"));
+    let bar = task_mgr.register_task(Task::new("Bar"));
+    task_mgr.get_mut(foo).unwrap().change_status(Status::Done);
+    assert_eq!(seqs(task_mgr.get(foo).unwrap()), vec![0, 0, 0]);
+
+    // events are numbered in the order they were recorded, across tasks
+    task_mgr.save(&config).unwrap();
+    assert_eq!(seqs(task_mgr.get(foo).unwrap()), vec![1, 2, 5]);
+    assert_eq!(seqs(task_mgr.get(bar).unwrap()), vec![3, 4]);
+    assert_eq!(task_mgr.clock(), 5);
+
+    // the clock goes on from the journaled events, even those of tasks deleted for good
+    task_mgr
+      .get_mut(bar)
+      .unwrap()
+      .change_status(Status::Ongoing);
+    task_mgr.remove_task(bar).unwrap();
+    task_mgr.save(&config).unwrap();
+    task_mgr.empty_trash(Duration::zero());
+    task_mgr.save(&config).unwrap();
+
+    let mut storage = MemoryStorage::with_tasks(task_mgr.storage().read_tasks().unwrap().unwrap());
+    storage
+      .append_journal(&task_mgr.storage().read_journal().unwrap().unwrap())
+      .unwrap();
+    let mut loaded = TaskManager::with_storage(&config, storage).unwrap();
+    assert_eq!(loaded.clock(), 7);
+
+    loaded.get_mut(foo).unwrap().change_status(Status::Todo);
+    loaded.save(&config).unwrap();
+    assert_eq!(seqs(loaded.get(foo).unwrap()), vec![1, 2, 5, 8]);
+
+    // a tasks file merged from another copy moves the clock past its events
+    let merged = r#"{
+      "next_uid": 1,
+      "clock": 2,
+      "tasks": {
+        "0": { "name": "Foo", "history": [{ "Created": "2021-01-01T00:00:00Z", "q": 9 }] }
+      }
+    }"#;
+    let loaded = TaskManager::with_storage(&config, MemoryStorage::with_tasks(merged)).unwrap();
+    assert_eq!(loaded.clock(), 9);
+  }
+
+  #[test]
+  fn trash() {
+    let mut task_mgr = TaskManager::in_memory();
+    let foo = task_mgr.register_task(Task::new("Foo"));
+    let bar = task_mgr.register_task(Task::new("Bar"));
+
+    task_mgr.remove_task(foo).unwrap();
+    assert!(task_mgr.get(foo).is_none());
+    assert!(task_mgr.remove_task(foo).is_err());
+    assert_eq!(task_mgr.trashed_tasks().count(), 1);
+
+    task_mgr.restore_task(foo).unwrap();
+    assert!(task_mgr.get(foo).is_some());
+    assert_eq!(task_mgr.trashed_tasks().count(), 0);
+
+    assert_eq!(task_mgr.remove_all_tasks(), 2);
+    assert!(task_mgr.empty_trash(Duration::days(1)).is_empty());
+    assert_eq!(task_mgr.trashed_tasks().count(), 2);
+
+    let mut purged = task_mgr.empty_trash(Duration::zero());
+    purged.sort();
+    assert_eq!(purged, vec![foo, bar]);
+    assert_eq!(task_mgr.trashed_tasks().count(), 0);
+  }
+
+  #[test]
+  fn integrity() {
+    let mut task_mgr = TaskManager::in_memory();
+    let foo = task_mgr.register_task(Task::new("Foo"));
+    let bar = task_mgr.register_task(Task::new("Bar"));
+    assert!(task_mgr.check_integrity().is_empty());
+
+    let date = Utc::now();
+    let task = task_mgr.get_mut(bar).unwrap();
+    task.add_note("a note");
+    task.record_event(Event::NoteReplaced {
+      event_date: date,
+      note_uid: UID(1),
+      content: "oops".to_owned(),
+    });
+    task.record_event(Event::ChecklistItemToggled {
+      event_date: date - Duration::days(1),
+      item_uid: UID(0),
+    });
+    task_mgr.next_uid = UID(1);
+    task_mgr.trash.insert(foo, Task::new("Foo"));
+
+    assert_eq!(
+      task_mgr.check_integrity(),
+      vec![
+        IntegrityIssue::NextUIDInUse {
+          next_uid: UID(1),
+          max_uid: bar
+        },
+        IntegrityIssue::DuplicateUID(foo),
+        IntegrityIssue::UnorderedHistory(bar),
+        IntegrityIssue::DanglingNote {
+          task_uid: bar,
+          note_uid: UID(1)
+        },
+        IntegrityIssue::DanglingChecklistItem {
+          task_uid: bar,
+          item_uid: UID(0)
+        },
+      ]
+    );
+  }
+
+  #[test]
+  fn sealed_history() {
+    let mut task = Task::new("Foo");
+    task.seal();
+    assert_eq!(task.seal.as_ref().map(|seal| seal.events), Some(2));
+
+    // legit changes extend the seal
+    task.change_status(Status::Ongoing);
+    task.seal();
+    let mut issues = Vec::new();
+    task.check_integrity(UID(0), &mut issues);
+    assert!(issues.is_empty());
+
+    // altering a sealed event breaks it for good
+    task.history[2].event = Event::StatusChanged {
+      event_date: *task.history[2].event.date(),
+      status: Status::Done,
+    };
+    task.check_integrity(UID(0), &mut issues);
+    assert_eq!(issues, vec![IntegrityIssue::BrokenSeal(UID(0))]);
+
+    task.change_status(Status::Cancelled);
+    task.seal();
+    assert_eq!(task.seal.as_ref().map(|seal| seal.events), Some(3));
+
+    // so do events appended by hand
+    let mut task = Task::new("Bar");
+    task.seal();
+    task.add_note("a note");
+    let mut issues = Vec::new();
+    task.check_integrity(UID(1), &mut issues);
+    assert_eq!(issues, vec![IntegrityIssue::BrokenSeal(UID(1))]);
+  }
+
+  #[test]
+  fn note_threads() {
+    let mut task = Task::new("Foo");
+    task.add_note("first");
+    task.add_note("second");
+    task.reply_to_note(UID(0), "re first").unwrap();
+    task.reply_to_note(UID(2), "re re first").unwrap();
+    task.reply_to_note(UID(1), "re second").unwrap();
+    assert!(task.reply_to_note(UID(5), "nothing to reply to").is_err());
+
+    let notes = task.notes();
+    assert_eq!(notes[2].in_reply_to, Some(UID(0)));
+    assert_eq!(
+      Note::threads(&notes),
+      vec![(0, 0), (2, 1), (3, 2), (1, 0), (4, 1)]
+    );
+
+    // replies to notes that don’t exist yet are dangling
+    task.record_event(Event::NoteAdded {
+      event_date: Utc::now(),
+      content: "re future".to_owned(),
+      in_reply_to: Some(UID(6)),
+    });
+    let mut issues = Vec::new();
+    task.check_integrity(UID(0), &mut issues);
+    assert_eq!(
+      issues,
+      vec![IntegrityIssue::DanglingNote {
+        task_uid: UID(0),
+        note_uid: UID(6)
+      }]
+    );
+    assert_eq!(Note::threads(&task.notes()).last(), Some(&(5, 0)));
+  }
+
+  #[test]
+  fn note_counts() {
+    let mut task = Task::new("Foo");
+    task.add_note("\nFirst line,  with words.\n\nThird line\n\n");
+    task.add_note("  ");
+
+    let notes = task.notes();
+    assert_eq!((notes[0].words(), notes[0].lines()), (6, 3));
+    assert_eq!((notes[1].words(), notes[1].lines()), (0, 0));
+  }
+
+  #[test]
+  fn authors() {
+    let mut task = Task::new("Foo");
+    task.stamp(Some("alice"));
+    task.change_status(Status::Ongoing);
+    task.stamp(Some("bob"));
+    assert_eq!(task.author(), Some("alice"));
+    assert_eq!(
+      task
+        .authored_history()
+        .map(|(_, author)| author)
+        .collect::<Vec<_>>(),
+      vec![Some("alice"), Some("alice"), Some("bob")]
+    );
+
+    // authors are stored along with the events, and only when known
+    let json = json::to_value(&task).unwrap();
+    assert_eq!(json["history"][2]["author"], "bob");
+    let mut task: Task = json::from_value(json).unwrap();
+    task.add_tag("shared");
+    task.stamp(None);
+    let json = json::to_value(&task).unwrap();
+    assert!(json["history"][3].get("author").is_none());
+    assert!(json["history"][3].get("AddTag").is_some());
+
+    assert!(task.check_metadata(&[Metadata::author("alice")], false));
+    assert!(task.check_metadata(&[Metadata::author("ALICE")], true));
+    assert!(!task.check_metadata(&[Metadata::author("bob")], false));
+  }
+
+  #[test]
+  fn inactivity() {
+    let now = Utc::now();
+
+    // created a month ago, last touched three weeks ago
+    let mut task = Task::from_history(
+      "Stale",
+      vec![
+        Event::Created(now - Duration::days(30)),
+        Event::StatusChanged {
+          event_date: now - Duration::days(21),
+          status: Status::Ongoing,
+        },
+      ],
+    );
+    assert_eq!(task.last_activity(), Some(&(now - Duration::days(21))));
+    assert!(task.inactivity() >= Duration::days(21));
+    assert!(task.age() >= Duration::days(30));
+
+    let stale = [Metadata::inactive(Ordering::Greater, Duration::days(14))];
+    let recent = [Metadata::inactive(Ordering::Less, Duration::days(2))];
+    assert!(task.check_metadata(&stale, false));
+    assert!(!task.check_metadata(&recent, true));
+
+    // any event counts as activity, not only status changes
+    task.add_tag("revived");
+    assert!(!task.check_metadata(&stale, true));
+    assert!(task.check_metadata(&recent, false));
+  }
+
+  #[test]
+  fn similar_active_tasks() {
+    let mut task_mgr = TaskManager::in_memory();
+    let rent = task_mgr.register_task(Task::new("Pay the rent"));
+    let rents = task_mgr.register_task(Task::new("Pay the rents"));
+    let mut done = Task::new("Pay the rent");
+    done.change_status(Status::Done);
+    task_mgr.register_task(done);
+    task_mgr.register_task(Task::new("Walk the dog"));
+
+    let similar = task_mgr
+      .similar_active_tasks("pay the rent!", 0.8)
+      .into_iter()
+      .map(|(uid, _, _)| uid)
+      .collect::<Vec<_>>();
+    assert_eq!(similar, vec![rent, rents]);
+  }
+
+  #[test]
+  fn plan() {
+    let mut task_mgr = TaskManager::in_memory();
+    let now = Utc::now();
+    let task = |name, metadata: Vec<Metadata>, deadline: Option<DateTime<Utc>>| {
+      let mut task = Task::new(name);
+      task.apply_metadata(metadata);
+      if let Some(deadline) = deadline {
+        task.set_deadline(deadline);
+      }
+      task
+    };
+    let high = || Metadata::priority(Priority::new("High"));
+    let critical = || Metadata::priority(Priority::new("Critical"));
+    let effort = |hours| Metadata::effort(Duration::hours(hours));
+
+    let report = task_mgr.register_task(task("Report", vec![high(), effort(2)], None));
+    let fire = task_mgr.register_task(task("Fire", vec![critical(), effort(1)], None));
+    let due = task_mgr.register_task(task("Taxes", vec![effort(3)], Some(now)));
+    let big = task_mgr.register_task(task("Big", vec![critical(), effort(5)], None));
+    let vague = task_mgr.register_task(task("Vague", vec![high()], None));
+    task_mgr.register_task(task(
+      "Later",
+      vec![effort(1)],
+      Some(now + Duration::days(7)),
+    ));
+    task_mgr.register_task(task(
+      "Low",
+      vec![Metadata::priority(Priority::new("Low")), effort(1)],
+      None,
+    ));
+
+    let plan = task_mgr.plan(&Priorities::default(), Duration::hours(6), now);
+    let uids = |tasks: &[(UID, &Task, Duration)]| tasks.iter().map(|t| t.0).collect::<Vec<_>>();
+    assert_eq!(uids(&plan.planned), vec![due, fire, report]);
+    assert_eq!(uids(&plan.left_out), vec![big]);
+    assert_eq!(
+      plan.unestimated.iter().map(|t| t.0).collect::<Vec<_>>(),
+      vec![vague]
+    );
+    assert_eq!(plan.free, Duration::zero());
+  }
+
+  #[test]
+  fn next_occurrence() {
+    let mut task = Task::new("Pay rent");
+    assert!(task.next_occurrence().is_none());
+
+    let scheduled = Utc::now() - Duration::days(40);
+    task.apply_metadata(vec![Metadata::project("home"), Metadata::tag("money")]);
+    task.set_scheduled(scheduled);
+    task.set_deadline(scheduled + Duration::days(5));
+    task.set_recurrence(Recurrence::Weeks(2));
+
+    let next = task.next_occurrence().unwrap();
+    assert_eq!(next.name(), "Pay rent");
+    assert_eq!(next.status(), Status::Todo);
+    assert_eq!(next.project(), Some("home"));
+    assert_eq!(next.tags().collect::<Vec<_>>(), vec!["money"]);
+    assert_eq!(next.recurrence(), Some(Recurrence::Weeks(2)));
+    assert_eq!(next.scheduled(), Some(scheduled + Duration::weeks(6)));
+    assert_eq!(
+      next.deadline(),
+      Some(scheduled + Duration::weeks(6) + Duration::days(5))
+    );
+  }
+
+  #[test]
+  fn next_occurrence_out_of_range() {
+    let mut task = Task::new("Bury a time capsule");
+    task.set_recurrence(Recurrence::Years(400_000));
+    assert!(task.next_occurrence().is_none());
+
+    task.set_recurrence(Recurrence::Days(u32::MAX));
+    assert!(task.next_occurrence().is_none());
+
+    let now = Utc::now();
+    assert_eq!(Recurrence::Months(u32::MAX).advance(now, 2), None);
+  }
+
+  #[test]
+  fn snooze() {
+    let mut task = Task::new("Water the plants");
+    let now = Utc::now();
+    assert!(!task.is_snoozed(now));
+
+    let until = task.snooze(Duration::days(2));
+    assert!(until >= now + Duration::days(2));
+    assert_eq!(task.scheduled(), Some(until));
+    assert!(task.is_snoozed(now + Duration::days(1)));
+    assert!(!task.is_snoozed(until));
+
+    // snoozing again pushes from the current schedule
+    assert_eq!(task.snooze(Duration::hours(1)), until + Duration::hours(1));
+  }
+
+  #[test]
+  fn someday_tasks_to_review() {
+    let mut task_mgr = TaskManager::in_memory();
+    let someday = |name| {
+      let mut task = Task::new(name);
+      task.set_someday(true);
+      task
+    };
+
+    let idea = task_mgr.register_task(someday("Learn the cello"));
+    let mut reviewed = someday("Write a novel");
+    reviewed.review();
+    let reviewed = task_mgr.register_task(reviewed);
+    task_mgr.register_task(Task::new("Buy milk"));
+
+    assert_eq!(
+      task_mgr
+        .someday_tasks_to_review(Duration::zero())
+        .into_iter()
+        .map(|(uid, _)| uid)
+        .collect::<Vec<_>>(),
+      vec![idea, reviewed]
+    );
+    assert!(task_mgr
+      .someday_tasks_to_review(Duration::days(1))
+      .is_empty());
+
+    task_mgr.get_mut(idea).unwrap().set_someday(false);
+    assert!(!task_mgr.get(idea).unwrap().is_someday());
+  }
+
+  #[test]
+  fn contexts() {
+    let mut task_mgr = TaskManager::in_memory();
+    let task = |name, context| {
+      let mut task = Task::new(name);
+      task.apply_metadata(vec![Metadata::context(context)]);
+      task
+    };
+
+    task_mgr.register_task(task("Call mom", "phone"));
+    task_mgr.register_task(task("Call the bank", "phone"));
+    task_mgr.register_task(task("Buy bread", "errands"));
+    let mut done = task("Call the plumber", "phone");
+    done.change_status(Status::Done);
+    task_mgr.register_task(done);
+
+    assert_eq!(task_mgr.contexts(), vec![("errands", 1), ("phone", 2)]);
+    assert!(task_mgr
+      .get(UID(0))
+      .unwrap()
+      .check_metadata(&[Metadata::context("Phone")], true));
+  }
+
+  #[test]
+  fn metadata_completion() {
+    let mut task_mgr = TaskManager::in_memory();
+    let mut task = Task::new("Write the docs");
+    task.apply_metadata(vec![
+      Metadata::project("toodoux"),
+      Metadata::project("toodoux.docs"),
+      Metadata::tag("docs"),
+      Metadata::tag("doctor"),
+      Metadata::context("desk"),
+    ]);
+    task_mgr.register_task(task);
+    let mut task = Task::new("Release");
+    task.apply_metadata(vec![Metadata::project("tools"), Metadata::milestone("v2")]);
+    task_mgr.register_task(task);
+
+    let complete = |word| task_mgr.complete_metadata(&Priorities::default(), word);
+    assert_eq!(complete("@to"), vec!["@toodoux.docs", "@tools"]);
+    assert_eq!(complete("#doc"), vec!["#docs", "#doctor"]);
+    assert_eq!(complete("#"), vec!["#docs", "#doctor"]);
+    assert_eq!(complete("ctx:"), vec!["ctx:desk"]);
+    assert_eq!(complete("milestone:v"), vec!["milestone:v2"]);
+    assert_eq!(complete("+h"), vec!["+High", "+h"]);
+    assert_eq!(complete("#DOCT"), vec!["#doctor"]);
+    assert!(complete("@x").is_empty());
+    assert!(complete("doc").is_empty());
+  }
+
+  #[test]
+  fn pushed_events() {
+    let date = Utc::now();
+    let later = date + Duration::hours(1);
+
+    let mut task = Task::from_events(
+      "Sync me",
+      vec![
+        Event::Created(date),
+        Event::NoteAdded {
+          event_date: date,
+          content: "From Jira".to_owned(),
+          in_reply_to: None,
+        },
+      ],
+    )
+    .unwrap();
+    task
+      .push_event(Event::StatusChanged {
+        event_date: later,
+        status: Status::Done,
+      })
+      .unwrap();
+    assert_eq!(task.status(), Status::Done);
+    let mut issues = Vec::new();
+    task.check_integrity(UID(0), &mut issues);
+    assert!(issues.is_empty());
+
+    assert_eq!(
+      task.push_event(Event::Created(later)),
+      Err(EventError::DuplicateCreation)
+    );
+    assert_eq!(
+      task.push_event(Event::Reviewed { event_date: date }),
+      Err(EventError::Unordered { date, last: later })
+    );
+    assert_eq!(
+      task.push_event(Event::NoteReplaced {
+        event_date: later,
+        note_uid: UID(1),
+        content: "Oops".to_owned(),
+      }),
+      Err(EventError::UnknownNote(UID(1)))
+    );
+    assert_eq!(
+      task.push_event(Event::ChecklistItemToggled {
+        event_date: later,
+        item_uid: UID(0),
+      }),
+      Err(EventError::UnknownChecklistItem(UID(0)))
+    );
+    assert_eq!(task.history().count(), 3);
+
+    assert_eq!(
+      Task::from_events("Orphan", vec![Event::Reviewed { event_date: date }]).unwrap_err(),
+      EventError::MissingCreation
+    );
+    assert_eq!(
+      Task::from_events("Empty", vec![]).unwrap_err(),
+      EventError::MissingCreation
+    );
+  }
+
+  #[test]
+  fn renamed_tags() {
+    let mut task_mgr = TaskManager::in_memory();
+    let mut task = Task::new("Fix the parser");
+    task.apply_metadata(vec![
+      Metadata::tag("Bugs"),
+      Metadata::tag("parser"),
+      Metadata::tag("Bugs"),
+    ]);
+    let renamed = task_mgr.register_task(task);
+    let mut task = Task::new("Fix the lexer");
+    task.apply_metadata(vec![Metadata::tag("bug"), Metadata::tag("Bugs")]);
+    let merged = task_mgr.register_task(task);
+    task_mgr.register_task(Task::new("Write the docs"));
+
+    let mut uids = Vec::new();
+    task_mgr.rename_tag("Bugs", "bug", |uid| uids.push(uid));
+    uids.sort();
+    assert_eq!(uids, vec![renamed, merged]);
+
+    let tags = |uid| task_mgr.get(uid).unwrap().tags().collect::<Vec<_>>();
+    assert_eq!(tags(renamed), vec!["bug", "parser"]);
+    assert_eq!(tags(merged), vec!["bug"]);
+  }
+
+  #[test]
+  fn close_projects_and_tags() {
+    let mut task_mgr = TaskManager::in_memory();
+    let mut task = Task::new("Write the docs");
+    task.apply_metadata(vec![Metadata::project("work"), Metadata::tag("docs")]);
+    task_mgr.register_task(task);
+
+    assert_eq!(task_mgr.close_project("wrok"), Some("work"));
+    assert_eq!(task_mgr.close_project("work"), None);
+    assert_eq!(task_mgr.close_project("home"), None);
+    assert_eq!(task_mgr.close_tag("doc"), Some("docs"));
+    assert_eq!(task_mgr.close_tag("wrok"), None);
+  }
+
+  #[test]
+  fn summary() {
+    let now = Utc::now();
+    let since = now - Duration::days(2);
+    let status = |days: i64, status| Event::StatusChanged {
+      event_date: now - Duration::days(days),
+      status,
+    };
+
+    // three days of work, only two of which are after `since`
+    let worked = Task::from_history(
+      "Worked",
+      vec![
+        Event::Created(now - Duration::days(5)),
+        status(3, Status::Ongoing),
+        status(0, Status::Done),
+      ],
+    );
+    let mut overdue = Task::new("Overdue");
+    overdue.set_deadline(now - Duration::hours(1));
+    let mut due_later = Task::new("Later");
+    due_later.set_deadline(now + Duration::hours(1));
+
+    let summary = Summary::new(vec![&worked, &overdue, &due_later], now, since);
+    assert_eq!(summary.todo, 2);
+    assert_eq!(summary.ongoing, 0);
+    assert_eq!(summary.done, 1);
+    assert_eq!(summary.overdue, 1);
+    assert_eq!(summary.spent, Duration::days(2));
+    assert_eq!(worked.spent_time(), Duration::days(3));
+  }
+
+  #[test]
+  fn paused_spent_time() {
+    let now = Utc::now();
+    let status = |days: i64, status| Event::StatusChanged {
+      event_date: now - Duration::days(days),
+      status,
+    };
+
+    // two days of work, interrupted by a pause of two days
+    let paused = Task::from_history(
+      "Paused",
+      vec![
+        Event::Created(now - Duration::days(6)),
+        status(5, Status::Ongoing),
+        status(4, Status::Paused),
+        status(2, Status::Ongoing),
+        status(1, Status::Ongoing),
+        status(1, Status::Paused),
+      ],
+    );
+    assert_eq!(paused.status(), Status::Paused);
+    assert!(paused.status().is_active());
+    assert_eq!(paused.spent_time(), Duration::days(2));
+
+    let summary = Summary::new(vec![&paused], now, now - Duration::days(3));
+    assert_eq!(summary.paused, 1);
+    assert_eq!(summary.ongoing, 0);
+    assert_eq!(summary.spent, Duration::days(1));
+  }
+
+  #[test]
+  fn adjusted_spent_time() {
+    let now = Utc::now();
+    let mut task = Task::from_history(
+      "Adjusted",
+      vec![
+        Event::Created(now - Duration::days(3)),
+        Event::StatusChanged {
+          event_date: now - Duration::days(2),
+          status: Status::Ongoing,
+        },
+        Event::TimeAdjusted {
+          event_date: now - Duration::days(2),
+          minutes: 30,
+          reason: None,
+        },
+        Event::StatusChanged {
+          event_date: now - Duration::days(2) + Duration::hours(1),
+          status: Status::Done,
+        },
+      ],
+    );
+
+    task.adjust_spent_time(Duration::minutes(45), Some("forgot to clock".to_owned()));
+    task.adjust_spent_time(Duration::minutes(-10), None);
+    assert_eq!(task.spent_time(), Duration::minutes(60 + 30 + 45 - 10));
+    // older adjustments don’t count
+    assert_eq!(
+      task.spent_time_since(now - Duration::days(1)),
+      Duration::minutes(35)
+    );
+
+    let entries = task.time_entries();
+    assert_eq!(entries.len(), 4);
+    assert_eq!(entries[0].duration(), Duration::hours(1));
+    assert_eq!(
+      entries[2],
+      TimeEntry::Adjustment {
+        date: *task.history().nth(4).unwrap().date(),
+        duration: Duration::minutes(45),
+        reason: Some("forgot to clock".to_owned()),
+      }
+    );
+
+    // the spent time is never negative
+    task.adjust_spent_time(Duration::days(-1), None);
+    assert_eq!(task.spent_time(), Duration::zero());
+  }
+
+  #[test]
+  fn pinned_tasks_come_first() {
+    let mut task_mgr = TaskManager::in_memory();
+    let mut urgent = Task::new("Urgent");
+    urgent.set_priority(Priority::new("Critical"));
+    let urgent = task_mgr.register_task(urgent);
+    let mut pinned = Task::new("Pinned");
+    pinned.set_pinned(true);
+    let pinned = task_mgr.register_task(pinned);
+    let mut unpinned = Task::new("Unpinned");
+    unpinned.set_pinned(true);
+    unpinned.set_pinned(false);
+    let unpinned = task_mgr.register_task(unpinned);
+
+    let uids = task_mgr
+      .filtered_task_listing(
+        &Priorities::default(),
+        NoPriorityOrder::default(),
+        Vec::new(),
+        TaskDescriptionFilter::new(None.into_iter(), false),
+        true,
+        true,
+        true,
+        true,
+        true,
+        true,
+        None,
+        false,
+      )
+      .into_iter()
+      .map(|(&uid, _)| uid)
+      .collect::<Vec<_>>();
+    assert_eq!(uids, vec![pinned, urgent, unpinned]);
+  }
+
+  #[test]
+  fn due_reminders() {
+    let mut task_mgr = TaskManager::in_memory();
+    let now = Utc::now();
+
+    let mut call = Task::new("Call the bank");
+    call.apply_metadata(vec![
+      Metadata::reminder(now - Duration::hours(1)),
+      Metadata::reminder(now + Duration::hours(1)),
+    ]);
+    let call = task_mgr.register_task(call);
+    let mut done = Task::new("Already done");
+    done.add_reminder(now - Duration::hours(2));
+    done.change_status(Status::Done);
+    task_mgr.register_task(done);
+
+    let due = task_mgr
+      .due_reminders(now)
+      .into_iter()
+      .map(|(uid, _, reminder_uid, _)| (uid, reminder_uid))
+      .collect::<Vec<_>>();
+    assert_eq!(due, vec![(call, UID(0))]);
+
+    let task = task_mgr.get_mut(call).unwrap();
+    task.deliver_reminder(UID(0)).unwrap();
+    assert!(task.deliver_reminder(UID(2)).is_err());
+    assert!(task.reminders()[0].delivered.is_some());
+    assert!(task_mgr.due_reminders(now).is_empty());
+    assert_eq!(task_mgr.due_reminders(now + Duration::hours(2)).len(), 1);
+  }
+
+  #[test]
+  fn events_since() {
+    let task_mgr: TaskManager = json::from_str(
+      r#"{
+        "next_uid": 2,
+        "tasks": {
+          "0": {
+            "name": "Foo",
+            "history": [
+              { "Created": "2021-01-01T00:00:00Z" },
+              { "StatusChanged": { "event_date": "2021-01-01T00:00:00Z", "status": "Todo" } },
+              { "StatusChanged": { "event_date": "2021-01-03T00:00:00Z", "status": "Done" } }
+            ]
+          }
+        },
+        "trash": {
+          "1": {
+            "name": "Bar",
+            "history": [
+              { "Created": "2021-01-02T00:00:00Z" },
+              { "Removed": { "event_date": "2021-01-03T00:00:00Z" } }
+            ]
+          }
+        }
+      }"#,
+    )
+    .unwrap();
+    let events = |since: &str| {
+      task_mgr
+        .events_since(since.parse().unwrap())
+        .into_iter()
+        .map(|(uid, _, event, _)| (uid.val(), *event.date()))
+        .collect::<Vec<_>>()
+    };
+    let date = |s: &str| s.parse::<DateTime<Utc>>().unwrap();
+
+    assert_eq!(
+      events("2021-01-02T00:00:00Z"),
+      vec![
+        (0, date("2021-01-03T00:00:00Z")),
+        (1, date("2021-01-03T00:00:00Z")),
+        (1, date("2021-01-02T00:00:00Z")),
+      ]
+    );
+    assert_eq!(events("2021-01-01T00:00:00Z").len(), 5);
+    assert!(events("2021-01-04T00:00:00Z").is_empty());
+  }
+
+  #[test]
+  fn import_tasks() {
+    let mut task_mgr = TaskManager::in_memory();
+    let imported = |name, external_id: Option<&str>| {
+      let mut task = Task::new(name);
+      if let Some(external_id) = external_id {
+        task.set_external_id(external_id);
+      }
+      task
+    };
+
+    let report = task_mgr.import_tasks(
+      vec![imported("Foo", Some("org:1")), imported("Bar", None)],
+      false,
+    );
+    assert_eq!(report.iter().filter(|t| t.duplicate).count(), 0);
+    task_mgr.remove_task(UID(0)).unwrap();
+
+    let report = task_mgr.import_tasks(
+      vec![
+        imported("Foo", Some("org:1")),
+        imported("Bar", None),
+        imported("Zoo", Some("org:2")),
+        imported("Zoo", Some("org:2")),
+      ],
+      false,
+    );
+    assert_eq!(
+      report,
+      vec![
+        ImportedTask {
+          external_id: Some("org:1".to_owned()),
+          uid: UID(0),
+          duplicate: true
+        },
+        ImportedTask {
+          external_id: None,
+          uid: UID(2),
+          duplicate: false
+        },
+        ImportedTask {
+          external_id: Some("org:2".to_owned()),
+          uid: UID(3),
+          duplicate: false
+        },
+        ImportedTask {
+          external_id: Some("org:2".to_owned()),
+          uid: UID(3),
+          duplicate: true
+        },
+      ]
+    );
+
+    let report = task_mgr.import_tasks(vec![imported("Foo", Some("org:1"))], true);
+    assert!(!report[0].duplicate);
+    assert_eq!(report[0].uid, UID(4));
+
+    let task = task_mgr.get(UID(4)).unwrap();
+    assert!(task.check_metadata(&[Metadata::external_id("org:1")], false));
+    assert!(!task.check_metadata(&[Metadata::external_id("ORG:1")], false));
+    assert!(task.check_metadata(&[Metadata::external_id("ORG:1")], true));
+  }
+
+  #[test]
+  fn fields() {
+    let mut task = Task::new("Write the quote");
+    assert!(task.fields().is_empty());
+
+    task.apply_metadata(vec![
+      Metadata::field("client", FieldValue::String("ACME".to_owned())),
+      Metadata::field("points", FieldValue::Number(3.)),
+    ]);
+    task.set_field("points", FieldValue::Number(5.));
+    assert_eq!(task.field("points"), Some(&FieldValue::Number(5.)));
+    assert_eq!(
+      task.fields(),
+      vec![
+        ("client", &FieldValue::String("ACME".to_owned())),
+        ("points", &FieldValue::Number(5.))
+      ]
+    );
+
+    let acme = Metadata::field("client", FieldValue::String("acme".to_owned()));
+    assert!(!task.check_metadata(std::slice::from_ref(&acme), false));
+    assert!(task.check_metadata(&[acme], true));
+    assert!(!task.check_metadata(&[Metadata::field("points", FieldValue::Number(3.))], false));
+  }
+
+  #[test]
+  fn project_hierarchy() {
+    let mut task_mgr = TaskManager::in_memory();
+
+    for (name, project) in [
+      ("API", "work.backend.api"),
+      ("DB", "work.backend"),
+      ("UI", "work.frontend"),
+      ("Workshop", "workshop"),
+    ]
+    .iter()
+    .copied()
+    {
+      let mut task = Task::new(name);
+      task.apply_metadata(vec![Metadata::project(project)]);
+      task_mgr.register_task(task);
+    }
+    task_mgr.register_task(Task::new("Orphan"));
+
+    let api = task_mgr.get(UID(0)).unwrap();
+    assert!(api.check_metadata(&[Metadata::project("work")], false));
+    assert!(api.check_metadata(&[Metadata::project("WORK.backend")], true));
+    assert!(!api.check_metadata(&[Metadata::project("work.frontend")], false));
+    assert!(!task_mgr
+      .get(UID(3))
+      .unwrap()
+      .check_metadata(&[Metadata::project("work")], false));
+
+    let tree = ProjectTree::new(task_mgr.tasks().map(|(_, task)| task));
+    assert_eq!(tree.count, 4);
+    assert_eq!(tree.children["work"].count, 3);
+    assert_eq!(tree.children["work"].children["backend"].count, 2);
+    assert_eq!(
+      tree.children["work"].children["backend"].children["api"].count,
+      1
+    );
+    assert_eq!(tree.children["workshop"].count, 1);
+
+    let mut renamed = Vec::new();
+    task_mgr.rename_project("work.backend", "job.server", |uid| renamed.push(uid));
+    renamed.sort();
+    assert_eq!(renamed, vec![UID(0), UID(1)]);
+    assert_eq!(
+      task_mgr.get(UID(0)).unwrap().project(),
+      Some("job.server.api")
+    );
+    assert_eq!(task_mgr.get(UID(1)).unwrap().project(), Some("job.server"));
+    assert_eq!(
+      task_mgr.get(UID(2)).unwrap().project(),
+      Some("work.frontend")
+    );
+  }
+
+  #[test]
+  fn milestones() {
+    let mut task_mgr = TaskManager::in_memory();
+    for &(project, milestone, status) in &[
+      ("toodoux", Some("v2"), Status::Done),
+      ("toodoux.cli", Some("v2"), Status::Todo),
+      ("toodoux", Some("v2"), Status::Cancelled),
+      ("toodoux", Some("v3"), Status::Ongoing),
+      ("toodoux", None, Status::Todo),
+      ("other", Some("v2"), Status::Todo),
+    ] {
+      let mut task = Task::new("Task");
+      task.apply_metadata(vec![Metadata::project(project)]);
+      if let Some(milestone) = milestone {
+        task.apply_metadata(vec![Metadata::milestone(milestone)]);
+      }
+      task.change_status(status);
+      task_mgr.register_task(task);
+    }
+
+    let milestones =
+      MilestoneCount::per_milestone(task_mgr.tasks().map(|(_, task)| task), "toodoux");
+    assert_eq!(milestones.len(), 2);
+    assert_eq!(milestones["v2"], MilestoneCount { open: 1, done: 1 });
+    assert_eq!(milestones["v3"], MilestoneCount { open: 1, done: 0 });
+    assert!(
+      MilestoneCount::per_milestone(task_mgr.tasks().map(|(_, task)| task), "none").is_empty()
+    );
+  }
+
+  #[test]
+  fn project_rollup() {
+    let mut task_mgr = TaskManager::in_memory();
+    for &(project, effort, status) in &[
+      ("work", Some(60), Status::Todo),
+      ("work.backend", Some(90), Status::Done),
+      ("work.backend.api", None, Status::Todo),
+      ("work.frontend", Some(30), Status::Cancelled),
+      ("workshop", Some(120), Status::Todo),
+    ] {
+      let mut task = Task::new("Task");
+      task.apply_metadata(vec![Metadata::project(project)]);
+      if let Some(effort) = effort {
+        task.apply_metadata(vec![Metadata::Effort(Duration::minutes(effort))]);
+      }
+      task.change_status(status);
+      task.adjust_spent_time(Duration::minutes(10), None);
+      task_mgr.register_task(task);
+    }
+
+    let (total, subprojects) = task_mgr.project_rollup("work");
+    assert_eq!(
+      total,
+      EffortRollup {
+        tasks: 3,
+        done: 1,
+        estimated: Duration::minutes(150),
+        unestimated: 1,
+        spent: Duration::minutes(40),
+      }
+    );
+    assert_eq!(
+      subprojects.keys().collect::<Vec<_>>(),
+      vec!["work.backend", "work.frontend"]
+    );
+    assert_eq!(subprojects["work.backend"].tasks, 2);
+    assert_eq!(subprojects["work.backend"].estimated, Duration::minutes(90));
+    assert_eq!(subprojects["work.frontend"].tasks, 0);
+    assert_eq!(subprojects["work.frontend"].spent, Duration::minutes(10));
+  }
+
+  #[test]
+  fn pause_ongoing_tasks() {
+    let mut task_mgr = TaskManager::in_memory();
+
+    for status in [
+      Status::Ongoing,
+      Status::Ongoing,
+      Status::Done,
+      Status::Ongoing,
+    ]
+    .iter()
+    .copied()
+    {
+      let mut task = Task::new("Foo");
+      task.change_status(status);
+      task_mgr.register_task(task);
+    }
+
+    assert_eq!(task_mgr.pause_ongoing_tasks(UID(1)), vec![UID(0), UID(3)]);
+    assert_eq!(task_mgr.get(UID(0)).unwrap().status(), Status::Paused);
+    assert_eq!(task_mgr.get(UID(1)).unwrap().status(), Status::Ongoing);
+    assert_eq!(task_mgr.get(UID(2)).unwrap().status(), Status::Done);
+    assert!(task_mgr.pause_ongoing_tasks(UID(1)).is_empty());
+  }
 }
@@ -0,0 +1,182 @@
+//! Compact form of the persisted tasks.
+//!
+//! Events make up most of the tasks file, and most of each event is made of field names. In the compact form, the
+//! fields of events are given short names, and the JSON is written without indentation. Tasks always serialize with
+//! the long names — which the integrity seals are computed over — and are shortened or expanded as JSON values when
+//! written or read, so that both forms can be read whatever the configuration.
+
+use serde_json as json;
+
+/// Long and short names of the fields of history entries.
+///
+/// Short names must not collide with any long name.
+const FIELDS: &[(&str, &str)] = &[
+  ("event_date", "d"),
+  ("author", "a"),
+  ("seq", "q"),
+  ("status", "s"),
+  ("content", "c"),
+  ("in_reply_to", "re"),
+  ("note_uid", "n"),
+  ("project", "p"),
+  ("priority", "pr"),
+  ("context", "cx"),
+  ("milestone", "ms"),
+  ("tag", "tg"),
+  ("scheduled", "sc"),
+  ("deadline", "dl"),
+  ("recurrence", "rc"),
+  ("minutes", "m"),
+  ("until", "u"),
+  ("someday", "sd"),
+  ("pinned", "pn"),
+  ("goal", "gl"),
+  ("external_id", "x"),
+  ("date", "dt"),
+  ("reminder_uid", "r"),
+  ("item_uid", "i"),
+  ("name", "nm"),
+  ("value", "v"),
+  ("reason", "rs"),
+];
+
+/// Shorten the field names of the history entries of the tasks of a task manager.
+pub fn shorten_tasks(task_mgr: &mut json::Value) {
+  for_each_task(task_mgr, shorten_task);
+}
+
+/// Expand the field names of the history entries of the tasks of a task manager, if they are shortened.
+pub fn expand_tasks(task_mgr: &mut json::Value) {
+  for_each_task(task_mgr, expand_task);
+}
+
+/// Shorten the field names of the history entries of a task.
+pub fn shorten_task(task: &mut json::Value) {
+  if let Some(history) = task.get_mut("history") {
+    shorten_entries(history);
+  }
+}
+
+/// Expand the field names of the history entries of a task, if they are shortened.
+pub fn expand_task(task: &mut json::Value) {
+  if let Some(history) = task.get_mut("history") {
+    expand_entries(history);
+  }
+}
+
+/// Shorten the field names of history entries.
+pub fn shorten_entries(entries: &mut json::Value) {
+  rename_entries(entries, shorten);
+}
+
+/// Expand the field names of history entries, if they are shortened.
+pub fn expand_entries(entries: &mut json::Value) {
+  rename_entries(entries, expand);
+}
+
+fn shorten(name: &str) -> Option<&'static str> {
+  FIELDS
+    .iter()
+    .find(|(long, _)| *long == name)
+    .map(|(_, short)| *short)
+}
+
+fn expand(name: &str) -> Option<&'static str> {
+  FIELDS
+    .iter()
+    .find(|(_, short)| *short == name)
+    .map(|(long, _)| *long)
+}
+
+fn for_each_task(task_mgr: &mut json::Value, f: fn(&mut json::Value)) {
+  for key in &["tasks", "trash"] {
+    if let Some(tasks) = task_mgr.get_mut(key).and_then(json::Value::as_object_mut) {
+      tasks.values_mut().for_each(f);
+    }
+  }
+}
+
+/// Rename the fields of history entries: the author, alongside the event, and the fields of the event.
+fn rename_entries(entries: &mut json::Value, rename: fn(&str) -> Option<&'static str>) {
+  for entry in entries.as_array_mut().into_iter().flatten() {
+    if let Some(entry) = entry.as_object_mut() {
+      for fields in entry.values_mut().filter_map(json::Value::as_object_mut) {
+        rename_fields(fields, rename);
+      }
+
+      rename_fields(entry, rename);
+    }
+  }
+}
+
+fn rename_fields(
+  fields: &mut json::Map<String, json::Value>,
+  rename: fn(&str) -> Option<&'static str>,
+) {
+  let renamed = fields
+    .keys()
+    .filter_map(|name| Some((name.clone(), rename(name)?)))
+    .collect::<Vec<_>>();
+
+  for (name, new_name) in renamed {
+    if let Some(value) = fields.remove(&name) {
+      fields.insert(new_name.to_owned(), value);
+    }
+  }
+}
+
+#[cfg(test)]
+mod unit_tests {
+  use super::*;
+
+  #[test]
+  fn short_names_are_unique() {
+    for (i, (long, short)) in FIELDS.iter().enumerate() {
+      assert!(FIELDS.iter().all(|(other, _)| other != short), "{}", short);
+      assert!(FIELDS[i + 1..]
+        .iter()
+        .all(|(other_long, other_short)| other_long != long && other_short != short));
+    }
+  }
+
+  #[test]
+  fn round_trip() {
+    let long = json::json!({
+      "version": 1,
+      "next_uid": 2,
+      "tasks": {
+        "0": {
+          "name": "Foo",
+          "history": [
+            { "Created": "2021-01-01T00:00:00Z", "author": "alice" },
+            { "StatusChanged": { "event_date": "2021-01-01T00:00:00Z", "status": "Todo" } },
+            { "SetField": { "event_date": "2021-01-02T00:00:00Z", "name": "points", "value": 3 } },
+          ],
+        },
+      },
+      "trash": {
+        "1": {
+          "name": "Bar",
+          "history": [{ "AddTag": { "event_date": "2021-01-01T00:00:00Z", "tag": "x" } }],
+        },
+      },
+    });
+
+    let mut tasks = long.clone();
+    shorten_tasks(&mut tasks);
+    assert_eq!(tasks["tasks"]["0"]["name"], "Foo");
+    assert_eq!(tasks["tasks"]["0"]["history"][0]["a"], "alice");
+    assert_eq!(
+      tasks["tasks"]["0"]["history"][2]["SetField"],
+      json::json!({ "d": "2021-01-02T00:00:00Z", "nm": "points", "v": 3 })
+    );
+    assert_eq!(tasks["trash"]["1"]["history"][0]["AddTag"]["tg"], "x");
+
+    expand_tasks(&mut tasks);
+    assert_eq!(tasks, long);
+
+    // tasks in the long form are left untouched
+    expand_tasks(&mut tasks);
+    assert_eq!(tasks, long);
+  }
+}
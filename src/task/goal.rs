@@ -0,0 +1,140 @@
+//! Goals, tracked by the tasks linked to them.
+//!
+//! A goal, such as shipping a release, is stored along with the tasks, and tasks are linked to it with an event. The
+//! progress towards a goal is the share of its linked tasks that are done; cancelled tasks don’t count.
+
+use super::{Status, Task, TaskManager, UID};
+use chrono::NaiveDate;
+use serde::{Deserialize, Serialize};
+
+/// Goal the tasks linked to it contribute to.
+#[derive(Clone, Debug, Deserialize, Eq, PartialEq, Serialize)]
+pub struct Goal {
+  /// Name of the goal.
+  name: String,
+  /// Date the goal is due, if any.
+  #[serde(default, skip_serializing_if = "Option::is_none")]
+  due: Option<NaiveDate>,
+}
+
+impl Goal {
+  pub fn new(name: impl Into<String>, due: Option<NaiveDate>) -> Self {
+    Self {
+      name: name.into(),
+      due,
+    }
+  }
+
+  /// Get the name of the [`Goal`].
+  pub fn name(&self) -> &str {
+    &self.name
+  }
+
+  /// Get the date the [`Goal`] is due, if any.
+  pub fn due(&self) -> Option<NaiveDate> {
+    self.due
+  }
+}
+
+/// Progress towards a goal.
+#[derive(Clone, Copy, Debug, Default, Eq, PartialEq)]
+pub struct Progress {
+  /// Number of linked tasks that are done.
+  pub done: usize,
+  /// Number of linked tasks, cancelled ones excepted.
+  pub total: usize,
+}
+
+impl TaskManager {
+  /// Add a goal and return its UID; goals are numbered from 1.
+  pub fn add_goal(&mut self, goal: Goal) -> UID {
+    let uid = self
+      .goals
+      .keys()
+      .next_back()
+      .map_or(UID(1), |last| UID(last.0 + 1));
+    self.goals.insert(uid, goal);
+    uid
+  }
+
+  /// Get a goal by its UID.
+  pub fn goal(&self, uid: UID) -> Option<&Goal> {
+    self.goals.get(&uid)
+  }
+
+  /// Iterate over the goals, by UID.
+  pub fn goals(&self) -> impl Iterator<Item = (UID, &Goal)> {
+    self.goals.iter().map(|(&uid, goal)| (uid, goal))
+  }
+
+  /// Get the tasks linked to a goal, by UID; removed tasks are left out.
+  pub fn goal_tasks(&self, goal: UID) -> Vec<(UID, &Task)> {
+    let mut tasks = self
+      .tasks
+      .iter()
+      .filter(|(_, task)| task.goal() == Some(goal))
+      .map(|(&uid, task)| (uid, task))
+      .collect::<Vec<_>>();
+    tasks.sort_by_key(|&(uid, _)| uid);
+    tasks
+  }
+
+  /// Compute the progress towards a goal.
+  pub fn goal_progress(&self, goal: UID) -> Progress {
+    self
+      .goal_tasks(goal)
+      .into_iter()
+      .fold(Progress::default(), |progress, (_, task)| {
+        match task.status() {
+          Status::Cancelled => progress,
+          Status::Done => Progress {
+            done: progress.done + 1,
+            total: progress.total + 1,
+          },
+          _ => Progress {
+            total: progress.total + 1,
+            ..progress
+          },
+        }
+      })
+  }
+}
+
+#[cfg(test)]
+mod unit_tests {
+  use super::*;
+
+  #[test]
+  fn goals() {
+    let mut task_mgr = TaskManager::in_memory();
+    let release = task_mgr.add_goal(Goal::new("Ship v1.0", NaiveDate::from_ymd_opt(2021, 9, 1)));
+    let blog = task_mgr.add_goal(Goal::new("Write a blog post", None));
+    assert_eq!((release, blog), (UID(1), UID(2)));
+    assert_eq!(task_mgr.goal(release).unwrap().name(), "Ship v1.0");
+    assert_eq!(task_mgr.goals().count(), 2);
+
+    let mut uids = vec![
+      Status::Done,
+      Status::Todo,
+      Status::Cancelled,
+      Status::Ongoing,
+    ]
+    .into_iter()
+    .map(|status| {
+      let mut task = Task::new("Task");
+      task.set_goal(release);
+      task.change_status(status);
+      task_mgr.register_task(task)
+    })
+    .collect::<Vec<_>>();
+    task_mgr.register_task(Task::new("Unlinked"));
+    task_mgr.remove_task(uids.pop().unwrap()).unwrap();
+
+    assert_eq!(task_mgr.goal_tasks(release).len(), 3);
+    assert_eq!(
+      task_mgr.goal_progress(release),
+      Progress { done: 1, total: 2 }
+    );
+    assert_eq!(task_mgr.goal_progress(blog), Progress::default());
+  }
+}
@@ -0,0 +1,501 @@
+//! Journal of the changes made to the tasks.
+//!
+//! When enabled, saving the tasks appends the changes made since the previous save to a journal — a JSON Lines file
+//! next to the tasks file — instead of rewriting the whole tasks file, so that quick commands only write a few bytes.
+//! The tasks are loaded by replaying the journal on top of the tasks file. Once the journal holds more than
+//! [`COMPACTION_THRESHOLD`] records, or with `td gc`, it is compacted: the tasks file is rewritten and the journal
+//! removed.
+//!
+//! Records are idempotent, so that replaying a journal that was already compacted — if toodoux was interrupted in the
+//! middle of a compaction — gives the same tasks.
+
+use super::{compact, goal::Goal, max_seq, HistoryEntry, Seal, Task, TaskManager, UID};
+use crate::error::Error;
+use serde::{Deserialize, Serialize};
+use serde_json as json;
+use std::{
+  collections::{BTreeMap, HashMap},
+  path::Path,
+};
+
+/// Number of records above which the journal is compacted into the tasks file.
+pub const COMPACTION_THRESHOLD: usize = 1000;
+
+/// Change recorded in the journal.
+#[derive(Debug, Deserialize, Serialize)]
+#[serde(tag = "op", rename_all = "snake_case")]
+enum Record {
+  /// Task added, or changed in a way that cannot be recorded as appended events.
+  Put { uid: UID, trashed: bool, task: Task },
+
+  /// Events appended to the history of a task, starting at the index `from`.
+  Append {
+    uid: UID,
+    trashed: bool,
+    from: usize,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    name: Option<String>,
+    events: Vec<HistoryEntry>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    seal: Option<Seal>,
+  },
+
+  /// Task deleted for good.
+  Delete { uid: UID },
+
+  /// State of the task manager.
+  State {
+    next_uid: UID,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    context: Option<String>,
+    /// Goals, as pairs rather than a map, as internally tagged records cannot have maps with UID keys.
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    goals: Vec<(UID, Goal)>,
+  },
+}
+
+/// State of the tasks as last persisted, to find out what changed since then.
+#[derive(Debug, Default)]
+pub struct Persisted {
+  tasks: HashMap<UID, PersistedTask>,
+  next_uid: UID,
+  context: Option<String>,
+  goals: BTreeMap<UID, Goal>,
+  /// Number of records in the journal.
+  records: usize,
+}
+
+#[derive(Debug)]
+struct PersistedTask {
+  trashed: bool,
+  name: String,
+  events: usize,
+  seal: Option<Seal>,
+}
+
+impl Persisted {
+  /// Record the current state of the tasks, along with the number of records in the journal.
+  pub fn new(task_mgr: &TaskManager, records: usize) -> Self {
+    let tasks = all_tasks(task_mgr)
+      .map(|(uid, trashed, task)| {
+        let persisted = PersistedTask {
+          trashed,
+          name: task.name.clone(),
+          events: task.history.len(),
+          seal: task.seal.clone(),
+        };
+        (uid, persisted)
+      })
+      .collect();
+
+    Persisted {
+      tasks,
+      next_uid: task_mgr.next_uid,
+      context: task_mgr.context.clone(),
+      goals: task_mgr.goals.clone(),
+      records,
+    }
+  }
+
+  /// Number of records in the journal.
+  pub fn records(&self) -> usize {
+    self.records
+  }
+
+  /// Forget the persisted state of a task whose history was rewritten, so that it is recorded as a whole.
+  pub fn forget(&mut self, uid: UID) {
+    self.tasks.remove(&uid);
+  }
+}
+
+/// Iterate over the active and removed tasks, along with whether they are removed.
+fn all_tasks(task_mgr: &TaskManager) -> impl Iterator<Item = (UID, bool, &Task)> {
+  let active = task_mgr.tasks.iter().map(|(&uid, task)| (uid, false, task));
+  let trashed = task_mgr.trash.iter().map(|(&uid, task)| (uid, true, task));
+  active.chain(trashed)
+}
+
+/// Replay the journal, if any, on top of the tasks; return the number of replayed records.
+///
+/// A truncated last record, left by an interrupted save, is ignored and removed from the journal, so that the next
+/// records are appended after the last complete one.
+pub fn replay(task_mgr: &mut TaskManager) -> Result<usize, Error> {
+  let content = match task_mgr.storage.read_journal()? {
+    Some(content) => content,
+    None => return Ok(0),
+  };
+
+  let mut records = 0;
+  let mut offset = 0;
+
+  for (i, line) in content.split_inclusive('\n').enumerate() {
+    if !line.trim().is_empty() {
+      match read_record(line) {
+        Ok(record) => {
+          apply(task_mgr, record);
+          records += 1;
+        }
+
+        Err(err) if offset + line.len() == content.len() => {
+          log::warn!("removing truncated journal record: {}", err);
+          task_mgr.storage.truncate_journal(offset)?;
+        }
+
+        Err(source) => {
+          return Err(Error::InvalidJournal {
+            path: task_mgr.storage.journal_path().map(Path::to_owned),
+            line: i + 1,
+            source,
+          })
+        }
+      }
+    }
+
+    offset += line.len();
+  }
+
+  Ok(records)
+}
+
+fn apply(task_mgr: &mut TaskManager, record: Record) {
+  match record {
+    Record::Put { uid, trashed, task } => {
+      task_mgr.tick(max_seq(&task.history));
+      task_mgr.tasks.remove(&uid);
+      task_mgr.trash.remove(&uid);
+      tasks_of(task_mgr, trashed).insert(uid, task);
+    }
+
+    Record::Append {
+      uid,
+      trashed,
+      from,
+      name,
+      events,
+      seal,
+    } => {
+      task_mgr.tick(max_seq(&events));
+      let task = task_mgr
+        .tasks
+        .remove(&uid)
+        .or_else(|| task_mgr.trash.remove(&uid));
+
+      match task {
+        Some(mut task) if task.history.len() >= from => {
+          task.history.truncate(from);
+          task.history.extend(events);
+          task.seal = seal;
+
+          if let Some(name) = name {
+            task.name = name;
+          }
+
+          tasks_of(task_mgr, trashed).insert(uid, task);
+        }
+
+        Some(task) => {
+          log::warn!(
+            "journal events of task {} don’t follow its history; ignoring them",
+            uid
+          );
+          tasks_of(task_mgr, trashed).insert(uid, task);
+        }
+
+        None => log::warn!("journal events of unknown task {}; ignoring them", uid),
+      }
+    }
+
+    Record::Delete { uid } => {
+      task_mgr.tasks.remove(&uid);
+      task_mgr.trash.remove(&uid);
+    }
+
+    Record::State {
+      next_uid,
+      context,
+      goals,
+    } => {
+      task_mgr.next_uid = next_uid;
+      task_mgr.context = context;
+      task_mgr.goals = goals.into_iter().collect();
+    }
+  }
+}
+
+fn tasks_of(task_mgr: &mut TaskManager, trashed: bool) -> &mut HashMap<UID, Task> {
+  if trashed {
+    &mut task_mgr.trash
+  } else {
+    &mut task_mgr.tasks
+  }
+}
+
+/// Compute the records of the changes made since the tasks were last persisted.
+///
+/// Histories only grow, so a task whose history is longer than when it was persisted is recorded as appended events;
+/// other changed tasks are recorded as a whole.
+fn changes(task_mgr: &TaskManager) -> Vec<Record> {
+  let persisted = &task_mgr.persisted;
+  let mut records = Vec::new();
+  let mut tasks = all_tasks(task_mgr).collect::<Vec<_>>();
+  tasks.sort_by_key(|&(uid, ..)| uid);
+
+  for (uid, trashed, task) in tasks {
+    let before = match persisted.tasks.get(&uid) {
+      Some(before) if before.events <= task.history.len() => before,
+
+      _ => {
+        records.push(Record::Put {
+          uid,
+          trashed,
+          task: task.clone(),
+        });
+        continue;
+      }
+    };
+
+    let renamed = before.name != task.name;
+    if before.events < task.history.len()
+      || before.trashed != trashed
+      || renamed
+      || before.seal != task.seal
+    {
+      records.push(Record::Append {
+        uid,
+        trashed,
+        from: before.events,
+        name: Some(task.name.clone()).filter(|_| renamed),
+        events: task.history[before.events..].to_vec(),
+        seal: task.seal.clone(),
+      });
+    }
+  }
+
+  let mut deleted = persisted
+    .tasks
+    .keys()
+    .filter(|uid| !task_mgr.tasks.contains_key(uid) && !task_mgr.trash.contains_key(uid))
+    .copied()
+    .collect::<Vec<_>>();
+  deleted.sort();
+  records.extend(deleted.into_iter().map(|uid| Record::Delete { uid }));
+
+  if persisted.next_uid != task_mgr.next_uid
+    || persisted.context != task_mgr.context
+    || persisted.goals != task_mgr.goals
+  {
+    records.push(Record::State {
+      next_uid: task_mgr.next_uid,
+      context: task_mgr.context.clone(),
+      goals: task_mgr
+        .goals
+        .iter()
+        .map(|(&uid, goal)| (uid, goal.clone()))
+        .collect(),
+    });
+  }
+
+  records
+}
+
+/// Read a record, in the long or the compact form.
+fn read_record(line: &str) -> Result<Record, json::Error> {
+  let mut record: json::Value = json::from_str(line)?;
+
+  if let Some(task) = record.get_mut("task") {
+    compact::expand_task(task);
+  }
+
+  if let Some(events) = record.get_mut("events") {
+    compact::expand_entries(events);
+  }
+
+  json::from_value(record)
+}
+
+/// Serialize a record, in the compact form if `compact` is set.
+fn write_record(record: &Record, compact: bool) -> Result<String, json::Error> {
+  if !compact {
+    return json::to_string(record);
+  }
+
+  let mut record = json::to_value(record)?;
+
+  if let Some(task) = record.get_mut("task") {
+    compact::shorten_task(task);
+  }
+
+  if let Some(events) = record.get_mut("events") {
+    compact::shorten_entries(events);
+  }
+
+  json::to_string(&record)
+}
+
+/// Append the changes made since the tasks were last persisted to the journal, in the compact form if `compact` is
+/// set.
+///
+/// Return the number of appended records.
+pub fn append(task_mgr: &mut TaskManager, compact: bool) -> Result<usize, Error> {
+  let records = changes(task_mgr);
+
+  if records.is_empty() {
+    return Ok(0);
+  }
+
+  // write all the records at once, so that a save is never partially journaled
+  let mut buffer = String::new();
+  for record in &records {
+    buffer.push_str(&write_record(record, compact)?);
+    buffer.push('\n');
+  }
+
+  task_mgr.storage.append_journal(&buffer)?;
+
+  Ok(records.len())
+}
+
+#[cfg(test)]
+mod unit_tests {
+  use super::*;
+  use crate::task::Status;
+
+  fn journal(task_mgr: &TaskManager) -> String {
+    task_mgr.storage.read_journal().unwrap().unwrap_or_default()
+  }
+
+  /// Empty tasks stored in memory, along with a journal.
+  fn with_journal(journal: &str) -> TaskManager {
+    let mut task_mgr = TaskManager::in_memory();
+    task_mgr.storage.append_journal(journal).unwrap();
+    task_mgr
+  }
+
+  /// Replay records, serialized and deserialized, on top of a copy of the persisted tasks.
+  fn replayed(base: &TaskManager, records: &[Record]) -> TaskManager {
+    let mut replayed = TaskManager::in_memory();
+    replayed.next_uid = base.next_uid;
+    replayed.context = base.context.clone();
+    replayed.tasks = base.tasks.clone();
+    replayed.trash = base.trash.clone();
+
+    for record in records {
+      apply(&mut replayed, clone_record(record));
+    }
+
+    replayed
+  }
+
+  fn clone_record(record: &Record) -> Record {
+    json::from_str(&json::to_string(record).unwrap()).unwrap()
+  }
+
+  fn assert_same_tasks(a: &TaskManager, b: &TaskManager) {
+    let summary = |task_mgr: &TaskManager| {
+      let mut tasks = all_tasks(task_mgr)
+        .map(|(uid, trashed, task)| {
+          (
+            uid,
+            trashed,
+            task.name.clone(),
+            task.history().cloned().collect::<Vec<_>>(),
+          )
+        })
+        .collect::<Vec<_>>();
+      tasks.sort_by_key(|(uid, ..)| *uid);
+      (
+        task_mgr.next_uid,
+        task_mgr.context.clone(),
+        task_mgr.goals.clone(),
+        tasks,
+      )
+    };
+
+    assert_eq!(summary(a), summary(b));
+  }
+
+  #[test]
+  fn journal_changes() {
+    let mut base = TaskManager::in_memory();
+    let foo = base.register_task(Task::new("Foo"));
+    let bar = base.register_task(Task::new("Bar"));
+    let zoo = base.register_task(Task::new("Zoo"));
+    base.remove_task(zoo).unwrap();
+    base.persisted = Persisted::new(&base, 0);
+    assert!(changes(&base).is_empty());
+
+    let mut task_mgr = TaskManager::in_memory();
+    task_mgr.next_uid = base.next_uid;
+    task_mgr.tasks = base.tasks.clone();
+    task_mgr.trash = base.trash.clone();
+    task_mgr.persisted = Persisted::new(&task_mgr, 0);
+
+    task_mgr.get_mut(foo).unwrap().change_status(Status::Done);
+    task_mgr.get_mut(foo).unwrap().change_name("Foo!");
+    task_mgr.remove_task(bar).unwrap();
+    task_mgr.trash.remove(&zoo);
+    let quux = task_mgr.register_task(Task::new("Quux"));
+    task_mgr.set_context(Some("work".to_owned()));
+    task_mgr.add_goal(Goal::new("Ship", None));
+
+    let records = changes(&task_mgr);
+    assert!(
+      matches!(
+        records[..],
+        [
+          Record::Append { uid: uid1, trashed: false, from: 2, name: Some(_), .. },
+          Record::Append { uid: uid2, trashed: true, from: 2, name: None, .. },
+          Record::Put { uid: uid3, trashed: false, .. },
+          Record::Delete { uid: uid4 },
+          Record::State { .. },
+        ] if uid1 == foo && uid2 == bar && uid3 == quux && uid4 == zoo
+      ),
+      "{:#?}",
+      records
+    );
+    assert_same_tasks(&replayed(&base, &records), &task_mgr);
+
+    // replaying the journal twice, as after an interrupted compaction, gives the same tasks
+    let twice = records
+      .iter()
+      .chain(&records)
+      .map(clone_record)
+      .collect::<Vec<_>>();
+    assert_same_tasks(&replayed(&base, &twice), &task_mgr);
+  }
+
+  #[test]
+  fn replay_truncated_journal() {
+    let mut saved = TaskManager::in_memory();
+    let foo = saved.register_task(Task::new("Foo"));
+    append(&mut saved, false).unwrap();
+    let complete = journal(&saved);
+
+    let mut replayed = with_journal(&format!("{}{{\"op\":\"put\",\"ui", complete));
+    assert_eq!(replay(&mut replayed).unwrap(), 2);
+    assert_eq!(replayed.get(foo).map(Task::name), Some("Foo"));
+    assert_eq!(journal(&replayed), complete);
+
+    // only the last record can be truncated
+    let mut replayed = with_journal(&format!("{{\"op\":\"put\"}}\n{}", complete));
+    assert!(replay(&mut replayed).is_err());
+  }
+
+  #[test]
+  fn replay_compact_journal() {
+    let mut saved = TaskManager::in_memory();
+    let foo = saved.register_task(Task::new("Foo"));
+    append(&mut saved, true).unwrap();
+    saved.persisted = Persisted::new(&saved, 2);
+    saved.get_mut(foo).unwrap().change_status(Status::Ongoing);
+    append(&mut saved, true).unwrap();
+
+    let journal = journal(&saved);
+    assert!(journal.contains("\"d\":"));
+    assert!(!journal.contains("event_date"));
+
+    let mut replayed = with_journal(&journal);
+    assert_eq!(replay(&mut replayed).unwrap(), 3);
+    assert_same_tasks(&replayed, &saved);
+  }
+}
@@ -0,0 +1,620 @@
+//! Merge of diverging copies of the tasks.
+//!
+//! Tasks shared between machines — with git or a shared drive — can change on both sides before being synced, leaving
+//! two copies of the tasks file, such as a “conflicted copy” left by the sync tool. Histories only grow: when the
+//! history of a task in a copy extends its history in the other copy, the longest one is kept. Otherwise, both
+//! histories diverged after a common prefix, and the task is a [`Conflict`], resolved with a [`Strategy`] or by editing
+//! its events by hand.
+//!
+//! Notes, reminders and checklist items are numbered in the order they are added to a task, so merging the events of
+//! both copies renumbers the items added by each copy, along with the events referring to them.
+
+use super::{Event, HistoryEntry, Task, TaskManager, UID};
+use crate::{error::Error, tr};
+use serde_json as json;
+use std::{collections::HashMap, fmt, mem, str::FromStr};
+
+/// Way of resolving a [`Conflict`].
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub enum Strategy {
+  /// Keep the events of both copies, ordered by date.
+  Union,
+  /// Keep the task as in the other copy.
+  Theirs,
+  /// Keep the task as in this copy.
+  Mine,
+}
+
+impl FromStr for Strategy {
+  type Err = String;
+
+  fn from_str(s: &str) -> Result<Self, Self::Err> {
+    match s {
+      "union" => Ok(Strategy::Union),
+      "theirs" => Ok(Strategy::Theirs),
+      "mine" => Ok(Strategy::Mine),
+      _ => Err(tr!("unknown strategy {}; use union, theirs or mine", s)),
+    }
+  }
+}
+
+impl fmt::Display for Strategy {
+  fn fmt(&self, f: &mut fmt::Formatter) -> Result<(), fmt::Error> {
+    f.write_str(match self {
+      Strategy::Union => "union",
+      Strategy::Theirs => "theirs",
+      Strategy::Mine => "mine",
+    })
+  }
+}
+
+/// Outcome of merging another copy of the tasks; see [`TaskManager::merge`].
+#[derive(Debug, Default)]
+pub struct Merge {
+  /// Tasks of the other copy that were missing from this one.
+  pub added: Vec<UID>,
+  /// Tasks of the other copy whose UID was taken by another task in this one, along with their new UID.
+  pub renumbered: Vec<(UID, UID)>,
+  /// Tasks whose history in the other copy extends their history in this one.
+  pub updated: Vec<UID>,
+  /// Tasks whose histories diverged, ordered by UID.
+  pub conflicts: Vec<Conflict>,
+}
+
+/// Task whose history diverged between both copies of the tasks.
+#[derive(Clone, Debug)]
+pub struct Conflict {
+  uid: UID,
+  /// Number of events both histories start with.
+  common: usize,
+  mine: Task,
+  theirs: Task,
+}
+
+impl Conflict {
+  /// UID of the task.
+  pub fn uid(&self) -> UID {
+    self.uid
+  }
+
+  /// Name of the task, in this copy.
+  pub fn name(&self) -> &str {
+    self.mine.name()
+  }
+
+  /// Events of this copy since the histories diverged, along with their author.
+  pub fn mine(&self) -> impl Iterator<Item = (&Event, Option<&str>)> {
+    self.mine.authored_history().skip(self.common)
+  }
+
+  /// Events of the other copy since the histories diverged, along with their author.
+  pub fn theirs(&self) -> impl Iterator<Item = (&Event, Option<&str>)> {
+    self.theirs.authored_history().skip(self.common)
+  }
+
+  /// Resolve the conflict with `strategy`, giving the resolved task.
+  pub fn resolve(self, strategy: Strategy) -> Task {
+    match strategy {
+      Strategy::Mine => self.mine,
+      Strategy::Theirs => self.theirs,
+      Strategy::Union => {
+        let events = self.union().into_iter().map(|(_, entry)| entry);
+        self.with_events(events)
+      }
+    }
+  }
+
+  /// Events of both copies since the histories diverged, one per line, to be edited by hand and passed to
+  /// [`Conflict::resolve_edited`].
+  ///
+  /// Events are written as JSON, ordered as with [`Strategy::Union`] and prefixed with the copy they come from.
+  pub fn to_edit(&self) -> Result<String, Error> {
+    let mut text = format!(
+      "# {}\n# {}\n# {}\n",
+      tr!(
+        "Task {} ({}) changed in both copies since they diverged.",
+        self.uid,
+        self.name()
+      ),
+      tr!("Delete the events to drop, or edit them, and save; the events before them are kept."),
+      tr!("Notes, reminders and checklist items are numbered from 0, in the order they are added."),
+    );
+
+    for (side, entry) in self.union() {
+      let side = match side {
+        Side::Mine => "mine  ",
+        Side::Theirs => "theirs",
+      };
+      text += &format!("{} {}\n", side, json::to_string(&entry)?);
+    }
+
+    Ok(text)
+  }
+
+  /// Resolve the conflict with the events of `text`, as given by [`Conflict::to_edit`] and edited by hand.
+  ///
+  /// Empty lines and lines starting with `#` are ignored; the events are read from the first `{` of each line.
+  pub fn resolve_edited(self, text: &str) -> Result<Task, Error> {
+    let mut events = Vec::new();
+
+    for (i, line) in text.lines().enumerate() {
+      let line = line.trim();
+      if line.is_empty() || line.starts_with('#') {
+        continue;
+      }
+
+      let event = line.find('{').map_or(line, |start| &line[start..]);
+      let entry = json::from_str(event).map_err(|source| Error::InvalidEvent {
+        line: i + 1,
+        source,
+      })?;
+      events.push(entry);
+    }
+
+    Ok(self.with_events(events))
+  }
+
+  /// Task of this copy, with `events` in place of the events since the histories diverged.
+  ///
+  /// The seal is dropped, for the history is rewritten; it is sealed anew when saved.
+  fn with_events(self, events: impl IntoIterator<Item = HistoryEntry>) -> Task {
+    let mut task = self.mine;
+    task.history.truncate(self.common);
+    task.history.extend(events);
+    task.seal = None;
+    task
+  }
+
+  /// Events of both copies since the histories diverged, ordered by date, along with the copy they come from.
+  ///
+  /// The events of each copy keep their order, and the items they add are renumbered after the items of both copies
+  /// added before them.
+  fn union(&self) -> Vec<(Side, HistoryEntry)> {
+    let mut mine = self.mine.history[self.common..].iter().peekable();
+    let mut theirs = self.theirs.history[self.common..].iter().peekable();
+    let mut union = Vec::new();
+
+    loop {
+      let side = match (mine.peek(), theirs.peek()) {
+        (Some(a), Some(b)) if (a.event.date(), a.seq) <= (b.event.date(), b.seq) => Side::Mine,
+        (Some(_), Some(_)) | (None, Some(_)) => Side::Theirs,
+        (Some(_), None) => Side::Mine,
+        (None, None) => break,
+      };
+
+      let entry = match side {
+        Side::Mine => mine.next(),
+        Side::Theirs => theirs.next(),
+      };
+      union.extend(entry.map(|entry| (side, entry.clone())));
+    }
+
+    // items added before the histories diverged keep their number
+    let mut common = HashMap::new();
+    for item in self.mine.history[..self.common]
+      .iter()
+      .filter_map(|entry| Numbered::added_by(&entry.event))
+    {
+      *common.entry(item).or_insert(0) += 1;
+    }
+
+    let mut added = common.clone();
+    let mut added_by_side = HashMap::new();
+    let mut renumbering = HashMap::new();
+
+    for (side, entry) in &mut union {
+      if let Some((item, uid)) = Numbered::referred_by(&mut entry.event) {
+        if let Some(&renumbered) = renumbering.get(&(*side, item, *uid)) {
+          *uid = renumbered;
+        }
+      }
+
+      if let Some(item) = Numbered::added_by(&entry.event) {
+        let base = common.get(&item).copied().unwrap_or(0);
+        let side_uid = added_by_side.entry((*side, item)).or_insert(base);
+        let uid = added.entry(item).or_insert(base);
+        renumbering.insert((*side, item, UID(*side_uid)), UID(*uid));
+        *side_uid += 1;
+        *uid += 1;
+      }
+    }
+
+    union
+  }
+}
+
+/// Copy of the tasks an event comes from.
+#[derive(Clone, Copy, Debug, Eq, Hash, PartialEq)]
+enum Side {
+  Mine,
+  Theirs,
+}
+
+/// Items numbered in the order they are added to a task.
+#[derive(Clone, Copy, Debug, Eq, Hash, PartialEq)]
+enum Numbered {
+  Note,
+  Reminder,
+  ChecklistItem,
+}
+
+impl Numbered {
+  /// Item added by `event`, if any.
+  fn added_by(event: &Event) -> Option<Self> {
+    match event {
+      Event::NoteAdded { .. } => Some(Numbered::Note),
+      Event::ReminderAdded { .. } => Some(Numbered::Reminder),
+      Event::ChecklistItemAdded { .. } => Some(Numbered::ChecklistItem),
+      _ => None,
+    }
+  }
+
+  /// Item `event` refers to, if any.
+  fn referred_by(event: &mut Event) -> Option<(Self, &mut UID)> {
+    match event {
+      Event::NoteAdded {
+        in_reply_to: Some(note_uid),
+        ..
+      }
+      | Event::NoteReplaced { note_uid, .. } => Some((Numbered::Note, note_uid)),
+      Event::ReminderDelivered { reminder_uid, .. } => Some((Numbered::Reminder, reminder_uid)),
+      Event::ChecklistItemToggled { item_uid, .. } => Some((Numbered::ChecklistItem, item_uid)),
+      _ => None,
+    }
+  }
+}
+
+impl TaskManager {
+  /// Merge `theirs`, another copy of the tasks, into these tasks.
+  ///
+  /// Tasks that only changed in the other copy are updated, and the tasks it adds are added — but for the tasks of its
+  /// trash missing here, which were deleted for good. A task of the other copy whose UID is taken here by another task
+  /// is added with a new UID. Tasks that changed in both copies are left as-is and returned as conflicts, to be
+  /// resolved and put back with [`TaskManager::settle`].
+  pub fn merge(&mut self, theirs: TaskManager) -> Merge {
+    let mut merge = Merge::default();
+    let mut renumbered = Vec::new();
+    self.tick(theirs.clock);
+
+    let mut tasks = theirs
+      .tasks
+      .into_iter()
+      .map(|(uid, task)| (uid, false, task))
+      .chain(
+        theirs
+          .trash
+          .into_iter()
+          .map(|(uid, task)| (uid, true, task)),
+      )
+      .collect::<Vec<_>>();
+    tasks.sort_by_key(|&(uid, ..)| uid);
+
+    for (their_uid, trashed, task) in tasks {
+      // tasks are matched by their creation, so that a task renumbered by a previous merge is found again
+      let matches = |mine: &Task| common_len(&mine.history, &task.history) > 0;
+      let uid = Some(their_uid)
+        .filter(|uid| self.find(*uid).is_some_and(matches))
+        .or_else(|| {
+          self
+            .tasks
+            .iter()
+            .chain(&self.trash)
+            .find(|(_, mine)| matches(mine))
+            .map(|(&uid, _)| uid)
+        });
+
+      let (uid, mine) = match uid.and_then(|uid| Some(uid).zip(self.find(uid))) {
+        Some(found) => found,
+        None if trashed => continue,
+        None if self.find(their_uid).is_some() => {
+          renumbered.push((their_uid, task));
+          continue;
+        }
+        None => {
+          self.settle(their_uid, task);
+          merge.added.push(their_uid);
+          continue;
+        }
+      };
+
+      let common = common_len(&mine.history, &task.history);
+      let (mine_events, their_events) = (&mine.history[common..], &task.history[common..]);
+
+      // events merged by a previous resolution are already there, though the items they refer to may be renumbered
+      let merged = their_events
+        .iter()
+        .all(|theirs| mine_events.iter().any(|mine| same_origin(mine, theirs)));
+
+      if merged {
+        continue;
+      } else if mine_events.is_empty() {
+        self.settle(uid, task);
+        merge.updated.push(uid);
+      } else {
+        merge.conflicts.push(Conflict {
+          uid,
+          common,
+          mine: mine.clone(),
+          theirs: task,
+        });
+      }
+    }
+
+    for (uid, goal) in theirs.goals {
+      self.goals.entry(uid).or_insert(goal);
+    }
+
+    self.next_uid = self.next_uid.max(theirs.next_uid);
+    for (uid, task) in renumbered {
+      let new_uid = self.next_uid;
+      self.increment_uid();
+      self.settle(new_uid, task);
+      merge.renumbered.push((uid, new_uid));
+    }
+
+    merge
+  }
+
+  /// Find the task, active or removed, at `uid`.
+  fn find(&self, uid: UID) -> Option<&Task> {
+    self.tasks.get(&uid).or_else(|| self.trash.get(&uid))
+  }
+
+  /// Put a merged task at `uid`, replacing the task there, if any; the task goes to the trash if it was removed.
+  pub fn settle(&mut self, uid: UID, task: Task) {
+    let trashed = task
+      .history()
+      .rev()
+      .find_map(|event| match event {
+        Event::Removed { .. } => Some(true),
+        Event::Restored { .. } => Some(false),
+        _ => None,
+      })
+      .unwrap_or(false);
+
+    self.tasks.remove(&uid);
+    self.trash.remove(&uid);
+    self.persisted.forget(uid);
+
+    if trashed {
+      self.trash.insert(uid, task);
+    } else {
+      self.tasks.insert(uid, task);
+    }
+  }
+}
+
+/// Number of entries both `a` and `b` start with.
+fn common_len(a: &[HistoryEntry], b: &[HistoryEntry]) -> usize {
+  a.iter()
+    .zip(b)
+    .take_while(|(a, b)| a.event == b.event && a.author == b.author && a.seq == b.seq)
+    .count()
+}
+
+/// Whether `a` and `b` are the same event, recorded once; the items they refer to may have been renumbered since.
+fn same_origin(a: &HistoryEntry, b: &HistoryEntry) -> bool {
+  a.event.date() == b.event.date()
+    && a.seq == b.seq
+    && a.author == b.author
+    && mem::discriminant(&a.event) == mem::discriminant(&b.event)
+}
+
+#[cfg(test)]
+mod unit_tests {
+  use super::*;
+  use crate::task::Status;
+
+  /// Tasks parsed from JSON, each task given as a UID and a history.
+  fn tasks(next_uid: u32, tasks: &[(u32, &str)]) -> TaskManager {
+    let tasks = tasks
+      .iter()
+      .map(|(uid, history)| {
+        format!(
+          r#""{}": {{ "name": "Task {}", "history": [{}] }}"#,
+          uid, uid, history
+        )
+      })
+      .collect::<Vec<_>>()
+      .join(",");
+    json::from_str(&format!(
+      r#"{{ "next_uid": {}, "tasks": {{ {} }} }}"#,
+      next_uid, tasks
+    ))
+    .unwrap()
+  }
+
+  /// Creation of the task `uid`, told apart from the creation of the other tasks by its sequence number.
+  fn created(uid: u32) -> String {
+    format!(
+      r#"{{ "Created": "2021-01-01T00:00:00Z", "seq": {} }}"#,
+      10 + uid
+    )
+  }
+
+  fn event(date: &str, seq: u64, event: &str) -> String {
+    format!(
+      r#"{{ {}, "seq": {} }}"#,
+      event.replace(
+        "DATE",
+        &format!("\"event_date\": \"2021-01-0{}T00:00:00Z\"", date)
+      ),
+      seq
+    )
+  }
+
+  #[test]
+  fn merge() {
+    let done = event("2", 2, r#""StatusChanged": { DATE, "status": "Done" }"#);
+    let ongoing = event("3", 3, r#""StatusChanged": { DATE, "status": "Ongoing" }"#);
+    let cancelled = event(
+      "3",
+      4,
+      r#""StatusChanged": { DATE, "status": "Cancelled" }"#,
+    );
+    let other = r#"{ "Created": "2021-01-05T00:00:00Z", "seq": 5 }"#;
+    let removed = event("4", 6, r#""Removed": { DATE }"#);
+
+    let mut mine = tasks(
+      4,
+      &[
+        (0, &created(0)),
+        (1, &format!("{}, {}", created(1), done)),
+        (2, &format!("{}, {}", created(2), ongoing)),
+        (3, &created(3)),
+      ],
+    );
+    let theirs = || {
+      let mut theirs = tasks(
+        6,
+        &[
+          (0, &format!("{}, {}", created(0), done)),
+          (1, &created(1)),
+          (2, &format!("{}, {}", created(2), cancelled)),
+          (3, other),
+          (4, &created(4)),
+        ],
+      );
+      theirs.trash.insert(
+        UID(5),
+        json::from_str(&format!(
+          r#"{{ "name": "Gone", "history": [{}, {}] }}"#,
+          created(5),
+          removed
+        ))
+        .unwrap(),
+      );
+      theirs
+    };
+
+    let merge = mine.merge(theirs());
+    assert_eq!(merge.updated, vec![UID(0)]);
+    assert_eq!(merge.added, vec![UID(4)]);
+    assert_eq!(merge.renumbered, vec![(UID(3), UID(6))]);
+    assert_eq!(
+      merge
+        .conflicts
+        .iter()
+        .map(Conflict::uid)
+        .collect::<Vec<_>>(),
+      vec![UID(2)]
+    );
+    assert_eq!(mine.get(UID(0)).map(Task::status), Some(Status::Done));
+    assert_eq!(mine.get(UID(1)).map(Task::status), Some(Status::Done));
+    assert!(mine.get(UID(5)).is_none());
+    assert_eq!(mine.next_uid, UID(7));
+
+    let conflict = merge.conflicts.into_iter().next().unwrap();
+    assert_eq!(conflict.mine().count(), 1);
+    assert_eq!(conflict.theirs().count(), 1);
+    assert_eq!(
+      conflict.clone().resolve(Strategy::Mine).status(),
+      Status::Ongoing
+    );
+    assert_eq!(
+      conflict.clone().resolve(Strategy::Theirs).status(),
+      Status::Cancelled
+    );
+
+    // both changes happened at the same date; the sequence numbers order them
+    let union = conflict.clone().resolve(Strategy::Union);
+    assert_eq!(union.history().count(), 3);
+    assert_eq!(union.status(), Status::Cancelled);
+
+    mine.settle(UID(2), union);
+    assert_eq!(mine.get(UID(2)).map(Task::status), Some(Status::Cancelled));
+
+    // merging the same copy again changes nothing
+    let merge = mine.merge(theirs());
+    assert!(merge.added.is_empty() && merge.renumbered.is_empty());
+    assert!(merge.updated.is_empty() && merge.conflicts.is_empty());
+  }
+
+  #[test]
+  fn union_renumbers_items() {
+    let note = |date, seq, content| {
+      event(
+        date,
+        seq,
+        &format!(r#""NoteAdded": {{ DATE, "content": "{}" }}"#, content),
+      )
+    };
+    let replaced = |date, seq, uid, content| {
+      event(
+        date,
+        seq,
+        &format!(
+          r#""NoteReplaced": {{ DATE, "note_uid": {}, "content": "{}" }}"#,
+          uid, content
+        ),
+      )
+    };
+
+    let common = format!("{}, {}", created(0), note("1", 2, "common"));
+    let mut mine = tasks(
+      1,
+      &[(
+        0,
+        &format!(
+          "{}, {}, {}",
+          common,
+          note("2", 3, "mine"),
+          replaced("4", 5, 1, "mine!")
+        ),
+      )],
+    );
+    let theirs = tasks(
+      1,
+      &[(
+        0,
+        &format!(
+          "{}, {}, {}",
+          common,
+          note("3", 3, "theirs"),
+          replaced("5", 4, 1, "theirs!")
+        ),
+      )],
+    );
+
+    let conflict = mine.merge(theirs).conflicts.into_iter().next().unwrap();
+    let notes = conflict
+      .resolve(Strategy::Union)
+      .notes()
+      .into_iter()
+      .map(|note| note.content)
+      .collect::<Vec<_>>();
+    assert_eq!(notes, vec!["common", "mine!", "theirs!"]);
+  }
+
+  #[test]
+  fn resolve_edited() {
+    let done = event("2", 2, r#""StatusChanged": { DATE, "status": "Done" }"#);
+    let cancelled = event(
+      "3",
+      2,
+      r#""StatusChanged": { DATE, "status": "Cancelled" }"#,
+    );
+    let mut mine = tasks(1, &[(0, &format!("{}, {}", created(0), done))]);
+    let theirs = tasks(1, &[(0, &format!("{}, {}", created(0), cancelled))]);
+    let conflict = mine.merge(theirs).conflicts.into_iter().next().unwrap();
+
+    let text = conflict.to_edit().unwrap();
+    let lines = text
+      .lines()
+      .filter(|line| !line.starts_with('#'))
+      .collect::<Vec<_>>();
+    assert_eq!(lines.len(), 2);
+    assert!(lines[0].starts_with("mine "));
+    assert!(lines[1].starts_with("theirs "));
+
+    // keep their change only
+    let task = conflict.clone().resolve_edited(lines[1]).unwrap();
+    assert_eq!(task.history().count(), 2);
+    assert_eq!(task.status(), Status::Cancelled);
+
+    assert!(matches!(
+      conflict.resolve_edited("# comment\n\nmine {"),
+      Err(Error::InvalidEvent { line: 3, .. })
+    ));
+  }
+}
@@ -0,0 +1,234 @@
+//! Squashing of task histories.
+//!
+//! Long-lived tasks accumulate events that later ones supersede: a deadline moved again and again, a note edited many
+//! times, a task flipped back and forth between inactive statuses. Squashing drops those events, while keeping what the
+//! task shows: its metadata, notes, status, spent time and timesheet, along with its key dates — creation, completion,
+//! last modification of each note, last review and last activity.
+//!
+//! Squashing rewrites histories, which otherwise only grow; copies of the tasks synced between machines should be in
+//! sync before squashing, so that merging them doesn’t bring the dropped events back.
+
+use super::{Event, Status, Task, TaskManager};
+
+impl Task {
+  /// Drop the events superseded by later ones; return how many were dropped.
+  pub fn squash(&mut self) -> usize {
+    let dropped = dropped_events(&self.history().collect::<Vec<_>>());
+    let mut dropped = dropped.into_iter();
+    let len = self.history.len();
+
+    self.history.retain(|_| !dropped.next().unwrap_or(false));
+    len - self.history.len()
+  }
+}
+
+impl TaskManager {
+  /// Squash the histories of all the tasks, including the ones in the trash; return how many events were dropped.
+  pub fn squash(&mut self) -> usize {
+    self
+      .tasks
+      .values_mut()
+      .chain(self.trash.values_mut())
+      .map(Task::squash)
+      .sum()
+  }
+}
+
+/// Find which events of a history can be dropped.
+///
+/// The last event is always kept, so that the last activity doesn’t change.
+fn dropped_events(events: &[&Event]) -> Vec<bool> {
+  let last = events.len().saturating_sub(1);
+  let mut dropped = events
+    .iter()
+    .enumerate()
+    .map(|(i, event)| i < last && events[i + 1..].iter().any(|later| supersedes(later, event)))
+    .collect::<Vec<_>>();
+
+  let statuses = events
+    .iter()
+    .enumerate()
+    .filter_map(|(i, event)| match event {
+      Event::StatusChanged { status, .. } => Some((i, *status)),
+      _ => None,
+    })
+    .collect::<Vec<_>>();
+
+  // status of the last status change kept
+  let mut current = None;
+  for (j, &(i, status)) in statuses.iter().enumerate() {
+    let later = &statuses[j + 1..];
+
+    // repeating an inactive status or starting an ongoing task again doesn’t change anything; done and cancelled are
+    // kept, as the last one dates the completion
+    let repeated = current == Some(status) && !matches!(status, Status::Done | Status::Cancelled);
+
+    // an inactive status following another inactive one neither starts nor ends a WIP period, so it doesn’t count
+    // towards the spent time once another status follows it; done and cancelled are only dropped if they happen again
+    let flipped = current.is_some_and(|current| current != Status::Ongoing)
+      && status != Status::Ongoing
+      && !later.is_empty()
+      && (!matches!(status, Status::Done | Status::Cancelled)
+        || later.iter().any(|&(_, next)| next == status));
+
+    if i < last && (repeated || flipped) {
+      dropped[i] = true;
+    } else {
+      current = Some(status);
+    }
+  }
+
+  dropped
+}
+
+/// Check whether `later` makes `earlier` irrelevant.
+fn supersedes(later: &Event, earlier: &Event) -> bool {
+  match (earlier, later) {
+    (Event::SetProject { .. }, Event::SetProject { .. })
+    | (Event::SetPriority { .. }, Event::SetPriority { .. })
+    | (Event::SetContext { .. }, Event::SetContext { .. })
+    | (Event::SetMilestone { .. }, Event::SetMilestone { .. })
+    | (Event::SetDeadline { .. }, Event::SetDeadline { .. })
+    | (Event::SetRecurrence { .. }, Event::SetRecurrence { .. })
+    | (Event::SetEffort { .. }, Event::SetEffort { .. })
+    | (Event::SetPinned { .. }, Event::SetPinned { .. })
+    | (Event::SetGoal { .. }, Event::SetGoal { .. })
+    | (Event::Snoozed { .. }, Event::Snoozed { .. }) => true,
+
+    // snoozing reschedules the task
+    (Event::SetScheduled { .. }, Event::SetScheduled { .. } | Event::Snoozed { .. }) => true,
+
+    // moving a task to the someday backlog dates its last review
+    (Event::Reviewed { .. }, Event::Reviewed { .. } | Event::SetSomeday { someday: true, .. }) => {
+      true
+    }
+
+    (
+      Event::SetSomeday { someday, .. },
+      Event::SetSomeday {
+        someday: someday_later,
+        ..
+      },
+    ) => !someday || *someday_later,
+
+    (Event::SetField { name, .. }, Event::SetField { name: later, .. }) => name == later,
+
+    (
+      Event::NoteReplaced { note_uid, .. },
+      Event::NoteReplaced {
+        note_uid: later, ..
+      },
+    ) => note_uid == later,
+
+    _ => false,
+  }
+}
+
+#[cfg(test)]
+mod unit_tests {
+  use super::*;
+  use crate::{metadata::Metadata, task::UID};
+  use chrono::{DateTime, Duration, Utc};
+
+  #[test]
+  fn squash() {
+    let start = Utc::now() - Duration::days(10);
+    let day = |n| start + Duration::days(n);
+    let status = |n, status| Event::StatusChanged {
+      event_date: day(n),
+      status,
+    };
+    let deadline = |n, deadline: DateTime<Utc>| Event::SetDeadline {
+      event_date: day(n),
+      deadline,
+    };
+
+    let mut task = Task::from_events(
+      "Ship it",
+      vec![
+        Event::Created(day(0)),
+        status(0, Status::Todo),
+        deadline(1, day(20)),
+        status(1, Status::Done),
+        status(2, Status::Todo),
+        deadline(2, day(30)),
+        Event::NoteAdded {
+          event_date: day(2),
+          content: "Draft".to_owned(),
+          in_reply_to: None,
+        },
+        Event::NoteReplaced {
+          event_date: day(3),
+          note_uid: UID::default(),
+          content: "Second draft".to_owned(),
+        },
+        status(3, Status::Ongoing),
+        status(4, Status::Ongoing),
+        status(5, Status::Paused),
+        status(6, Status::Todo),
+        Event::NoteReplaced {
+          event_date: day(6),
+          note_uid: UID::default(),
+          content: "Final".to_owned(),
+        },
+        status(7, Status::Ongoing),
+        status(8, Status::Done),
+        Event::SetSomeday {
+          event_date: day(8),
+          someday: true,
+        },
+        Event::SetSomeday {
+          event_date: day(9),
+          someday: false,
+        },
+      ],
+    )
+    .unwrap();
+    task.apply_metadata(vec![Metadata::project("toodoux")]);
+
+    let before = (
+      task.status(),
+      task.deadline(),
+      task.notes(),
+      task.time_entries(),
+      task.review_date().copied(),
+      task.last_activity().copied(),
+    );
+    let events = task.history().count();
+
+    assert_eq!(task.squash(), 6);
+    assert_eq!(task.history().count(), events - 6);
+    assert_eq!(
+      before,
+      (
+        task.status(),
+        task.deadline(),
+        task.notes(),
+        task.time_entries(),
+        task.review_date().copied(),
+        task.last_activity().copied(),
+      )
+    );
+
+    // the first completion and the repeated start are gone, but the WIP periods and the last completion are kept
+    let statuses = task
+      .history()
+      .filter_map(|event| match event {
+        Event::StatusChanged { status, .. } => Some(*status),
+        _ => None,
+      })
+      .collect::<Vec<_>>();
+    assert_eq!(
+      statuses,
+      vec![
+        Status::Todo,
+        Status::Ongoing,
+        Status::Paused,
+        Status::Ongoing,
+        Status::Done,
+      ]
+    );
+
+    assert_eq!(task.squash(), 0);
+  }
+}
@@ -0,0 +1,329 @@
+//! Format templates, used to output tasks in custom ways.
+//!
+//! A template is a string in which `{field}` placeholders are replaced by the value of a field of each task, such as
+//! `{uid}\t{project}\t{name}`. A placeholder can be aligned in a column of a given width with `{field:<20}` (left) or
+//! `{field:>8}` (right). `{{` and `}}` output literal braces, and `\t`, `\n` and `\\` output a tabulation, a newline
+//! and a backslash, so that templates can be written as-is in a shell.
+//!
+//! Missing values, such as the project of a task without project, are replaced by an empty string.
+
+use crate::{
+  config::Config,
+  metadata::Fields,
+  nlp,
+  task::{Status, Task, UID},
+  time, tr,
+};
+use itertools::Itertools as _;
+use std::{error::Error, fmt};
+use unicode_width::UnicodeWidthStr as _;
+
+/// Builtin fields, available in every template.
+pub const BUILTIN_FIELDS: &[&str] = &[
+  "uid",
+  "name",
+  "status",
+  "project",
+  "priority",
+  "context",
+  "milestone",
+  "tags",
+  "created",
+  "age",
+  "activity",
+  "spent",
+  "effort",
+  "scheduled",
+  "deadline",
+  "recurrence",
+  "notes",
+  "ext",
+];
+
+/// Possible errors that can happen when parsing a template.
+#[derive(Debug, Eq, PartialEq)]
+pub enum TemplateError {
+  /// A placeholder is not closed.
+  UnclosedPlaceholder,
+  /// A closing brace doesn’t close any placeholder; use `}}` for a literal brace.
+  UnmatchedBrace,
+  /// The field of a placeholder is neither builtin nor user-defined.
+  UnknownField(String),
+  /// The alignment of a placeholder is invalid.
+  InvalidAlignment(String),
+}
+
+impl Error for TemplateError {}
+
+impl fmt::Display for TemplateError {
+  fn fmt(&self, f: &mut fmt::Formatter) -> Result<(), fmt::Error> {
+    match *self {
+      TemplateError::UnclosedPlaceholder => f.write_str(tr!("unclosed placeholder")),
+      TemplateError::UnmatchedBrace => f.write_str(tr!("unmatched }; use }} for a literal brace")),
+      TemplateError::UnknownField(ref field) => f.write_str(&tr!(
+        "unknown field {}; available fields are {} and the user-defined ones",
+        field,
+        BUILTIN_FIELDS.join(", ")
+      )),
+      TemplateError::InvalidAlignment(ref align) => {
+        f.write_str(&tr!("invalid alignment: {}", align))
+      }
+    }
+  }
+}
+
+/// Alignment of a placeholder in its column.
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+enum Align {
+  Left(usize),
+  Right(usize),
+}
+
+impl Align {
+  fn parse(s: &str) -> Result<Self, TemplateError> {
+    let invalid = || TemplateError::InvalidAlignment(s.to_owned());
+
+    let (right, width) = match s.as_bytes().first() {
+      Some(b'<') => (false, &s[1..]),
+      Some(b'>') => (true, &s[1..]),
+      _ => (false, s),
+    };
+    let width = width.parse().map_err(|_| invalid())?;
+
+    Ok(if right {
+      Align::Right(width)
+    } else {
+      Align::Left(width)
+    })
+  }
+}
+
+/// Part of a template.
+#[derive(Clone, Debug, Eq, PartialEq)]
+enum Segment {
+  /// Text output as-is.
+  Literal(String),
+  /// Value of a field, optionally aligned.
+  Placeholder { field: String, align: Option<Align> },
+}
+
+/// A parsed format template.
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct Template {
+  segments: Vec<Segment>,
+}
+
+impl Template {
+  /// Parse a template; placeholders must refer to builtin fields or to the user-defined `fields`.
+  pub fn parse(s: &str, fields: &Fields) -> Result<Self, TemplateError> {
+    let mut segments = Vec::new();
+    let mut literal = String::new();
+    let mut chars = s.chars().peekable();
+
+    while let Some(c) = chars.next() {
+      match c {
+        '{' if chars.peek() == Some(&'{') => {
+          chars.next();
+          literal.push('{');
+        }
+
+        '}' if chars.peek() == Some(&'}') => {
+          chars.next();
+          literal.push('}');
+        }
+
+        '}' => return Err(TemplateError::UnmatchedBrace),
+
+        '\\' => match chars.peek() {
+          Some('t') => {
+            chars.next();
+            literal.push('\t');
+          }
+
+          Some('n') => {
+            chars.next();
+            literal.push('\n');
+          }
+
+          Some('\\') => {
+            chars.next();
+            literal.push('\\');
+          }
+
+          _ => literal.push('\\'),
+        },
+
+        '{' => {
+          let mut placeholder = String::new();
+          loop {
+            match chars.next() {
+              Some('}') => break,
+              Some(c) => placeholder.push(c),
+              None => return Err(TemplateError::UnclosedPlaceholder),
+            }
+          }
+
+          let (field, align) = match placeholder.split_once(':') {
+            Some((field, align)) => (field.trim(), Some(Align::parse(align.trim())?)),
+            None => (placeholder.trim(), None),
+          };
+
+          if !BUILTIN_FIELDS.contains(&field) && fields.find(field).is_none() {
+            return Err(TemplateError::UnknownField(field.to_owned()));
+          }
+
+          if !literal.is_empty() {
+            segments.push(Segment::Literal(std::mem::take(&mut literal)));
+          }
+
+          segments.push(Segment::Placeholder {
+            field: field.to_owned(),
+            align,
+          });
+        }
+
+        _ => literal.push(c),
+      }
+    }
+
+    if !literal.is_empty() {
+      segments.push(Segment::Literal(literal));
+    }
+
+    Ok(Template { segments })
+  }
+
+  /// Render the template for a task.
+  ///
+  /// Status aliases and priority labels are looked up in `config`.
+  pub fn render(&self, config: &Config, uid: UID, task: &Task) -> String {
+    let mut output = String::new();
+
+    for segment in &self.segments {
+      match segment {
+        Segment::Literal(literal) => output.push_str(literal),
+
+        Segment::Placeholder { field, align } => {
+          let value = field_value(config, field, uid, task);
+          let padding = match *align {
+            Some(Align::Left(width)) | Some(Align::Right(width)) => {
+              width.saturating_sub(value.width())
+            }
+            None => 0,
+          };
+
+          if let Some(Align::Right(_)) = align {
+            output.extend(std::iter::repeat(' ').take(padding));
+            output.push_str(&value);
+          } else {
+            output.push_str(&value);
+            output.extend(std::iter::repeat(' ').take(padding));
+          }
+        }
+      }
+    }
+
+    output
+  }
+}
+
+/// Value of a field of a task, as a string.
+fn field_value(config: &Config, field: &str, uid: UID, task: &Task) -> String {
+  let date = |date: Option<_>| date.map(nlp::format_date_time).unwrap_or_default();
+
+  match field {
+    "uid" => uid.to_string(),
+    "name" => task.name().to_owned(),
+    "status" => match task.status() {
+      Status::Todo => config.todo_alias(),
+      Status::Ongoing => config.wip_alias(),
+      Status::Done => config.done_alias(),
+      Status::Cancelled => config.cancelled_alias(),
+      Status::Paused => config.paused_alias(),
+    }
+    .to_owned(),
+    "project" => task.project().unwrap_or_default().to_owned(),
+    "priority" => task
+      .priority()
+      .map(|prio| config.priorities().label(prio))
+      .unwrap_or_default(),
+    "context" => task.context().unwrap_or_default().to_owned(),
+    "milestone" => task.milestone().unwrap_or_default().to_owned(),
+    "tags" => task.tags().join(","),
+    "created" => date(task.creation_date().copied()),
+    "age" => time::format_duration(task.age()),
+    "activity" => time::format_duration(task.inactivity()),
+    "spent" => time::format_duration(task.spent_time()),
+    "effort" => task.effort().map(time::format_duration).unwrap_or_default(),
+    "scheduled" => date(task.scheduled()),
+    "deadline" => date(task.deadline()),
+    "recurrence" => task
+      .recurrence()
+      .map(|recurrence| recurrence.to_string())
+      .unwrap_or_default(),
+    "notes" => task.notes().len().to_string(),
+    "ext" => task.external_id().unwrap_or_default().to_owned(),
+    _ => task
+      .field(field)
+      .map(ToString::to_string)
+      .unwrap_or_default(),
+  }
+}
+
+#[cfg(test)]
+mod unit_tests {
+  use super::*;
+  use crate::metadata::{FieldDef, FieldKind, FieldValue, Metadata};
+
+  #[test]
+  fn parse() {
+    let fields = Fields::new(vec![FieldDef::new("client", FieldKind::String)]);
+
+    assert!(Template::parse("{uid}\\t{name} {client:>10}", &fields).is_ok());
+    assert_eq!(
+      Template::parse("{uid", &fields),
+      Err(TemplateError::UnclosedPlaceholder)
+    );
+    assert_eq!(
+      Template::parse("uid}", &fields),
+      Err(TemplateError::UnmatchedBrace)
+    );
+    assert_eq!(
+      Template::parse("{points}", &fields),
+      Err(TemplateError::UnknownField("points".to_owned()))
+    );
+    assert_eq!(
+      Template::parse("{name:wide}", &fields),
+      Err(TemplateError::InvalidAlignment("wide".to_owned()))
+    );
+  }
+
+  #[test]
+  fn render() {
+    let config = Config::default();
+    let fields = Fields::new(vec![FieldDef::new("client", FieldKind::String)]);
+    let mut task = Task::new("Write the quote");
+    task.apply_metadata(vec![
+      Metadata::project("work"),
+      Metadata::tag("sales"),
+      Metadata::tag("q3"),
+      Metadata::field("client", FieldValue::String("acme".to_owned())),
+    ]);
+    let uid = "3".parse::<UID>().unwrap();
+
+    let render = |s| {
+      Template::parse(s, &fields)
+        .unwrap()
+        .render(&config, uid, &task)
+    };
+    assert_eq!(
+      render("{uid}\\t{project}\\t{name}"),
+      "3\twork\tWrite the quote"
+    );
+    assert_eq!(
+      render("{{{status}}} {tags} {client} [{context}]"),
+      "{TODO} sales,q3 acme []"
+    );
+    assert_eq!(render("{uid:>3}|{project:<6}|"), "  3|work  |");
+  }
+}
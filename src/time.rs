@@ -0,0 +1,213 @@
+//! Durations, as written on the command line and in metadata.
+//!
+//! A duration is made of a number and a unit — `min` (or `m`), `h`, `d` or `w` — several of them being combined,
+//! largest first; e.g. `45min`, `2d` or `1h30m`. It is used for efforts (`effort:1h30m`), snoozes (`td 3 snooze 2d`)
+//! and time adjustments (`td 3 time add 45min`), among others.
+
+use crate::tr;
+use chrono::Duration;
+use std::fmt;
+
+/// Units of durations, along with their length in seconds.
+const UNITS: &[(&str, i64)] = &[
+  ("min", 60),
+  ("m", 60),
+  ("h", 60 * 60),
+  ("d", 24 * 60 * 60),
+  ("w", 7 * 24 * 60 * 60),
+];
+
+/// Longest duration, in seconds, that can be represented.
+const MAX_SECONDS: i64 = i64::MAX / 1000;
+
+/// Error while parsing a duration.
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub enum DurationError {
+  /// The duration is empty.
+  Empty,
+  /// A unit is not preceded by a number, such as in `h`.
+  MissingNumber(String),
+  /// A number is not followed by a unit, such as in `3`.
+  MissingUnit(String),
+  /// A unit is not supported, such as in `2hours`.
+  UnknownUnit(String),
+  /// The duration is too long to be represented.
+  TooLong,
+}
+
+impl fmt::Display for DurationError {
+  fn fmt(&self, f: &mut fmt::Formatter) -> Result<(), fmt::Error> {
+    match *self {
+      DurationError::Empty => f.write_str(tr!("empty duration")),
+
+      DurationError::MissingNumber(ref unit) => f.write_str(&tr!("missing number before {}", unit)),
+
+      DurationError::MissingUnit(ref n) => f.write_str(&tr!(
+        "missing unit after {0}; use min, h, d or w, e.g. {0}h",
+        n
+      )),
+
+      DurationError::UnknownUnit(ref unit) => {
+        f.write_str(&tr!("unknown unit {}; use min, h, d or w", unit))
+      }
+
+      DurationError::TooLong => f.write_str(tr!("duration too long")),
+    }
+  }
+}
+
+impl std::error::Error for DurationError {}
+
+/// Parse a duration such as `45min`, `2h` or `1h30m`.
+pub fn parse_duration(s: &str) -> Result<Duration, DurationError> {
+  let mut rest = s.trim();
+  let mut seconds = 0i64;
+
+  if rest.is_empty() {
+    return Err(DurationError::Empty);
+  }
+
+  while !rest.is_empty() {
+    let digits = rest
+      .find(|c: char| !c.is_ascii_digit())
+      .unwrap_or(rest.len());
+    let (n, tail) = rest.split_at(digits);
+    let units = tail
+      .find(|c: char| c.is_ascii_digit())
+      .unwrap_or(tail.len());
+    let (unit, tail) = tail.split_at(units);
+
+    if unit.is_empty() {
+      return Err(DurationError::MissingUnit(n.to_owned()));
+    }
+
+    let unit_seconds = UNITS
+      .iter()
+      .find(|(name, _)| *name == unit)
+      .map(|&(_, unit_seconds)| unit_seconds)
+      .ok_or_else(|| DurationError::UnknownUnit(unit.to_owned()))?;
+
+    if n.is_empty() {
+      return Err(DurationError::MissingNumber(unit.to_owned()));
+    }
+
+    seconds = n
+      .parse::<i64>()
+      .ok()
+      .and_then(|n| n.checked_mul(unit_seconds))
+      .and_then(|n| n.checked_add(seconds))
+      .filter(|&seconds| seconds <= MAX_SECONDS)
+      .ok_or(DurationError::TooLong)?;
+    rest = tail;
+  }
+
+  Ok(Duration::seconds(seconds))
+}
+
+/// Format a duration the way [`parse_duration`] reads it, to the minute; e.g. `1h30min`.
+pub fn format_duration(duration: Duration) -> String {
+  let minutes = duration.num_minutes();
+  let (days, hours, minutes) = (minutes / (24 * 60), minutes / 60 % 24, minutes % 60);
+  let mut output = String::new();
+
+  if days > 0 {
+    output += &format!("{}d", days);
+  }
+
+  if hours > 0 {
+    output += &format!("{}h", hours);
+  }
+
+  if minutes > 0 || output.is_empty() {
+    output += &format!("{}min", minutes);
+  }
+
+  output
+}
+
+#[cfg(test)]
+mod unit_tests {
+  use super::*;
+
+  #[test]
+  fn durations() {
+    assert_eq!(parse_duration("45min"), Ok(Duration::minutes(45)));
+    assert_eq!(parse_duration("90min"), Ok(Duration::minutes(90)));
+    assert_eq!(parse_duration("90m"), Ok(Duration::minutes(90)));
+    assert_eq!(parse_duration("6h"), Ok(Duration::hours(6)));
+    assert_eq!(
+      parse_duration("1h30m"),
+      Ok(Duration::hours(1) + Duration::minutes(30))
+    );
+    assert_eq!(parse_duration("2d"), Ok(Duration::days(2)));
+    assert_eq!(
+      parse_duration(" 1w2d "),
+      Ok(Duration::weeks(1) + Duration::days(2))
+    );
+  }
+
+  #[test]
+  fn invalid_durations() {
+    assert_eq!(parse_duration(""), Err(DurationError::Empty));
+    assert_eq!(parse_duration("  "), Err(DurationError::Empty));
+    assert_eq!(
+      parse_duration("h"),
+      Err(DurationError::MissingNumber("h".to_owned()))
+    );
+    assert_eq!(
+      parse_duration("3"),
+      Err(DurationError::MissingUnit("3".to_owned()))
+    );
+    assert_eq!(
+      parse_duration("1h30"),
+      Err(DurationError::MissingUnit("30".to_owned()))
+    );
+    assert_eq!(
+      parse_duration("2hours"),
+      Err(DurationError::UnknownUnit("hours".to_owned()))
+    );
+    assert_eq!(
+      parse_duration("2 h"),
+      Err(DurationError::UnknownUnit(" h".to_owned()))
+    );
+    assert_eq!(
+      parse_duration("-2h"),
+      Err(DurationError::UnknownUnit("-".to_owned()))
+    );
+    assert_eq!(
+      parse_duration("soon"),
+      Err(DurationError::UnknownUnit("soon".to_owned()))
+    );
+    assert_eq!(
+      parse_duration("99999999999999999999w"),
+      Err(DurationError::TooLong)
+    );
+    assert_eq!(
+      parse_duration("9999999999999w"),
+      Err(DurationError::TooLong)
+    );
+  }
+
+  #[test]
+  fn errors() {
+    assert_eq!(
+      parse_duration("3").unwrap_err().to_string(),
+      "missing unit after 3; use min, h, d or w, e.g. 3h"
+    );
+    assert_eq!(
+      parse_duration("2hours").unwrap_err().to_string(),
+      "unknown unit hours; use min, h, d or w"
+    );
+  }
+
+  #[test]
+  fn format() {
+    assert_eq!(format_duration(Duration::minutes(90)), "1h30min");
+    assert_eq!(format_duration(Duration::days(1)), "1d");
+    assert_eq!(format_duration(Duration::zero()), "0min");
+    assert_eq!(
+      parse_duration(&format_duration(Duration::minutes(1530))),
+      Ok(Duration::minutes(1530))
+    );
+  }
+}
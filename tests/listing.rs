@@ -0,0 +1,147 @@
+use serde_json as json;
+use std::{fs, path::Path};
+use toodoux::{
+  config::Config,
+  filter::TaskDescriptionFilter,
+  metadata::{NoPriorityOrder, Priorities, Priority},
+  task::{Task, TaskManager},
+};
+
+/// Get the UIDs of all the tasks, in listing order.
+fn listing_uids(task_mgr: &TaskManager) -> Vec<u32> {
+  listing_uids_with(task_mgr, NoPriorityOrder::default())
+}
+
+/// Get the UIDs of all the tasks, in listing order, with tasks without a priority listed according to `no_priority`.
+fn listing_uids_with(task_mgr: &TaskManager, no_priority: NoPriorityOrder) -> Vec<u32> {
+  task_mgr
+    .filtered_task_listing(
+      &Priorities::default(),
+      no_priority,
+      Vec::new(),
+      TaskDescriptionFilter::new(None.into_iter(), false),
+      true,
+      true,
+      true,
+      true,
+      true,
+      true,
+      None,
+      false,
+    )
+    .into_iter()
+    .map(|(uid, _)| uid.val())
+    .collect()
+}
+
+#[test]
+fn seeded_listing_order() {
+  // loading may migrate and rewrite the tasks file, so load a copy of the fixture
+  let fixture = Path::new(env!("CARGO_MANIFEST_DIR")).join("intg-tests");
+  let dir = tempdir::TempDir::new("toodoux-listing").unwrap();
+  fs::copy(fixture.join("tasks.json"), dir.path().join("tasks.json")).unwrap();
+  let config = Config::create(Some(dir.path())).unwrap();
+  let task_mgr = TaskManager::new_from_config(&config).unwrap();
+
+  let expected = vec![6, 9, 10, 3, 5, 7, 4, 0, 1, 2, 8, 11];
+  for _ in 0..10 {
+    assert_eq!(listing_uids(&task_mgr), expected);
+  }
+}
+
+#[test]
+fn equal_sort_keys_are_ordered_by_uid() {
+  let task = r#"{
+    "name": "Same",
+    "history": [
+      { "Created": "2021-01-01T00:00:00Z" },
+      { "StatusChanged": { "event_date": "2021-01-01T00:00:00Z", "status": "Todo" } }
+    ]
+  }"#;
+  let tasks = (0..20)
+    .map(|uid| format!(r#""{}": {}"#, uid, task))
+    .collect::<Vec<_>>()
+    .join(",");
+  let task_mgr: TaskManager = json::from_str(&format!(
+    r#"{{ "next_uid": 20, "tasks": {{ {} }} }}"#,
+    tasks
+  ))
+  .unwrap();
+
+  assert_eq!(listing_uids(&task_mgr), (0..20).collect::<Vec<_>>());
+}
+
+#[test]
+fn large_listing_order_is_deterministic() {
+  let dates = ["2021-01-01T00:00:00Z", "2021-02-01T00:00:00Z"];
+  let tasks = (0..5000)
+    .map(|uid| {
+      let date = dates[uid % dates.len()];
+      let priority = if uid % 3 == 0 {
+        format!(
+          r#", {{ "SetPriority": {{ "event_date": "{}", "priority": "High" }} }}"#,
+          date
+        )
+      } else {
+        String::new()
+      };
+
+      let status = format!(
+        r#"{{ "StatusChanged": {{ "event_date": "{}", "status": "Todo" }} }}"#,
+        date
+      );
+
+      format!(
+        r#""{}": {{ "name": "Task {}", "history": [{{ "Created": "{}" }}, {}{}] }}"#,
+        uid, uid, date, status, priority
+      )
+    })
+    .collect::<Vec<_>>()
+    .join(",");
+  let task_mgr: TaskManager = json::from_str(&format!(
+    r#"{{ "next_uid": 5000, "tasks": {{ {} }} }}"#,
+    tasks
+  ))
+  .unwrap();
+
+  // prioritized tasks first, then the oldest ones; ties are ordered by UID
+  let mut expected = (0..5000).collect::<Vec<u32>>();
+  expected.sort_by_key(|uid| (uid % 3 != 0, uid % 2, *uid));
+
+  for _ in 0..5 {
+    assert_eq!(listing_uids(&task_mgr), expected);
+  }
+}
+
+#[test]
+fn no_priority_order() {
+  let mut task_mgr = TaskManager::in_memory();
+  let mut register = |priority: Option<&str>, pinned: bool| {
+    let mut task = Task::new("Task");
+    if let Some(priority) = priority {
+      task.set_priority(Priority::new(priority));
+    }
+    task.set_pinned(pinned);
+    task_mgr.register_task(task).val()
+  };
+
+  let medium = register(Some("Medium"), false);
+  let none = register(None, false);
+  let low = register(Some("Low"), false);
+  let unknown = register(Some("Someday"), false);
+  let pinned_none = register(None, true);
+  let pinned_low = register(Some("Low"), true);
+
+  assert_eq!(
+    listing_uids_with(&task_mgr, NoPriorityOrder::BelowLowest),
+    vec![pinned_low, pinned_none, medium, low, none, unknown]
+  );
+  assert_eq!(
+    listing_uids_with(&task_mgr, NoPriorityOrder::AboveLowest),
+    vec![pinned_none, pinned_low, medium, none, unknown, low]
+  );
+  assert_eq!(
+    listing_uids_with(&task_mgr, NoPriorityOrder::Last),
+    vec![pinned_low, medium, low, pinned_none, none, unknown]
+  );
+}
@@ -0,0 +1,234 @@
+//! Import and export round-trips over random task histories.
+//!
+//! Every format that can be both exported to and imported from must give back equivalent tasks; export-only formats
+//! must at least handle any task.
+
+use chrono::{DateTime, Duration, Local, Offset as _, TimeZone, Utc};
+use proptest::{collection::vec, option, prelude::*};
+use serde_json as json;
+use std::collections::BTreeSet;
+use toodoux::{
+  config::Config,
+  io::{Exporter, Registry},
+  metadata::Priority,
+  task::{Event, Status, Task, UID},
+};
+
+fn status() -> impl Strategy<Value = Status> {
+  prop_oneof![
+    Just(Status::Todo),
+    Just(Status::Ongoing),
+    Just(Status::Paused),
+    Just(Status::Done),
+    Just(Status::Cancelled),
+  ]
+}
+
+fn words(max: usize) -> impl Strategy<Value = String> {
+  vec("[a-z]{1,8}", 1..=max).prop_map(|words| words.join(" "))
+}
+
+/// Something happening to a task after its creation.
+#[derive(Clone, Debug)]
+enum Step {
+  ChangeStatus(Status),
+  AddNote(Vec<String>),
+}
+
+fn step() -> impl Strategy<Value = Step> {
+  prop_oneof![
+    status().prop_map(Step::ChangeStatus),
+    vec(words(6), 1..=3).prop_map(Step::AddNote),
+  ]
+}
+
+prop_compose! {
+  /// Random task, with minute-precision dates as most formats don’t go further.
+  fn task()(
+    name in words(5),
+    created_minutes in 0..10_000_000_i64,
+    project in option::of("[a-z][a-z-]{0,10}"),
+    priority in option::of(prop_oneof![Just("Low"), Just("Medium"), Just("High"), Just("Critical")]),
+    tags in proptest::collection::btree_set("[a-z0-9_]{1,8}", 0..4),
+    scheduled_minutes in option::of(0..100_000_i64),
+    deadline_minutes in option::of(0..100_000_i64),
+    steps in vec((1..10_000_i64, step()), 0..8),
+  ) -> Task {
+    let created = Utc.timestamp(946_684_800, 0) + Duration::minutes(created_minutes);
+    let mut history = vec![
+      Event::Created(created),
+      Event::StatusChanged { event_date: created, status: Status::Todo },
+    ];
+
+    if let Some(project) = project {
+      history.push(Event::SetProject { event_date: created, project });
+    }
+
+    if let Some(priority) = priority {
+      history.push(Event::SetPriority { event_date: created, priority: Priority::new(priority) });
+    }
+
+    for tag in tags {
+      history.push(Event::AddTag { event_date: created, tag });
+    }
+
+    if let Some(minutes) = scheduled_minutes {
+      history.push(Event::SetScheduled { event_date: created, scheduled: created + Duration::minutes(minutes) });
+    }
+
+    if let Some(minutes) = deadline_minutes {
+      history.push(Event::SetDeadline { event_date: created, deadline: created + Duration::minutes(minutes) });
+    }
+
+    let mut event_date = created;
+    for (delay, step) in steps {
+      event_date = event_date + Duration::minutes(delay);
+
+      history.push(match step {
+        Step::ChangeStatus(status) => Event::StatusChanged { event_date, status },
+        Step::AddNote(lines) => Event::NoteAdded { event_date, content: lines.join("\n"), in_reply_to: None },
+      });
+    }
+
+    json::from_value(json::json!({ "name": name, "history": history })).unwrap()
+  }
+}
+
+/// Whether a date is far from DST transitions; Org timestamps are written in local time, without offset, so the dates
+/// around a transition cannot round-trip.
+fn far_from_dst_transition(date: &DateTime<Utc>) -> bool {
+  let offset = |date: DateTime<Utc>| date.with_timezone(&Local).offset().fix();
+  offset(*date - Duration::hours(2)) == offset(*date + Duration::hours(2))
+}
+
+/// Random task whose dates can be written as Org timestamps.
+fn org_task() -> impl Strategy<Value = Task> {
+  task().prop_filter("date around a DST transition", |task| {
+    task
+      .history()
+      .map(Event::date)
+      .copied()
+      .chain(task.scheduled())
+      .chain(task.deadline())
+      .all(|date| far_from_dst_transition(&date))
+  })
+}
+
+fn export(exporter: &dyn Exporter, config: &Config, tasks: &[Task]) -> String {
+  let tasks = tasks
+    .iter()
+    .enumerate()
+    .map(|(i, task)| (i.to_string().parse::<UID>().unwrap(), task))
+    .collect::<Vec<_>>();
+  let mut out = Vec::new();
+  exporter.export(&mut out, config, &tasks).unwrap();
+
+  String::from_utf8(out).unwrap()
+}
+
+/// Check that two tasks are equivalent, as far as their state is concerned.
+fn assert_equivalent(format: &str, expected: &Task, found: &Task) {
+  let notes = |task: &Task| {
+    task
+      .notes()
+      .into_iter()
+      .map(|note| (note.creation_date, note.content))
+      .collect::<Vec<_>>()
+  };
+  let tags = |task: &Task| task.tags().map(str::to_owned).collect::<BTreeSet<_>>();
+  // open WIP periods are counted up to now, which moves between the two computations
+  let spent_diff = found.spent_time() - expected.spent_time();
+
+  assert_eq!(found.name(), expected.name(), "{}: name", format);
+  assert_eq!(found.status(), expected.status(), "{}: status", format);
+  assert_eq!(
+    found.creation_date(),
+    expected.creation_date(),
+    "{}: creation date",
+    format
+  );
+  assert_eq!(found.project(), expected.project(), "{}: project", format);
+  assert_eq!(
+    found.priority(),
+    expected.priority(),
+    "{}: priority",
+    format
+  );
+  assert_eq!(tags(found), tags(expected), "{}: tags", format);
+  assert_eq!(
+    found.scheduled(),
+    expected.scheduled(),
+    "{}: scheduled",
+    format
+  );
+  assert_eq!(
+    found.deadline(),
+    expected.deadline(),
+    "{}: deadline",
+    format
+  );
+  assert_eq!(notes(found), notes(expected), "{}: notes", format);
+  assert!(
+    spent_diff.num_seconds().abs() < 60,
+    "{}: spent time differs by {}",
+    format,
+    spent_diff
+  );
+}
+
+proptest! {
+  /// Formats that can be both exported to and imported from give back equivalent tasks.
+  #[test]
+  fn round_trip(tasks in vec(org_task(), 1..4)) {
+    let config = Config::default();
+    let registry = Registry::default();
+
+    for format in registry.formats() {
+      let (exporter, importer) = match (format.exporter(), format.importer()) {
+        (Some(exporter), Some(importer)) => (exporter, importer),
+        _ => continue,
+      };
+      let name = format.name();
+
+      let exported = export(exporter, &config, &tasks);
+      let imported = importer.import(&exported, &config).unwrap();
+
+      prop_assert_eq!(imported.len(), tasks.len(), "{}: {}", name, exported);
+      for (expected, found) in tasks.iter().zip(&imported) {
+        assert_equivalent(name, expected, found);
+      }
+
+      // a second trip doesn’t change anything
+      let imported_again = importer.import(&export(exporter, &config, &imported), &config).unwrap();
+      for (expected, found) in imported.iter().zip(&imported_again) {
+        assert_equivalent(name, expected, found);
+        prop_assert_eq!(found.external_id(), expected.external_id());
+      }
+    }
+  }
+
+  /// Export-only formats handle any task.
+  #[test]
+  fn export_only(tasks in vec(task(), 1..4)) {
+    let config = Config::default();
+    let registry = Registry::default();
+
+    for format in registry.formats().filter(|format| format.importer().is_none()) {
+      let name = format.name();
+      let exported = export(format.exporter().unwrap(), &config, &tasks);
+
+      for task in &tasks {
+        if name == "reminders" {
+          // only the active tasks with a deadline are exported
+          let listed = tasks.iter().any(|other| {
+            other.name() == task.name() && other.status().is_active() && other.deadline().is_some()
+          });
+          let line = format!("set name to \"{}\"", task.name());
+          prop_assert_eq!(exported.contains(&line), listed, "{}: {}", name, exported);
+        } else {
+          prop_assert!(exported.contains(task.name()), "{}: {}", name, exported);
+        }
+      }
+    }
+  }
+}